@@ -3,7 +3,7 @@ mod model;
 use std::time::Duration;
 use ketch_core::resource::AssetManager;
 use ketch_core::settings::Settings;
-use ketch_engine::EventHandler;
+use ketch_engine::{ControlFlow, EventHandler};
 use ketch_core::input::input_event::ElementState::Released;
 use ketch_core::input::input_event::ElementState::Pressed;
 use std::path::Path;
@@ -135,10 +135,15 @@ impl GameState {
 
 impl EventHandler for GameState {
     fn init(&mut self, settings: &Settings, asset_manager: &mut AssetManager) {
-        let mesh = asset_manager.create_mesh("test_mesh", model::generate_vertices(), model::generate_indices());
-        let texture = asset_manager.load_texture("crate", Path::new("example/data/crate.jpg"));
-        asset_manager.add_texture(texture.clone());
-        mesh.write().unwrap().set_texture(texture);
+        let mesh = asset_manager.create_mesh("test_mesh", model::generate_vertices(), model::generate_indices()).expect("Couldn't create cube mesh");
+        // cubes keep the default texture if the crate texture can't be loaded
+        match asset_manager.load_texture("crate", Path::new("example/data/crate.jpg")) {
+            Ok(texture) => {
+                asset_manager.add_texture(texture.clone());
+                mesh.write().unwrap().set_texture(texture);
+            },
+            Err(err) => eprintln!("Couldn't load crate texture: {}", err),
+        }
         asset_manager.add_mesh(mesh);
         let camera = Camera::new();
         asset_manager.set_active_scene(Scene::new("test_scene", camera));
@@ -157,18 +162,27 @@ impl EventHandler for GameState {
     fn process_input(&mut self, input_system: &mut InputSystem, input_events: Vec<InputEvent>) {
         self.input.update_input(input_system, input_events);
     }
-    fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration) {
+    fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration) -> ControlFlow {
         for object in asset_manager.active_scene_mut().unwrap().objects_mut().iter_mut().filter(|x| x.name() == "test_object") {
             let (x, y, z) = object.rotation_angles();
             object.set_rotation_angles(x, y + 0.01, z);
         }
         self.input.update_camera(asset_manager.active_scene_mut().unwrap().camera_mut(), elapsed_time);
+        ControlFlow::Continue
+    }
+    fn update_photo_camera(&mut self, camera: &mut Camera, elapsed_time: Duration) {
+        self.input.update_camera(camera, elapsed_time);
     }
 }
 
 fn main() {
-    env_logger::init();
-    let mut engine = Engine::new(Settings::new("ŚWIATEŁA", 1024.0, 768.0));
+    let mut engine = match Engine::new(Settings::new("ŚWIATEŁA", 1024.0, 768.0)) {
+        Ok(engine) => engine,
+        Err(err) => {
+            eprintln!("Couldn't start engine: {}", err);
+            std::process::exit(1);
+        },
+    };
     let time_per_update = engine.settings().time_per_update();
     let state = GameState::new(5.0, 0.2);
 
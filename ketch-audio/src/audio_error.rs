@@ -0,0 +1,22 @@
+use std::error::Error;
+
+use rodio::decoder::DecoderError;
+
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum AudioError {
+        NoOutputDevice {
+            display("NoOutputDevice: there is no audio output device to play sounds on")
+        }
+        SoundNotLoaded(name: String) {
+            display("SoundNotLoaded: sound {} isn't added to asset manager", name)
+        }
+        DecoderError(err: DecoderError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
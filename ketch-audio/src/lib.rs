@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::sync::Arc;
+
+use rodio::{Decoder, Device, Sink, Source, SpatialSink};
+use nalgebra_glm as glm;
+use log::*;
+
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::sound::{Sound, SoundEmitter};
+
+use crate::audio_error::AudioError;
+use crate::listener::Listener;
+
+pub mod audio_error;
+pub mod listener;
+
+/// Encoded sound data shared with the sound asset, so that it isn't copied every time the sound is played.
+struct SoundData(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SoundData {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Sound played by an emitter attached to an object.
+/// Sink is None if the sound couldn't be played, so that it isn't retried until emitter sound changes.
+struct EmitterPlayback {
+    sound: String,
+    sink: Option<SpatialSink>,
+}
+
+/// Soundtrack of a video, started together with the video playback with given id.
+struct SoundtrackPlayback {
+    id: u64,
+    sink: Option<Sink>,
+}
+
+/// Plays one-shot sounds, looping music, soundtracks of playing videos and sounds of emitters attached to objects of the active scene.
+/// Emitters are heard from the position of the active scene camera.
+pub struct AudioSystem {
+    device: Option<Device>,
+    one_shots: Vec<Sink>,
+    music: Option<Sink>,
+    emitters: HashMap<u32, EmitterPlayback>,
+    soundtracks: HashMap<String, SoundtrackPlayback>,
+    paused: bool,
+}
+
+impl AudioSystem {
+    /// Creates audio system playing on the default output device.
+    /// If there is no output device, audio system is created anyway, but doesn't play anything.
+    pub fn new() -> Self {
+        let device = rodio::default_output_device();
+        match &device {
+            Some(device) => info!("Using audio device: {}", device.name()),
+            None => warn!("No audio output device found, sounds won't be played"),
+        }
+        AudioSystem {
+            device,
+            one_shots: Vec::new(),
+            music: None,
+            emitters: HashMap::new(),
+            soundtracks: HashMap::new(),
+            paused: false,
+        }
+    }
+
+    /// Plays sound once with given volume, where 1.0 is the original volume.
+    pub fn play_sound(&mut self, sound: &Sound, volume: f32) -> Result<(), AudioError> {
+        let mut sink = Sink::new(self.device()?);
+        sink.set_volume(volume);
+        sink.append(decode(sound, false)?);
+        if self.paused {
+            sink.pause();
+        }
+        self.one_shots.push(sink);
+        Ok(())
+    }
+
+    /// Plays sound in a loop as music, replacing currently playing music.
+    pub fn play_music(&mut self, sound: &Sound, volume: f32) -> Result<(), AudioError> {
+        self.stop_music();
+        let mut sink = Sink::new(self.device()?);
+        sink.set_volume(volume);
+        sink.append(decode(sound, true)?);
+        if self.paused {
+            sink.pause();
+        }
+        self.music = Some(sink);
+        Ok(())
+    }
+
+    /// Stops currently playing music.
+    pub fn stop_music(&mut self) {
+        if let Some(music) = self.music.take() {
+            music.stop();
+        }
+    }
+
+    /// Pauses or resumes all sounds, e.g. while the game is paused in the editor.
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused == self.paused {
+            return;
+        }
+        self.paused = paused;
+        let soundtrack_sinks = self.soundtracks.values().filter_map(|playback| playback.sink.as_ref());
+        let sinks = self.one_shots.iter().chain(self.music.iter()).chain(soundtrack_sinks);
+        let spatial_sinks = self.emitters.values().filter_map(|playback| playback.sink.as_ref());
+        if paused {
+            sinks.for_each(Sink::pause);
+            spatial_sinks.for_each(SpatialSink::pause);
+        } else {
+            sinks.for_each(Sink::play);
+            spatial_sinks.for_each(SpatialSink::play);
+        }
+    }
+
+    /// Returns true if sounds are paused.
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Starts sounds of new emitters in the active scene, stops sounds of removed ones
+    /// and updates volume and panning of all emitters relative to the scene camera.
+    /// Soundtracks of videos are started, paused and stopped together with the videos.
+    pub fn update(&mut self, asset_manager: &AssetManager) {
+        self.one_shots.retain(|sink| !sink.empty());
+        self.update_soundtracks(asset_manager);
+
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => {
+                self.emitters.clear();
+                return;
+            },
+        };
+        let listener = Listener::from_camera(&scene.view_camera());
+
+        let mut emitting_objects = HashSet::new();
+        for object in scene.objects() {
+            let emitter = match object.sound_emitter() {
+                Some(emitter) => emitter,
+                None => continue,
+            };
+            emitting_objects.insert(object.id());
+
+            let sound_changed = self.emitters.get(&object.id()).map(|playback| playback.sound != emitter.sound).unwrap_or(true);
+            if sound_changed {
+                let sink = match self.start_emitter(emitter, &listener, asset_manager) {
+                    Ok(sink) => Some(sink),
+                    Err(err) => {
+                        error!("Couldn't play sound {} of object {}: {}", emitter.sound, object.name(), err);
+                        None
+                    },
+                };
+                self.emitters.insert(object.id(), EmitterPlayback { sound: emitter.sound.clone(), sink });
+            }
+
+            if let Some(sink) = self.emitters.get_mut(&object.id()).and_then(|playback| playback.sink.as_mut()) {
+                let position = scene.world_position(object);
+                sink.set_emitter_position(listener.spatial_emitter_position(&position));
+                sink.set_left_ear_position(listener.left_ear());
+                sink.set_right_ear_position(listener.right_ear());
+                sink.set_volume(emitter.volume_at(glm::distance(&listener.position(), &position)));
+            }
+        }
+        // dropped sinks stop playing
+        self.emitters.retain(|id, _playback| emitting_objects.contains(id));
+    }
+
+    fn update_soundtracks(&mut self, asset_manager: &AssetManager) {
+        let video_player = asset_manager.video_player();
+        let mut playing_textures = HashSet::new();
+        for (texture, playback) in video_player.playbacks() {
+            let soundtrack = match asset_manager.video(playback.video()).and_then(|video| video.soundtrack().map(String::from)) {
+                Some(soundtrack) => soundtrack,
+                None => continue,
+            };
+            playing_textures.insert(texture.to_string());
+
+            let restarted = self.soundtracks.get(texture).map(|soundtrack_playback| soundtrack_playback.id != playback.id()).unwrap_or(true);
+            if restarted {
+                let sink = match self.start_soundtrack(&soundtrack, playback.looping(), asset_manager) {
+                    Ok(sink) => Some(sink),
+                    Err(err) => {
+                        error!("Couldn't play soundtrack {} of video {}: {}", soundtrack, playback.video(), err);
+                        None
+                    },
+                };
+                self.soundtracks.insert(texture.to_string(), SoundtrackPlayback { id: playback.id(), sink });
+            }
+
+            if let Some(sink) = self.soundtracks.get(texture).and_then(|soundtrack_playback| soundtrack_playback.sink.as_ref()) {
+                if self.paused || playback.paused() {
+                    sink.pause();
+                } else {
+                    sink.play();
+                }
+            }
+        }
+        // dropped sinks stop playing
+        self.soundtracks.retain(|texture, _playback| playing_textures.contains(texture));
+    }
+
+    fn start_soundtrack(&self, soundtrack: &str, looping: bool, asset_manager: &AssetManager) -> Result<Sink, AudioError> {
+        let sound = asset_manager.sound(soundtrack).ok_or_else(|| AudioError::SoundNotLoaded(soundtrack.to_string()))?;
+        let mut sink = Sink::new(self.device()?);
+        sink.append(decode(&sound, looping)?);
+        if self.paused {
+            sink.pause();
+        }
+        Ok(sink)
+    }
+
+    fn start_emitter(&self, emitter: &SoundEmitter, listener: &Listener, asset_manager: &AssetManager) -> Result<SpatialSink, AudioError> {
+        let sound = asset_manager.sound(&emitter.sound).ok_or_else(|| AudioError::SoundNotLoaded(emitter.sound.clone()))?;
+        let sink = SpatialSink::new(self.device()?, listener.position().into(), listener.left_ear(), listener.right_ear());
+        sink.append(decode(&sound, emitter.looping)?);
+        if self.paused {
+            sink.pause();
+        }
+        Ok(sink)
+    }
+
+    fn device(&self) -> Result<&Device, AudioError> {
+        self.device.as_ref().ok_or(AudioError::NoOutputDevice)
+    }
+}
+
+impl Default for AudioSystem {
+    fn default() -> Self {
+        AudioSystem::new()
+    }
+}
+
+/// Decodes sound, repeating it forever if it's looping.
+fn decode(sound: &Sound, looping: bool) -> Result<Box<Source<Item = i16> + Send>, AudioError> {
+    let decoder = Decoder::new(Cursor::new(SoundData(sound.data())))?;
+    if looping {
+        Ok(Box::new(decoder.repeat_infinite()))
+    } else {
+        Ok(Box::new(decoder))
+    }
+}
@@ -0,0 +1,69 @@
+use nalgebra_glm::Vec3;
+use nalgebra_glm as glm;
+
+use ketch_core::resource::camera::Camera;
+
+/// Distance between ears of the listener.
+const EAR_DISTANCE: f32 = 0.2;
+/// Emitters closer to the listener than this distance are heard equally by both ears.
+const MIN_EMITTER_DISTANCE: f32 = 0.001;
+
+/// Position and orientation sounds are heard from.
+pub struct Listener {
+    position: Vec3,
+    right: Vec3,
+}
+
+impl Listener {
+    /// Creates listener hearing sounds from the camera position.
+    pub fn from_camera(camera: &Camera) -> Self {
+        Listener {
+            position: camera.position_vec3(),
+            right: camera.right(),
+        }
+    }
+
+    /// Returns listener position.
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn left_ear(&self) -> [f32; 3] {
+        (self.position - self.right * EAR_DISTANCE * 0.5).into()
+    }
+
+    pub fn right_ear(&self) -> [f32; 3] {
+        (self.position + self.right * EAR_DISTANCE * 0.5).into()
+    }
+
+    /// Returns position an emitter is played at by a spatial sink. Emitters are moved to unit distance from the listener,
+    /// keeping only their direction for panning, because distance attenuation is done with `SoundEmitter::volume_at`.
+    pub fn spatial_emitter_position(&self, emitter_position: &Vec3) -> [f32; 3] {
+        let offset = emitter_position - self.position;
+        if glm::length(&offset) < MIN_EMITTER_DISTANCE {
+            return self.position.into();
+        }
+        (self.position + glm::normalize(&offset)).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+        glm::distance(&Vec3::from(a), &Vec3::from(b))
+    }
+
+    #[test]
+    fn emitter_on_the_right_is_closer_to_the_right_ear() {
+        let camera = Camera::new();
+        let listener = Listener::from_camera(&camera);
+        let emitter_position = camera.position_vec3() + camera.right() * 50.0;
+
+        let spatial_position = listener.spatial_emitter_position(&emitter_position);
+
+        assert!((distance(spatial_position, listener.position().into()) - 1.0).abs() < 0.001);
+        assert!(distance(spatial_position, listener.right_ear()) < distance(spatial_position, listener.left_ear()));
+    }
+}
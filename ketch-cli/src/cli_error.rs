@@ -0,0 +1,42 @@
+use std::error::Error;
+
+use quick_error::quick_error;
+
+use ketch_core::resource::asset_pack::asset_pack_error::AssetPackError;
+use ketch_core::resource::model::model_error::ModelError;
+use ketch_core::resource::scene::scene_error::SceneSerializationError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum CliError {
+        IoError(err: std::io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ImageError(err: image::ImageError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ModelError(err: ModelError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        AssetPackError(err: AssetPackError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SceneError(err: SceneSerializationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        InvalidScene(problems: usize) {
+            description("scene is invalid")
+            display(x) -> ("{}: {} problems found", x.description(), problems)
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use structopt::StructOpt;
+
+use ketch_core::resource::asset_pack::{self, AssetPack};
+use ketch_core::resource::material::Material;
+use ketch_core::resource::model::{self, ModelData};
+use ketch_core::resource::primitives::Primitive;
+use ketch_core::resource::scene::scene_data::SceneData;
+use ketch_core::resource::texture;
+
+use crate::cli_error::CliError;
+
+mod cli_error;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "ketch-cli", about = "Prepares assets of ketch games, e.g. in build scripts")]
+enum Command {
+    /// Convert OBJ or glTF model to the binary model format, which loads without parsing
+    #[structopt(name = "convert-model")]
+    ConvertModel {
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// Path of the converted model, by default the input path with kmodel extension
+        #[structopt(parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Downscale textures and save them as PNG, or in the binary texture format which loads without decoding
+    #[structopt(name = "compress-textures")]
+    CompressTextures {
+        #[structopt(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+        /// Directory the compressed textures are saved to
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        output: PathBuf,
+        /// How many times the size of textures is halved
+        #[structopt(long = "downscale", default_value = "0")]
+        downscale: u32,
+        /// Save textures in the binary texture format, which is larger than PNG but loads faster
+        #[structopt(long = "binary")]
+        binary: bool,
+    },
+    /// Bundle every file of a directory into an asset pack
+    #[structopt(name = "pack")]
+    Pack {
+        #[structopt(parse(from_os_str))]
+        directory: PathBuf,
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+    },
+    /// Check that a scene can be loaded without problems
+    #[structopt(name = "validate-scene")]
+    ValidateScene {
+        #[structopt(parse(from_os_str))]
+        scene: PathBuf,
+        /// Asset pack whose meshes, materials and textures the scene references
+        #[structopt(long = "pack", parse(from_os_str))]
+        pack: Option<PathBuf>,
+    },
+}
+
+fn main() {
+    let result = match Command::from_args() {
+        Command::ConvertModel { input, output } => {
+            let output = output.unwrap_or_else(|| input.with_extension(model::BINARY_MODEL_EXTENSION));
+            convert_model(&input, &output)
+        },
+        Command::CompressTextures { inputs, output, downscale, binary } => compress_textures(&inputs, &output, downscale, binary),
+        Command::Pack { directory, output } => pack(&directory, &output),
+        Command::ValidateScene { scene, pack } => validate_scene(&scene, pack.as_ref().map(|pack| pack.as_path())),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn convert_model(input: &Path, output: &Path) -> Result<(), CliError> {
+    let model = ModelData::load(input)?;
+    model.save_binary(output)?;
+    println!("Converted {} with {} meshes and {} textures to {}", input.display(), model.meshes.len(), model.textures.len(), output.display());
+    Ok(())
+}
+
+/// Textures are re-encoded as PNG or raw RGBA rather than GPU block compressed formats, which the texture loader can't upload.
+fn compress_textures(inputs: &[PathBuf], output: &Path, downscale: u32, binary: bool) -> Result<(), CliError> {
+    std::fs::create_dir_all(output)?;
+    for input in inputs {
+        let image = texture::downscale(texture::open_image(input)?, downscale);
+        let extension = if binary { texture::BINARY_TEXTURE_EXTENSION } else { "png" };
+        let file_name = input.file_stem().map(PathBuf::from).unwrap_or_default().with_extension(extension);
+        let output_path = output.join(file_name);
+        if binary {
+            std::fs::write(&output_path, texture::image_to_binary(&image))?;
+        } else {
+            image.save(&output_path)?;
+        }
+        println!("Compressed {} to {}", input.display(), output_path.display());
+    }
+    Ok(())
+}
+
+fn pack(directory: &Path, output: &Path) -> Result<(), CliError> {
+    let pack = AssetPack::from_directory(directory)?;
+    pack.save(output)?;
+    println!("Packed {} files of {} to {}", pack.len(), directory.display(), output.display());
+    Ok(())
+}
+
+fn validate_scene(scene_path: &Path, pack_path: Option<&Path>) -> Result<(), CliError> {
+    let scene_data = SceneData::load(scene_path)?;
+    let mut problems = scene_data.validate();
+    if let Some(pack_path) = pack_path {
+        let pack = AssetPack::load(pack_path)?;
+        problems.extend(missing_assets(&scene_data, &pack));
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(CliError::InvalidScene(problems.len()));
+    }
+    println!("{} is valid", scene_path.display());
+    Ok(())
+}
+
+/// Returns problems with assets referenced by the scene which aren't primitives or assets of the pack.
+fn missing_assets(scene_data: &SceneData, pack: &AssetPack) -> Vec<String> {
+    let mut meshes = HashSet::new();
+    let mut materials = HashSet::new();
+    let mut textures = HashSet::new();
+    let mut problems = Vec::new();
+    for name in pack.names() {
+        let bytes = pack.file(name).unwrap_or_default();
+        let asset_name = asset_pack::asset_name(name);
+        let extension = Path::new(name).extension().and_then(|extension| extension.to_str()).unwrap_or("").to_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "bmp" | "tga" | texture::BINARY_TEXTURE_EXTENSION => {
+                textures.insert(asset_name.to_string());
+            },
+            model::BINARY_MODEL_EXTENSION => match ModelData::from_binary(bytes) {
+                Ok(model) => {
+                    meshes.extend(model.mesh_names(asset_name));
+                    textures.extend(model.texture_names(asset_name));
+                },
+                Err(err) => problems.push(format!("Model {} of the pack can't be loaded: {}", name, err)),
+            },
+            // materials are RON files, but so are scenes and other assets
+            "ron" => if let Ok(material) = std::str::from_utf8(bytes).map_err(|_| ()).and_then(|text| Material::parse(text).map_err(|_| ())) {
+                materials.insert(material.name().to_string());
+            },
+            _ => (),
+        }
+    }
+
+    for (index, object) in scene_data.objects.iter().enumerate() {
+        if let Some(mesh) = &object.mesh {
+            if Primitive::from_mesh_name(mesh).is_none() && !meshes.contains(mesh) {
+                problems.push(format!("Object {} ({}) references mesh {}, which isn't in the pack", index, object.name, mesh));
+            }
+        }
+        if let Some(material) = &object.material {
+            if !materials.contains(material) {
+                problems.push(format!("Object {} ({}) references material {}, which isn't in the pack", index, object.name, material));
+            }
+        }
+        if let Some(lightmap) = &object.lightmap {
+            if !textures.contains(&lightmap.texture) {
+                problems.push(format!("Object {} ({}) references lightmap texture {}, which isn't in the pack", index, object.name, lightmap.texture));
+            }
+        }
+    }
+    problems
+}
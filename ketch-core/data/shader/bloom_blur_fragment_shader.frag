@@ -0,0 +1,25 @@
+#version 450
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D bloom;
+
+layout(push_constant) uniform PushConstants {
+  // distance between blurred texels, along the blurred axis
+  vec2 direction;
+} push_constants;
+
+// 9 tap gaussian blur, where each of the outer taps samples between two texels with bilinear filtering
+const float WEIGHTS[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+const float OFFSETS[3] = float[](0.0, 1.3846153846, 3.2307692308);
+
+void main() {
+  vec3 color = texture(bloom, uv).rgb * WEIGHTS[0];
+  for (int i = 1; i < 3; i++) {
+    vec2 offset = push_constants.direction * OFFSETS[i];
+    color += texture(bloom, uv + offset).rgb * WEIGHTS[i];
+    color += texture(bloom, uv - offset).rgb * WEIGHTS[i];
+  }
+  f_color = vec4(color, 1.0);
+}
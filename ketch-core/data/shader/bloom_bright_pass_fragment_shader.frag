@@ -0,0 +1,24 @@
+#version 450
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D scene_color;
+
+layout(push_constant) uniform PushConstants {
+  vec2 texel_size;
+  float threshold;
+} push_constants;
+
+void main() {
+  // bloom image has half the resolution of the scene, so four scene pixels are averaged to avoid flickering of thin bright details
+  vec2 offset = push_constants.texel_size * 0.5;
+  vec3 color = (texture(scene_color, uv + vec2(-offset.x, -offset.y)).rgb
+              + texture(scene_color, uv + vec2(offset.x, -offset.y)).rgb
+              + texture(scene_color, uv + vec2(-offset.x, offset.y)).rgb
+              + texture(scene_color, uv + vec2(offset.x, offset.y)).rgb) * 0.25;
+  // only brightness above the threshold blooms, so that colors of glowing surfaces are kept
+  float brightness = max(color.r, max(color.g, color.b));
+  float contribution = max(brightness - push_constants.threshold, 0.0) / max(brightness, 0.0001);
+  f_color = vec4(color * contribution, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 color;
+
+layout(location = 0) out vec3 o_color;
+
+layout(push_constant) uniform PushConstants {
+  mat4 view_projection;
+} push_constants;
+
+void main() {
+  gl_Position = push_constants.view_projection * vec4(position, 1.0);
+  o_color = color;
+}
@@ -2,45 +2,179 @@
 layout(location = 0) in vec2 o_tex_coord;
 layout(location = 1) in vec3 o_normal;
 layout(location = 2) in vec3 frag_position;
-layout(location = 3) in vec3 view_space_light_position;
+layout(location = 3) flat in uint o_object_index;
+layout(location = 4) in vec2 o_lightmap_coord;
 
 ///outgoing final color
 layout(location = 0) out vec4 f_color;
 
-layout(binding = 1) uniform LightData {
-  vec3 light_position;
-  vec3 light_color;
+const int MAX_LIGHTS = 8;
+
+const int POINT_LIGHT = 0;
+const int SPOT_LIGHT = 1;
+const int DIRECTIONAL_LIGHT = 2;
+
+struct Light {
+  vec4 position_range;
+  vec4 color_intensity;
+  // xyz - world-space direction the light shines in, w - light type
+  vec4 direction_type;
+  // x - cosine of inner cone angle, y - cosine of outer cone angle
+  vec4 cone;
+};
+
+struct TransformationData {
+  mat4 model;
+  mat4 view;
+  mat4 proj;
+};
+
+struct MaterialData {
+  vec4 base_color;
+  // x - metallic, y - roughness, z - lightmap intensity, 0 if the object isn't lightmapped
+  vec4 parameters;
+  // rgb - emitted color, a - emissive intensity
+  vec4 emissive;
+  // values of custom parameters declared by the material, in order of declaration
+  // x - glow, which adds albedo to emitted light, y - fade, which makes the object transparent
+  vec4 custom;
+};
+
+//Transformations of all objects drawn in a frame
+layout(set = 0, binding = 0) readonly buffer Transformations {
+  TransformationData transformations[];
+};
+
+layout(set = 0, binding = 1) uniform LightData {
+  int light_count;
+  // factor lit colors are multiplied by, set by exposure of the camera
+  float exposure;
+  // rgb - ambient color of surfaces facing up, a - ambient intensity
+  vec4 ambient_sky;
+  // rgb - ambient color of surfaces facing down
+  vec4 ambient_ground;
+  Light lights[MAX_LIGHTS];
 } light_data;
 
-layout(binding = 2) uniform sampler2D tex;
+//Materials of all objects drawn in a frame
+layout(set = 0, binding = 2) readonly buffer Materials {
+  MaterialData materials[];
+};
+
+layout(set = 1, binding = 0) uniform sampler2D tex;
+// baked lighting of the object, objects without a lightmap have their texture bound here
+layout(set = 1, binding = 1) uniform sampler2D lightmap;
 
 layout(push_constant) uniform PushConstants {
-  bool light_source;
   bool uniform_scale;
+  bool selected;
+  float depth_bias;
 } push_constants;
 
-const float AMBIENT_STRENGTH = 0.1;
-const float SPECULAR_STRENGTH = 0.5;
+const float PI = 3.14159265359;
+
+const vec3 SELECTION_COLOR = vec3(1.0, 0.6, 0.0);
+const float SELECTION_STRENGTH = 0.35;
+
+// GGX normal distribution function
+float distribution_ggx(vec3 normal, vec3 halfway, float roughness) {
+  float a = roughness * roughness;
+  float a2 = a * a;
+  float n_dot_h = max(dot(normal, halfway), 0.0);
+  float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+  return a2 / max(PI * denominator * denominator, 0.0001);
+}
+
+// Schlick-GGX geometry function for both view and light direction
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+  float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+  float ggx_view = n_dot_v / (n_dot_v * (1.0 - k) + k);
+  float ggx_light = n_dot_l / (n_dot_l * (1.0 - k) + k);
+  return ggx_view * ggx_light;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+  return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
 
 void main() {
-  if(push_constants.light_source) {
-    f_color = vec4(light_data.light_color, 1.0);
-  } else {
-    // ambient
-    vec3 ambient = AMBIENT_STRENGTH * light_data.light_color;
-
-    // diffuse
-    vec3 norm = normalize(o_normal);
-    vec3 light_dir = normalize(view_space_light_position - frag_position);
-    float diff = max(dot(norm, light_dir), 0.0);
-    vec3 diffuse = diff * light_data.light_color;
-
-    // specular
-    vec3 view_dir = normalize(-frag_position); // the viewer is always at (0,0,0) in view-space, so viewDir is (0,0,0) - Position => -Position
-    vec3 reflect_dir = reflect(-light_dir, norm);  
-    float spec = pow(max(dot(view_dir, reflect_dir), 0.0), 32);
-    vec3 specular = SPECULAR_STRENGTH * spec * light_data.light_color; 
-
-    f_color = vec4((ambient + diffuse + specular) * vec3(texture(tex, o_tex_coord)), 1.0);
+  TransformationData u_main = transformations[o_object_index];
+  MaterialData material = materials[o_object_index];
+
+  vec4 texture_color = texture(tex, o_tex_coord);
+  vec3 albedo = texture_color.rgb * material.base_color.rgb;
+  float alpha = texture_color.a * material.base_color.a * clamp(1.0 - material.custom.y, 0.0, 1.0);
+  float metallic = material.parameters.x;
+  float roughness = max(material.parameters.y, 0.04);
+  float lightmap_intensity = material.parameters.z;
+
+  vec3 norm = normalize(o_normal);
+  // back faces of double-sided geometry are lit from their own side
+  if(!gl_FrontFacing) {
+    norm = -norm;
+  }
+  vec3 view_dir = normalize(-frag_position); // the viewer is always at (0,0,0) in view-space, so viewDir is (0,0,0) - Position => -Position
+  float n_dot_v = max(dot(norm, view_dir), 0.0001);
+  vec3 f0 = mix(vec3(0.04), albedo, metallic);
+
+  // ambient light is blended between ground and sky color by how much the surface faces up in world space
+  vec3 world_normal = transpose(mat3(u_main.view)) * norm;
+  vec3 ambient_color = mix(light_data.ambient_ground.rgb, light_data.ambient_sky.rgb, world_normal.y * 0.5 + 0.5);
+  vec3 lighting = ambient_color * light_data.ambient_sky.a * albedo;
+  // baked lighting replaces ambient light and diffuse lighting of lights
+  if(lightmap_intensity > 0.0) {
+    lighting = texture(lightmap, o_lightmap_coord).rgb * lightmap_intensity * albedo;
+  }
+  for(int i = 0; i < light_data.light_count && i < MAX_LIGHTS; i++) {
+    Light light = light_data.lights[i];
+    vec3 light_color = light.color_intensity.rgb * light.color_intensity.a;
+    int light_type = int(light.direction_type.w + 0.5);
+    // Transform world-space light direction to view-space light direction
+    vec3 view_space_light_direction = normalize(mat3(u_main.view) * light.direction_type.xyz);
+
+    vec3 light_dir;
+    float attenuation = 1.0;
+    if(light_type == DIRECTIONAL_LIGHT) {
+      // directional light comes from infinitely far away, so it doesn't fade out
+      light_dir = -view_space_light_direction;
+    } else {
+      // Transform world-space light position to view-space light position
+      vec3 view_space_light_position = vec3(u_main.view * vec4(light.position_range.xyz, 1.0));
+      light_dir = normalize(view_space_light_position - frag_position);
+
+      // attenuation, light fades out completely at its range
+      float light_distance = length(view_space_light_position - frag_position);
+      float range = max(light.position_range.w, 0.0001);
+      attenuation = clamp(1.0 - light_distance / range, 0.0, 1.0);
+      attenuation *= attenuation;
+
+      if(light_type == SPOT_LIGHT) {
+        // spot light fades out between its inner and outer cone
+        float cos_angle = dot(-light_dir, view_space_light_direction);
+        float cone_width = max(light.cone.x - light.cone.y, 0.0001);
+        attenuation *= clamp((cos_angle - light.cone.y) / cone_width, 0.0, 1.0);
+      }
+    }
+
+    // Cook-Torrance specular and Lambertian diffuse
+    vec3 halfway = normalize(view_dir + light_dir);
+    float n_dot_l = max(dot(norm, light_dir), 0.0);
+
+    float distribution = distribution_ggx(norm, halfway, roughness);
+    float geometry = geometry_smith(n_dot_v, n_dot_l, roughness);
+    vec3 fresnel = fresnel_schlick(max(dot(halfway, view_dir), 0.0), f0);
+    vec3 specular = distribution * geometry * fresnel / max(4.0 * n_dot_v * n_dot_l, 0.0001);
+    vec3 diffuse = lightmap_intensity > 0.0 ? vec3(0.0) : (vec3(1.0) - fresnel) * (1.0 - metallic) * albedo / PI;
+
+    lighting += (diffuse + specular) * light_color * n_dot_l * PI * attenuation;
+  }
+
+  // emitted light isn't affected by lights, and with intensity above 1 it can be brighter than any lit surface
+  vec3 emission = material.emissive.rgb * material.emissive.a + albedo * max(material.custom.x, 0.0);
+
+  f_color = vec4((lighting + emission) * light_data.exposure, alpha);
+
+  if(push_constants.selected) {
+    f_color = vec4(mix(f_color.rgb, SELECTION_COLOR, SELECTION_STRENGTH), f_color.a);
   }
-}
\ No newline at end of file
+}
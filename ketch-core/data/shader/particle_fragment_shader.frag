@@ -0,0 +1,11 @@
+#version 450
+layout(location = 0) in vec2 uv;
+layout(location = 1) in vec4 particle_color;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+void main() {
+  f_color = texture(tex, uv) * particle_color;
+}
@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec2 uv;
+layout(location = 1) out vec4 particle_color;
+
+layout(push_constant) uniform PushConstants {
+  mat4 view_projection;
+} push_constants;
+
+// positions are corners of camera facing quads in world space
+void main() {
+  gl_Position = push_constants.view_projection * vec4(position, 1.0);
+  uv = tex_coord;
+  particle_color = color;
+}
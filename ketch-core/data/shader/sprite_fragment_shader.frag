@@ -0,0 +1,15 @@
+#version 450
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+  vec4 color;
+  vec2 screen_size;
+} push_constants;
+
+void main() {
+  f_color = texture(tex, uv) * push_constants.color;
+}
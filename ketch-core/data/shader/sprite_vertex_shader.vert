@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 tex_coord;
+
+layout(location = 0) out vec2 uv;
+
+layout(push_constant) uniform PushConstants {
+  vec4 color;
+  vec2 screen_size;
+} push_constants;
+
+// positions are in window pixels with origin at the top left corner
+void main() {
+  gl_Position = vec4(position / push_constants.screen_size * 2.0 - 1.0, 0.0, 1.0);
+  uv = tex_coord;
+}
@@ -0,0 +1,26 @@
+#version 450
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+  vec4 channel_mask;
+  float mip_level;
+  uint single_channel;
+} push_constants;
+
+const float CHECKER_SIZE = 16.0;
+
+void main() {
+  vec4 color = textureLod(tex, uv, push_constants.mip_level);
+  if (push_constants.single_channel == 1) {
+    float value = dot(color, push_constants.channel_mask);
+    f_color = vec4(value, value, value, 1.0);
+  } else {
+    // transparent pixels are shown over a checkerboard
+    float checker = mod(floor(uv.x * CHECKER_SIZE) + floor(uv.y * CHECKER_SIZE), 2.0) * 0.2 + 0.4;
+    f_color = vec4(mix(vec3(checker), color.rgb, color.a), 1.0);
+  }
+}
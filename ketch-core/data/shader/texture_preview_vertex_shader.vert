@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) out vec2 uv;
+
+// draws a single triangle covering the whole viewport
+void main() {
+  vec2 position = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+  gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+  // textures are flipped vertically when they're loaded
+  uv = vec2(position.x, 1.0 - position.y);
+}
@@ -0,0 +1,39 @@
+#version 450
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D scene_color;
+layout(set = 0, binding = 1) uniform sampler2D scene_depth;
+layout(set = 0, binding = 2) uniform sampler2D bloom;
+
+layout(push_constant) uniform PushConstants {
+  vec2 texel_size;
+  uint sharpen;
+  // 0 when bloom is disabled
+  float bloom_intensity;
+} push_constants;
+
+// strength of sharpening, between -1/8 and -1/5 like in contrast adaptive sharpening
+const float SHARPENING_PEAK = -0.15;
+
+void main() {
+  vec3 center = texture(scene_color, uv).rgb;
+  vec3 color = center;
+  if (push_constants.sharpen == 1) {
+    vec2 texel = push_constants.texel_size;
+    vec3 north = texture(scene_color, uv - vec2(0.0, texel.y)).rgb;
+    vec3 south = texture(scene_color, uv + vec2(0.0, texel.y)).rgb;
+    vec3 west = texture(scene_color, uv - vec2(texel.x, 0.0)).rgb;
+    vec3 east = texture(scene_color, uv + vec2(texel.x, 0.0)).rgb;
+    vec3 minimum = min(center, min(min(north, south), min(west, east)));
+    vec3 maximum = max(center, max(max(north, south), max(west, east)));
+    // edges which already have high contrast are sharpened less, so they don't ring
+    vec3 amount = sqrt(clamp(min(minimum, 1.0 - maximum) / max(maximum, 0.0001), 0.0, 1.0)) * SHARPENING_PEAK;
+    color = clamp((center + (north + south + west + east) * amount) / (1.0 + 4.0 * amount), 0.0, 1.0);
+  }
+  color += texture(bloom, uv).rgb * push_constants.bloom_intensity;
+  f_color = vec4(color, 1.0);
+  // depth is upscaled too, so that particles drawn afterwards are hidden behind objects
+  gl_FragDepth = texture(scene_depth, uv).r;
+}
@@ -3,33 +3,49 @@
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec3 normal;
 layout(location = 2) in vec2 tex_coord;
+// index of object data in storage buffers, given per instance
+layout(location = 3) in uint object_index;
+// texture coordinates in the lightmap, only set by lightmapped meshes
+layout(location = 4) in vec2 lightmap_coord;
 
 layout(location = 0) out vec2 o_tex_coord;
 layout(location = 1) out vec3 o_normal;
 layout(location = 2) out vec3 frag_position;
-layout(location = 3) out vec3 view_space_light_position;
-
-layout(binding = 1) uniform LightData {
-  vec3 light_position;
-  vec3 light_color;
-} light_data;
+layout(location = 3) flat out uint o_object_index;
+layout(location = 4) out vec2 o_lightmap_coord;
 
 layout(push_constant) uniform PushConstants {
-  bool light_source;
   bool uniform_scale;
+  bool selected;
+  float depth_bias;
 } push_constants;
 
-//Global uniforms
-layout(set = 0, binding = 0) uniform TransformationData {
+// depth difference resolvable by 16 bit depth buffers, which depth bias is measured in
+const float DEPTH_BIAS_UNIT = 1.0 / 65536.0;
+
+struct TransformationData {
   mat4 model;
   mat4 view;
   mat4 proj;
-} u_main;
+};
+
+//Transformations of all objects drawn in a frame
+layout(set = 0, binding = 0) readonly buffer Transformations {
+  TransformationData transformations[];
+};
 
 void main() {
+  TransformationData u_main = transformations[object_index];
+  o_object_index = object_index;
+
   gl_Position = u_main.proj * u_main.view * u_main.model * vec4(position, 1.0);
+  // depth is offset before perspective division, so it's multiplied by w to stay constant after it
+  gl_Position.z += push_constants.depth_bias * DEPTH_BIAS_UNIT * gl_Position.w;
+  // only used by point list pipelines, which need point size to be written
+  gl_PointSize = 1.0;
 
   o_tex_coord = tex_coord;
+  o_lightmap_coord = lightmap_coord;
 
   if(push_constants.uniform_scale) {
     o_normal = mat3(u_main.view * u_main.model) * normal;
@@ -38,5 +54,4 @@ void main() {
   }
   
   frag_position = vec3(u_main.view * u_main.model * vec4(position, 1.0));
-  view_space_light_position = vec3(u_main.view * vec4(light_data.light_position, 1.0)); // Transform world-space light position to view-space light position
 }
@@ -0,0 +1,54 @@
+// Shader of the wgpu render backend, lighting objects with the first light of the frame.
+
+struct FrameUniforms {
+    view_proj: mat4x4<f32>,
+    light_position: vec4<f32>,
+    // light color in rgb and intensity in w, which is zero when frame has no lights
+    light_color: vec4<f32>,
+};
+
+struct DrawUniforms {
+    model: mat4x4<f32>,
+    color: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> frame: FrameUniforms;
+@group(0) @binding(1) var<uniform> draw: DrawUniforms;
+@group(1) @binding(0) var diffuse_texture: texture_2d<f32>;
+@group(1) @binding(1) var diffuse_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coord: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) tex_coord: vec2<f32>,
+};
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    let world_position = draw.model * vec4<f32>(vertex.position, 1.0);
+    var out: VertexOutput;
+    out.clip_position = frame.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    out.normal = (draw.model * vec4<f32>(vertex.normal, 0.0)).xyz;
+    out.tex_coord = vertex.tex_coord;
+    return out;
+}
+
+@fragment
+fn fs_main(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    let base_color = textureSample(diffuse_texture, diffuse_sampler, fragment.tex_coord) * draw.color;
+    var lighting = vec3<f32>(1.0, 1.0, 1.0);
+    if (frame.light_color.w > 0.0) {
+        let light_direction = normalize(frame.light_position.xyz - fragment.world_position);
+        let diffuse = max(dot(normalize(fragment.normal), light_direction), 0.0);
+        lighting = vec3<f32>(0.1, 0.1, 0.1) + frame.light_color.rgb * frame.light_color.w * diffuse;
+    }
+    return vec4<f32>(base_color.rgb * lighting, base_color.a);
+}
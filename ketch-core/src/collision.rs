@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use crate::collision::world_shape::WorldShape;
+use crate::math::bounds::Bounds;
+use crate::event_bus::{EventBus, GameEvent, TriggerEvent};
+use crate::resource::scene::Scene;
+
+pub mod character_controller;
+pub mod world_shape;
+
+/// Detects overlaps of colliders attached to objects, without simulating any physics.
+/// Colliders marked as sensors are triggers, which report objects entering and leaving them.
+pub struct CollisionSystem {
+    /// Pairs of trigger and object overlapping it after the last update.
+    overlaps: HashSet<(u32, u32)>,
+    scene_name: Option<String>,
+}
+
+impl CollisionSystem {
+    /// Creates collision system without any overlaps.
+    pub fn new() -> Self {
+        CollisionSystem {
+            overlaps: HashSet::new(),
+            scene_name: None,
+        }
+    }
+
+    /// Finds objects overlapping triggers and publishes events for objects which entered or left a trigger since the last update.
+    /// Overlaps are forgotten without events when scene changes.
+    pub fn update(&mut self, scene: &Scene, event_bus: &mut EventBus) {
+        if self.scene_name.as_deref() != Some(scene.name()) {
+            self.clear();
+            self.scene_name = Some(scene.name().to_string());
+        }
+
+        let shapes = world_shapes(scene);
+        let mut overlaps = Vec::new();
+        for (trigger, trigger_shape, trigger_bounds) in &shapes {
+            let trigger_is_sensor = scene.object(*trigger).and_then(|object| object.collider()).map(|collider| collider.sensor).unwrap_or(false);
+            if !trigger_is_sensor {
+                continue;
+            }
+            for (object, shape, bounds) in &shapes {
+                if object != trigger && world_shape::bounds_overlap(trigger_bounds, bounds) && trigger_shape.overlaps(shape) {
+                    overlaps.push((*trigger, *object));
+                }
+            }
+        }
+
+        let mut exited: Vec<(u32, u32)> = self.overlaps.iter().filter(|overlap| !overlaps.contains(overlap)).cloned().collect();
+        exited.sort();
+        for (trigger, object) in exited {
+            event_bus.publish(GameEvent::Trigger(TriggerEvent::Exited { trigger, object }));
+        }
+        for &(trigger, object) in overlaps.iter().filter(|overlap| !self.overlaps.contains(overlap)) {
+            event_bus.publish(GameEvent::Trigger(TriggerEvent::Entered { trigger, object }));
+        }
+        self.overlaps = overlaps.into_iter().collect();
+    }
+
+    /// Forgets all overlaps, so that objects already inside triggers will enter them again on the next update.
+    pub fn clear(&mut self) {
+        self.overlaps.clear();
+        self.scene_name = None;
+    }
+
+    /// Returns true if object was inside the trigger during the last update.
+    pub fn is_inside(&self, trigger: u32, object: u32) -> bool {
+        self.overlaps.contains(&(trigger, object))
+    }
+
+    /// Returns ids of objects which were inside the trigger during the last update, sorted by id.
+    pub fn objects_inside(&self, trigger: u32) -> Vec<u32> {
+        let mut objects: Vec<u32> = self.overlaps.iter().filter(|(overlap_trigger, _)| *overlap_trigger == trigger).map(|(_, object)| *object).collect();
+        objects.sort();
+        objects
+    }
+}
+
+impl Default for CollisionSystem {
+    fn default() -> Self {
+        CollisionSystem::new()
+    }
+}
+
+/// Returns ids of objects whose colliders overlap given shape, in scene order.
+pub fn overlapping_objects(scene: &Scene, shape: &WorldShape) -> Vec<u32> {
+    let bounds = shape.bounds();
+    world_shapes(scene).into_iter()
+                       .filter(|(_, object_shape, object_bounds)| world_shape::bounds_overlap(&bounds, object_bounds) && shape.overlaps(object_shape))
+                       .map(|(id, _, _)| id)
+                       .collect()
+}
+
+/// Returns true if colliders of both objects overlap. Returns false if one of them doesn't have a collider.
+pub fn objects_overlap(scene: &Scene, id: u32, other_id: u32) -> bool {
+    let shape = |id| scene.object(id).and_then(|object| WorldShape::from_object(scene, object));
+    match (shape(id), shape(other_id)) {
+        (Some(shape), Some(other_shape)) => shape.overlaps(&other_shape),
+        _ => false,
+    }
+}
+
+fn world_shapes(scene: &Scene) -> Vec<(u32, WorldShape, Bounds)> {
+    scene.objects().iter()
+                   .filter_map(|object| WorldShape::from_object(scene, object).map(|shape| (object.id(), shape, shape.bounds())))
+                   .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::Camera;
+    use crate::resource::collider::{Collider, ColliderShape};
+    use crate::resource::object::ObjectBuilder;
+
+    #[test]
+    fn objects_enter_and_exit_triggers() {
+        let mut scene = Scene::new("test", Camera::new());
+        let mut trigger = ObjectBuilder::new("trigger").build();
+        trigger.set_collider(Some(Collider::sensor(ColliderShape::Cuboid { half_extents: (1.0, 1.0, 1.0) })));
+        let mut ball = ObjectBuilder::new("ball").with_position(0.5, 0.0, 0.0).build();
+        ball.set_collider(Some(Collider::new(ColliderShape::Sphere { radius: 0.5 })));
+        let (trigger_id, ball_id) = (trigger.id(), ball.id());
+        scene.add_object(trigger);
+        scene.add_object(ball);
+        let mut collision_system = CollisionSystem::new();
+        let mut event_bus = EventBus::new();
+
+        collision_system.update(&scene, &mut event_bus);
+        collision_system.update(&scene, &mut event_bus);
+        assert_eq!(vec![GameEvent::Trigger(TriggerEvent::Entered { trigger: trigger_id, object: ball_id })], event_bus.drain());
+
+        scene.object_mut(ball_id).unwrap().set_position(3.0, 0.0, 0.0);
+        collision_system.update(&scene, &mut event_bus);
+        assert_eq!(vec![GameEvent::Trigger(TriggerEvent::Exited { trigger: trigger_id, object: ball_id })], event_bus.drain());
+    }
+}
@@ -0,0 +1,290 @@
+use std::time::Duration;
+
+use nalgebra_glm::{self as glm, Vec3};
+
+use crate::collision::world_shape::{self, Contact, WorldShape};
+#[cfg(feature = "window")]
+use crate::input::input_actions::InputActions;
+#[cfg(feature = "window")]
+use crate::input::input_event::VirtualKeyCode;
+use crate::resource::scene::Scene;
+
+pub const MOVE_FORWARD_ACTION: &str = "move_forward";
+pub const MOVE_BACKWARD_ACTION: &str = "move_backward";
+pub const MOVE_LEFT_ACTION: &str = "move_left";
+pub const MOVE_RIGHT_ACTION: &str = "move_right";
+pub const JUMP_ACTION: &str = "jump";
+
+/// Maximum number of times penetration is resolved after every part of a move.
+const MAX_RESOLVE_ITERATIONS: u32 = 4;
+
+/// Movement requested from the character controller during an update.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct CharacterInput {
+    /// Movement along facing direction from -1.0 to 1.0.
+    pub forward: f32,
+    /// Movement to the right of facing direction from -1.0 to 1.0.
+    pub right: f32,
+    pub jump: bool,
+}
+
+#[cfg(feature = "window")]
+impl CharacterInput {
+    /// Reads movement from character actions.
+    pub fn from_actions(actions: &InputActions) -> Self {
+        CharacterInput {
+            forward: actions.axis(MOVE_BACKWARD_ACTION, MOVE_FORWARD_ACTION),
+            right: actions.axis(MOVE_LEFT_ACTION, MOVE_RIGHT_ACTION),
+            jump: actions.is_pressed(JUMP_ACTION),
+        }
+    }
+}
+
+/// Binds character actions to WASD, arrow keys and space.
+#[cfg(feature = "window")]
+pub fn bind_default_actions(actions: &mut InputActions) {
+    actions.bind(MOVE_FORWARD_ACTION, VirtualKeyCode::W);
+    actions.bind(MOVE_FORWARD_ACTION, VirtualKeyCode::Up);
+    actions.bind(MOVE_BACKWARD_ACTION, VirtualKeyCode::S);
+    actions.bind(MOVE_BACKWARD_ACTION, VirtualKeyCode::Down);
+    actions.bind(MOVE_LEFT_ACTION, VirtualKeyCode::A);
+    actions.bind(MOVE_LEFT_ACTION, VirtualKeyCode::Left);
+    actions.bind(MOVE_RIGHT_ACTION, VirtualKeyCode::D);
+    actions.bind(MOVE_RIGHT_ACTION, VirtualKeyCode::Right);
+    actions.bind(JUMP_ACTION, VirtualKeyCode::Space);
+}
+
+/// Moves an object as an upright capsule, which collides with colliders of other objects, without using physics.
+/// Character walks up slopes not steeper than maximum slope angle and steps onto obstacles not higher than step height.
+/// Capsule is centered at the object position, which should be a root object. Sensors don't block the character.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CharacterController {
+    pub radius: f32,
+    /// Half of distance between centers of capsule caps.
+    pub half_height: f32,
+    /// Walking speed in units per second.
+    pub speed: f32,
+    /// Vertical speed at the start of a jump in units per second.
+    pub jump_speed: f32,
+    /// Gravity in units per second squared.
+    pub gravity: f32,
+    /// Steepest slope character can stand on, in radians.
+    pub max_slope_angle: f32,
+    pub step_height: f32,
+    vertical_velocity: f32,
+    grounded: bool,
+}
+
+impl CharacterController {
+    /// Creates character controller with capsule of given size.
+    pub fn new(radius: f32, half_height: f32) -> Self {
+        CharacterController {
+            radius,
+            half_height,
+            speed: 5.0,
+            jump_speed: 5.0,
+            gravity: 9.81,
+            max_slope_angle: std::f32::consts::FRAC_PI_4,
+            step_height: 0.3,
+            vertical_velocity: 0.0,
+            grounded: false,
+        }
+    }
+
+    /// Returns true if character stood on the ground after the last update.
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Returns vertical velocity in units per second, positive while jumping.
+    pub fn vertical_velocity(&self) -> f32 {
+        self.vertical_velocity
+    }
+
+    /// Moves object with the input. Forward is the direction character faces, only its horizontal part is used.
+    pub fn update(&mut self, scene: &mut Scene, object_id: u32, input: &CharacterInput, forward: Vec3, time_delta: Duration) {
+        let time_delta = time_delta.as_millis() as f32 / 1000.0;
+        let (position, local_position) = match scene.object(object_id) {
+            Some(object) => (scene.world_position(object), object.position()),
+            None => return,
+        };
+        let obstacles: Vec<WorldShape> = scene.objects().iter()
+                                                        .filter(|object| object.id() != object_id)
+                                                        .filter(|object| object.collider().map(|collider| !collider.sensor).unwrap_or(false))
+                                                        .filter_map(|object| WorldShape::from_object(scene, object))
+                                                        .collect();
+
+        let mut forward = Vec3::new(forward.x, 0.0, forward.z);
+        if glm::length(&forward) > 0.0 {
+            forward = glm::normalize(&forward);
+        }
+        let right = Vec3::new(-forward.z, 0.0, forward.x);
+        let mut direction = forward * input.forward + right * input.right;
+        if glm::length(&direction) > 1.0 {
+            direction = glm::normalize(&direction);
+        }
+        let horizontal = direction * self.speed * time_delta;
+
+        let was_grounded = self.grounded;
+        if was_grounded && input.jump {
+            self.vertical_velocity = self.jump_speed;
+        }
+        self.vertical_velocity -= self.gravity * time_delta;
+
+        let (mut moved_position, normals) = self.move_and_collide(position, horizontal, &obstacles);
+        let blocked = normals.iter().any(|normal| !self.is_ground(normal) && normal.y > -self.min_ground_normal_y());
+        if was_grounded && blocked && self.step_height > 0.0 {
+            if let Some(stepped_position) = self.step_up(position, horizontal, &obstacles) {
+                if horizontal_distance(&position, &stepped_position) > horizontal_distance(&position, &moved_position) {
+                    moved_position = stepped_position;
+                }
+            }
+        }
+
+        // grounded character is pulled down by step height, so that it follows slopes and steps going down
+        let fall = Vec3::new(0.0, self.vertical_velocity * time_delta, 0.0);
+        let snapping = was_grounded && self.vertical_velocity <= 0.0;
+        let (mut final_position, mut normals) = if snapping {
+            self.move_and_collide(moved_position, fall - Vec3::new(0.0, self.step_height, 0.0), &obstacles)
+        } else {
+            self.move_and_collide(moved_position, fall, &obstacles)
+        };
+        if snapping && !normals.iter().any(|normal| self.is_ground(normal)) {
+            // character walked off a ledge, so it starts falling instead of being snapped down
+            let (falling_position, falling_normals) = self.move_and_collide(moved_position, fall, &obstacles);
+            final_position = falling_position;
+            normals = falling_normals;
+        }
+
+        self.grounded = self.vertical_velocity <= 0.0 && normals.iter().any(|normal| self.is_ground(normal));
+        let hit_ceiling = self.vertical_velocity > 0.0 && normals.iter().any(|normal| normal.y <= -self.min_ground_normal_y());
+        if self.grounded || hit_ceiling {
+            self.vertical_velocity = 0.0;
+        }
+
+        let displacement = final_position - position;
+        if let Some(object) = scene.object_mut(object_id) {
+            object.set_position(local_position.0 + displacement.x, local_position.1 + displacement.y, local_position.2 + displacement.z);
+        }
+    }
+
+    /// Moves capsule in steps much shorter than its radius and pushes it out of obstacles after every step.
+    /// Short steps keep it from passing through thin obstacles and from sinking deep enough into edges that it's pushed out
+    /// along a steeper normal than of the surface it touched, e.g. back off a step it's being snapped down onto.
+    /// Returns final position and normals of all contacts.
+    fn move_and_collide(&self, position: Vec3, displacement: Vec3, obstacles: &[WorldShape]) -> (Vec3, Vec<Vec3>) {
+        let steps = ((glm::length(&displacement) / (self.radius * 0.1)).ceil() as u32).max(1);
+        let mut position = position;
+        let mut normals = Vec::new();
+        for _ in 0..steps {
+            position += displacement / steps as f32;
+            for _ in 0..MAX_RESOLVE_ITERATIONS {
+                let mut resolved = true;
+                for obstacle in obstacles {
+                    if let Some(contact) = self.contact(&position, obstacle) {
+                        position += self.push_out(&contact);
+                        normals.push(contact.normal);
+                        resolved = false;
+                    }
+                }
+                if resolved {
+                    break;
+                }
+            }
+        }
+        (position, normals)
+    }
+
+    /// Moves capsule up by step height, then forward and back down. Returns None if there is no ground to step on.
+    fn step_up(&self, position: Vec3, horizontal: Vec3, obstacles: &[WorldShape]) -> Option<Vec3> {
+        let (raised_position, _) = self.move_and_collide(position, Vec3::new(0.0, self.step_height, 0.0), obstacles);
+        let (moved_position, _) = self.move_and_collide(raised_position, horizontal, obstacles);
+        let lowering = Vec3::new(0.0, position.y - raised_position.y, 0.0);
+        let (lowered_position, normals) = self.move_and_collide(moved_position, lowering, obstacles);
+        if normals.iter().any(|normal| self.is_ground(normal)) {
+            Some(lowered_position)
+        } else {
+            None
+        }
+    }
+
+    fn contact(&self, position: &Vec3, obstacle: &WorldShape) -> Option<Contact> {
+        let half_height = Vec3::new(0.0, self.half_height, 0.0);
+        world_shape::capsule_contact(&(position - half_height), &(position + half_height), self.radius, obstacle)
+    }
+
+    /// Returns movement leaving the obstacle. Character is pushed straight up from walkable ground, so it doesn't slide down slopes.
+    fn push_out(&self, contact: &Contact) -> Vec3 {
+        if self.is_ground(&contact.normal) {
+            Vec3::new(0.0, contact.depth / contact.normal.y, 0.0)
+        } else {
+            contact.normal * contact.depth
+        }
+    }
+
+    fn is_ground(&self, normal: &Vec3) -> bool {
+        normal.y >= self.min_ground_normal_y()
+    }
+
+    fn min_ground_normal_y(&self) -> f32 {
+        self.max_slope_angle.cos()
+    }
+}
+
+fn horizontal_distance(from: &Vec3, to: &Vec3) -> f32 {
+    glm::length(&Vec3::new(to.x - from.x, 0.0, to.z - from.z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::Camera;
+    use crate::resource::collider::{Collider, ColliderShape};
+    use crate::resource::object::ObjectBuilder;
+
+    fn scene_with_floor() -> Scene {
+        let mut scene = Scene::new("test", Camera::new());
+        let mut floor = ObjectBuilder::new("floor").with_position(0.0, -0.5, 0.0).build();
+        floor.set_collider(Some(Collider::new(ColliderShape::Cuboid { half_extents: (10.0, 0.5, 10.0) })));
+        scene.add_object(floor);
+        scene
+    }
+
+    #[test]
+    fn character_lands_on_floor() {
+        let mut scene = scene_with_floor();
+        let character = ObjectBuilder::new("character").with_position(0.0, 2.0, 0.0).build();
+        let character_id = character.id();
+        scene.add_object(character);
+        let mut controller = CharacterController::new(0.5, 0.5);
+
+        for _ in 0..120 {
+            controller.update(&mut scene, character_id, &CharacterInput::default(), Vec3::new(0.0, 0.0, -1.0), Duration::from_millis(16));
+        }
+
+        assert!(controller.grounded());
+        assert!((scene.object(character_id).unwrap().position_y() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn character_steps_onto_low_obstacle() {
+        let mut scene = scene_with_floor();
+        let mut step = ObjectBuilder::new("step").with_position(3.0, 0.1, 0.0).build();
+        step.set_collider(Some(Collider::new(ColliderShape::Cuboid { half_extents: (1.5, 0.1, 10.0) })));
+        scene.add_object(step);
+        let character = ObjectBuilder::new("character").with_position(0.0, 1.0, 0.0).build();
+        let character_id = character.id();
+        scene.add_object(character);
+        let mut controller = CharacterController::new(0.5, 0.5);
+        let input = CharacterInput { forward: 1.0, right: 0.0, jump: false };
+
+        for _ in 0..40 {
+            controller.update(&mut scene, character_id, &input, Vec3::new(1.0, 0.0, 0.0), Duration::from_millis(16));
+        }
+
+        let (x, y, _) = scene.object(character_id).unwrap().position();
+        assert!(x > 2.5);
+        assert!((y - 1.2).abs() < 0.01);
+        assert!(controller.grounded());
+    }
+}
@@ -0,0 +1,265 @@
+use nalgebra_glm::{self as glm, Vec3, Vec4};
+
+use crate::math::bounds::Bounds;
+use crate::resource::collider::ColliderShape;
+use crate::resource::object::Object;
+use crate::resource::scene::Scene;
+
+/// Number of iterations used to find point of a segment closest to a box.
+const SEGMENT_BOX_ITERATIONS: u32 = 32;
+
+/// Distance below which closest points of shapes are treated as the same point.
+const CONTACT_EPSILON: f32 = 0.0001;
+
+/// Collider shape placed in the world.
+/// Boxes are axis aligned, they don't rotate with their objects.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorldShape {
+    Aabb(Bounds),
+    Sphere { center: Vec3, radius: f32 },
+    /// Capsule around segment from start to end.
+    Capsule { start: Vec3, end: Vec3, radius: f32 },
+}
+
+impl WorldShape {
+    /// Returns collider shape of the object placed at its world position,
+    /// or None if object doesn't have a collider.
+    pub fn from_object(scene: &Scene, object: &Object) -> Option<Self> {
+        let world_matrix = scene.world_matrix(object);
+        let column_length = |column: usize| glm::length(&world_matrix.column(column).xyz());
+        let scale = (column_length(0), column_length(1), column_length(2));
+        let center = (world_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+
+        let shape = match object.collider()?.shape.scaled(scale) {
+            ColliderShape::Cuboid { half_extents: (x, y, z) } => {
+                let half_extents = Vec3::new(x, y, z);
+                WorldShape::Aabb(Bounds::new(center - half_extents, center + half_extents))
+            },
+            ColliderShape::Sphere { radius } => WorldShape::Sphere { center, radius },
+            ColliderShape::Capsule { half_height, radius } => {
+                let axis = (world_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz();
+                let axis = if glm::length(&axis) > 0.0 { glm::normalize(&axis) * half_height } else { axis };
+                WorldShape::Capsule { start: center - axis, end: center + axis, radius }
+            },
+        };
+        Some(shape)
+    }
+
+    /// Returns axis-aligned bounds containing the whole shape.
+    pub fn bounds(&self) -> Bounds {
+        match *self {
+            WorldShape::Aabb(bounds) => bounds,
+            WorldShape::Sphere { center, radius } => {
+                let radius = Vec3::new(radius, radius, radius);
+                Bounds::new(center - radius, center + radius)
+            },
+            WorldShape::Capsule { start, end, radius } => {
+                let radius = Vec3::new(radius, radius, radius);
+                Bounds::new(glm::min2(&start, &end) - radius, glm::max2(&start, &end) + radius)
+            },
+        }
+    }
+
+    /// Returns true if shapes overlap or touch.
+    pub fn overlaps(&self, other: &WorldShape) -> bool {
+        match (*self, *other) {
+            (WorldShape::Aabb(bounds), WorldShape::Aabb(other_bounds)) => bounds_overlap(&bounds, &other_bounds),
+            (WorldShape::Aabb(bounds), WorldShape::Sphere { center, radius })
+            | (WorldShape::Sphere { center, radius }, WorldShape::Aabb(bounds)) => {
+                glm::distance2(&center, &closest_point_in_bounds(&bounds, &center)) <= radius * radius
+            },
+            (WorldShape::Aabb(bounds), WorldShape::Capsule { start, end, radius })
+            | (WorldShape::Capsule { start, end, radius }, WorldShape::Aabb(bounds)) => {
+                segment_bounds_distance2(&start, &end, &bounds) <= radius * radius
+            },
+            (WorldShape::Sphere { center, radius }, WorldShape::Sphere { center: other_center, radius: other_radius }) => {
+                glm::distance2(&center, &other_center) <= (radius + other_radius) * (radius + other_radius)
+            },
+            (WorldShape::Sphere { center, radius: sphere_radius }, WorldShape::Capsule { start, end, radius })
+            | (WorldShape::Capsule { start, end, radius }, WorldShape::Sphere { center, radius: sphere_radius }) => {
+                let closest_point = closest_point_on_segment(&start, &end, &center);
+                glm::distance2(&center, &closest_point) <= (radius + sphere_radius) * (radius + sphere_radius)
+            },
+            (WorldShape::Capsule { start, end, radius }, WorldShape::Capsule { start: other_start, end: other_end, radius: other_radius }) => {
+                segment_segment_distance2(&start, &end, &other_start, &other_end) <= (radius + other_radius) * (radius + other_radius)
+            },
+        }
+    }
+}
+
+/// Contact of a capsule with a shape it penetrates.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Contact {
+    /// Direction in which capsule needs to move to leave the shape.
+    pub normal: Vec3,
+    /// Distance capsule needs to move along the normal to leave the shape.
+    pub depth: f32,
+}
+
+/// Returns contact of a capsule around segment from start to end with the shape, or None if they don't overlap.
+pub fn capsule_contact(start: &Vec3, end: &Vec3, radius: f32, shape: &WorldShape) -> Option<Contact> {
+    let (capsule_point, shape_point, shape_radius) = match *shape {
+        WorldShape::Aabb(bounds) => {
+            let (capsule_point, bounds_point) = closest_points_segment_bounds(start, end, &bounds);
+            (capsule_point, bounds_point, 0.0)
+        },
+        WorldShape::Sphere { center, radius } => (closest_point_on_segment(start, end, &center), center, radius),
+        WorldShape::Capsule { start: other_start, end: other_end, radius } => {
+            let (capsule_point, other_point) = closest_points_on_segments(start, end, &other_start, &other_end);
+            (capsule_point, other_point, radius)
+        },
+    };
+    let offset = capsule_point - shape_point;
+    let distance = glm::length(&offset);
+    let depth = radius + shape_radius - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+    if distance > CONTACT_EPSILON {
+        return Some(Contact { normal: offset / distance, depth });
+    }
+
+    // capsule center line is inside the shape, so direction from closest points can't be used
+    match *shape {
+        WorldShape::Aabb(bounds) => {
+            let (min, max) = (bounds.min(), bounds.max());
+            let faces = [
+                (Vec3::new(-1.0, 0.0, 0.0), capsule_point.x - min.x),
+                (Vec3::new(1.0, 0.0, 0.0), max.x - capsule_point.x),
+                (Vec3::new(0.0, -1.0, 0.0), capsule_point.y - min.y),
+                (Vec3::new(0.0, 1.0, 0.0), max.y - capsule_point.y),
+                (Vec3::new(0.0, 0.0, -1.0), capsule_point.z - min.z),
+                (Vec3::new(0.0, 0.0, 1.0), max.z - capsule_point.z),
+            ];
+            let (normal, face_distance) = faces.iter()
+                                               .cloned()
+                                               .min_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(std::cmp::Ordering::Equal))
+                                               .expect("box has faces");
+            Some(Contact { normal, depth: depth + face_distance })
+        },
+        _ => Some(Contact { normal: Vec3::new(0.0, 1.0, 0.0), depth }),
+    }
+}
+
+/// Returns true if bounds overlap or touch.
+pub fn bounds_overlap(bounds: &Bounds, other: &Bounds) -> bool {
+    let (min, max, other_min, other_max) = (bounds.min(), bounds.max(), other.min(), other.max());
+    min.x <= other_max.x && max.x >= other_min.x
+    && min.y <= other_max.y && max.y >= other_min.y
+    && min.z <= other_max.z && max.z >= other_min.z
+}
+
+pub(crate) fn closest_point_in_bounds(bounds: &Bounds, point: &Vec3) -> Vec3 {
+    glm::clamp_vec(point, &bounds.min(), &bounds.max())
+}
+
+pub(crate) fn closest_point_on_segment(start: &Vec3, end: &Vec3, point: &Vec3) -> Vec3 {
+    let direction = end - start;
+    let length2 = glm::length2(&direction);
+    if length2 == 0.0 {
+        return *start;
+    }
+    let t = (glm::dot(&(point - start), &direction) / length2).clamp(0.0, 1.0);
+    start + direction * t
+}
+
+/// Returns squared distance between a segment and a box.
+fn segment_bounds_distance2(start: &Vec3, end: &Vec3, bounds: &Bounds) -> f32 {
+    let (segment_point, bounds_point) = closest_points_segment_bounds(start, end, bounds);
+    glm::distance2(&segment_point, &bounds_point)
+}
+
+/// Returns squared distance between closest points of two segments.
+fn segment_segment_distance2(start: &Vec3, end: &Vec3, other_start: &Vec3, other_end: &Vec3) -> f32 {
+    let (point, other_point) = closest_points_on_segments(start, end, other_start, other_end);
+    glm::distance2(&point, &other_point)
+}
+
+/// Returns point of the segment closest to a box and point of the box closest to it.
+/// Distance to a box changes along a segment as a convex function, so its minimum is found with ternary search.
+pub(crate) fn closest_points_segment_bounds(start: &Vec3, end: &Vec3, bounds: &Bounds) -> (Vec3, Vec3) {
+    let point_at = |t: f32| start + (end - start) * t;
+    let distance2_at = |t: f32| {
+        let point = point_at(t);
+        glm::distance2(&point, &closest_point_in_bounds(bounds, &point))
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..SEGMENT_BOX_ITERATIONS {
+        let first_third = low + (high - low) / 3.0;
+        let second_third = high - (high - low) / 3.0;
+        if distance2_at(first_third) <= distance2_at(second_third) {
+            high = second_third;
+        } else {
+            low = first_third;
+        }
+    }
+    let t = [(low + high) * 0.5, 0.0, 1.0].iter()
+                                          .cloned()
+                                          .min_by(|x, y| distance2_at(*x).partial_cmp(&distance2_at(*y)).unwrap_or(std::cmp::Ordering::Equal))
+                                          .unwrap_or(0.0);
+    let point = point_at(t);
+    (point, closest_point_in_bounds(bounds, &point))
+}
+
+/// Returns closest points of two segments.
+pub(crate) fn closest_points_on_segments(start: &Vec3, end: &Vec3, other_start: &Vec3, other_end: &Vec3) -> (Vec3, Vec3) {
+    let direction = end - start;
+    let other_direction = other_end - other_start;
+    let offset = start - other_start;
+    let length2 = glm::length2(&direction);
+    let other_length2 = glm::length2(&other_direction);
+    let other_offset = glm::dot(&other_direction, &offset);
+
+    let (t, other_t) = if length2 == 0.0 && other_length2 == 0.0 {
+        (0.0, 0.0)
+    } else if length2 == 0.0 {
+        (0.0, (other_offset / other_length2).clamp(0.0, 1.0))
+    } else {
+        let offset_along = glm::dot(&direction, &offset);
+        if other_length2 == 0.0 {
+            ((-offset_along / length2).clamp(0.0, 1.0), 0.0)
+        } else {
+            let directions_dot = glm::dot(&direction, &other_direction);
+            let denominator = length2 * other_length2 - directions_dot * directions_dot;
+            // parallel segments have no single closest point, any point of the first one can be used
+            let t = if denominator != 0.0 {
+                ((directions_dot * other_offset - offset_along * other_length2) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let other_t = (directions_dot * t + other_offset) / other_length2;
+            if other_t < 0.0 {
+                ((-offset_along / length2).clamp(0.0, 1.0), 0.0)
+            } else if other_t > 1.0 {
+                (((directions_dot - offset_along) / length2).clamp(0.0, 1.0), 1.0)
+            } else {
+                (t, other_t)
+            }
+        }
+    };
+    (start + direction * t, other_start + other_direction * other_t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capsule_overlaps_box_only_within_radius() {
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let capsule = |x: f32| WorldShape::Capsule { start: Vec3::new(x, -3.0, 3.0), end: Vec3::new(x, 3.0, -3.0), radius: 0.5 };
+
+        assert!(WorldShape::Aabb(bounds).overlaps(&capsule(1.4)));
+        assert!(!WorldShape::Aabb(bounds).overlaps(&capsule(1.6)));
+    }
+
+    #[test]
+    fn crossing_capsules_overlap() {
+        let capsule = WorldShape::Capsule { start: Vec3::new(-2.0, 0.0, 0.0), end: Vec3::new(2.0, 0.0, 0.0), radius: 0.25 };
+        let crossing = WorldShape::Capsule { start: Vec3::new(0.0, 0.4, -2.0), end: Vec3::new(0.0, 0.4, 2.0), radius: 0.25 };
+        let distant = WorldShape::Capsule { start: Vec3::new(0.0, 0.6, -2.0), end: Vec3::new(0.0, 0.6, 2.0), radius: 0.25 };
+
+        assert!(capsule.overlaps(&crossing));
+        assert!(!capsule.overlaps(&distant));
+    }
+}
@@ -0,0 +1,81 @@
+use nalgebra_glm::Vec3;
+
+use crate::ui::UiEvent;
+
+/// Collision between objects, which started or stopped touching during the last fixed update.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CollisionEvent {
+    Started(u32, u32),
+    Stopped(u32, u32),
+}
+
+/// Object entering or leaving a trigger, which is an object with a sensor collider.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TriggerEvent {
+    Entered { trigger: u32, object: u32 },
+    Exited { trigger: u32, object: u32 },
+}
+
+/// Object added to or removed from the active scene, used by gameplay systems to keep per-object state.
+/// Objects of a scene which becomes active are reported by `SceneEvent::Activated` rather than one by one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ObjectEvent {
+    Added(u32),
+    Removed(u32),
+}
+
+/// Scene which became active or stopped being active, or whose origin was shifted.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SceneEvent {
+    Activated(String),
+    Deactivated(String),
+    /// Origin of the active scene was shifted by the offset. Positions kept by the game, like nav meshes or waypoints, should be moved by the negated offset.
+    OriginShifted(Vec3),
+}
+
+/// Events published by engine subsystems.
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Collision(CollisionEvent),
+    Trigger(TriggerEvent),
+    Ui(UiEvent),
+    Object(ObjectEvent),
+    Scene(SceneEvent),
+    /// Current language was switched, string tables of the new language are used from now on.
+    LanguageChanged(String),
+}
+
+/// Queue of events published during a fixed update, which are handed to the game after it.
+pub struct EventBus {
+    events: Vec<GameEvent>,
+}
+
+impl EventBus {
+    /// Creates new empty event bus.
+    pub fn new() -> Self {
+        EventBus {
+            events: Vec::new(),
+        }
+    }
+
+    /// Adds event to the queue.
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// Returns true if no events were published since the queue was last drained.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Removes and returns all events in the order they were published.
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
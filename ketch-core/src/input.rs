@@ -1,13 +1,31 @@
+pub mod cursor_icon;
+pub mod gamepad;
+pub mod input_actions;
+pub mod input_axes;
+pub mod input_bindings_error;
 pub mod input_event;
+pub mod input_router;
 
 use winit::Window;
+#[cfg(feature = "vulkan")]
 use vulkano::swapchain::Surface;
 use std::sync::Arc;
-use crate::settings::Settings;
+use std::path::Path;
+use std::time::Instant;
+#[cfg(feature = "gamepad")]
+use std::time::Duration;
 use crate::input::input_event::InputEvent;
+use crate::input::cursor_icon::CursorIcon;
+#[cfg(feature = "gamepad")]
+use crate::input::gamepad::{GamepadId, Gamepads, RumbleEnvelope};
+use crate::input::input_actions::{InputActions, InputBindings};
+use crate::input::input_bindings_error::InputBindingsError;
+use crate::input::input_axes::InputAxes;
+use crate::input::input_event::ElementState;
+use crate::input::input_router::InputRouter;
+use crate::math::ray::Ray;
+use crate::resource::camera::Camera;
 
-use std::cell::RefCell;
-use std::rc::Rc;
 
 use winit::EventsLoop;
 use winit::Event;
@@ -15,36 +33,80 @@ use winit::WindowEvent;
 
 use log::*;
 
+/// Window whose input is fetched, shared with the renderer drawing to it.
+enum InputWindow {
+    /// Window of the Vulkan renderer, owned by its surface.
+    #[cfg(feature = "vulkan")]
+    Surface(Arc<Surface<Window>>),
+    /// Window of renderers which don't draw to Vulkan surfaces.
+    Window(Arc<Window>),
+}
+
 /// Manages input. Fetches input events and manages window.
 pub struct InputSystem {
-    events_loop: EventsLoop,
-    surface: Option<Arc<Surface<Window>>>,
+    // None when input system is created without a window
+    events_loop: Option<EventsLoop>,
+    window: Option<InputWindow>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
+    actions: InputActions,
+    axes: InputAxes,
+    last_axes_update: Option<Instant>,
+    router: InputRouter,
+    // last cursor position in logical pixels, measured from the top left corner of the window
+    cursor_position: Option<(f64, f64)>,
+    cursor_icon: CursorIcon,
 }
 
 impl InputSystem {
     /// Creates new input system. At first surface is set to None because renderer is created after input system.
     pub fn new() -> Self {
-        let events_loop = EventsLoop::new();
+        InputSystem::with_events_loop(Some(EventsLoop::new()))
+    }
+
+    /// Creates input system without a window, e.g. for the null renderer on machines without a display. It reads only gamepads, if they're enabled.
+    pub fn without_window() -> Self {
+        InputSystem::with_events_loop(None)
+    }
 
+    fn with_events_loop(events_loop: Option<EventsLoop>) -> Self {
         InputSystem {
             events_loop: events_loop,
-            surface: None,
+            window: None,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(),
+            actions: InputActions::new(),
+            axes: InputAxes::new(),
+            last_axes_update: None,
+            router: InputRouter::new(),
+            cursor_position: None,
+            cursor_icon: CursorIcon::default(),
         }
     }
 
-    /// Returns a reference to the events loop.
-    pub fn events_loop(&self) -> &EventsLoop {
-        &self.events_loop
+    /// Returns a reference to the events loop, or None if input system was created without a window.
+    pub fn events_loop(&self) -> Option<&EventsLoop> {
+        self.events_loop.as_ref()
     }
 
     /// Sets the current surface.
+    #[cfg(feature = "vulkan")]
     pub fn set_surface(&mut self, surface: Arc<Surface<Window>>) {
-        self.surface = Some(surface);
+        self.window = Some(InputWindow::Surface(surface));
+    }
+
+    /// Sets the current window, when it isn't drawn to by the Vulkan renderer.
+    pub fn set_window(&mut self, window: Arc<Window>) {
+        self.window = Some(InputWindow::Window(window));
     }
 
     /// Returns an Option with a reference to the application window.
     pub fn window(&self) -> Option<&Window> {
-        self.surface.as_ref().map(|x| x.window())
+        self.window.as_ref().map(|window| match window {
+            #[cfg(feature = "vulkan")]
+            InputWindow::Surface(surface) => surface.window(),
+            InputWindow::Window(window) => &**window,
+        })
     }
 
     /// Grabs cursor, preventing it from leaving the window.
@@ -63,13 +125,171 @@ impl InputSystem {
         }
     }
 
+    /// Sets icon of the system cursor. It can be set every frame, window is only updated when the icon changes.
+    /// Custom cursor images aren't supported by the windowing library, games can hide the cursor instead
+    /// and draw a UI image at `cursor_position`.
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        if icon == self.cursor_icon {
+            return;
+        }
+        self.cursor_icon = icon;
+        if let Some(window) = self.window() {
+            window.set_cursor(icon.mouse_cursor());
+        }
+    }
+
+    /// Returns icon of the system cursor.
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    /// Returns actions bound to keys, updated with input events before they are passed to the game.
+    pub fn actions(&self) -> &InputActions {
+        &self.actions
+    }
+
+    /// Returns a mutable reference to actions bound to keys.
+    pub fn actions_mut(&mut self) -> &mut InputActions {
+        &mut self.actions
+    }
+
+    /// Returns analog axes, filtered before input is delivered to the game.
+    pub fn axes(&self) -> &InputAxes {
+        &self.axes
+    }
+
+    /// Returns a mutable reference to analog axes, used to bind them and change their filters.
+    pub fn axes_mut(&mut self) -> &mut InputAxes {
+        &mut self.axes
+    }
+
+    /// Returns true if any key or button bound to the action is pressed.
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.actions.is_pressed(action)
+    }
+
+    /// Returns filtered value of the axis, or zero if it isn't bound.
+    pub fn axis(&self, axis: &str) -> f32 {
+        self.axes.value(axis)
+    }
+
+    /// Returns serializable bindings of all actions and axes.
+    pub fn bindings(&self) -> InputBindings {
+        InputBindings { axes: self.axes.bindings(), ..self.actions.bindings() }
+    }
+
+    /// Replaces bindings of all actions and axes.
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.axes.set_bindings(bindings.axes.clone());
+        self.actions.set_bindings(bindings);
+    }
+
+    /// Replaces bindings of all actions and axes with ones loaded from a controls settings file.
+    pub fn load_bindings<P: AsRef<Path>>(&mut self, path: P) -> Result<(), InputBindingsError> {
+        self.set_bindings(InputBindings::load(path)?);
+        Ok(())
+    }
+
+    /// Saves bindings of all actions and axes to a controls settings file.
+    pub fn save_bindings<P: AsRef<Path>>(&self, path: P) -> Result<(), InputBindingsError> {
+        self.bindings().save(path)
+    }
+
+    /// Filters values of axes with input handled since the previous call, using real time between the calls.
+    pub fn update_axes(&mut self) {
+        let now = Instant::now();
+        let delta_time = self.last_axes_update.map(|last| now - last).unwrap_or_default();
+        self.last_axes_update = Some(now);
+        self.axes.update(&self.actions, delta_time.as_secs() as f32 + delta_time.subsec_nanos() as f32 / 1_000_000_000.0);
+    }
+
+    /// Returns a mutable reference to the router, which passes input through layers registered by the game.
+    pub fn router_mut(&mut self) -> &mut InputRouter {
+        &mut self.router
+    }
+
+    /// Passes events through input layers with priority in given range and returns events none of them consumed.
+    /// Cursor position is updated with all events, and consumed key and button releases still release actions, so that no action stays pressed.
+    pub fn route_events(&mut self, events: Vec<InputEvent>, min_priority: i32, max_priority: i32) -> Vec<InputEvent> {
+        for event in &events {
+            match event {
+                InputEvent::CursorMoved { x, y } => self.cursor_position = Some((*x, *y)),
+                InputEvent::KeyboardInput { state: ElementState::Released, .. } |
+                InputEvent::MouseInput { state: ElementState::Released, .. } |
+                InputEvent::GamepadButton { state: ElementState::Released, .. } => self.actions.handle_event(event),
+                _ => (),
+            }
+        }
+        self.router.route(events, min_priority, max_priority)
+    }
+
+    /// Updates actions, axes and cursor position with the input event.
+    pub fn handle_event(&mut self, event: &InputEvent) {
+        if let InputEvent::CursorMoved { x, y } = event {
+            self.cursor_position = Some((*x, *y));
+        }
+        self.actions.handle_event(event);
+        self.axes.handle_event(event);
+    }
+
+    /// Returns last cursor position in logical pixels, measured from the top left corner of the window.
+    /// Returns None if cursor didn't move over the window yet.
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor_position
+    }
+
+    /// Returns a ray going from the camera through the mouse cursor, used for picking objects in the world.
+    /// Cursor position and window size are both in logical pixels, so the ray doesn't depend on DPI of the monitor.
+    /// Returns None if there is no window or cursor didn't move over it yet.
+    pub fn mouse_ray(&self, camera: &Camera) -> Option<Ray> {
+        let (x, y) = self.cursor_position?;
+        let window_size = self.window()?.get_inner_size()?;
+        if window_size.width <= 0.0 || window_size.height <= 0.0 {
+            return None;
+        }
+        Some(Ray::from_screen_position(camera, x as f32, y as f32, window_size.width as f32, window_size.height as f32))
+    }
+
+    /// Returns ids of connected gamepads.
+    #[cfg(feature = "gamepad")]
+    pub fn gamepads(&self) -> Vec<GamepadId> {
+        self.gamepads.connected()
+    }
+
+    /// Rumbles the gamepad with strength from 0.0 to 1.0 for given duration, rising and falling over lengths of the envelope.
+    /// Does nothing if the gamepad was disconnected or doesn't support force feedback.
+    #[cfg(feature = "gamepad")]
+    pub fn rumble(&mut self, gamepad: GamepadId, strength: f32, duration: Duration, envelope: RumbleEnvelope) {
+        self.gamepads.rumble(gamepad, strength, duration, envelope);
+    }
+
+    /// Rumbles every connected gamepad with strength from 0.0 to 1.0 for given duration. Gamepads without force feedback are skipped.
+    #[cfg(feature = "gamepad")]
+    pub fn rumble_all(&mut self, strength: f32, duration: Duration) {
+        self.gamepads.rumble_all(strength, duration);
+    }
+
+    /// Returns input events of connected gamepads since the last call.
+    #[cfg(feature = "gamepad")]
+    pub fn fetch_gamepad_events(&mut self) -> Vec<InputEvent> {
+        self.gamepads.poll_events()
+    }
+
+    /// Returns no events, gamepads are read only with the `gamepad` feature.
+    #[cfg(not(feature = "gamepad"))]
+    pub fn fetch_gamepad_events(&mut self) -> Vec<InputEvent> {
+        Vec::new()
+    }
+
     /// Loads pending events
     pub fn fetch_pending_events(&mut self) -> Vec<Event> {
         let mut events = Vec::new();
 
-        self.events_loop.poll_events(|input_event| {
-            events.push(input_event);
-        });
+        if let Some(events_loop) = &mut self.events_loop {
+            events_loop.poll_events(|input_event| {
+                events.push(input_event);
+            });
+        }
 
         events
     }
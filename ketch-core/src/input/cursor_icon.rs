@@ -0,0 +1,51 @@
+use winit::MouseCursor;
+
+/// Icon of the system cursor shown over the window.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CursorIcon {
+    Arrow,
+    /// Pointing hand, shown over clickable elements.
+    Hand,
+    Crosshair,
+    /// Text cursor, shown over editable text.
+    Text,
+    /// Shown while something is being moved.
+    Move,
+    Grab,
+    Grabbing,
+    Wait,
+    NotAllowed,
+    ResizeHorizontal,
+    ResizeVertical,
+    /// Resizing along the diagonal from the top left to the bottom right corner.
+    ResizeDiagonal,
+    /// Resizing along the diagonal from the bottom left to the top right corner.
+    ResizeAntiDiagonal,
+}
+
+impl CursorIcon {
+    /// Returns cursor of the windowing library.
+    pub(crate) fn mouse_cursor(self) -> MouseCursor {
+        match self {
+            CursorIcon::Arrow => MouseCursor::Default,
+            CursorIcon::Hand => MouseCursor::Hand,
+            CursorIcon::Crosshair => MouseCursor::Crosshair,
+            CursorIcon::Text => MouseCursor::Text,
+            CursorIcon::Move => MouseCursor::Move,
+            CursorIcon::Grab => MouseCursor::Grab,
+            CursorIcon::Grabbing => MouseCursor::Grabbing,
+            CursorIcon::Wait => MouseCursor::Wait,
+            CursorIcon::NotAllowed => MouseCursor::NotAllowed,
+            CursorIcon::ResizeHorizontal => MouseCursor::EwResize,
+            CursorIcon::ResizeVertical => MouseCursor::NsResize,
+            CursorIcon::ResizeDiagonal => MouseCursor::NwseResize,
+            CursorIcon::ResizeAntiDiagonal => MouseCursor::NeswResize,
+        }
+    }
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self {
+        CursorIcon::Arrow
+    }
+}
@@ -0,0 +1,222 @@
+#[cfg(feature = "gamepad")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "gamepad")]
+use gilrs::{Axis, Button, EventType, Gilrs};
+#[cfg(feature = "gamepad")]
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Envelope, Repeat, Replay, Ticks};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gamepad")]
+use crate::input::input_event::{ElementState, InputEvent};
+
+#[cfg(feature = "gamepad")]
+use log::*;
+
+/// Identifier of a connected gamepad, stays the same until it's disconnected.
+#[cfg(feature = "gamepad")]
+pub use gilrs::GamepadId;
+
+/// Button of a gamepad, named by its position on the standard layout rather than its label, which differs between vendors.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadButton {
+    /// Bottom face button, e.g. A on Xbox controllers.
+    South,
+    /// Right face button, e.g. B on Xbox controllers.
+    East,
+    /// Top face button, e.g. Y on Xbox controllers.
+    North,
+    /// Left face button, e.g. X on Xbox controllers.
+    West,
+    LeftBumper,
+    LeftTrigger,
+    RightBumper,
+    RightTrigger,
+    Select,
+    Start,
+    /// Button with the vendor logo.
+    Mode,
+    /// Pressed left stick.
+    LeftStick,
+    /// Pressed right stick.
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadButton {
+    fn from_gilrs(button: Button) -> Option<Self> {
+        match button {
+            Button::South => Some(GamepadButton::South),
+            Button::East => Some(GamepadButton::East),
+            Button::North => Some(GamepadButton::North),
+            Button::West => Some(GamepadButton::West),
+            Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+            Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger),
+            Button::RightTrigger => Some(GamepadButton::RightBumper),
+            Button::RightTrigger2 => Some(GamepadButton::RightTrigger),
+            Button::Select => Some(GamepadButton::Select),
+            Button::Start => Some(GamepadButton::Start),
+            Button::Mode => Some(GamepadButton::Mode),
+            Button::LeftThumb => Some(GamepadButton::LeftStick),
+            Button::RightThumb => Some(GamepadButton::RightStick),
+            Button::DPadUp => Some(GamepadButton::DPadUp),
+            Button::DPadDown => Some(GamepadButton::DPadDown),
+            Button::DPadLeft => Some(GamepadButton::DPadLeft),
+            Button::DPadRight => Some(GamepadButton::DPadRight),
+            _ => None,
+        }
+    }
+}
+
+/// Analog axis of a gamepad, from -1.0 to 1.0. Vertical stick axes are positive up.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    DPadX,
+    DPadY,
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadAxis {
+    fn from_gilrs(axis: Axis) -> Option<Self> {
+        match axis {
+            Axis::LeftStickX => Some(GamepadAxis::LeftStickX),
+            Axis::LeftStickY => Some(GamepadAxis::LeftStickY),
+            Axis::RightStickX => Some(GamepadAxis::RightStickX),
+            Axis::RightStickY => Some(GamepadAxis::RightStickY),
+            Axis::DPadX => Some(GamepadAxis::DPadX),
+            Axis::DPadY => Some(GamepadAxis::DPadY),
+            _ => None,
+        }
+    }
+}
+
+/// Shape of a rumble: its strength rises from zero over `attack` and falls back to zero over `fade` at the end.
+/// The default envelope plays with full strength for the whole duration.
+#[cfg(feature = "gamepad")]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct RumbleEnvelope {
+    pub attack: Duration,
+    pub fade: Duration,
+}
+
+/// Force feedback effect playing on a gamepad, which is stopped once its end has passed.
+#[cfg(feature = "gamepad")]
+struct Rumble {
+    effect: Effect,
+    end: Instant,
+}
+
+/// Reads events of connected gamepads and plays force feedback on them. Input of all gamepads is merged, as if they were a single one.
+#[cfg(feature = "gamepad")]
+pub(crate) struct Gamepads {
+    // None if gamepads aren't supported on the platform
+    gilrs: Option<Gilrs>,
+    rumbles: Vec<Rumble>,
+}
+
+#[cfg(feature = "gamepad")]
+impl Gamepads {
+    pub(crate) fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                warn!("Gamepads aren't available: {}", err);
+                None
+            },
+        };
+        Gamepads {
+            gilrs,
+            rumbles: Vec::new(),
+        }
+    }
+
+    /// Returns ids of connected gamepads.
+    pub(crate) fn connected(&self) -> Vec<GamepadId> {
+        self.gilrs.as_ref().map(|gilrs| gilrs.gamepads().map(|(id, _)| id).collect()).unwrap_or_default()
+    }
+
+    /// Rumbles the gamepad with strength from 0.0 to 1.0 for given duration, shaped by the envelope.
+    /// Envelopes whose attack and fade together aren't shorter than the duration are ignored.
+    /// Does nothing if the gamepad isn't connected or doesn't support force feedback.
+    pub(crate) fn rumble(&mut self, gamepad: GamepadId, strength: f32, duration: Duration, envelope: RumbleEnvelope) {
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+        match gilrs.connected_gamepad(gamepad) {
+            Some(connected) if connected.is_ff_supported() => (),
+            _ => return,
+        }
+        let magnitude = (strength.max(0.0).min(1.0) * f32::from(u16::max_value())) as u16;
+        let play_for = Ticks::from_ms(duration.as_millis() as u32);
+        let attack_length = Ticks::from_ms(envelope.attack.as_millis() as u32);
+        let fade_length = Ticks::from_ms(envelope.fade.as_millis() as u32);
+        // gilrs requires the envelope to fit in the effect
+        let envelope = if attack_length + fade_length < play_for {
+            Envelope { attack_length, attack_level: 0.0, fade_length, fade_level: 0.0 }
+        } else {
+            Envelope::default()
+        };
+        let scheduling = Replay { play_for, ..Default::default() };
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect { kind: BaseEffectType::Strong { magnitude }, scheduling, envelope })
+            .add_effect(BaseEffect { kind: BaseEffectType::Weak { magnitude }, scheduling, envelope })
+            .repeat(Repeat::For(play_for))
+            .gamepads(&[gamepad])
+            .finish(gilrs)
+            .and_then(|effect| effect.play().map(|_| effect));
+        match effect {
+            Ok(effect) => self.rumbles.push(Rumble { effect, end: Instant::now() + duration }),
+            Err(err) => warn!("Gamepad {} can't rumble: {}", gilrs.gamepad(gamepad).name(), err),
+        }
+    }
+
+    /// Rumbles every connected gamepad which supports force feedback, with strength from 0.0 to 1.0 for given duration.
+    /// Every gamepad plays an effect of its own, so a gamepad failing to play it doesn't stop the others.
+    pub(crate) fn rumble_all(&mut self, strength: f32, duration: Duration) {
+        for gamepad in self.connected() {
+            self.rumble(gamepad, strength, duration, RumbleEnvelope::default());
+        }
+    }
+
+    /// Returns input events of gamepads since the last call. Buttons and axes which games can't bind are left out.
+    pub(crate) fn poll_events(&mut self) -> Vec<InputEvent> {
+        let now = Instant::now();
+        self.rumbles.retain(|rumble| {
+            let playing = rumble.end > now;
+            if !playing {
+                if let Err(err) = rumble.effect.stop() {
+                    warn!("Gamepad rumble can't be stopped: {}", err);
+                }
+            }
+            playing
+        });
+        let mut events = Vec::new();
+        if let Some(gilrs) = self.gilrs.as_mut() {
+            while let Some(event) = gilrs.next_event() {
+                let input_event = match event.event {
+                    EventType::ButtonPressed(button, _) => GamepadButton::from_gilrs(button)
+                        .map(|button| InputEvent::GamepadButton { button, state: ElementState::Pressed }),
+                    EventType::ButtonReleased(button, _) => GamepadButton::from_gilrs(button)
+                        .map(|button| InputEvent::GamepadButton { button, state: ElementState::Released }),
+                    EventType::AxisChanged(axis, value, _) => GamepadAxis::from_gilrs(axis).map(|axis| InputEvent::GamepadAxis { axis, value }),
+                    EventType::Connected => {
+                        info!("Gamepad {} connected", gilrs.gamepad(event.id).name());
+                        None
+                    },
+                    _ => None,
+                };
+                events.extend(input_event);
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::gamepad::GamepadButton;
+use crate::input::input_axes::AxisBinding;
+use crate::input::input_bindings_error::InputBindingsError;
+use crate::input::input_event::{ElementState, InputEvent, MouseButton, VirtualKeyCode};
+
+/// Serializable inputs bound to every action and axis, saved by games with their controls settings.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct InputBindings {
+    /// Keys bound to actions.
+    pub actions: BTreeMap<String, Vec<VirtualKeyCode>>,
+    #[serde(default)]
+    pub mouse_buttons: BTreeMap<String, Vec<MouseButton>>,
+    #[serde(default)]
+    pub gamepad_buttons: BTreeMap<String, Vec<GamepadButton>>,
+    #[serde(default)]
+    pub axes: BTreeMap<String, AxisBinding>,
+}
+
+impl InputBindings {
+    /// Loads bindings from a RON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputBindingsError> {
+        let serialized_bindings = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized_bindings)?)
+    }
+
+    /// Saves bindings to a RON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), InputBindingsError> {
+        let serialized_bindings = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, serialized_bindings)?;
+        Ok(())
+    }
+}
+
+/// Key bound to more than one action.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BindingConflict {
+    pub keycode: VirtualKeyCode,
+    /// Names of actions bound to the key, sorted by name.
+    pub actions: Vec<String>,
+}
+
+/// Maps keys, mouse buttons and gamepad buttons to named actions, so that game logic doesn't depend on specific inputs.
+/// Action can be bound to many inputs and is pressed while any of them is held.
+pub struct InputActions {
+    bindings: HashMap<String, Vec<VirtualKeyCode>>,
+    mouse_bindings: HashMap<String, Vec<MouseButton>>,
+    gamepad_bindings: HashMap<String, Vec<GamepadButton>>,
+    pressed_keys: HashSet<VirtualKeyCode>,
+    pressed_mouse_buttons: HashSet<MouseButton>,
+    pressed_gamepad_buttons: HashSet<GamepadButton>,
+    // action which the next pressed key is bound to
+    capturing_action: Option<String>,
+    captured_binding: Option<(String, VirtualKeyCode)>,
+}
+
+impl InputActions {
+    /// Creates action map without any bindings.
+    pub fn new() -> Self {
+        InputActions {
+            bindings: HashMap::new(),
+            mouse_bindings: HashMap::new(),
+            gamepad_bindings: HashMap::new(),
+            pressed_keys: HashSet::new(),
+            pressed_mouse_buttons: HashSet::new(),
+            pressed_gamepad_buttons: HashSet::new(),
+            capturing_action: None,
+            captured_binding: None,
+        }
+    }
+
+    /// Binds key to the action. Key can be bound to many actions.
+    pub fn bind<S: Into<String>>(&mut self, action: S, keycode: VirtualKeyCode) {
+        let keys = self.bindings.entry(action.into()).or_insert_with(Vec::new);
+        if !keys.contains(&keycode) {
+            keys.push(keycode);
+        }
+    }
+
+    /// Binds mouse button to the action.
+    pub fn bind_mouse_button<S: Into<String>>(&mut self, action: S, button: MouseButton) {
+        let buttons = self.mouse_bindings.entry(action.into()).or_insert_with(Vec::new);
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Binds button of any gamepad to the action.
+    pub fn bind_gamepad_button<S: Into<String>>(&mut self, action: S, button: GamepadButton) {
+        let buttons = self.gamepad_bindings.entry(action.into()).or_insert_with(Vec::new);
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Removes all keys and buttons bound to the action.
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+        self.mouse_bindings.remove(action);
+        self.gamepad_bindings.remove(action);
+    }
+
+    /// Returns keys bound to the action.
+    pub fn keys(&self, action: &str) -> &[VirtualKeyCode] {
+        self.bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns mouse buttons bound to the action.
+    pub fn mouse_buttons(&self, action: &str) -> &[MouseButton] {
+        self.mouse_bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns gamepad buttons bound to the action.
+    pub fn gamepad_buttons(&self, action: &str) -> &[GamepadButton] {
+        self.gamepad_bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns names of all actions with bound keys or buttons, sorted by name.
+    pub fn actions(&self) -> Vec<&str> {
+        let mut actions: Vec<&str> = self.bindings.keys()
+                                                  .chain(self.mouse_bindings.keys())
+                                                  .chain(self.gamepad_bindings.keys())
+                                                  .map(String::as_str)
+                                                  .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+
+    /// Returns names of actions bound to the key, sorted by name.
+    pub fn actions_bound_to(&self, keycode: VirtualKeyCode) -> Vec<&str> {
+        let mut actions: Vec<&str> = self.bindings.iter()
+                                                  .filter(|(_, keys)| keys.contains(&keycode))
+                                                  .map(|(action, _)| action.as_str())
+                                                  .collect();
+        actions.sort();
+        actions
+    }
+
+    /// Returns keys bound to more than one action, which controls screens can warn about.
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut keys: Vec<VirtualKeyCode> = self.bindings.values().flatten().cloned().collect();
+        keys.sort_by_key(|&keycode| keycode as u32);
+        keys.dedup();
+        keys.into_iter().filter_map(|keycode| {
+            let actions = self.actions_bound_to(keycode);
+            if actions.len() > 1 {
+                Some(BindingConflict { keycode, actions: actions.into_iter().map(String::from).collect() })
+            } else {
+                None
+            }
+        }).collect()
+    }
+
+    /// Returns serializable copy of all bindings. Axes are left empty, they're bound by `InputAxes`.
+    pub fn bindings(&self) -> InputBindings {
+        InputBindings {
+            actions: self.bindings.iter().map(|(action, keys)| (action.clone(), keys.clone())).collect(),
+            mouse_buttons: self.mouse_bindings.iter().map(|(action, buttons)| (action.clone(), buttons.clone())).collect(),
+            gamepad_buttons: self.gamepad_bindings.iter().map(|(action, buttons)| (action.clone(), buttons.clone())).collect(),
+            axes: BTreeMap::new(),
+        }
+    }
+
+    /// Replaces all bindings of actions, e.g. with ones loaded from controls settings. Pressed keys and buttons are released.
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.bindings = bindings.actions.into_iter().collect();
+        self.mouse_bindings = bindings.mouse_buttons.into_iter().collect();
+        self.gamepad_bindings = bindings.gamepad_buttons.into_iter().collect();
+        self.release_all();
+    }
+
+    /// Starts listening for the next pressed key, which is bound to the action instead of pressing actions.
+    /// Listening for another action cancels the previous one.
+    pub fn capture_next_key<S: Into<String>>(&mut self, action: S) {
+        self.capturing_action = Some(action.into());
+    }
+
+    /// Stops listening for the next pressed key without binding it.
+    pub fn cancel_capture(&mut self) {
+        self.capturing_action = None;
+    }
+
+    /// Returns action which the next pressed key will be bound to.
+    pub fn capturing_action(&self) -> Option<&str> {
+        self.capturing_action.as_ref().map(String::as_str)
+    }
+
+    /// Returns action and key bound by the last capture, once.
+    pub fn take_captured_binding(&mut self) -> Option<(String, VirtualKeyCode)> {
+        self.captured_binding.take()
+    }
+
+    /// Updates state of pressed keys and buttons, or binds pressed key to the action if a key is being captured.
+    pub fn handle_event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::KeyboardInput { state, keycode } => {
+                if let (ElementState::Pressed, Some(action)) = (state, self.capturing_action.take()) {
+                    self.bind(action.clone(), *keycode);
+                    self.captured_binding = Some((action, *keycode));
+                    return;
+                }
+                update_pressed(&mut self.pressed_keys, *keycode, *state);
+            },
+            InputEvent::MouseInput { button, state } => update_pressed(&mut self.pressed_mouse_buttons, *button, *state),
+            InputEvent::GamepadButton { button, state } => update_pressed(&mut self.pressed_gamepad_buttons, *button, *state),
+            _ => (),
+        }
+    }
+
+    /// Releases all keys and buttons, used when game stops receiving input, so that no action stays pressed.
+    pub fn release_all(&mut self) {
+        self.pressed_keys.clear();
+        self.pressed_mouse_buttons.clear();
+        self.pressed_gamepad_buttons.clear();
+    }
+
+    /// Returns true if any key or button bound to the action is pressed.
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.keys(action).iter().any(|keycode| self.pressed_keys.contains(keycode)) ||
+            self.mouse_buttons(action).iter().any(|button| self.pressed_mouse_buttons.contains(button)) ||
+            self.gamepad_buttons(action).iter().any(|button| self.pressed_gamepad_buttons.contains(button))
+    }
+
+    /// Returns value from -1.0 to 1.0 of an axis controlled by two actions.
+    pub fn axis(&self, negative_action: &str, positive_action: &str) -> f32 {
+        let value = |action| if self.is_pressed(action) { 1.0 } else { 0.0 };
+        value(positive_action) - value(negative_action)
+    }
+}
+
+fn update_pressed<T: Eq + std::hash::Hash>(pressed: &mut HashSet<T>, input: T, state: ElementState) {
+    match state {
+        ElementState::Pressed => pressed.insert(input),
+        ElementState::Released => pressed.remove(&input),
+    };
+}
+
+impl Default for InputActions {
+    fn default() -> Self {
+        InputActions::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_is_pressed_while_any_bound_key_is_held() {
+        let mut actions = InputActions::new();
+        actions.bind("jump", VirtualKeyCode::Space);
+        actions.bind("jump", VirtualKeyCode::Up);
+
+        actions.handle_event(&InputEvent::KeyboardInput { state: ElementState::Pressed, keycode: VirtualKeyCode::Space });
+        actions.handle_event(&InputEvent::KeyboardInput { state: ElementState::Pressed, keycode: VirtualKeyCode::Up });
+        actions.handle_event(&InputEvent::KeyboardInput { state: ElementState::Released, keycode: VirtualKeyCode::Space });
+
+        assert!(actions.is_pressed("jump"));
+        assert!(!actions.is_pressed("crouch"));
+    }
+
+    #[test]
+    fn action_is_pressed_by_mouse_and_gamepad_buttons() {
+        let mut actions = InputActions::new();
+        actions.bind("fire", VirtualKeyCode::LControl);
+        actions.bind_mouse_button("fire", MouseButton::Left);
+        actions.bind_gamepad_button("fire", GamepadButton::RightTrigger);
+
+        actions.handle_event(&InputEvent::GamepadButton { button: GamepadButton::RightTrigger, state: ElementState::Pressed });
+        assert!(actions.is_pressed("fire"));
+        actions.handle_event(&InputEvent::GamepadButton { button: GamepadButton::RightTrigger, state: ElementState::Released });
+        actions.handle_event(&InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed });
+        assert!(actions.is_pressed("fire"));
+        actions.release_all();
+        assert!(!actions.is_pressed("fire"));
+
+        let mut restored = InputActions::new();
+        restored.set_bindings(actions.bindings());
+        assert_eq!(&[GamepadButton::RightTrigger], restored.gamepad_buttons("fire"));
+        assert_eq!(vec!["fire"], restored.actions());
+    }
+
+    #[test]
+    fn captured_key_is_bound_and_reported_as_conflict() {
+        let mut actions = InputActions::new();
+        actions.bind("jump", VirtualKeyCode::Space);
+        actions.bind("crouch", VirtualKeyCode::C);
+
+        actions.capture_next_key("crouch");
+        actions.handle_event(&InputEvent::KeyboardInput { state: ElementState::Pressed, keycode: VirtualKeyCode::Space });
+
+        assert!(!actions.is_pressed("jump"));
+        assert_eq!(None, actions.capturing_action());
+        assert_eq!(Some(("crouch".to_string(), VirtualKeyCode::Space)), actions.take_captured_binding());
+        assert_eq!(vec!["crouch", "jump"], actions.actions());
+        assert_eq!(vec![BindingConflict { keycode: VirtualKeyCode::Space, actions: vec!["crouch".to_string(), "jump".to_string()] }], actions.conflicts());
+
+        let mut restored = InputActions::new();
+        restored.set_bindings(actions.bindings());
+        assert_eq!(&[VirtualKeyCode::C, VirtualKeyCode::Space], restored.keys("crouch"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::gamepad::GamepadAxis;
+use crate::input::input_actions::InputActions;
+use crate::input::input_event::{AxisId, InputEvent};
+
+/// Input an axis reads its value from.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum AxisSource {
+    /// Horizontal mouse movement in pixels since the last update.
+    MouseX,
+    /// Vertical mouse movement in pixels since the last update.
+    MouseY,
+    /// Last value of a device axis, such as a gamepad stick.
+    Device(AxisId),
+    /// Last value of an axis of any connected gamepad.
+    Gamepad(GamepadAxis),
+    /// Digital axis controlled by two actions, from -1.0 to 1.0.
+    Actions { negative: String, positive: String },
+}
+
+/// Shape of the deadzone ignoring small values, such as drift of a gamepad stick.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum DeadzoneShape {
+    /// Each axis has its own deadzone, which snaps diagonal movement to the axes.
+    Axial,
+    /// Deadzone is measured on the stick formed with the named axis, which keeps the direction of diagonal movement.
+    Radial(String),
+}
+
+/// Curve applied to axis values after the deadzone, which makes small movements more precise.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ResponseCurve {
+    Linear,
+    Quadratic,
+    Cubic,
+}
+
+impl ResponseCurve {
+    /// Applies curve to the value, keeping its sign.
+    pub fn apply(self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        let curved = match self {
+            ResponseCurve::Linear => magnitude,
+            ResponseCurve::Quadratic => magnitude * magnitude,
+            ResponseCurve::Cubic => magnitude * magnitude * magnitude,
+        };
+        curved.copysign(value)
+    }
+}
+
+/// Filters applied to an axis, in order: deadzone, response curve, sensitivity and smoothing.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct AxisFilter {
+    /// Values smaller than the deadzone are ignored, and larger ones are remapped to start from zero.
+    pub deadzone: f32,
+    pub deadzone_shape: DeadzoneShape,
+    pub response_curve: ResponseCurve,
+    /// Factor the value is multiplied by.
+    pub sensitivity: f32,
+    /// Time in seconds the value needs to move most of the way to a new input. Zero disables smoothing.
+    /// Smoothing is frame rate independent, so the same input gives the same value at any frame rate.
+    pub smoothing: f32,
+}
+
+impl Default for AxisFilter {
+    fn default() -> Self {
+        AxisFilter {
+            deadzone: 0.0,
+            deadzone_shape: DeadzoneShape::Axial,
+            response_curve: ResponseCurve::Linear,
+            sensitivity: 1.0,
+            smoothing: 0.0,
+        }
+    }
+}
+
+/// Serializable source and filter of an axis, saved with the controls settings.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AxisBinding {
+    pub source: AxisSource,
+    #[serde(default)]
+    pub filter: AxisFilter,
+}
+
+struct Axis {
+    source: AxisSource,
+    filter: AxisFilter,
+    raw_value: f32,
+    value: f32,
+}
+
+/// Named analog axes read from the mouse, devices or actions, filtered before they're delivered to the game.
+pub struct InputAxes {
+    axes: HashMap<String, Axis>,
+}
+
+impl InputAxes {
+    /// Creates axes without any bindings.
+    pub fn new() -> Self {
+        InputAxes {
+            axes: HashMap::new(),
+        }
+    }
+
+    /// Binds axis to the source, replacing previous binding of the axis.
+    pub fn bind<S: Into<String>>(&mut self, axis: S, source: AxisSource, filter: AxisFilter) {
+        self.axes.insert(axis.into(), Axis { source, filter, raw_value: 0.0, value: 0.0 });
+    }
+
+    /// Removes binding of the axis.
+    pub fn unbind(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    /// Returns a mutable reference to filter of the axis, which can be changed by the options screen.
+    pub fn filter_mut(&mut self, axis: &str) -> Option<&mut AxisFilter> {
+        self.axes.get_mut(axis).map(|axis| &mut axis.filter)
+    }
+
+    /// Returns serializable copy of bindings of all axes.
+    pub fn bindings(&self) -> BTreeMap<String, AxisBinding> {
+        self.axes.iter().map(|(name, axis)| (name.clone(), AxisBinding { source: axis.source.clone(), filter: axis.filter.clone() })).collect()
+    }
+
+    /// Replaces bindings of all axes, e.g. with ones loaded from controls settings.
+    pub fn set_bindings(&mut self, bindings: BTreeMap<String, AxisBinding>) {
+        self.axes.clear();
+        for (name, binding) in bindings {
+            self.bind(name, binding.source, binding.filter);
+        }
+    }
+
+    /// Returns filtered value of the axis, or zero if it isn't bound.
+    pub fn value(&self, axis: &str) -> f32 {
+        self.axes.get(axis).map(|axis| axis.value).unwrap_or(0.0)
+    }
+
+    /// Updates raw values of axes with the input event. Mouse movement is accumulated until the next update.
+    pub fn handle_event(&mut self, event: &InputEvent) {
+        for axis in self.axes.values_mut() {
+            match (&axis.source, event) {
+                (AxisSource::MouseX, InputEvent::MouseMotion { delta }) => axis.raw_value += delta.0 as f32,
+                (AxisSource::MouseY, InputEvent::MouseMotion { delta }) => axis.raw_value += delta.1 as f32,
+                (AxisSource::Device(source_axis), InputEvent::Motion { axis: event_axis, value }) if source_axis == event_axis => {
+                    axis.raw_value = *value as f32
+                },
+                (AxisSource::Gamepad(source_axis), InputEvent::GamepadAxis { axis: event_axis, value }) if source_axis == event_axis => {
+                    axis.raw_value = *value
+                },
+                _ => (),
+            }
+        }
+    }
+
+    /// Filters raw values into values read by the game, given time since the previous update in seconds.
+    pub fn update(&mut self, actions: &InputActions, delta_time: f32) {
+        for axis in self.axes.values_mut() {
+            if let AxisSource::Actions { negative, positive } = &axis.source {
+                axis.raw_value = actions.axis(negative, positive);
+            }
+        }
+        let raw_values: HashMap<String, f32> = self.axes.iter().map(|(name, axis)| (name.clone(), axis.raw_value)).collect();
+
+        for axis in self.axes.values_mut() {
+            let filter = &axis.filter;
+            let magnitude = match &filter.deadzone_shape {
+                DeadzoneShape::Axial => axis.raw_value.abs(),
+                DeadzoneShape::Radial(paired_axis) => {
+                    let paired_value = raw_values.get(paired_axis).cloned().unwrap_or(0.0);
+                    (axis.raw_value * axis.raw_value + paired_value * paired_value).sqrt()
+                },
+            };
+            let value = if magnitude <= filter.deadzone {
+                0.0
+            } else {
+                let remapped_magnitude = (magnitude - filter.deadzone) / (1.0 - filter.deadzone).max(std::f32::EPSILON);
+                axis.raw_value / magnitude * remapped_magnitude
+            };
+            let target = filter.response_curve.apply(value) * filter.sensitivity;
+
+            axis.value = if filter.smoothing > 0.0 {
+                axis.value + (target - axis.value) * (1.0 - (-delta_time.max(0.0) / filter.smoothing).exp())
+            } else {
+                target
+            };
+            if let AxisSource::MouseX | AxisSource::MouseY = axis.source {
+                axis.raw_value = 0.0;
+            }
+        }
+    }
+}
+
+impl Default for InputAxes {
+    fn default() -> Self {
+        InputAxes::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radial_deadzone_keeps_direction() {
+        let mut axes = InputAxes::new();
+        let filter = |paired_axis: &str| AxisFilter { deadzone: 0.2, deadzone_shape: DeadzoneShape::Radial(paired_axis.to_string()), ..AxisFilter::default() };
+        axes.bind("look x", AxisSource::Device(0), filter("look y"));
+        axes.bind("look y", AxisSource::Device(1), filter("look x"));
+
+        axes.handle_event(&InputEvent::Motion { axis: 0, value: 0.1 });
+        axes.handle_event(&InputEvent::Motion { axis: 1, value: 0.1 });
+        axes.update(&InputActions::new(), 0.016);
+        assert_eq!(0.0, axes.value("look x"));
+
+        axes.handle_event(&InputEvent::Motion { axis: 0, value: 0.6 });
+        axes.handle_event(&InputEvent::Motion { axis: 1, value: 0.6 });
+        axes.update(&InputActions::new(), 0.016);
+        assert!(axes.value("look x") > 0.0);
+        assert_eq!(axes.value("look x"), axes.value("look y"));
+    }
+
+    #[test]
+    fn smoothing_is_frame_rate_independent() {
+        let smoothed_axes = || {
+            let mut axes = InputAxes::new();
+            axes.bind("turn", AxisSource::Device(0), AxisFilter { smoothing: 0.1, ..AxisFilter::default() });
+            axes.handle_event(&InputEvent::Motion { axis: 0, value: 1.0 });
+            axes
+        };
+        let actions = InputActions::new();
+        let mut slow = smoothed_axes();
+        let mut fast = smoothed_axes();
+
+        slow.update(&actions, 0.04);
+        for _ in 0..4 {
+            fast.update(&actions, 0.01);
+        }
+
+        assert!((slow.value("turn") - fast.value("turn")).abs() < 0.0001);
+        assert!(slow.value("turn") > 0.0 && slow.value("turn") < 1.0);
+    }
+}
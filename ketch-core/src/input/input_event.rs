@@ -3,6 +3,8 @@ pub use winit::dpi::LogicalPosition;
 pub use winit::WindowEvent;
 pub use winit::DeviceEvent;
 
+use crate::input::gamepad::{GamepadAxis, GamepadButton};
+
 /// Enum containing input events
 pub enum InputEvent {
     KeyboardInput { state: ElementState, keycode: VirtualKeyCode },
@@ -12,6 +14,10 @@ pub enum InputEvent {
     MouseInput { button: MouseButton, state: ElementState },
     Motion { axis: AxisId, value: f64 },
     Button { button: ButtonId, state: ElementState },
+    /// Button of any connected gamepad.
+    GamepadButton { button: GamepadButton, state: ElementState },
+    /// Axis of any connected gamepad.
+    GamepadAxis { axis: GamepadAxis, value: f32 },
 }
 
 /// Changes winit Events to InputEvents
@@ -0,0 +1,114 @@
+use crate::input::input_event::InputEvent;
+
+/// Priority of the in-game UI, which consumes clicks on its buttons.
+pub const UI_PRIORITY: i32 = 100;
+
+/// Consumer of input events, such as a pause menu or a dialog, which can hide events from layers below it.
+pub trait InputLayer {
+    /// Handles input event. Returns true if the event was consumed and shouldn't reach layers with lower priority.
+    fn handle_input(&mut self, event: &InputEvent) -> bool;
+}
+
+struct LayerEntry {
+    name: String,
+    priority: i32,
+    layer: Box<InputLayer>,
+}
+
+/// Passes input events through layers registered by the game, from the highest priority to the lowest.
+/// Layers above `UI_PRIORITY` see events before in-game UI, other layers only see events UI didn't consume,
+/// and gameplay receives events no layer consumed. While the GUI editor is editing, it consumes all input before any layer.
+pub struct InputRouter {
+    /// Layers sorted from the highest priority, layers with equal priority keep order in which they were added.
+    layers: Vec<LayerEntry>,
+}
+
+impl InputRouter {
+    /// Creates router without any layers.
+    pub fn new() -> Self {
+        InputRouter {
+            layers: Vec::new(),
+        }
+    }
+
+    /// Adds layer with given priority. Layer with the same name is replaced.
+    pub fn add_layer<S: Into<String>>(&mut self, name: S, priority: i32, layer: Box<InputLayer>) {
+        let name = name.into();
+        self.remove_layer(&name);
+        let index = self.layers.iter().position(|entry| entry.priority < priority).unwrap_or_else(|| self.layers.len());
+        self.layers.insert(index, LayerEntry { name, priority, layer });
+    }
+
+    /// Removes and returns layer with given name.
+    pub fn remove_layer(&mut self, name: &str) -> Option<Box<InputLayer>> {
+        let index = self.layers.iter().position(|entry| entry.name == name)?;
+        Some(self.layers.remove(index).layer)
+    }
+
+    /// Returns a mutable reference to the layer with given name.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Box<InputLayer>> {
+        self.layers.iter_mut().find(|entry| entry.name == name).map(|entry| &mut entry.layer)
+    }
+
+    /// Passes events through layers with priority in given range, highest first, and returns events none of them consumed.
+    pub fn route(&mut self, events: Vec<InputEvent>, min_priority: i32, max_priority: i32) -> Vec<InputEvent> {
+        events.into_iter().filter(|event| {
+            !self.layers.iter_mut()
+                        .filter(|entry| entry.priority >= min_priority && entry.priority <= max_priority)
+                        .any(|entry| entry.layer.handle_input(event))
+        }).collect()
+    }
+}
+
+impl Default for InputRouter {
+    fn default() -> Self {
+        InputRouter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::input::input_event::{ElementState, MouseButton, MouseScrollDelta};
+
+    /// Layer recording names of layers which saw events, consuming mouse input if `consume` is set.
+    struct RecordingLayer {
+        name: &'static str,
+        consume: bool,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl InputLayer for RecordingLayer {
+        fn handle_input(&mut self, event: &InputEvent) -> bool {
+            self.log.borrow_mut().push(self.name);
+            match event {
+                InputEvent::MouseInput { .. } => self.consume,
+                _ => false,
+            }
+        }
+    }
+
+    #[test]
+    fn higher_layers_consume_events_first() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let layer = |name, consume| Box::new(RecordingLayer { name, consume, log: log.clone() });
+        let mut router = InputRouter::new();
+        router.add_layer("gameplay overlay", 0, layer("gameplay overlay", false));
+        router.add_layer("dialog", UI_PRIORITY + 1, layer("dialog", true));
+        router.add_layer("pause menu", UI_PRIORITY + 1, layer("pause menu", true));
+
+        let click = InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed };
+        let remaining = router.route(vec![click, InputEvent::CursorMoved { x: 1.0, y: 2.0 }], std::i32::MIN, std::i32::MAX);
+
+        assert_eq!(1, remaining.len());
+        assert_eq!(vec!["dialog", "dialog", "pause menu", "gameplay overlay"], *log.borrow());
+
+        log.borrow_mut().clear();
+        router.route(vec![InputEvent::MouseWheel(MouseScrollDelta::LineDelta(0.0, 1.0))], std::i32::MIN, UI_PRIORITY);
+        assert_eq!(vec!["gameplay overlay"], *log.borrow());
+        assert!(router.remove_layer("dialog").is_some());
+        assert!(router.layer_mut("dialog").is_none());
+    }
+}
@@ -1,4 +1,49 @@
+pub mod collision;
+pub mod event_bus;
+#[cfg(feature = "window")]
 pub mod input;
+pub mod localization;
+pub mod logging;
+pub mod math;
+pub mod navigation;
+pub mod particles;
+pub mod pause;
+pub mod profiling;
+pub mod random;
 pub mod renderer;
 pub mod resource;
-pub mod settings;
\ No newline at end of file
+pub mod save;
+pub mod settings;
+pub mod tween;
+pub mod ui;
+pub mod video;
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "vulkan")]
+    use crate::renderer::Renderer;
+    #[cfg(feature = "vulkan")]
+    use crate::renderer::shader::ShaderSet;
+    use crate::renderer::upload::AssetUploader;
+    use crate::resource::AssetManager;
+    #[cfg(feature = "vulkan")]
+    use crate::settings::Settings;
+    use crate::tween::TweenSystem;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn shared_engine_state_can_be_used_from_other_threads() {
+        assert_send_sync::<AssetUploader>();
+        assert_send_sync::<AssetManager>();
+        assert_send_sync::<TweenSystem>();
+    }
+
+    #[cfg(feature = "vulkan")]
+    #[test]
+    fn vulkan_renderer_state_can_be_used_from_other_threads() {
+        assert_send_sync::<Settings>();
+        assert_send_sync::<ShaderSet>();
+        assert_send_sync::<Renderer>();
+    }
+}
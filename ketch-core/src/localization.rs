@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use log::*;
+
+use crate::resource::string_table::StringTable;
+
+/// Translates strings to the current language using string tables.
+/// Strings missing from the current language are taken from the fallback language, or replaced by their keys.
+pub struct Localization {
+    tables: HashMap<String, StringTable>,
+    language: Option<String>,
+    fallback_language: Option<String>,
+    /// Fonts used instead of other fonts for a language, e.g. fonts with glyphs of its alphabet.
+    font_overrides: HashMap<(String, String), String>,
+    language_changed: bool,
+}
+
+impl Localization {
+    /// Creates localization without any languages.
+    pub fn new() -> Self {
+        Localization {
+            tables: HashMap::new(),
+            language: None,
+            fallback_language: None,
+            font_overrides: HashMap::new(),
+            language_changed: false,
+        }
+    }
+
+    /// Adds string table. Strings of a language which already has a table are merged into it.
+    /// The first language added becomes the current and the fallback language.
+    pub fn add_table(&mut self, table: StringTable) {
+        let language = table.language().to_string();
+        if self.language.is_none() {
+            self.language = Some(language.clone());
+        }
+        if self.fallback_language.is_none() {
+            self.fallback_language = Some(language.clone());
+        }
+        match self.tables.get_mut(&language) {
+            Some(existing) => existing.merge(table),
+            None => {
+                self.tables.insert(language, table);
+            },
+        }
+    }
+
+    /// Returns languages with string tables, sorted.
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.tables.keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Returns the current language.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Switches to the language. Returns false and keeps the current language if there's no string table for it.
+    pub fn set_language(&mut self, language: &str) -> bool {
+        if !self.tables.contains_key(language) {
+            warn!("There's no string table for language {}", language);
+            return false;
+        }
+        if self.language() != Some(language) {
+            self.language = Some(language.to_string());
+            self.language_changed = true;
+        }
+        true
+    }
+
+    /// Sets language used for strings missing from the current language.
+    pub fn set_fallback_language<S: Into<String>>(&mut self, language: S) {
+        self.fallback_language = Some(language.into());
+    }
+
+    /// Returns the language if it was changed since the last call, used by the engine to publish language change events.
+    pub fn take_language_change(&mut self) -> Option<String> {
+        if std::mem::replace(&mut self.language_changed, false) {
+            self.language.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Returns string for the key in the current language.
+    pub fn tr(&self, key: &str) -> String {
+        self.lookup(key).to_string()
+    }
+
+    /// Returns string for the key in the current language, with `{name}` placeholders replaced by values of the arguments.
+    pub fn tr_args(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut string = self.lookup(key).to_string();
+        for (name, value) in args {
+            string = string.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        string
+    }
+
+    /// Makes text drawn with the font use another font while the language is current.
+    pub fn set_font_override<L: Into<String>, F: Into<String>, O: Into<String>>(&mut self, language: L, font: F, override_font: O) {
+        self.font_overrides.insert((language.into(), font.into()), override_font.into());
+    }
+
+    /// Returns name of the font which should be used instead of the font in the current language.
+    /// Used by the text renderer to draw text in alphabets missing from the font.
+    pub fn font<'a>(&'a self, font: &'a str) -> &'a str {
+        match &self.language {
+            Some(language) => self.font_overrides.get(&(language.clone(), font.to_string())).map(String::as_str).unwrap_or(font),
+            None => font,
+        }
+    }
+
+    fn lookup<'a>(&'a self, key: &'a str) -> &'a str {
+        for language in self.language.iter().chain(self.fallback_language.iter()) {
+            if let Some(string) = self.tables.get(language).and_then(|table| table.get(key)) {
+                return string;
+            }
+        }
+        debug!("Missing translation of {}", key);
+        key
+    }
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Localization::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_strings_fall_back_to_the_first_language() {
+        let mut english = StringTable::new("en");
+        english.insert("greeting", "Hello, {name}!");
+        english.insert("quit", "Quit");
+        let mut polish = StringTable::new("pl");
+        polish.insert("greeting", "Cześć, {name}!");
+        let mut localization = Localization::new();
+        localization.add_table(english);
+        localization.add_table(polish);
+
+        assert!(localization.set_language("pl"));
+
+        let name: &Display = &"Ania";
+        assert_eq!("Cześć, Ania!", localization.tr_args("greeting", &[("name", name)]));
+        assert_eq!("Quit", localization.tr("quit"));
+        assert_eq!("missing", localization.tr("missing"));
+        assert_eq!(Some(String::from("pl")), localization.take_language_change());
+        assert_eq!(None, localization.take_language_change());
+    }
+}
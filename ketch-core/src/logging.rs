@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::logging::logging_error::LoggingError;
+#[cfg(feature = "vulkan")]
+use crate::settings::Settings;
+
+pub mod logging_error;
+
+/// Number of records kept in memory for the editor console, older records are dropped.
+pub const CAPTURED_RECORDS: usize = 1000;
+
+/// Log record kept in memory.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogRecord {
+    pub level: Level,
+    /// Module which logged the record, e.g. `ketch_core::renderer`.
+    pub target: String,
+    pub message: String,
+}
+
+struct CapturedRecords {
+    records: VecDeque<LogRecord>,
+    total: u64,
+}
+
+/// Latest records logged by the engine logger, shared with the editor console. Clones share the same records.
+#[derive(Clone)]
+pub struct LogCapture {
+    captured: Arc<Mutex<CapturedRecords>>,
+    capacity: usize,
+}
+
+impl LogCapture {
+    /// Creates capture keeping given number of latest records.
+    pub fn new(capacity: usize) -> Self {
+        LogCapture {
+            captured: Arc::new(Mutex::new(CapturedRecords { records: VecDeque::new(), total: 0 })),
+            capacity,
+        }
+    }
+
+    /// Returns kept records, from the oldest.
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.captured.lock().unwrap().records.iter().cloned().collect()
+    }
+
+    /// Returns number of records captured since the capture was created, including dropped and cleared ones.
+    /// Used to check if there are new records without copying them.
+    pub fn total(&self) -> u64 {
+        self.captured.lock().unwrap().total
+    }
+
+    /// Removes kept records.
+    pub fn clear(&self) {
+        self.captured.lock().unwrap().records.clear();
+    }
+
+    fn push(&self, record: LogRecord) {
+        let mut captured = self.captured.lock().unwrap();
+        if captured.records.len() >= self.capacity {
+            captured.records.pop_front();
+        }
+        captured.records.push_back(record);
+        captured.total += 1;
+    }
+}
+
+/// Logger writing records to stderr, optionally to a file, and to in-memory capture.
+struct EngineLogger {
+    level: LevelFilter,
+    module_levels: Vec<(String, LevelFilter)>,
+    file: Option<Mutex<File>>,
+    capture: LogCapture,
+}
+
+impl Log for EngineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= module_level(self.level, &self.module_levels, metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+        let line = format!("[{} {}] {}", record.level(), record.target(), message);
+        eprintln!("{}", line);
+        if let Some(file) = &self.file {
+            // logging can't report its own errors, so failed writes are ignored
+            let _ = writeln!(file.lock().unwrap(), "{}", line);
+        }
+        self.capture.push(LogRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message,
+        });
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// Returns level of the most specific module matching the target, or the default level if no module matches.
+/// A module matches targets equal to it and targets of its submodules.
+fn module_level(default: LevelFilter, module_levels: &[(String, LevelFilter)], target: &str) -> LevelFilter {
+    module_levels.iter()
+                 .filter(|(module, _)| target == module || (target.starts_with(module.as_str()) && target[module.len()..].starts_with("::")))
+                 .max_by_key(|(module, _)| module.len())
+                 .map(|(_, level)| *level)
+                 .unwrap_or(default)
+}
+
+/// Sets up the engine logger with levels and log file from settings, and returns capture of logged records.
+/// Log file is appended to. Fails if the file can't be opened or if another logger was already set up.
+#[cfg(feature = "vulkan")]
+pub fn init(settings: &Settings) -> Result<LogCapture, LoggingError> {
+    init_with_levels(settings.log_level(), settings.module_log_levels(), settings.log_file())
+}
+
+/// Sets up the engine logger with given default level, levels of modules and optional log file, for games without engine settings.
+pub fn init_with_levels(level: LevelFilter, module_levels: &[(String, LevelFilter)], log_file: Option<&Path>) -> Result<LogCapture, LoggingError> {
+    let file = match log_file {
+        Some(path) => Some(Mutex::new(OpenOptions::new().create(true).append(true).open(path)?)),
+        None => None,
+    };
+    let capture = LogCapture::new(CAPTURED_RECORDS);
+    let logger = EngineLogger {
+        level,
+        module_levels: module_levels.to_vec(),
+        file,
+        capture: capture.clone(),
+    };
+    let max_level = module_levels.iter().map(|(_, level)| *level).fold(level, std::cmp::max);
+    // logger lives until the program ends, so it's leaked instead of stored in a static
+    log::set_logger(Box::leak(Box::new(logger))).map_err(|_| LoggingError::LoggerAlreadySet)?;
+    log::set_max_level(max_level);
+    Ok(capture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_module_level_is_used() {
+        let module_levels = vec![
+            ("ketch_core".to_string(), LevelFilter::Warn),
+            ("ketch_core::renderer".to_string(), LevelFilter::Debug),
+        ];
+
+        assert_eq!(LevelFilter::Debug, module_level(LevelFilter::Info, &module_levels, "ketch_core::renderer::upload"));
+        assert_eq!(LevelFilter::Warn, module_level(LevelFilter::Info, &module_levels, "ketch_core::resource"));
+        assert_eq!(LevelFilter::Info, module_level(LevelFilter::Info, &module_levels, "ketch_core_extras"));
+        assert_eq!(LevelFilter::Info, module_level(LevelFilter::Info, &module_levels, "example"));
+    }
+
+    #[test]
+    fn capture_keeps_latest_records() {
+        let capture = LogCapture::new(2);
+        for message in &["first", "second", "third"] {
+            capture.push(LogRecord { level: Level::Info, target: "example".to_string(), message: message.to_string() });
+        }
+
+        let messages: Vec<_> = capture.records().into_iter().map(|record| record.message).collect();
+        assert_eq!(vec!["second", "third"], messages);
+        assert_eq!(3, capture.total());
+    }
+}
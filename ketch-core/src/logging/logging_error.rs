@@ -0,0 +1,15 @@
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum LoggingError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        LoggerAlreadySet {
+            display("LoggerAlreadySet: another logger was set up before the engine logger")
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod bounds;
+pub mod ray;
+pub mod frustum;
@@ -0,0 +1,97 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Bounds {
+    /// Creates new bounds with given minimum and maximum corner.
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Bounds {
+            min,
+            max,
+        }
+    }
+
+    /// Creates smallest bounds containing all given points. Returns empty bounds at origin if there are no points.
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        let mut points = points.into_iter();
+        let first_point = match points.next() {
+            Some(point) => point,
+            None => return Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0)),
+        };
+
+        points.fold(Bounds::new(first_point, first_point), |bounds, point| {
+            Bounds::new(
+                Vec3::new(bounds.min.x.min(point.x), bounds.min.y.min(point.y), bounds.min.z.min(point.z)),
+                Vec3::new(bounds.max.x.max(point.x), bounds.max.y.max(point.y), bounds.max.z.max(point.z)),
+            )
+        })
+    }
+
+    /// Returns minimum corner.
+    pub fn min(&self) -> Vec3 {
+        self.min
+    }
+
+    /// Returns maximum corner.
+    pub fn max(&self) -> Vec3 {
+        self.max
+    }
+
+    /// Returns center of these bounds.
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns all eight corners of these bounds.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Returns smallest axis-aligned bounds containing these bounds transformed by given matrix.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        Bounds::from_points(self.corners().iter().map(|corner| {
+            let point = matrix * Vec4::new(corner.x, corner.y, corner.z, 1.0);
+            Vec3::new(point.x, point.y, point.z)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points_contains_all_points() {
+        let bounds = Bounds::from_points(vec![Vec3::new(1.0, -2.0, 0.0), Vec3::new(-1.0, 3.0, 0.5)]);
+
+        assert_eq!(Vec3::new(-1.0, -2.0, 0.0), bounds.min());
+        assert_eq!(Vec3::new(1.0, 3.0, 0.5), bounds.max());
+    }
+
+    #[test]
+    fn transformed_bounds_contain_rotated_corners() {
+        let bounds = Bounds::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let matrix = nalgebra_glm::rotate_y(&nalgebra_glm::translation(&Vec3::new(5.0, 0.0, 0.0)), std::f32::consts::FRAC_PI_4);
+
+        let transformed = bounds.transformed(&matrix);
+
+        let half_diagonal = 2.0f32.sqrt();
+        assert!((transformed.min() - Vec3::new(5.0 - half_diagonal, -1.0, -half_diagonal)).norm() < 0.001);
+        assert!((transformed.max() - Vec3::new(5.0 + half_diagonal, 1.0, half_diagonal)).norm() < 0.001);
+    }
+}
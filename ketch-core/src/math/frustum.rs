@@ -0,0 +1,98 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use nalgebra_glm as glm;
+
+use crate::math::bounds::Bounds;
+
+/// Volume visible by a camera, described by six planes with normals pointing inside.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts frustum planes from view projection matrix. Depth is expected to be in range from 0 to 1.
+    /// Planes are normalized, so that distances to them are measured in world units.
+    pub fn from_matrix(view_projection: &Mat4) -> Self {
+        let row = |i: usize| Vec4::new(view_projection[(i, 0)], view_projection[(i, 1)], view_projection[(i, 2)], view_projection[(i, 3)]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let mut planes = [w + x, w - x, w + y, w - y, z, w - z];
+        for plane in planes.iter_mut() {
+            let normal_length = glm::length(&Vec3::new(plane.x, plane.y, plane.z));
+            if normal_length > f32::EPSILON {
+                *plane /= normal_length;
+            }
+        }
+        Frustum {
+            planes,
+        }
+    }
+
+    /// Returns planes of this frustum in order: left, right, bottom, top, near, far.
+    /// Plane is stored as unit normal in xyz and distance from origin in w.
+    pub fn planes(&self) -> &[Vec4; 6] {
+        &self.planes
+    }
+
+    /// Returns true if point is inside this frustum.
+    pub fn contains_point(&self, point: &Vec3) -> bool {
+        self.planes.iter().all(|plane| signed_distance(plane, point) >= 0.0)
+    }
+
+    /// Returns true if sphere with given center and radius is at least partially inside this frustum.
+    /// Spheres close to the frustum corners can be reported as intersecting even if they are outside.
+    pub fn intersects_sphere(&self, center: &Vec3, radius: f32) -> bool {
+        self.planes.iter().all(|plane| signed_distance(plane, center) >= -radius)
+    }
+
+    /// Returns false if bounds transformed by model matrix are certainly outside of this frustum.
+    /// Bounds close to the frustum corners can be reported as intersecting even if they are outside.
+    pub fn intersects_bounds(&self, bounds: &Bounds, model_matrix: &Mat4) -> bool {
+        let corners: Vec<Vec3> = bounds.corners().iter().map(|corner| {
+            let point = model_matrix * Vec4::new(corner.x, corner.y, corner.z, 1.0);
+            Vec3::new(point.x, point.y, point.z)
+        }).collect();
+
+        self.planes.iter().all(|plane| corners.iter().any(|corner| signed_distance(plane, corner) >= 0.0))
+    }
+}
+
+fn signed_distance(plane: &Vec4, point: &Vec3) -> f32 {
+    glm::dot(&Vec3::new(plane.x, plane.y, plane.z), point) + plane.w
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::Camera;
+
+    fn camera_frustum() -> Frustum {
+        let camera = Camera::new();
+        Frustum::from_matrix(&(camera.projection_matrix(800.0, 600.0) * camera.view_matrix()))
+    }
+
+    #[test]
+    fn bounds_in_front_of_camera_are_visible() {
+        let bounds = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+        let model_matrix = glm::translate(&Mat4::identity(), &Vec3::new(0.0, 0.0, -5.0));
+
+        assert!(camera_frustum().intersects_bounds(&bounds, &model_matrix));
+    }
+
+    #[test]
+    fn bounds_behind_camera_are_culled() {
+        let bounds = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+        let model_matrix = glm::translate(&Mat4::identity(), &Vec3::new(0.0, 0.0, 5.0));
+
+        assert!(!camera_frustum().intersects_bounds(&bounds, &model_matrix));
+    }
+
+    #[test]
+    fn spheres_are_tested_against_normalized_planes() {
+        let frustum = camera_frustum();
+        let near_plane = Camera::new().near_plane();
+
+        assert!((signed_distance(&frustum.planes()[4], &Vec3::new(0.0, 0.0, 3.0 - near_plane - 1.0)) - 1.0).abs() < 0.001);
+        assert!(frustum.intersects_sphere(&Vec3::new(0.0, 0.0, 4.0), 1.5));
+        assert!(!frustum.intersects_sphere(&Vec3::new(0.0, 0.0, 4.0), 0.5));
+    }
+}
@@ -0,0 +1,152 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use nalgebra_glm as glm;
+
+use crate::math::bounds::Bounds;
+use crate::resource::camera::Camera;
+
+/// Half-line starting at origin and going in direction.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    origin: Vec3,
+    direction: Vec3,
+}
+
+impl Ray {
+    /// Creates new ray. Direction is normalized.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction: glm::normalize(&direction),
+        }
+    }
+
+    /// Creates a ray going from the camera through given point on the screen.
+    /// Coordinates are measured from the top left corner of the window.
+    pub fn from_screen_position(camera: &Camera, x: f32, y: f32, window_width: f32, window_height: f32) -> Self {
+        let inverse_view_projection = glm::inverse(&(camera.projection_matrix(window_width, window_height) * camera.view_matrix()));
+        let ndc_x = 2.0 * x / window_width - 1.0;
+        let ndc_y = 2.0 * y / window_height - 1.0;
+
+        let near_point = unproject(&inverse_view_projection, Vec4::new(ndc_x, ndc_y, 0.0, 1.0));
+        let far_point = unproject(&inverse_view_projection, Vec4::new(ndc_x, ndc_y, 1.0, 1.0));
+
+        Ray::new(near_point, far_point - near_point)
+    }
+
+    /// Returns ray origin.
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    /// Returns normalized ray direction.
+    pub fn direction(&self) -> Vec3 {
+        self.direction
+    }
+
+    /// Returns point on the ray at given distance from its origin.
+    pub fn point_at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Returns distance to the intersection with bounds transformed by model matrix, or None if they don't intersect.
+    pub fn intersect_bounds(&self, bounds: &Bounds, model_matrix: &Mat4) -> Option<f32> {
+        let inverse_model_matrix = glm::inverse(model_matrix);
+        let origin = inverse_model_matrix * Vec4::new(self.origin.x, self.origin.y, self.origin.z, 1.0);
+        let direction = inverse_model_matrix * Vec4::new(self.direction.x, self.direction.y, self.direction.z, 0.0);
+
+        let (min, max) = (bounds.min(), bounds.max());
+        let mut t_min = f32::NEG_INFINITY;
+        let mut t_max = f32::INFINITY;
+
+        for axis in 0..3 {
+            if direction[axis].abs() < f32::EPSILON {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+            } else {
+                let t1 = (min[axis] - origin[axis]) / direction[axis];
+                let t2 = (max[axis] - origin[axis]) / direction[axis];
+                t_min = t_min.max(t1.min(t2));
+                t_max = t_max.min(t1.max(t2));
+            }
+        }
+
+        if t_max < t_min.max(0.0) {
+            None
+        } else {
+            Some(t_min.max(0.0))
+        }
+    }
+
+    /// Returns distance to the closest intersection with a sphere, or None if they don't intersect.
+    pub fn intersect_sphere(&self, center: &Vec3, radius: f32) -> Option<f32> {
+        let to_center = center - self.origin;
+        let projection = glm::dot(&to_center, &self.direction);
+        let distance_squared = glm::dot(&to_center, &to_center) - projection * projection;
+        if distance_squared > radius * radius {
+            return None;
+        }
+        let half_chord = (radius * radius - distance_squared).sqrt();
+        if projection + half_chord < 0.0 {
+            None
+        } else {
+            Some((projection - half_chord).max(0.0))
+        }
+    }
+
+    /// Returns distance to the intersection with a plane, or None if ray is parallel to the plane or points away from it.
+    pub fn intersect_plane(&self, point_on_plane: &Vec3, normal: &Vec3) -> Option<f32> {
+        let denominator = glm::dot(normal, &self.direction);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = glm::dot(&(point_on_plane - self.origin), normal) / denominator;
+        if distance < 0.0 {
+            None
+        } else {
+            Some(distance)
+        }
+    }
+}
+
+fn unproject(inverse_view_projection: &Mat4, ndc_point: Vec4) -> Vec3 {
+    let point = inverse_view_projection * ndc_point;
+    Vec3::new(point.x / point.w, point.y / point.w, point.z / point.w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_hits_bounds_in_front_of_it() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let bounds = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+
+        assert_eq!(Some(4.5), ray.intersect_bounds(&bounds, &Mat4::identity()));
+    }
+
+    #[test]
+    fn ray_misses_bounds_behind_it() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0));
+        let bounds = Bounds::new(Vec3::new(-0.5, -0.5, -0.5), Vec3::new(0.5, 0.5, 0.5));
+
+        assert_eq!(None, ray.intersect_bounds(&bounds, &Mat4::identity()));
+    }
+
+    #[test]
+    fn ray_hits_sphere_surface_closest_to_origin() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert_eq!(Some(4.0), ray.intersect_sphere(&Vec3::new(0.0, 0.0, 0.0), 1.0));
+        assert_eq!(None, ray.intersect_sphere(&Vec3::new(3.0, 0.0, 0.0), 1.0));
+    }
+
+    #[test]
+    fn ray_from_screen_center_goes_forward() {
+        let camera = Camera::new();
+        let ray = Ray::from_screen_position(&camera, 400.0, 300.0, 800.0, 600.0);
+
+        assert!(glm::distance(&ray.direction(), &Vec3::new(0.0, 0.0, -1.0)) < 0.001);
+    }
+}
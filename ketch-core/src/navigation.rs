@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use nalgebra_glm::{self as glm, U3, Vec3, Vec4};
+
+use crate::renderer::debug_draw::DebugDraw;
+use crate::resource::mesh::Topology;
+use crate::resource::object::Object;
+use crate::resource::scene::Scene;
+
+pub mod path_follower;
+
+/// Distance below which vertices of different triangles are merged into one.
+const WELD_DISTANCE: f32 = 0.001;
+
+/// Walkable surface made of triangles, used to find paths for AI agents.
+/// Triangles sharing an edge are connected, paths are searched on the graph of triangles with A*.
+pub struct NavMesh {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[usize; 3]>,
+    /// Triangle on the other side of each edge of each triangle, edge i goes from vertex i to vertex i + 1.
+    neighbours: Vec<[Option<usize>; 3]>,
+}
+
+impl NavMesh {
+    /// Creates nav mesh from vertices and triangles indexing them, used to author nav meshes by hand.
+    /// Triangles with indices out of bounds are ignored.
+    pub fn new(vertices: Vec<Vec3>, triangles: Vec<[usize; 3]>) -> Self {
+        let triangles: Vec<[usize; 3]> = triangles.into_iter().filter(|triangle| triangle.iter().all(|index| *index < vertices.len())).collect();
+        let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            for edge in 0..3 {
+                let (a, b) = (triangle[edge], triangle[(edge + 1) % 3]);
+                edges.entry((a.min(b), a.max(b))).or_default().push((triangle_index, edge));
+            }
+        }
+        let mut neighbours = vec![[None; 3]; triangles.len()];
+        for sharing in edges.values() {
+            // edges shared by more than two triangles are ambiguous and aren't connected
+            if let [(first, first_edge), (second, second_edge)] = sharing.as_slice() {
+                neighbours[*first][*first_edge] = Some(*second);
+                neighbours[*second][*second_edge] = Some(*first);
+            }
+        }
+        NavMesh {
+            vertices,
+            triangles,
+            neighbours,
+        }
+    }
+
+    /// Generates nav mesh from meshes of scene objects accepted by the filter.
+    /// Only triangles facing up and not steeper than max slope angle in radians are walkable.
+    pub fn from_scene<F: Fn(&Object) -> bool>(scene: &Scene, max_slope_angle: f32, filter: F) -> Self {
+        let mut vertices = Vec::new();
+        let mut welded: HashMap<(i64, i64, i64), usize> = HashMap::new();
+        let mut triangles = Vec::new();
+        for object in scene.objects().iter().filter(|object| filter(object)) {
+            let mesh = match object.mesh() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let mesh = mesh.read().unwrap();
+            // lines and points don't form walkable surfaces
+            if mesh.topology() != Topology::TriangleList {
+                continue;
+            }
+            let world_matrix = scene.world_matrix(object);
+            let positions: Vec<Vec3> = mesh.vertices().iter()
+                                           .map(|vertex| (world_matrix * Vec4::new(vertex.position[0], vertex.position[1], vertex.position[2], 1.0)).xyz())
+                                           .collect();
+            for indices in mesh.indices().chunks(3).filter(|indices| indices.len() == 3) {
+                let corners = [positions[indices[0] as usize], positions[indices[1] as usize], positions[indices[2] as usize]];
+                let normal = glm::cross::<f32, U3>(&(corners[1] - corners[0]), &(corners[2] - corners[0]));
+                if glm::length(&normal) <= f32::EPSILON || glm::normalize(&normal).y < max_slope_angle.cos() {
+                    continue;
+                }
+                let mut triangle = [0; 3];
+                for (index, corner) in triangle.iter_mut().zip(corners.iter()) {
+                    let key = ((corner.x / WELD_DISTANCE).round() as i64, (corner.y / WELD_DISTANCE).round() as i64, (corner.z / WELD_DISTANCE).round() as i64);
+                    *index = *welded.entry(key).or_insert_with(|| {
+                        vertices.push(*corner);
+                        vertices.len() - 1
+                    });
+                }
+                if triangle[0] != triangle[1] && triangle[1] != triangle[2] && triangle[0] != triangle[2] {
+                    triangles.push(triangle);
+                }
+            }
+        }
+        NavMesh::new(vertices, triangles)
+    }
+
+    /// Moves the nav mesh by the negated offset, after the scene it was generated from shifted its origin by it.
+    pub fn shift_origin(&mut self, offset: &Vec3) {
+        for vertex in &mut self.vertices {
+            *vertex -= offset;
+        }
+    }
+
+    /// Returns vertices of the nav mesh.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// Returns triangles of the nav mesh as indices of their vertices.
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Draws edges of triangles, used to check generated nav mesh. Edges shared by triangles are drawn once.
+    pub fn draw(&self, debug_draw: &mut DebugDraw, color: Vec3) {
+        for (triangle_index, triangle) in self.triangles.iter().enumerate() {
+            for edge in 0..3 {
+                let drawn_by_neighbour = self.neighbours[triangle_index][edge].map(|neighbour| neighbour < triangle_index).unwrap_or(false);
+                if !drawn_by_neighbour {
+                    debug_draw.line(self.vertices[triangle[edge]], self.vertices[triangle[(edge + 1) % 3]], color);
+                }
+            }
+        }
+    }
+
+    /// Returns triangle below or above the point, closest to it vertically.
+    pub fn find_triangle(&self, point: &Vec3) -> Option<usize> {
+        self.triangles.iter()
+                      .enumerate()
+                      .filter_map(|(index, triangle)| {
+                          let [a, b, c] = self.corners(triangle);
+                          height_in_triangle(&a, &b, &c, point).map(|height| (index, (height - point.y).abs()))
+                      })
+                      .min_by(|(_, first), (_, second)| first.partial_cmp(second).unwrap_or(Ordering::Equal))
+                      .map(|(index, _)| index)
+    }
+
+    /// Returns shortest path from start to end as points where direction changes, including start and end.
+    /// Returns None if either point is outside the nav mesh or there is no path between them.
+    pub fn find_path(&self, start: &Vec3, end: &Vec3) -> Option<Vec<Vec3>> {
+        let start_triangle = self.find_triangle(start)?;
+        let end_triangle = self.find_triangle(end)?;
+        let corridor = self.find_corridor(start_triangle, end_triangle, end)?;
+
+        let mut portals = vec![(*start, *start)];
+        for pair in corridor.windows(2) {
+            let (a, b) = self.shared_edge(pair[0], pair[1]);
+            let center = self.center(pair[0]);
+            portals.push(if triangle_area_2d(&center, &a, &b) < 0.0 { (b, a) } else { (a, b) });
+        }
+        portals.push((*end, *end));
+        Some(string_pull(&portals))
+    }
+
+    /// Finds triangles along the shortest path between triangles with A*, measuring distances between their centers.
+    fn find_corridor(&self, start: usize, end: usize, goal: &Vec3) -> Option<Vec<usize>> {
+        let mut costs = vec![f32::INFINITY; self.triangles.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.triangles.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0.0;
+        open.push(OpenTriangle { estimate: glm::distance(&self.center(start), goal), triangle: start });
+
+        while let Some(OpenTriangle { triangle, .. }) = open.pop() {
+            if triangle == end {
+                let mut corridor = vec![end];
+                while let Some(triangle) = previous[*corridor.last().expect("corridor isn't empty")] {
+                    corridor.push(triangle);
+                }
+                corridor.reverse();
+                return Some(corridor);
+            }
+            for neighbour in self.neighbours[triangle].iter().filter_map(|neighbour| *neighbour) {
+                let cost = costs[triangle] + glm::distance(&self.center(triangle), &self.center(neighbour));
+                if cost < costs[neighbour] {
+                    costs[neighbour] = cost;
+                    previous[neighbour] = Some(triangle);
+                    open.push(OpenTriangle { estimate: cost + glm::distance(&self.center(neighbour), goal), triangle: neighbour });
+                }
+            }
+        }
+        None
+    }
+
+    fn corners(&self, triangle: &[usize; 3]) -> [Vec3; 3] {
+        [self.vertices[triangle[0]], self.vertices[triangle[1]], self.vertices[triangle[2]]]
+    }
+
+    fn center(&self, triangle: usize) -> Vec3 {
+        let [a, b, c] = self.corners(&self.triangles[triangle]);
+        (a + b + c) / 3.0
+    }
+
+    fn shared_edge(&self, triangle: usize, neighbour: usize) -> (Vec3, Vec3) {
+        let edge = self.neighbours[triangle].iter().position(|candidate| *candidate == Some(neighbour)).expect("triangles in corridor are neighbours");
+        let indices = self.triangles[triangle];
+        (self.vertices[indices[edge]], self.vertices[indices[(edge + 1) % 3]])
+    }
+}
+
+/// Triangle waiting to be visited by A*, ordered so that the one with the lowest estimated path cost is popped first.
+struct OpenTriangle {
+    estimate: f32,
+    triangle: usize,
+}
+
+impl PartialEq for OpenTriangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenTriangle {}
+
+impl PartialOrd for OpenTriangle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenTriangle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.partial_cmp(&self.estimate).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Returns height of the triangle at the point projected on the XZ plane, or None if the projection is outside of it.
+fn height_in_triangle(a: &Vec3, b: &Vec3, c: &Vec3, point: &Vec3) -> Option<f32> {
+    let area = triangle_area_2d(a, b, c);
+    if area.abs() <= f32::EPSILON {
+        return None;
+    }
+    let u = triangle_area_2d(point, b, c) / area;
+    let v = triangle_area_2d(a, point, c) / area;
+    let w = 1.0 - u - v;
+    let tolerance = -0.0001;
+    if u < tolerance || v < tolerance || w < tolerance {
+        return None;
+    }
+    Some(a.y * u + b.y * v + c.y * w)
+}
+
+/// Returns twice the signed area of the triangle projected on the XZ plane.
+fn triangle_area_2d(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
+    (c.x - a.x) * (b.z - a.z) - (b.x - a.x) * (c.z - a.z)
+}
+
+/// Shortens path going through portals, given as left and right points, with the simple stupid funnel algorithm.
+fn string_pull(portals: &[(Vec3, Vec3)]) -> Vec<Vec3> {
+    let start = portals[0].0;
+    let end = portals[portals.len() - 1].0;
+    let mut path = vec![start];
+    let (mut apex, mut left, mut right) = (start, start, start);
+    let (mut left_index, mut right_index) = (0, 0);
+
+    let mut index = 1;
+    while index < portals.len() {
+        let (portal_left, portal_right) = portals[index];
+        if triangle_area_2d(&apex, &right, &portal_right) <= 0.0 {
+            if apex == right || triangle_area_2d(&apex, &left, &portal_right) > 0.0 {
+                right = portal_right;
+                right_index = index;
+            } else {
+                // right side crossed left side, left point is a corner of the path
+                if path.last() != Some(&left) {
+                    path.push(left);
+                }
+                apex = left;
+                right = left;
+                right_index = left_index;
+                index = left_index + 1;
+                continue;
+            }
+        }
+        if triangle_area_2d(&apex, &left, &portal_left) >= 0.0 {
+            if apex == left || triangle_area_2d(&apex, &right, &portal_left) < 0.0 {
+                left = portal_left;
+                left_index = index;
+            } else {
+                if path.last() != Some(&right) {
+                    path.push(right);
+                }
+                apex = right;
+                left = right;
+                left_index = right_index;
+                index = right_index + 1;
+                continue;
+            }
+        }
+        index += 1;
+    }
+    if path.last() != Some(&end) {
+        path.push(end);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three unit squares forming an L shape: (0, 0), (1, 0) and (1, 1).
+    fn l_shaped_nav_mesh() -> NavMesh {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 1.0), Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 2.0), Vec3::new(2.0, 0.0, 2.0),
+        ];
+        let triangles = vec![[0, 3, 4], [0, 4, 1], [1, 4, 5], [1, 5, 2], [4, 6, 7], [4, 7, 5]];
+        NavMesh::new(vertices, triangles)
+    }
+
+    #[test]
+    fn path_bends_around_corner() {
+        let nav_mesh = l_shaped_nav_mesh();
+
+        let path = nav_mesh.find_path(&Vec3::new(0.5, 0.0, 0.5), &Vec3::new(1.5, 0.0, 1.8)).unwrap();
+
+        assert_eq!(vec![Vec3::new(0.5, 0.0, 0.5), Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.5, 0.0, 1.8)], path);
+    }
+
+    #[test]
+    fn points_outside_nav_mesh_have_no_path() {
+        let nav_mesh = l_shaped_nav_mesh();
+
+        assert!(nav_mesh.find_path(&Vec3::new(0.5, 0.0, 0.5), &Vec3::new(0.5, 0.0, 1.5)).is_none());
+    }
+}
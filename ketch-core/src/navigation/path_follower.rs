@@ -0,0 +1,88 @@
+use nalgebra_glm::{self as glm, Vec3};
+
+/// Steers an agent along a path found on a nav mesh. Heights of path points are ignored,
+/// agents are expected to stay on the ground, e.g. by moving with a character controller.
+pub struct PathFollower {
+    path: Vec<Vec3>,
+    next_point: usize,
+    /// Distance at which a path point counts as reached.
+    pub arrival_distance: f32,
+    /// Distance from the end of the path at which the agent starts slowing down.
+    pub slowing_distance: f32,
+}
+
+impl PathFollower {
+    /// Creates follower of the path, heading to its first point.
+    pub fn new(path: Vec<Vec3>) -> Self {
+        PathFollower {
+            path,
+            next_point: 0,
+            arrival_distance: 0.2,
+            slowing_distance: 1.0,
+        }
+    }
+
+    /// Returns the path being followed.
+    pub fn path(&self) -> &[Vec3] {
+        &self.path
+    }
+
+    /// Returns the point the agent is heading to, or None if the end of the path was reached.
+    pub fn next_point(&self) -> Option<Vec3> {
+        self.path.get(self.next_point).cloned()
+    }
+
+    /// Returns true if the end of the path was reached.
+    pub fn is_finished(&self) -> bool {
+        self.next_point >= self.path.len()
+    }
+
+    /// Returns horizontal velocity moving agent at given position towards the next point of the path, with length of at most max speed.
+    /// Agent slows down when it approaches the end of the path. Returns zero vector when the end is reached.
+    pub fn steer(&mut self, position: &Vec3, max_speed: f32) -> Vec3 {
+        while let Some(point) = self.next_point() {
+            if horizontal_distance(position, &point) > self.arrival_distance {
+                break;
+            }
+            self.next_point += 1;
+        }
+        let point = match self.next_point() {
+            Some(point) => point,
+            None => return Vec3::zeros(),
+        };
+
+        let offset = Vec3::new(point.x - position.x, 0.0, point.z - position.z);
+        let distance = glm::length(&offset);
+        let is_last_point = self.next_point == self.path.len() - 1;
+        let speed = if is_last_point && distance < self.slowing_distance {
+            max_speed * distance / self.slowing_distance
+        } else {
+            max_speed
+        };
+        offset / distance * speed
+    }
+}
+
+fn horizontal_distance(a: &Vec3, b: &Vec3) -> f32 {
+    ((a.x - b.x).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follower_heads_to_next_point_after_reaching_one() {
+        let mut follower = PathFollower::new(vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 5.0), Vec3::new(5.0, 0.0, 5.0)]);
+
+        let velocity = follower.steer(&Vec3::new(0.0, 0.0, 0.1), 2.0);
+        assert_eq!(Vec3::new(0.0, 0.0, 2.0), velocity);
+
+        let velocity = follower.steer(&Vec3::new(0.0, 0.0, 5.0), 2.0);
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0), velocity);
+
+        let velocity = follower.steer(&Vec3::new(4.9, 0.0, 5.0), 2.0);
+        assert_eq!(Vec3::zeros(), velocity);
+        assert!(follower.is_finished());
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use nalgebra_glm as glm;
+use nalgebra_glm::{Mat4, U3, Vec3};
+
+use crate::random::Rng;
+use crate::resource::particle_effect::ParticleEffect;
+use crate::resource::scene::Scene;
+
+/// Single particle simulated in world space.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Time in seconds since the particle was emitted.
+    pub age: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+/// Particles emitted by a particle emitter of a single object.
+pub struct ParticleInstance {
+    effect: String,
+    particles: Vec<Particle>,
+    /// Time since the effect was started.
+    time: f32,
+    /// Fraction of a particle which wasn't emitted yet, carried over to the next update.
+    pending_emission: f32,
+    burst_emitted: bool,
+}
+
+impl ParticleInstance {
+    fn new(effect: &str) -> Self {
+        ParticleInstance {
+            effect: effect.to_string(),
+            particles: Vec::new(),
+            time: 0.0,
+            pending_emission: 0.0,
+            burst_emitted: false,
+        }
+    }
+
+    /// Returns name of the played effect.
+    pub fn effect(&self) -> &str {
+        &self.effect
+    }
+
+    /// Returns living particles.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    fn restart(&mut self) {
+        self.time = 0.0;
+        self.pending_emission = 0.0;
+        self.burst_emitted = false;
+    }
+
+    fn update(&mut self, elapsed: f32, effect: &ParticleEffect, world_matrix: Option<Mat4>, playing: bool, rng: &mut Rng) {
+        let gravity = Vec3::new(effect.gravity.0, effect.gravity.1, effect.gravity.2);
+        for particle in &mut self.particles {
+            particle.age += elapsed;
+            particle.velocity += gravity * elapsed;
+            particle.position += particle.velocity * elapsed;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        if let (Some(world_matrix), true) = (world_matrix, playing) {
+            if effect.looping && effect.duration > 0.0 && self.time >= effect.duration {
+                self.time %= effect.duration;
+                self.burst_emitted = false;
+            }
+            let mut count = 0;
+            if !self.burst_emitted {
+                count += effect.burst;
+                self.burst_emitted = true;
+            }
+            if self.time < effect.duration {
+                self.pending_emission += effect.emission_rate.max(0.0) * elapsed.min(effect.duration - self.time);
+                count += self.pending_emission as u32;
+                self.pending_emission = self.pending_emission.fract();
+            }
+            for _ in 0..count {
+                if self.particles.len() >= effect.max_particles as usize {
+                    break;
+                }
+                self.particles.push(emit(effect, &world_matrix, rng));
+            }
+            self.time += elapsed;
+        }
+
+        for particle in &mut self.particles {
+            let t = if particle.lifetime > 0.0 { particle.age / particle.lifetime } else { 1.0 };
+            particle.size = effect.size.evaluate(t).unwrap_or(1.0);
+            particle.color = effect.color.evaluate(t).unwrap_or([1.0; 4]);
+        }
+    }
+}
+
+/// Simulates particles of particle emitters attached to objects of a scene.
+pub struct ParticleSystem {
+    instances: HashMap<u32, ParticleInstance>,
+    rng: Rng,
+    scene_name: Option<String>,
+}
+
+impl ParticleSystem {
+    /// Creates particle system without any particles. Particles are emitted in random directions drawn from the seed.
+    pub fn new(seed: u64) -> Self {
+        ParticleSystem {
+            instances: HashMap::new(),
+            rng: Rng::from_seed(seed),
+            scene_name: None,
+        }
+    }
+
+    /// Emits and moves particles of emitters attached to scene objects. Effects are found by name using the lookup.
+    /// Particles of removed objects and stopped emitters live until the end of their lifetime.
+    /// All particles are removed when scene changes.
+    pub fn update<F>(&mut self, elapsed: Duration, scene: &Scene, effect_lookup: &F)
+        where F: Fn(&str) -> Option<Arc<RwLock<ParticleEffect>>> {
+        let elapsed = elapsed.as_secs() as f32 + elapsed.subsec_nanos() as f32 / 1_000_000_000.0;
+        if self.scene_name.as_deref() != Some(scene.name()) {
+            self.clear();
+            self.scene_name = Some(scene.name().to_string());
+        }
+
+        for object in scene.objects() {
+            if let Some(emitter) = object.particle_emitter() {
+                let instance = self.instances.entry(object.id()).or_insert_with(|| ParticleInstance::new(&emitter.effect));
+                if instance.effect != emitter.effect {
+                    *instance = ParticleInstance::new(&emitter.effect);
+                }
+            }
+        }
+
+        let rng = &mut self.rng;
+        self.instances.retain(|id, instance| {
+            let effect = match effect_lookup(&instance.effect) {
+                Some(effect) => effect,
+                None => return false,
+            };
+            let object = scene.object(*id);
+            let playing = object.and_then(|object| object.particle_emitter()).map(|emitter| emitter.playing).unwrap_or(false);
+            instance.update(elapsed, &effect.read().unwrap(), object.map(|object| scene.world_matrix(object)), playing, rng);
+            object.and_then(|object| object.particle_emitter()).is_some() || !instance.particles.is_empty()
+        });
+    }
+
+    /// Starts effect played by the object from the beginning, emitting its burst again.
+    pub fn restart(&mut self, object: u32) {
+        if let Some(instance) = self.instances.get_mut(&object) {
+            instance.restart();
+        }
+    }
+
+    /// Removes all particles.
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.scene_name = None;
+    }
+
+    /// Moves all particles by the negated offset, after the active scene shifted its origin by it.
+    pub fn shift_origin(&mut self, offset: &Vec3) {
+        for particle in self.instances.values_mut().flat_map(|instance| instance.particles.iter_mut()) {
+            particle.position -= offset;
+        }
+    }
+
+    /// Returns particles emitted by the object.
+    pub fn instance(&self, object: u32) -> Option<&ParticleInstance> {
+        self.instances.get(&object)
+    }
+
+    /// Returns particles of all emitters, in no particular order.
+    pub fn instances(&self) -> impl Iterator<Item = &ParticleInstance> {
+        self.instances.values()
+    }
+
+    /// Returns number of living particles.
+    pub fn particle_count(&self) -> usize {
+        self.instances.values().map(|instance| instance.particles.len()).sum()
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        ParticleSystem::new(0)
+    }
+}
+
+fn emit(effect: &ParticleEffect, world_matrix: &Mat4, rng: &mut Rng) -> Particle {
+    let position = Vec3::new(world_matrix[(0, 3)], world_matrix[(1, 3)], world_matrix[(2, 3)]);
+    let (x, y, z) = effect.direction;
+    let local_direction = Vec3::new(x, y, z);
+    let mut direction = Vec3::new(world_matrix[(0, 0)] * x + world_matrix[(0, 1)] * y + world_matrix[(0, 2)] * z,
+                                  world_matrix[(1, 0)] * x + world_matrix[(1, 1)] * y + world_matrix[(1, 2)] * z,
+                                  world_matrix[(2, 0)] * x + world_matrix[(2, 1)] * y + world_matrix[(2, 2)] * z);
+    if glm::length2(&direction) > 0.0 {
+        direction = glm::normalize(&direction);
+    } else if glm::length2(&local_direction) > 0.0 {
+        direction = glm::normalize(&local_direction);
+    } else {
+        direction = Vec3::new(0.0, 1.0, 0.0);
+    }
+    let direction = spread(&direction, effect.spread_angle, rng);
+    let speed = rng.range_f32(effect.speed.0, effect.speed.1);
+    Particle {
+        position,
+        velocity: direction * speed,
+        age: 0.0,
+        lifetime: rng.range_f32(effect.lifetime.0, effect.lifetime.1),
+        size: effect.size.evaluate(0.0).unwrap_or(1.0),
+        color: effect.color.evaluate(0.0).unwrap_or([1.0; 4]),
+    }
+}
+
+/// Returns random direction at most the angle away from the direction.
+fn spread(direction: &Vec3, angle: f32, rng: &mut Rng) -> Vec3 {
+    if angle <= 0.0 {
+        return *direction;
+    }
+    let axis = if direction.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    let first = glm::normalize(&glm::cross::<f32, U3>(direction, &axis));
+    let second = glm::cross::<f32, U3>(direction, &first);
+    // cosine is drawn uniformly so that directions are spread evenly over the cone
+    let cos_angle = rng.range_f32(angle.min(std::f32::consts::PI).cos(), 1.0);
+    let sin_angle = (1.0 - cos_angle * cos_angle).max(0.0).sqrt();
+    let rotation = rng.range_f32(0.0, 2.0 * std::f32::consts::PI);
+    direction * cos_angle + (first * rotation.cos() + second * rotation.sin()) * sin_angle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::Camera;
+    use crate::resource::object::ObjectBuilder;
+    use crate::resource::particle_effect::ParticleEmitter;
+
+    #[test]
+    fn emitted_particles_die_after_their_lifetime() {
+        let mut effect = ParticleEffect::new("sparks");
+        effect.burst = 5;
+        effect.emission_rate = 0.0;
+        effect.looping = false;
+        effect.lifetime = (1.0, 1.0);
+        let effect = Arc::new(RwLock::new(effect));
+        let lookup = |name: &str| if name == "sparks" { Some(effect.clone()) } else { None };
+        let mut scene = Scene::new("level", Camera::new());
+        let mut object = ObjectBuilder::new("torch").with_position(1.0, 2.0, 3.0).build();
+        object.set_particle_emitter(Some(ParticleEmitter::new("sparks")));
+        let id = object.id();
+        scene.add_object(object);
+        let mut particle_system = ParticleSystem::new(1);
+
+        particle_system.update(Duration::from_millis(500), &scene, &lookup);
+        assert_eq!(5, particle_system.particle_count());
+        assert_eq!(Vec3::new(1.0, 2.0, 3.0), particle_system.instance(id).unwrap().particles()[0].position);
+
+        particle_system.update(Duration::from_secs(1), &scene, &lookup);
+        assert_eq!(0, particle_system.particle_count());
+    }
+}
@@ -0,0 +1,50 @@
+/// Subsystems paused independently of the game update, e.g. by a pause menu stopping physics and sounds,
+/// or by a photo mode freezing particles while the camera still moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PauseFlags {
+    /// Physics step and trigger detection.
+    pub physics: bool,
+    /// Tweens of objects and UI nodes.
+    pub animations: bool,
+    /// Particle simulation. Particles stay where they are and are still drawn.
+    pub particles: bool,
+    /// Sound emitters and music, which resume where they stopped.
+    pub audio: bool,
+    /// Video playback.
+    pub videos: bool,
+}
+
+impl PauseFlags {
+    /// Flags pausing nothing.
+    pub const NONE: PauseFlags = PauseFlags { physics: false, animations: false, particles: false, audio: false, videos: false };
+    /// Flags pausing every subsystem, while the game is still updated, e.g. for a pause menu.
+    pub const ALL: PauseFlags = PauseFlags { physics: true, animations: true, particles: true, audio: true, videos: true };
+
+    /// Returns flags pausing subsystems paused by either of the flags, used to combine global flags with flags of the active scene.
+    pub fn combined(self, other: PauseFlags) -> Self {
+        PauseFlags {
+            physics: self.physics || other.physics,
+            animations: self.animations || other.animations,
+            particles: self.particles || other.particles,
+            audio: self.audio || other.audio,
+            videos: self.videos || other.videos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_flags_pause_subsystems_paused_by_either() {
+        let photo_mode = PauseFlags { physics: true, particles: true, ..PauseFlags::NONE };
+        let muted = PauseFlags { audio: true, ..PauseFlags::NONE };
+
+        let combined = photo_mode.combined(muted);
+
+        assert!(combined.physics && combined.particles && combined.audio);
+        assert!(!combined.animations && !combined.videos);
+        assert_eq!(PauseFlags::ALL, PauseFlags::ALL.combined(PauseFlags::NONE));
+    }
+}
@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "profiling")]
+pub use tracing;
+
+/// True if the engine was built with the `profiling` feature, so that spans are recorded.
+pub const ENABLED: bool = cfg!(feature = "profiling");
+
+/// Most spans recorded in a single frame, so that threads which never finish frames don't use more and more memory.
+const MAX_SPANS_PER_FRAME: usize = 4096;
+
+/// Records a span named with a string literal, lasting until the end of the enclosing scope.
+/// Spans are collected per frame for the editor profiler and entered as `tracing` spans, so they can be shown by any tracing subscriber.
+/// Compiles to nothing unless the `profiling` feature is enabled.
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profiling::ProfileScope::new($name);
+        let _tracing_span = $crate::profiling::tracing::trace_span!($name);
+        let _tracing_guard = _tracing_span.enter();
+    };
+}
+
+/// Records a span named with a string literal, lasting until the end of the enclosing scope.
+/// Compiles to nothing unless the `profiling` feature is enabled.
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {};
+}
+
+/// Span recorded during a frame.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProfileSpan {
+    pub name: &'static str,
+    /// Number of spans this span was nested in.
+    pub depth: u32,
+    /// Time from the beginning of the frame to the beginning of the span.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Spans recorded during a single frame.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ProfileFrame {
+    spans: Vec<ProfileSpan>,
+    duration: Duration,
+}
+
+impl ProfileFrame {
+    /// Returns spans recorded during the frame, in the order they ended.
+    pub fn spans(&self) -> &[ProfileSpan] {
+        &self.spans
+    }
+
+    /// Returns duration of the whole frame.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns summed duration of spans with given name.
+    pub fn total(&self, name: &str) -> Duration {
+        self.spans.iter().filter(|span| span.name == name).map(|span| span.duration).fold(Duration::from_secs(0), |total, duration| total + duration)
+    }
+}
+
+/// Spans of the current frame recorded on one thread.
+struct Profiler {
+    frame_start: Instant,
+    depth: u32,
+    spans: Vec<ProfileSpan>,
+    last_frame: ProfileFrame,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Profiler {
+            frame_start: Instant::now(),
+            depth: 0,
+            spans: Vec::new(),
+            last_frame: ProfileFrame::default(),
+        }
+    }
+
+    fn close_span(&mut self, name: &'static str, start: Instant) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.spans.len() >= MAX_SPANS_PER_FRAME {
+            return;
+        }
+        // spans started before the frame began are cut at its beginning
+        let (start, duration) = if start >= self.frame_start {
+            (start.duration_since(self.frame_start), start.elapsed())
+        } else {
+            (Duration::from_secs(0), self.frame_start.elapsed())
+        };
+        self.spans.push(ProfileSpan {
+            name,
+            depth: self.depth,
+            start,
+            duration,
+        });
+    }
+
+    fn finish_frame(&mut self) {
+        self.last_frame = ProfileFrame {
+            spans: std::mem::take(&mut self.spans),
+            duration: self.frame_start.elapsed(),
+        };
+        self.frame_start = Instant::now();
+    }
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::new());
+}
+
+/// Span which lasts until it's dropped. Created by `profile_scope!`.
+pub struct ProfileScope {
+    name: &'static str,
+    start: Instant,
+}
+
+impl ProfileScope {
+    /// Starts span with given name on the current thread.
+    pub fn new(name: &'static str) -> Self {
+        PROFILER.with(|profiler| profiler.borrow_mut().depth += 1);
+        ProfileScope {
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let (name, start) = (self.name, self.start);
+        PROFILER.with(|profiler| profiler.borrow_mut().close_span(name, start));
+    }
+}
+
+/// Ends the current frame of the calling thread and starts a new one. Called by the engine loop once per rendered frame.
+pub fn finish_frame() {
+    PROFILER.with(|profiler| profiler.borrow_mut().finish_frame());
+}
+
+/// Returns spans recorded by the calling thread during its last finished frame.
+pub fn last_frame() -> ProfileFrame {
+    PROFILER.with(|profiler| profiler.borrow().last_frame.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_spans_are_recorded_with_depth() {
+        {
+            let _outer = ProfileScope::new("outer");
+            let _inner = ProfileScope::new("inner");
+        }
+        finish_frame();
+
+        let frame = last_frame();
+        let spans: Vec<(&str, u32)> = frame.spans().iter().map(|span| (span.name, span.depth)).collect();
+        assert_eq!(vec![("inner", 1), ("outer", 0)], spans);
+        assert!(frame.total("outer") >= frame.total("inner"));
+
+        finish_frame();
+        assert!(last_frame().spans().is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use nalgebra_glm::Vec3;
+
+/// Small, fast pseudo random number generator (xorshift64*). Sequences depend only on the seed,
+/// so the same seed gives the same numbers on every platform.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates generator from the seed. Every seed, including zero, gives a different sequence.
+    pub fn from_seed(seed: u64) -> Self {
+        // xorshift state can't be zero, so seed is scrambled first
+        let state = split_mix(seed);
+        Rng {
+            state: if state == 0 { 0x9E37_79B9_7F4A_7C15 } else { state },
+        }
+    }
+
+    /// Returns random 64 bit number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns random 32 bit number.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns random number from 0.0 inclusive to 1.0 exclusive.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns random number from min inclusive to max exclusive.
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Returns random integer from min inclusive to max exclusive. Returns min if the range is empty.
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        let length = (i64::from(max) - i64::from(min)) as u64;
+        (i64::from(min) + (self.next_u64() % length) as i64) as i32
+    }
+
+    /// Returns true with given probability from 0.0 to 1.0.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Returns random element of the slice, or None if it's empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.range_i32(0, items.len() as i32) as usize)
+        }
+    }
+
+    /// Returns index of random weight, where the probability of picking an index is proportional to its weight.
+    /// Returns None if there are no positive weights.
+    pub fn weighted_index(&mut self, weights: &[f32]) -> Option<usize> {
+        let total: f32 = weights.iter().filter(|weight| **weight > 0.0).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut remaining = self.next_f32() * total;
+        let mut last_positive = None;
+        for (index, weight) in weights.iter().enumerate().filter(|(_, weight)| **weight > 0.0) {
+            if remaining < *weight {
+                return Some(index);
+            }
+            remaining -= weight;
+            last_positive = Some(index);
+        }
+        // rounding errors can leave a tiny remainder after the last weight
+        last_positive
+    }
+
+    /// Shuffles the slice in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.range_i32(0, index as i32 + 1) as usize;
+            items.swap(index, other);
+        }
+    }
+
+    /// Returns number from normal distribution with given mean and standard deviation.
+    pub fn normal(&mut self, mean: f32, standard_deviation: f32) -> f32 {
+        // Box-Muller transform, first number is kept above zero so that its logarithm is finite
+        let u1 = 1.0 - self.next_f32();
+        let u2 = self.next_f32();
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+        mean + z * standard_deviation
+    }
+
+    /// Returns random direction of length 1.
+    pub fn unit_vector(&mut self) -> Vec3 {
+        let z = self.range_f32(-1.0, 1.0);
+        let angle = self.range_f32(0.0, 2.0 * std::f32::consts::PI);
+        let radius = (1.0 - z * z).sqrt();
+        Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+    }
+
+    /// Returns random point inside a sphere with given radius, centered at the origin.
+    pub fn point_in_sphere(&mut self, radius: f32) -> Vec3 {
+        self.unit_vector() * radius * self.next_f32().cbrt()
+    }
+}
+
+/// Source of random numbers for the game and engine subsystems, seeded with a single global seed.
+/// Each subsystem draws from its own named stream, so that adding random calls to one subsystem
+/// doesn't change numbers drawn by others, which keeps replays and procedural generation deterministic.
+pub struct RandomService {
+    seed: u64,
+    streams: HashMap<String, Rng>,
+}
+
+impl RandomService {
+    /// Creates service with the global seed.
+    pub fn new(seed: u64) -> Self {
+        RandomService {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Returns the global seed.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Changes the global seed and restarts all streams.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    /// Returns stream with the given name, created from the global seed and the name when it's used for the first time.
+    pub fn stream(&mut self, name: &str) -> &mut Rng {
+        let seed = self.seed;
+        self.streams.entry(name.to_string()).or_insert_with(|| Rng::from_seed(seed ^ hash_name(name)))
+    }
+}
+
+/// Returns seed based on the current time, used when no seed was configured.
+pub fn time_seed() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+                                .map(|duration| duration.as_secs().wrapping_mul(1_000_000_000).wrapping_add(u64::from(duration.subsec_nanos())))
+                                .unwrap_or(0)
+}
+
+fn split_mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// FNV-1a hash, which is stable between runs unlike the standard library hasher.
+fn hash_name(name: &str) -> u64 {
+    name.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_deterministic_and_independent() {
+        let mut first = RandomService::new(7);
+        let mut second = RandomService::new(7);
+
+        first.stream("physics").next_u64();
+        let loot_numbers: Vec<u32> = (0..4).map(|_| first.stream("loot").next_u32()).collect();
+        let expected: Vec<u32> = (0..4).map(|_| second.stream("loot").next_u32()).collect();
+
+        assert_eq!(expected, loot_numbers);
+        assert_ne!(first.stream("physics").next_u64(), first.stream("loot").next_u64());
+    }
+
+    #[test]
+    fn ranges_stay_within_bounds() {
+        let mut rng = Rng::from_seed(0);
+        for _ in 0..1000 {
+            let integer = rng.range_i32(-3, 3);
+            let float = rng.range_f32(1.0, 2.0);
+            assert!(integer >= -3 && integer < 3);
+            assert!(float >= 1.0 && float < 2.0);
+        }
+        assert_eq!(None, rng.weighted_index(&[0.0, -1.0]));
+        assert_eq!(Some(1), rng.weighted_index(&[0.0, 2.0]));
+    }
+}
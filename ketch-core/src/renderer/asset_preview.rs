@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::R8G8B8A8Unorm;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::pipeline::vertex::BufferlessDefinition;
+use vulkano::pipeline::viewport::Viewport;
+
+use crate::renderer::offscreen_target::PreviewTarget;
+use crate::renderer::renderer_error::RendererCreationError;
+use crate::renderer::shader::ShaderSet;
+use crate::resource::mesh::vertex_format::VertexFormat;
+
+/// Width and height of asset preview image in pixels.
+pub const ASSET_PREVIEW_SIZE: u32 = 256;
+
+/// Channels of a texture shown in texture preview.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextureChannel {
+    All,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl TextureChannel {
+    pub const ALL: [TextureChannel; 5] = [
+        TextureChannel::All,
+        TextureChannel::Red,
+        TextureChannel::Green,
+        TextureChannel::Blue,
+        TextureChannel::Alpha,
+    ];
+
+    /// Returns label of the channel button.
+    pub fn label(self) -> &'static str {
+        match self {
+            TextureChannel::All => "RGBA",
+            TextureChannel::Red => "R",
+            TextureChannel::Green => "G",
+            TextureChannel::Blue => "B",
+            TextureChannel::Alpha => "A",
+        }
+    }
+
+    /// Returns mask selecting the channel from a color, or None if all channels are shown.
+    pub(crate) fn mask(self) -> Option<[f32; 4]> {
+        match self {
+            TextureChannel::All => None,
+            TextureChannel::Red => Some([1.0, 0.0, 0.0, 0.0]),
+            TextureChannel::Green => Some([0.0, 1.0, 0.0, 0.0]),
+            TextureChannel::Blue => Some([0.0, 0.0, 1.0, 0.0]),
+            TextureChannel::Alpha => Some([0.0, 0.0, 0.0, 1.0]),
+        }
+    }
+}
+
+/// Pipeline drawing a fullscreen triangle without vertex buffers.
+/// Its type isn't erased, as only the bufferless definition accepts `BufferlessVertices` in draw calls.
+pub(crate) type BufferlessPipeline = GraphicsPipeline<BufferlessDefinition, Box<PipelineLayoutAbstract + Send + Sync>, Arc<RenderPassAbstract + Send + Sync>>;
+
+/// Offscreen image which previewed texture or mesh is rendered to, along with pipelines drawing them.
+/// Meshes are drawn by pipelines of their vertex format.
+pub struct AssetPreview {
+    target: PreviewTarget,
+    mesh_pipelines: HashMap<VertexFormat, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+    wireframe_pipelines: Option<HashMap<VertexFormat, Arc<GraphicsPipelineAbstract + Send + Sync>>>,
+    texture_pipeline: Arc<BufferlessPipeline>,
+}
+
+impl AssetPreview {
+    /// Creates preview image and everything needed to render to it.
+    /// Wireframe pipeline is created only if device supports non solid fill mode.
+    pub(crate) fn new(device: Arc<Device>, queue: Arc<Queue>, shader_set: &ShaderSet) -> Result<Self, RendererCreationError> {
+        let target = PreviewTarget::new(device.clone(), queue, [ASSET_PREVIEW_SIZE, ASSET_PREVIEW_SIZE])?;
+        let viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [ASSET_PREVIEW_SIZE as f32, ASSET_PREVIEW_SIZE as f32],
+            depth_range: 0.0 .. 1.0,
+        };
+
+        let mut mesh_pipelines = HashMap::new();
+        for &vertex_format in VertexFormat::ALL.iter() {
+            let mesh_pipeline = GraphicsPipeline::start()
+                .vertex_input(shader_set.vertex_layout(vertex_format))
+                .vertex_shader(shader_set.vertex_shader().main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .viewports(std::iter::once(viewport.clone()))
+                .fragment_shader(shader_set.fragment_shader().main_entry_point(), ())
+                .depth_stencil_simple_depth()
+                .render_pass(Subpass::from(target.render_pass(), 0).unwrap())
+                .build(device.clone())?;
+            mesh_pipelines.insert(vertex_format, Arc::new(mesh_pipeline) as Arc<GraphicsPipelineAbstract + Send + Sync>);
+        }
+
+        let wireframe_pipelines = if device.enabled_features().fill_mode_non_solid {
+            let mut wireframe_pipelines = HashMap::new();
+            for &vertex_format in VertexFormat::ALL.iter() {
+                let wireframe_pipeline = GraphicsPipeline::start()
+                    .vertex_input(shader_set.vertex_layout(vertex_format))
+                    .vertex_shader(shader_set.vertex_shader().main_entry_point(), ())
+                    .triangle_list()
+                    .polygon_mode_line()
+                    .viewports_dynamic_scissors_irrelevant(1)
+                    .viewports(std::iter::once(viewport.clone()))
+                    .fragment_shader(shader_set.fragment_shader().main_entry_point(), ())
+                    .depth_stencil_simple_depth()
+                    .render_pass(Subpass::from(target.render_pass(), 0).unwrap())
+                    .build(device.clone())?;
+                wireframe_pipelines.insert(vertex_format, Arc::new(wireframe_pipeline) as Arc<GraphicsPipelineAbstract + Send + Sync>);
+            }
+            Some(wireframe_pipelines)
+        } else {
+            None
+        };
+
+        let texture_pipeline = GraphicsPipeline::start()
+            .vertex_input(BufferlessDefinition)
+            .vertex_shader(shader_set.texture_preview_vertex_shader().main_entry_point(), ())
+            .triangle_list()
+            .viewports_dynamic_scissors_irrelevant(1)
+            .viewports(std::iter::once(viewport))
+            .fragment_shader(shader_set.texture_preview_fragment_shader().main_entry_point(), ())
+            .render_pass(Subpass::from(target.render_pass(), 0).unwrap())
+            .build(device)?;
+
+        Ok(AssetPreview {
+            target,
+            mesh_pipelines,
+            wireframe_pipelines,
+            texture_pipeline: Arc::new(texture_pipeline),
+        })
+    }
+
+    /// Returns image assets are previewed in.
+    pub fn image(&self) -> Arc<ImmutableImage<R8G8B8A8Unorm>> {
+        self.target.image()
+    }
+
+    /// Returns true if meshes can be previewed as wireframe.
+    pub fn supports_wireframe(&self) -> bool {
+        self.wireframe_pipelines.is_some()
+    }
+
+    pub(crate) fn target(&self) -> &PreviewTarget {
+        &self.target
+    }
+
+    /// Returns pipeline drawing meshes of given vertex format, falls back to solid pipeline if wireframe isn't supported.
+    pub(crate) fn mesh_pipeline(&self, wireframe: bool, vertex_format: VertexFormat) -> Arc<GraphicsPipelineAbstract + Send + Sync> {
+        match &self.wireframe_pipelines {
+            Some(wireframe_pipelines) if wireframe => wireframe_pipelines[&vertex_format].clone(),
+            _ => self.mesh_pipelines[&vertex_format].clone(),
+        }
+    }
+
+    pub(crate) fn texture_pipeline(&self) -> Arc<BufferlessPipeline> {
+        self.texture_pipeline.clone()
+    }
+}
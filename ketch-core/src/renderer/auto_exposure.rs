@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::pipeline_layout::PipelineLayout;
+use vulkano::device::Device;
+use vulkano::image::{ImageAccess, SwapchainImage};
+use vulkano::pipeline::ComputePipeline;
+use vulkano::sampler::Sampler;
+use winit::Window;
+
+use crate::profile_scope;
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::luminance_histogram_compute_shader;
+use crate::renderer::shader::luminance_histogram_compute_shader::ty::PushConstants as HistogramPushConstants;
+use crate::resource::camera::AutoExposure;
+
+/// Number of bins of the luminance histogram. The first bin counts pixels too dark to be metered.
+const HISTOGRAM_BIN_COUNT: usize = 64;
+/// Log2 luminance covered by the histogram, matching the compute shader.
+const MIN_LOG_LUMINANCE: f32 = -10.0;
+const LOG_LUMINANCE_RANGE: f32 = 10.0;
+
+/// Number of pixels metered along the longer side of a frame.
+const METERED_SIZE: u32 = 256;
+/// Number of pixels metered by a single compute shader work group along each side.
+const WORK_GROUP_SIZE: u32 = 16;
+/// Number of histograms, so that the histogram read by the CPU was written a few frames ago and the GPU is done with it.
+const HISTOGRAM_COUNT: usize = 3;
+
+type HistogramPipeline = ComputePipeline<PipelineLayout<luminance_histogram_compute_shader::Layout>>;
+
+/// Histogram written by the GPU, along with exposure of the frame it metered.
+struct Histogram {
+    buffer: Arc<CpuAccessibleBuffer<[u32]>>,
+    metered_exposure: Option<f32>,
+}
+
+/// Compute pass metering luminance of rendered frames and adapting exposure to it.
+/// Histograms are read back a few frames later, so exposure follows brightness of the scene with a small delay.
+pub(crate) struct AutoExposurePass {
+    pipeline: Arc<HistogramPipeline>,
+    sampler: Arc<Sampler>,
+    histograms: Vec<Histogram>,
+    next_histogram: usize,
+    // exposure chosen by adaptation, in stops added to exposure of the camera
+    adapted_exposure: f32,
+    // exposure of the current frame, which is metered if it's set
+    frame_exposure: Option<f32>,
+    last_adaptation: Option<Instant>,
+}
+
+impl AutoExposurePass {
+    /// Creates compute pipeline building luminance histograms and buffers they're written to.
+    pub fn new(device: Arc<Device>, shader_set: &ShaderSet) -> Result<Self, RendererCreationError> {
+        let pipeline = Arc::new(ComputePipeline::new(device.clone(), &shader_set.luminance_histogram_compute_shader().main_entry_point(), &())?);
+        let mut histograms = Vec::with_capacity(HISTOGRAM_COUNT);
+        for _ in 0..HISTOGRAM_COUNT {
+            let buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage { storage_buffer: true, ..BufferUsage::none() }, (0..HISTOGRAM_BIN_COUNT).map(|_| 0u32))
+                                                .map_err(RenderError::from)?;
+            histograms.push(Histogram { buffer, metered_exposure: None });
+        }
+
+        Ok(AutoExposurePass {
+            pipeline,
+            sampler: Sampler::simple_repeat_linear_no_mipmap(device),
+            histograms,
+            next_histogram: 0,
+            adapted_exposure: 0.0,
+            frame_exposure: None,
+            last_adaptation: None,
+        })
+    }
+
+    /// Adapts exposure to the oldest metered frame and returns exposure the next frame should be rendered with,
+    /// which is exposure of the camera added to the adapted one. The next frame is metered by `add_commands`.
+    pub fn begin_frame(&mut self, settings: &AutoExposure, camera_exposure: f32) -> f32 {
+        let now = Instant::now();
+        let delta_time = self.last_adaptation.map(|last| duration_seconds(now - last)).unwrap_or(0.0);
+        self.last_adaptation = Some(now);
+
+        let histogram = &mut self.histograms[self.next_histogram];
+        if let Some(metered_exposure) = histogram.metered_exposure {
+            // histogram which is still used by the GPU is read in one of the next frames
+            if let Ok(mut bins) = histogram.buffer.write() {
+                if let Some(frame_log_luminance) = average_log_luminance(&bins) {
+                    // metered frame was already multiplied by its exposure
+                    let scene_log_luminance = frame_log_luminance - metered_exposure;
+                    self.adapted_exposure = settings.adapt(self.adapted_exposure, scene_log_luminance, delta_time);
+                }
+                for bin in bins.iter_mut() {
+                    *bin = 0;
+                }
+                histogram.metered_exposure = None;
+            }
+        }
+
+        let exposure = camera_exposure + self.adapted_exposure;
+        self.frame_exposure = Some(exposure);
+        exposure
+    }
+
+    /// Adds commands metering given swapchain image, if the current frame was started with `begin_frame`.
+    /// Has to be called outside of a render pass, after the frame was drawn.
+    pub fn add_commands(&mut self, command_buffer: AutoCommandBufferBuilder, image: Arc<SwapchainImage<Window>>) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let exposure = match self.frame_exposure.take() {
+            Some(exposure) => exposure,
+            None => return Ok(command_buffer),
+        };
+        let histogram = &mut self.histograms[self.next_histogram];
+        if histogram.metered_exposure.is_some() {
+            // histogram couldn't be read yet, so this frame isn't metered
+            return Ok(command_buffer);
+        }
+        profile_scope!("meter luminance");
+
+        let [width, height] = image.dimensions().width_height();
+        let step = ((width.max(height) + METERED_SIZE - 1) / METERED_SIZE).max(1);
+        let descriptor_set = PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+                                                     .add_sampled_image(image, self.sampler.clone())?
+                                                     .add_buffer(histogram.buffer.clone())?
+                                                     .build()?;
+        let push_constants = HistogramPushConstants {
+            size: [width, height],
+            step,
+        };
+        let work_groups = |size: u32| ((size + step - 1) / step + WORK_GROUP_SIZE - 1) / WORK_GROUP_SIZE;
+        let command_buffer = command_buffer.dispatch([work_groups(width), work_groups(height), 1], self.pipeline.clone(), descriptor_set, push_constants)?;
+
+        histogram.metered_exposure = Some(exposure);
+        self.next_histogram = (self.next_histogram + 1) % self.histograms.len();
+        Ok(command_buffer)
+    }
+}
+
+/// Returns average log2 luminance of pixels counted by the histogram, ignoring pixels too dark to be metered.
+/// Returns None if there are no such pixels.
+fn average_log_luminance(bins: &[u32]) -> Option<f32> {
+    let metered_bins = bins.len() as f32 - 1.0;
+    let (weighted_sum, count) = bins.iter().enumerate().skip(1).fold((0.0, 0u64), |(sum, count), (bin, &pixels)| {
+        let bin_center = MIN_LOG_LUMINANCE + (bin as f32 - 0.5) / metered_bins * LOG_LUMINANCE_RANGE;
+        (sum + bin_center * pixels as f32, count + pixels as u64)
+    });
+    if count == 0 {
+        return None;
+    }
+    Some(weighted_sum / count as f32)
+}
+
+fn duration_seconds(duration: std::time::Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_ignores_black_pixels() {
+        let mut bins = [0; HISTOGRAM_BIN_COUNT];
+        assert_eq!(None, average_log_luminance(&bins));
+
+        bins[0] = 1000;
+        bins[HISTOGRAM_BIN_COUNT - 1] = 10;
+        let brightest = average_log_luminance(&bins).unwrap();
+        assert!(brightest < 0.0 && brightest > -0.2);
+
+        bins[1] = 10;
+        let average = average_log_luminance(&bins).unwrap();
+        assert!((average - MIN_LOG_LUMINANCE / 2.0).abs() < 0.001);
+    }
+}
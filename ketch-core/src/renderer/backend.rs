@@ -0,0 +1,60 @@
+use image::RgbaImage;
+use nalgebra_glm as glm;
+
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::mesh::Vertex;
+
+/// Mesh uploaded to a render backend.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MeshHandle(pub(crate) usize);
+
+/// Texture uploaded to a render backend.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TextureHandle(pub(crate) usize);
+
+/// Single mesh drawn during a frame.
+#[derive(Clone, Copy, Debug)]
+pub struct DrawCommand {
+    pub mesh: MeshHandle,
+    pub texture: TextureHandle,
+    pub model: glm::Mat4,
+    /// Color multiplied with the texture.
+    pub color: [f32; 4],
+}
+
+impl DrawCommand {
+    /// Creates command drawing given mesh with given texture and model matrix, without changing texture colors.
+    pub fn new(mesh: MeshHandle, texture: TextureHandle, model: glm::Mat4) -> Self {
+        DrawCommand {
+            mesh,
+            texture,
+            model,
+            color: [1.0; 4],
+        }
+    }
+}
+
+/// Interface implemented by graphics APIs the engine can render with.
+/// Assets are uploaded once and referred to by handles, and every frame is a list of draws between `begin_frame` and `end_frame`.
+pub trait RenderBackend {
+    type Error: std::error::Error;
+
+    /// Returns name of the graphics API and device used by the backend.
+    fn name(&self) -> String;
+
+    /// Uploads mesh, which can be drawn by all following frames.
+    fn upload_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<MeshHandle, Self::Error>;
+
+    /// Uploads texture, which can be used by all following frames.
+    fn upload_texture(&mut self, image: &RgbaImage) -> Result<TextureHandle, Self::Error>;
+
+    /// Starts a frame seen by given camera and lit by given lights.
+    fn begin_frame(&mut self, camera: &Camera, lights: &[Light]) -> Result<(), Self::Error>;
+
+    /// Adds draw to the current frame. Draws are submitted when the frame ends.
+    fn draw(&mut self, command: DrawCommand);
+
+    /// Submits draws of the current frame and presents it.
+    fn end_frame(&mut self) -> Result<(), Self::Error>;
+}
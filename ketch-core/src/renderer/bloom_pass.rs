@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract, Subpass};
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::image::ImageUsage;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineCreationError};
+use vulkano::pipeline::vertex::{BufferlessDefinition, BufferlessVertices};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::single_pass_renderpass;
+
+use crate::renderer::asset_preview::BufferlessPipeline;
+use crate::renderer::render_scale::ScaledTarget;
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::renderer_stats::RendererStats;
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::bloom_blur_fragment_shader::ty::PushConstants as BlurPushConstants;
+use crate::renderer::shader::bloom_bright_pass_fragment_shader::ty::PushConstants as BrightPassPushConstants;
+use crate::resource::camera::Bloom;
+
+/// Format of the scene rendered with bloom and of bloom images, which keeps colors brighter than white.
+pub(crate) const HDR_FORMAT: Format = Format::R16G16B16A16Sfloat;
+/// Number of times the bloom image is blurred along both axes. Every blur widens the glow.
+const BLUR_ITERATIONS: usize = 2;
+
+/// Returns dimensions of the bloom image of a scene, half of them so that blurring is cheap and reaches further.
+pub(crate) fn bloom_dimensions(scene_dimensions: [u32; 2]) -> [u32; 2] {
+    [(scene_dimensions[0] / 2).max(1), (scene_dimensions[1] / 2).max(1)]
+}
+
+/// Bloom images blurred back and forth, with framebuffers drawing to them.
+struct BloomImages {
+    images: [Arc<AttachmentImage>; 2],
+    framebuffers: [Arc<FramebufferAbstract + Send + Sync>; 2],
+    dimensions: [u32; 2],
+}
+
+/// Passes extracting pixels of the scene brighter than the bloom threshold, like glowing emissive surfaces, and blurring them,
+/// so that the upscaler can add them on top of the scene.
+pub(crate) struct BloomPass {
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    bright_pass_pipeline: Arc<BufferlessPipeline>,
+    blur_pipeline: Arc<BufferlessPipeline>,
+    sampler: Arc<Sampler>,
+    // created for the dimensions of the first scene drawn with bloom
+    images: Option<BloomImages>,
+}
+
+impl BloomPass {
+    /// Creates pipelines of the bright pass and the blur. Their viewports are dynamic, so they don't depend on dimensions of the scene.
+    pub(crate) fn new(device: Arc<Device>, shader_set: &ShaderSet) -> Result<Self, RendererCreationError> {
+        let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
+                                       attachments: {
+                                           color: {
+                                               load: DontCare,
+                                               store: Store,
+                                               format: HDR_FORMAT,
+                                               samples: 1,
+                                           }
+                                       },
+                                       pass: {
+                                           color: [color],
+                                           depth_stencil: {}
+                                       }
+                                   )?) as Arc<RenderPassAbstract + Send + Sync>;
+        let sampler = Sampler::new(device.clone(), Filter::Linear, Filter::Linear, MipmapMode::Nearest, SamplerAddressMode::ClampToEdge,
+                                   SamplerAddressMode::ClampToEdge, SamplerAddressMode::ClampToEdge, 0.0, 1.0, 0.0, 0.0).map_err(RenderError::from)?;
+        Ok(BloomPass {
+            bright_pass_pipeline: create_bright_pass_pipeline(device.clone(), shader_set, render_pass.clone())?,
+            blur_pipeline: create_blur_pipeline(device, shader_set, render_pass.clone())?,
+            render_pass,
+            sampler,
+            images: None,
+        })
+    }
+
+    /// Adds commands drawing bloom of the scene rendered to the target, outside of render passes. Returns the blurred bloom image.
+    pub(crate) fn add_commands(&mut self, device: Arc<Device>, mut command_buffer: AutoCommandBufferBuilder, target: &ScaledTarget, bloom: &Bloom,
+                               stats: &mut RendererStats) -> Result<(AutoCommandBufferBuilder, Arc<AttachmentImage>), RenderError> {
+        let dimensions = bloom_dimensions(target.dimensions());
+        if self.images.as_ref().map(|images| images.dimensions) != Some(dimensions) {
+            self.images = Some(self.create_images(device, dimensions)?);
+        }
+        let images = self.images.as_ref().unwrap();
+        let dynamic_state = DynamicState {
+            viewports: Some(vec![Viewport {
+                origin: [0.0, 0.0],
+                dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+                depth_range: 0.0 .. 1.0,
+            }]),
+            ..DynamicState::none()
+        };
+        let vertices = || BufferlessVertices { vertices: 3, instances: 1 };
+
+        let [scene_width, scene_height] = target.dimensions();
+        let descriptor_set = PersistentDescriptorSet::start(self.bright_pass_pipeline.clone(), 0)
+                                                     .add_sampled_image(target.color(), self.sampler.clone())?
+                                                     .build()?;
+        let push_constants = BrightPassPushConstants {
+            texel_size: [1.0 / scene_width as f32, 1.0 / scene_height as f32],
+            threshold: bloom.threshold.max(0.0),
+        };
+        command_buffer = command_buffer.begin_render_pass(images.framebuffers[0].clone(), false, vec![ClearValue::None])?
+                                       .draw(self.bright_pass_pipeline.clone(), &dynamic_state, vertices(), descriptor_set, push_constants)?
+                                       .end_render_pass()?;
+        stats.draw_calls += 1;
+
+        // separable blur, horizontal from the first image to the second and vertical back to the first
+        let texel_size = [1.0 / dimensions[0] as f32, 1.0 / dimensions[1] as f32];
+        for _ in 0..BLUR_ITERATIONS {
+            for &(source, destination, direction) in &[(0, 1, [texel_size[0], 0.0]), (1, 0, [0.0, texel_size[1]])] {
+                let descriptor_set = PersistentDescriptorSet::start(self.blur_pipeline.clone(), 0)
+                                                             .add_sampled_image(images.images[source].clone(), self.sampler.clone())?
+                                                             .build()?;
+                command_buffer = command_buffer.begin_render_pass(images.framebuffers[destination].clone(), false, vec![ClearValue::None])?
+                                               .draw(self.blur_pipeline.clone(), &dynamic_state, vertices(), descriptor_set, BlurPushConstants { direction })?
+                                               .end_render_pass()?;
+                stats.draw_calls += 1;
+            }
+        }
+        Ok((command_buffer, images.images[0].clone()))
+    }
+
+    fn create_images(&self, device: Arc<Device>, dimensions: [u32; 2]) -> Result<BloomImages, RenderError> {
+        let usage = ImageUsage {
+            sampled: true,
+            color_attachment: true,
+            ..ImageUsage::none()
+        };
+        let image = || AttachmentImage::with_usage(device.clone(), dimensions, HDR_FORMAT, usage);
+        let images = [image()?, image()?];
+        let framebuffer = |image: &Arc<AttachmentImage>| -> Result<Arc<FramebufferAbstract + Send + Sync>, RenderError> {
+            Ok(Arc::new(Framebuffer::start(self.render_pass.clone()).add(image.clone())?.build()?))
+        };
+        let framebuffers = [framebuffer(&images[0])?, framebuffer(&images[1])?];
+        Ok(BloomImages {
+            images,
+            framebuffers,
+            dimensions,
+        })
+    }
+}
+
+fn create_bright_pass_pipeline(device: Arc<Device>, shader_set: &ShaderSet,
+                               render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Arc<BufferlessPipeline>, GraphicsPipelineCreationError> {
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(BufferlessDefinition)
+        .vertex_shader(shader_set.upscale_vertex_shader().main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(shader_set.bloom_bright_pass_fragment_shader().main_entry_point(), ())
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device)?;
+    Ok(Arc::new(pipeline))
+}
+
+fn create_blur_pipeline(device: Arc<Device>, shader_set: &ShaderSet,
+                        render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Arc<BufferlessPipeline>, GraphicsPipelineCreationError> {
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(BufferlessDefinition)
+        .vertex_shader(shader_set.upscale_vertex_shader().main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(shader_set.bloom_blur_fragment_shader().main_entry_point(), ())
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device)?;
+    Ok(Arc::new(pipeline))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bloom_has_half_resolution_of_the_scene() {
+        assert_eq!([960, 540], bloom_dimensions([1920, 1080]));
+        assert_eq!([1, 1], bloom_dimensions([1, 3]));
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use vulkano::device::{Device, Queue};
+use vulkano::format::R8G8B8A8Unorm;
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::pipeline::GraphicsPipelineAbstract;
+
+use crate::renderer::{create_pipelines, PipelineKey};
+use crate::renderer::offscreen_target::PreviewTarget;
+use crate::renderer::renderer_error::RendererCreationError;
+use crate::renderer::shader::ShaderSet;
+
+/// Width of camera preview image in pixels.
+pub const CAMERA_PREVIEW_WIDTH: u32 = 320;
+/// Height of camera preview image in pixels.
+pub const CAMERA_PREVIEW_HEIGHT: u32 = 180;
+
+/// Offscreen image which the scene is rendered to as seen by a game camera, along with pipelines drawing it.
+pub struct CameraPreview {
+    target: PreviewTarget,
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+}
+
+impl CameraPreview {
+    /// Creates preview image and pipelines for every material state.
+    pub(crate) fn new(device: Arc<Device>, queue: Arc<Queue>, shader_set: Arc<ShaderSet>) -> Result<Self, RendererCreationError> {
+        let dimensions = [CAMERA_PREVIEW_WIDTH, CAMERA_PREVIEW_HEIGHT];
+        let target = PreviewTarget::new(device.clone(), queue, dimensions)?;
+        let pipelines = create_pipelines(device, shader_set, dimensions, target.render_pass())?;
+
+        Ok(CameraPreview {
+            target,
+            pipelines,
+        })
+    }
+
+    /// Returns image camera preview is shown in.
+    pub fn image(&self) -> Arc<ImmutableImage<R8G8B8A8Unorm>> {
+        self.target.image()
+    }
+
+    pub(crate) fn target(&self) -> &PreviewTarget {
+        &self.target
+    }
+
+    pub(crate) fn pipelines(&self) -> &HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>> {
+        &self.pipelines
+    }
+}
@@ -0,0 +1,109 @@
+use vulkano::format::Format;
+use vulkano::instance::{loader, PhysicalDevice, PhysicalDeviceType};
+use vulkano::VulkanObject;
+
+/// Depth formats which are checked for depth attachment support.
+const DEPTH_FORMATS: [Format; 5] = [
+    Format::D16Unorm,
+    Format::X8_D24UnormPack32,
+    Format::D32Sfloat,
+    Format::D24Unorm_S8Uint,
+    Format::D32Sfloat_S8Uint,
+];
+
+/// Limits and features of the device used by the renderer, which games can use to pick quality settings.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RendererCapabilities {
+    /// Name of the device.
+    pub device_name: String,
+    pub device_type: PhysicalDeviceType,
+    /// Largest width and height of 2D textures, in pixels.
+    pub max_texture_size: u32,
+    /// Sample counts supported by both color and depth attachments, in ascending order. Always contains 1.
+    pub msaa_sample_counts: Vec<u32>,
+    /// Largest anisotropy of texture samplers, or None if anisotropic filtering isn't supported.
+    pub max_anisotropy: Option<f32>,
+    /// Formats which can be used as depth attachments.
+    pub depth_formats: Vec<Format>,
+}
+
+impl RendererCapabilities {
+    /// Queries capabilities of given physical device.
+    pub fn query(physical_device: PhysicalDevice) -> Self {
+        let limits = physical_device.limits();
+        let sample_count_mask = limits.framebuffer_color_sample_counts() & limits.framebuffer_depth_sample_counts();
+        let max_anisotropy = if physical_device.supported_features().sampler_anisotropy {
+            Some(limits.max_sampler_anisotropy())
+        } else {
+            None
+        };
+        let depth_formats = DEPTH_FORMATS.iter().cloned()
+                                         .filter(|format| supports_depth_attachment(physical_device, *format))
+                                         .collect();
+
+        RendererCapabilities {
+            device_name: physical_device.name(),
+            device_type: physical_device.ty(),
+            max_texture_size: limits.max_image_dimension_2d(),
+            msaa_sample_counts: sample_counts(sample_count_mask),
+            max_anisotropy,
+            depth_formats,
+        }
+    }
+
+    /// Returns highest supported sample count which isn't bigger than given one.
+    pub fn max_msaa_samples(&self, requested: u32) -> u32 {
+        self.msaa_sample_counts.iter().cloned().filter(|&samples| samples <= requested).max().unwrap_or(1)
+    }
+}
+
+/// Returns true if optimally tiled images of given format can be used as depth attachments.
+/// vulkano doesn't expose format properties, so they're queried through the Vulkan loader like vulkano does internally.
+fn supports_depth_attachment(physical_device: PhysicalDevice, format: Format) -> bool {
+    let function_pointers = match loader::auto_loader() {
+        Ok(function_pointers) => function_pointers,
+        Err(_) => return false,
+    };
+    let instance = physical_device.instance().internal_object();
+    let pointers = vk_sys::InstancePointers::load(|name| unsafe {
+        std::mem::transmute(function_pointers.get_instance_proc_addr(instance, name.as_ptr()))
+    });
+    let properties = unsafe {
+        let mut properties = std::mem::zeroed::<vk_sys::FormatProperties>();
+        pointers.GetPhysicalDeviceFormatProperties(physical_device.internal_object(), format as u32, &mut properties);
+        properties
+    };
+    properties.optimalTilingFeatures & vk_sys::FORMAT_FEATURE_DEPTH_STENCIL_ATTACHMENT_BIT != 0
+}
+
+/// Converts mask of Vulkan sample count flags to sample counts. Every flag bit is equal to the count it stands for.
+fn sample_counts(mask: u32) -> Vec<u32> {
+    let mut counts: Vec<u32> = (0..7).map(|bit| 1 << bit).filter(|count| mask & count != 0).collect();
+    if !counts.contains(&1) {
+        counts.insert(0, 1);
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_counts_are_read_from_mask() {
+        assert_eq!(vec![1, 2, 4, 8], sample_counts(0b1111));
+        assert_eq!(vec![1, 4], sample_counts(0b0100));
+
+        let capabilities = RendererCapabilities {
+            device_name: "test".to_string(),
+            device_type: PhysicalDeviceType::Cpu,
+            max_texture_size: 4096,
+            msaa_sample_counts: vec![1, 2, 4],
+            max_anisotropy: None,
+            depth_formats: vec![Format::D16Unorm],
+        };
+        assert_eq!(4, capabilities.max_msaa_samples(8));
+        assert_eq!(2, capabilities.max_msaa_samples(3));
+        assert_eq!(1, capabilities.max_msaa_samples(0));
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use vulkano::buffer::BufferUsage;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DrawIndexedIndirectCommand};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::pipeline_layout::PipelineLayout;
+use vulkano::device::Device;
+use vulkano::pipeline::ComputePipeline;
+
+use crate::math::bounds::Bounds;
+use crate::math::frustum::Frustum;
+use crate::profile_scope;
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::culling_compute_shader;
+use crate::renderer::shader::culling_compute_shader::ty::{ObjectBounds, PushConstants as CullingPushConstants};
+use crate::renderer::uniform_manager::{UniformBuffer, UniformFrame, UniformRing};
+
+/// Number of objects culled by a single compute shader work group.
+const WORK_GROUP_SIZE: u32 = 64;
+
+type CullingPipeline = ComputePipeline<PipelineLayout<culling_compute_shader::Layout>>;
+
+/// Object culled on the GPU, with bounds of its mesh and location of its indices and vertices in their memory blocks.
+pub(crate) struct CulledObject {
+    pub bounds: Bounds,
+    pub index_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: u32,
+}
+
+/// Compute pass culling object bounds against the camera frustum on the GPU.
+/// It writes one indirect draw command per object, with instance count set to zero for objects outside of the frustum,
+/// so objects are culled without reading results back and without recording a draw call per object.
+/// Commands address meshes inside their memory blocks, so a single indirect draw can cover objects with different meshes.
+pub(crate) struct CullingPass {
+    device: Arc<Device>,
+    pipeline: Arc<CullingPipeline>,
+    bounds_ring: UniformRing<ObjectBounds>,
+    command_ring: UniformRing<DrawIndexedIndirectCommand>,
+}
+
+impl CullingPass {
+    /// Creates compute pipeline culling objects. Draw commands are shared with queues of given families, because they're read by graphics queue.
+    pub fn new(device: Arc<Device>, shader_set: &ShaderSet, compute_sharing_families: Vec<u32>) -> Result<Self, RendererCreationError> {
+        let pipeline = Arc::new(ComputePipeline::new(device.clone(), &shader_set.culling_compute_shader().main_entry_point(), &())?);
+
+        Ok(CullingPass {
+            device,
+            pipeline,
+            bounds_ring: UniformRing::new(BufferUsage { storage_buffer: true, ..BufferUsage::none() }),
+            command_ring: UniformRing::shared(BufferUsage { storage_buffer: true, indirect_buffer: true, ..BufferUsage::none() }, compute_sharing_families),
+        })
+    }
+
+    /// Adds commands culling objects uploaded with given uniform frame, in the order they were pushed to uniform manager.
+    /// Has to be called outside of a render pass, either with a graphics command buffer or with a compute one.
+    /// Returns buffer of draw commands and number of buffers which had to be allocated.
+    pub fn add_commands(&mut self, command_buffer: AutoCommandBufferBuilder, frustum: &Frustum, uniform_frame: &UniformFrame,
+                        objects: &[CulledObject]) -> Result<(AutoCommandBufferBuilder, UniformBuffer<DrawIndexedIndirectCommand>, u32), RenderError> {
+        profile_scope!("gpu culling");
+        let bounds: Vec<ObjectBounds> = objects.iter().map(|object| {
+            let (min, max) = (object.bounds.min(), object.bounds.max());
+            ObjectBounds {
+                min: [min.x, min.y, min.z, 1.0],
+                max: [max.x, max.y, max.z, 1.0],
+            }
+        }).collect();
+        // every command draws a single instance of the object with its index, unless the compute shader culls it
+        let commands: Vec<DrawIndexedIndirectCommand> = objects.iter().enumerate().map(|(object_index, object)| DrawIndexedIndirectCommand {
+            index_count: object.index_count,
+            instance_count: 1,
+            first_index: object.first_index,
+            vertex_offset: object.vertex_offset,
+            first_instance: object_index as u32,
+        }).collect();
+
+        let (bounds, bounds_allocated) = self.bounds_ring.upload(self.device.clone(), &bounds)?;
+        let (commands, commands_allocated) = self.command_ring.upload(self.device.clone(), &commands)?;
+
+        let descriptor_set = PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+                                                     .add_buffer(uniform_frame.transformations())?
+                                                     .add_buffer(bounds)?
+                                                     .add_buffer(commands.clone())?
+                                                     .build()?;
+
+        let mut planes = [[0.0; 4]; 6];
+        for (plane, frustum_plane) in planes.iter_mut().zip(frustum.planes().iter()) {
+            *plane = [frustum_plane.x, frustum_plane.y, frustum_plane.z, frustum_plane.w];
+        }
+        let push_constants = CullingPushConstants {
+            planes,
+            object_count: objects.len() as u32,
+        };
+
+        let work_groups = (objects.len() as u32 + WORK_GROUP_SIZE - 1) / WORK_GROUP_SIZE;
+        let command_buffer = command_buffer.dispatch([work_groups, 1, 1], self.pipeline.clone(), descriptor_set, push_constants)?;
+
+        Ok((command_buffer, commands, bounds_allocated as u32 + commands_allocated as u32))
+    }
+}
@@ -0,0 +1,120 @@
+use nalgebra_glm as glm;
+use nalgebra_glm::{U3, Vec3};
+
+#[cfg(feature = "vulkan")]
+use vulkano::impl_vertex;
+
+use crate::resource::camera::Camera;
+
+pub(crate) mod stroke_font;
+
+/// Height of text labels on screen in pixels, which doesn't depend on their distance from the camera.
+const LABEL_HEIGHT: f32 = 12.0;
+/// Distance in pixels between a labelled point and the bottom of its label.
+const LABEL_OFFSET: f32 = 4.0;
+
+/// Vertex of a debug line.
+#[derive(Clone, Copy)]
+pub struct DebugVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(DebugVertex, position, color);
+
+/// Text shown above a point in world space.
+struct Label {
+    position: Vec3,
+    text: String,
+    color: Vec3,
+}
+
+/// Collects lines and text labels drawn on top of the scene for one frame, used by editor helpers and debugging.
+/// Lines and labels are cleared after every rendered frame.
+pub struct DebugDraw {
+    vertices: Vec<DebugVertex>,
+    labels: Vec<Label>,
+}
+
+impl DebugDraw {
+    /// Creates new empty debug draw.
+    pub fn new() -> Self {
+        DebugDraw {
+            vertices: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Adds a line going from one point to another.
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: Vec3) {
+        self.line_gradient(from, color, to, color);
+    }
+
+    /// Adds a line with color interpolated between its ends.
+    pub fn line_gradient(&mut self, from: Vec3, from_color: Vec3, to: Vec3, to_color: Vec3) {
+        self.vertices.push(DebugVertex { position: from.into(), color: from_color.into() });
+        self.vertices.push(DebugVertex { position: to.into(), color: to_color.into() });
+    }
+
+    /// Adds white text label above a point, e.g. showing state of an AI agent or name of a spawn point.
+    pub fn text_3d(&mut self, position: Vec3, text: &str) {
+        self.text_3d_colored(position, text, Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    /// Adds text label above a point. Labels face the camera and have the same size on screen at any distance.
+    /// Lines of the text are separated with newlines, letters are shown as capitals.
+    pub fn text_3d_colored(&mut self, position: Vec3, text: &str, color: Vec3) {
+        self.labels.push(Label { position, text: text.to_string(), color });
+    }
+
+    /// Returns true if there is nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty() && self.labels.is_empty()
+    }
+
+    /// Returns vertices of all lines, two for every line.
+    pub fn vertices(&self) -> &[DebugVertex] {
+        &self.vertices
+    }
+
+    /// Returns vertices of lines drawing the labels as seen by the camera in a viewport of given height in pixels.
+    /// Labels behind the camera are skipped.
+    pub fn label_vertices(&self, camera: &Camera, viewport_height: f32) -> Vec<DebugVertex> {
+        let camera_position = camera.position_vec3();
+        let front = camera.front();
+        let right = camera.right();
+        let up = glm::normalize(&glm::cross::<f32, U3>(&right, &front));
+        // height of a pixel in world space at a distance of one unit in front of the camera
+        let pixel_size = 2.0 * (camera.fov() * 0.5).to_radians().tan() / viewport_height.max(1.0);
+
+        let mut vertices = Vec::new();
+        for label in &self.labels {
+            let distance = glm::dot(&(label.position - camera_position), &front);
+            if distance <= camera.near_plane() {
+                continue;
+            }
+            let unit = pixel_size * distance * LABEL_HEIGHT / stroke_font::GLYPH_HEIGHT;
+            let origin = label.position + up * (pixel_size * distance * LABEL_OFFSET);
+            for (from, to) in stroke_font::text_segments(&label.text, true) {
+                for point in &[from, to] {
+                    let position = origin + right * (point.x * unit) + up * (point.y * unit);
+                    vertices.push(DebugVertex { position: position.into(), color: label.color.into() });
+                }
+            }
+        }
+        vertices
+    }
+
+    /// Removes all lines and labels.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.labels.clear();
+    }
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        DebugDraw::new()
+    }
+}
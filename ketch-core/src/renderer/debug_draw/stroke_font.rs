@@ -0,0 +1,132 @@
+use nalgebra_glm::Vec2;
+
+/// Height of a glyph in grid units. Glyphs are drawn with lines between points of a grid 4 units wide.
+pub(crate) const GLYPH_HEIGHT: f32 = 6.0;
+/// Horizontal distance between starts of consecutive glyphs.
+const GLYPH_ADVANCE: f32 = 6.0;
+/// Vertical distance between baselines of consecutive lines of text.
+const LINE_ADVANCE: f32 = 9.0;
+
+type Glyph = &'static [&'static [(u8, u8)]];
+
+/// Glyph drawn for characters without one of their own.
+const MISSING_GLYPH: Glyph = &[&[(0, 0), (0, 6), (4, 6), (4, 0), (0, 0)]];
+
+/// Returns polylines drawing the character, with the origin in the bottom left corner and y going up.
+/// Letters are drawn as capitals.
+fn glyph(character: char) -> Glyph {
+    match character.to_ascii_uppercase() {
+        ' ' => &[],
+        'A' => &[&[(0, 0), (0, 4), (2, 6), (4, 4), (4, 0)], &[(0, 3), (4, 3)]],
+        'B' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)], &[(3, 3), (4, 2), (4, 1), (3, 0), (0, 0)]],
+        'C' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0), (3, 0), (4, 1)]],
+        'D' => &[&[(0, 0), (0, 6), (2, 6), (4, 4), (4, 2), (2, 0), (0, 0)]],
+        'E' => &[&[(4, 6), (0, 6), (0, 0), (4, 0)], &[(0, 3), (3, 3)]],
+        'F' => &[&[(4, 6), (0, 6), (0, 0)], &[(0, 3), (3, 3)]],
+        'G' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0), (3, 0), (4, 1), (4, 3), (2, 3)]],
+        'H' => &[&[(0, 0), (0, 6)], &[(4, 0), (4, 6)], &[(0, 3), (4, 3)]],
+        'I' => &[&[(1, 6), (3, 6)], &[(2, 6), (2, 0)], &[(1, 0), (3, 0)]],
+        'J' => &[&[(4, 6), (4, 1), (3, 0), (1, 0), (0, 1)]],
+        'K' => &[&[(0, 0), (0, 6)], &[(4, 6), (0, 2)], &[(1, 3), (4, 0)]],
+        'L' => &[&[(0, 6), (0, 0), (4, 0)]],
+        'M' => &[&[(0, 0), (0, 6), (2, 3), (4, 6), (4, 0)]],
+        'N' => &[&[(0, 0), (0, 6), (4, 0), (4, 6)]],
+        'O' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)]],
+        'P' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)]],
+        'Q' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)], &[(2, 2), (4, 0)]],
+        'R' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)], &[(2, 3), (4, 0)]],
+        'S' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 4), (1, 3), (3, 3), (4, 2), (4, 1), (3, 0), (1, 0), (0, 1)]],
+        'T' => &[&[(0, 6), (4, 6)], &[(2, 6), (2, 0)]],
+        'U' => &[&[(0, 6), (0, 1), (1, 0), (3, 0), (4, 1), (4, 6)]],
+        'V' => &[&[(0, 6), (2, 0), (4, 6)]],
+        'W' => &[&[(0, 6), (1, 0), (2, 3), (3, 0), (4, 6)]],
+        'X' => &[&[(0, 0), (4, 6)], &[(0, 6), (4, 0)]],
+        'Y' => &[&[(0, 6), (2, 3), (4, 6)], &[(2, 3), (2, 0)]],
+        'Z' => &[&[(0, 6), (4, 6), (0, 0), (4, 0)]],
+        '0' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)], &[(0, 1), (4, 5)]],
+        '1' => &[&[(1, 5), (2, 6), (2, 0)], &[(1, 0), (3, 0)]],
+        '2' => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (0, 0), (4, 0)]],
+        '3' => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (3, 3), (1, 3)], &[(3, 3), (4, 2), (4, 1), (3, 0), (1, 0), (0, 1)]],
+        '4' => &[&[(3, 0), (3, 6), (0, 2), (4, 2)]],
+        '5' => &[&[(4, 6), (0, 6), (0, 3), (3, 3), (4, 2), (4, 1), (3, 0), (0, 0)]],
+        '6' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (3, 3), (0, 3)]],
+        '7' => &[&[(0, 6), (4, 6), (1, 0)]],
+        '8' => &[&[(1, 3), (0, 4), (0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (3, 3), (1, 3), (0, 2), (0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (3, 3)]],
+        '9' => &[&[(4, 3), (1, 3), (0, 4), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0), (0, 1)]],
+        '.' => &[&[(2, 0), (2, 1)]],
+        ',' => &[&[(2, 1), (1, 0)]],
+        ':' => &[&[(2, 1), (2, 2)], &[(2, 4), (2, 5)]],
+        '-' => &[&[(1, 3), (3, 3)]],
+        '_' => &[&[(0, 0), (4, 0)]],
+        '+' => &[&[(1, 3), (3, 3)], &[(2, 2), (2, 4)]],
+        '=' => &[&[(1, 2), (3, 2)], &[(1, 4), (3, 4)]],
+        '*' => &[&[(1, 2), (3, 4)], &[(1, 4), (3, 2)], &[(2, 2), (2, 4)]],
+        '/' => &[&[(0, 0), (4, 6)]],
+        '\\' => &[&[(0, 6), (4, 0)]],
+        '(' => &[&[(3, 6), (2, 5), (2, 1), (3, 0)]],
+        ')' => &[&[(1, 6), (2, 5), (2, 1), (1, 0)]],
+        '[' => &[&[(3, 6), (2, 6), (2, 0), (3, 0)]],
+        ']' => &[&[(1, 6), (2, 6), (2, 0), (1, 0)]],
+        '<' => &[&[(4, 6), (0, 3), (4, 0)]],
+        '>' => &[&[(0, 6), (4, 3), (0, 0)]],
+        '!' => &[&[(2, 6), (2, 2)], &[(2, 1), (2, 0)]],
+        '?' => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (2, 3), (2, 2)], &[(2, 1), (2, 0)]],
+        '#' => &[&[(1, 0), (1, 6)], &[(3, 0), (3, 6)], &[(0, 2), (4, 2)], &[(0, 4), (4, 4)]],
+        '%' => &[&[(0, 0), (4, 6)], &[(0, 6), (0, 5)], &[(4, 1), (4, 0)]],
+        '\'' => &[&[(2, 6), (2, 4)]],
+        '"' => &[&[(1, 6), (1, 4)], &[(3, 6), (3, 4)]],
+        _ => MISSING_GLYPH,
+    }
+}
+
+/// Returns line segments drawing the text in grid units, with the origin at the bottom of the last line, so that the text sits above it.
+/// Lines of the text are centered horizontally on the origin, or start at it if they aren't centered.
+pub(crate) fn text_segments(text: &str, centered: bool) -> Vec<(Vec2, Vec2)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut segments = Vec::new();
+    for (line_index, line) in lines.iter().enumerate() {
+        let start = if centered { -line_width(line) * 0.5 } else { 0.0 };
+        let baseline = (lines.len() - 1 - line_index) as f32 * LINE_ADVANCE;
+        for (index, character) in line.chars().enumerate() {
+            let origin = Vec2::new(start + index as f32 * GLYPH_ADVANCE, baseline);
+            let point = |(x, y): (u8, u8)| origin + Vec2::new(f32::from(x), f32::from(y));
+            for polyline in glyph(character) {
+                for pair in polyline.windows(2) {
+                    segments.push((point(pair[0]), point(pair[1])));
+                }
+            }
+        }
+    }
+    segments
+}
+
+/// Returns width and height of the text in grid units.
+pub(crate) fn text_size(text: &str) -> (f32, f32) {
+    let width = text.lines().map(line_width).fold(0.0, f32::max);
+    let lines = text.lines().count().max(1);
+    (width, (lines - 1) as f32 * LINE_ADVANCE + GLYPH_HEIGHT)
+}
+
+fn line_width(line: &str) -> f32 {
+    // glyphs are 4 units wide, so the last one doesn't count its spacing
+    (line.chars().count() as f32 * GLYPH_ADVANCE - (GLYPH_ADVANCE - 4.0)).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_centered_above_origin() {
+        let segments = text_segments("T\nii", true);
+
+        // T is the only glyph of the first line, two lines above the origin
+        assert_eq!((Vec2::new(-2.0, 15.0), Vec2::new(2.0, 15.0)), segments[0]);
+        // lowercase letters are drawn as capitals, the second line is 10 units wide
+        assert_eq!((Vec2::new(-4.0, 6.0), Vec2::new(-2.0, 6.0)), segments[2]);
+        assert_eq!(2 + 3 * 2, segments.len());
+        assert_eq!(MISSING_GLYPH, glyph('ł'));
+        assert_eq!((10.0, 15.0), text_size("T\nii"));
+        assert_eq!((Vec2::new(0.0, 15.0), Vec2::new(4.0, 15.0)), text_segments("T\nii", false)[0]);
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+
+/// Environment variable which, when set to `1`, makes `assert_golden_image` write rendered images as new references.
+pub const UPDATE_GOLDEN_IMAGES_VARIABLE: &str = "KETCH_UPDATE_GOLDEN";
+
+/// Largest possible difference of two pixels returned by `pixel_difference`.
+const MAX_PIXEL_DIFFERENCE: f32 = 35215.0;
+
+/// How much a rendered image may differ from its reference image.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GoldenTolerance {
+    /// Perceptual difference of two pixels, from 0 to 1, above which they're considered different.
+    pub pixel_threshold: f32,
+    /// Fraction of pixels, from 0 to 1, which are allowed to differ.
+    pub max_differing_pixels: f32,
+}
+
+impl Default for GoldenTolerance {
+    fn default() -> Self {
+        GoldenTolerance {
+            pixel_threshold: 0.1,
+            max_differing_pixels: 0.001,
+        }
+    }
+}
+
+/// Result of comparing rendered image with its reference image.
+pub struct ImageComparison {
+    pub differing_pixels: u32,
+    pub total_pixels: u32,
+    /// Reference image faded to gray, with differing pixels marked red.
+    pub diff_image: RgbaImage,
+}
+
+impl ImageComparison {
+    /// Returns fraction of pixels which differ.
+    pub fn differing_fraction(&self) -> f32 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+        self.differing_pixels as f32 / self.total_pixels as f32
+    }
+
+    /// Returns true if images are within given tolerance.
+    pub fn passes(&self, tolerance: &GoldenTolerance) -> bool {
+        self.differing_fraction() <= tolerance.max_differing_pixels
+    }
+}
+
+/// Compares images of the same dimensions pixel by pixel. Pixels are compared in the YIQ color space,
+/// where differences of brightness weigh more than differences of hue, which is closer to how they're perceived than RGB distance.
+pub fn compare_images(expected: &RgbaImage, actual: &RgbaImage, tolerance: &GoldenTolerance) -> ImageComparison {
+    assert_eq!(expected.dimensions(), actual.dimensions(), "Compared images have different dimensions");
+    let max_difference = MAX_PIXEL_DIFFERENCE * tolerance.pixel_threshold * tolerance.pixel_threshold;
+    let mut differing_pixels = 0;
+    let diff_image = RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let expected_pixel = *expected.get_pixel(x, y);
+        if pixel_difference(expected_pixel, *actual.get_pixel(x, y)) > max_difference {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let (brightness, _, _) = yiq(expected_pixel);
+            let faded = (255.0 - (255.0 - brightness) * 0.2) as u8;
+            Rgba([faded, faded, faded, 255])
+        }
+    });
+
+    ImageComparison {
+        differing_pixels,
+        total_pixels: expected.width() * expected.height(),
+        diff_image,
+    }
+}
+
+/// Checks that rendered image matches reference PNG at given path, and panics if it doesn't.
+/// On failure the rendered image and the diff image are written next to the reference, with `.actual.png` and `.diff.png` extensions.
+/// When `KETCH_UPDATE_GOLDEN` is set to `1`, the rendered image is written as the reference instead.
+pub fn assert_golden_image<P: AsRef<Path>>(actual: &RgbaImage, reference_path: P, tolerance: &GoldenTolerance) {
+    let reference_path = reference_path.as_ref();
+    if std::env::var(UPDATE_GOLDEN_IMAGES_VARIABLE).map(|value| value == "1").unwrap_or(false) {
+        save_image(actual, reference_path);
+        return;
+    }
+
+    let expected = match image::open(reference_path) {
+        Ok(image) => image.to_rgba(),
+        Err(err) => panic!("Can't read reference image {}: {}. Run with {}=1 to create it", reference_path.display(), err, UPDATE_GOLDEN_IMAGES_VARIABLE),
+    };
+    let actual_path = sibling_path(reference_path, "actual");
+    if expected.dimensions() != actual.dimensions() {
+        save_image(actual, &actual_path);
+        panic!("Rendered image is {:?}, but reference image {} is {:?}. Rendered image was written to {}",
+               actual.dimensions(), reference_path.display(), expected.dimensions(), actual_path.display());
+    }
+
+    let comparison = compare_images(&expected, actual, tolerance);
+    if !comparison.passes(tolerance) {
+        let diff_path = sibling_path(reference_path, "diff");
+        save_image(actual, &actual_path);
+        save_image(&comparison.diff_image, &diff_path);
+        panic!("{} of {} pixels differ from reference image {}. Rendered image was written to {} and diff to {}",
+               comparison.differing_pixels, comparison.total_pixels, reference_path.display(), actual_path.display(), diff_path.display());
+    }
+}
+
+fn save_image(image: &RgbaImage, path: &Path) {
+    if let Some(directory) = path.parent() {
+        if let Err(err) = fs::create_dir_all(directory) {
+            panic!("Can't create directory {}: {}", directory.display(), err);
+        }
+    }
+    if let Err(err) = image.save(path) {
+        panic!("Can't write image {}: {}", path.display(), err);
+    }
+}
+
+/// Returns path of a PNG in the same directory, named after the given file with given suffix.
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+/// Returns weighted squared distance of pixels in the YIQ color space.
+fn pixel_difference(first: Rgba<u8>, second: Rgba<u8>) -> f32 {
+    let (y1, i1, q1) = yiq(first);
+    let (y2, i2, q2) = yiq(second);
+    let (dy, di, dq) = (y1 - y2, i1 - i2, q1 - q2);
+    0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq
+}
+
+/// Converts pixel blended over white background to the YIQ color space.
+fn yiq(pixel: Rgba<u8>) -> (f32, f32, f32) {
+    let [r, g, b, a] = pixel.data;
+    let alpha = a as f32 / 255.0;
+    let blend = |channel: u8| 255.0 + (channel as f32 - 255.0) * alpha;
+    let (r, g, b) = (blend(r), blend(g), blend(b));
+    (
+        r * 0.298_895 + g * 0.586_622 + b * 0.114_482,
+        r * 0.595_978 - g * 0.274_176 - b * 0.321_802,
+        r * 0.211_470 - g * 0.522_617 + b * 0.311_147,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_differences_are_tolerated() {
+        let expected = RgbaImage::from_pixel(10, 10, Rgba([100, 150, 200, 255]));
+        let mut actual = RgbaImage::from_pixel(10, 10, Rgba([101, 149, 201, 255]));
+        let tolerance = GoldenTolerance::default();
+        assert_eq!(0, compare_images(&expected, &actual, &tolerance).differing_pixels);
+
+        actual.put_pixel(3, 4, Rgba([0, 0, 0, 255]));
+        let comparison = compare_images(&expected, &actual, &tolerance);
+        assert_eq!(1, comparison.differing_pixels);
+        assert_eq!(Rgba([255, 0, 0, 255]), *comparison.diff_image.get_pixel(3, 4));
+        assert!(!comparison.passes(&tolerance));
+        assert!(comparison.passes(&GoldenTolerance { max_differing_pixels: 0.01, ..tolerance }));
+    }
+
+    /// Returns empty directory for images written by a test.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("ketch_golden_image_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't read reference image")]
+    fn missing_reference_fails() {
+        let reference_path = test_directory("missing").join("missing.png");
+
+        assert_golden_image(&RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])), reference_path, &GoldenTolerance::default());
+    }
+
+    #[test]
+    fn differing_image_writes_actual_and_diff_images() {
+        let directory = test_directory("differing");
+        let reference_path = directory.join("reference.png");
+        save_image(&RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255])), &reference_path);
+        let actual = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+
+        let result = std::panic::catch_unwind(|| assert_golden_image(&actual, &reference_path, &GoldenTolerance::default()));
+
+        assert!(result.is_err());
+        assert_eq!(actual.into_raw(), image::open(directory.join("reference.actual.png")).unwrap().to_rgba().into_raw());
+        let diff_image = image::open(directory.join("reference.diff.png")).unwrap().to_rgba();
+        assert!(diff_image.pixels().all(|pixel| *pixel == Rgba([255, 0, 0, 255])));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use vulkano::device::{Device, Queue};
+use vulkano::format::R8G8B8A8Unorm;
+use vulkano::framebuffer::Subpass;
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::pipeline::viewport::Viewport;
+
+use crate::renderer::offscreen_target::PreviewTarget;
+use crate::renderer::renderer_error::RendererCreationError;
+use crate::renderer::shader::ShaderSet;
+use crate::resource::mesh::vertex_format::VertexFormat;
+
+/// Width and height of material preview image in pixels.
+pub const MATERIAL_PREVIEW_SIZE: u32 = 128;
+pub(crate) const PREVIEW_BACKGROUND_COLOR: [f32; 4] = [0.15, 0.15, 0.15, 1.0];
+/// Distance of the preview camera from the center of the previewed sphere.
+pub(crate) const PREVIEW_CAMERA_DISTANCE: f32 = 1.6;
+
+/// Offscreen image which material preview sphere is rendered to, along with pipeline drawing it.
+pub struct MaterialPreview {
+    target: PreviewTarget,
+    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+}
+
+impl MaterialPreview {
+    /// Creates preview image and everything needed to render to it.
+    pub(crate) fn new(device: Arc<Device>, queue: Arc<Queue>, shader_set: &ShaderSet) -> Result<Self, RendererCreationError> {
+        let target = PreviewTarget::new(device.clone(), queue, [MATERIAL_PREVIEW_SIZE, MATERIAL_PREVIEW_SIZE])?;
+
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input(shader_set.vertex_layout(VertexFormat::Standard))
+            .vertex_shader(shader_set.vertex_shader().main_entry_point(), ())
+            .triangle_list()
+            .viewports_dynamic_scissors_irrelevant(1)
+            .viewports(std::iter::once(Viewport {
+                origin: [0.0, 0.0],
+                dimensions: [MATERIAL_PREVIEW_SIZE as f32, MATERIAL_PREVIEW_SIZE as f32],
+                depth_range: 0.0 .. 1.0,
+            }))
+            .fragment_shader(shader_set.fragment_shader().main_entry_point(), ())
+            .depth_stencil_simple_depth()
+            .render_pass(Subpass::from(target.render_pass(), 0).unwrap())
+            .build(device)?;
+
+        Ok(MaterialPreview {
+            target,
+            pipeline: Arc::new(pipeline),
+        })
+    }
+
+    /// Returns image material preview is shown in.
+    pub fn image(&self) -> Arc<ImmutableImage<R8G8B8A8Unorm>> {
+        self.target.image()
+    }
+
+    pub(crate) fn target(&self) -> &PreviewTarget {
+        &self.target
+    }
+
+    pub(crate) fn pipeline(&self) -> Arc<GraphicsPipelineAbstract + Send + Sync> {
+        self.pipeline.clone()
+    }
+}
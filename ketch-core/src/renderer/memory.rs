@@ -0,0 +1,289 @@
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use vulkano::buffer::{BufferAccess, BufferInner, BufferUsage, DeviceLocalBuffer, TypedBufferAccess};
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::image::ImageAccess;
+use vulkano::instance::QueueFamily;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::sync::AccessError;
+
+/// Size of memory blocks which mesh buffers are sub-allocated from, in bytes.
+/// Buffers bigger than a block get a block of their own.
+pub const MEMORY_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Free list of element ranges inside a memory block, which allocates the first range big enough.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RangeAllocator {
+    size: usize,
+    // sorted by start, neighbouring free ranges are always merged
+    free_ranges: Vec<Range<usize>>,
+}
+
+impl RangeAllocator {
+    /// Creates allocator of a block with given number of elements, which are all free.
+    pub fn new(size: usize) -> Self {
+        RangeAllocator {
+            size,
+            free_ranges: if size > 0 { vec![0..size] } else { Vec::new() },
+        }
+    }
+
+    /// Returns number of elements in the block.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns number of allocated elements.
+    pub fn used(&self) -> usize {
+        self.size - self.free_ranges.iter().map(|range| range.end - range.start).sum::<usize>()
+    }
+
+    /// Allocates given number of consecutive elements and returns index of the first one, or None if no free range is big enough.
+    pub fn allocate(&mut self, len: usize) -> Option<usize> {
+        let position = self.free_ranges.iter().position(|range| range.end - range.start >= len)?;
+        let start = self.free_ranges[position].start;
+        self.free_ranges[position].start += len;
+        if self.free_ranges[position].start == self.free_ranges[position].end {
+            self.free_ranges.remove(position);
+        }
+        Some(start)
+    }
+
+    /// Frees elements allocated before, merging them with neighbouring free ranges.
+    pub fn free(&mut self, start: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let end = start + len;
+        let position = self.free_ranges.iter().position(|range| range.start > start).unwrap_or_else(|| self.free_ranges.len());
+        self.free_ranges.insert(position, start..end);
+
+        if position + 1 < self.free_ranges.len() && self.free_ranges[position + 1].start == end {
+            let next = self.free_ranges.remove(position + 1);
+            self.free_ranges[position].end = next.end;
+        }
+        if position > 0 && self.free_ranges[position - 1].end == start {
+            let current = self.free_ranges.remove(position);
+            self.free_ranges[position - 1].end = current.end;
+        }
+    }
+}
+
+/// Device local buffer which sub-buffers are allocated from.
+struct MemoryBlock<T> {
+    buffer: Arc<DeviceLocalBuffer<[T]>>,
+    allocator: Mutex<RangeAllocator>,
+}
+
+/// Allocates buffers of elements of the same type from big device local blocks, instead of allocating device memory for every buffer.
+/// Fewer allocations are made, because drivers limit their count, and freed ranges are reused by buffers created later.
+pub struct BufferArena<T> {
+    device: Arc<Device>,
+    usage: BufferUsage,
+    blocks: Mutex<Vec<Arc<MemoryBlock<T>>>>,
+}
+
+impl<T: Send + Sync + 'static> BufferArena<T> {
+    /// Creates arena without any blocks. Blocks are created with given usage when buffers don't fit in the existing ones.
+    pub fn new(device: Arc<Device>, usage: BufferUsage) -> Self {
+        BufferArena {
+            device,
+            usage,
+            blocks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Allocates buffer for given number of elements. New blocks are shared by given queue families.
+    pub fn allocate<'a, I>(&self, len: usize, queue_families: I) -> Result<Arc<SubBuffer<T>>, DeviceMemoryAllocError>
+        where I: IntoIterator<Item = QueueFamily<'a>> {
+        // empty buffers still take one element, so that every buffer has a range of its own
+        let allocated_len = len.max(1);
+        let mut blocks = self.blocks.lock().unwrap();
+        for block in blocks.iter() {
+            if let Some(start) = block.allocator.lock().unwrap().allocate(allocated_len) {
+                return Ok(Arc::new(SubBuffer::new(block.clone(), start, len)));
+            }
+        }
+
+        let block_len = (MEMORY_BLOCK_SIZE / std::mem::size_of::<T>().max(1)).max(allocated_len);
+        let block = Arc::new(MemoryBlock {
+            buffer: DeviceLocalBuffer::array(self.device.clone(), block_len, self.usage, queue_families)?,
+            allocator: Mutex::new(RangeAllocator::new(block_len)),
+        });
+        let start = block.allocator.lock().unwrap().allocate(allocated_len).expect("New memory block is too small");
+        blocks.push(block.clone());
+        Ok(Arc::new(SubBuffer::new(block, start, len)))
+    }
+
+    /// Returns number of device memory blocks allocated by this arena.
+    pub fn block_count(&self) -> usize {
+        self.blocks.lock().unwrap().len()
+    }
+
+    /// Returns size of allocated blocks and size used by buffers in them, in bytes.
+    pub fn memory_usage(&self) -> (u64, u64) {
+        let element_size = std::mem::size_of::<T>() as u64;
+        self.blocks.lock().unwrap().iter().fold((0, 0), |(allocated, used), block| {
+            let allocator = block.allocator.lock().unwrap();
+            (allocated + allocator.size() as u64 * element_size, used + allocator.used() as u64 * element_size)
+        })
+    }
+}
+
+/// State of GPU access to a sub-buffer.
+enum GpuAccess {
+    None,
+    NonExclusive { num: u32 },
+    Exclusive { num: u32 },
+}
+
+/// Range of a memory block used as a separate buffer. The range is freed when the sub-buffer is dropped.
+/// GPU access is tracked for every sub-buffer, so that uploading one of them doesn't conflict with drawing the others.
+pub struct SubBuffer<T> {
+    block: Arc<MemoryBlock<T>>,
+    start: usize,
+    len: usize,
+    gpu_lock: Mutex<GpuAccess>,
+}
+
+impl<T> SubBuffer<T> {
+    fn new(block: Arc<MemoryBlock<T>>, start: usize, len: usize) -> Self {
+        SubBuffer {
+            block,
+            start,
+            len,
+            gpu_lock: Mutex::new(GpuAccess::None),
+        }
+    }
+
+    /// Returns buffer of the whole memory block this sub-buffer was allocated from.
+    /// Draws binding it address the sub-buffer by `block_offset`, so that meshes from the same block can be drawn together.
+    pub fn block_buffer(&self) -> Arc<DeviceLocalBuffer<[T]>> {
+        self.block.buffer.clone()
+    }
+
+    /// Returns key which is equal for sub-buffers allocated from the same memory block.
+    pub fn block_key(&self) -> usize {
+        &*self.block as *const MemoryBlock<T> as usize
+    }
+
+    /// Returns index of the first element of this sub-buffer in its memory block.
+    pub fn block_offset(&self) -> usize {
+        self.start
+    }
+}
+
+impl<T> Drop for SubBuffer<T> {
+    fn drop(&mut self) {
+        self.block.allocator.lock().unwrap().free(self.start, self.len.max(1));
+    }
+}
+
+unsafe impl<T: Send + Sync + 'static> BufferAccess for SubBuffer<T> {
+    fn inner(&self) -> BufferInner {
+        let block_inner = self.block.buffer.inner();
+        BufferInner {
+            buffer: block_inner.buffer,
+            offset: block_inner.offset + self.start * std::mem::size_of::<T>(),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.len * std::mem::size_of::<T>()
+    }
+
+    fn conflicts_buffer(&self, other: &BufferAccess) -> bool {
+        let self_inner = self.inner();
+        let other_inner = other.inner();
+        if self_inner.buffer.key() != other_inner.buffer.key() {
+            return false;
+        }
+        self_inner.offset < other_inner.offset + other.size() && other_inner.offset < self_inner.offset + self.size()
+    }
+
+    fn conflicts_image(&self, _other: &ImageAccess) -> bool {
+        false
+    }
+
+    fn conflict_key(&self) -> (u64, usize) {
+        let inner = self.inner();
+        (inner.buffer.key(), inner.offset)
+    }
+
+    fn try_gpu_lock(&self, exclusive_access: bool, _queue: &Queue) -> Result<(), AccessError> {
+        let mut gpu_lock = self.gpu_lock.lock().unwrap();
+        match *gpu_lock {
+            GpuAccess::None => {
+                *gpu_lock = if exclusive_access {
+                    GpuAccess::Exclusive { num: 1 }
+                } else {
+                    GpuAccess::NonExclusive { num: 1 }
+                };
+                Ok(())
+            },
+            GpuAccess::NonExclusive { ref mut num } if !exclusive_access => {
+                *num += 1;
+                Ok(())
+            },
+            _ => Err(AccessError::AlreadyInUse),
+        }
+    }
+
+    unsafe fn increase_gpu_lock(&self) {
+        let mut gpu_lock = self.gpu_lock.lock().unwrap();
+        match *gpu_lock {
+            GpuAccess::None => panic!("Sub-buffer isn't locked"),
+            GpuAccess::NonExclusive { ref mut num } | GpuAccess::Exclusive { ref mut num } => *num += 1,
+        }
+    }
+
+    unsafe fn unlock(&self) {
+        let mut gpu_lock = self.gpu_lock.lock().unwrap();
+        let unlocked = match *gpu_lock {
+            GpuAccess::None => panic!("Sub-buffer isn't locked"),
+            GpuAccess::NonExclusive { ref mut num } | GpuAccess::Exclusive { ref mut num } => {
+                *num -= 1;
+                *num == 0
+            },
+        };
+        if unlocked {
+            *gpu_lock = GpuAccess::None;
+        }
+    }
+}
+
+unsafe impl<T: Send + Sync + 'static> TypedBufferAccess for SubBuffer<T> {
+    type Content = [T];
+}
+
+unsafe impl<T> DeviceOwned for SubBuffer<T> {
+    fn device(&self) -> &Arc<Device> {
+        self.block.buffer.device()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_ranges_are_merged_and_reused() {
+        let mut allocator = RangeAllocator::new(100);
+        let first = allocator.allocate(30).unwrap();
+        let second = allocator.allocate(30).unwrap();
+        let third = allocator.allocate(30).unwrap();
+        assert_eq!((0, 30, 60), (first, second, third));
+        assert_eq!(None, allocator.allocate(20));
+
+        allocator.free(first, 30);
+        allocator.free(second, 30);
+        assert_eq!(30, allocator.used());
+        assert_eq!(Some(0), allocator.allocate(50));
+
+        allocator.free(0, 50);
+        allocator.free(third, 30);
+        assert_eq!(0, allocator.used());
+        assert_eq!(Some(0), allocator.allocate(100));
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::vec::IntoIter as VecIntoIter;
+
+use vulkano::buffer::BufferAccess;
+use vulkano::pipeline::shader::ShaderInterfaceDef;
+use vulkano::pipeline::vertex::{AttributeInfo, IncompatibleVertexDefinitionError, InputRate, Vertex as VulkanoVertex, VertexDefinition, VertexSource};
+
+use crate::renderer::uniform_manager::ObjectInstance;
+use crate::resource::mesh::vertex_format::{LightmappedVertex, VertexFormat};
+
+/// Vertex input of pipelines drawing meshes of a vertex format. Mesh vertices are read from the first buffer and indices of drawn objects from the second one.
+/// Attributes which the vertex shader reads but the format lacks come from a single vertex with every attribute, bound as a third buffer with zero stride,
+/// so that the same shaders draw meshes of every format.
+#[derive(Clone)]
+pub struct MeshVertexDefinition {
+    format: VertexFormat,
+    defaults: Arc<BufferAccess + Send + Sync>,
+}
+
+impl MeshVertexDefinition {
+    /// Creates definition of given format, reading missing attributes from a buffer holding a single lightmapped vertex.
+    pub(crate) fn new(format: VertexFormat, defaults: Arc<BufferAccess + Send + Sync>) -> Self {
+        MeshVertexDefinition { format, defaults }
+    }
+}
+
+unsafe impl<I: ShaderInterfaceDef> VertexDefinition<I> for MeshVertexDefinition {
+    type BuffersIter = VecIntoIter<(u32, usize, InputRate)>;
+    type AttribsIter = VecIntoIter<(u32, u32, AttributeInfo)>;
+
+    fn definition(&self, interface: &I) -> Result<(Self::BuffersIter, Self::AttribsIter), IncompatibleVertexDefinitionError> {
+        let mut attribs = Vec::new();
+        for element in interface.elements() {
+            let name = element.name.as_ref().expect("Vertex shader input without a name");
+            let (binding, info) = if let Some(info) = self.format.member(name) {
+                (0, info)
+            } else if let Some(info) = <ObjectInstance as VulkanoVertex>::member(name) {
+                (1, info)
+            } else if let Some(info) = <LightmappedVertex as VulkanoVertex>::member(name) {
+                (2, info)
+            } else {
+                return Err(IncompatibleVertexDefinitionError::MissingAttribute { attribute: name.clone().into_owned() });
+            };
+
+            let locations = element.location.end - element.location.start;
+            if !info.ty.matches(info.array_size, element.format, locations) {
+                return Err(IncompatibleVertexDefinitionError::FormatMismatch {
+                    attribute: name.clone().into_owned(),
+                    shader: (element.format, locations as usize),
+                    definition: (info.ty, info.array_size),
+                });
+            }
+
+            let mut offset = info.offset;
+            for location in element.location.clone() {
+                attribs.push((location, binding, AttributeInfo { offset, format: element.format }));
+                offset += element.format.size().expect("Vertex attribute format without a size");
+            }
+        }
+
+        let buffers = vec![
+            (0, self.format.stride(), InputRate::Vertex),
+            (1, std::mem::size_of::<ObjectInstance>(), InputRate::Instance),
+            // every vertex reads the same default vertex
+            (2, 0, InputRate::Vertex),
+        ];
+        Ok((buffers.into_iter(), attribs.into_iter()))
+    }
+}
+
+unsafe impl VertexSource<Vec<Arc<BufferAccess + Send + Sync>>> for MeshVertexDefinition {
+    fn decode(&self, source: Vec<Arc<BufferAccess + Send + Sync>>) -> (Vec<Box<BufferAccess + Send + Sync>>, usize, usize) {
+        assert_eq!(source.len(), 2, "Meshes are drawn with a vertex buffer and an instance buffer");
+        let vertices = source[0].size() / self.format.stride();
+        let instances = source[1].size() / std::mem::size_of::<ObjectInstance>();
+        let mut buffers: Vec<Box<BufferAccess + Send + Sync>> = source.into_iter().map(|buffer| Box::new(buffer) as Box<BufferAccess + Send + Sync>).collect();
+        buffers.push(Box::new(self.defaults.clone()));
+        (buffers, vertices, instances)
+    }
+}
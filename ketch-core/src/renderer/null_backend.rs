@@ -0,0 +1,95 @@
+use std::convert::Infallible;
+
+use image::RgbaImage;
+
+use crate::renderer::backend::{DrawCommand, MeshHandle, RenderBackend, TextureHandle};
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::mesh::Vertex;
+
+/// Render backend which draws nothing and needs neither a window nor a GPU, so that games and their tests run on CI machines without them.
+/// It records uploaded assets and draws of the last frame, which tests can check instead of rendered images.
+#[derive(Default)]
+pub struct NullBackend {
+    // vertex and index counts of uploaded meshes
+    meshes: Vec<(usize, usize)>,
+    // sizes of uploaded textures
+    textures: Vec<(u32, u32)>,
+    frame: Option<Vec<DrawCommand>>,
+    last_frame: Vec<DrawCommand>,
+    frames_rendered: u64,
+}
+
+impl NullBackend {
+    /// Creates backend without uploaded assets or rendered frames.
+    pub fn new() -> Self {
+        NullBackend::default()
+    }
+
+    /// Returns number of uploaded meshes.
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// Returns number of vertices and indices of an uploaded mesh.
+    pub fn mesh_size(&self, mesh: MeshHandle) -> Option<(usize, usize)> {
+        self.meshes.get(mesh.0).cloned()
+    }
+
+    /// Returns number of uploaded textures.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Returns width and height of an uploaded texture.
+    pub fn texture_size(&self, texture: TextureHandle) -> Option<(u32, u32)> {
+        self.textures.get(texture.0).cloned()
+    }
+
+    /// Returns draws of the last ended frame.
+    pub fn last_frame(&self) -> &[DrawCommand] {
+        &self.last_frame
+    }
+
+    /// Returns number of frames ended since the backend was created.
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames_rendered
+    }
+}
+
+impl RenderBackend for NullBackend {
+    type Error = Infallible;
+
+    fn name(&self) -> String {
+        "null".to_string()
+    }
+
+    fn upload_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<MeshHandle, Infallible> {
+        self.meshes.push((vertices.len(), indices.len()));
+        Ok(MeshHandle(self.meshes.len() - 1))
+    }
+
+    fn upload_texture(&mut self, image: &RgbaImage) -> Result<TextureHandle, Infallible> {
+        self.textures.push(image.dimensions());
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    fn begin_frame(&mut self, _camera: &Camera, _lights: &[Light]) -> Result<(), Infallible> {
+        self.frame = Some(Vec::new());
+        Ok(())
+    }
+
+    fn draw(&mut self, command: DrawCommand) {
+        if let Some(frame) = &mut self.frame {
+            frame.push(command);
+        }
+    }
+
+    fn end_frame(&mut self) -> Result<(), Infallible> {
+        if let Some(frame) = self.frame.take() {
+            self.last_frame = frame;
+            self.frames_rendered += 1;
+        }
+        Ok(())
+    }
+}
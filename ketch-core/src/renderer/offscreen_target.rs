@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use vulkano::buffer::BufferAccess;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::{Device, Queue};
+use vulkano::format::{Format, R8G8B8A8Unorm};
+use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract};
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::image::{Dimensions, ImageAccess, ImageInner, ImageLayout, ImageUsage};
+use vulkano::single_pass_renderpass;
+use vulkano::sync::AccessError;
+
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+
+const OFFSCREEN_FORMAT: Format = Format::R8G8B8A8Srgb;
+const OFFSCREEN_DEPTH_FORMAT: Format = Format::D16Unorm;
+
+/// Image with depth buffer, which can be rendered to and then sampled, e.g. shown in the editor GUI, or copied to a buffer.
+pub(crate) struct OffscreenTarget {
+    image: Arc<AttachmentImage>,
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+}
+
+impl OffscreenTarget {
+    /// Creates target image of given width and height along with render pass drawing to it.
+    pub(crate) fn new(device: Arc<Device>, dimensions: [u32; 2]) -> Result<Self, RendererCreationError> {
+        let usage = ImageUsage {
+            transfer_source: true,
+            sampled: true,
+            color_attachment: true,
+            ..ImageUsage::none()
+        };
+        let image = AttachmentImage::with_usage(device.clone(), dimensions, OFFSCREEN_FORMAT, usage)?;
+        let depth_buffer = AttachmentImage::transient(device.clone(), dimensions, OFFSCREEN_DEPTH_FORMAT)?;
+
+        let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
+                                       attachments: {
+                                           color: {
+                                               load: Clear,
+                                               store: Store,
+                                               format: OFFSCREEN_FORMAT,
+                                               samples: 1,
+                                           },
+                                           depth: {
+                                               load: Clear,
+                                               store: DontCare,
+                                               format: OFFSCREEN_DEPTH_FORMAT,
+                                               samples: 1,
+                                           }
+                                       },
+                                       pass: {
+                                           color: [color],
+                                           depth_stencil: {depth}
+                                       }
+                                   )?) as Arc<RenderPassAbstract + Send + Sync>;
+
+        let framebuffer = Arc::new(Framebuffer::start(render_pass.clone())
+                                               .add(image.clone())?
+                                               .add(depth_buffer)?
+                                               .build()?) as Arc<FramebufferAbstract + Send + Sync>;
+
+        Ok(OffscreenTarget {
+            image,
+            render_pass,
+            framebuffer,
+        })
+    }
+
+    /// Returns image rendered to.
+    pub(crate) fn image(&self) -> Arc<AttachmentImage> {
+        self.image.clone()
+    }
+
+    /// Returns render pass with a single subpass drawing to the image.
+    pub(crate) fn render_pass(&self) -> Arc<RenderPassAbstract + Send + Sync> {
+        self.render_pass.clone()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.framebuffer.clone()
+    }
+}
+
+/// Offscreen target of an editor preview, along with the image showing it in the GUI.
+/// Preview uses its own render pass, so it has to be rendered before `render_scene` is called.
+/// When the render pass ends, `add_display_commands` copies the rendered image to the shown one.
+pub(crate) struct PreviewTarget {
+    target: OffscreenTarget,
+    display: DisplayImage,
+}
+
+impl PreviewTarget {
+    /// Creates offscreen target and black shown image of given width and height.
+    pub(crate) fn new(device: Arc<Device>, queue: Arc<Queue>, dimensions: [u32; 2]) -> Result<Self, RendererCreationError> {
+        Ok(PreviewTarget {
+            target: OffscreenTarget::new(device, dimensions)?,
+            display: DisplayImage::new(queue, dimensions)?,
+        })
+    }
+
+    /// Returns image showing the preview in the GUI.
+    pub(crate) fn image(&self) -> Arc<ImmutableImage<R8G8B8A8Unorm>> {
+        self.display.image()
+    }
+
+    pub(crate) fn render_pass(&self) -> Arc<RenderPassAbstract + Send + Sync> {
+        self.target.render_pass()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.target.framebuffer()
+    }
+
+    /// Adds command copying the rendered preview to the shown image.
+    pub(crate) fn add_display_commands(&self, command_buffer: AutoCommandBufferBuilder) -> Result<AutoCommandBufferBuilder, RenderError> {
+        self.display.add_copy_commands(command_buffer, &self.target)
+    }
+}
+
+/// Image shown in the editor GUI, which an offscreen image is copied to after it's rendered.
+/// GUI draws only immutable images, so the copy goes through `DisplayImageWrite`.
+struct DisplayImage {
+    image: Arc<ImmutableImage<R8G8B8A8Unorm>>,
+}
+
+impl DisplayImage {
+    /// Creates black image of given width and height.
+    fn new(queue: Arc<Queue>, dimensions: [u32; 2]) -> Result<Self, RendererCreationError> {
+        let pixels = vec![[0u8, 0, 0, 255]; (dimensions[0] * dimensions[1]) as usize];
+        let (image, _future) = ImmutableImage::from_iter(pixels.into_iter(), Dimensions::Dim2d { width: dimensions[0], height: dimensions[1] },
+                                                         R8G8B8A8Unorm, queue)?;
+        Ok(DisplayImage {
+            image,
+        })
+    }
+
+    fn image(&self) -> Arc<ImmutableImage<R8G8B8A8Unorm>> {
+        self.image.clone()
+    }
+
+    /// Adds command copying given offscreen target image to the display image, outside of a render pass.
+    fn add_copy_commands(&self, command_buffer: AutoCommandBufferBuilder, target: &OffscreenTarget) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let [width, height] = AttachmentImage::dimensions(&target.image);
+        Ok(command_buffer.copy_image(target.image(), [0, 0, 0], 0, 0, DisplayImageWrite(self.image.clone()), [0, 0, 0], 0, 0, [width, height, 1], 1)?)
+    }
+}
+
+/// Access to a display image which lets commands write to it, unlike the immutable image itself.
+/// Conflicts are detected by the same key as the immutable image, so commands sampling it afterwards wait for the copy.
+struct DisplayImageWrite(Arc<ImmutableImage<R8G8B8A8Unorm>>);
+
+unsafe impl ImageAccess for DisplayImageWrite {
+    fn inner(&self) -> ImageInner {
+        ImageAccess::inner(&*self.0)
+    }
+
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::ShaderReadOnlyOptimal
+    }
+
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::ShaderReadOnlyOptimal
+    }
+
+    fn conflicts_buffer(&self, _other: &BufferAccess) -> bool {
+        false
+    }
+
+    fn conflicts_image(&self, other: &ImageAccess) -> bool {
+        self.conflict_key() == other.conflict_key()
+    }
+
+    fn conflict_key(&self) -> u64 {
+        self.0.conflict_key()
+    }
+
+    // the image is only written by copies recorded in the same command buffer as the GUI drawing it
+    fn try_gpu_lock(&self, _exclusive_access: bool, _expected_layout: ImageLayout) -> Result<(), AccessError> {
+        Ok(())
+    }
+
+    unsafe fn increase_gpu_lock(&self) {
+    }
+
+    unsafe fn unlock(&self, _new_layout: Option<ImageLayout>) {
+    }
+}
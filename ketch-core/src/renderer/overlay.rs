@@ -0,0 +1,92 @@
+use nalgebra_glm::{Vec2, Vec3};
+
+use crate::renderer::debug_draw::DebugVertex;
+use crate::renderer::debug_draw::stroke_font;
+use crate::ui::ui_node::Rect;
+
+/// Height of overlay text in pixels.
+pub const OVERLAY_TEXT_HEIGHT: f32 = 12.0;
+
+/// Rectangles, lines and text in window pixels drawn on top of everything else, e.g. by the diagnostics overlay.
+/// Unlike in-game UI it doesn't need any loaded fonts, text is drawn with lines of a built-in font.
+pub struct Overlay {
+    rects: Vec<(Rect, [f32; 4])>,
+    vertices: Vec<DebugVertex>,
+}
+
+impl Overlay {
+    /// Creates new empty overlay.
+    pub fn new() -> Self {
+        Overlay {
+            rects: Vec::new(),
+            vertices: Vec::new(),
+        }
+    }
+
+    /// Adds rectangle filled with color, which may be transparent. Rectangles are drawn below lines and text.
+    pub fn rect(&mut self, rect: Rect, color: [f32; 4]) {
+        self.rects.push((rect, color));
+    }
+
+    /// Adds line between two points in window pixels.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), color: Vec3) {
+        self.vertices.push(DebugVertex { position: [from.0, from.1, 0.0], color: color.into() });
+        self.vertices.push(DebugVertex { position: [to.0, to.1, 0.0], color: color.into() });
+    }
+
+    /// Adds text with top left corner at given position. Lines of the text are separated with newlines, letters are shown as capitals.
+    pub fn text(&mut self, x: f32, y: f32, text: &str, color: Vec3) {
+        let unit = OVERLAY_TEXT_HEIGHT / stroke_font::GLYPH_HEIGHT;
+        let (_, height) = stroke_font::text_size(text);
+        for (from, to) in stroke_font::text_segments(text, false) {
+            let point = |point: Vec2| (x + point.x * unit, y + (height - point.y) * unit);
+            self.line(point(from), point(to), color);
+        }
+    }
+
+    /// Returns width and height of text added with `text` in pixels.
+    pub fn text_size(text: &str) -> (f32, f32) {
+        let unit = OVERLAY_TEXT_HEIGHT / stroke_font::GLYPH_HEIGHT;
+        let (width, height) = stroke_font::text_size(text);
+        (width * unit, height * unit)
+    }
+
+    /// Returns true if there is nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty() && self.vertices.is_empty()
+    }
+
+    #[cfg(feature = "vulkan")]
+    pub(crate) fn rects(&self) -> &[(Rect, [f32; 4])] {
+        &self.rects
+    }
+
+    /// Returns vertices of lines and text, two for every line, with window pixels as x and y.
+    #[cfg(any(feature = "vulkan", test))]
+    pub(crate) fn vertices(&self) -> &[DebugVertex] {
+        &self.vertices
+    }
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Overlay::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_starts_at_top_left_corner() {
+        let mut overlay = Overlay::new();
+        overlay.text(10.0, 20.0, "T", Vec3::new(1.0, 1.0, 1.0));
+
+        let positions: Vec<_> = overlay.vertices().iter().map(|vertex| vertex.position).collect();
+        assert_eq!([10.0, 20.0, 0.0], positions[0]);
+        assert_eq!([18.0, 20.0, 0.0], positions[1]);
+        assert_eq!([14.0, 20.0 + OVERLAY_TEXT_HEIGHT, 0.0], positions[3]);
+        assert_eq!((8.0, OVERLAY_TEXT_HEIGHT), Overlay::text_size("T"));
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use nalgebra_glm as glm;
+use nalgebra_glm::{U3, Vec3};
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::impl_vertex;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract, GraphicsPipelineCreationError};
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::depth_stencil::DepthStencil;
+use vulkano::pipeline::viewport::Viewport;
+
+use crate::particles::{Particle, ParticleSystem};
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::renderer_stats::RendererStats;
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::particle_vertex_shader::ty::PushConstants as ParticlePushConstants;
+use crate::resource::AssetManager;
+use crate::resource::camera::Camera;
+use crate::resource::material::BlendMode;
+use crate::resource::texture::Texture;
+
+/// Corner of a camera facing particle quad in world space.
+#[derive(Clone, Copy)]
+pub struct ParticleVertex {
+    pub position: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl_vertex!(ParticleVertex, position, tex_coord, color);
+
+/// Draws particles as camera facing textured quads. Particles of each emitter are drawn with a single draw call,
+/// sorted from the farthest to the nearest, so that transparent particles blend correctly.
+pub(crate) struct ParticleRenderer {
+    pipelines: HashMap<BlendMode, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+    white_texture: Arc<Texture>,
+}
+
+impl ParticleRenderer {
+    pub(crate) fn new(device: Arc<Device>, upload_queue: Arc<Queue>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                      render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Self, RendererCreationError> {
+        let white_image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255])));
+        Ok(ParticleRenderer {
+            pipelines: create_particle_pipelines(device.clone(), shader_set, dimensions, render_pass)?,
+            white_texture: Arc::new(Texture::new("particle white", white_image, upload_queue, device)?),
+        })
+    }
+
+    /// Recreates pipelines drawing to images of new dimensions.
+    pub(crate) fn recreate_pipelines(&mut self, device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                                     render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<(), GraphicsPipelineCreationError> {
+        self.pipelines = create_particle_pipelines(device, shader_set, dimensions, render_pass)?;
+        Ok(())
+    }
+
+    /// Adds commands drawing particles seen by the camera, inside an already started render pass.
+    /// Particles of effects which aren't in asset manager are skipped.
+    pub(crate) fn add_commands(&self, mut command_buffer: AutoCommandBufferBuilder, particle_system: &ParticleSystem, asset_manager: &AssetManager,
+                               camera: &Camera, dimensions: [f32; 2], device: Arc<Device>, stats: &mut RendererStats) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let view_projection = camera.projection_matrix(dimensions[0], dimensions[1]) * camera.view_matrix();
+        let camera_position = camera.position_vec3();
+        let right = camera.right();
+        let up = glm::normalize(&glm::cross::<f32, U3>(&right, &camera.front()));
+
+        for instance in particle_system.instances() {
+            if instance.particles().is_empty() {
+                continue;
+            }
+            let (texture_name, blend_mode) = match asset_manager.particle_effect(instance.effect()) {
+                Some(effect) => {
+                    let effect = effect.read().unwrap();
+                    (effect.texture.clone(), effect.blend_mode)
+                },
+                None => continue,
+            };
+            let texture = texture_name.and_then(|name| asset_manager.texture(&name)).unwrap_or_else(|| self.white_texture.clone());
+
+            let mut particles: Vec<&Particle> = instance.particles().iter().collect();
+            particles.sort_by(|first, second| {
+                let first_distance = glm::distance2(&first.position, &camera_position);
+                let second_distance = glm::distance2(&second.position, &camera_position);
+                second_distance.partial_cmp(&first_distance).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            let vertices: Vec<ParticleVertex> = particles.iter().flat_map(|particle| quad(particle, &right, &up)).collect();
+
+            let pipeline = self.pipelines[&blend_mode].clone();
+            let vertex_buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::vertex_buffer(), vertices.into_iter())?;
+            let (image_buffer, sampler) = texture.sampled_image()?;
+            let descriptor_set = PersistentDescriptorSet::start(pipeline.clone(), 0)
+                                                         .add_sampled_image(image_buffer, sampler)?
+                                                         .build()?;
+            stats.descriptor_sets_created += 1;
+            let push_constants = ParticlePushConstants {
+                view_projection: view_projection.into(),
+            };
+            command_buffer = command_buffer.draw(pipeline, &DynamicState::none(), vec!(vertex_buffer), descriptor_set, push_constants)?;
+            stats.draw_calls += 1;
+        }
+        Ok(command_buffer)
+    }
+}
+
+/// Returns two triangles of a quad facing the camera, centered at the particle.
+fn quad(particle: &Particle, right: &Vec3, up: &Vec3) -> Vec<ParticleVertex> {
+    let half_size = particle.size * 0.5;
+    let right = right * half_size;
+    let up = up * half_size;
+    let corner = |offset: Vec3, tex_coord: [f32; 2]| {
+        let position = particle.position + offset;
+        ParticleVertex { position: [position.x, position.y, position.z], tex_coord, color: particle.color }
+    };
+    // textures are flipped vertically when they're loaded
+    let bottom_left = corner(-right - up, [0.0, 0.0]);
+    let bottom_right = corner(right - up, [1.0, 0.0]);
+    let top_right = corner(right + up, [1.0, 1.0]);
+    let top_left = corner(up - right, [0.0, 1.0]);
+    vec![bottom_left, bottom_right, top_right, bottom_left, top_right, top_left]
+}
+
+/// Creates pipelines for all blend modes. Particles are tested against the depth buffer, but only opaque particles write to it.
+fn create_particle_pipelines(device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2], render_pass: Arc<RenderPassAbstract + Send + Sync>)
+                             -> Result<HashMap<BlendMode, Arc<GraphicsPipelineAbstract + Send + Sync>>, GraphicsPipelineCreationError> {
+    let mut pipelines = HashMap::new();
+    for blend_mode in BlendMode::ALL.iter() {
+        let blend = match blend_mode {
+            BlendMode::Opaque => AttachmentBlend::pass_through(),
+            BlendMode::AlphaBlend => AttachmentBlend::alpha_blending(),
+            BlendMode::Additive => AttachmentBlend {
+                color_destination: BlendFactor::One,
+                alpha_destination: BlendFactor::One,
+                ..AttachmentBlend::alpha_blending()
+            },
+        };
+        let depth_stencil = DepthStencil {
+            depth_write: !blend_mode.is_transparent(),
+            ..DepthStencil::simple_depth_test()
+        };
+
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input(ShaderSet::particle_vertex_layout())
+            .vertex_shader(shader_set.particle_vertex_shader().main_entry_point(), ())
+            .triangle_list()
+            .viewports_dynamic_scissors_irrelevant(1)
+            .viewports(std::iter::once(Viewport {
+                origin: [0.0, 0.0],
+                dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+                depth_range: 0.0 .. 1.0,
+            }))
+            .fragment_shader(shader_set.particle_fragment_shader().main_entry_point(), ())
+            .depth_stencil(depth_stencil)
+            .blend_collective(blend)
+            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+            .build(device.clone())?;
+        pipelines.insert(*blend_mode, Arc::new(pipeline) as Arc<GraphicsPipelineAbstract + Send + Sync>);
+    }
+    Ok(pipelines)
+}
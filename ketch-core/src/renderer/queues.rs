@@ -1,65 +1,90 @@
 use vulkano::instance::{PhysicalDevice, QueueFamily};
 use vulkano::swapchain::Surface;
 use vulkano::device::{Queue, QueuesIter};
+use vulkano::command_buffer::{AutoCommandBuffer, CommandBuffer};
+use vulkano::sync::{GpuFuture, SharingMode};
 use winit::Window;
 
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+
 use log::*;
 use std::sync::Arc;
 
+/// Queue roles which have their own queue family on the device. Roles without one share the graphics queue.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct QueueLayout {
+    /// Compute work can overlap with rendering.
+    pub dedicated_compute: bool,
+    /// Asset uploads can overlap with rendering.
+    pub dedicated_transfer: bool,
+    /// Graphics queue can't present, so swapchain images are presented by a queue of another family.
+    pub separate_present: bool,
+}
+
 /// Struct with vulkan queues.
 #[derive(Clone)]
 pub struct Queues {
     graphics_queue: Arc<Queue>,
+    present_queue: Arc<Queue>,
     compute_queue: Arc<Queue>,
     transfer_queue: Arc<Queue>,
 }
 
 impl Queues {
-    /// Creates new struct with vulkan queues.
-    pub fn new(queues: QueuesIter) -> Self {
-        let mut graphic_queue = None;
-        let mut compute_queue = None;
-        let mut transfer_queue = None;
-
-        for queue in queues{
-            if queue.family().supports_graphics() && graphic_queue.is_none() {
-                graphic_queue = Some(queue);
-                continue;
-            }
-
-            if queue.family().supports_compute() && compute_queue.is_none() {
-                compute_queue = Some(queue);
-                continue;
-            }
-
-            if !queue.family().supports_transfers() && transfer_queue.is_none(){
-                transfer_queue = Some(queue);
-                continue;
-            }
-        }
-
-        //Check the queues
-        if graphic_queue.is_none(){
-            panic!("No graphics queue found");
-        }
+    /// Assigns queues created from families returned by `find_queues` to their roles.
+    /// Roles without a dedicated queue family fall back to the graphics queue.
+    pub fn new(queues: QueuesIter, surface: &Surface<Window>) -> Result<Self, RendererCreationError> {
+        let queues: Vec<Arc<Queue>> = queues.collect();
+        let supports_present = |queue: &Arc<Queue>| surface.is_supported(queue.family()).unwrap_or(false);
+
+        let graphics_queue = queues.iter().find(|queue| queue.family().supports_graphics() && supports_present(queue))
+                                   .or_else(|| queues.iter().find(|queue| queue.family().supports_graphics()))
+                                   .cloned()
+                                   .ok_or(RendererCreationError::NoGraphicsQueueError)?;
+        let present_queue = if supports_present(&graphics_queue) {
+            graphics_queue.clone()
+        } else {
+            queues.iter().find(|queue| supports_present(queue)).cloned().ok_or(RendererCreationError::NoPresentQueueError)?
+        };
+        let compute_queue = queues.iter().find(|queue| queue.family().supports_compute() && !queue.family().supports_graphics()).cloned()
+                                  .unwrap_or_else(|| {
+                                      info!("No dedicated compute queue found, using graphics queue");
+                                      graphics_queue.clone()
+                                  });
+        let transfer_queue = queues.iter().find(|queue| is_transfer_only(queue.family())).cloned()
+                                   .unwrap_or_else(|| {
+                                       info!("No dedicated transfer queue found, using graphics queue");
+                                       graphics_queue.clone()
+                                   });
+
+        Ok(Queues {
+            graphics_queue,
+            present_queue,
+            compute_queue,
+            transfer_queue,
+        })
+    }
 
-        if compute_queue.is_none(){
-            warn!("No compute queue found, using graphics queue", );
-            compute_queue = graphic_queue.clone();
+    /// Returns which queue roles have their own queue families.
+    pub fn layout(&self) -> QueueLayout {
+        QueueLayout {
+            dedicated_compute: self.has_dedicated_compute_queue(),
+            dedicated_transfer: self.has_dedicated_transfer_queue(),
+            separate_present: self.present_queue.family().id() != self.graphics_queue.family().id(),
         }
+    }
 
-        //Currently always using compute queue since multi queue sync is not implemented for vulkano atm.
-        transfer_queue = compute_queue.clone();
-
-        if transfer_queue.is_none(){
-            warn!("No transfer queue found, using compute", );
-            transfer_queue = compute_queue.clone();
-        }
+    /// Returns queue presenting swapchain images. It's the graphics queue unless its family can't present to the surface.
+    pub fn present_queue(&self) -> Arc<Queue> {
+        self.present_queue.clone()
+    }
 
-        Queues{
-            graphics_queue: graphic_queue.expect("Failed to find graphics queue"),
-            compute_queue: compute_queue.expect("Failed to find compute queue"),
-            transfer_queue: transfer_queue.expect("Failed to find transfer queue"),
+    /// Returns sharing mode of swapchain images, which are shared with the present queue if it belongs to another family.
+    pub fn swapchain_sharing_mode(&self) -> SharingMode {
+        if self.layout().separate_present {
+            SharingMode::Concurrent(vec![self.graphics_queue.family().id(), self.present_queue.family().id()])
+        } else {
+            SharingMode::Exclusive(self.graphics_queue.family().id())
         }
     }
 
@@ -73,51 +98,90 @@ impl Queues {
         self.compute_queue.clone()
     }
 
-    /// Returns transfer queue. Currently always is the same as compute queue since multi queue sync is not implemented for vulkano at the moment.
+    /// Returns transfer queue used for uploading assets. Can be the same as graphics queue if a dedicated one is not found.
     pub fn transfer_queue(&self) -> Arc<Queue> {
         self.transfer_queue.clone()
     }
+
+    /// Returns true if transfer queue belongs to a different queue family than graphics queue.
+    pub fn has_dedicated_transfer_queue(&self) -> bool {
+        self.transfer_queue.family().id() != self.graphics_queue.family().id()
+    }
+
+    /// Returns true if compute queue belongs to a different queue family than graphics queue, so compute work can overlap with rendering.
+    pub fn has_dedicated_compute_queue(&self) -> bool {
+        self.compute_queue.family().id() != self.graphics_queue.family().id()
+    }
+
+    /// Returns ids of queue families which share resources written by compute queue and read by graphics queue.
+    /// It's empty if both queues belong to the same family, so resources don't have to be shared.
+    pub fn compute_sharing_families(&self) -> Vec<u32> {
+        if self.has_dedicated_compute_queue() {
+            vec![self.graphics_queue.family().id(), self.compute_queue.family().id()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Executes command buffer on transfer queue. Returned future signals a semaphore, which work using transferred data has to wait for.
+    pub fn submit_transfer(&self, command_buffer: AutoCommandBuffer) -> Result<Box<GpuFuture + Send + Sync>, RenderError> {
+        submit_signaling_semaphore(self.transfer_queue(), command_buffer)
+    }
+
+    /// Executes command buffer on compute queue. Returned future signals a semaphore, which work using computed data has to wait for.
+    pub fn submit_compute(&self, command_buffer: AutoCommandBuffer) -> Result<Box<GpuFuture + Send + Sync>, RenderError> {
+        submit_signaling_semaphore(self.compute_queue(), command_buffer)
+    }
 }
 
-/// Finds available queues supported by the physical device.
-pub fn find_queues<'a>(physical_device: PhysicalDevice<'a>, surface: &Surface<Window>) -> Vec<(QueueFamily<'a>, f32)>{
-    
-    let mut queue_collection = Vec::new();
+fn submit_signaling_semaphore(queue: Arc<Queue>, command_buffer: AutoCommandBuffer) -> Result<Box<GpuFuture + Send + Sync>, RenderError> {
+    let future = command_buffer.execute(queue)?
+                               .then_signal_semaphore_and_flush()?;
+    Ok(Box::new(future))
+}
 
-    let mut has_graphics = false;
-    let mut has_compute = false;
-    let mut has_transfer = false;
+/// Returns true if queue family only supports transfers, which makes it the best fit for uploads.
+fn is_transfer_only(family: QueueFamily) -> bool {
+    family.supports_transfers() && !family.supports_compute() && !family.supports_graphics()
+}
 
+/// Finds queue families queues are created from: a graphics family, preferably one which can present to the surface,
+/// a present family if the graphics one can't present, and dedicated compute and transfer families if the device has them.
+/// Every family is requested once, so devices with a single queue family get a single queue shared by all roles.
+pub fn find_queues<'a>(physical_device: PhysicalDevice<'a>, surface: &Surface<Window>) -> Result<Vec<(QueueFamily<'a>, f32)>, RendererCreationError> {
     info!("QUEUE INFO:\n=========");
-
-    for queue in physical_device.queue_families(){
-        info!("Queue {}, graphics: {}, compute: {}, count: {}",
+    for queue in physical_device.queue_families() {
+        info!("Queue {}, graphics: {}, compute: {}, transfer: {}, present: {}, count: {}",
             queue.id(),
             queue.supports_graphics(),
             queue.supports_compute(),
+            queue.supports_transfers(),
+            surface.is_supported(queue).unwrap_or(false),
             queue.queues_count()
         );
-
-        if queue.supports_graphics() && surface.is_supported(queue).unwrap_or(false) && !has_graphics {
-            queue_collection.push((queue, 1.0));
-            has_graphics = true
-        }
-
-        if queue.supports_compute() && !queue.supports_graphics() && !has_compute{
-            queue_collection.push((queue, 0.75));
-            has_compute = true;
-        }
-
-        if queue.supports_transfers() && !queue.supports_compute() && !queue.supports_graphics() && !has_transfer{
-            queue_collection.push((queue, 0.5));
-            has_transfer = true;
-        }
-
     }
-
     info!("=========");
 
-    info!("Found queues: graphics: {}, compute: {}, transfer: {}", has_graphics, has_compute, has_transfer);
-
-    queue_collection
+    let supports_present = |queue: &QueueFamily| surface.is_supported(*queue).unwrap_or(false);
+    let graphics = physical_device.queue_families().find(|queue| queue.supports_graphics() && supports_present(queue))
+                                  .or_else(|| physical_device.queue_families().find(QueueFamily::supports_graphics))
+                                  .ok_or(RendererCreationError::NoGraphicsQueueError)?;
+    let present = if supports_present(&graphics) {
+        None
+    } else {
+        Some(physical_device.queue_families().find(&supports_present).ok_or(RendererCreationError::NoPresentQueueError)?)
+    };
+    let compute = physical_device.queue_families().find(|queue| queue.supports_compute() && !queue.supports_graphics());
+    let transfer = physical_device.queue_families().find(|queue| is_transfer_only(*queue));
+
+    info!("Found queues: graphics: {}, separate present: {}, dedicated compute: {}, dedicated transfer: {}",
+          graphics.id(), present.is_some(), compute.is_some(), transfer.is_some());
+
+    let mut queue_collection = vec![(graphics, 1.0)];
+    for (queue, priority) in vec![(present, 1.0), (compute, 0.75), (transfer, 0.5)] {
+        if let Some(queue) = queue.filter(|queue| queue_collection.iter().all(|(added, _)| added.id() != queue.id())) {
+            queue_collection.push((queue, priority));
+        }
+    }
+    Ok(queue_collection)
 }
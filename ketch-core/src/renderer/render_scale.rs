@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract, Subpass};
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::image::ImageUsage;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract, GraphicsPipelineCreationError};
+use vulkano::pipeline::depth_stencil::{Compare, DepthStencil};
+use vulkano::pipeline::vertex::{BufferlessDefinition, BufferlessVertices};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::single_pass_renderpass;
+
+use crate::renderer::asset_preview::BufferlessPipeline;
+use crate::renderer::{create_debug_pipeline, create_pipelines, PipelineKey};
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::upscale_fragment_shader::ty::PushConstants as UpscalePushConstants;
+
+/// Smallest fraction of the window resolution the scene can be rendered at.
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+/// Dynamic render scale changes in steps of this size, so that the scaled target isn't recreated for tiny changes.
+const RENDER_SCALE_STEP: f32 = 0.05;
+/// Number of frames whose times are averaged before dynamic render scale is adjusted.
+const ADJUSTMENT_FRAMES: u32 = 30;
+/// Dynamic render scale grows only if frames take less than this fraction of the target frame time, so that it doesn't oscillate.
+const GROWTH_THRESHOLD: f32 = 0.8;
+
+const SCALED_DEPTH_FORMAT: Format = Format::D16Unorm;
+
+/// Filter used to upscale the scene rendered below the window resolution.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpscaleFilter {
+    Bilinear,
+    /// Bilinear upscaling followed by contrast adaptive sharpening, similar to the sharpening pass of AMD FSR 1,
+    /// which restores some of the detail lost by rendering at a lower resolution.
+    Sharpened,
+}
+
+/// Returns render scale limited to the supported range, from `MIN_RENDER_SCALE` to 1.
+pub fn clamp_render_scale(scale: f32) -> f32 {
+    if scale.is_nan() {
+        return 1.0;
+    }
+    scale.max(MIN_RENDER_SCALE).min(1.0)
+}
+
+/// Returns dimensions of the scene rendered at given fraction of the window dimensions, at least one pixel on each side.
+pub fn scaled_dimensions(dimensions: [u32; 2], scale: f32) -> [u32; 2] {
+    let scale_side = |side: u32| ((side as f32 * scale).round() as u32).max(1);
+    [scale_side(dimensions[0]), scale_side(dimensions[1])]
+}
+
+/// Adjusts render scale to keep frame time at the target, lowering resolution on weak GPUs and raising it back when frames get faster.
+/// Frame times are measured on the CPU, so with vsync frames never get faster than the refresh interval.
+pub struct DynamicRenderScale {
+    target_frame_time: Duration,
+    max_scale: f32,
+    scale: f32,
+    measured_time: Duration,
+    measured_frames: u32,
+}
+
+impl DynamicRenderScale {
+    /// Creates dynamic render scale aiming at given frame time, which starts at and never exceeds the max scale.
+    pub fn new(target_frame_time: Duration, max_scale: f32) -> Self {
+        let max_scale = clamp_render_scale(max_scale);
+        DynamicRenderScale {
+            target_frame_time,
+            max_scale,
+            scale: max_scale,
+            measured_time: Duration::new(0, 0),
+            measured_frames: 0,
+        }
+    }
+
+    /// Returns frame time the scale is adjusted to.
+    pub fn target_frame_time(&self) -> Duration {
+        self.target_frame_time
+    }
+
+    /// Sets largest scale, which the scale is lowered to if it's bigger.
+    pub fn set_max_scale(&mut self, max_scale: f32) {
+        self.max_scale = clamp_render_scale(max_scale);
+        self.scale = self.scale.min(self.max_scale);
+    }
+
+    /// Returns current render scale.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Records time of a frame and returns render scale of the next frame. Scale changes once per `ADJUSTMENT_FRAMES` frames,
+    /// by the square root of the ratio of target and average frame time, since rendering time follows the number of pixels.
+    pub fn update(&mut self, frame_time: Duration) -> f32 {
+        self.measured_time += frame_time;
+        self.measured_frames += 1;
+        if self.measured_frames < ADJUSTMENT_FRAMES {
+            return self.scale;
+        }
+        let average = duration_seconds(self.measured_time) / self.measured_frames as f32;
+        self.measured_time = Duration::new(0, 0);
+        self.measured_frames = 0;
+
+        let ratio = duration_seconds(self.target_frame_time) / average.max(std::f32::EPSILON);
+        if ratio < 1.0 || ratio * GROWTH_THRESHOLD > 1.0 {
+            let scale = (self.scale * ratio.sqrt() / RENDER_SCALE_STEP).round() * RENDER_SCALE_STEP;
+            self.scale = clamp_render_scale(scale).min(self.max_scale);
+        }
+        self.scale
+    }
+}
+
+/// Color and depth images the scene is rendered to below the window resolution, or with bloom, along with pipelines drawing to them.
+/// Depth is kept, so that it can be upscaled along with colors.
+pub(crate) struct ScaledTarget {
+    color: Arc<AttachmentImage>,
+    depth: Arc<AttachmentImage>,
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+    debug_pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    format: Format,
+    dimensions: [u32; 2],
+}
+
+impl ScaledTarget {
+    /// Creates images of given dimensions and color format. Colors are in the swapchain format so that the scene looks the same as when it's drawn directly,
+    /// unless bloom needs colors brighter than white.
+    pub(crate) fn new(device: Arc<Device>, shader_set: Arc<ShaderSet>, format: Format, dimensions: [u32; 2]) -> Result<Self, RenderError> {
+        let color_usage = ImageUsage {
+            sampled: true,
+            color_attachment: true,
+            ..ImageUsage::none()
+        };
+        let depth_usage = ImageUsage {
+            sampled: true,
+            depth_stencil_attachment: true,
+            ..ImageUsage::none()
+        };
+        let color = AttachmentImage::with_usage(device.clone(), dimensions, format, color_usage)?;
+        let depth = AttachmentImage::with_usage(device.clone(), dimensions, SCALED_DEPTH_FORMAT, depth_usage)?;
+
+        let render_pass = Arc::new(single_pass_renderpass!(device.clone(),
+                                       attachments: {
+                                           color: {
+                                               load: Clear,
+                                               store: Store,
+                                               format: format,
+                                               samples: 1,
+                                           },
+                                           depth: {
+                                               load: Clear,
+                                               store: Store,
+                                               format: SCALED_DEPTH_FORMAT,
+                                               samples: 1,
+                                           }
+                                       },
+                                       pass: {
+                                           color: [color],
+                                           depth_stencil: {depth}
+                                       }
+                                   )?) as Arc<RenderPassAbstract + Send + Sync>;
+
+        let framebuffer = Arc::new(Framebuffer::start(render_pass.clone())
+                                               .add(color.clone())?
+                                               .add(depth.clone())?
+                                               .build()?) as Arc<FramebufferAbstract + Send + Sync>;
+        let pipelines = create_pipelines(device.clone(), shader_set.clone(), dimensions, render_pass.clone())?;
+        let debug_pipeline = create_debug_pipeline(device, shader_set, dimensions, render_pass)?;
+
+        Ok(ScaledTarget {
+            color,
+            depth,
+            framebuffer,
+            pipelines,
+            debug_pipeline,
+            format,
+            dimensions,
+        })
+    }
+
+    pub(crate) fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    pub(crate) fn format(&self) -> Format {
+        self.format
+    }
+
+    pub(crate) fn color(&self) -> Arc<AttachmentImage> {
+        self.color.clone()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.framebuffer.clone()
+    }
+
+    pub(crate) fn pipelines(&self) -> &HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>> {
+        &self.pipelines
+    }
+
+    pub(crate) fn debug_pipeline(&self) -> Arc<GraphicsPipelineAbstract + Send + Sync> {
+        self.debug_pipeline.clone()
+    }
+}
+
+/// Draws the scaled scene over the whole swapchain image, writing its upscaled depth as well and adding bloom on top of it.
+pub(crate) struct Upscaler {
+    pipeline: Arc<BufferlessPipeline>,
+    color_sampler: Arc<Sampler>,
+    depth_sampler: Arc<Sampler>,
+}
+
+impl Upscaler {
+    pub(crate) fn new(device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                      render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Self, RendererCreationError> {
+        let sampler = |filter: Filter| Sampler::new(device.clone(), filter, filter, MipmapMode::Nearest, SamplerAddressMode::ClampToEdge,
+                                                    SamplerAddressMode::ClampToEdge, SamplerAddressMode::ClampToEdge, 0.0, 1.0, 0.0, 0.0);
+        Ok(Upscaler {
+            pipeline: create_upscale_pipeline(device.clone(), shader_set, dimensions, render_pass)?,
+            color_sampler: sampler(Filter::Linear).map_err(RenderError::from)?,
+            // depths of neighbouring pixels aren't blended, since that would put edges of objects between them
+            depth_sampler: sampler(Filter::Nearest).map_err(RenderError::from)?,
+        })
+    }
+
+    /// Recreates pipeline drawing to images of new dimensions.
+    pub(crate) fn recreate_pipeline(&mut self, device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                                    render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<(), GraphicsPipelineCreationError> {
+        self.pipeline = create_upscale_pipeline(device, shader_set, dimensions, render_pass)?;
+        Ok(())
+    }
+
+    /// Adds command drawing the scaled target, inside an already started render pass. Blurred bloom image is added with given intensity.
+    pub(crate) fn add_commands(&self, command_buffer: AutoCommandBufferBuilder, target: &ScaledTarget, filter: UpscaleFilter,
+                               bloom: Option<(Arc<AttachmentImage>, f32)>) -> Result<AutoCommandBufferBuilder, RenderError> {
+        // without bloom the scene itself is bound in its place, and added with zero intensity
+        let (bloom_image, bloom_intensity) = bloom.unwrap_or_else(|| (target.color.clone(), 0.0));
+        let descriptor_set = PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+                                                     .add_sampled_image(target.color.clone(), self.color_sampler.clone())?
+                                                     .add_sampled_image(target.depth.clone(), self.depth_sampler.clone())?
+                                                     .add_sampled_image(bloom_image, self.color_sampler.clone())?
+                                                     .build()?;
+        let push_constants = UpscalePushConstants {
+            texel_size: [1.0 / target.dimensions[0] as f32, 1.0 / target.dimensions[1] as f32],
+            sharpen: (filter == UpscaleFilter::Sharpened) as u32,
+            bloom_intensity,
+        };
+        Ok(command_buffer.draw(self.pipeline.clone(), &DynamicState::none(), BufferlessVertices { vertices: 3, instances: 1 }, descriptor_set, push_constants)?)
+    }
+}
+
+fn create_upscale_pipeline(device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                           render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Arc<BufferlessPipeline>, GraphicsPipelineCreationError> {
+    // every pixel is written, with depth of the scaled scene in place of the cleared one
+    let depth_stencil = DepthStencil {
+        depth_compare: Compare::Always,
+        depth_write: true,
+        ..DepthStencil::simple_depth_test()
+    };
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(BufferlessDefinition)
+        .vertex_shader(shader_set.upscale_vertex_shader().main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .viewports(std::iter::once(Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }))
+        .fragment_shader(shader_set.upscale_fragment_shader().main_entry_point(), ())
+        .depth_stencil(depth_stencil)
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device)?;
+    Ok(Arc::new(pipeline))
+}
+
+fn duration_seconds(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_dimensions_are_rounded_and_never_empty() {
+        assert_eq!([960, 540], scaled_dimensions([1920, 1080], 0.5));
+        assert_eq!([1, 1], scaled_dimensions([1, 2], 0.25));
+        assert_eq!(MIN_RENDER_SCALE, clamp_render_scale(0.0));
+        assert_eq!(1.0, clamp_render_scale(2.0));
+    }
+
+    #[test]
+    fn dynamic_scale_follows_frame_time() {
+        let mut dynamic_scale = DynamicRenderScale::new(Duration::from_millis(16), 1.0);
+        let run = |dynamic_scale: &mut DynamicRenderScale, millis: u64| {
+            (0..ADJUSTMENT_FRAMES).map(|_| dynamic_scale.update(Duration::from_millis(millis))).last().unwrap()
+        };
+
+        // four times slower frames need a quarter of the pixels
+        assert_eq!(0.5, run(&mut dynamic_scale, 64));
+        // frames slightly faster than the target don't change the scale
+        assert_eq!(0.5, run(&mut dynamic_scale, 15));
+        assert_eq!(1.0, run(&mut dynamic_scale, 4));
+        assert_eq!(MIN_RENDER_SCALE, run(&mut dynamic_scale, 1000));
+
+        dynamic_scale.set_max_scale(0.75);
+        assert_eq!(0.75, run(&mut dynamic_scale, 1));
+    }
+}
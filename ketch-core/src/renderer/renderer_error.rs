@@ -1,28 +1,43 @@
+#[cfg(feature = "vulkan")]
 use std::error::Error;
 
-use vulkano::sync::FlushError;
-use vulkano::command_buffer::CommandBufferExecError;
-use vulkano::command_buffer::BuildError;
-use vulkano::command_buffer::AutoCommandBufferBuilderContextError;
-use vulkano::command_buffer::DrawIndexedError;
-use vulkano::descriptor::descriptor_set::PersistentDescriptorSetBuildError;
-use vulkano::descriptor::descriptor_set::PersistentDescriptorSetError;
-use vulkano::memory::DeviceMemoryAllocError;
-
-use vulkano::command_buffer::BeginRenderPassError;
-use vulkano::OomError;
-use vulkano::swapchain::AcquireError;
-use vulkano::framebuffer::FramebufferCreationError;
-use vulkano::pipeline::GraphicsPipelineCreationError;
-use vulkano::framebuffer::RenderPassCreationError;
-use vulkano::swapchain::SwapchainCreationError;
-use vulkano::swapchain::CapabilitiesError;
-use vulkano::device::DeviceCreationError;
-use vulkano::instance::InstanceCreationError;
+#[cfg(feature = "vulkan")]
+use vulkano::{
+    OomError,
+    buffer::cpu_access::ReadLockError,
+    command_buffer::AutoCommandBufferBuilderContextError,
+    command_buffer::BeginRenderPassError,
+    command_buffer::BuildError,
+    command_buffer::CommandBufferExecError,
+    command_buffer::CopyBufferError,
+    command_buffer::CopyBufferImageError,
+    command_buffer::CopyImageError,
+    command_buffer::DispatchError,
+    command_buffer::DrawError,
+    command_buffer::DrawIndexedError,
+    command_buffer::DrawIndexedIndirectError,
+    descriptor::descriptor_set::PersistentDescriptorSetBuildError,
+    descriptor::descriptor_set::PersistentDescriptorSetError,
+    device::DeviceCreationError,
+    framebuffer::FramebufferCreationError,
+    framebuffer::RenderPassCreationError,
+    image::ImageCreationError,
+    instance::InstanceCreationError,
+    memory::DeviceMemoryAllocError,
+    pipeline::ComputePipelineCreationError,
+    pipeline::GraphicsPipelineCreationError,
+    sampler::SamplerCreationError,
+    swapchain::AcquireError,
+    swapchain::CapabilitiesError,
+    swapchain::SwapchainCreationError,
+    sync::FlushError,
+};
+#[cfg(feature = "vulkan")]
 use vulkano_win::{CreationError as WindowCreationError};
 
 use quick_error::quick_error; 
 
+#[cfg(feature = "vulkan")]
 quick_error! {
     #[derive(Debug)]
     pub enum RenderError {
@@ -76,6 +91,11 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        DrawError(err: DrawError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
         AutoCommandBufferBuilderContextError(err: AutoCommandBufferBuilderContextError) {
             from()
             display(x) -> ("{}: {}", x.description(), err)
@@ -96,9 +116,85 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        CopyBufferError(err: CopyBufferError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        CopyBufferImageError(err: CopyBufferImageError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        CopyImageError(err: CopyImageError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ImageCreationError(err: ImageCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        DispatchError(err: DispatchError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        DrawIndexedIndirectError(err: DrawIndexedIndirectError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        ReadLockError(err: ReadLockError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SamplerCreationError(err: SamplerCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        RenderPassCreationError(err: RenderPassCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        StaticTextureUpdate(texture: String) {
+            description("only textures created with Texture::upload_dynamic can be updated")
+            display(x) -> ("{}: {}", x.description(), texture)
+        }
+        InvalidTextureRegion(texture: String) {
+            description("updated region doesn't fit in the texture or doesn't match number of given pixels")
+            display(x) -> ("{}: {}", x.description(), texture)
+        }
+        MissingUploader(texture: String) {
+            description("textures on the GPU can only be updated with an uploader")
+            display(x) -> ("{}: {}", x.description(), texture)
+        }
+        CpuOnlyAsset(asset: String) {
+            description("asset is kept only on the CPU, so it can't be drawn by the Vulkan renderer")
+            display(x) -> ("{}: {}", x.description(), asset)
+        }
+    }
+}
+
+// without Vulkan assets are kept on the CPU, so only errors of updating their pixels remain
+#[cfg(not(feature = "vulkan"))]
+quick_error! {
+    #[derive(Debug)]
+    pub enum RenderError {
+        StaticTextureUpdate(texture: String) {
+            display("only dynamic textures can be updated: {}", texture)
+        }
+        InvalidTextureRegion(texture: String) {
+            display("updated region doesn't fit in the texture or doesn't match number of given pixels: {}", texture)
+        }
     }
 }
 
+#[cfg(feature = "vulkan")]
 quick_error! {
     #[derive(Debug)]
     pub enum RendererCreationError {
@@ -137,13 +233,47 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        ComputePipelineCreationError(err: ComputePipelineCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
         RenderPassCreationError(err: RenderPassCreationError) {
             from()
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        ImageCreationError(err: ImageCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        RenderError(err: RenderError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        OomError(err: OomError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        DeviceMemoryAllocError(err: DeviceMemoryAllocError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
         NoPhysicalDeviceError {
             display("NoPhysicalDeviceError: couldn't find usable physical device")
         }
+        NoSurfaceFormatError {
+            display("NoSurfaceFormatError: surface doesn't support any format")
+        }
+        NoGraphicsQueueError {
+            display("NoGraphicsQueueError: device doesn't have a queue family supporting graphics")
+        }
+        NoPresentQueueError {
+            display("NoPresentQueueError: device doesn't have a queue family able to present to the surface")
+        }
     } 
 }
\ No newline at end of file
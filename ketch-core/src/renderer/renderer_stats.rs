@@ -0,0 +1,64 @@
+use crate::resource::mesh::Topology;
+
+/// Counters describing work done by the renderer.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct RendererStats {
+    /// Objects drawn in the last frame. When objects are culled on the GPU, it includes objects culled by it.
+    pub objects_rendered: u32,
+    /// Objects skipped in the last frame, because they were outside of the camera view. Objects culled on the GPU aren't counted.
+    pub objects_culled: u32,
+    /// Instances of instanced meshes drawn in the last frame.
+    pub instances_rendered: u32,
+    /// Triangles of objects and instanced meshes drawn in the last frame. When objects are culled on the GPU, it includes triangles of culled objects.
+    pub triangles_rendered: u64,
+    /// Draw calls recorded in the last frame.
+    pub draw_calls: u32,
+    /// Descriptor sets created in the last frame.
+    pub descriptor_sets_created: u32,
+    /// Uniform buffers allocated in the last frame, because buffers of the ring were too small or still read by the GPU.
+    pub uniform_buffers_allocated: u32,
+    /// Objects whose world transforms were recomputed in the last frame, because they or their parents moved.
+    pub transforms_updated: u32,
+    /// Number of times swapchain was recreated since the renderer was created.
+    pub swapchain_recreations: u32,
+}
+
+impl RendererStats {
+    /// Resets counters which are collected per frame.
+    pub fn reset_frame_counters(&mut self) {
+        self.objects_rendered = 0;
+        self.objects_culled = 0;
+        self.instances_rendered = 0;
+        self.triangles_rendered = 0;
+        self.draw_calls = 0;
+        self.descriptor_sets_created = 0;
+        self.uniform_buffers_allocated = 0;
+        self.transforms_updated = 0;
+    }
+
+    /// Adds triangles drawn from given number of indices of a mesh. Lines and points aren't counted.
+    pub fn add_triangles(&mut self, topology: Topology, index_count: usize) {
+        if topology == Topology::TriangleList {
+            self.triangles_rendered += (index_count / 3) as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_counters_are_reset_and_lines_are_not_triangles() {
+        let mut stats = RendererStats::default();
+        stats.add_triangles(Topology::TriangleList, 36);
+        stats.add_triangles(Topology::LineList, 24);
+        stats.swapchain_recreations = 2;
+        assert_eq!(12, stats.triangles_rendered);
+
+        stats.reset_frame_counters();
+
+        assert_eq!(0, stats.triangles_rendered);
+        assert_eq!(2, stats.swapchain_recreations);
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use image::{Rgba, RgbaImage};
+use vulkano::device::Device;
+use vulkano::framebuffer::FramebufferAbstract;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::pipeline::GraphicsPipelineAbstract;
+
+use crate::renderer::{create_pipelines, PipelineKey};
+use crate::renderer::offscreen_target::OffscreenTarget;
+use crate::renderer::renderer_error::RendererCreationError;
+use crate::renderer::shader::ShaderSet;
+
+/// Offscreen image which the active scene is rendered to and read back from, e.g. by golden image tests.
+/// It doesn't draw to the window, so it works the same when the window isn't visible.
+pub struct SceneCapture {
+    target: OffscreenTarget,
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+    dimensions: [u32; 2],
+}
+
+impl SceneCapture {
+    /// Creates capture image of given width and height and pipelines for every material state.
+    pub(crate) fn new(device: Arc<Device>, shader_set: Arc<ShaderSet>, dimensions: [u32; 2]) -> Result<Self, RendererCreationError> {
+        let target = OffscreenTarget::new(device.clone(), dimensions)?;
+        let pipelines = create_pipelines(device, shader_set, dimensions, target.render_pass())?;
+
+        Ok(SceneCapture {
+            target,
+            pipelines,
+            dimensions,
+        })
+    }
+
+    /// Returns width and height of captured images.
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    pub(crate) fn image(&self) -> Arc<AttachmentImage> {
+        self.target.image()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.target.framebuffer()
+    }
+
+    pub(crate) fn pipelines(&self) -> &HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>> {
+        &self.pipelines
+    }
+}
+
+/// Shrinks image captured at a multiple of the target size, averaging each block of `factor` by `factor` pixels,
+/// which gives super-sampled screenshots with smooth edges. Pixels at the right and bottom edges which don't fill a whole block are dropped.
+pub fn downsample(image: &RgbaImage, factor: u32) -> RgbaImage {
+    let factor = factor.max(1);
+    let (width, height) = ((image.width() / factor).max(1), (image.height() / factor).max(1));
+    RgbaImage::from_fn(width, height, |x, y| {
+        let mut sum = [0u32; 4];
+        let mut count = 0;
+        for source_y in (y * factor..(y + 1) * factor).filter(|source_y| *source_y < image.height()) {
+            for source_x in (x * factor..(x + 1) * factor).filter(|source_x| *source_x < image.width()) {
+                let channels = image.get_pixel(source_x, source_y).data;
+                for (channel_sum, channel) in sum.iter_mut().zip(channels.iter()) {
+                    *channel_sum += u32::from(*channel);
+                }
+                count += 1;
+            }
+        }
+        let average = |channel: u32| (channel / count.max(1)) as u8;
+        Rgba([average(sum[0]), average(sum[1]), average(sum[2]), average(sum[3])])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsampled_pixels_average_their_block() {
+        let image = RgbaImage::from_fn(4, 2, |x, _| if x % 2 == 0 { Rgba([200, 0, 0, 255]) } else { Rgba([0, 100, 0, 255]) });
+
+        let downsampled = downsample(&image, 2);
+
+        assert_eq!((2, 1), downsampled.dimensions());
+        assert_eq!(Rgba([100, 50, 0, 255]), *downsampled.get_pixel(1, 0));
+        assert_eq!(&*image, &*downsample(&image, 1));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use image::RgbaImage;
+
+use crate::renderer::backend::{DrawCommand, MeshHandle, RenderBackend, TextureHandle};
+use crate::renderer::renderer_stats::RendererStats;
+use crate::resource::AssetManager;
+use crate::resource::camera::Camera;
+use crate::resource::mesh::{Mesh, Topology};
+use crate::resource::object::Object;
+use crate::resource::scene::static_batch::StaticBatch;
+use crate::resource::texture::Texture;
+
+/// Draws objects of the active scene with a render backend. Meshes and textures are uploaded to the backend the first time they're drawn.
+/// Unlike the Vulkan renderer it draws only triangle meshes with their texture and base color, so particles, UI and debug shapes aren't drawn.
+/// Textures have to be kept on the CPU, like the ones of asset managers created without a device, otherwise they're drawn white.
+pub struct SceneRenderer<B: RenderBackend> {
+    backend: B,
+    // uploaded assets are kept alive, so that their addresses aren't reused by other assets
+    meshes: HashMap<usize, (Arc<RwLock<Mesh>>, MeshHandle)>,
+    textures: HashMap<usize, (Arc<Texture>, TextureHandle)>,
+    white_texture: TextureHandle,
+    stats: RendererStats,
+}
+
+impl<B: RenderBackend> SceneRenderer<B> {
+    /// Creates scene renderer drawing with given backend.
+    pub fn new(mut backend: B) -> Result<Self, B::Error> {
+        let white_texture = backend.upload_texture(&RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255])))?;
+        Ok(SceneRenderer {
+            backend,
+            meshes: HashMap::new(),
+            textures: HashMap::new(),
+            white_texture,
+            stats: RendererStats::default(),
+        })
+    }
+
+    /// Returns the backend frames are drawn with.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns a mutable reference to the backend frames are drawn with.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Returns statistics of the last frame.
+    pub fn stats(&self) -> RendererStats {
+        self.stats
+    }
+
+    /// Draws a frame of the active scene as seen by its view camera. Frame without an active scene is empty.
+    pub fn render(&mut self, asset_manager: &AssetManager) -> Result<(), B::Error> {
+        self.stats.reset_frame_counters();
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => {
+                self.backend.begin_frame(&Camera::new(), &[])?;
+                return self.backend.end_frame();
+            },
+        };
+        let camera = scene.view_camera();
+        self.backend.begin_frame(&camera, scene.lights())?;
+
+        // batched objects are drawn by their static batches
+        let visibility_mask = camera.visibility_mask();
+        let objects = scene.objects().iter()
+                                     .filter(|object| !object.is_batched())
+                                     .chain(scene.static_batches().iter().map(StaticBatch::object))
+                                     .filter(|object| visibility_mask.intersects(object.render_layers()));
+        for object in objects {
+            let mesh = match object.mesh() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let (mesh_handle, mesh_texture, index_count) = {
+                let mesh_data = mesh.read().unwrap();
+                if mesh_data.topology() != Topology::TriangleList {
+                    continue;
+                }
+                (self.upload_mesh(&mesh, &mesh_data)?, mesh_data.texture(), mesh_data.indices().len())
+            };
+            let (texture, color) = object_texture(object, mesh_texture, asset_manager);
+            let texture_handle = self.upload_texture(texture)?;
+            self.backend.draw(DrawCommand {
+                color,
+                ..DrawCommand::new(mesh_handle, texture_handle, scene.world_matrix(object))
+            });
+            self.stats.add_triangles(Topology::TriangleList, index_count);
+            self.stats.objects_rendered += 1;
+            self.stats.draw_calls += 1;
+        }
+        self.backend.end_frame()
+    }
+
+    /// Returns handle of the mesh, uploading it to the backend if it wasn't drawn before.
+    fn upload_mesh(&mut self, mesh: &Arc<RwLock<Mesh>>, mesh_data: &Mesh) -> Result<MeshHandle, B::Error> {
+        let key = &**mesh as *const RwLock<Mesh> as usize;
+        if let Some((_mesh, handle)) = self.meshes.get(&key) {
+            return Ok(*handle);
+        }
+        let handle = self.backend.upload_mesh(mesh_data.vertices(), mesh_data.indices())?;
+        self.meshes.insert(key, (mesh.clone(), handle));
+        Ok(handle)
+    }
+
+    /// Returns handle of the texture, uploading it to the backend if it wasn't drawn before. Textures without pixels on the CPU are white.
+    fn upload_texture(&mut self, texture: Arc<Texture>) -> Result<TextureHandle, B::Error> {
+        let key = &*texture as *const Texture as usize;
+        if let Some((_texture, handle)) = self.textures.get(&key) {
+            return Ok(*handle);
+        }
+        let handle = match texture.pixels() {
+            Some(pixels) => self.backend.upload_texture(&pixels)?,
+            None => self.white_texture,
+        };
+        self.textures.insert(key, (texture, handle));
+        Ok(handle)
+    }
+}
+
+/// Returns texture and color an object is drawn with, which are the albedo texture and base color of its material if it has one.
+fn object_texture(object: &Object, mesh_texture: Arc<Texture>, asset_manager: &AssetManager) -> (Arc<Texture>, [f32; 4]) {
+    match object.material() {
+        Some(material) => {
+            let material = material.read().unwrap();
+            let texture = material.albedo_texture().and_then(|name| asset_manager.texture(name)).unwrap_or(mesh_texture);
+            let (r, g, b, a) = material.base_color();
+            (texture, [r, g, b, a])
+        },
+        None => (mesh_texture, [1.0; 4]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::DynamicImage;
+    use crate::renderer::null_backend::NullBackend;
+    use crate::resource::material::Material;
+    use crate::resource::object::ObjectBuilder;
+    use crate::resource::primitives::Primitive;
+    use crate::resource::scene::Scene;
+
+    #[test]
+    fn objects_of_active_scene_are_drawn_and_assets_uploaded_once() {
+        let mut asset_manager = AssetManager::without_device().unwrap();
+        let cube = asset_manager.primitive_mesh(Primitive::Cube).unwrap();
+        let texture = asset_manager.create_texture("checker", DynamicImage::new_rgba8(4, 2)).unwrap();
+        asset_manager.add_texture(texture);
+        let mut material = Material::new("red");
+        material.set_base_color(1.0, 0.0, 0.0, 1.0);
+        material.set_albedo_texture(Some("checker".to_string()));
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let mut red_cube = ObjectBuilder::new("red cube").with_mesh(cube.clone()).build();
+        red_cube.set_material(Some(Arc::new(RwLock::new(material))));
+        scene.add_object(red_cube);
+        scene.add_object(ObjectBuilder::new("cube").with_mesh(cube).build());
+        asset_manager.set_active_scene(scene);
+        let mut scene_renderer = SceneRenderer::new(NullBackend::new()).unwrap();
+
+        scene_renderer.render(&asset_manager).unwrap();
+        scene_renderer.render(&asset_manager).unwrap();
+
+        let backend = scene_renderer.backend();
+        assert_eq!(2, backend.frames_rendered());
+        assert_eq!(1, backend.mesh_count());
+        let draws = backend.last_frame();
+        assert_eq!(2, draws.len());
+        assert_eq!([1.0, 0.0, 0.0, 1.0], draws[0].color);
+        assert_eq!(Some((4, 2)), backend.texture_size(draws[0].texture));
+        assert_eq!([1.0; 4], draws[1].color);
+        assert_eq!(2, scene_renderer.stats().objects_rendered);
+        assert_eq!(24, scene_renderer.stats().triangles_rendered);
+    }
+
+    #[test]
+    fn frame_without_active_scene_is_empty() {
+        let asset_manager = AssetManager::without_device().unwrap();
+        let mut scene_renderer = SceneRenderer::new(NullBackend::new()).unwrap();
+
+        scene_renderer.render(&asset_manager).unwrap();
+
+        assert_eq!(1, scene_renderer.backend().frames_rendered());
+        assert!(scene_renderer.backend().last_frame().is_empty());
+    }
+}
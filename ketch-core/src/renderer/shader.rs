@@ -1,35 +1,117 @@
 pub mod vertex_shader;
 pub mod fragment_shader;
+pub mod debug_vertex_shader;
+pub mod debug_fragment_shader;
+pub mod texture_preview_vertex_shader;
+pub mod texture_preview_fragment_shader;
+pub mod sprite_vertex_shader;
+pub mod sprite_fragment_shader;
+pub mod particle_vertex_shader;
+pub mod particle_fragment_shader;
+pub mod upscale_vertex_shader;
+pub mod upscale_fragment_shader;
+pub mod bloom_bright_pass_fragment_shader;
+pub mod bloom_blur_fragment_shader;
+pub mod culling_compute_shader;
+pub mod luminance_histogram_compute_shader;
 
 use vulkano::device::Device;
 use std::sync::Arc;
 
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer};
 use vulkano::pipeline::vertex::SingleBufferDefinition;
 
-use crate::resource::mesh::Vertex;
+use crate::resource::mesh::vertex_format::{LightmappedVertex, VertexFormat};
+use crate::renderer::renderer_error::RendererCreationError;
+use crate::renderer::mesh_vertex_definition::MeshVertexDefinition;
+use crate::renderer::debug_draw::DebugVertex;
+use crate::renderer::ui_renderer::SpriteVertex;
+use crate::renderer::particle_renderer::ParticleVertex;
 
 /// Contains shaders used by the engine.
 pub struct ShaderSet {
     vertex_shader: vertex_shader::Shader,
     fragment_shader: fragment_shader::Shader,
+    debug_vertex_shader: debug_vertex_shader::Shader,
+    debug_fragment_shader: debug_fragment_shader::Shader,
+    texture_preview_vertex_shader: texture_preview_vertex_shader::Shader,
+    texture_preview_fragment_shader: texture_preview_fragment_shader::Shader,
+    sprite_vertex_shader: sprite_vertex_shader::Shader,
+    sprite_fragment_shader: sprite_fragment_shader::Shader,
+    particle_vertex_shader: particle_vertex_shader::Shader,
+    particle_fragment_shader: particle_fragment_shader::Shader,
+    upscale_vertex_shader: upscale_vertex_shader::Shader,
+    upscale_fragment_shader: upscale_fragment_shader::Shader,
+    bloom_bright_pass_fragment_shader: bloom_bright_pass_fragment_shader::Shader,
+    bloom_blur_fragment_shader: bloom_blur_fragment_shader::Shader,
+    culling_compute_shader: culling_compute_shader::Shader,
+    luminance_histogram_compute_shader: luminance_histogram_compute_shader::Shader,
+    default_vertex: Arc<BufferAccess + Send + Sync>,
 }
 
 impl ShaderSet {
-    /// Loads shaders.
-    pub fn load(device: Arc<Device>) -> Self {
+    /// Loads shaders. Fails if the driver can't create shader modules or the default vertex buffer.
+    pub fn load(device: Arc<Device>) -> Result<Self, RendererCreationError> {
 
-        let v_s = vertex_shader::Shader::load(device.clone()).expect("Failed to load vertex shader!");
-        let f_s = fragment_shader::Shader::load(device.clone()).expect("Failed to load fragment shader!");
+        let v_s = vertex_shader::Shader::load(device.clone())?;
+        let f_s = fragment_shader::Shader::load(device.clone())?;
+        let debug_v_s = debug_vertex_shader::Shader::load(device.clone())?;
+        let debug_f_s = debug_fragment_shader::Shader::load(device.clone())?;
+        let texture_preview_v_s = texture_preview_vertex_shader::Shader::load(device.clone())?;
+        let texture_preview_f_s = texture_preview_fragment_shader::Shader::load(device.clone())?;
+        let sprite_v_s = sprite_vertex_shader::Shader::load(device.clone())?;
+        let sprite_f_s = sprite_fragment_shader::Shader::load(device.clone())?;
+        let particle_v_s = particle_vertex_shader::Shader::load(device.clone())?;
+        let particle_f_s = particle_fragment_shader::Shader::load(device.clone())?;
+        let upscale_v_s = upscale_vertex_shader::Shader::load(device.clone())?;
+        let upscale_f_s = upscale_fragment_shader::Shader::load(device.clone())?;
+        let bloom_bright_pass_f_s = bloom_bright_pass_fragment_shader::Shader::load(device.clone())?;
+        let bloom_blur_f_s = bloom_blur_fragment_shader::Shader::load(device.clone())?;
+        let culling_c_s = culling_compute_shader::Shader::load(device.clone())?;
+        let luminance_histogram_c_s = luminance_histogram_compute_shader::Shader::load(device.clone())?;
+        // normal points up, so that meshes without normals are lit from above
+        let default_vertex = LightmappedVertex { position: [0.0; 3], normal: [0.0, 1.0, 0.0], tex_coord: [0.0; 2], lightmap_coord: [0.0; 2] };
+        let default_vertex = CpuAccessibleBuffer::from_iter(device, BufferUsage::vertex_buffer(), std::iter::once(default_vertex))?;
 
-        ShaderSet {
+        Ok(ShaderSet {
             vertex_shader: v_s,
             fragment_shader: f_s,
-        }
+            debug_vertex_shader: debug_v_s,
+            debug_fragment_shader: debug_f_s,
+            texture_preview_vertex_shader: texture_preview_v_s,
+            texture_preview_fragment_shader: texture_preview_f_s,
+            sprite_vertex_shader: sprite_v_s,
+            sprite_fragment_shader: sprite_f_s,
+            particle_vertex_shader: particle_v_s,
+            particle_fragment_shader: particle_f_s,
+            upscale_vertex_shader: upscale_v_s,
+            upscale_fragment_shader: upscale_f_s,
+            bloom_bright_pass_fragment_shader: bloom_bright_pass_f_s,
+            bloom_blur_fragment_shader: bloom_blur_f_s,
+            culling_compute_shader: culling_c_s,
+            luminance_histogram_compute_shader: luminance_histogram_c_s,
+            default_vertex,
+        })
     }
 
-    /// Returns vertex shader layout.
-    pub fn vertex_layout() -> SingleBufferDefinition<Vertex> {
-        SingleBufferDefinition::<Vertex>::new()
+    /// Returns vertex shader layout for meshes of given vertex format. Mesh vertices are read from the first buffer and indices of drawn objects from the second one.
+    pub fn vertex_layout(&self, format: VertexFormat) -> MeshVertexDefinition {
+        MeshVertexDefinition::new(format, self.default_vertex.clone())
+    }
+
+    /// Returns debug line vertex shader layout.
+    pub fn debug_vertex_layout() -> SingleBufferDefinition<DebugVertex> {
+        SingleBufferDefinition::<DebugVertex>::new()
+    }
+
+    /// Returns sprite vertex shader layout.
+    pub fn sprite_vertex_layout() -> SingleBufferDefinition<SpriteVertex> {
+        SingleBufferDefinition::<SpriteVertex>::new()
+    }
+
+    /// Returns particle vertex shader layout.
+    pub fn particle_vertex_layout() -> SingleBufferDefinition<ParticleVertex> {
+        SingleBufferDefinition::<ParticleVertex>::new()
     }
 
     /// Returns vertex shader.
@@ -41,4 +123,74 @@ impl ShaderSet {
     pub fn fragment_shader(&self) -> &fragment_shader::Shader {
         &self.fragment_shader
     }
-}
\ No newline at end of file
+
+    /// Returns debug line vertex shader.
+    pub fn debug_vertex_shader(&self) -> &debug_vertex_shader::Shader {
+        &self.debug_vertex_shader
+    }
+
+    /// Returns debug line fragment shader.
+    pub fn debug_fragment_shader(&self) -> &debug_fragment_shader::Shader {
+        &self.debug_fragment_shader
+    }
+
+    /// Returns texture preview vertex shader.
+    pub fn texture_preview_vertex_shader(&self) -> &texture_preview_vertex_shader::Shader {
+        &self.texture_preview_vertex_shader
+    }
+
+    /// Returns texture preview fragment shader.
+    pub fn texture_preview_fragment_shader(&self) -> &texture_preview_fragment_shader::Shader {
+        &self.texture_preview_fragment_shader
+    }
+
+    /// Returns sprite vertex shader.
+    pub fn sprite_vertex_shader(&self) -> &sprite_vertex_shader::Shader {
+        &self.sprite_vertex_shader
+    }
+
+    /// Returns sprite fragment shader.
+    pub fn sprite_fragment_shader(&self) -> &sprite_fragment_shader::Shader {
+        &self.sprite_fragment_shader
+    }
+
+    /// Returns particle vertex shader.
+    pub fn particle_vertex_shader(&self) -> &particle_vertex_shader::Shader {
+        &self.particle_vertex_shader
+    }
+
+    /// Returns particle fragment shader.
+    pub fn particle_fragment_shader(&self) -> &particle_fragment_shader::Shader {
+        &self.particle_fragment_shader
+    }
+
+    /// Returns vertex shader drawing a triangle over the whole viewport, used to upscale the scaled scene and by bloom passes.
+    pub fn upscale_vertex_shader(&self) -> &upscale_vertex_shader::Shader {
+        &self.upscale_vertex_shader
+    }
+
+    /// Returns fragment shader upscaling the scaled scene, optionally sharpening it.
+    pub fn upscale_fragment_shader(&self) -> &upscale_fragment_shader::Shader {
+        &self.upscale_fragment_shader
+    }
+
+    /// Returns fragment shader extracting pixels brighter than the bloom threshold into the half resolution bloom image.
+    pub fn bloom_bright_pass_fragment_shader(&self) -> &bloom_bright_pass_fragment_shader::Shader {
+        &self.bloom_bright_pass_fragment_shader
+    }
+
+    /// Returns fragment shader blurring the bloom image along one axis.
+    pub fn bloom_blur_fragment_shader(&self) -> &bloom_blur_fragment_shader::Shader {
+        &self.bloom_blur_fragment_shader
+    }
+
+    /// Returns compute shader culling objects on the GPU.
+    pub fn culling_compute_shader(&self) -> &culling_compute_shader::Shader {
+        &self.culling_compute_shader
+    }
+
+    /// Returns compute shader building luminance histogram of rendered frames.
+    pub fn luminance_histogram_compute_shader(&self) -> &luminance_histogram_compute_shader::Shader {
+        &self.luminance_histogram_compute_shader
+    }
+}
@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "fragment",
+    path: "data/shader/bloom_bright_pass_fragment_shader.frag",
+}
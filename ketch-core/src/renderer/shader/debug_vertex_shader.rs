@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "vertex",
+    path: "data/shader/debug_vertex_shader.vert",
+}
@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "compute",
+    path: "data/shader/luminance_histogram_compute_shader.comp",
+}
@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "fragment",
+    path: "data/shader/particle_fragment_shader.frag",
+}
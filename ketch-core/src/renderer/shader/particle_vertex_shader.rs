@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "vertex",
+    path: "data/shader/particle_vertex_shader.vert",
+}
@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "vertex",
+    path: "data/shader/sprite_vertex_shader.vert",
+}
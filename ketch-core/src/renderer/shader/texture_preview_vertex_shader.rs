@@ -0,0 +1,4 @@
+vulkano_shaders::shader!{
+    ty: "vertex",
+    path: "data/shader/texture_preview_vertex_shader.vert",
+}
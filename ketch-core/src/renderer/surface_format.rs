@@ -0,0 +1,59 @@
+use vulkano::format::Format;
+use vulkano::swapchain::ColorSpace;
+
+/// Formats preferred for standard dynamic range output, best first.
+/// Srgb formats come first, because shaders output linear colors which have to be gamma corrected when they're written.
+pub const SDR_FORMATS: [Format; 4] = [
+    Format::B8G8R8A8Srgb,
+    Format::R8G8B8A8Srgb,
+    Format::B8G8R8A8Unorm,
+    Format::R8G8B8A8Unorm,
+];
+
+/// Formats preferred for high dynamic range output, best first. They're tried before standard formats when HDR output is requested.
+pub const HDR_FORMATS: [Format; 3] = [
+    Format::R16G16B16A16Sfloat,
+    Format::A2B10G10R10UnormPack32,
+    Format::A2R10G10B10UnormPack32,
+];
+
+/// Chooses swapchain format and color space from ones supported by the surface.
+/// Format set in settings is used if it's supported, then HDR formats if they're requested and then standard formats.
+/// Only formats in the sRGB non-linear color space are considered, because swapchains are always created in it.
+/// Falls back to the first supported format if none of the preferred ones is supported.
+pub fn choose_surface_format(supported_formats: &[(Format, ColorSpace)], preferred_format: Option<Format>, hdr_output: bool) -> Option<(Format, ColorSpace)> {
+    let hdr_formats: &[Format] = if hdr_output { &HDR_FORMATS } else { &[] };
+    let candidates = preferred_format.iter().chain(hdr_formats.iter()).chain(SDR_FORMATS.iter());
+    for candidate in candidates {
+        let supported = supported_formats.iter().find(|&&(format, color_space)| format == *candidate && color_space == ColorSpace::SrgbNonLinear);
+        if let Some(&surface_format) = supported {
+            return Some(surface_format);
+        }
+    }
+    supported_formats.iter().find(|&&(_, color_space)| color_space == ColorSpace::SrgbNonLinear)
+                     .or_else(|| supported_formats.first())
+                     .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_chosen_by_preference() {
+        let supported = vec![
+            (Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+            (Format::A2B10G10R10UnormPack32, ColorSpace::SrgbNonLinear),
+            (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear),
+        ];
+        assert_eq!(Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)), choose_surface_format(&supported, None, false));
+        assert_eq!(Some((Format::A2B10G10R10UnormPack32, ColorSpace::SrgbNonLinear)), choose_surface_format(&supported, None, true));
+        assert_eq!(Some((Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear)), choose_surface_format(&supported, Some(Format::B8G8R8A8Unorm), true));
+        // unsupported format from settings is ignored
+        assert_eq!(Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)), choose_surface_format(&supported, Some(Format::R8G8B8A8Srgb), false));
+
+        let unusual = vec![(Format::R5G6B5UnormPack16, ColorSpace::SrgbNonLinear)];
+        assert_eq!(Some((Format::R5G6B5UnormPack16, ColorSpace::SrgbNonLinear)), choose_surface_format(&unusual, None, false));
+        assert_eq!(None, choose_surface_format(&[], None, false));
+    }
+}
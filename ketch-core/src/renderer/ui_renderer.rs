@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::impl_vertex;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract, GraphicsPipelineCreationError};
+use vulkano::pipeline::blend::AttachmentBlend;
+use vulkano::pipeline::viewport::Viewport;
+
+use crate::renderer::renderer_error::{RenderError, RendererCreationError};
+use crate::renderer::renderer_stats::RendererStats;
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::shader::sprite_vertex_shader::ty::PushConstants as SpritePushConstants;
+use crate::resource::AssetManager;
+use crate::resource::font::TEXT_PADDING;
+use crate::resource::texture::Texture;
+use crate::ui::Ui;
+use crate::ui::ui_node::{Rect, UiElement};
+
+/// Brightness of a button under the cursor relative to its color.
+const HOVERED_BUTTON_BRIGHTNESS: f32 = 1.2;
+/// Brightness of a held down button relative to its color.
+const PRESSED_BUTTON_BRIGHTNESS: f32 = 0.8;
+
+/// Vertex of a sprite in window pixels.
+#[derive(Clone, Copy)]
+pub struct SpriteVertex {
+    pub position: [f32; 2],
+    pub tex_coord: [f32; 2],
+}
+
+impl_vertex!(SpriteVertex, position, tex_coord);
+
+/// Font name, text and font size bits identifying rasterized text.
+type TextKey = (String, String, u32);
+
+/// Draws in-game UI on top of the scene as textured sprites.
+/// Text is rasterized into textures, which are kept while the text is shown.
+pub(crate) struct UiRenderer {
+    pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    white_texture: Arc<Texture>,
+    text_textures: HashMap<TextKey, Arc<Texture>>,
+}
+
+impl UiRenderer {
+    pub(crate) fn new(device: Arc<Device>, upload_queue: Arc<Queue>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                      render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Self, RendererCreationError> {
+        let white_image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255])));
+        Ok(UiRenderer {
+            pipeline: create_sprite_pipeline(device.clone(), shader_set, dimensions, render_pass)?,
+            white_texture: Arc::new(Texture::new("ui white", white_image, upload_queue, device)?),
+            text_textures: HashMap::new(),
+        })
+    }
+
+    /// Recreates pipeline drawing to images of new dimensions.
+    pub(crate) fn recreate_pipeline(&mut self, device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                                    render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<(), GraphicsPipelineCreationError> {
+        self.pipeline = create_sprite_pipeline(device, shader_set, dimensions, render_pass)?;
+        Ok(())
+    }
+
+    /// Adds commands drawing visible UI nodes, inside an already started render pass.
+    pub(crate) fn add_commands(&mut self, command_buffer: AutoCommandBufferBuilder, ui: &Ui, asset_manager: &AssetManager, device: Arc<Device>,
+                               upload_queue: Arc<Queue>, dimensions: [f32; 2], stats: &mut RendererStats) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let mut sprites: Vec<(Rect, Arc<Texture>, [f32; 4])> = Vec::new();
+        let mut used_text = HashSet::new();
+
+        for (id, node, rect) in ui.draw_list() {
+            match &node.element {
+                UiElement::Panel => sprites.push((rect, self.white_texture.clone(), node.color)),
+                UiElement::Image { texture } => {
+                    if let Some(texture) = asset_manager.texture(texture) {
+                        sprites.push((rect, texture, node.color));
+                    }
+                },
+                UiElement::Text { text, font, font_size } => {
+                    if let Some((texture, size)) = self.text_texture(text, font, *font_size, asset_manager, &device, &upload_queue, &mut used_text)? {
+                        sprites.push((text_rect(rect.x, rect.y, size), texture, node.color));
+                    }
+                },
+                UiElement::Button { label, font, font_size, label_color } => {
+                    let brightness = if ui.is_pressed(id) {
+                        PRESSED_BUTTON_BRIGHTNESS
+                    } else if ui.is_hovered(id) {
+                        HOVERED_BUTTON_BRIGHTNESS
+                    } else {
+                        1.0
+                    };
+                    let [r, g, b, a] = node.color;
+                    sprites.push((rect, self.white_texture.clone(), [r * brightness, g * brightness, b * brightness, a]));
+                    if let Some((texture, size)) = self.text_texture(label, font, *font_size, asset_manager, &device, &upload_queue, &mut used_text)? {
+                        let x = rect.x + (rect.width - size.0) * 0.5;
+                        let y = rect.y + (rect.height - size.1) * 0.5;
+                        sprites.push((text_rect(x, y, size), texture, *label_color));
+                    }
+                },
+            }
+        }
+        self.text_textures.retain(|key, _| used_text.contains(key));
+        self.add_sprite_commands(command_buffer, sprites, device, dimensions, stats)
+    }
+
+    /// Adds commands drawing rectangles filled with colors, inside an already started render pass.
+    pub(crate) fn add_rect_commands(&self, command_buffer: AutoCommandBufferBuilder, rects: &[(Rect, [f32; 4])], device: Arc<Device>,
+                                    dimensions: [f32; 2], stats: &mut RendererStats) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let sprites = rects.iter().map(|(rect, color)| (*rect, self.white_texture.clone(), *color)).collect();
+        self.add_sprite_commands(command_buffer, sprites, device, dimensions, stats)
+    }
+
+    /// Adds commands drawing textured rectangles multiplied by colors, one draw call for each.
+    fn add_sprite_commands(&self, mut command_buffer: AutoCommandBufferBuilder, sprites: Vec<(Rect, Arc<Texture>, [f32; 4])>, device: Arc<Device>,
+                           dimensions: [f32; 2], stats: &mut RendererStats) -> Result<AutoCommandBufferBuilder, RenderError> {
+        for (rect, texture, color) in sprites {
+            // textures are flipped vertically when they're loaded
+            let vertices = [
+                SpriteVertex { position: [rect.x, rect.y], tex_coord: [0.0, 1.0] },
+                SpriteVertex { position: [rect.x + rect.width, rect.y], tex_coord: [1.0, 1.0] },
+                SpriteVertex { position: [rect.x + rect.width, rect.y + rect.height], tex_coord: [1.0, 0.0] },
+                SpriteVertex { position: [rect.x, rect.y], tex_coord: [0.0, 1.0] },
+                SpriteVertex { position: [rect.x + rect.width, rect.y + rect.height], tex_coord: [1.0, 0.0] },
+                SpriteVertex { position: [rect.x, rect.y + rect.height], tex_coord: [0.0, 0.0] },
+            ];
+            let vertex_buffer = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::vertex_buffer(), vertices.iter().cloned())?;
+            let (image_buffer, sampler) = texture.sampled_image()?;
+            let descriptor_set = PersistentDescriptorSet::start(self.pipeline.clone(), 0)
+                                                         .add_sampled_image(image_buffer, sampler)?
+                                                         .build()?;
+            stats.descriptor_sets_created += 1;
+            let push_constants = SpritePushConstants {
+                color,
+                screen_size: dimensions,
+            };
+            command_buffer = command_buffer.draw(self.pipeline.clone(), &DynamicState::none(), vec!(vertex_buffer), descriptor_set, push_constants)?;
+            stats.draw_calls += 1;
+        }
+        Ok(command_buffer)
+    }
+
+    /// Returns texture with rasterized text and size of the text in pixels, rasterizing it if it isn't cached.
+    /// Returns None if font isn't loaded or text is empty.
+    fn text_texture(&mut self, text: &str, font: &str, font_size: f32, asset_manager: &AssetManager, device: &Arc<Device>,
+                    upload_queue: &Arc<Queue>, used_text: &mut HashSet<TextKey>) -> Result<Option<(Arc<Texture>, (f32, f32))>, RenderError> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let font = asset_manager.localization().font(font);
+        let key = (font.to_string(), text.to_string(), font_size.to_bits());
+        let texture = match self.text_textures.get(&key) {
+            Some(texture) => texture.clone(),
+            None => {
+                let image = match asset_manager.font(font) {
+                    Some(font) => font.rasterize(text, font_size),
+                    None => return Ok(None),
+                };
+                let texture = Arc::new(Texture::new(text, DynamicImage::ImageRgba8(image), upload_queue.clone(), device.clone())?);
+                self.text_textures.insert(key.clone(), texture.clone());
+                texture
+            },
+        };
+        used_text.insert(key);
+        let (width, height) = texture.dimensions();
+        let size = ((width - TEXT_PADDING * 2) as f32, (height - TEXT_PADDING * 2) as f32);
+        Ok(Some((texture, size)))
+    }
+}
+
+/// Returns rectangle of rasterized text placed at given position, including padding around it.
+fn text_rect(x: f32, y: f32, size: (f32, f32)) -> Rect {
+    let padding = TEXT_PADDING as f32;
+    Rect { x: x - padding, y: y - padding, width: size.0 + padding * 2.0, height: size.1 + padding * 2.0 }
+}
+
+/// Creates a pipeline drawing alpha blended sprites without depth test.
+fn create_sprite_pipeline(device: Arc<Device>, shader_set: &ShaderSet, dimensions: [u32; 2],
+                          render_pass: Arc<RenderPassAbstract + Send + Sync>) -> Result<Arc<GraphicsPipelineAbstract + Send + Sync>, GraphicsPipelineCreationError> {
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(ShaderSet::sprite_vertex_layout())
+        .vertex_shader(shader_set.sprite_vertex_shader().main_entry_point(), ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .viewports(std::iter::once(Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }))
+        .fragment_shader(shader_set.sprite_fragment_shader().main_entry_point(), ())
+        .blend_collective(AttachmentBlend::alpha_blending())
+        .render_pass(Subpass::from(render_pass, 0).unwrap())
+        .build(device)?;
+
+    Ok(Arc::new(pipeline))
+}
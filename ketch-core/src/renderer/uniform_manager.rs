@@ -1,75 +1,250 @@
 use vulkano::memory::DeviceMemoryAllocError;
-use vulkano::buffer::cpu_pool::CpuBufferPoolSubbuffer;
-use vulkano::buffer::cpu_pool::CpuBufferPool;
-use vulkano::buffer::BufferUsage;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, TypedBufferAccess};
 use vulkano::device::Device;
-use vulkano::memory::pool::StdMemoryPool;
-
-use nalgebra_glm::{Vec3, Mat4};
+use vulkano::impl_vertex;
 
+use std::ops::Range;
 use std::sync::Arc;
 
 use crate::renderer::shader::vertex_shader::ty::TransformationData;
 use crate::renderer::shader::fragment_shader::ty::LightData;
+use crate::renderer::shader::fragment_shader::ty::Light;
+use crate::renderer::shader::fragment_shader::ty::MaterialData;
+use crate::resource::light::MAX_LIGHTS;
 
-/// Struct which stores uniform data and uniform buffers.
-pub struct UniformManager {
-    // data used in transformations (model, view, projection matrix)
-    transformation_data: TransformationData,
-    transformation_data_buffer_pool: CpuBufferPool<TransformationData>,
+/// Number of buffers each kind of uniform data cycles through. A buffer is written again only after all others were,
+/// so the GPU has usually finished reading it by then.
+const RING_SIZE: usize = 16;
 
-    // data used with lighting
-    light_data: LightData,
-    light_data_buffer_pool: CpuBufferPool<LightData>,
+/// Number of buffers each kind of uniform data cycles through in a single frame. Previews rendered by the editor upload data several times per frame.
+const UPLOADS_PER_FRAME: usize = 4;
+
+/// Per instance vertex data of drawn meshes, pointing at data of the drawn object in uniform buffers.
+#[derive(Clone, Copy, Debug)]
+pub struct ObjectInstance {
+    pub object_index: u32,
 }
 
-impl UniformManager {
-    /// Creates new uniform manager.
-    pub fn new(device: Arc<Device>) -> Self {
-        let transformation_data = TransformationData {
-            model: Mat4::identity().into(),
-            view: Mat4::identity().into(),
-            proj: Mat4::identity().into(),
-        };
+impl_vertex!(ObjectInstance, object_index);
+
+/// Buffer holding uniform data of many objects.
+pub type UniformBuffer<T> = Arc<CpuAccessibleBuffer<[T]>>;
+
+/// Ring of persistently mapped buffers.
+pub(crate) struct UniformRing<T> {
+    buffers: Vec<Option<UniformBuffer<T>>>,
+    next: usize,
+    usage: BufferUsage,
+    // families of queues using buffers concurrently, buffers are owned by a single family if it's empty
+    queue_families: Vec<u32>,
+}
+
+impl<T: Copy + Send + Sync + 'static> UniformRing<T> {
+    pub(crate) fn new(usage: BufferUsage) -> Self {
+        UniformRing::shared(usage, Vec::new())
+    }
+
+    /// Creates ring of buffers used concurrently by queues of given families.
+    pub(crate) fn shared(usage: BufferUsage, queue_families: Vec<u32>) -> Self {
+        UniformRing::with_size(usage, queue_families, RING_SIZE)
+    }
 
-        let transformation_data_buffer_pool = CpuBufferPool::<TransformationData>::new(
-            device.clone(), BufferUsage::all()
-        );
+    /// Creates ring of given number of buffers used concurrently by queues of given families.
+    pub(crate) fn with_size(usage: BufferUsage, queue_families: Vec<u32>, size: usize) -> Self {
+        UniformRing {
+            buffers: (0..size).map(|_| None).collect(),
+            next: 0,
+            usage,
+            queue_families,
+        }
+    }
 
-        let light_data = LightData {
-            _dummy0: [0; 4],
-            light_position: Vec3::new(0.0, 0.0, 0.0).into(),
-            light_color: Vec3::new(1.0, 1.0, 1.0).into(),
+    /// Writes data to the next buffer of the ring. The buffer is replaced with a bigger one if data doesn't fit in it,
+    /// or with a new one if the GPU still reads it. Returns the written buffer and true if it had to be allocated.
+    pub(crate) fn upload(&mut self, device: Arc<Device>, data: &[T]) -> Result<(UniformBuffer<T>, bool), DeviceMemoryAllocError> {
+        let index = self.next;
+        self.next = (index + 1) % self.buffers.len();
+        let slot = &mut self.buffers[index];
+
+        // buffers can't be empty, so one element is allocated even if there's no data
+        let mut capacity = data.len().max(1).next_power_of_two();
+        if let Some(buffer) = slot {
+            if buffer.len() >= data.len() {
+                if let Ok(mut contents) = buffer.write() {
+                    contents[..data.len()].copy_from_slice(data);
+                    return Ok((buffer.clone(), false));
+                }
+            }
+            capacity = capacity.max(buffer.len());
+        }
+
+        let physical_device = device.physical_device();
+        // buffer is owned by a single family if there are no families sharing it
+        let queue_families = self.queue_families.iter().filter_map(|id| physical_device.queue_family_by_id(*id));
+        // size matches the capacity of the array, which is the only thing raw buffer creation doesn't check
+        let buffer = unsafe {
+            CpuAccessibleBuffer::<[T]>::raw(device.clone(), capacity * std::mem::size_of::<T>(), self.usage, queue_families)?
         };
+        {
+            let mut contents = buffer.write().expect("New buffer is locked");
+            let (uploaded, rest) = contents.split_at_mut(data.len());
+            uploaded.copy_from_slice(data);
+            // elements after uploaded data are zeroed until later uploads overwrite them, zero is valid for the plain data of shaders
+            unsafe { std::ptr::write_bytes(rest.as_mut_ptr(), 0, rest.len()) };
+        }
+        *slot = Some(buffer.clone());
+        Ok((buffer, true))
+    }
+}
 
-        let light_data_buffer_pool = CpuBufferPool::<LightData>::new(device.clone(), BufferUsage::all());
+/// Uniform buffers written during a single frame.
+struct FramePool {
+    transformation_ring: UniformRing<TransformationData>,
+    light_ring: UniformRing<LightData>,
+    material_ring: UniformRing<MaterialData>,
+    instance_ring: UniformRing<ObjectInstance>,
+}
 
+impl FramePool {
+    fn new(compute_sharing_families: Vec<u32>) -> Self {
+        FramePool {
+            transformation_ring: UniformRing::with_size(BufferUsage { storage_buffer: true, ..BufferUsage::none() }, compute_sharing_families, UPLOADS_PER_FRAME),
+            light_ring: UniformRing::with_size(BufferUsage::uniform_buffer(), Vec::new(), UPLOADS_PER_FRAME),
+            material_ring: UniformRing::with_size(BufferUsage { storage_buffer: true, ..BufferUsage::none() }, Vec::new(), UPLOADS_PER_FRAME),
+            instance_ring: UniformRing::with_size(BufferUsage::vertex_buffer(), Vec::new(), UPLOADS_PER_FRAME),
+        }
+    }
+}
+
+/// Collects uniform data of objects drawn in a frame and writes all of it at once to buffers from a ring,
+/// instead of allocating buffers for every object. Transformations and materials are stored in arrays,
+/// which shaders index with the object index passed in push constants, so a single descriptor set is bound for all objects.
+/// Every frame in flight writes to buffers of its own pool, so a frame doesn't overwrite uniforms read by the previous ones.
+pub struct UniformManager {
+    device: Arc<Device>,
+    compute_sharing_families: Vec<u32>,
+    frame_pools: Vec<FramePool>,
+    current_pool: usize,
+
+    // data used in transformations (model, view, projection matrix)
+    transformations: Vec<TransformationData>,
+
+    // data used with lighting, shared by all objects
+    light_data: LightData,
+
+    // data describing surface of rendered object
+    materials: Vec<MaterialData>,
+
+    // indices of objects in the order they were added, used as instance data of draws
+    instances: Vec<ObjectInstance>,
+}
+
+impl UniformManager {
+    /// Creates new uniform manager with a pool of buffers for every frame in flight.
+    /// Transformations are shared with queues of given families, because compute shaders read them too.
+    pub fn new(device: Arc<Device>, compute_sharing_families: Vec<u32>, frames_in_flight: usize) -> Self {
         UniformManager {
-            transformation_data,
-            transformation_data_buffer_pool,
-            light_data,
-            light_data_buffer_pool,
+            device,
+            frame_pools: (0..frames_in_flight.max(1)).map(|_| FramePool::new(compute_sharing_families.clone())).collect(),
+            compute_sharing_families,
+            current_pool: 0,
+            transformations: Vec::new(),
+            light_data: LightData {
+                light_count: 0,
+                exposure: 1.0,
+                _dummy0: [0; 8],
+                ambient_sky: [0.0; 4],
+                ambient_ground: [0.0; 4],
+                lights: [Light { position_range: [0.0; 4], color_intensity: [0.0; 4], direction_type: [0.0; 4], cone: [0.0; 4] }; MAX_LIGHTS],
+            },
+            materials: Vec::new(),
+            instances: Vec::new(),
         }
     }
 
-    /// Updates light uniform data.
-    pub fn update_light_data(&mut self, light_data: LightData) {
-        self.light_data = light_data;
+    /// Starts a new frame, which writes uniforms to the pool least recently used.
+    pub fn begin_frame(&mut self) {
+        self.current_pool = (self.current_pool + 1) % self.frame_pools.len();
     }
 
-    /// Returns subbuffer from light uniform buffer.
-    pub fn get_light_subbuffer_data(&self) -> Result<CpuBufferPoolSubbuffer<LightData, Arc<StdMemoryPool>>, DeviceMemoryAllocError> {
-        self.light_data_buffer_pool.next(self.light_data.clone())
+    /// Changes number of frames in flight, when swapchain was recreated with a different number of images.
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) {
+        let frames_in_flight = frames_in_flight.max(1);
+        let compute_sharing_families = self.compute_sharing_families.clone();
+        self.frame_pools.truncate(frames_in_flight);
+        while self.frame_pools.len() < frames_in_flight {
+            self.frame_pools.push(FramePool::new(compute_sharing_families.clone()));
+        }
+        self.current_pool %= frames_in_flight;
     }
 
-    /// Updates transformation uniform data.
-    pub fn update_transformation_data(&mut self, transformation_data: TransformationData) {
-        self.transformation_data = transformation_data;
+    /// Sets light uniform data used by objects drawn with the next upload.
+    pub fn set_light_data(&mut self, light_data: LightData) {
+        self.light_data = light_data;
     }
 
-    /// Returns subbuffer from transformation uniform buffer.
-    pub fn get_transformation_subbuffer_data(&self) -> Result<CpuBufferPoolSubbuffer<TransformationData, Arc<StdMemoryPool>>, DeviceMemoryAllocError> {
-        self.transformation_data_buffer_pool.next(self.transformation_data.clone())
+    /// Adds uniform data of an object drawn with the next upload and returns index of the object in uploaded buffers.
+    pub fn push_object(&mut self, transformation_data: TransformationData, material_data: MaterialData) -> usize {
+        self.transformations.push(transformation_data);
+        self.materials.push(material_data);
+        let object_index = self.instances.len();
+        self.instances.push(ObjectInstance { object_index: object_index as u32 });
+        object_index
     }
+
+    /// Writes data of objects added since the last upload to uniform buffers. Nothing is drawn from the buffers if no object was added.
+    pub fn upload(&mut self) -> Result<UniformFrame, DeviceMemoryAllocError> {
+        let pool = &mut self.frame_pools[self.current_pool];
+        let (transformations, transformations_allocated) = pool.transformation_ring.upload(self.device.clone(), &self.transformations)?;
+        let (light, light_allocated) = pool.light_ring.upload(self.device.clone(), &[self.light_data.clone()])?;
+        let (materials, materials_allocated) = pool.material_ring.upload(self.device.clone(), &self.materials)?;
+        let (instances, instances_allocated) = pool.instance_ring.upload(self.device.clone(), &self.instances)?;
+        self.transformations.clear();
+        self.materials.clear();
+        self.instances.clear();
+
+        Ok(UniformFrame {
+            transformations,
+            light,
+            materials,
+            instances,
+            buffers_allocated: transformations_allocated as u32 + light_allocated as u32 + materials_allocated as u32 + instances_allocated as u32,
+        })
+    }
+}
+
+/// Uniform buffers written by a single upload.
+pub struct UniformFrame {
+    transformations: UniformBuffer<TransformationData>,
+    light: UniformBuffer<LightData>,
+    materials: UniformBuffer<MaterialData>,
+    instances: UniformBuffer<ObjectInstance>,
+    buffers_allocated: u32,
 }
 
+impl UniformFrame {
+    /// Returns array of transformation data of uploaded objects.
+    pub fn transformations(&self) -> UniformBuffer<TransformationData> {
+        self.transformations.clone()
+    }
+
+    /// Returns light data.
+    pub fn light(&self) -> UniformBuffer<LightData> {
+        self.light.clone()
+    }
+
+    /// Returns array of material data of uploaded objects.
+    pub fn materials(&self) -> UniformBuffer<MaterialData> {
+        self.materials.clone()
+    }
+
+    /// Returns instance buffer drawing objects with indices from given range, which have to use the same mesh.
+    pub fn instances(&self, objects: Range<usize>) -> Arc<BufferAccess + Send + Sync> {
+        Arc::new(self.instances.clone().into_buffer_slice().slice(objects).expect("Object indices out of uploaded range"))
+    }
+
+    /// Returns number of buffers which had to be allocated by the upload.
+    pub fn buffers_allocated(&self) -> u32 {
+        self.buffers_allocated
+    }
+}
@@ -0,0 +1,208 @@
+use std::sync::{Arc, Mutex};
+
+use image::RgbaImage;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBuffer, AutoCommandBufferBuilder};
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::instance::QueueFamily;
+use vulkano::image::{Dimensions, ImageLayout, ImageUsage, ImmutableImage, MipmapsCount, StorageImage};
+use vulkano::sync::{FlushError, GpuFuture};
+
+use crate::renderer::memory::{BufferArena, SubBuffer};
+use crate::renderer::queues::Queues;
+use crate::renderer::renderer_error::RenderError;
+use crate::resource::mesh::Vertex;
+use crate::resource::texture;
+
+/// Uploads vertex, index and image data of assets to device local memory through staging buffers.
+/// Copies run on the transfer queue, if the device has a dedicated one, so that big uploads don't stall rendering.
+/// Each upload signals a semaphore, which the next rendered frame waits for on the graphics queue.
+/// Mesh buffers are sub-allocated from shared memory blocks. Assets can be uploaded from any thread.
+pub struct AssetUploader {
+    device: Arc<Device>,
+    queues: Queues,
+    vertex_arena: BufferArena<Vertex>,
+    index_arena: BufferArena<u32>,
+    pending_uploads: Mutex<Vec<Box<GpuFuture + Send + Sync>>>,
+}
+
+impl AssetUploader {
+    /// Creates uploader copying data with the transfer queue.
+    pub fn new(device: Arc<Device>, queues: Queues) -> Self {
+        AssetUploader {
+            vertex_arena: BufferArena::new(device.clone(), BufferUsage { vertex_buffer: true, transfer_destination: true, ..BufferUsage::none() }),
+            index_arena: BufferArena::new(device.clone(), BufferUsage { index_buffer: true, transfer_destination: true, ..BufferUsage::none() }),
+            device,
+            queues,
+            pending_uploads: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns device assets are uploaded to.
+    pub fn device(&self) -> Arc<Device> {
+        self.device.clone()
+    }
+
+    /// Creates device local buffer filled with given data. Buffer can be used after the upload is waited for by a rendered frame.
+    pub fn upload_buffer<T, I>(&self, data: I, usage: BufferUsage) -> Result<Arc<ImmutableBuffer<[T]>>, RenderError>
+        where T: Send + Sync + 'static, I: ExactSizeIterator<Item = T> {
+        let size = data.len() * std::mem::size_of::<T>();
+        let staging_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_source(), data)?;
+
+        let transfer_queue = self.queues.transfer_queue();
+        let graphics_queue = self.queues.graphics_queue();
+        let usage = BufferUsage { transfer_destination: true, ..usage };
+        // buffer is shared by both queues, so that it doesn't need ownership transfer
+        let (buffer, initialization) = unsafe {
+            ImmutableBuffer::<[T]>::raw(self.device.clone(), size, usage, shared_families(&transfer_queue, &graphics_queue))?
+        };
+
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), transfer_queue.family())?
+                                                      .copy_buffer(staging_buffer, initialization)?
+                                                      .build()?;
+        self.submit(command_buffer)?;
+        Ok(buffer)
+    }
+
+    /// Creates vertex buffer sub-allocated from memory blocks shared by vertices of all meshes.
+    pub fn upload_vertices(&self, vertices: &[Vertex]) -> Result<Arc<SubBuffer<Vertex>>, RenderError> {
+        self.upload_to_arena(&self.vertex_arena, vertices)
+    }
+
+    /// Creates index buffer sub-allocated from memory blocks shared by indices of all meshes.
+    pub fn upload_indices(&self, indices: &[u32]) -> Result<Arc<SubBuffer<u32>>, RenderError> {
+        self.upload_to_arena(&self.index_arena, indices)
+    }
+
+    /// Returns number of device memory blocks allocated for mesh buffers.
+    pub fn mesh_memory_blocks(&self) -> usize {
+        self.vertex_arena.block_count() + self.index_arena.block_count()
+    }
+
+    /// Returns size of memory blocks allocated for mesh buffers and size used by meshes in them, in bytes.
+    pub fn mesh_memory_usage(&self) -> (u64, u64) {
+        let (vertex_allocated, vertex_used) = self.vertex_arena.memory_usage();
+        let (index_allocated, index_used) = self.index_arena.memory_usage();
+        (vertex_allocated + index_allocated, vertex_used + index_used)
+    }
+
+    /// Creates device local sampled image with given pixels and mipmaps generated from them.
+    /// Image can be used after the upload is waited for by a rendered frame.
+    pub fn upload_image(&self, image: RgbaImage) -> Result<Arc<ImmutableImage<Format>>, RenderError> {
+        self.upload_mip_levels(texture::mip_chain(image))
+    }
+
+    /// Creates device local sampled image with given mipmap levels, each half the size of the previous one.
+    /// Image can be used after the upload is waited for by a rendered frame.
+    pub fn upload_mip_levels(&self, levels: Vec<RgbaImage>) -> Result<Arc<ImmutableImage<Format>>, RenderError> {
+        let (width, height) = levels[0].dimensions();
+        let dimensions = Dimensions::Dim2d { width, height };
+
+        let transfer_queue = self.queues.transfer_queue();
+        let graphics_queue = self.queues.graphics_queue();
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let (texture_image, initialization) = ImmutableImage::uninitialized(
+            self.device.clone(),
+            dimensions,
+            Format::R8G8B8A8Srgb,
+            MipmapsCount::Specific(levels.len() as u32),
+            usage,
+            ImageLayout::ShaderReadOnlyOptimal,
+            shared_families(&transfer_queue, &graphics_queue),
+        )?;
+
+        // levels are copied from the CPU, because blits generating them on the GPU need the graphics queue
+        let initialization = Arc::new(initialization);
+        let mut command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), transfer_queue.family())?;
+        for (mip_level, level) in levels.into_iter().enumerate() {
+            let size = [level.width(), level.height(), 1];
+            let staging_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_source(), level.into_raw().into_iter())?;
+            command_buffer = command_buffer.copy_buffer_to_image_dimensions(staging_buffer, initialization.clone(), [0, 0, 0], size,
+                                                                            0, 1, mip_level as u32)?;
+        }
+        self.submit(command_buffer.build()?)?;
+        Ok(texture_image)
+    }
+
+    /// Creates device local sampled image with given pixels, which can be updated later with `update_image_region`.
+    pub fn upload_dynamic_image(&self, image: RgbaImage) -> Result<Arc<StorageImage<Format>>, RenderError> {
+        let dimensions = Dimensions::Dim2d { width: image.width(), height: image.height() };
+        let staging_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_source(), image.into_raw().into_iter())?;
+
+        let transfer_queue = self.queues.transfer_queue();
+        let graphics_queue = self.queues.graphics_queue();
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let texture_image = StorageImage::with_usage(self.device.clone(), dimensions, Format::R8G8B8A8Srgb, usage,
+                                                     shared_families(&transfer_queue, &graphics_queue))?;
+
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), transfer_queue.family())?
+                                                      .copy_buffer_to_image(staging_buffer, texture_image.clone())?
+                                                      .build()?;
+        self.submit(command_buffer)?;
+        Ok(texture_image)
+    }
+
+    /// Copies RGBA pixels to a region of dynamic image, starting at given offset. Pixels are in rows, from the first row of the region.
+    /// New pixels are used by frames rendered after the upload.
+    pub fn update_image_region(&self, image: Arc<StorageImage<Format>>, offset: [u32; 2], size: [u32; 2], pixels: Vec<u8>) -> Result<(), RenderError> {
+        let staging_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_source(), pixels.into_iter())?;
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), self.queues.transfer_queue().family())?
+                                                      .copy_buffer_to_image_dimensions(staging_buffer, image, [offset[0], offset[1], 0],
+                                                                                       [size[0], size[1], 1], 0, 1, 0)?
+                                                      .build()?;
+        self.submit(command_buffer)
+    }
+
+    /// Returns uploads started since the last call, which the next frame has to wait for.
+    pub(crate) fn take_pending_uploads(&self) -> Vec<Box<GpuFuture + Send + Sync>> {
+        self.pending_uploads.lock().unwrap().drain(..).collect()
+    }
+
+    /// Blocks until all started uploads finish. Used when no frames are rendered, which would otherwise wait for them.
+    pub fn wait_for_uploads(&self) -> Result<(), FlushError> {
+        for upload in self.take_pending_uploads() {
+            upload.then_signal_fence_and_flush()?.wait(None)?;
+        }
+        Ok(())
+    }
+
+    fn upload_to_arena<T: Copy + Send + Sync + 'static>(&self, arena: &BufferArena<T>, data: &[T]) -> Result<Arc<SubBuffer<T>>, RenderError> {
+        let transfer_queue = self.queues.transfer_queue();
+        let graphics_queue = self.queues.graphics_queue();
+        let buffer = arena.allocate(data.len(), shared_families(&transfer_queue, &graphics_queue))?;
+        if data.is_empty() {
+            return Ok(buffer);
+        }
+
+        let staging_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_source(), data.iter().cloned())?;
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), transfer_queue.family())?
+                                                      .copy_buffer(staging_buffer, buffer.clone())?
+                                                      .build()?;
+        self.submit(command_buffer)?;
+        Ok(buffer)
+    }
+
+    fn submit(&self, command_buffer: AutoCommandBuffer) -> Result<(), RenderError> {
+        let upload = self.queues.submit_transfer(command_buffer)?;
+        self.pending_uploads.lock().unwrap().push(upload);
+        Ok(())
+    }
+}
+
+/// Returns families of both queues, or a single family if they're the same.
+fn shared_families<'a>(transfer_queue: &'a Arc<Queue>, graphics_queue: &'a Arc<Queue>) -> Vec<QueueFamily<'a>> {
+    if transfer_queue.family().id() == graphics_queue.family().id() {
+        vec![graphics_queue.family()]
+    } else {
+        vec![transfer_queue.family(), graphics_queue.family()]
+    }
+}
@@ -0,0 +1,1530 @@
+pub mod vulkan_backend;
+
+use vulkano::swapchain::SwapchainAcquireFuture;
+use crate::renderer::shader::fragment_shader::ty::PushConstants;
+use vulkano::command_buffer::AutoCommandBuffer;
+use crate::renderer::renderer_error::RenderError;
+use vulkano::framebuffer::FramebufferCreationError;
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use crate::renderer::renderer_error::RendererCreationError;
+use vulkano::format::Format;
+use vulkano::framebuffer::RenderPassCreationError;
+use vulkano::device::DeviceCreationError;
+use vulkano::device::QueuesIter;
+use vulkano::instance::QueueFamily;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::image::ImageCreationError;
+use crate::resource::AssetManager;
+use crate::resource::asset_error::AssetError;
+use log::*;
+
+use crate::settings::{RendererKind, Settings};
+use crate::renderer::{get_window_dimensions, queues, render_scale, bloom_pass, surface_format, window_builder};
+
+use vulkano::instance::{Instance, InstanceCreationError, PhysicalDevice, PhysicalDeviceType, PhysicalDevicesIter};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::DescriptorSet;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::device::{Device, RawDeviceExtensions};
+use vulkano::instance::RawInstanceExtensions;
+use std::ffi::CString;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::image::SwapchainImage;
+use vulkano::swapchain::{Surface, PresentMode, Swapchain, SurfaceTransform, CompositeAlpha};
+use vulkano::single_pass_renderpass;
+use vulkano::framebuffer::{RenderPassAbstract, Framebuffer, FramebufferAbstract, Subpass};
+use winit::{EventsLoop, Window};
+use vulkano::sync::GpuFuture;
+use vulkano::sync;
+use vulkano::swapchain::{AcquireError};
+use vulkano::swapchain;
+
+use vulkano_win::VkSurfaceBuild;
+
+use std::sync::{Arc, RwLock};
+
+use crate::renderer::queues::Queues;
+use crate::renderer::memory::SubBuffer;
+use crate::renderer::uniform_manager::{UniformFrame, UniformManager};
+use crate::renderer::culling_pass::{CulledObject, CullingPass};
+use crate::renderer::auto_exposure::AutoExposurePass;
+use crate::renderer::bloom_pass::BloomPass;
+use crate::renderer::shader::vertex_shader::ty::TransformationData;
+use crate::renderer::shader::fragment_shader::ty::MaterialData;
+use crate::renderer::shader::ShaderSet;
+use crate::renderer::debug_draw::DebugDraw;
+use crate::renderer::renderer_stats::RendererStats;
+use crate::renderer::capabilities::RendererCapabilities;
+use crate::math::bounds::Bounds;
+use crate::renderer::shader::debug_vertex_shader::ty::PushConstants as DebugPushConstants;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, TypedBufferAccess};
+use vulkano::format::ClearValue;
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::depth_stencil::DepthStencil;
+use vulkano::pipeline::input_assembly::PrimitiveTopology;
+use crate::renderer::material_preview::{MaterialPreview, MATERIAL_PREVIEW_SIZE, PREVIEW_BACKGROUND_COLOR, PREVIEW_CAMERA_DISTANCE};
+use crate::renderer::asset_preview::{AssetPreview, TextureChannel, ASSET_PREVIEW_SIZE};
+use crate::renderer::camera_preview::CameraPreview;
+use crate::renderer::scene_capture::SceneCapture;
+use crate::renderer::ui_renderer::UiRenderer;
+use crate::renderer::particle_renderer::ParticleRenderer;
+use crate::renderer::overlay::Overlay;
+use crate::renderer::render_scale::{DynamicRenderScale, ScaledTarget, UpscaleFilter, Upscaler};
+use crate::particles::ParticleSystem;
+use crate::ui::Ui;
+use crate::renderer::shader::texture_preview_fragment_shader::ty::PushConstants as TexturePreviewPushConstants;
+use crate::resource::mesh::{Mesh, Topology};
+use crate::resource::mesh::vertex_format::{MeshVertexBuffer, VertexFormat};
+use crate::resource::texture::Texture;
+use vulkano::pipeline::vertex::BufferlessVertices;
+use crate::resource::material::{BlendMode, CullMode, FrontFace, Material};
+use crate::resource::object::Object;
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::primitives::Primitive;
+use crate::resource::render_queue::{DrawOrder, RenderQueue};
+use crate::resource::render_layers::RenderLayers;
+use crate::resource::scene::Scene;
+use crate::resource::scene::static_batch::StaticBatch;
+use crate::profile_scope;
+use std::collections::HashMap;
+use std::time::Duration;
+use nalgebra_glm as glm;
+use nalgebra_glm::Mat4;
+use crate::math::frustum::Frustum;
+use image::RgbaImage;
+
+/// Instance extension listing portability drivers, like MoltenVK on macOS, when it's enabled.
+const PORTABILITY_ENUMERATION_EXTENSION: &str = "VK_KHR_portability_enumeration";
+/// Device extension which has to be enabled on drivers implementing only a subset of Vulkan.
+const PORTABILITY_SUBSET_EXTENSION: &str = "VK_KHR_portability_subset";
+
+/// Smallest radius of a previewed mesh, so that camera doesn't end up inside flat or empty meshes.
+const MIN_PREVIEW_RADIUS: f32 = 0.1;
+
+/// Mesh rendered to an offscreen target, along with camera and light used to render it.
+struct OffscreenMesh<'a> {
+    mesh: &'a Mesh,
+    texture: Arc<Texture>,
+    material: &'a Material,
+    model: Mat4,
+    camera: Camera,
+    light_position: (f32, f32, f32),
+    size: u32,
+}
+
+/// Fixed function state of a pipeline drawing objects, which is set by their materials, mesh topology and vertex format.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct PipelineKey {
+    blend_mode: BlendMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    topology: Topology,
+    vertex_format: VertexFormat,
+}
+
+impl PipelineKey {
+    /// Returns state of the pipeline drawing meshes of given topology and vertex format with given material.
+    /// Lines and points have no faces, so their pipelines ignore cull mode and winding of the material.
+    fn new(material: &Material, topology: Topology, vertex_format: VertexFormat) -> Self {
+        match topology {
+            Topology::TriangleList => PipelineKey {
+                blend_mode: material.blend_mode(),
+                cull_mode: material.cull_mode(),
+                front_face: material.front_face(),
+                topology,
+                vertex_format,
+            },
+            _ => PipelineKey {
+                blend_mode: material.blend_mode(),
+                cull_mode: CullMode::None,
+                front_face: FrontFace::CounterClockwise,
+                topology,
+                vertex_format,
+            },
+        }
+    }
+
+    /// Returns every combination of pipeline state.
+    fn all() -> Vec<PipelineKey> {
+        let mut keys = Vec::new();
+        for &vertex_format in VertexFormat::ALL.iter() {
+            for &blend_mode in BlendMode::ALL.iter() {
+                for &cull_mode in CullMode::ALL.iter() {
+                    for &front_face in FrontFace::ALL.iter() {
+                        keys.push(PipelineKey { blend_mode, cull_mode, front_face, topology: Topology::TriangleList, vertex_format });
+                    }
+                }
+                for &topology in Topology::ALL.iter().filter(|&&topology| topology != Topology::TriangleList) {
+                    keys.push(PipelineKey { blend_mode, cull_mode: CullMode::None, front_face: FrontFace::CounterClockwise, topology, vertex_format });
+                }
+            }
+        }
+        keys
+    }
+}
+
+impl Default for PipelineKey {
+    fn default() -> Self {
+        PipelineKey::new(&Material::default(), Topology::default(), VertexFormat::default())
+    }
+}
+
+/// Framebuffer objects are drawn to, along with pipelines drawing them.
+struct ObjectsTarget<'a> {
+    framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+    pipelines: &'a HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+}
+
+/// Object which passed frustum culling, with everything needed to draw it. When objects are culled on the GPU, every object with a mesh is visible.
+struct VisibleObject {
+    pipeline_key: PipelineKey,
+    draw_order: DrawOrder,
+    mesh_key: usize,
+    material_key: usize,
+    vertex_buffer: MeshVertexBuffer,
+    index_buffer: Arc<SubBuffer<u32>>,
+    bounds: Bounds,
+    texture: Arc<Texture>,
+    lightmap: Option<Arc<Texture>>,
+    transformation_data: TransformationData,
+    material_data: MaterialData,
+    depth_bias: f32,
+    uniform_scale: bool,
+    selected: bool,
+}
+
+impl VisibleObject {
+    fn texture_key(&self) -> usize {
+        &*self.texture as *const Texture as usize
+    }
+
+    fn lightmap_key(&self) -> usize {
+        self.lightmap.as_ref().map(|lightmap| &**lightmap as *const Texture as usize).unwrap_or(0)
+    }
+
+    /// Returns true if both objects can be drawn with a single draw call, which requires the same mesh, texture, pipeline and push constants.
+    fn batches_with(&self, other: &VisibleObject) -> bool {
+        self.mesh_key == other.mesh_key && self.shares_draw_state(other)
+    }
+
+    /// Returns true if both objects can be drawn with a single indirect draw call. Indirect commands address meshes inside memory blocks,
+    /// so meshes only need to share vertex and index blocks.
+    fn batches_indirectly_with(&self, other: &VisibleObject) -> bool {
+        self.vertex_buffer.block_key() == other.vertex_buffer.block_key() && self.index_buffer.block_key() == other.index_buffer.block_key()
+            && self.shares_draw_state(other)
+    }
+
+    /// Returns true if both objects use the same texture, lightmap, pipeline and push constants.
+    fn shares_draw_state(&self, other: &VisibleObject) -> bool {
+        self.texture_key() == other.texture_key() && self.lightmap_key() == other.lightmap_key() && self.pipeline_key == other.pipeline_key
+            && self.depth_bias == other.depth_bias && self.uniform_scale == other.uniform_scale && self.selected == other.selected
+    }
+}
+
+/// Visible instances of an instanced mesh, drawn with a single draw call after scene objects.
+struct InstancedDraw {
+    pipeline_key: PipelineKey,
+    vertex_buffer: MeshVertexBuffer,
+    index_buffer: Arc<SubBuffer<u32>>,
+    texture: Arc<Texture>,
+    material_data: MaterialData,
+    depth_bias: f32,
+    transforms: Vec<Mat4>,
+}
+
+/// Top level struct of vulkan renderer.
+pub struct Renderer {
+    instance: Arc<Instance>,
+    surface: Arc<Surface<Window>>,
+    device: Arc<Device>,
+    queues: Queues,
+    swapchain: Arc<Swapchain<Window>>,
+    images: Vec<Arc<SwapchainImage<Window>>>,
+    uniform_manager: UniformManager,
+    culling_pass: CullingPass,
+    // None if swapchain images can't be sampled, which metering needs
+    auto_exposure: Option<AutoExposurePass>,
+    // keyed by addresses of the texture and the lightmap, which is zero without a lightmap
+    texture_sets: HashMap<(usize, usize), (Arc<Texture>, Option<Arc<Texture>>, Arc<DescriptorSet + Send + Sync>)>,
+    shader_set: Arc<ShaderSet>,
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>,
+    debug_pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>,
+    ui_renderer: UiRenderer,
+    particle_renderer: ParticleRenderer,
+    upscaler: Upscaler,
+    bloom_pass: BloomPass,
+    // None while the scene is rendered at the window resolution without bloom
+    scaled_target: Option<ScaledTarget>,
+    framebuffers: Vec<Arc<FramebufferAbstract + Send + Sync>>,
+
+    recreate_swapchain: bool,
+    previous_frame: Option<Box<GpuFuture + Send + Sync>>,
+    // work on other queues, which the next submitted frame waits for with semaphores
+    pending_semaphores: Vec<Box<GpuFuture + Send + Sync>>,
+
+    selected_objects: Vec<u32>,
+    free_camera: bool,
+    batched_rendering: bool,
+    gpu_culling: bool,
+    render_scale: f32,
+    dynamic_render_scale: Option<DynamicRenderScale>,
+    upscale_filter: UpscaleFilter,
+    debug_draw: DebugDraw,
+    stats: RendererStats,
+    capabilities: RendererCapabilities,
+}
+
+impl Renderer {
+    /// Creates new renderer.
+    pub fn new(settings: &Settings, events_loop: &EventsLoop) -> Result<Self, RendererCreationError> {
+        let instance = create_new_instance()?;
+
+        let physical_device = rank_devices(PhysicalDevice::enumerate(&instance), settings.renderer_kind())?;
+        info!("Using device: {} (type: {:?})", physical_device.name(), physical_device.ty());
+        let capabilities = RendererCapabilities::query(physical_device);
+
+        let surface = window_builder(settings).build_vk_surface(events_loop, instance.clone())?;
+        let window = surface.window();
+
+        let physical_queues = queues::find_queues(physical_device, &surface)?;
+
+        let (device, queues) = create_logical_device(physical_device, &physical_queues)?;
+
+        let queues = Queues::new(queues, &surface)?;
+
+        let (swapchain, images) = create_swapchain(surface.clone(), physical_device, device.clone(), &queues, settings)?;
+
+        let queue_layout = queues.layout();
+        if queue_layout.dedicated_compute {
+            info!("Compute work runs on a dedicated compute queue");
+        }
+        if queue_layout.separate_present {
+            info!("Swapchain images are presented by a separate present queue");
+        }
+        let uniform_manager = UniformManager::new(device.clone(), queues.compute_sharing_families(), images.len());
+        let shader_set = Arc::new(ShaderSet::load(device.clone())?);
+        let culling_pass = CullingPass::new(device.clone(), &shader_set, queues.compute_sharing_families())?;
+        let auto_exposure = if surface.capabilities(physical_device)?.supported_usage_flags.sampled {
+            Some(AutoExposurePass::new(device.clone(), &shader_set)?)
+        } else {
+            warn!("Swapchain images can't be sampled, auto exposure is disabled");
+            None
+        };
+
+        let render_pass = create_renderpass(device.clone(), swapchain.format())?;
+
+        let pipelines = create_pipelines(device.clone(), shader_set.clone(), images[0].dimensions(), render_pass.clone())?;
+        let debug_pipeline = create_debug_pipeline(device.clone(), shader_set.clone(), images[0].dimensions(), render_pass.clone())?;
+        let ui_renderer = UiRenderer::new(device.clone(), queues.graphics_queue(), &shader_set, images[0].dimensions(), render_pass.clone())?;
+        let particle_renderer = ParticleRenderer::new(device.clone(), queues.graphics_queue(), &shader_set, images[0].dimensions(), render_pass.clone())?;
+        let upscaler = Upscaler::new(device.clone(), &shader_set, images[0].dimensions(), render_pass.clone())?;
+        let bloom_pass = BloomPass::new(device.clone(), &shader_set)?;
+        let framebuffers = create_framebuffers::<RendererCreationError>(device.clone(), &images, render_pass.clone())?;
+        let render_scale = render_scale::clamp_render_scale(settings.render_scale());
+
+        Ok(Renderer {
+            instance,
+            surface,
+            device: device.clone(),
+            queues,
+            swapchain,
+            images,
+            uniform_manager,
+            culling_pass,
+            auto_exposure,
+            texture_sets: HashMap::new(),
+            shader_set,
+            render_pass,
+            pipelines,
+            debug_pipeline,
+            ui_renderer,
+            particle_renderer,
+            upscaler,
+            bloom_pass,
+            scaled_target: None,
+            framebuffers,
+            recreate_swapchain: false,
+            previous_frame: None,
+            pending_semaphores: Vec::new(),
+            selected_objects: Vec::new(),
+            free_camera: false,
+            batched_rendering: settings.batched_rendering(),
+            gpu_culling: settings.gpu_culling(),
+            render_scale,
+            dynamic_render_scale: settings.target_frame_time().map(|target_frame_time| DynamicRenderScale::new(target_frame_time, render_scale)),
+            upscale_filter: settings.upscale_filter(),
+            debug_draw: DebugDraw::new(),
+            stats: RendererStats::default(),
+            capabilities,
+        })
+    }
+
+    /// Forces renderer to recreate swapchain.
+    pub fn force_recreate_swapchain(&mut self) {
+        self.recreate_swapchain = true;
+    }
+
+    /// Waits until the GPU finishes all submitted work, so that resources can be destroyed, e.g. when the engine shuts down.
+    pub fn wait_idle(&mut self) -> Result<(), RenderError> {
+        unsafe { self.device.wait()? };
+        if let Some(previous_frame) = &mut self.previous_frame {
+            previous_frame.cleanup_finished();
+        }
+        Ok(())
+    }
+
+    /// Sets ids of objects which are highlighted as selected when rendered.
+    pub fn set_selected_objects(&mut self, selected_objects: Vec<u32>) {
+        self.selected_objects = selected_objects;
+    }
+
+    /// Sets whether the active scene is rendered through its free camera even if a camera object is active.
+    /// Used by the editor, whose camera is the free camera, while the game is edited.
+    pub fn set_free_camera(&mut self, free_camera: bool) {
+        self.free_camera = free_camera;
+    }
+
+    /// Returns camera the scene is rendered through.
+    fn scene_camera(&self, scene: &Scene) -> Camera {
+        if self.free_camera {
+            scene.camera().clone()
+        } else {
+            scene.view_camera()
+        }
+    }
+
+    /// Returns statistics of the last rendered frame.
+    pub fn stats(&self) -> RendererStats {
+        self.stats
+    }
+
+    /// Returns limits and features of the device used for rendering.
+    pub fn capabilities(&self) -> &RendererCapabilities {
+        &self.capabilities
+    }
+
+    /// Enables or disables batched rendering, which sorts opaque objects by material and mesh and draws consecutive objects sharing them with a single draw call.
+    pub fn set_batched_rendering(&mut self, batched_rendering: bool) {
+        self.batched_rendering = batched_rendering;
+    }
+
+    /// Returns true if objects are rendered in batches.
+    pub fn batched_rendering(&self) -> bool {
+        self.batched_rendering
+    }
+
+    /// Enables or disables culling objects on the GPU. Culled objects are skipped by indirect draw commands written by a compute pass,
+    /// instead of being tested against the camera frustum one by one on the CPU, which scales to much bigger scenes.
+    pub fn set_gpu_culling(&mut self, gpu_culling: bool) {
+        self.gpu_culling = gpu_culling;
+    }
+
+    /// Returns true if objects are culled on the GPU.
+    pub fn gpu_culling(&self) -> bool {
+        self.gpu_culling
+    }
+
+    /// Sets fraction of the window resolution the scene is rendered at, from `MIN_RENDER_SCALE` to 1. Scaled scene is upscaled to the window,
+    /// while particles and UI are still drawn at the window resolution. With dynamic render scale, it's the largest scale used.
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        self.render_scale = render_scale::clamp_render_scale(render_scale);
+        if let Some(dynamic_render_scale) = &mut self.dynamic_render_scale {
+            dynamic_render_scale.set_max_scale(self.render_scale);
+        }
+    }
+
+    /// Returns fraction of the window resolution the next frame is rendered at.
+    pub fn render_scale(&self) -> f32 {
+        match &self.dynamic_render_scale {
+            Some(dynamic_render_scale) => dynamic_render_scale.scale(),
+            None => self.render_scale,
+        }
+    }
+
+    /// Sets frame time which render scale is lowered to reach, or None to always render at the scale set with `set_render_scale`.
+    pub fn set_target_frame_time(&mut self, target_frame_time: Option<Duration>) {
+        self.dynamic_render_scale = target_frame_time.map(|target_frame_time| DynamicRenderScale::new(target_frame_time, self.render_scale));
+    }
+
+    /// Returns frame time targeted by dynamic render scale, if it's enabled.
+    pub fn target_frame_time(&self) -> Option<Duration> {
+        self.dynamic_render_scale.as_ref().map(|dynamic_render_scale| dynamic_render_scale.target_frame_time())
+    }
+
+    /// Records time of the last frame, which dynamic render scale is adjusted to. Called once per frame, before `render_scene`.
+    pub fn update_render_scale(&mut self, frame_time: Duration) {
+        if let Some(dynamic_render_scale) = &mut self.dynamic_render_scale {
+            dynamic_render_scale.update(frame_time);
+        }
+    }
+
+    /// Sets filter used to upscale the scene rendered below the window resolution.
+    pub fn set_upscale_filter(&mut self, upscale_filter: UpscaleFilter) {
+        self.upscale_filter = upscale_filter;
+    }
+
+    /// Returns filter used to upscale the scene rendered below the window resolution.
+    pub fn upscale_filter(&self) -> UpscaleFilter {
+        self.upscale_filter
+    }
+
+    /// Returns a mutable reference to debug draw. Lines added to it are drawn in the next frame.
+    pub fn debug_draw_mut(&mut self) -> &mut DebugDraw {
+        &mut self.debug_draw
+    }
+
+    /// Renders one frame using active scene from asset manager.
+    pub fn render_scene(&mut self, command_buffer: AutoCommandBufferBuilder, asset_manager: &mut AssetManager) -> Result<(usize, SwapchainAcquireFuture<winit::Window>, AutoCommandBufferBuilder), RenderError> {
+        profile_scope!("render scene");
+        let (image_num, acquire_future) = self.acquire_image()?;
+
+        let command_buffer = self.add_scene_commands(command_buffer, image_num, asset_manager)?;
+        // frame waits on semaphores signaled by asset uploads, which may still run on the transfer queue
+        if let Some(uploader) = asset_manager.uploader() {
+            self.pending_semaphores.extend(uploader.take_pending_uploads());
+        }
+
+        Ok((image_num, acquire_future, command_buffer))
+    }
+
+    /// Acquires swapchain image the next frame is rendered to, recreating swapchain first if it's out of date.
+    fn acquire_image(&mut self) -> Result<(usize, SwapchainAcquireFuture<winit::Window>), RenderError> {
+        if let Some(previous_frame) = &mut self.previous_frame {
+            previous_frame.cleanup_finished();
+        }
+
+        if self.recreate_swapchain {
+            self.recreate_swapchain()?;
+        }
+
+        match swapchain::acquire_next_image(self.swapchain.clone(), None) {
+            Ok(r) => Ok(r),
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                Err(RenderError::AcquireError(AcquireError::OutOfDate))
+            },
+            Err(err) => Err(RenderError::AcquireError(err)),
+        }
+    }
+
+    /// Executes commands stored in command buffer.
+    pub fn execute_command_buffer(&mut self, image_num: usize, acquire_future: SwapchainAcquireFuture<winit::Window>, command_buffer: AutoCommandBufferBuilder) -> Result<(), RenderError> {
+        profile_scope!("submit frame");
+        let mut command_buffer = command_buffer.end_render_pass()?;
+        if let Some(auto_exposure) = &mut self.auto_exposure {
+            command_buffer = auto_exposure.add_commands(command_buffer, self.images[image_num].clone())?;
+        }
+        let command_buffer = command_buffer.build()?;
+        
+        let previous_frame = self.previous_frame.take()
+                                 .unwrap_or_else(|| Box::new(sync::now(self.device.clone())) as Box<_>);
+        let previous_frame = self.pending_semaphores.drain(..)
+                                 .fold(previous_frame, |future, upload| Box::new(future.join(upload)) as Box<_>);
+
+        let future = previous_frame.join(acquire_future)
+                                .then_execute(self.queues.graphics_queue(), command_buffer)?
+                                .then_swapchain_present(self.queues.present_queue(), self.swapchain.clone(), image_num)
+                                .then_signal_fence_and_flush();
+
+        match future {
+            Ok(future) => {
+                self.previous_frame = Some(Box::new(future) as Box<_>);
+                Ok(())
+            }
+            Err(sync::FlushError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                return Err(RenderError::FlushError(sync::FlushError::OutOfDate))
+            }
+            Err(e) => {
+                return Err(RenderError::FlushError(e))
+            }
+        }   
+    }
+
+    /// Creates vulkan command buffer of a new frame. Uniforms uploaded until the next call are written to buffers of this frame.
+    pub fn create_command_buffer(&mut self) -> Result<AutoCommandBufferBuilder, RenderError> {
+        self.uniform_manager.begin_frame();
+        Ok(AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), self.queues.graphics_queue().family())?)
+    }
+
+    /// Adds commands used to draw current scene to command buffer.
+    /// Scene rendered below the window resolution or with bloom is upscaled at the start of the swapchain render pass, which is left open,
+    /// so bloom isn't drawn around particles and UI drawn afterwards.
+    fn add_scene_commands(&mut self, mut command_buffer: AutoCommandBufferBuilder, image_num: usize, asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let framebuffer = self.framebuffers[image_num].clone();
+
+        self.stats.reset_frame_counters();
+        self.remove_unused_texture_sets();
+        if let Some(scene) = asset_manager.active_scene_mut() {
+            profile_scope!("update transforms");
+            self.stats.transforms_updated = scene.update_transforms().len() as u32;
+        }
+        let bloom = asset_manager.active_scene().and_then(|scene| self.scene_camera(scene).bloom());
+        self.update_scaled_target(bloom.is_some())?;
+
+        if let Some(scene) = asset_manager.active_scene() {
+            let (scene_framebuffer, pipelines, debug_pipeline, dimensions) = match &self.scaled_target {
+                Some(scaled_target) => {
+                    let [width, height] = scaled_target.dimensions();
+                    (scaled_target.framebuffer(), scaled_target.pipelines().clone(), scaled_target.debug_pipeline(), [width as f32, height as f32])
+                },
+                None => {
+                    let window_dimensions = get_window_dimensions(self.surface.window());
+                    (framebuffer.clone(), self.pipelines.clone(), self.debug_pipeline.clone(), [window_dimensions.width as f32, window_dimensions.height as f32])
+                },
+            };
+            // auto exposure adapts to frames metered after they're drawn, so it only applies to the main camera
+            let mut camera = self.scene_camera(scene);
+            let view_projection = camera.projection_matrix(dimensions[0], dimensions[1]) * camera.view_matrix();
+            let target = ObjectsTarget {
+                framebuffer: scene_framebuffer,
+                pipelines: &pipelines,
+            };
+            if let (Some(settings), Some(auto_exposure)) = (camera.auto_exposure(), &mut self.auto_exposure) {
+                let exposure = auto_exposure.begin_frame(&settings, camera.exposure());
+                camera.set_exposure(exposure);
+            }
+            command_buffer = self.add_objects_commands(command_buffer, target, &camera, dimensions, true, asset_manager)?;
+
+            if !self.debug_draw.is_empty() && camera.visibility_mask().intersects(RenderLayers::HELPERS) {
+                let mut vertices = self.debug_draw.vertices().to_vec();
+                vertices.extend(self.debug_draw.label_vertices(&camera, dimensions[1]));
+                let vertex_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::vertex_buffer(), vertices.into_iter())?;
+                command_buffer = command_buffer.draw(
+                    debug_pipeline,
+                    &DynamicState::none(),
+                    vec!(vertex_buffer),
+                    (),
+                    DebugPushConstants { view_projection: view_projection.into() },
+                )?;
+                self.stats.draw_calls += 1;
+            }
+
+            if let Some(scaled_target) = &self.scaled_target {
+                command_buffer = command_buffer.end_render_pass()?;
+                let bloom_image = match &bloom {
+                    Some(bloom) => {
+                        profile_scope!("bloom");
+                        let (bloom_commands, bloom_image) = self.bloom_pass.add_commands(self.device.clone(), command_buffer, scaled_target, bloom, &mut self.stats)?;
+                        command_buffer = bloom_commands;
+                        Some((bloom_image, bloom.intensity.max(0.0)))
+                    },
+                    None => None,
+                };
+                profile_scope!("upscale scene");
+                command_buffer = command_buffer.begin_render_pass(framebuffer, false, clear_values())?;
+                command_buffer = self.upscaler.add_commands(command_buffer, scaled_target, self.upscale_filter, bloom_image)?;
+                self.stats.draw_calls += 1;
+            }
+        } else {
+            command_buffer = command_buffer.begin_render_pass(framebuffer, false, clear_values())?;
+        }
+        self.debug_draw.clear();
+
+        Ok(command_buffer)
+    }
+
+    /// Creates target the scene is rendered to when render scale or bloom changed, or drops it when the scene is rendered at the window resolution without bloom.
+    /// Bloom needs colors brighter than white, which the swapchain format can't store, so with bloom the scene is rendered to an HDR target.
+    fn update_scaled_target(&mut self, bloom: bool) -> Result<(), RenderError> {
+        let dimensions = render_scale::scaled_dimensions(self.images[0].dimensions(), self.render_scale());
+        let format = if bloom { bloom_pass::HDR_FORMAT } else { self.swapchain.format() };
+        if dimensions == self.images[0].dimensions() && !bloom {
+            self.scaled_target = None;
+        } else if self.scaled_target.as_ref().map(|scaled_target| (scaled_target.dimensions(), scaled_target.format())) != Some((dimensions, format)) {
+            self.scaled_target = Some(ScaledTarget::new(self.device.clone(), self.shader_set.clone(), format, dimensions)?);
+        }
+        Ok(())
+    }
+
+    /// Adds commands drawing particles of the active scene, if its camera draws the particles layer. Has to be called after `render_scene`, while its render pass is still open.
+    pub fn add_particle_commands(&mut self, command_buffer: AutoCommandBufferBuilder, particle_system: &ParticleSystem, asset_manager: &AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        profile_scope!("draw particles");
+        let camera = match asset_manager.active_scene().map(|scene| self.scene_camera(scene)) {
+            Some(camera) if camera.visibility_mask().intersects(RenderLayers::PARTICLES) => camera,
+            _ => return Ok(command_buffer),
+        };
+        let window_dimensions = get_window_dimensions(self.surface.window());
+        let dimensions = [window_dimensions.width as f32, window_dimensions.height as f32];
+        self.particle_renderer.add_commands(command_buffer, particle_system, asset_manager, &camera, dimensions, self.device.clone(), &mut self.stats)
+    }
+
+    /// Adds commands drawing in-game UI on top of the scene. Has to be called after `render_scene`, while its render pass is still open.
+    pub fn add_ui_commands(&mut self, command_buffer: AutoCommandBufferBuilder, ui: &Ui, asset_manager: &AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        profile_scope!("draw ui");
+        let window_dimensions = get_window_dimensions(self.surface.window());
+        let dimensions = [window_dimensions.width as f32, window_dimensions.height as f32];
+        self.ui_renderer.add_commands(command_buffer, ui, asset_manager, self.device.clone(), self.queues.graphics_queue(), dimensions, &mut self.stats)
+    }
+
+    /// Adds commands drawing overlay on top of everything drawn before it. Has to be called after `render_scene`, while its render pass is still open.
+    pub fn add_overlay_commands(&mut self, command_buffer: AutoCommandBufferBuilder, overlay: &Overlay) -> Result<AutoCommandBufferBuilder, RenderError> {
+        if overlay.is_empty() {
+            return Ok(command_buffer);
+        }
+        profile_scope!("draw overlay");
+        let window_dimensions = get_window_dimensions(self.surface.window());
+        let dimensions = [window_dimensions.width as f32, window_dimensions.height as f32];
+        let mut command_buffer = self.ui_renderer.add_rect_commands(command_buffer, overlay.rects(), self.device.clone(), dimensions, &mut self.stats)?;
+        if !overlay.vertices().is_empty() {
+            // maps window pixels to clip space, with lines in front of everything drawn before
+            let pixels_to_clip = glm::ortho(0.0, dimensions[0], 0.0, dimensions[1], -1.0, 1.0);
+            let vertex_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::vertex_buffer(), overlay.vertices().iter().cloned())?;
+            command_buffer = command_buffer.draw(
+                self.debug_pipeline.clone(),
+                &DynamicState::none(),
+                vec!(vertex_buffer),
+                (),
+                DebugPushConstants { view_projection: pixels_to_clip.into() },
+            )?;
+            self.stats.draw_calls += 1;
+        }
+        Ok(command_buffer)
+    }
+
+    /// Adds commands drawing objects of the active scene as seen by the camera. Render pass of the target is started by this function,
+    /// because objects culled on the GPU are culled by a compute pass, which has to run before it, and is left open for further commands.
+    fn add_objects_commands(&mut self, mut command_buffer: AutoCommandBufferBuilder, target: ObjectsTarget, camera: &Camera, dimensions: [f32; 2],
+                            highlight_selection: bool, asset_manager: &AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        profile_scope!("draw objects");
+        let ObjectsTarget { framebuffer, pipelines } = target;
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => return Ok(command_buffer.begin_render_pass(framebuffer, false, clear_values())?),
+        };
+        let [width, height] = dimensions;
+        let mut transformation_uniform_data = camera.as_uniform_data(width, height);
+        let mut light_data = scene.light_data();
+        light_data.exposure = camera.exposure_multiplier();
+        self.uniform_manager.set_light_data(light_data);
+        let frustum = camera.frustum(width, height);
+
+        // objects are drawn queue by queue and by sort key, transparent ones are drawn from the farthest to the closest
+        let camera_position = camera.position_vec3();
+        // batched objects are drawn by their static batches
+        let visibility_mask = camera.visibility_mask();
+        let objects = scene.objects().iter()
+                                     .filter(|object| !object.is_batched())
+                                     .chain(scene.static_batches().iter().map(StaticBatch::object))
+                                     .filter(|object| visibility_mask.intersects(object.render_layers()));
+        let mut draw_list: Vec<(&Object, PipelineKey, DrawOrder)> = objects.map(|object| {
+            let (topology, vertex_format) = object.mesh().map(|mesh| {
+                let mesh = mesh.read().unwrap();
+                (mesh.topology(), mesh.vertex_format())
+            }).unwrap_or_default();
+            let pipeline_key = match object.material() {
+                Some(material) => PipelineKey::new(&material.read().unwrap(), topology, vertex_format),
+                None => PipelineKey::new(&Material::default(), topology, vertex_format),
+            };
+            let draw_order = DrawOrder {
+                queue: object.render_queue().unwrap_or_else(|| RenderQueue::for_blend_mode(pipeline_key.blend_mode)),
+                sort_key: object.sort_key(),
+                distance: glm::distance(&camera_position, &scene.world_position(object)),
+            };
+            (object, pipeline_key, draw_order)
+        }).collect();
+        draw_list.sort_by(|x, y| x.2.compare(&y.2));
+
+        // uniform data of all visible objects is collected first, so that it can be uploaded at once
+        let mut visible_objects = Vec::with_capacity(draw_list.len());
+        for (object, pipeline_key, draw_order) in draw_list {
+            let mesh = match object.mesh() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let (mesh_texture, (vertex_buffer, index_buffer), mesh_bounds) = {
+                let mesh = mesh.read().unwrap();
+                (mesh.texture(), mesh.gpu_buffers()?, mesh.bounds())
+            };
+
+            let model_matrix = scene.world_matrix(object);
+            if !self.gpu_culling && !frustum.intersects_bounds(&mesh_bounds, &model_matrix) {
+                self.stats.objects_culled += 1;
+                continue;
+            }
+
+            let mut material_data = object_material_data(object, scene.light_color());
+            let (texture, depth_bias) = match object.material() {
+                Some(material) if !object.light_source() => {
+                    let material = material.read().unwrap();
+                    let texture = material.albedo_texture().and_then(|name| asset_manager.texture(name)).unwrap_or(mesh_texture);
+                    (texture, material.depth_bias())
+                },
+                _ => (mesh_texture, 0.0),
+            };
+            // lightmaps missing from assets leave the object lit dynamically
+            let lightmap = object.lightmap().and_then(|lightmap| {
+                let texture = asset_manager.texture(&lightmap.texture)?;
+                material_data.parameters[2] = lightmap.intensity;
+                Some(texture)
+            });
+
+            transformation_uniform_data.model = model_matrix.into();
+            visible_objects.push(VisibleObject {
+                pipeline_key,
+                draw_order,
+                mesh_key: &*mesh as *const RwLock<Mesh> as usize,
+                material_key: object.material().map(|material| &*material as *const RwLock<Material> as usize).unwrap_or(0),
+                vertex_buffer,
+                index_buffer,
+                bounds: mesh_bounds,
+                texture,
+                lightmap,
+                transformation_data: transformation_uniform_data,
+                material_data,
+                depth_bias,
+                uniform_scale: object.uniform_scale(),
+                selected: highlight_selection && self.selected_objects.contains(&object.id()),
+            });
+        }
+        let instanced_draws = self.instanced_draws(&frustum, visibility_mask, asset_manager)?;
+        if visible_objects.is_empty() && instanced_draws.is_empty() {
+            return Ok(command_buffer.begin_render_pass(framebuffer, false, clear_values())?);
+        }
+        if self.batched_rendering {
+            // objects of the opaque queue with the same sort key can be drawn in any order, other queues keep their order
+            let opaque_start = visible_objects.iter().take_while(|visible_object| visible_object.draw_order.queue < RenderQueue::Opaque).count();
+            let opaque_end = opaque_start + visible_objects[opaque_start..].iter()
+                                                                           .take_while(|visible_object| visible_object.draw_order.queue == RenderQueue::Opaque)
+                                                                           .count();
+            visible_objects[opaque_start..opaque_end].sort_by_key(|visible_object| {
+                (visible_object.draw_order.sort_key, visible_object.material_key, visible_object.texture_key(), visible_object.lightmap_key(),
+                 visible_object.vertex_buffer.block_key(), visible_object.mesh_key)
+            });
+        }
+
+        // objects are added in drawing order, so objects drawn by a batch have consecutive indices
+        for visible_object in &visible_objects {
+            self.uniform_manager.push_object(visible_object.transformation_data, visible_object.material_data);
+        }
+        // instances are added after all objects, so that indices of objects culled on the GPU start at zero
+        for instanced_draw in &instanced_draws {
+            for transform in &instanced_draw.transforms {
+                transformation_uniform_data.model = (*transform).into();
+                self.uniform_manager.push_object(transformation_uniform_data, instanced_draw.material_data);
+            }
+        }
+        // all pipelines share the same layout, so the descriptor set created for one of them is used with all of them
+        let (uniform_frame, uniform_set) = self.upload_uniforms(pipelines[&PipelineKey::default()].clone())?;
+
+        let draw_commands = if self.gpu_culling && !visible_objects.is_empty() {
+            let culled_objects: Vec<CulledObject> = visible_objects.iter().map(|visible_object| CulledObject {
+                bounds: visible_object.bounds,
+                index_count: visible_object.index_buffer.len() as u32,
+                first_index: visible_object.index_buffer.block_offset() as u32,
+                vertex_offset: visible_object.vertex_buffer.block_offset() as u32,
+            }).collect();
+            let draw_commands = if self.queues.has_dedicated_compute_queue() {
+                // culling runs on compute queue while the rest of the frame is recorded, frame waits for it when it's submitted
+                let compute_command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), self.queues.compute_queue().family())?;
+                let (compute_command_buffer, draw_commands, buffers_allocated) = self.culling_pass.add_commands(compute_command_buffer, &frustum, &uniform_frame, &culled_objects)?;
+                self.pending_semaphores.push(self.queues.submit_compute(compute_command_buffer.build()?)?);
+                self.stats.uniform_buffers_allocated += buffers_allocated;
+                draw_commands
+            } else {
+                let (culling_command_buffer, draw_commands, buffers_allocated) = self.culling_pass.add_commands(command_buffer, &frustum, &uniform_frame, &culled_objects)?;
+                command_buffer = culling_command_buffer;
+                self.stats.uniform_buffers_allocated += buffers_allocated;
+                draw_commands
+            };
+            Some(draw_commands)
+        } else {
+            None
+        };
+        command_buffer = command_buffer.begin_render_pass(framebuffer, false, clear_values())?;
+
+        let mut batch_start = 0;
+        while batch_start < visible_objects.len() {
+            let first_object = &visible_objects[batch_start];
+            let mut batch_end = batch_start + 1;
+            if self.batched_rendering {
+                let batches = |visible_object: &VisibleObject| if draw_commands.is_some() {
+                    visible_object.batches_indirectly_with(first_object)
+                } else {
+                    visible_object.batches_with(first_object)
+                };
+                while batch_end < visible_objects.len() && batches(&visible_objects[batch_end]) {
+                    batch_end += 1;
+                }
+            }
+
+            let pipeline = pipelines[&first_object.pipeline_key].clone();
+            let texture_set = self.texture_set(pipeline.clone(), first_object.texture.clone(), first_object.lightmap.clone())?;
+
+            let push_constants = PushConstants {
+                uniform_scale: first_object.uniform_scale as u32,
+                selected: first_object.selected as u32,
+                depth_bias: first_object.depth_bias,
+            };
+
+            command_buffer = match &draw_commands {
+                // every indirect command draws one instance starting at index of its object, so instance buffer covers all objects,
+                // and addresses its mesh inside the memory blocks, so whole blocks are bound
+                Some(draw_commands) => command_buffer.draw_indexed_indirect(
+                    pipeline,
+                    &DynamicState::none(),
+                    vec!(first_object.vertex_buffer.block_buffer(), uniform_frame.instances(0..visible_objects.len())),
+                    first_object.index_buffer.block_buffer(),
+                    draw_commands.clone().into_buffer_slice().slice(batch_start..batch_end).expect("Object indices out of culled range"),
+                    (uniform_set.clone(), texture_set),
+                    push_constants,
+                )?,
+                None => command_buffer.draw_indexed(
+                    pipeline,
+                    &DynamicState::none(), 
+                    vec!(first_object.vertex_buffer.buffer(), uniform_frame.instances(batch_start..batch_end)),
+                    first_object.index_buffer.clone(), 
+                    (uniform_set.clone(), texture_set),
+                    push_constants,
+                )?,
+            };
+            for visible_object in &visible_objects[batch_start..batch_end] {
+                self.stats.add_triangles(visible_object.pipeline_key.topology, visible_object.index_buffer.len());
+            }
+            self.stats.objects_rendered += (batch_end - batch_start) as u32;
+            self.stats.draw_calls += 1;
+            batch_start = batch_end;
+        }
+
+        let mut instance_start = visible_objects.len();
+        for instanced_draw in instanced_draws {
+            let instance_count = instanced_draw.transforms.len();
+            let instance_end = instance_start + instance_count;
+            self.stats.add_triangles(instanced_draw.pipeline_key.topology, instanced_draw.index_buffer.len() * instance_count);
+            let pipeline = pipelines[&instanced_draw.pipeline_key].clone();
+            let texture_set = self.texture_set(pipeline.clone(), instanced_draw.texture, None)?;
+            // instances can be scaled differently, so normals are always transformed with the inverse transpose of their model matrix
+            let push_constants = PushConstants {
+                uniform_scale: 0,
+                selected: 0,
+                depth_bias: instanced_draw.depth_bias,
+            };
+            command_buffer = command_buffer.draw_indexed(
+                pipeline,
+                &DynamicState::none(),
+                vec!(instanced_draw.vertex_buffer.buffer(), uniform_frame.instances(instance_start..instance_end)),
+                instanced_draw.index_buffer,
+                (uniform_set.clone(), texture_set),
+                push_constants,
+            )?;
+            self.stats.instances_rendered += instance_count as u32;
+            self.stats.draw_calls += 1;
+            instance_start = instance_end;
+        }
+
+        Ok(command_buffer)
+    }
+
+    /// Collects instances of instanced meshes in visible render layers which are inside the frustum. Instances are culled on the CPU, one by one.
+    fn instanced_draws(&mut self, frustum: &Frustum, visibility_mask: RenderLayers, asset_manager: &AssetManager) -> Result<Vec<InstancedDraw>, RenderError> {
+        let mut instanced_draws = Vec::new();
+        for instanced_mesh in asset_manager.instanced_meshes() {
+            let instanced_mesh = instanced_mesh.read().unwrap();
+            if !visibility_mask.intersects(instanced_mesh.render_layers()) {
+                continue;
+            }
+            let mesh = instanced_mesh.mesh();
+            let mesh = mesh.read().unwrap();
+            let bounds = mesh.bounds();
+            let transforms: Vec<Mat4> = instanced_mesh.transforms().iter()
+                                                       .filter(|transform| frustum.intersects_bounds(&bounds, transform))
+                                                       .cloned()
+                                                       .collect();
+            self.stats.objects_culled += (instanced_mesh.instance_count() - transforms.len()) as u32;
+            if transforms.is_empty() {
+                continue;
+            }
+
+            let (pipeline_key, material_data, texture, depth_bias) = match instanced_mesh.material() {
+                Some(material) => {
+                    let material = material.read().unwrap();
+                    let texture = material.albedo_texture().and_then(|name| asset_manager.texture(name)).unwrap_or_else(|| mesh.texture());
+                    (PipelineKey::new(&material, mesh.topology(), mesh.vertex_format()), material.shader_data(), texture, material.depth_bias())
+                },
+                None => (PipelineKey::new(&Material::default(), mesh.topology(), mesh.vertex_format()), Material::default().shader_data(), mesh.texture(), 0.0),
+            };
+            let (vertex_buffer, index_buffer) = mesh.gpu_buffers()?;
+            instanced_draws.push(InstancedDraw {
+                pipeline_key,
+                vertex_buffer,
+                index_buffer,
+                texture,
+                material_data,
+                depth_bias,
+                transforms,
+            });
+        }
+        Ok(instanced_draws)
+    }
+
+    /// Uploads uniform data of objects added to the uniform manager and creates descriptor set binding it, which is shared by all drawn objects.
+    fn upload_uniforms(&mut self, pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>) -> Result<(UniformFrame, Arc<DescriptorSet + Send + Sync>), RenderError> {
+        profile_scope!("upload uniforms");
+        let uniform_frame = self.uniform_manager.upload()?;
+        self.stats.uniform_buffers_allocated += uniform_frame.buffers_allocated();
+        let uniform_set = PersistentDescriptorSet::start(pipeline, 0)
+                                                  .add_buffer(uniform_frame.transformations())?
+                                                  .add_buffer(uniform_frame.light())?
+                                                  .add_buffer(uniform_frame.materials())?
+                                                  .build()?;
+        self.stats.descriptor_sets_created += 1;
+        Ok((uniform_frame, Arc::new(uniform_set)))
+    }
+
+    /// Returns descriptor set binding given texture. Descriptor sets are created once per texture and kept as long as the texture is used.
+    fn texture_set(&mut self, pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>, texture: Arc<Texture>,
+                   lightmap: Option<Arc<Texture>>) -> Result<Arc<DescriptorSet + Send + Sync>, RenderError> {
+        // textures are keyed by address, which stays the same while the set keeps the texture alive
+        let texture_key = &*texture as *const Texture as usize;
+        let lightmap_key = lightmap.as_ref().map(|lightmap| &**lightmap as *const Texture as usize).unwrap_or(0);
+        if let Some((_texture, _lightmap, texture_set)) = self.texture_sets.get(&(texture_key, lightmap_key)) {
+            return Ok(texture_set.clone());
+        }
+        // objects without a lightmap don't sample it, so their own texture fills its binding
+        let bound_lightmap = lightmap.clone().unwrap_or_else(|| texture.clone());
+        let (image_buffer, sampler) = texture.sampled_image()?;
+        let (lightmap_buffer, lightmap_sampler) = bound_lightmap.sampled_image()?;
+        let texture_set: Arc<DescriptorSet + Send + Sync> = Arc::new(PersistentDescriptorSet::start(pipeline, 1)
+                                                                                               .add_sampled_image(image_buffer, sampler)?
+                                                                                               .add_sampled_image(lightmap_buffer, lightmap_sampler)?
+                                                                                               .build()?);
+        self.stats.descriptor_sets_created += 1;
+        self.texture_sets.insert((texture_key, lightmap_key), (texture, lightmap, texture_set.clone()));
+        Ok(texture_set)
+    }
+
+    /// Removes descriptor sets of textures and lightmaps which were removed from assets, which are only referenced by the sets.
+    fn remove_unused_texture_sets(&mut self) {
+        let mut set_references: HashMap<usize, usize> = HashMap::new();
+        for &(texture_key, lightmap_key) in self.texture_sets.keys() {
+            *set_references.entry(texture_key).or_insert(0) += 1;
+            if lightmap_key != 0 {
+                *set_references.entry(lightmap_key).or_insert(0) += 1;
+            }
+        }
+        let used = |texture: &Arc<Texture>| Arc::strong_count(texture) > set_references[&(&**texture as *const Texture as usize)];
+        self.texture_sets.retain(|_, (texture, lightmap, _)| used(texture) && lightmap.as_ref().map_or(true, |lightmap| used(lightmap)));
+    }
+
+    /// Creates asset manager uploading assets with the device and queues of this renderer.
+    pub fn create_asset_manager(&self) -> Result<AssetManager, AssetError> {
+        AssetManager::new(self.queues(), self.device())
+    }
+
+    /// Creates offscreen target used to preview what game cameras see.
+    pub fn create_camera_preview(&self) -> Result<CameraPreview, RendererCreationError> {
+        CameraPreview::new(self.device.clone(), self.queues.graphics_queue(), self.shader_set.clone())
+    }
+
+    /// Adds commands rendering active scene as seen by the camera to the camera preview image.
+    /// Projection uses given aspect ratio, so that preview can be shown stretched to the game window proportions.
+    pub fn add_camera_preview_commands(&mut self, command_buffer: AutoCommandBufferBuilder, camera_preview: &CameraPreview, camera: &Camera,
+                                       aspect_ratio: f32, asset_manager: &AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let target = ObjectsTarget {
+            framebuffer: camera_preview.target().framebuffer(),
+            pipelines: camera_preview.pipelines(),
+        };
+        let command_buffer = self.add_objects_commands(command_buffer, target, camera, [aspect_ratio, 1.0], false, asset_manager)?;
+        camera_preview.target().add_display_commands(command_buffer.end_render_pass()?)
+    }
+
+    /// Creates offscreen target which the active scene can be captured to, with given width and height.
+    pub fn create_scene_capture(&self, width: u32, height: u32) -> Result<SceneCapture, RendererCreationError> {
+        SceneCapture::new(self.device.clone(), self.shader_set.clone(), [width, height])
+    }
+
+    /// Renders objects of the active scene as seen through its camera to the scene capture image and reads the image back.
+    /// Particles, UI and debug shapes aren't drawn. Blocks until the GPU finishes, so it's meant for tests and screenshots.
+    pub fn capture_scene(&mut self, scene_capture: &SceneCapture, asset_manager: &AssetManager) -> Result<RgbaImage, RenderError> {
+        let [width, height] = scene_capture.dimensions();
+        let camera = asset_manager.active_scene().map(|scene| self.scene_camera(scene)).unwrap_or_else(Camera::new);
+        let target = ObjectsTarget {
+            framebuffer: scene_capture.framebuffer(),
+            pipelines: scene_capture.pipelines(),
+        };
+
+        let command_buffer = self.create_command_buffer()?;
+        self.stats.reset_frame_counters();
+        let command_buffer = self.add_objects_commands(command_buffer, target, &camera, [width as f32, height as f32], false, asset_manager)?;
+        let pixel_buffer = CpuAccessibleBuffer::from_iter(self.device.clone(), BufferUsage::transfer_destination(), (0 .. width * height * 4).map(|_| 0u8))?;
+        let command_buffer = command_buffer.end_render_pass()?
+                                           .copy_image_to_buffer(scene_capture.image(), pixel_buffer.clone())?
+                                           .build()?;
+
+        let uploads = asset_manager.uploader().map(|uploader| uploader.take_pending_uploads()).unwrap_or_default();
+        let future = uploads.into_iter().fold(Box::new(sync::now(self.device.clone())) as Box<GpuFuture + Send + Sync>, |future, upload| Box::new(future.join(upload)) as Box<_>);
+        future.then_execute(self.queues.graphics_queue(), command_buffer)?
+              .then_signal_fence_and_flush()?
+              .wait(None)?;
+
+        let pixels = pixel_buffer.read()?.to_vec();
+        Ok(RgbaImage::from_raw(width, height, pixels).expect("Scene capture has wrong size"))
+    }
+
+    /// Creates offscreen target used to preview materials.
+    pub fn create_material_preview(&self) -> Result<MaterialPreview, RendererCreationError> {
+        MaterialPreview::new(self.device.clone(), self.queues.graphics_queue(), &self.shader_set)
+    }
+
+    /// Adds commands rendering a sphere with given material to the material preview image.
+    pub fn add_material_preview_commands(&mut self, command_buffer: AutoCommandBufferBuilder, material_preview: &MaterialPreview,
+                                         material: &Material, asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let mesh = asset_manager.primitive_mesh(Primitive::Sphere)?;
+        let mesh = mesh.read().unwrap();
+        let texture = material.albedo_texture().and_then(|name| asset_manager.texture(name)).unwrap_or_else(|| mesh.texture());
+
+        let mut camera = Camera::new();
+        camera.set_position(0.0, 0.0, PREVIEW_CAMERA_DISTANCE);
+        let preview = OffscreenMesh {
+            mesh: &mesh,
+            texture,
+            material,
+            model: Mat4::identity(),
+            camera,
+            light_position: (2.0, 2.0, 2.0),
+            size: MATERIAL_PREVIEW_SIZE,
+        };
+        let command_buffer = self.add_offscreen_mesh_commands(command_buffer, material_preview.target().framebuffer(), material_preview.pipeline(), preview)?;
+        material_preview.target().add_display_commands(command_buffer)
+    }
+
+    /// Creates offscreen target used to preview textures and meshes.
+    pub fn create_asset_preview(&self) -> Result<AssetPreview, RendererCreationError> {
+        AssetPreview::new(self.device.clone(), self.queues.graphics_queue(), &self.shader_set)
+    }
+
+    /// Adds commands rendering given channels and mipmap level of a texture to the asset preview image.
+    pub fn add_texture_preview_commands(&mut self, command_buffer: AutoCommandBufferBuilder, asset_preview: &AssetPreview,
+                                        texture: &Texture, channel: TextureChannel, mip_level: u32) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let pipeline = asset_preview.texture_pipeline();
+        let (image_buffer, sampler) = texture.sampled_image()?;
+        let descriptor_set = PersistentDescriptorSet::start(pipeline.clone(), 0)
+                                                     .add_sampled_image(image_buffer, sampler)?
+                                                     .build()?;
+
+        let push_constants = TexturePreviewPushConstants {
+            channel_mask: channel.mask().unwrap_or([0.0; 4]),
+            mip_level: mip_level.min(texture.mip_levels().saturating_sub(1)) as f32,
+            single_channel: channel.mask().is_some() as u32,
+        };
+
+        let command_buffer = command_buffer.begin_render_pass(asset_preview.target().framebuffer(), false, vec![PREVIEW_BACKGROUND_COLOR.into(), 1f32.into()])?
+                                           .draw(pipeline, &DynamicState::none(), BufferlessVertices { vertices: 3, instances: 1 }, descriptor_set, push_constants)?
+                                           .end_render_pass()?;
+        asset_preview.target().add_display_commands(command_buffer)
+    }
+
+    /// Adds commands rendering a mesh rotated around vertical axis by given angle in radians to the asset preview image.
+    /// Camera distance is chosen so that the whole mesh is visible.
+    pub fn add_mesh_preview_commands(&mut self, command_buffer: AutoCommandBufferBuilder, asset_preview: &AssetPreview,
+                                     mesh: &Mesh, rotation: f32, wireframe: bool) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let bounds = mesh.bounds();
+        let radius = (glm::distance(&bounds.min(), &bounds.max()) * 0.5).max(MIN_PREVIEW_RADIUS);
+        let model = glm::rotation(rotation, &glm::vec3(0.0, 1.0, 0.0)) * glm::translation(&-bounds.center());
+
+        let mut camera = Camera::new();
+        let distance = radius / (camera.fov() * 0.5).to_radians().sin();
+        camera.set_position(0.0, 0.0, distance);
+        camera.set_far_plane(camera.far_plane().max(distance + radius));
+        let preview = OffscreenMesh {
+            mesh,
+            texture: mesh.texture(),
+            material: &Material::default(),
+            model,
+            camera,
+            light_position: (distance, distance, distance),
+            size: ASSET_PREVIEW_SIZE,
+        };
+        let command_buffer = self.add_offscreen_mesh_commands(command_buffer, asset_preview.target().framebuffer(), asset_preview.mesh_pipeline(wireframe, mesh.vertex_format()), preview)?;
+        asset_preview.target().add_display_commands(command_buffer)
+    }
+
+    /// Adds commands rendering a mesh to an offscreen framebuffer, in a render pass of its own.
+    fn add_offscreen_mesh_commands(&mut self, command_buffer: AutoCommandBufferBuilder, framebuffer: Arc<FramebufferAbstract + Send + Sync>,
+                                   pipeline: Arc<GraphicsPipelineAbstract + Send + Sync>, preview: OffscreenMesh) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let mut preview_scene = Scene::new("preview", preview.camera);
+        let mut preview_light = Light::new("preview light");
+        let (x, y, z) = preview.light_position;
+        preview_light.set_position(x, y, z);
+        preview_scene.add_light(preview_light);
+
+        let mut transformation_uniform_data = preview_scene.camera().as_uniform_data(preview.size as f32, preview.size as f32);
+        transformation_uniform_data.model = preview.model.into();
+
+        self.uniform_manager.set_light_data(preview_scene.light_data());
+        let uniform_index = self.uniform_manager.push_object(transformation_uniform_data, preview.material.shader_data());
+        let (uniform_frame, uniform_set) = self.upload_uniforms(pipeline.clone())?;
+        let texture_set = self.texture_set(pipeline.clone(), preview.texture, None)?;
+        let (vertex_buffer, index_buffer) = preview.mesh.gpu_buffers()?;
+        let vertices = vec!(vertex_buffer.buffer(), uniform_frame.instances(uniform_index..uniform_index + 1));
+
+        let push_constants = PushConstants {
+            uniform_scale: 1,
+            selected: 0,
+            depth_bias: 0.0,
+        };
+
+        Ok(command_buffer.begin_render_pass(framebuffer, false, vec![PREVIEW_BACKGROUND_COLOR.into(), 1f32.into()])?
+                         .draw_indexed(pipeline, &DynamicState::none(), vertices, index_buffer, (uniform_set, texture_set), push_constants)?
+                         .end_render_pass()?)
+    }
+
+    /// Recreates swapchain when surface changed.
+    fn recreate_swapchain(&mut self) -> Result<(), RenderError>{
+        let window_dimensions: (u32, u32) = get_window_dimensions(self.surface.window()).into();
+
+        let (new_swapchain, new_images) = self.swapchain.recreate_with_dimension([window_dimensions.0, window_dimensions.1])?;
+
+        self.swapchain = new_swapchain;
+        self.images = new_images;
+        self.uniform_manager.set_frames_in_flight(self.images.len());
+
+        self.pipelines = create_pipelines(self.device.clone(), self.shader_set.clone(), self.images[0].dimensions(), self.render_pass.clone())?;
+        self.debug_pipeline = create_debug_pipeline(self.device.clone(), self.shader_set.clone(), self.images[0].dimensions(), self.render_pass.clone())?;
+        self.ui_renderer.recreate_pipeline(self.device.clone(), &self.shader_set, self.images[0].dimensions(), self.render_pass.clone())?;
+        self.particle_renderer.recreate_pipelines(self.device.clone(), &self.shader_set, self.images[0].dimensions(), self.render_pass.clone())?;
+        self.upscaler.recreate_pipeline(self.device.clone(), &self.shader_set, self.images[0].dimensions(), self.render_pass.clone())?;
+        self.framebuffers = create_framebuffers::<RenderError>(self.device.clone(), &self.images, self.render_pass.clone())?;
+
+        self.recreate_swapchain = false;
+        self.stats.swapchain_recreations += 1;
+        Ok(())
+    }
+
+    /// Returns vulkan queues.
+    pub fn queues(&self) -> Queues {
+        self.queues.clone()
+    }
+
+    /// Returns vulkan device.
+    pub fn device(&self) -> Arc<Device> {
+        self.device.clone()
+    }
+
+    pub fn surface(&self) -> Arc<Surface<Window>> {
+        self.surface.clone()
+    }
+
+    pub fn render_pass(&self) -> Arc<RenderPassAbstract + Send + Sync> {
+        self.render_pass.clone()
+    }
+
+    pub fn framebuffer(&self, image_num: usize) -> Arc<FramebufferAbstract + Send + Sync> {
+        self.framebuffers[image_num].clone()
+    }
+
+}
+
+/// Returns values color and depth attachments are cleared with at the start of a render pass drawing objects.
+pub(crate) fn clear_values() -> Vec<ClearValue> {
+    vec![[0.0, 0.0, 0.0, 1.0].into(), 1f32.into()]
+}
+
+/// Creates framebuffers, which contain list of images that are attached.
+fn create_framebuffers<E: From<FramebufferCreationError> + From<ImageCreationError>>(
+    device: Arc<Device>,
+    images: &[Arc<SwapchainImage<Window>>], 
+    render_pass: Arc<RenderPassAbstract + Send + Sync>
+) -> Result<Vec<Arc<FramebufferAbstract + Send + Sync>>, E> {
+
+    let dimensions = images[0].dimensions();
+
+    let mut framebuffers = Vec::with_capacity(images.len());
+
+    for image in images {
+        // every image has a depth buffer of its own, so that frames in flight don't write to the same one
+        let depth_buffer = AttachmentImage::transient(device.clone(), dimensions, Format::D16Unorm)?;
+        let framebuffer = Framebuffer::start(render_pass.clone())
+                                                        .add(image.clone())?
+                                                        .add(depth_buffer)?
+                                                        .build()?;
+        framebuffers.push(Arc::new(framebuffer) as Arc<FramebufferAbstract + Send + Sync>);
+    }
+
+    Ok(framebuffers)
+}
+
+/// Creates pipelines for every combination of blend mode, cull mode, winding, topology and vertex format, drawing to images of given dimensions.
+pub(crate) fn create_pipelines(
+    device: Arc<Device>,
+    shader_set: Arc<ShaderSet>,
+    dimensions: [u32; 2],
+    render_pass: Arc<RenderPassAbstract + Send + Sync>
+) -> Result<HashMap<PipelineKey, Arc<GraphicsPipelineAbstract + Send + Sync>>, GraphicsPipelineCreationError> {
+    let mut pipelines = HashMap::new();
+    for key in PipelineKey::all() {
+        pipelines.insert(key, create_pipeline(device.clone(), shader_set.clone(), dimensions, render_pass.clone(), key)?);
+    }
+    Ok(pipelines)
+}
+
+/// Creates a pipeline, which describe a graphical or computer operation.
+/// Transparent blend modes don't write to the depth buffer.
+fn create_pipeline(
+    device: Arc<Device>, 
+    shader_set: Arc<ShaderSet>, 
+    dimensions: [u32; 2],
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    key: PipelineKey,
+) -> Result<Arc<GraphicsPipelineAbstract + Send + Sync>, GraphicsPipelineCreationError> {
+    let PipelineKey { blend_mode, cull_mode, front_face, topology, vertex_format } = key;
+    let blend = match blend_mode {
+        BlendMode::Opaque => AttachmentBlend::pass_through(),
+        BlendMode::AlphaBlend => AttachmentBlend::alpha_blending(),
+        BlendMode::Additive => AttachmentBlend {
+            color_destination: BlendFactor::One,
+            alpha_destination: BlendFactor::One,
+            ..AttachmentBlend::alpha_blending()
+        },
+    };
+    let depth_stencil = DepthStencil {
+        depth_write: !blend_mode.is_transparent(),
+        ..DepthStencil::simple_depth_test()
+    };
+
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(shader_set.vertex_layout(vertex_format))
+        .vertex_shader(shader_set.vertex_shader().main_entry_point(), ())
+        .primitive_topology(match topology {
+            Topology::TriangleList => PrimitiveTopology::TriangleList,
+            Topology::LineList => PrimitiveTopology::LineList,
+            Topology::PointList => PrimitiveTopology::PointList,
+        })
+        .viewports_dynamic_scissors_irrelevant(1)
+        .viewports(std::iter::once(Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }))
+        .fragment_shader(shader_set.fragment_shader().main_entry_point(), ())
+        .depth_stencil(depth_stencil)
+        .blend_collective(blend);
+    let pipeline = match cull_mode {
+        CullMode::None => pipeline.cull_mode_disabled(),
+        CullMode::Front => pipeline.cull_mode_front(),
+        CullMode::Back => pipeline.cull_mode_back(),
+    };
+    let pipeline = match front_face {
+        FrontFace::CounterClockwise => pipeline.front_face_counter_clockwise(),
+        FrontFace::Clockwise => pipeline.front_face_clockwise(),
+    };
+    let pipeline = pipeline.render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+                           .build(device.clone())?;
+
+    Ok(Arc::new(pipeline))
+}
+
+/// Creates a pipeline used to draw debug lines.
+pub(crate) fn create_debug_pipeline(
+    device: Arc<Device>, 
+    shader_set: Arc<ShaderSet>, 
+    dimensions: [u32; 2],
+    render_pass: Arc<RenderPassAbstract + Send + Sync>
+) -> Result<Arc<GraphicsPipelineAbstract + Send + Sync>, GraphicsPipelineCreationError> {
+    
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(ShaderSet::debug_vertex_layout())
+        .vertex_shader(shader_set.debug_vertex_shader().main_entry_point(), ())
+        .line_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .viewports(std::iter::once(Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }))
+        .fragment_shader(shader_set.debug_fragment_shader().main_entry_point(), ())
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device.clone())?;
+
+    Ok(Arc::new(pipeline))
+}
+
+/// Finds the best graphical device to render to.
+fn rank_devices(devices: PhysicalDevicesIter, renderer_kind: RendererKind) -> Result<PhysicalDevice, RendererCreationError> {
+    let device = devices.into_iter().map(|device|
+        match (device.ty(), renderer_kind) {
+            (PhysicalDeviceType::Cpu, RendererKind::Software) => (device, 5),
+            (PhysicalDeviceType::DiscreteGpu, _) => (device, 4),
+            (PhysicalDeviceType::VirtualGpu, _) => (device, 3),
+            (PhysicalDeviceType::IntegratedGpu, _) => (device, 2),
+            (PhysicalDeviceType::Cpu, _) => (device, 1),
+            (PhysicalDeviceType::Other, _) => (device, 0),
+        }
+    ).max_by(|x, y| x.1.cmp(&y.1)).map(|(device, _)| device).ok_or(RendererCreationError::NoPhysicalDeviceError)?;
+    if renderer_kind == RendererKind::Software && device.ty() != PhysicalDeviceType::Cpu {
+        warn!("Software renderer was requested, but no CPU implementation of Vulkan was found");
+    }
+    Ok(device)
+}
+
+/// Creates new vulkan instance
+fn create_new_instance() -> Result<Arc<Instance>, InstanceCreationError> {
+    let extensions = RawInstanceExtensions::from(&vulkano_win::required_extensions());
+    // portability drivers, like MoltenVK on macOS, are listed only when the loader is told the application supports them
+    let portability_extensions = RawInstanceExtensions::new(vec![CString::new(PORTABILITY_ENUMERATION_EXTENSION).unwrap()]);
+    let supported_portability_extensions = match RawInstanceExtensions::supported_by_core() {
+        Ok(supported_extensions) => portability_extensions.intersection(&supported_extensions),
+        Err(err) => {
+            warn!("Couldn't query supported instance extensions: {}", err);
+            RawInstanceExtensions::none()
+        }
+    };
+    Instance::new(None, extensions.union(&supported_portability_extensions), None)
+}
+
+/// Creates new vulkan logical device
+fn create_logical_device<'a>(physical_device: PhysicalDevice, physical_queues: &[(QueueFamily<'a>, f32)]) 
+        -> Result<(Arc<Device>, QueuesIter), DeviceCreationError> {
+    let minimal_features = vulkano::device::Features {
+        depth_clamp: physical_device.supported_features().depth_clamp, //needed for correct shadow mapping, missing on some portability drivers
+        fill_mode_non_solid: physical_device.supported_features().fill_mode_non_solid, //used by wireframe mesh preview when available
+        sampler_anisotropy: physical_device.supported_features().sampler_anisotropy, //used by texture samplers depending on texture quality
+        .. vulkano::device::Features::none()
+    };
+
+    let device_extensions_needed = vulkano::device::DeviceExtensions {
+        khr_swapchain: true,
+        .. vulkano::device::DeviceExtensions::none()
+    };
+
+    // portability subset drivers, like MoltenVK, require applications to enable the extension describing what they don't support
+    let portability_subset = RawDeviceExtensions::new(vec![CString::new(PORTABILITY_SUBSET_EXTENSION).unwrap()])
+                                                  .intersection(&RawDeviceExtensions::supported_by_device(physical_device));
+    if portability_subset != RawDeviceExtensions::none() {
+        info!("Device is a Vulkan portability subset implementation");
+    }
+    let device_extensions = RawDeviceExtensions::from(&device_extensions_needed).union(&portability_subset);
+
+    Device::new(
+        physical_device, &minimal_features,
+        device_extensions, physical_queues.iter().cloned()
+    )
+}
+
+/// Creates a swapchain, which is a collection of images that are presented to the screen.
+fn create_swapchain<'a>(surface: Arc<Surface<Window>>, physical_device: PhysicalDevice<'a>,
+                        device: Arc<Device>, queues: &Queues, settings: &Settings) 
+        -> Result<(Arc<Swapchain<Window>>, Vec<Arc<SwapchainImage<Window>>>), RendererCreationError> {
+    let capabilities = surface.capabilities(physical_device)?;
+    let usage = capabilities.supported_usage_flags;
+    if let Some(preferred_format) = settings.swapchain_format() {
+        if !capabilities.supported_formats.iter().any(|&(format, _)| format == preferred_format) {
+            warn!("Swapchain format {:?} isn't supported by the surface", preferred_format);
+        }
+    }
+    let (format, color_space) = surface_format::choose_surface_format(&capabilities.supported_formats, settings.swapchain_format(), settings.hdr_output())
+                                               .ok_or(RendererCreationError::NoSurfaceFormatError)?;
+    info!("Using swapchain format {:?} with {:?} color space", format, color_space);
+
+    let initial_dimensions = match capabilities.current_extent {
+        Some(dimensions) => dimensions,
+        None => {
+            let dimensions: (u32, u32) = get_window_dimensions(surface.window()).into();
+            [dimensions.0, dimensions.1]
+        }
+    };
+
+    let present_mode = {
+        if capabilities.present_modes.mailbox {
+            info!("Using Mailbox presentation mode");
+            PresentMode::Mailbox
+        } else {
+            info!("Using Fifo presentation mode");
+            PresentMode::Fifo
+        }
+    };
+
+    Swapchain::new(
+        device.clone(),
+        surface.clone(),
+        capabilities.min_image_count,
+        format,
+        initial_dimensions,
+        1,
+        usage,
+        queues.swapchain_sharing_mode(),
+        SurfaceTransform::Identity,
+        CompositeAlpha::Opaque,
+        present_mode,
+        true,
+        None
+    ).map_err(RendererCreationError::from)
+}
+
+/// Returns material data uploaded for an object, with values of custom parameters set by the object.
+/// Light sources glow with color of the main light instead of using their material.
+fn object_material_data(object: &Object, light_color: (f32, f32, f32)) -> MaterialData {
+    match object.material() {
+        _ if object.light_source() => {
+            let (r, g, b) = light_color;
+            Material::emissive("light source", r, g, b, 1.0).shader_data()
+        },
+        Some(material) => material.read().unwrap().object_shader_data(object.params()),
+        None => Material::default().shader_data(),
+    }
+}
+
+/// Creates render pass, which is a collection of attachments, subpasses, and dependencies between the subpasses.
+fn create_renderpass(device: Arc<Device>, format: Format) -> Result<Arc<RenderPassAbstract + Send + Sync>, RenderPassCreationError> {
+    let render_pass = single_pass_renderpass!(device.clone(),
+                            attachments: {
+                                color: {
+                                    load: Clear,
+                                    store: Store,
+                                    format: format,
+                                    samples: 1,
+                                },
+                                depth: {
+                                    load: Clear,
+                                    store: DontCare,
+                                    format: Format::D16Unorm,
+                                    samples: 1,
+                                }
+                            },
+                            pass: {
+                                color: [color],
+                                depth_stencil: {depth}
+                            }
+                      )?;
+    Ok(Arc::new(render_pass))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::object::ObjectBuilder;
+
+    #[test]
+    fn pipelines_exist_for_every_material_topology_and_vertex_format() {
+        let keys = PipelineKey::all();
+        let mut material = Material::default();
+        material.set_cull_mode(CullMode::Front);
+        material.set_front_face(FrontFace::Clockwise);
+        for &topology in Topology::ALL.iter() {
+            for &vertex_format in VertexFormat::ALL.iter() {
+                assert!(keys.contains(&PipelineKey::new(&material, topology, vertex_format)));
+            }
+        }
+        assert_eq!(PipelineKey::new(&Material::default(), Topology::PointList, VertexFormat::Standard),
+                   PipelineKey::new(&material, Topology::PointList, VertexFormat::Standard));
+    }
+
+    #[test]
+    fn params_set_by_objects_are_uploaded_in_declaration_order() {
+        let mut material = Material::new("wobbly");
+        material.declare_custom_param("wobble_amount", 0.1);
+        material.declare_custom_param("wobble_speed", 1.0);
+        material.declare_custom_param("glow", 0.0);
+        let mut object = ObjectBuilder::new("jelly").build();
+        object.set_material(Some(Arc::new(RwLock::new(material))));
+        object.set_param("glow", 2.0);
+        object.set_param("wobble_amount", 0.3);
+
+        let material_data = object_material_data(&object, (1.0, 1.0, 1.0));
+
+        assert_eq!([0.3, 1.0, 2.0, 0.0], material_data.custom);
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use image::{DynamicImage, RgbaImage};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::swapchain::SwapchainAcquireFuture;
+use winit::Window;
+use log::*;
+
+use crate::renderer::{clear_values, get_window_dimensions, PipelineKey, Renderer};
+use crate::renderer::backend::{DrawCommand, MeshHandle, RenderBackend, TextureHandle};
+use crate::renderer::renderer_error::RenderError;
+use crate::renderer::shader::fragment_shader::ty::PushConstants;
+use crate::renderer::upload::AssetUploader;
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::material::Material;
+use crate::resource::mesh::{Mesh, Vertex};
+use crate::resource::scene::Scene;
+use crate::resource::texture::{SamplerQuality, Texture};
+
+/// Frame started by `begin_frame`, which collects draws until it ends.
+struct BackendFrame {
+    image_num: usize,
+    acquire_future: SwapchainAcquireFuture<Window>,
+    command_buffer: AutoCommandBufferBuilder,
+    camera: Camera,
+    lights: Vec<Light>,
+    draws: Vec<DrawCommand>,
+}
+
+/// Render backend drawing with Vulkan through the engine renderer.
+/// Meshes and textures uploaded through the backend are owned by it, separately from assets of the asset manager.
+pub struct VulkanBackend {
+    renderer: Renderer,
+    uploader: AssetUploader,
+    meshes: Vec<Mesh>,
+    textures: Vec<Arc<Texture>>,
+    frame: Option<BackendFrame>,
+}
+
+impl VulkanBackend {
+    /// Creates backend rendering with given renderer.
+    pub fn new(renderer: Renderer) -> Result<Self, RenderError> {
+        let uploader = AssetUploader::new(renderer.device(), renderer.queues());
+        let white_pixel = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255])));
+        let default_texture = Arc::new(Texture::upload("backend default", white_pixel, &uploader, renderer.device(), SamplerQuality::default())?);
+        Ok(VulkanBackend {
+            renderer,
+            uploader,
+            meshes: Vec::new(),
+            textures: vec![default_texture],
+            frame: None,
+        })
+    }
+
+    /// Returns renderer used by the backend.
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+
+    /// Returns mutable reference to the renderer used by the backend.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// Records draws of a frame in the render pass of its swapchain image, which is left open.
+    fn add_draw_commands(&mut self, mut command_buffer: AutoCommandBufferBuilder, image_num: usize, camera: &Camera, lights: Vec<Light>,
+                         draws: &[DrawCommand]) -> Result<AutoCommandBufferBuilder, RenderError> {
+        let renderer = &mut self.renderer;
+        command_buffer = command_buffer.begin_render_pass(renderer.framebuffers[image_num].clone(), false, clear_values())?;
+        if draws.is_empty() {
+            return Ok(command_buffer);
+        }
+
+        let mut scene = Scene::new("backend frame", camera.clone());
+        for light in lights {
+            scene.add_light(light);
+        }
+        let window_dimensions = get_window_dimensions(renderer.surface.window());
+        renderer.uniform_manager.set_light_data(scene.light_data());
+        for draw in draws {
+            let mut transformation_data = camera.as_uniform_data(window_dimensions.width as f32, window_dimensions.height as f32);
+            transformation_data.model = draw.model.into();
+            let mut material = Material::default();
+            let [r, g, b, a] = draw.color;
+            material.set_base_color(r, g, b, a);
+            renderer.uniform_manager.push_object(transformation_data, material.shader_data());
+        }
+
+        let pipeline = renderer.pipelines[&PipelineKey::default()].clone();
+        let (uniform_frame, uniform_set) = renderer.upload_uniforms(pipeline.clone())?;
+        let push_constants = PushConstants {
+            uniform_scale: 0,
+            selected: 0,
+            depth_bias: 0.0,
+        };
+        for (object_index, draw) in draws.iter().enumerate() {
+            let mesh = &self.meshes[draw.mesh.0];
+            let texture_set = renderer.texture_set(pipeline.clone(), self.textures[draw.texture.0].clone(), None)?;
+            let (vertex_buffer, index_buffer) = mesh.gpu_buffers()?;
+            command_buffer = command_buffer.draw_indexed(
+                pipeline.clone(),
+                &DynamicState::none(),
+                vec!(vertex_buffer.buffer(), uniform_frame.instances(object_index..object_index + 1)),
+                index_buffer,
+                (uniform_set.clone(), texture_set),
+                push_constants,
+            )?;
+            renderer.stats.add_triangles(mesh.topology(), mesh.indices().len());
+            renderer.stats.objects_rendered += 1;
+            renderer.stats.draw_calls += 1;
+        }
+        Ok(command_buffer)
+    }
+}
+
+impl RenderBackend for VulkanBackend {
+    type Error = RenderError;
+
+    fn name(&self) -> String {
+        format!("Vulkan ({})", self.renderer.capabilities().device_name)
+    }
+
+    fn upload_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<MeshHandle, RenderError> {
+        let name = format!("backend mesh {}", self.meshes.len());
+        let mesh = Mesh::upload(name, vertices.to_vec(), indices.to_vec(), self.textures[0].clone(), &self.uploader)?;
+        self.meshes.push(mesh);
+        Ok(MeshHandle(self.meshes.len() - 1))
+    }
+
+    fn upload_texture(&mut self, image: &RgbaImage) -> Result<TextureHandle, RenderError> {
+        let name = format!("backend texture {}", self.textures.len());
+        let texture = Texture::upload(name, DynamicImage::ImageRgba8(image.clone()), &self.uploader, self.renderer.device(), SamplerQuality::default())?;
+        self.textures.push(Arc::new(texture));
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    fn begin_frame(&mut self, camera: &Camera, lights: &[Light]) -> Result<(), RenderError> {
+        let command_buffer = self.renderer.create_command_buffer()?;
+        self.renderer.stats.reset_frame_counters();
+        let (image_num, acquire_future) = self.renderer.acquire_image()?;
+        self.frame = Some(BackendFrame {
+            image_num,
+            acquire_future,
+            command_buffer,
+            camera: camera.clone(),
+            lights: lights.to_vec(),
+            draws: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn draw(&mut self, command: DrawCommand) {
+        match &mut self.frame {
+            Some(frame) => frame.draws.push(command),
+            None => warn!("Draw added outside of a frame was ignored"),
+        }
+    }
+
+    fn end_frame(&mut self) -> Result<(), RenderError> {
+        let BackendFrame { image_num, acquire_future, command_buffer, camera, lights, draws } = match self.frame.take() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        let command_buffer = self.add_draw_commands(command_buffer, image_num, &camera, lights, &draws)?;
+        // frame waits on semaphores signaled by uploads of the backend assets
+        self.renderer.pending_semaphores.extend(self.uploader.take_pending_uploads());
+        self.renderer.execute_command_buffer(image_num, acquire_future, command_buffer)
+    }
+}
@@ -0,0 +1,652 @@
+use std::sync::mpsc;
+#[cfg(feature = "window")]
+use std::sync::Arc;
+
+use image::{DynamicImage, RgbaImage};
+use nalgebra_glm::Mat4;
+#[cfg(feature = "window")]
+use wgpu::rwh;
+use wgpu::util::DeviceExt;
+#[cfg(feature = "window")]
+use winit::Window;
+use log::*;
+
+use crate::renderer::backend::{DrawCommand, MeshHandle, RenderBackend, TextureHandle};
+use crate::renderer::wgpu_backend::wgpu_backend_error::WgpuBackendError;
+use crate::resource::camera::Camera;
+use crate::resource::light::Light;
+use crate::resource::mesh::Vertex;
+
+pub mod wgpu_backend_error;
+
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// Distance between uniforms of consecutive draws in the draw uniform buffer, which is the required alignment of dynamic offsets.
+const DRAW_UNIFORM_STRIDE: u64 = 256;
+
+/// Uniforms shared by all draws of a frame.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FrameUniforms {
+    view_proj: [[f32; 4]; 4],
+    light_position: [f32; 4],
+    light_color: [f32; 4],
+}
+
+/// Uniforms of a single draw.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DrawUniforms {
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+}
+
+struct GpuMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+struct GpuTexture {
+    // texture is kept alive as long as its bind group
+    _texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Frame started by `begin_frame`, which collects draws until it ends.
+struct WgpuFrame {
+    uniforms: FrameUniforms,
+    draws: Vec<DrawCommand>,
+}
+
+/// Image frames are rendered to.
+enum FrameTarget {
+    /// Offscreen image, which can be read with `read_frame`.
+    Offscreen(wgpu::Texture),
+    /// Surface of a window or of a canvas of a web page, which frames are presented to.
+    Window {
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+    },
+}
+
+/// Render backend drawing with wgpu, which runs on Vulkan, Metal, DirectX 12 and OpenGL.
+/// Frames are presented to a window given to `with_window` or a canvas given to `with_canvas`, or rendered to an offscreen image,
+/// which can be read with `read_frame`.
+pub struct WgpuBackend {
+    adapter_info: wgpu::AdapterInfo,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::RenderPipeline,
+    uniform_layout: wgpu::BindGroupLayout,
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    target: FrameTarget,
+    depth_target: wgpu::Texture,
+    dimensions: [u32; 2],
+    meshes: Vec<GpuMesh>,
+    textures: Vec<GpuTexture>,
+    frame: Option<WgpuFrame>,
+    // surface of the frame target is created from raw handles of the window, so the window is dropped after it
+    #[cfg(feature = "window")]
+    _window: Option<Arc<Window>>,
+}
+
+impl WgpuBackend {
+    /// Creates backend rendering frames of given size with the best available adapter, blocking until its device is created.
+    pub fn new(width: u32, height: u32) -> Result<Self, WgpuBackendError> {
+        pollster::block_on(WgpuBackend::request(width, height))
+    }
+
+    /// Creates backend like `new` without blocking, for platforms where the adapter and device can only be awaited, like browsers with WebGPU.
+    pub async fn request(width: u32, height: u32) -> Result<Self, WgpuBackendError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        WgpuBackend::create(instance, None, width, height).await
+    }
+
+    /// Creates backend presenting frames to given window, blocking until its device is created.
+    /// Frames have the inner size of the window until the backend is resized.
+    #[cfg(feature = "window")]
+    pub fn with_window(window: Arc<Window>) -> Result<Self, WgpuBackendError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let size = window.get_inner_size()
+                         .map(|size| size.to_physical(window.get_hidpi_factor()))
+                         .ok_or(WgpuBackendError::UnsupportedWindowError)?;
+        // handles stay valid as long as the window, which the backend keeps alive
+        let surface = unsafe { instance.create_surface_unsafe(surface_target(&window)?)? };
+        let mut backend = pollster::block_on(WgpuBackend::create(instance, Some(surface), size.width as u32, size.height as u32))?;
+        backend._window = Some(window);
+        Ok(backend)
+    }
+
+    /// Creates backend presenting frames to given canvas of a web page, without blocking like `request`.
+    /// Frames have the size of the canvas until the backend is resized.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn with_canvas(canvas: web_sys::HtmlCanvasElement) -> Result<Self, WgpuBackendError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let (width, height) = (canvas.width(), canvas.height());
+        let surface = instance.create_surface(wgpu::SurfaceTarget::Canvas(canvas))?;
+        WgpuBackend::create(instance, Some(surface), width, height).await
+    }
+
+    async fn create(instance: wgpu::Instance, surface: Option<wgpu::Surface<'static>>, width: u32, height: u32) -> Result<Self, WgpuBackendError> {
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: surface.as_ref(),
+            force_fallback_adapter: false,
+        }).await.ok_or(WgpuBackendError::NoAdapterError)?;
+        let adapter_info = adapter.get_info();
+        info!("Using wgpu adapter: {} ({:?})", adapter_info.name, adapter_info.backend);
+
+        let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("ketch device"),
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::downlevel_defaults(),
+        }, None).await?;
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("uniform layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<DrawUniforms>() as u64),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("texture layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let target = match surface {
+            Some(surface) => {
+                let mut config = surface.get_default_config(&adapter, width.max(1), height.max(1))
+                                        .ok_or(WgpuBackendError::UnsupportedSurfaceError)?;
+                // textures are sampled as sRGB, so frames are written to sRGB surfaces like in the Vulkan renderer
+                if let Some(format) = surface.get_capabilities(&adapter).formats.into_iter().find(wgpu::TextureFormat::is_srgb) {
+                    config.format = format;
+                }
+                surface.configure(&device, &config);
+                FrameTarget::Window { surface, config }
+            },
+            None => FrameTarget::Offscreen(create_target(&device, COLOR_FORMAT, width, height)),
+        };
+        let color_format = match &target {
+            FrameTarget::Offscreen(_) => COLOR_FORMAT,
+            FrameTarget::Window { config, .. } => config.format,
+        };
+        let pipeline = create_pipeline(&device, &uniform_layout, &texture_layout, color_format);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("texture sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let depth_target = create_target(&device, DEPTH_FORMAT, width, height);
+
+        let mut backend = WgpuBackend {
+            adapter_info,
+            device,
+            queue,
+            pipeline,
+            uniform_layout,
+            texture_layout,
+            sampler,
+            target,
+            depth_target,
+            dimensions: [width, height],
+            meshes: Vec::new(),
+            textures: Vec::new(),
+            frame: None,
+            #[cfg(feature = "window")]
+            _window: None,
+        };
+        // first texture is white, so that meshes without textures can be drawn with their color only
+        backend.upload_texture(&RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255])))?;
+        Ok(backend)
+    }
+
+    /// Returns width and height of rendered frames.
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.dimensions
+    }
+
+    /// Changes size of rendered frames, e.g. after the window is resized. Frames of minimized windows, whose size is zero, keep their size.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || self.dimensions == [width, height] {
+            return;
+        }
+        self.dimensions = [width, height];
+        self.depth_target = create_target(&self.device, DEPTH_FORMAT, width, height);
+        match &mut self.target {
+            FrameTarget::Offscreen(color_target) => *color_target = create_target(&self.device, COLOR_FORMAT, width, height),
+            FrameTarget::Window { surface, config, .. } => {
+                config.width = width;
+                config.height = height;
+                surface.configure(&self.device, config);
+            },
+        }
+    }
+
+    /// Waits until the GPU finishes all submitted frames.
+    pub fn wait_idle(&self) {
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    /// Copies the last rendered frame to an image. Blocks until the GPU finishes rendering it.
+    /// Frames presented to a window can't be read.
+    pub fn read_frame(&self) -> Result<RgbaImage, WgpuBackendError> {
+        let color_target = match &self.target {
+            FrameTarget::Offscreen(color_target) => color_target,
+            FrameTarget::Window { .. } => return Err(WgpuBackendError::UnreadableFrameError),
+        };
+        let [width, height] = self.dimensions;
+        let row_size = 4 * width;
+        // rows of copied images have to be aligned, padding is removed after reading
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_size = (row_size + alignment - 1) / alignment * alignment;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("frame readback"),
+            size: u64::from(padded_row_size * height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("frame readback") });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: color_target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().expect("Frame readback wasn't mapped")?;
+
+        let pixels: Vec<u8> = slice.get_mapped_range()
+                                   .chunks(padded_row_size as usize)
+                                   .flat_map(|row| row[..row_size as usize].iter().cloned())
+                                   .collect();
+        buffer.unmap();
+        Ok(RgbaImage::from_raw(width, height, pixels).expect("Frame readback has wrong size"))
+    }
+}
+
+impl RenderBackend for WgpuBackend {
+    type Error = WgpuBackendError;
+
+    fn name(&self) -> String {
+        format!("wgpu {:?} ({})", self.adapter_info.backend, self.adapter_info.name)
+    }
+
+    fn upload_mesh(&mut self, vertices: &[Vertex], indices: &[u32]) -> Result<MeshHandle, WgpuBackendError> {
+        let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mesh vertices"),
+            contents: as_bytes(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mesh indices"),
+            contents: as_bytes(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        self.meshes.push(GpuMesh {
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+        });
+        Ok(MeshHandle(self.meshes.len() - 1))
+    }
+
+    fn upload_texture(&mut self, image: &RgbaImage) -> Result<TextureHandle, WgpuBackendError> {
+        // images are flipped the same way as textures of the Vulkan renderer, so that meshes use the same texture coordinates
+        let image = DynamicImage::ImageRgba8(image.clone()).flipv().to_rgba();
+        let (width, height) = image.dimensions();
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &image.into_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("texture bind group"),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+            ],
+        });
+        self.textures.push(GpuTexture {
+            _texture: texture,
+            bind_group,
+        });
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    fn begin_frame(&mut self, camera: &Camera, lights: &[Light]) -> Result<(), WgpuBackendError> {
+        let [width, height] = self.dimensions;
+        // projection of the camera flips y axis for Vulkan, whose y axis points down, unlike the one of wgpu
+        let flip_y = Mat4::new(1.0, 0.0, 0.0, 0.0,
+                               0.0,-1.0, 0.0, 0.0,
+                               0.0, 0.0, 1.0, 0.0,
+                               0.0, 0.0, 0.0, 1.0);
+        let view_proj = flip_y * camera.projection_matrix(width as f32, height as f32) * camera.view_matrix();
+        let (light_position, light_color) = match lights.first() {
+            Some(light) => {
+                let ((x, y, z), (r, g, b)) = (light.position(), light.color());
+                ([x, y, z, 1.0], [r, g, b, light.intensity()])
+            },
+            None => ([0.0; 4], [0.0; 4]),
+        };
+        self.frame = Some(WgpuFrame {
+            uniforms: FrameUniforms {
+                view_proj: view_proj.into(),
+                light_position,
+                light_color,
+            },
+            draws: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn draw(&mut self, command: DrawCommand) {
+        match &mut self.frame {
+            Some(frame) => frame.draws.push(command),
+            None => warn!("Draw added outside of a frame was ignored"),
+        }
+    }
+
+    fn end_frame(&mut self) -> Result<(), WgpuBackendError> {
+        let frame = match self.frame.take() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        let frame_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("frame uniforms"),
+            contents: as_bytes(&[frame.uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let mut draw_data = vec![0u8; frame.draws.len().max(1) * DRAW_UNIFORM_STRIDE as usize];
+        for (draw_index, draw) in frame.draws.iter().enumerate() {
+            let uniforms = DrawUniforms {
+                model: draw.model.into(),
+                color: draw.color,
+            };
+            let offset = draw_index * DRAW_UNIFORM_STRIDE as usize;
+            draw_data[offset..offset + std::mem::size_of::<DrawUniforms>()].copy_from_slice(as_bytes(&[uniforms]));
+        }
+        let draw_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("draw uniforms"),
+            contents: &draw_data,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let uniform_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("uniform bind group"),
+            layout: &self.uniform_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: frame_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &draw_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<DrawUniforms>() as u64),
+                    }),
+                },
+            ],
+        });
+
+        let surface_texture = match &self.target {
+            FrameTarget::Offscreen(_) => None,
+            FrameTarget::Window { surface, config, .. } => match surface.get_current_texture() {
+                Ok(surface_texture) => Some(surface_texture),
+                // surface no longer matches the window, e.g. while it's resized, so the frame is skipped
+                Err(wgpu::SurfaceError::Lost) | Err(wgpu::SurfaceError::Outdated) => {
+                    surface.configure(&self.device, config);
+                    return Ok(());
+                },
+                Err(err) => return Err(err.into()),
+            },
+        };
+        let color_view = match (&self.target, &surface_texture) {
+            (_, Some(surface_texture)) => surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            (FrameTarget::Offscreen(color_target), None) => color_target.create_view(&wgpu::TextureViewDescriptor::default()),
+            (FrameTarget::Window { .. }, None) => unreachable!("Window frames are rendered to surface textures"),
+        };
+        let depth_view = self.depth_target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("frame") });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("frame"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            for (draw_index, draw) in frame.draws.iter().enumerate() {
+                let mesh = &self.meshes[draw.mesh.0];
+                if mesh.index_count == 0 {
+                    continue;
+                }
+                render_pass.set_bind_group(0, &uniform_bind_group, &[(draw_index as u64 * DRAW_UNIFORM_STRIDE) as u32]);
+                render_pass.set_bind_group(1, &self.textures[draw.texture.0].bind_group, &[]);
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+        }
+        self.queue.submit(Some(encoder.finish()));
+        if let Some(surface_texture) = surface_texture {
+            surface_texture.present();
+        }
+        Ok(())
+    }
+}
+
+/// Creates pipeline drawing opaque textured meshes to images of given format.
+fn create_pipeline(device: &wgpu::Device, uniform_layout: &wgpu::BindGroupLayout, texture_layout: &wgpu::BindGroupLayout,
+                   color_format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("wgpu shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("../../data/shader/wgpu_shader.wgsl").into()),
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("pipeline layout"),
+        bind_group_layouts: &[uniform_layout, texture_layout],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("mesh pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2],
+            }],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: color_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+/// Creates image frames are rendered to, which can be copied to buffers.
+fn create_target(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("frame target"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Returns raw handles of a window, which windows of winit 0.18 only expose through platform extensions.
+#[cfg(all(feature = "window", any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+fn surface_target(window: &Window) -> Result<wgpu::SurfaceTargetUnsafe, WgpuBackendError> {
+    use std::ptr::NonNull;
+    use winit::os::unix::WindowExt;
+
+    if let (Some(display), Some(xlib_window)) = (window.get_xlib_display(), window.get_xlib_window()) {
+        return Ok(wgpu::SurfaceTargetUnsafe::RawHandle {
+            raw_display_handle: rwh::XlibDisplayHandle::new(NonNull::new(display), window.get_xlib_screen_id().unwrap_or(0)).into(),
+            raw_window_handle: rwh::XlibWindowHandle::new(xlib_window).into(),
+        });
+    }
+    match (window.get_wayland_display().and_then(NonNull::new), window.get_wayland_surface().and_then(NonNull::new)) {
+        (Some(display), Some(surface)) => Ok(wgpu::SurfaceTargetUnsafe::RawHandle {
+            raw_display_handle: rwh::WaylandDisplayHandle::new(display).into(),
+            raw_window_handle: rwh::WaylandWindowHandle::new(surface).into(),
+        }),
+        _ => Err(WgpuBackendError::UnsupportedWindowError),
+    }
+}
+
+/// Returns raw handles of a window, which windows of winit 0.18 only expose through platform extensions.
+#[cfg(all(feature = "window", target_os = "windows"))]
+fn surface_target(window: &Window) -> Result<wgpu::SurfaceTargetUnsafe, WgpuBackendError> {
+    use winit::os::windows::WindowExt;
+
+    let hwnd = std::num::NonZeroIsize::new(window.get_hwnd() as isize).ok_or(WgpuBackendError::UnsupportedWindowError)?;
+    Ok(wgpu::SurfaceTargetUnsafe::RawHandle {
+        raw_display_handle: rwh::WindowsDisplayHandle::new().into(),
+        raw_window_handle: rwh::Win32WindowHandle::new(hwnd).into(),
+    })
+}
+
+/// Returns raw handles of a window, which windows of winit 0.18 only expose through platform extensions.
+#[cfg(all(feature = "window", target_os = "macos"))]
+fn surface_target(window: &Window) -> Result<wgpu::SurfaceTargetUnsafe, WgpuBackendError> {
+    use std::ptr::NonNull;
+    use winit::os::macos::WindowExt;
+
+    let view = NonNull::new(window.get_nsview()).ok_or(WgpuBackendError::UnsupportedWindowError)?;
+    Ok(wgpu::SurfaceTargetUnsafe::RawHandle {
+        raw_display_handle: rwh::AppKitDisplayHandle::new().into(),
+        raw_window_handle: rwh::AppKitWindowHandle::new(view).into(),
+    })
+}
+
+/// Windows of other platforms can't be presented to.
+#[cfg(all(feature = "window", not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd",
+                                     target_os = "windows", target_os = "macos"))))]
+fn surface_target(_window: &Window) -> Result<wgpu::SurfaceTargetUnsafe, WgpuBackendError> {
+    Err(WgpuBackendError::UnsupportedWindowError)
+}
+
+/// Returns memory of plain values as bytes, which is what buffers are filled with.
+fn as_bytes<T: Copy>(data: &[T]) -> &[u8] {
+    // values are #[repr(C)] structs and numbers, which have no padding read by shaders
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * std::mem::size_of::<T>()) }
+}
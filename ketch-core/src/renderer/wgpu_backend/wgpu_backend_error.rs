@@ -0,0 +1,41 @@
+use std::error::Error;
+
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum WgpuBackendError {
+        NoAdapterError {
+            display("NoAdapterError: couldn't find usable graphics adapter")
+        }
+        UnsupportedWindowError {
+            display("UnsupportedWindowError: window doesn't expose handles wgpu can present to")
+        }
+        UnsupportedSurfaceError {
+            display("UnsupportedSurfaceError: adapter can't present to the window")
+        }
+        UnreadableFrameError {
+            display("UnreadableFrameError: frames presented to a window can't be read")
+        }
+        CreateSurfaceError(err: wgpu::CreateSurfaceError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SurfaceError(err: wgpu::SurfaceError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        RequestDeviceError(err: wgpu::RequestDeviceError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        BufferAsyncError(err: wgpu::BufferAsyncError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
@@ -1,58 +1,239 @@
-use image::DynamicImage;
+use image::{DynamicImage, GenericImageView, RgbaImage};
 use std::sync::RwLock;
 use std::path::Path;
+use std::time::Duration;
+use nalgebra_glm::Vec3;
+#[cfg(feature = "vulkan")]
 use vulkano::device::Device;
+#[cfg(feature = "vulkan")]
+use crate::resource::texture::SamplerQuality;
 use crate::resource::texture::Texture;
 use std::sync::Arc;
 use crate::resource::mesh::Vertex;
+use crate::resource::mesh::vertex_format::{LightmappedVertex, MeshVertex};
+#[cfg(feature = "vulkan")]
 use crate::renderer::queues::Queues;
+use crate::profile_scope;
+use crate::renderer::upload::AssetUploader;
+use crate::renderer::renderer_error::RenderError;
+use crate::resource::asset_error::AssetError;
+use crate::resource::asset_pack::AssetPack;
 use std::collections::HashMap;
-use crate::settings::Settings;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::settings::TextureQuality;
 use crate::resource::mesh::Mesh;
+use crate::resource::model::ModelData;
+use crate::resource::model::model_error::ModelError;
 use crate::resource::scene::Scene;
+use crate::event_bus::{GameEvent, SceneEvent};
+use crate::resource::scene::scene_data::SceneData;
+use crate::resource::scene::scene_error::SceneSerializationError;
+use crate::resource::primitives::Primitive;
+use crate::resource::material::Material;
+use crate::resource::material::material_error::MaterialError;
+use crate::resource::particle_effect::ParticleEffect;
+use crate::resource::instanced_mesh::InstancedMesh;
+use crate::resource::streaming::{StreamedAsset, StreamRequest, StreamingQueue};
+use crate::resource::texture_streaming::TextureStreamer;
+use crate::math::frustum::Frustum;
+use crate::resource::particle_effect::particle_effect_error::ParticleEffectError;
+use crate::resource::terrain::Terrain;
+use crate::resource::terrain::terrain_error::TerrainError;
+use crate::resource::sound::Sound;
+use crate::resource::video::Video;
+use crate::resource::font::Font;
+use crate::resource::string_table::StringTable;
+use crate::resource::string_table::string_table_error::StringTableError;
+use crate::localization::Localization;
+use crate::random::RandomService;
+use crate::video::VideoPlayer;
+use crate::resource::world::{CellChange, World};
+use log::*;
 
+pub mod asset_error;
+pub mod asset_pack;
 pub mod mesh;
+pub mod model;
 pub mod camera;
+pub mod collider;
+pub mod fetch;
+pub mod font;
+pub mod instanced_mesh;
 pub mod scene;
 pub mod object;
+pub mod light;
+pub mod lightmap;
+pub mod material;
+pub mod particle_effect;
+pub mod primitives;
+pub mod render_layers;
+pub mod render_queue;
+pub mod rigid_body;
+pub mod sound;
+pub mod streaming;
+pub mod string_table;
+pub mod terrain;
 pub mod texture;
+pub mod texture_streaming;
+pub mod video;
+pub mod world;
 
-const DEFAULT_TEXTURE_NAME: &'static str = "default";
+/// Name of the texture used by meshes which weren't given one.
+pub const DEFAULT_TEXTURE_NAME: &str = "default";
 
 /// Manages game assets and scenes.
 pub struct AssetManager {
     active_scene: Option<Scene>,
 
     scenes: HashMap<String, Scene>,
+    // scene activation changes and object events of scenes which stopped being active, in order they happened
+    scene_events: Vec<GameEvent>,
     meshes: HashMap<String, Arc<RwLock<Mesh>>>,
     textures: HashMap<String, Arc<Texture>>,
+    materials: HashMap<String, Arc<RwLock<Material>>>,
+    particle_effects: HashMap<String, Arc<RwLock<ParticleEffect>>>,
+    instanced_meshes: HashMap<String, Arc<RwLock<InstancedMesh>>>,
+    streaming_queue: StreamingQueue,
+    texture_streamer: TextureStreamer,
+    world: World,
+    terrains: HashMap<String, Arc<RwLock<Terrain>>>,
+    sounds: HashMap<String, Arc<Sound>>,
+    videos: HashMap<String, Arc<Video>>,
+    fonts: HashMap<String, Arc<Font>>,
+    localization: Localization,
+    random: RandomService,
+    video_player: VideoPlayer,
 
-    device: Arc<Device>,
-    queues: Queues,
+    // None if meshes and textures are kept only on the CPU, for render backends other than the Vulkan renderer
+    uploader: Option<AssetUploader>,
+    texture_quality: TextureQuality,
 }
 
 impl AssetManager {
-    /// Creates new asset manager.
-    pub fn new(queues: Queues, device: Arc<Device>) -> Self {
-        let image = image::load_from_memory(include_bytes!("../data/default.png")).unwrap();
-        let default_texture = Arc::new(Texture::new(DEFAULT_TEXTURE_NAME, image, queues.graphics_queue(), device.clone()));
+    /// Creates new asset manager, which uploads meshes and textures to the device of the Vulkan renderer.
+    #[cfg(feature = "vulkan")]
+    pub fn new(queues: Queues, device: Arc<Device>) -> Result<Self, AssetError> {
+        let image = image::load_from_memory(include_bytes!("../data/default.png"))?;
+        let uploader = AssetUploader::new(device.clone(), queues);
+        let default_texture = Texture::upload(DEFAULT_TEXTURE_NAME, image, &uploader, device, SamplerQuality::default())?;
+        Ok(AssetManager::with_uploader(default_texture, Some(uploader)))
+    }
+
+    /// Creates asset manager keeping meshes and textures only on the CPU, used with render backends which upload them themselves
+    /// and when nothing is rendered. It doesn't need a GPU.
+    pub fn without_device() -> Result<Self, AssetError> {
+        let image = image::load_from_memory(include_bytes!("../data/default.png"))?;
+        Ok(AssetManager::with_uploader(Texture::from_image(DEFAULT_TEXTURE_NAME, image), None))
+    }
+
+    fn with_uploader(default_texture: Texture, uploader: Option<AssetUploader>) -> Self {
+        let default_texture = Arc::new(default_texture);
         let mut textures = HashMap::new();
         textures.insert(DEFAULT_TEXTURE_NAME.to_string(), default_texture);
         AssetManager {
             active_scene: None,
             scenes: HashMap::new(),
+            scene_events: Vec::new(),
             meshes: HashMap::new(),
             textures,
-            queues,
-            device,
+            materials: HashMap::new(),
+            particle_effects: HashMap::new(),
+            instanced_meshes: HashMap::new(),
+            streaming_queue: StreamingQueue::default(),
+            texture_streamer: TextureStreamer::default(),
+            world: World::default(),
+            terrains: HashMap::new(),
+            sounds: HashMap::new(),
+            videos: HashMap::new(),
+            fonts: HashMap::new(),
+            localization: Localization::new(),
+            random: RandomService::new(0),
+            video_player: VideoPlayer::new(),
+            uploader,
+            texture_quality: TextureQuality::High,
         }
     }
 
     /// Creates a new mesh.
-    pub fn create_mesh<S: Into<String>>(&self, name: S, vertices: Vec<Vertex>, indices: Vec<u32>) -> Arc<RwLock<Mesh>> {
-        Arc::new(RwLock::new(Mesh::new(name, vertices, indices, self.textures.get(DEFAULT_TEXTURE_NAME).unwrap().clone(), self.queues.graphics_queue())))
+    pub fn create_mesh<S: Into<String>>(&self, name: S, vertices: Vec<Vertex>, indices: Vec<u32>) -> Result<Arc<RwLock<Mesh>>, RenderError> {
+        profile_scope!("create mesh");
+        let mesh = Mesh::create(name, &vertices[..], indices, self.textures[DEFAULT_TEXTURE_NAME].clone(), self.uploader.as_ref())?;
+        Ok(Arc::new(RwLock::new(mesh)))
+    }
+
+    /// Creates mesh with vertices of any vertex format, e.g. `LightmappedVertex` for static geometry with baked lighting.
+    pub fn create_mesh_with_format<S: Into<String>, V: MeshVertex>(&self, name: S, vertices: &[V], indices: Vec<u32>) -> Result<Arc<RwLock<Mesh>>, RenderError> {
+        profile_scope!("create mesh");
+        let mesh = Mesh::create(name, vertices, indices, self.textures[DEFAULT_TEXTURE_NAME].clone(), self.uploader.as_ref())?;
+        Ok(Arc::new(RwLock::new(mesh)))
+    }
+
+    /// Loads OBJ, glTF 2.0 or binary model and adds its meshes and textures to asset manager. A model with a single mesh adds it with given name,
+    /// otherwise meshes are named with their index, e.g. "ship/0". Textures are named like "ship/texture0", and set as textures of meshes using them.
+    /// Meshes with a second set of texture coordinates use them as lightmap coordinates.
+    pub fn load_model<S: Into<String>, P: AsRef<Path>>(&mut self, name: S, path: P) -> Result<Vec<Arc<RwLock<Mesh>>>, ModelError> {
+        profile_scope!("load model");
+        let model = ModelData::load(path)?;
+        self.add_model(name, model)
+    }
+
+    /// Uploads meshes and textures of a loaded model and adds them to asset manager, named like by `load_model`.
+    pub fn add_model<S: Into<String>>(&mut self, name: S, model: ModelData) -> Result<Vec<Arc<RwLock<Mesh>>>, ModelError> {
+        let name = name.into();
+        let mesh_names = model.mesh_names(&name);
+        let mut textures = Vec::new();
+        for (texture_name, image) in model.texture_names(&name).into_iter().zip(model.textures) {
+            let texture = self.create_texture(texture_name, image)?;
+            self.add_texture(texture.clone());
+            textures.push(texture);
+        }
+
+        let mut meshes = Vec::new();
+        for (mesh_name, model_mesh) in mesh_names.into_iter().zip(model.meshes) {
+            let mesh = match model_mesh.lightmap_coords {
+                Some(lightmap_coords) => {
+                    let vertices: Vec<LightmappedVertex> = model_mesh.vertices.iter().zip(lightmap_coords).map(|(vertex, lightmap_coord)| LightmappedVertex {
+                        position: vertex.position,
+                        normal: vertex.normal,
+                        tex_coord: vertex.tex_coord,
+                        lightmap_coord,
+                    }).collect();
+                    self.create_mesh_with_format(mesh_name, &vertices, model_mesh.indices)?
+                },
+                None => self.create_mesh(mesh_name, model_mesh.vertices, model_mesh.indices)?,
+            };
+            if let Some(texture) = model_mesh.texture.and_then(|index| textures.get(index)) {
+                mesh.write().unwrap().set_texture(texture.clone());
+            }
+            self.add_mesh(mesh.clone());
+            meshes.push(mesh);
+        }
+        Ok(meshes)
+    }
+
+    /// Adds textures, binary textures and binary models of an asset pack to asset manager, named by their paths in the pack without the extension, e.g. "textures/stone".
+    /// Other files of the pack, like scenes and materials, are left to their own loaders.
+    pub fn load_pack(&mut self, pack: &AssetPack) -> Result<(), ModelError> {
+        profile_scope!("load asset pack");
+        for name in pack.names() {
+            let bytes = pack.file(name).unwrap_or_default();
+            let extension = Path::new(name).extension().and_then(|extension| extension.to_str()).unwrap_or("").to_lowercase();
+            match extension.as_str() {
+                "png" | "jpg" | "jpeg" | "bmp" | "tga" => {
+                    let image = image::load_from_memory(bytes)?;
+                    let texture = self.create_texture(asset_pack::asset_name(name), image)?;
+                    self.add_texture(texture);
+                },
+                texture::BINARY_TEXTURE_EXTENSION => {
+                    let texture = self.create_texture(asset_pack::asset_name(name), texture::image_from_binary(bytes)?)?;
+                    self.add_texture(texture);
+                },
+                model::BINARY_MODEL_EXTENSION => {
+                    self.add_model(asset_pack::asset_name(name), ModelData::from_binary(bytes)?)?;
+                },
+                _ => (),
+            }
+        }
+        Ok(())
     }
 
     /// Adds mesh to asset manager. Meshes need to have unique name. 
@@ -75,14 +256,203 @@ impl AssetManager {
         self.meshes.remove(name)
     }
 
-    /// Loads and creates texture from file.
-    pub fn load_texture<S: Into<String>, P: AsRef<Path>>(&self, name: S, image_path: P) -> Arc<Texture> {
-        Arc::new(Texture::load(name, image_path, self.queues.graphics_queue(), self.device.clone()))
+    /// Returns names of all meshes added to asset manager, sorted alphabetically.
+    pub fn mesh_names(&self) -> Vec<String> {
+        let mut mesh_names: Vec<String> = self.meshes.keys().cloned().collect();
+        mesh_names.sort();
+        mesh_names
+    }
+
+    /// Returns number of meshes added to asset manager.
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// Returns number of textures added to asset manager, including the default texture.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Returns estimated size of GPU memory used by meshes and textures added to asset manager, in bytes.
+    pub fn estimated_memory_usage(&self) -> u64 {
+        let meshes_size: u64 = self.meshes.values().map(|mesh| mesh.read().unwrap().memory_size()).sum();
+        let textures_size: u64 = self.textures.values().map(|texture| texture.memory_size()).sum();
+        meshes_size + textures_size
+    }
+
+    /// Returns mesh of given primitive. Mesh is generated and added to asset manager when it's used for the first time.
+    pub fn primitive_mesh(&mut self, primitive: Primitive) -> Result<Arc<RwLock<Mesh>>, RenderError> {
+        match self.mesh(primitive.mesh_name()) {
+            Some(mesh) => Ok(mesh),
+            None => {
+                let mesh = self.create_primitive_mesh(primitive)?;
+                self.add_mesh(mesh.clone());
+                Ok(mesh)
+            },
+        }
+    }
+
+    fn create_primitive_mesh(&self, primitive: Primitive) -> Result<Arc<RwLock<Mesh>>, RenderError> {
+        let (vertices, indices) = primitive.generate();
+        self.create_mesh(primitive.mesh_name(), vertices, indices)
+    }
+
+    /// Loads and creates texture from an image file or a texture in the binary texture format.
+    pub fn load_texture<S: Into<String>, P: AsRef<Path>>(&self, name: S, image_path: P) -> Result<Arc<Texture>, AssetError> {
+        profile_scope!("load texture");
+        let loaded_image = texture::open_image(image_path)?;
+
+        Ok(self.create_texture(name, loaded_image)?)
+    }
+
+    /// Fetches image or texture in the binary texture format from given URL, relative to the page, and creates texture from it.
+    /// Used instead of `load_texture` in browsers, where there's no filesystem.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn fetch_texture<S: Into<String>>(&self, name: S, url: &str) -> Result<Arc<Texture>, AssetError> {
+        let bytes = fetch::fetch(url).await?;
+        let binary = Path::new(url).extension().map(|extension| extension == texture::BINARY_TEXTURE_EXTENSION).unwrap_or(false);
+        let image = if binary {
+            texture::image_from_binary(&bytes)?
+        } else {
+            image::load_from_memory(&bytes)?
+        };
+        Ok(self.create_texture(name, image)?)
+    }
+
+    /// Creates texture from loaded image. Image is downscaled and sampled according to texture quality.
+    pub fn create_texture<S: Into<String>>(&self, name: S, image: DynamicImage) -> Result<Arc<Texture>, RenderError> {
+        profile_scope!("create texture");
+        let image = texture::downscale(image, self.texture_quality.downscale());
+        let texture = match &self.uploader {
+            #[cfg(feature = "vulkan")]
+            Some(uploader) => {
+                let sampler_quality = SamplerQuality::for_device(self.texture_quality, &uploader.device());
+                Texture::upload(name, image, uploader, uploader.device(), sampler_quality)?
+            },
+            #[cfg(not(feature = "vulkan"))]
+            Some(uploader) => match *uploader {},
+            None => Texture::from_image(name, image),
+        };
+        Ok(Arc::new(texture))
+    }
+
+    /// Creates texture from loaded image, whose regions can be updated with `Texture::update_region`.
+    /// Dynamic textures aren't downscaled, so that updated regions keep their coordinates.
+    pub fn create_dynamic_texture<S: Into<String>>(&self, name: S, image: DynamicImage) -> Result<Arc<Texture>, RenderError> {
+        profile_scope!("create dynamic texture");
+        let texture = match &self.uploader {
+            #[cfg(feature = "vulkan")]
+            Some(uploader) => {
+                let sampler_quality = SamplerQuality::for_device(self.texture_quality, &uploader.device());
+                Texture::upload_dynamic(name, image, uploader, uploader.device(), sampler_quality)?
+            },
+            #[cfg(not(feature = "vulkan"))]
+            Some(uploader) => match *uploader {},
+            None => Texture::from_image(name, image),
+        };
+        Ok(Arc::new(texture))
+    }
+
+    /// Creates texture streamed by distance from the camera and adds it to asset manager. Its full mip chain is generated right away,
+    /// but only mips from the lowest one down are uploaded. Sharper mips are uploaded by `update_texture_streaming`
+    /// when objects using the texture come close to the camera. Image is downscaled according to texture quality.
+    pub fn add_streamed_texture<S: Into<String>>(&mut self, name: S, image: DynamicImage) -> Result<Arc<Texture>, RenderError> {
+        let name = name.into();
+        let image = texture::downscale(image, self.texture_quality.downscale());
+        let lowest_mips = self.texture_streamer.add(name.clone(), image);
+        let texture = self.create_streamed_texture(name, lowest_mips)?;
+        self.replace_texture(texture.clone());
+        Ok(texture)
+    }
+
+    /// Creates texture from resident mips of a streamed texture, starting with the sharpest one.
+    fn create_streamed_texture(&self, name: String, mut mip_levels: Vec<RgbaImage>) -> Result<Arc<Texture>, RenderError> {
+        let texture = match &self.uploader {
+            #[cfg(feature = "vulkan")]
+            Some(uploader) => {
+                let sampler_quality = SamplerQuality::for_device(self.texture_quality, &uploader.device());
+                Texture::upload_mip_levels(name, mip_levels, uploader, uploader.device(), sampler_quality)?
+            },
+            #[cfg(not(feature = "vulkan"))]
+            Some(uploader) => match *uploader {},
+            None => Texture::from_image(name, DynamicImage::ImageRgba8(mip_levels.swap_remove(0))),
+        };
+        Ok(Arc::new(texture))
+    }
+
+    /// Returns streamer choosing mips of streamed textures.
+    pub fn texture_streamer(&self) -> &TextureStreamer {
+        &self.texture_streamer
+    }
+
+    /// Returns mutable reference to streamer choosing mips of streamed textures, used to change its memory budget.
+    pub fn texture_streamer_mut(&mut self) -> &mut TextureStreamer {
+        &mut self.texture_streamer
+    }
+
+    /// Uploads mips of streamed textures for distances from the camera of the active scene to the closest objects using them.
+    /// Mips are sharpened progressively within the upload budget of the streaming queue, and lowered when textures don't fit in memory budget.
+    pub fn update_texture_streaming(&mut self) {
+        if self.texture_streamer.is_empty() {
+            return;
+        }
+        profile_scope!("texture streaming");
+        let scene = match &self.active_scene {
+            Some(scene) => scene,
+            None => return,
+        };
+        let camera_position = scene.view_camera().position_vec3();
+        let mut distances: HashMap<String, f32> = HashMap::new();
+        for object in scene.objects() {
+            let distance = (scene.world_position(object) - camera_position).norm();
+            let mut texture_names = Vec::new();
+            if let Some(mesh) = object.mesh() {
+                texture_names.push(mesh.read().unwrap().texture().name().to_string());
+            }
+            if let Some(material) = object.material() {
+                texture_names.extend(material.read().unwrap().albedo_texture().map(str::to_string));
+            }
+            texture_names.extend(object.lightmap().map(|lightmap| lightmap.texture.clone()));
+            for name in texture_names {
+                let closest = distances.entry(name).or_insert(distance);
+                *closest = closest.min(distance);
+            }
+        }
+        for (name, mip) in self.texture_streamer.update(&distances, self.streaming_queue.budget()) {
+            let result = match self.texture_streamer.mip_levels(&name, mip) {
+                Some(mip_levels) => self.create_streamed_texture(name.clone(), mip_levels).map(|texture| self.replace_texture(texture)),
+                None => Ok(()),
+            };
+            if let Err(err) = result {
+                error!("Couldn't upload mip {} of streamed texture {}: {}", mip, name, err);
+            }
+        }
+    }
+
+    /// Adds texture, and gives it to meshes which used texture with the same name.
+    fn replace_texture(&mut self, texture: Arc<Texture>) {
+        for mesh in self.meshes.values() {
+            let mut mesh = mesh.write().unwrap();
+            if mesh.texture().name() == texture.name() {
+                mesh.set_texture(texture.clone());
+            }
+        }
+        self.add_texture(texture);
     }
 
-    /// Creates texture from loaded image.
-    pub fn create_texture<S: Into<String>>(&self, name: S, image: DynamicImage) -> Arc<Texture> {
-        Arc::new(Texture::new(name, image, self.queues.graphics_queue(), self.device.clone()))
+    /// Sets quality of textures created after the change. Textures which were already created keep their quality.
+    pub fn set_texture_quality(&mut self, texture_quality: TextureQuality) {
+        self.texture_quality = texture_quality;
+    }
+
+    /// Returns quality of created textures.
+    pub fn texture_quality(&self) -> TextureQuality {
+        self.texture_quality
+    }
+
+    /// Returns uploader copying mesh and texture data to the GPU on the transfer queue, or None if assets are kept only on the CPU.
+    pub fn uploader(&self) -> Option<&AssetUploader> {
+        self.uploader.as_ref()
     }
 
     /// Adds texture to asset manager. Textures need to have unique name. 
@@ -102,12 +472,340 @@ impl AssetManager {
     /// Removes and returns a texture with the given name.
     pub fn remove_texture(&mut self, name: &str) -> Option<Arc<Texture>> {
         if name != DEFAULT_TEXTURE_NAME {
+            self.texture_streamer.remove(name);
             self.textures.remove(name)
         } else {
             None
         }
     }
 
+    /// Returns names of all textures added to asset manager, sorted alphabetically.
+    pub fn texture_names(&self) -> Vec<String> {
+        let mut texture_names: Vec<String> = self.textures.keys().cloned().collect();
+        texture_names.sort();
+        texture_names
+    }
+
+    /// Adds material to asset manager. Materials need to have unique name.
+    /// If two materials have the same name, the old material will be replaced with the new one.
+    pub fn add_material(&mut self, material: Arc<RwLock<Material>>) {
+        let name = material.read().unwrap().name().to_string();
+        self.materials.insert(name, material);
+    }
+
+    /// Loads material from a file and adds it to asset manager.
+    pub fn load_material<P: AsRef<Path>>(&mut self, path: P) -> Result<Arc<RwLock<Material>>, MaterialError> {
+        profile_scope!("load material");
+        let material = Arc::new(RwLock::new(Material::load(path)?));
+        self.add_material(material.clone());
+        Ok(material)
+    }
+
+    /// Returns a material with the given name.
+    pub fn material(&self, name: &str) -> Option<Arc<RwLock<Material>>> {
+        self.materials.get(name).cloned()
+    }
+
+    /// Removes and returns a material with the given name.
+    pub fn remove_material(&mut self, name: &str) -> Option<Arc<RwLock<Material>>> {
+        self.materials.remove(name)
+    }
+
+    /// Returns names of all materials added to asset manager, sorted alphabetically.
+    pub fn material_names(&self) -> Vec<String> {
+        let mut material_names: Vec<String> = self.materials.keys().cloned().collect();
+        material_names.sort();
+        material_names
+    }
+
+    /// Adds instanced mesh to asset manager, which draws it with the active scene. Instanced meshes need to have unique name.
+    /// If two instanced meshes have the same name, the old one will be replaced with the new one.
+    pub fn add_instanced_mesh(&mut self, instanced_mesh: Arc<RwLock<InstancedMesh>>) {
+        let name = instanced_mesh.read().unwrap().name().to_string();
+        self.instanced_meshes.insert(name, instanced_mesh);
+    }
+
+    /// Returns an instanced mesh with the given name.
+    pub fn instanced_mesh(&self, name: &str) -> Option<Arc<RwLock<InstancedMesh>>> {
+        self.instanced_meshes.get(name).cloned()
+    }
+
+    /// Removes and returns an instanced mesh with the given name.
+    pub fn remove_instanced_mesh(&mut self, name: &str) -> Option<Arc<RwLock<InstancedMesh>>> {
+        self.instanced_meshes.remove(name)
+    }
+
+    /// Returns all instanced meshes, sorted by name so that they're drawn in the same order every frame.
+    pub fn instanced_meshes(&self) -> Vec<Arc<RwLock<InstancedMesh>>> {
+        let mut names: Vec<&String> = self.instanced_meshes.keys().collect();
+        names.sort();
+        names.into_iter().map(|name| self.instanced_meshes[name].clone()).collect()
+    }
+
+    /// Queues mesh or texture to be uploaded by `update_streaming` within the upload budget of a frame,
+    /// after which it's added to asset manager. Request for asset with the same name which is still waiting is replaced.
+    pub fn stream(&mut self, request: StreamRequest) {
+        self.streaming_queue.push(request);
+    }
+
+    /// Returns queue of assets waiting to be streamed.
+    pub fn streaming_queue(&self) -> &StreamingQueue {
+        &self.streaming_queue
+    }
+
+    /// Returns a mutable reference to queue of assets waiting to be streamed, used to change the upload budget or cancel requests.
+    pub fn streaming_queue_mut(&mut self) -> &mut StreamingQueue {
+        &mut self.streaming_queue
+    }
+
+    /// Uploads streamed assets fitting in the budget of this frame, assets inside the frustum first, and adds them to asset manager.
+    /// Called by the engine once per frame. Returns names of added assets, assets which failed to upload are logged and dropped.
+    pub fn update_streaming(&mut self, frustum: Option<&Frustum>) -> Vec<String> {
+        profile_scope!("streaming");
+        let mut added = Vec::new();
+        for request in self.streaming_queue.next_uploads(frustum) {
+            let (name, asset) = request.into_asset();
+            let result = match asset {
+                StreamedAsset::Mesh { vertices, indices } => self.create_mesh(name.clone(), vertices, indices).map(|mesh| self.add_mesh(mesh)),
+                StreamedAsset::Texture(image) => self.create_texture(name.clone(), image).map(|texture| self.add_texture(texture)),
+            };
+            match result {
+                Ok(()) => added.push(name),
+                Err(err) => error!("Couldn't upload streamed asset {}: {}", name, err),
+            }
+        }
+        added
+    }
+
+    /// Adds particle effect to asset manager. Particle effects need to have unique name.
+    /// If two particle effects have the same name, the old effect will be replaced with the new one.
+    pub fn add_particle_effect(&mut self, particle_effect: Arc<RwLock<ParticleEffect>>) {
+        let name = particle_effect.read().unwrap().name().to_string();
+        self.particle_effects.insert(name, particle_effect);
+    }
+
+    /// Loads particle effect from a file and adds it to asset manager.
+    pub fn load_particle_effect<P: AsRef<Path>>(&mut self, path: P) -> Result<Arc<RwLock<ParticleEffect>>, ParticleEffectError> {
+        profile_scope!("load particle effect");
+        let particle_effect = Arc::new(RwLock::new(ParticleEffect::load(path)?));
+        self.add_particle_effect(particle_effect.clone());
+        Ok(particle_effect)
+    }
+
+    /// Returns a particle effect with the given name.
+    pub fn particle_effect(&self, name: &str) -> Option<Arc<RwLock<ParticleEffect>>> {
+        self.particle_effects.get(name).cloned()
+    }
+
+    /// Removes and returns a particle effect with the given name.
+    pub fn remove_particle_effect(&mut self, name: &str) -> Option<Arc<RwLock<ParticleEffect>>> {
+        self.particle_effects.remove(name)
+    }
+
+    /// Returns names of all particle effects added to asset manager, sorted alphabetically.
+    pub fn particle_effect_names(&self) -> Vec<String> {
+        let mut particle_effect_names: Vec<String> = self.particle_effects.keys().cloned().collect();
+        particle_effect_names.sort();
+        particle_effect_names
+    }
+
+    /// Adds terrain to asset manager, along with its mesh, which has the same name as the terrain.
+    /// If two terrains have the same name, the old terrain will be replaced with the new one.
+    pub fn add_terrain(&mut self, terrain: Arc<RwLock<Terrain>>) -> Result<(), RenderError> {
+        let name = terrain.read().unwrap().name().to_string();
+        self.terrains.insert(name.clone(), terrain);
+        self.update_terrain_mesh(&name)
+    }
+
+    /// Loads terrain from a file and adds it to asset manager.
+    pub fn load_terrain<P: AsRef<Path>>(&mut self, path: P) -> Result<Arc<RwLock<Terrain>>, TerrainError> {
+        profile_scope!("load terrain");
+        let terrain = Arc::new(RwLock::new(Terrain::load(path)?));
+        self.add_terrain(terrain.clone())?;
+        Ok(terrain)
+    }
+
+    /// Returns a terrain with the given name.
+    pub fn terrain(&self, name: &str) -> Option<Arc<RwLock<Terrain>>> {
+        self.terrains.get(name).cloned()
+    }
+
+    /// Removes and returns a terrain with the given name. Its mesh is kept, so that objects using it can still be drawn.
+    pub fn remove_terrain(&mut self, name: &str) -> Option<Arc<RwLock<Terrain>>> {
+        self.terrains.remove(name)
+    }
+
+    /// Returns names of all terrains added to asset manager, sorted alphabetically.
+    pub fn terrain_names(&self) -> Vec<String> {
+        let mut terrain_names: Vec<String> = self.terrains.keys().cloned().collect();
+        terrain_names.sort();
+        terrain_names
+    }
+
+    /// Regenerates mesh of the terrain after its heights were changed. Objects already using the mesh show the new shape.
+    pub fn update_terrain_mesh(&mut self, name: &str) -> Result<(), RenderError> {
+        let (vertices, indices) = match self.terrains.get(name) {
+            Some(terrain) => terrain.read().unwrap().generate(),
+            None => return Ok(()),
+        };
+        let mesh = self.create_mesh(name, vertices, indices)?;
+        match self.meshes.get(name) {
+            Some(existing_mesh) => {
+                let mut existing_mesh = existing_mesh.write().unwrap();
+                let mut mesh = mesh.write().unwrap();
+                // texture assigned to the terrain mesh is kept
+                mesh.set_texture(existing_mesh.texture());
+                std::mem::swap(&mut *existing_mesh, &mut *mesh);
+            },
+            None => self.add_mesh(mesh),
+        }
+        Ok(())
+    }
+
+    /// Adds sound to asset manager. Sounds need to have unique name.
+    /// If two sounds have the same name, the old sound will be replaced with the new one.
+    pub fn add_sound(&mut self, sound: Arc<Sound>) {
+        self.sounds.insert(sound.name().to_string(), sound);
+    }
+
+    /// Loads sound from a file and adds it to asset manager.
+    pub fn load_sound<S: Into<String>, P: AsRef<Path>>(&mut self, name: S, path: P) -> std::io::Result<Arc<Sound>> {
+        profile_scope!("load sound");
+        let sound = Arc::new(Sound::load(name, path)?);
+        self.add_sound(sound.clone());
+        Ok(sound)
+    }
+
+    /// Returns a sound with the given name.
+    pub fn sound(&self, name: &str) -> Option<Arc<Sound>> {
+        self.sounds.get(name).cloned()
+    }
+
+    /// Removes and returns a sound with the given name.
+    pub fn remove_sound(&mut self, name: &str) -> Option<Arc<Sound>> {
+        self.sounds.remove(name)
+    }
+
+    /// Returns names of all sounds added to asset manager, sorted alphabetically.
+    pub fn sound_names(&self) -> Vec<String> {
+        let mut sound_names: Vec<String> = self.sounds.keys().cloned().collect();
+        sound_names.sort();
+        sound_names
+    }
+
+    /// Adds video to asset manager. Videos need to have unique name.
+    /// If two videos have the same name, the old video will be replaced with the new one.
+    pub fn add_video(&mut self, video: Arc<Video>) {
+        self.videos.insert(video.name().to_string(), video);
+    }
+
+    /// Loads video from a directory of frame images and adds it to asset manager.
+    pub fn load_video<S: Into<String>, P: AsRef<Path>>(&mut self, name: S, directory: P, frame_rate: f32) -> std::io::Result<Arc<Video>> {
+        profile_scope!("load video");
+        let video = Arc::new(Video::load(name, directory, frame_rate)?);
+        self.add_video(video.clone());
+        Ok(video)
+    }
+
+    /// Returns a video with the given name.
+    pub fn video(&self, name: &str) -> Option<Arc<Video>> {
+        self.videos.get(name).cloned()
+    }
+
+    /// Removes and returns a video with the given name.
+    pub fn remove_video(&mut self, name: &str) -> Option<Arc<Video>> {
+        self.videos.remove(name)
+    }
+
+    /// Returns names of all videos added to asset manager, sorted alphabetically.
+    pub fn video_names(&self) -> Vec<String> {
+        let mut video_names: Vec<String> = self.videos.keys().cloned().collect();
+        video_names.sort();
+        video_names
+    }
+
+    /// Returns video player, which plays videos into textures.
+    pub fn video_player(&self) -> &VideoPlayer {
+        &self.video_player
+    }
+
+    /// Returns a mutable reference to video player, used to start and stop videos.
+    pub fn video_player_mut(&mut self) -> &mut VideoPlayer {
+        &mut self.video_player
+    }
+
+    /// Advances playing videos and uploads their new frames into dynamic textures they're played into.
+    /// Texture is created by the first frame and updated in place by the following ones, unless the frame size changes.
+    /// Meshes hold their textures directly, so videos should be shown through material albedo textures, which are looked up by name.
+    pub fn update_videos(&mut self, elapsed: Duration) {
+        profile_scope!("update videos");
+        let videos = &self.videos;
+        let changed_frames = self.video_player.advance(elapsed, |name| videos.get(name).cloned());
+        for (texture_name, video, frame) in changed_frames {
+            let image = match video.decode_frame(frame) {
+                Ok(image) => image,
+                Err(err) => {
+                    error!("Couldn't decode frame {} of video {}: {}", frame, video.name(), err);
+                    continue;
+                },
+            };
+            let (width, height) = image.dimensions();
+            let texture = self.textures.get(&texture_name).filter(|texture| texture.is_dynamic() && texture.dimensions() == (width, height)).cloned();
+            let upload = match texture {
+                Some(texture) => texture.update_region(self.uploader.as_ref(), 0, 0, width, height, &image.to_rgba().into_raw()),
+                None => self.create_dynamic_texture(texture_name, image).map(|texture| self.add_texture(texture)),
+            };
+            if let Err(err) = upload {
+                error!("Couldn't upload frame {} of video {}: {}", frame, video.name(), err);
+            }
+        }
+    }
+
+    /// Adds font to asset manager. Fonts need to have unique name.
+    /// If two fonts have the same name, the old font will be replaced with the new one.
+    pub fn add_font(&mut self, font: Arc<Font>) {
+        self.fonts.insert(font.name().to_string(), font);
+    }
+
+    /// Loads font from a TrueType file and adds it to asset manager.
+    pub fn load_font<S: Into<String>, P: AsRef<Path>>(&mut self, name: S, path: P) -> std::io::Result<Arc<Font>> {
+        profile_scope!("load font");
+        let font = Arc::new(Font::load(name, path)?);
+        self.add_font(font.clone());
+        Ok(font)
+    }
+
+    /// Returns a font with the given name.
+    pub fn font(&self, name: &str) -> Option<Arc<Font>> {
+        self.fonts.get(name).cloned()
+    }
+
+    /// Loads string table from a file and adds it to localization.
+    pub fn load_string_table<P: AsRef<Path>>(&mut self, path: P) -> Result<(), StringTableError> {
+        self.localization.add_table(StringTable::load(path)?);
+        Ok(())
+    }
+
+    /// Returns localization, which translates strings to the current language.
+    pub fn localization(&self) -> &Localization {
+        &self.localization
+    }
+
+    /// Returns a mutable reference to localization, used to add string tables and switch languages.
+    pub fn localization_mut(&mut self) -> &mut Localization {
+        &mut self.localization
+    }
+
+    /// Returns string for the key in the current language.
+    pub fn tr(&self, key: &str) -> String {
+        self.localization.tr(key)
+    }
+
+    /// Returns random number service, which gives each subsystem its own stream of numbers derived from the global seed.
+    pub fn random_mut(&mut self) -> &mut RandomService {
+        &mut self.random
+    }
+
     /// Adds scene to asset manager. Scenes need to have unique name. 
     /// If two scenes have the same name, the old scene will be replaced with the new one.
     pub fn add_scene(&mut self, scene: Scene) {
@@ -119,19 +817,104 @@ impl AssetManager {
         self.scenes.get_mut(name)
     }
 
+    /// Returns world whose cells are merged into the active scene by `update_world`.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Returns mutable reference to world whose cells are merged into the active scene by `update_world`.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Loads and unloads world cells by their distance to the camera the active scene is seen through.
+    /// Loaded cells are merged into the active scene, which keeps its own objects, lights and camera.
+    pub fn update_world(&mut self) {
+        profile_scope!("world");
+        // cells are placed in absolute coordinates, which differ from scene coordinates once the origin is shifted
+        let camera_position = match &self.active_scene {
+            Some(scene) => {
+                let position = scene.absolute_position(&scene.view_camera().position_vec3());
+                Vec3::new(position.x as f32, position.y as f32, position.z as f32)
+            },
+            None => return,
+        };
+        for change in self.world.update(camera_position) {
+            match change {
+                CellChange::Loaded(name, scene_data) => {
+                    let cell_scene = self.scene_from_data(*scene_data);
+                    let ids = match &mut self.active_scene {
+                        Some(scene) => scene.merge(cell_scene),
+                        None => Vec::new(),
+                    };
+                    self.world.set_merged_objects(&name, ids);
+                },
+                CellChange::Unloaded(ids) => if let Some(scene) = &mut self.active_scene {
+                    scene.remove_objects(&ids);
+                },
+            }
+        }
+    }
+
+    /// Loads scene from a file. Meshes and materials referenced by the scene have to be added to asset manager before loading it,
+    /// primitive meshes are generated when missing. Objects whose primitive mesh couldn't be generated are loaded without a mesh.
+    /// Static objects are merged into static batches, if that fails they're drawn one by one.
+    pub fn load_scene<P: AsRef<Path>>(&self, path: P) -> Result<Scene, SceneSerializationError> {
+        profile_scope!("load scene");
+        let scene_data = SceneData::load(path)?;
+        Ok(self.scene_from_data(scene_data))
+    }
+
+    /// Creates scene from data read from a file, finding its meshes and materials and baking static batches like `load_scene`.
+    pub fn scene_from_data(&self, scene_data: SceneData) -> Scene {
+        let primitive_mesh = |primitive: Primitive| match self.create_primitive_mesh(primitive) {
+            Ok(mesh) => Some(mesh),
+            Err(err) => {
+                error!("Couldn't create mesh of primitive {}: {}", primitive.mesh_name(), err);
+                None
+            },
+        };
+        let mut scene = scene_data.into_scene(
+            |name| self.mesh(name).or_else(|| Primitive::from_mesh_name(name).and_then(&primitive_mesh)),
+            |name| self.material(name),
+        );
+        if let Err(err) = scene.bake_static_batches(self.uploader.as_ref()) {
+            error!("Couldn't bake static batches of scene {}: {}", scene.name(), err);
+        }
+        scene
+    }
+
     /// Removes and returns a scene with given name.
     pub fn remove_scene(&mut self, name: &str) -> Option<Scene> {
         self.scenes.remove(name)
     }
 
-    /// Sets active scene
-    pub fn set_active_scene(&mut self, scene: Scene) {
+    /// Sets active scene, replacing the current one.
+    /// Objects of the new scene are reported by `SceneEvent::Activated` instead of events of objects added before.
+    pub fn set_active_scene(&mut self, mut scene: Scene) {
+        self.remove_active_scene();
+        scene.take_object_events();
+        self.scene_events.push(GameEvent::Scene(SceneEvent::Activated(scene.name().to_string())));
         self.active_scene = Some(scene);
     }
 
-    /// Removes and returns active scene.
+    /// Removes and returns active scene. Objects of loaded world cells are removed from it, the cells are loaded again into the next active scene.
     pub fn remove_active_scene(&mut self) -> Option<Scene> {
-        self.active_scene.take()
+        let mut scene = self.active_scene.take()?;
+        scene.remove_objects(&self.world.unload_all());
+        self.scene_events.extend(scene.take_object_events().into_iter().map(GameEvent::Object));
+        self.scene_events.push(GameEvent::Scene(SceneEvent::Deactivated(scene.name().to_string())));
+        Some(scene)
+    }
+
+    /// Removes and returns scene activation changes and object events of the active scene since the last call,
+    /// used by the engine to publish lifetime events.
+    pub fn take_scene_events(&mut self) -> Vec<GameEvent> {
+        let mut events = std::mem::take(&mut self.scene_events);
+        if let Some(scene) = &mut self.active_scene {
+            events.extend(scene.take_object_events().into_iter().map(GameEvent::Object));
+        }
+        events
     }
 
     /// Returns an Option with reference to the active scene.
@@ -0,0 +1,25 @@
+use quick_error::quick_error;
+
+use crate::renderer::renderer_error::RenderError;
+use crate::resource::fetch::fetch_error::FetchError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum AssetError {
+        ImageError(err: image::ImageError) {
+            from()
+            display("ImageError: {}", err)
+            cause(err)
+        }
+        RenderError(err: RenderError) {
+            from()
+            display("RenderError: {}", err)
+            cause(err)
+        }
+        FetchError(err: FetchError) {
+            from()
+            display("FetchError: {}", err)
+            cause(err)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resource::asset_pack::asset_pack_error::AssetPackError;
+
+pub mod asset_pack_error;
+
+/// Start of asset pack files, followed by the version of the format.
+const ASSET_PACK_MAGIC: &[u8; 4] = b"KPAK";
+const ASSET_PACK_VERSION: u32 = 1;
+
+/// Files of a game bundled into a single file, so that they can be shipped and read at once.
+/// Files are named by their paths relative to the packed directory, with '/' as the separator.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct AssetPack {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl AssetPack {
+    /// Creates empty asset pack.
+    pub fn new() -> Self {
+        AssetPack::default()
+    }
+
+    /// Creates asset pack with every file of a directory and its subdirectories.
+    pub fn from_directory<P: AsRef<Path>>(directory: P) -> Result<Self, AssetPackError> {
+        let mut pack = AssetPack::new();
+        pack.add_directory(directory.as_ref(), "")?;
+        Ok(pack)
+    }
+
+    fn add_directory(&mut self, directory: &Path, prefix: &str) -> Result<(), AssetPackError> {
+        for entry in std::fs::read_dir(directory)? {
+            let entry = entry?;
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            if entry.file_type()?.is_dir() {
+                self.add_directory(&entry.path(), &format!("{}/", name))?;
+            } else {
+                self.add_file(name, std::fs::read(entry.path())?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds file with given name to the pack, replacing file with the same name.
+    pub fn add_file<S: Into<String>>(&mut self, name: S, bytes: Vec<u8>) {
+        self.files.insert(name.into(), bytes);
+    }
+
+    /// Returns contents of file with given name.
+    pub fn file(&self, name: &str) -> Option<&[u8]> {
+        self.files.get(name).map(|bytes| bytes.as_slice())
+    }
+
+    /// Returns names of files in the pack in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|name| name.as_str())
+    }
+
+    /// Returns number of files in the pack.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns true if the pack has no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Loads asset pack from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssetPackError> {
+        AssetPack::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Fetches asset pack from given URL, relative to the page, in browsers where it can't be loaded from a file.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn fetch(url: &str) -> Result<Self, AssetPackError> {
+        AssetPack::from_bytes(&crate::resource::fetch::fetch(url).await?)
+    }
+
+    /// Reads asset pack from its serialized form.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AssetPackError> {
+        let mut version = [0; 4];
+        if bytes.len() < 8 || &bytes[..4] != ASSET_PACK_MAGIC {
+            return Err(AssetPackError::InvalidPack);
+        }
+        version.copy_from_slice(&bytes[4..8]);
+        if u32::from_le_bytes(version) != ASSET_PACK_VERSION {
+            return Err(AssetPackError::InvalidPack);
+        }
+        Ok(bincode::deserialize(&bytes[8..])?)
+    }
+
+    /// Returns serialized asset pack.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AssetPackError> {
+        let mut bytes = ASSET_PACK_MAGIC.to_vec();
+        bytes.extend_from_slice(&ASSET_PACK_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(self)?);
+        Ok(bytes)
+    }
+
+    /// Saves asset pack to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AssetPackError> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+}
+
+/// Returns name an asset gets from a file of a pack, which is its path without the extension.
+pub fn asset_name(file_name: &str) -> &str {
+    match file_name.rfind('.') {
+        Some(dot) if !file_name[dot..].contains('/') => &file_name[..dot],
+        _ => file_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_keeps_files_when_serialized() {
+        let mut pack = AssetPack::new();
+        pack.add_file("textures/stone.png", vec![1, 2, 3]);
+        pack.add_file("scene.ron", Vec::new());
+
+        let loaded = AssetPack::from_bytes(&pack.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(pack, loaded);
+        assert_eq!(vec!["scene.ron", "textures/stone.png"], loaded.names().collect::<Vec<_>>());
+        assert_eq!(Some(&[1, 2, 3][..]), loaded.file("textures/stone.png"));
+        assert!(AssetPack::from_bytes(b"KMDL\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn asset_names_are_paths_without_extension() {
+        assert_eq!("textures/stone", asset_name("textures/stone.png"));
+        assert_eq!("models.v2/ship", asset_name("models.v2/ship"));
+    }
+}
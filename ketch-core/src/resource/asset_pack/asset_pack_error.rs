@@ -0,0 +1,27 @@
+use quick_error::quick_error;
+
+use crate::resource::fetch::fetch_error::FetchError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum AssetPackError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        SerializationError(err: bincode::Error) {
+            from()
+            display("SerializationError: {}", err)
+            cause(err)
+        }
+        FetchError(err: FetchError) {
+            from()
+            display("FetchError: {}", err)
+            cause(err)
+        }
+        InvalidPack {
+            display("data isn't an asset pack in a supported version of the format")
+        }
+    }
+}
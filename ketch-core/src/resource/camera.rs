@@ -1,9 +1,10 @@
-use crate::settings::Settings;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::math::frustum::Frustum;
+#[cfg(feature = "vulkan")]
 use crate::renderer::shader::vertex_shader::ty::TransformationData;
-use nalgebra_glm::{U3, Vec3, Mat4};
+use nalgebra_glm::{U3, Vec3, Vec4, Mat4};
 use nalgebra_glm as glm;
+use serde::{Deserialize, Serialize};
+use crate::resource::render_layers::RenderLayers;
 
 const DEFAULT_NEAR_PLANE: f32 = 0.1;
 const DEFAULT_FAR_PLANE: f32 = 1000.0;
@@ -14,6 +15,58 @@ const MIN_PITCH: f32 = -89.0;
 const MIN_FOV: f32 = 1.0;
 const MAX_FOV: f32 = 45.0;
 
+/// Settings of automatic exposure, which adapts exposure of the camera to brightness of rendered frames.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct AutoExposure {
+    /// Lowest exposure adaptation can choose, in stops added to exposure of the camera.
+    pub min_exposure: f32,
+    /// Highest exposure adaptation can choose, in stops added to exposure of the camera.
+    pub max_exposure: f32,
+    /// Average luminance frames are adapted to, 0.18 is middle gray.
+    pub target_luminance: f32,
+    /// How fast exposure adapts, higher values reach the target exposure sooner.
+    pub adaptation_speed: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        AutoExposure {
+            min_exposure: -4.0,
+            max_exposure: 4.0,
+            target_luminance: 0.18,
+            adaptation_speed: 1.5,
+        }
+    }
+}
+
+impl AutoExposure {
+    /// Returns exposure moved from the current one towards exposure bringing scene of given average log2 luminance to the target luminance.
+    /// Adaptation is smoothed over time, so that exposure doesn't jump when moving between bright and dark areas.
+    pub fn adapt(&self, exposure: f32, scene_log_luminance: f32, delta_time: f32) -> f32 {
+        let target_exposure = (self.target_luminance.max(f32::EPSILON).log2() - scene_log_luminance).max(self.min_exposure).min(self.max_exposure);
+        let blend = 1.0 - (-self.adaptation_speed.max(0.0) * delta_time.max(0.0)).exp();
+        exposure + (target_exposure - exposure) * blend
+    }
+}
+
+/// Settings of bloom, which spreads light of pixels brighter than white, like glowing emissive surfaces, over their surroundings.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Bloom {
+    /// Brightness above which pixels bloom, 1.0 is fully lit white.
+    pub threshold: f32,
+    /// Factor blurred bright pixels are multiplied by before they're added to the image.
+    pub intensity: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 1.0,
+            intensity: 0.5,
+        }
+    }
+}
+
 /// Struct representing a camera.
 #[derive(Clone)]
 pub struct Camera {
@@ -30,6 +83,12 @@ pub struct Camera {
     far_plane: f32,
 
     fov: f32,
+
+    exposure: f32,
+    auto_exposure: Option<AutoExposure>,
+    bloom: Option<Bloom>,
+
+    visibility_mask: RenderLayers,
 }
 
 impl Camera {
@@ -52,6 +111,10 @@ impl Camera {
             fov: 45.0,
             near_plane: DEFAULT_NEAR_PLANE,
             far_plane: DEFAULT_FAR_PLANE,
+            exposure: 0.0,
+            auto_exposure: None,
+            bloom: None,
+            visibility_mask: RenderLayers::ALL,
         }
     }
     /// Returns camera position
@@ -64,6 +127,16 @@ impl Camera {
         self.position
     }
 
+    /// Returns normalized direction the camera is looking at
+    pub fn front(&self) -> Vec3 {
+        self.front
+    }
+
+    /// Returns normalized direction to the right of the camera
+    pub fn right(&self) -> Vec3 {
+        self.right
+    }
+
     /// Sets camera position
     pub fn set_position(&mut self, x: f32, y: f32, z: f32) {
         self.position = Vec3::new(x, y, z);
@@ -152,6 +225,51 @@ impl Camera {
         self.far_plane = value;
     }
 
+    /// Returns exposure of the camera in stops. Every stop doubles brightness of the rendered image.
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Sets exposure of the camera in stops. When auto exposure is enabled, it's added to the adapted exposure as compensation.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    /// Returns factor colors of the rendered image are multiplied by.
+    pub fn exposure_multiplier(&self) -> f32 {
+        self.exposure.exp2()
+    }
+
+    /// Returns settings of auto exposure, or None if it's disabled.
+    pub fn auto_exposure(&self) -> Option<AutoExposure> {
+        self.auto_exposure
+    }
+
+    /// Enables auto exposure with given settings, or disables it when None is given.
+    pub fn set_auto_exposure(&mut self, auto_exposure: Option<AutoExposure>) {
+        self.auto_exposure = auto_exposure;
+    }
+
+    /// Returns settings of bloom, or None if it's disabled.
+    pub fn bloom(&self) -> Option<Bloom> {
+        self.bloom
+    }
+
+    /// Enables bloom with given settings, or disables it when None is given. Only the Vulkan renderer draws bloom.
+    pub fn set_bloom(&mut self, bloom: Option<Bloom>) {
+        self.bloom = bloom;
+    }
+
+    /// Returns render layers drawn by this camera. Objects which aren't in any of them are skipped.
+    pub fn visibility_mask(&self) -> RenderLayers {
+        self.visibility_mask
+    }
+
+    /// Sets render layers drawn by this camera, for example a minimap camera can leave out particles.
+    pub fn set_visibility_mask(&mut self, visibility_mask: RenderLayers) {
+        self.visibility_mask = visibility_mask;
+    }
+
     fn update_camera_vectors(&mut self) {
         self.front.x = self.yaw.to_radians().cos() * self.pitch.to_radians().cos();
         self.front.y = self.pitch.to_radians().sin();
@@ -180,8 +298,53 @@ impl Camera {
         return correction_matrix * proj_matrix;
     }
 
+    /// Returns projection matrix multiplied by view matrix, which transforms points in world space to clip space.
+    pub fn view_projection_matrix(&self, window_width: f32, window_height: f32) -> Mat4 {
+        self.projection_matrix(window_width, window_height) * self.view_matrix()
+    }
+
+    /// Returns volume seen by the camera, the same one the renderer culls objects with.
+    pub fn frustum(&self, window_width: f32, window_height: f32) -> Frustum {
+        Frustum::from_matrix(&self.view_projection_matrix(window_width, window_height))
+    }
+
+    /// Returns planes of the volume seen by the camera, with unit normals pointing inside, in order: left, right, bottom, top, near, far.
+    pub fn frustum_planes(&self, window_width: f32, window_height: f32) -> [Vec4; 6] {
+        *self.frustum(window_width, window_height).planes()
+    }
+
+    /// Projects point in world space onto the screen. Coordinates are measured from the top left corner of the window.
+    /// Returns None if point is behind the camera.
+    pub fn world_to_screen(&self, point: &Vec3, window_width: f32, window_height: f32) -> Option<(f32, f32)> {
+        let clip_position = self.projection_matrix(window_width, window_height) * self.view_matrix() * Vec4::new(point.x, point.y, point.z, 1.0);
+        if clip_position.w <= 0.0 {
+            return None;
+        }
+        let ndc_x = clip_position.x / clip_position.w;
+        let ndc_y = clip_position.y / clip_position.w;
+        Some(((ndc_x + 1.0) / 2.0 * window_width, (ndc_y + 1.0) / 2.0 * window_height))
+    }
+
+    /// Returns corners of the volume seen by the camera, with far corners moved closer if far plane is farther than `max_distance`.
+    /// Near corners come first, both in order: bottom left, bottom right, top right, top left.
+    pub fn frustum_corners(&self, aspect_ratio: f32, max_distance: f32) -> [Vec3; 8] {
+        let inverse_view_projection = glm::inverse(&(self.projection_matrix(aspect_ratio, 1.0) * self.view_matrix()));
+        let unproject = |x: f32, y: f32, depth: f32| {
+            let point = inverse_view_projection * Vec4::new(x, y, depth, 1.0);
+            Vec3::new(point.x, point.y, point.z) / point.w
+        };
+        let far_scale = (max_distance / self.far_plane).min(1.0);
+        let far_corner = |x: f32, y: f32| self.position + (unproject(x, y, 1.0) - self.position) * far_scale;
+        // y axis points down after projection
+        [
+            unproject(-1.0, 1.0, 0.0), unproject(1.0, 1.0, 0.0), unproject(1.0, -1.0, 0.0), unproject(-1.0, -1.0, 0.0),
+            far_corner(-1.0, 1.0), far_corner(1.0, 1.0), far_corner(1.0, -1.0), far_corner(-1.0, -1.0),
+        ]
+    }
+
     /// Returns model, view and projection matrix as uniform data. 
     /// Model should be updated with model matrix from Object.
+    #[cfg(feature = "vulkan")]
     pub fn as_uniform_data(&self, window_width: f32, window_height: f32) -> TransformationData {
         TransformationData {
             model: Mat4::identity().into(),
@@ -202,6 +365,34 @@ pub enum Direction {
 mod tests {
     use super::*;
 
+    #[test]
+    fn frustum_corners_lie_on_near_plane_and_are_cut_at_max_distance() {
+        let camera = Camera::new();
+
+        let corners = camera.frustum_corners(16.0 / 9.0, 5.0);
+
+        for near_corner in &corners[..4] {
+            assert!((glm::dot(&(near_corner - camera.position_vec3()), &camera.front()) - DEFAULT_NEAR_PLANE).abs() < 0.001);
+        }
+        for far_corner in &corners[4..] {
+            assert!((glm::dot(&(far_corner - camera.position_vec3()), &camera.front()) - 5.0).abs() < 0.01);
+        }
+        assert!(corners[0].y < corners[3].y);
+        assert!(corners[0].x < corners[1].x);
+    }
+
+    #[test]
+    fn auto_exposure_adapts_smoothly_within_limits() {
+        let auto_exposure = AutoExposure::default();
+        let dark_scene = auto_exposure.target_luminance.log2() - 2.0;
+
+        let exposure = auto_exposure.adapt(0.0, dark_scene, 0.1);
+        assert!(exposure > 0.0 && exposure < 2.0);
+        assert!((auto_exposure.adapt(0.0, dark_scene, 100.0) - 2.0).abs() < 0.001);
+        assert!((auto_exposure.adapt(0.0, -20.0, 100.0) - auto_exposure.max_exposure).abs() < 0.001);
+        assert_eq!(1.0, auto_exposure.adapt(1.0, dark_scene, 0.0));
+    }
+
     #[test]
     fn test_move_camera_left() {
         let mut camera = Camera::new();
@@ -372,4 +563,14 @@ mod tests {
         assert_eq!(MIN_FOV, camera.fov());
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn point_in_front_of_camera_is_projected_to_screen_center() {
+        let camera = Camera::new();
+        let point = camera.position_vec3() + camera.front() * 5.0;
+
+        let (x, y) = camera.world_to_screen(&point, 800.0, 600.0).unwrap();
+
+        assert!((x - 400.0).abs() < 0.01 && (y - 300.0).abs() < 0.01);
+        assert_eq!(None, camera.world_to_screen(&(camera.position_vec3() - camera.front() * 5.0), 800.0, 600.0));
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// Shape of a collider in object space, centered at the object position.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ColliderShape {
+    /// Box rotated with its object by physics. Standalone collision detection keeps it axis aligned.
+    Cuboid { half_extents: (f32, f32, f32) },
+    Sphere { radius: f32 },
+    /// Capsule along the y axis. Half height doesn't include the rounded caps.
+    Capsule { half_height: f32, radius: f32 },
+}
+
+impl ColliderShape {
+    /// Returns shape scaled by object scale. Round shapes are scaled by the largest scale which affects them, so that they stay round.
+    pub fn scaled(self, scale: (f32, f32, f32)) -> ColliderShape {
+        let (scale_x, scale_y, scale_z) = (scale.0.abs(), scale.1.abs(), scale.2.abs());
+        match self {
+            ColliderShape::Cuboid { half_extents: (x, y, z) } => ColliderShape::Cuboid { half_extents: (x * scale_x, y * scale_y, z * scale_z) },
+            ColliderShape::Sphere { radius } => ColliderShape::Sphere { radius: radius * scale_x.max(scale_y).max(scale_z) },
+            ColliderShape::Capsule { half_height, radius } => ColliderShape::Capsule {
+                half_height: half_height * scale_y,
+                radius: radius * scale_x.max(scale_z),
+            },
+        }
+    }
+}
+
+/// Collider component, giving object a shape which collides with other objects.
+/// Sensors don't collide, but report objects overlapping them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    #[serde(default)]
+    pub sensor: bool,
+    #[serde(default = "default_density")]
+    pub density: f32,
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+    #[serde(default)]
+    pub restitution: f32,
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
+fn default_friction() -> f32 {
+    0.5
+}
+
+impl Collider {
+    /// Creates solid collider of given shape.
+    pub fn new(shape: ColliderShape) -> Self {
+        Collider {
+            shape,
+            sensor: false,
+            density: default_density(),
+            friction: default_friction(),
+            restitution: 0.0,
+        }
+    }
+
+    /// Creates sensor of given shape.
+    pub fn sensor(shape: ColliderShape) -> Self {
+        Collider {
+            sensor: true,
+            ..Collider::new(shape)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_sphere_stays_round() {
+        let shape = ColliderShape::Sphere { radius: 1.0 }.scaled((1.0, 3.0, -2.0));
+
+        assert_eq!(ColliderShape::Sphere { radius: 3.0 }, shape);
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsCast;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_futures::JsFuture;
+
+#[cfg(target_arch = "wasm32")]
+use crate::resource::fetch::fetch_error::FetchError;
+
+pub mod fetch_error;
+
+/// Fetches file from given URL, relative to the page. Browsers have no filesystem, so this is how assets are read there.
+#[cfg(target_arch = "wasm32")]
+pub async fn fetch(url: &str) -> Result<Vec<u8>, FetchError> {
+    let request_error = |err: wasm_bindgen::JsValue| FetchError::RequestError(url.to_string(), format!("{:?}", err));
+    let window = web_sys::window().ok_or(FetchError::NoWindow)?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_str(url)).await
+                                                                                 .map_err(request_error)?
+                                                                                 .dyn_into()
+                                                                                 .map_err(request_error)?;
+    if !response.ok() {
+        return Err(FetchError::StatusError(url.to_string(), response.status()));
+    }
+    let buffer = JsFuture::from(response.array_buffer().map_err(request_error)?).await.map_err(request_error)?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
@@ -0,0 +1,16 @@
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum FetchError {
+        NoWindow {
+            display("NoWindow: fetching needs the window of a web page")
+        }
+        RequestError(url: String, message: String) {
+            display("RequestError: request of {} failed: {}", url, message)
+        }
+        StatusError(url: String, status: u16) {
+            display("StatusError: server responded to request of {} with status {}", url, status)
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+use rusttype::{point, Scale};
+
+/// Empty pixels around rasterized text, so that filtering doesn't pick up pixels from the opposite edge.
+pub(crate) const TEXT_PADDING: u32 = 1;
+
+/// TrueType font used to draw text of in-game UI.
+pub struct Font {
+    name: String,
+    font: rusttype::Font<'static>,
+}
+
+impl Font {
+    /// Creates font from TrueType font data.
+    pub fn new<S: Into<String>>(name: S, data: Vec<u8>) -> std::io::Result<Self> {
+        let font = rusttype::Font::from_bytes(data).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Ok(Font {
+            name: name.into(),
+            font,
+        })
+    }
+
+    /// Loads font from a TrueType file.
+    pub fn load<S: Into<String>, P: AsRef<Path>>(name: S, path: P) -> std::io::Result<Self> {
+        Font::new(name, std::fs::read(path)?)
+    }
+
+    /// Returns the name of this font.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns width and height in pixels of a single line of text.
+    pub fn text_size(&self, text: &str, font_size: f32) -> (f32, f32) {
+        let scale = Scale::uniform(font_size);
+        let v_metrics = self.font.v_metrics(scale);
+        let width = self.font.layout(text, scale, point(0.0, 0.0))
+                             .last()
+                             .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+                             .unwrap_or(0.0);
+        (width.ceil(), (v_metrics.ascent - v_metrics.descent).ceil())
+    }
+
+    /// Draws a single line of white text on transparent background, with coverage stored in alpha.
+    pub fn rasterize(&self, text: &str, font_size: f32) -> RgbaImage {
+        let scale = Scale::uniform(font_size);
+        let v_metrics = self.font.v_metrics(scale);
+        let (width, height) = self.text_size(text, font_size);
+        let mut image = RgbaImage::from_pixel(width as u32 + TEXT_PADDING * 2, height as u32 + TEXT_PADDING * 2, Rgba([255, 255, 255, 0]));
+        let origin = point(TEXT_PADDING as f32, TEXT_PADDING as f32 + v_metrics.ascent);
+        for glyph in self.font.layout(text, scale, origin) {
+            if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                glyph.draw(|x, y, coverage| {
+                    let (x, y) = (x as i32 + bounding_box.min.x, y as i32 + bounding_box.min.y);
+                    if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                        image.get_pixel_mut(x as u32, y as u32).data[3] = (coverage * 255.0) as u8;
+                    }
+                });
+            }
+        }
+        image
+    }
+}
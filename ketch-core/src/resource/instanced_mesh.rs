@@ -0,0 +1,92 @@
+use std::sync::{Arc, RwLock};
+
+use nalgebra_glm::Mat4;
+
+use crate::resource::material::Material;
+use crate::resource::mesh::Mesh;
+use crate::resource::render_layers::RenderLayers;
+
+/// Mesh drawn many times with a single draw call, once for every instance transform.
+/// Instanced meshes added to asset manager are drawn with the active scene, after its objects,
+/// so they're meant for opaque geometry such as foliage or crowds which would be too many objects.
+pub struct InstancedMesh {
+    name: String,
+    mesh: Arc<RwLock<Mesh>>,
+    material: Option<Arc<RwLock<Material>>>,
+    transforms: Vec<Mat4>,
+    render_layers: RenderLayers,
+}
+
+impl InstancedMesh {
+    /// Creates instanced mesh without any instances.
+    pub fn new<S: Into<String>>(name: S, mesh: Arc<RwLock<Mesh>>) -> Self {
+        InstancedMesh {
+            name: name.into(),
+            mesh,
+            material: None,
+            transforms: Vec::new(),
+            render_layers: RenderLayers::DEFAULT,
+        }
+    }
+
+    /// Returns the name of this instanced mesh.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns mesh drawn by every instance.
+    pub fn mesh(&self) -> Arc<RwLock<Mesh>> {
+        self.mesh.clone()
+    }
+
+    /// Sets mesh drawn by every instance.
+    pub fn set_mesh(&mut self, mesh: Arc<RwLock<Mesh>>) {
+        self.mesh = mesh;
+    }
+
+    /// Returns material shared by all instances, or None if the default material is used.
+    pub fn material(&self) -> Option<Arc<RwLock<Material>>> {
+        self.material.clone()
+    }
+
+    /// Sets material shared by all instances, or the default material if None is passed.
+    pub fn set_material(&mut self, material: Option<Arc<RwLock<Material>>>) {
+        self.material = material;
+    }
+
+    /// Returns render layers all instances belong to.
+    pub fn render_layers(&self) -> RenderLayers {
+        self.render_layers
+    }
+
+    /// Sets render layers all instances belong to. Cameras only draw instances in layers of their visibility mask.
+    pub fn set_render_layers(&mut self, render_layers: RenderLayers) {
+        self.render_layers = render_layers;
+    }
+
+    /// Returns world matrices of instances.
+    pub fn transforms(&self) -> &[Mat4] {
+        &self.transforms
+    }
+
+    /// Replaces world matrices of all instances at once, which is the cheapest way to move them every frame.
+    pub fn set_transforms(&mut self, transforms: Vec<Mat4>) {
+        self.transforms = transforms;
+    }
+
+    /// Returns a mutable reference to world matrices of instances, used to add, remove or move instances in place.
+    pub fn transforms_mut(&mut self) -> &mut Vec<Mat4> {
+        &mut self.transforms
+    }
+
+    /// Adds instance with given world matrix and returns its index.
+    pub fn add_instance(&mut self, transform: Mat4) -> usize {
+        self.transforms.push(transform);
+        self.transforms.len() - 1
+    }
+
+    /// Returns number of instances.
+    pub fn instance_count(&self) -> usize {
+        self.transforms.len()
+    }
+}
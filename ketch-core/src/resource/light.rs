@@ -0,0 +1,238 @@
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter used to assign every light a unique id.
+static ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximum number of lights used by the renderer, additional lights are ignored.
+pub const MAX_LIGHTS: usize = 8;
+
+/// Largest outer cone angle of a spot light, in radians.
+pub const MAX_CONE_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+
+/// Way in which light reaches objects.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LightType {
+    /// Shines in all directions from its position, up to its range.
+    #[default]
+    Point,
+    /// Shines from its position in its direction, inside a cone, up to its range.
+    Spot,
+    /// Shines in its direction from infinitely far away, like the sun. Position and range are ignored.
+    Directional,
+}
+
+impl LightType {
+    /// Returns value identifying this type in the fragment shader.
+    pub fn shader_value(self) -> f32 {
+        match self {
+            LightType::Point => 0.0,
+            LightType::Spot => 1.0,
+            LightType::Directional => 2.0,
+        }
+    }
+}
+
+/// Light reaching every surface of the scene from all directions, so that sides facing away from lights aren't black.
+/// Surfaces facing up receive sky color and surfaces facing down receive ground color, which approximates light bounced by the environment.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct AmbientLight {
+    pub sky_color: (f32, f32, f32),
+    pub ground_color: (f32, f32, f32),
+    pub intensity: f32,
+}
+
+impl AmbientLight {
+    /// Creates ambient light with the same color from all directions.
+    pub fn uniform(r: f32, g: f32, b: f32, intensity: f32) -> Self {
+        AmbientLight {
+            sky_color: (r, g, b),
+            ground_color: (r, g, b),
+            intensity,
+        }
+    }
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        AmbientLight::uniform(1.0, 1.0, 1.0, 0.1)
+    }
+}
+
+/// Light illuminating objects in the scene.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Light {
+    id: u32,
+    name: String,
+    light_type: LightType,
+    position: Vec3,
+    direction: Vec3,
+    color: Vec3,
+    intensity: f32,
+    range: f32,
+    inner_cone_angle: f32,
+    outer_cone_angle: f32,
+}
+
+impl Light {
+    /// Creates white point light with given name placed at the origin.
+    /// Its direction points down, for when it's changed to a spot or directional light.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Light {
+            id: generate_id(),
+            name: name.into(),
+            light_type: LightType::Point,
+            position: Vec3::new(0.0, 0.0, 0.0),
+            direction: Vec3::new(0.0, -1.0, 0.0),
+            color: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            range: 50.0,
+            inner_cone_angle: 0.35,
+            outer_cone_angle: 0.5,
+        }
+    }
+
+    /// Creates white directional light with given name, shining in given direction.
+    pub fn directional<S: Into<String>>(name: S, x: f32, y: f32, z: f32) -> Self {
+        let mut light = Light::new(name);
+        light.set_light_type(LightType::Directional);
+        light.set_direction(x, y, z);
+        light
+    }
+
+    /// Creates white spot light with given name placed at given position, shining in given direction.
+    pub fn spot<S: Into<String>>(name: S, position: (f32, f32, f32), direction: (f32, f32, f32)) -> Self {
+        let mut light = Light::new(name);
+        light.set_light_type(LightType::Spot);
+        light.set_position(position.0, position.1, position.2);
+        light.set_direction(direction.0, direction.1, direction.2);
+        light
+    }
+
+    /// Returns unique id of this light.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    pub fn light_type(&self) -> LightType {
+        self.light_type
+    }
+
+    pub fn set_light_type(&mut self, light_type: LightType) {
+        self.light_type = light_type;
+    }
+
+    pub fn position_vec3(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn position(&self) -> (f32, f32, f32) {
+        (self.position.x, self.position.y, self.position.z)
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32, z: f32) {
+        self.position = Vec3::new(x, y, z);
+    }
+
+    /// Returns normalized direction in which spot and directional lights shine.
+    pub fn direction_vec3(&self) -> Vec3 {
+        self.direction
+    }
+
+    pub fn direction(&self) -> (f32, f32, f32) {
+        (self.direction.x, self.direction.y, self.direction.z)
+    }
+
+    /// Sets direction in which spot and directional lights shine. It's normalized, and zero vector is ignored.
+    pub fn set_direction(&mut self, x: f32, y: f32, z: f32) {
+        let direction = Vec3::new(x, y, z);
+        let length = direction.norm();
+        if length > f32::EPSILON {
+            self.direction = direction / length;
+        }
+    }
+
+    pub fn color_vec3(&self) -> Vec3 {
+        self.color
+    }
+
+    pub fn color(&self) -> (f32, f32, f32) {
+        (self.color.x, self.color.y, self.color.z)
+    }
+
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        self.color = Vec3::new(r, g, b);
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets intensity of this light, negative values are clamped to 0.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
+    /// Returns distance at which light no longer illuminates objects.
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    /// Sets range of this light, negative values are clamped to 0.
+    pub fn set_range(&mut self, range: f32) {
+        self.range = range.max(0.0);
+    }
+
+    /// Returns angle in radians between direction of a spot light and the edge of its fully lit cone.
+    pub fn inner_cone_angle(&self) -> f32 {
+        self.inner_cone_angle
+    }
+
+    /// Returns angle in radians between direction of a spot light and the edge of its cone, beyond which it doesn't light objects.
+    pub fn outer_cone_angle(&self) -> f32 {
+        self.outer_cone_angle
+    }
+
+    /// Sets cone angles of a spot light in radians. Light fades out between the inner and the outer cone.
+    /// Outer angle is clamped to between 0 and `MAX_CONE_ANGLE`, and inner angle to between 0 and the outer angle.
+    pub fn set_cone_angles(&mut self, inner: f32, outer: f32) {
+        self.outer_cone_angle = outer.clamp(0.0, MAX_CONE_ANGLE);
+        self.inner_cone_angle = inner.max(0.0).min(self.outer_cone_angle);
+    }
+}
+
+/// Generates new unique id.
+fn generate_id() -> u32 {
+    let id = ID_COUNTER.fetch_add(1, Ordering::SeqCst) + 1;
+    id as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_is_normalized_and_cone_angles_are_clamped() {
+        let mut light = Light::spot("flashlight", (0.0, 1.0, 0.0), (0.0, 0.0, -2.0));
+        assert_eq!((0.0, 0.0, -1.0), light.direction());
+        light.set_direction(0.0, 0.0, 0.0);
+        assert_eq!((0.0, 0.0, -1.0), light.direction());
+
+        light.set_cone_angles(1.0, 0.5);
+        assert_eq!(0.5, light.inner_cone_angle());
+        assert_eq!(0.5, light.outer_cone_angle());
+        light.set_cone_angles(-1.0, 4.0);
+        assert_eq!(0.0, light.inner_cone_angle());
+        assert_eq!(MAX_CONE_ANGLE, light.outer_cone_angle());
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Lighting of a static object baked by an external tool into a texture, sampled with lightmap coordinates of its mesh.
+/// Mesh of the object has to use `VertexFormat::Lightmapped`, other meshes sample the corner of the lightmap.
+/// Baked lighting replaces ambient and diffuse lighting of the object, dynamic lights only add their specular highlights.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Lightmap {
+    /// Name of the lightmap texture in asset manager.
+    pub texture: String,
+    /// Factor baked lighting is multiplied by, e.g. to match exposure of the baking tool.
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+impl Lightmap {
+    /// Creates lightmap sampling texture with given name at full intensity.
+    pub fn new<S: Into<String>>(texture: S) -> Self {
+        Lightmap {
+            texture: texture.into(),
+            intensity: default_intensity(),
+        }
+    }
+}
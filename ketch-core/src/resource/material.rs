@@ -0,0 +1,393 @@
+#[cfg(feature = "vulkan")]
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "vulkan")]
+use crate::renderer::shader::fragment_shader::ty::MaterialData;
+use crate::resource::material::material_error::MaterialError;
+
+pub mod material_error;
+
+/// Name of the material used by objects without a material.
+pub const DEFAULT_MATERIAL_NAME: &str = "default";
+
+/// Largest emissive intensity which can be set in the editor.
+pub const MAX_EDITOR_EMISSIVE_INTENSITY: f32 = 10.0;
+
+/// Number of custom shader parameters a material can declare. Their values are passed to shaders in one vec4 per object.
+pub const MAX_CUSTOM_PARAMS: usize = 4;
+
+/// Describes how rendered object is combined with what was already drawn.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BlendMode {
+    Opaque,
+    AlphaBlend,
+    Additive,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 3] = [BlendMode::Opaque, BlendMode::AlphaBlend, BlendMode::Additive];
+
+    /// Returns name of the blend mode shown in the editor.
+    pub fn label(self) -> &'static str {
+        match self {
+            BlendMode::Opaque => "opaque",
+            BlendMode::AlphaBlend => "alpha blend",
+            BlendMode::Additive => "additive",
+        }
+    }
+
+    /// Returns true if objects using this blend mode have to be drawn after opaque ones.
+    pub fn is_transparent(self) -> bool {
+        self != BlendMode::Opaque
+    }
+}
+
+/// Describes which faces of rendered objects are discarded.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CullMode {
+    /// Both faces are drawn, used by double-sided geometry like foliage cards.
+    None,
+    /// Front faces are discarded, used by meshes seen from the inside like skyboxes.
+    Front,
+    Back,
+}
+
+impl CullMode {
+    pub const ALL: [CullMode; 3] = [CullMode::None, CullMode::Front, CullMode::Back];
+
+    /// Returns name of the cull mode shown in the editor.
+    pub fn label(self) -> &'static str {
+        match self {
+            CullMode::None => "none",
+            CullMode::Front => "front",
+            CullMode::Back => "back",
+        }
+    }
+}
+
+/// Winding order of vertices of front faces, as seen on the screen.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FrontFace {
+    CounterClockwise,
+    Clockwise,
+}
+
+impl FrontFace {
+    pub const ALL: [FrontFace; 2] = [FrontFace::CounterClockwise, FrontFace::Clockwise];
+
+    /// Returns name of the winding shown in the editor.
+    pub fn label(self) -> &'static str {
+        match self {
+            FrontFace::CounterClockwise => "counter-clockwise",
+            FrontFace::Clockwise => "clockwise",
+        }
+    }
+}
+
+/// Custom shader parameter declared by a material, whose value can be set per object with `Object::set_param`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CustomParam {
+    pub name: String,
+    /// Value used by objects which didn't set the parameter.
+    pub default: f32,
+}
+
+/// Surface properties of rendered objects. Textures are referenced by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Material {
+    name: String,
+    base_color: (f32, f32, f32, f32),
+    albedo_texture: Option<String>,
+    metallic: f32,
+    roughness: f32,
+    emissive_color: (f32, f32, f32),
+    emissive_intensity: f32,
+    blend_mode: BlendMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    depth_bias: f32,
+    custom_params: Vec<CustomParam>,
+}
+
+impl Material {
+    /// Creates new white, non-metallic, opaque and double-sided material, which doesn't emit light.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Material {
+            name: name.into(),
+            base_color: (1.0, 1.0, 1.0, 1.0),
+            albedo_texture: None,
+            metallic: 0.0,
+            roughness: 0.5,
+            emissive_color: (1.0, 1.0, 1.0),
+            emissive_intensity: 0.0,
+            blend_mode: BlendMode::Opaque,
+            cull_mode: CullMode::None,
+            front_face: FrontFace::CounterClockwise,
+            depth_bias: 0.0,
+            custom_params: Vec::new(),
+        }
+    }
+
+    /// Creates black material glowing with given color, used to draw light sources.
+    pub fn emissive<S: Into<String>>(name: S, r: f32, g: f32, b: f32, intensity: f32) -> Self {
+        let mut material = Material::new(name);
+        material.set_base_color(0.0, 0.0, 0.0, 1.0);
+        material.set_roughness(1.0);
+        material.set_emissive_color(r, g, b);
+        material.set_emissive_intensity(intensity);
+        material
+    }
+
+    /// Loads material from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MaterialError> {
+        let serialized_material = std::fs::read_to_string(path)?;
+        Material::parse(&serialized_material)
+    }
+
+    /// Reads material serialized as RON, e.g. from an asset pack.
+    pub fn parse(serialized_material: &str) -> Result<Self, MaterialError> {
+        Ok(ron::de::from_str(serialized_material)?)
+    }
+
+    /// Saves material to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MaterialError> {
+        let serialized_material = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, serialized_material)?;
+        Ok(())
+    }
+
+    /// Returns the name of this material.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the name of this material.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Returns color multiplied with albedo texture, alpha is used by blended materials.
+    pub fn base_color(&self) -> (f32, f32, f32, f32) {
+        self.base_color
+    }
+
+    /// Sets color multiplied with albedo texture. Components are clamped to 0.0 - 1.0 range.
+    pub fn set_base_color(&mut self, r: f32, g: f32, b: f32, a: f32) {
+        self.base_color = (clamp_unit(r), clamp_unit(g), clamp_unit(b), clamp_unit(a));
+    }
+
+    /// Returns name of the albedo texture or None if mesh texture is used.
+    pub fn albedo_texture(&self) -> Option<&str> {
+        self.albedo_texture.as_deref()
+    }
+
+    /// Sets name of the albedo texture. If None is passed, mesh texture is used.
+    pub fn set_albedo_texture(&mut self, albedo_texture: Option<String>) {
+        self.albedo_texture = albedo_texture;
+    }
+
+    /// Returns how metallic the surface is, from 0.0 to 1.0.
+    pub fn metallic(&self) -> f32 {
+        self.metallic
+    }
+
+    /// Sets how metallic the surface is. Value is clamped to 0.0 - 1.0 range.
+    pub fn set_metallic(&mut self, metallic: f32) {
+        self.metallic = clamp_unit(metallic);
+    }
+
+    /// Returns surface roughness, from 0.0 to 1.0.
+    pub fn roughness(&self) -> f32 {
+        self.roughness
+    }
+
+    /// Sets surface roughness. Value is clamped to 0.0 - 1.0 range.
+    pub fn set_roughness(&mut self, roughness: f32) {
+        self.roughness = clamp_unit(roughness);
+    }
+
+    /// Returns color of light emitted by the surface.
+    pub fn emissive_color(&self) -> (f32, f32, f32) {
+        self.emissive_color
+    }
+
+    /// Sets color of light emitted by the surface. Components are clamped to 0.0 - 1.0 range.
+    pub fn set_emissive_color(&mut self, r: f32, g: f32, b: f32) {
+        self.emissive_color = (clamp_unit(r), clamp_unit(g), clamp_unit(b));
+    }
+
+    /// Returns how strongly the surface glows. 0.0 means it doesn't emit light.
+    pub fn emissive_intensity(&self) -> f32 {
+        self.emissive_intensity
+    }
+
+    /// Sets how strongly the surface glows. Values above 1.0 make it brighter than fully lit white surface,
+    /// so that it blooms when the camera has bloom enabled. Negative values are clamped to 0.
+    pub fn set_emissive_intensity(&mut self, intensity: f32) {
+        self.emissive_intensity = intensity.max(0.0);
+    }
+
+    /// Returns blend mode used when rendering objects with this material.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets blend mode used when rendering objects with this material.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns which faces are discarded when rendering objects with this material.
+    pub fn cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    /// Sets which faces are discarded when rendering objects with this material.
+    pub fn set_cull_mode(&mut self, cull_mode: CullMode) {
+        self.cull_mode = cull_mode;
+    }
+
+    /// Returns winding order of front faces of objects with this material.
+    pub fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    /// Sets winding order of front faces. Meshes with reversed winding need `FrontFace::Clockwise` to be culled and lit correctly.
+    pub fn set_front_face(&mut self, front_face: FrontFace) {
+        self.front_face = front_face;
+    }
+
+    /// Returns offset added to depth of objects with this material.
+    pub fn depth_bias(&self) -> f32 {
+        self.depth_bias
+    }
+
+    /// Sets offset added to depth of objects with this material, in steps of a 16 bit depth buffer.
+    /// Negative values pull surfaces toward the camera, which keeps decals laid over surfaces from z-fighting with them,
+    /// positive values push them away.
+    pub fn set_depth_bias(&mut self, depth_bias: f32) {
+        self.depth_bias = depth_bias;
+    }
+
+    /// Declares custom shader parameter with given default value, or changes default of an already declared one.
+    /// Values of parameters are passed to shaders in `custom` vector of material data, in order of declaration.
+    /// The built-in shader reads the first parameter as glow, which makes the object emit light of its own color,
+    /// and the second one as fade, which makes it transparent at 1. Objects fading out need a blended material.
+    /// Returns false if the material already declares `MAX_CUSTOM_PARAMS` other parameters.
+    pub fn declare_custom_param<S: Into<String>>(&mut self, name: S, default: f32) -> bool {
+        let name = name.into();
+        if let Some(param) = self.custom_params.iter_mut().find(|param| param.name == name) {
+            param.default = default;
+            return true;
+        }
+        if self.custom_params.len() >= MAX_CUSTOM_PARAMS {
+            return false;
+        }
+        self.custom_params.push(CustomParam { name, default });
+        true
+    }
+
+    /// Removes declaration of custom shader parameter. Parameters declared after it move to the previous component.
+    pub fn remove_custom_param(&mut self, name: &str) -> Option<CustomParam> {
+        let index = self.custom_params.iter().position(|param| param.name == name)?;
+        Some(self.custom_params.remove(index))
+    }
+
+    /// Returns custom shader parameters declared by this material, in order of their components.
+    pub fn custom_params(&self) -> &[CustomParam] {
+        &self.custom_params
+    }
+
+    /// Returns material parameters as uniform data, with default values of custom parameters.
+    #[cfg(feature = "vulkan")]
+    pub fn shader_data(&self) -> MaterialData {
+        self.object_shader_data(&BTreeMap::new())
+    }
+
+    /// Returns material parameters as uniform data, with values of custom parameters set by an object.
+    #[cfg(feature = "vulkan")]
+    pub fn object_shader_data(&self, params: &BTreeMap<String, f32>) -> MaterialData {
+        let (r, g, b, a) = self.base_color;
+        let (emissive_r, emissive_g, emissive_b) = self.emissive_color;
+        let mut custom = [0.0; MAX_CUSTOM_PARAMS];
+        for (value, param) in custom.iter_mut().zip(&self.custom_params) {
+            *value = params.get(&param.name).cloned().unwrap_or(param.default);
+        }
+        MaterialData {
+            base_color: [r, g, b, a],
+            parameters: [self.metallic, self.roughness, 0.0, 0.0],
+            emissive: [emissive_r, emissive_g, emissive_b, self.emissive_intensity],
+            custom,
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::new(DEFAULT_MATERIAL_NAME)
+    }
+}
+
+fn clamp_unit(value: f32) -> f32 {
+    value.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_parameters_are_clamped() {
+        let mut material = Material::new("test_material");
+
+        material.set_metallic(2.0);
+        material.set_roughness(-1.0);
+        material.set_base_color(0.5, 1.5, 0.0, -0.5);
+        material.set_emissive_color(2.0, 0.5, -1.0);
+        material.set_emissive_intensity(-1.0);
+
+        assert_eq!(1.0, material.metallic());
+        assert_eq!(0.0, material.roughness());
+        assert_eq!((0.5, 1.0, 0.0, 0.0), material.base_color());
+        assert_eq!((1.0, 0.5, 0.0), material.emissive_color());
+        assert_eq!(0.0, material.emissive_intensity());
+    }
+
+    #[test]
+    fn material_survives_serialization() {
+        let mut material = Material::new("test_material");
+        material.set_albedo_texture(Some("test_texture".to_string()));
+        material.set_blend_mode(BlendMode::Additive);
+        material.set_cull_mode(CullMode::Front);
+        material.set_front_face(FrontFace::Clockwise);
+        material.set_depth_bias(-2.0);
+
+        let serialized = ron::ser::to_string(&material).unwrap();
+        let deserialized: Material = ron::de::from_str(&serialized).unwrap();
+
+        assert_eq!(material, deserialized);
+    }
+
+    #[test]
+    #[cfg(feature = "vulkan")]
+    fn objects_override_defaults_of_custom_params() {
+        let mut material = Material::new("test_material");
+        assert!(material.declare_custom_param("wobble_amount", 0.5));
+        assert!(material.declare_custom_param("wobble_speed", 2.0));
+        for name in &["a", "b"] {
+            assert!(material.declare_custom_param(*name, 0.0));
+        }
+        assert!(!material.declare_custom_param("too_many", 0.0));
+
+        let mut params = BTreeMap::new();
+        params.insert("wobble_speed".to_string(), 3.0);
+        params.insert("undeclared".to_string(), 9.0);
+
+        assert_eq!([0.5, 3.0, 0.0, 0.0], material.object_shader_data(&params).custom);
+        assert_eq!([0.5, 2.0, 0.0, 0.0], material.shader_data().custom);
+    }
+}
@@ -1,12 +1,23 @@
 use crate::resource::texture::Texture;
+use crate::math::bounds::Bounds;
+use nalgebra_glm::Vec3;
 use std::sync::Arc;
 
+#[cfg(feature = "vulkan")]
 use vulkano::impl_vertex;
-use vulkano::buffer::ImmutableBuffer;
-use vulkano::buffer::BufferUsage;
-use vulkano::device::Queue;
+
+#[cfg(feature = "vulkan")]
+use crate::renderer::memory::SubBuffer;
+use crate::renderer::renderer_error::RenderError;
+use crate::renderer::upload::AssetUploader;
+use crate::resource::mesh::vertex_format::{MeshVertex, VertexFormat};
+#[cfg(feature = "vulkan")]
+use crate::resource::mesh::vertex_format::MeshVertexBuffer;
+
+pub mod vertex_format;
 
 ///Defines the information a Vertex should have
+#[repr(C)]
 #[derive(Clone,Copy)]
 pub struct Vertex {
     pub position: [f32; 3],
@@ -14,46 +25,110 @@ pub struct Vertex {
     pub tex_coord: [f32; 2],
 }
 
+#[cfg(feature = "vulkan")]
 impl_vertex!(Vertex, position, normal, tex_coord);
 
+/// Primitives which indices of a mesh are assembled into.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Topology {
+    /// Every three indices form a triangle.
+    #[default]
+    TriangleList,
+    /// Every two indices form a line, used by debug meshes and outlines.
+    LineList,
+    /// Every index is a single pixel sized point, used by point clouds.
+    PointList,
+}
+
+impl Topology {
+    pub const ALL: [Topology; 3] = [Topology::TriangleList, Topology::LineList, Topology::PointList];
+
+    /// Returns number of indices forming a single primitive.
+    pub fn indices_per_primitive(self) -> usize {
+        match self {
+            Topology::TriangleList => 3,
+            Topology::LineList => 2,
+            Topology::PointList => 1,
+        }
+    }
+}
+
+/// Vertex and index buffers of a mesh on the GPU.
+#[cfg(feature = "vulkan")]
+struct MeshBuffers {
+    vertex_buffer: MeshVertexBuffer,
+    index_buffer: Arc<SubBuffer<u32>>,
+}
+
 /// Mesh is a collection of vertices, edges and faces that defines shape of object.
 pub struct Mesh {
     name: String,
 
     vertices: Vec<Vertex>,
-    vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
-
     indices: Vec<u32>,
-    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    vertex_format: VertexFormat,
+    // None for meshes kept only on the CPU, which render backends upload themselves
+    #[cfg(feature = "vulkan")]
+    buffers: Option<MeshBuffers>,
+    topology: Topology,
 
     texture: Arc<Texture>,
+
+    bounds: Bounds,
 }
 
 impl Mesh {
-    /// Creates new mesh.
-    pub fn new<S: Into<String>>(name: S, vertices: Vec<Vertex>, indices: Vec<u32>, texture: Arc<Texture>, upload_queue: Arc<Queue>) -> Self {
-        let (vertex_buffer, _buffer_future) = ImmutableBuffer::from_iter(
-            vertices.iter().cloned(),
-            BufferUsage::all(),
-            upload_queue.clone()
-        ).expect("failed to create vertex buffer");
-
-        let (index_buffer, _future) = ImmutableBuffer::from_iter(
-            indices.iter().cloned(),
-            BufferUsage::all(),
-            upload_queue
-        ).expect("failed to create index buffer");
+    /// Creates new mesh, copying its buffers to the GPU on the transfer queue of asset uploader.
+    /// Buffers are sub-allocated from memory blocks shared by all meshes.
+    #[cfg(feature = "vulkan")]
+    pub fn upload<S: Into<String>>(name: S, vertices: Vec<Vertex>, indices: Vec<u32>, texture: Arc<Texture>, uploader: &AssetUploader) -> Result<Self, RenderError> {
+        Mesh::upload_with_format(name, &vertices[..], indices, texture, uploader)
+    }
+
+    /// Creates new mesh with vertices of any vertex format, which are uploaded as they are.
+    /// Vertices converted to the standard format are kept on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn upload_with_format<S: Into<String>, V: MeshVertex>(name: S, vertices: &[V], indices: Vec<u32>, texture: Arc<Texture>, uploader: &AssetUploader) -> Result<Self, RenderError> {
+        let buffers = MeshBuffers {
+            vertex_buffer: V::upload(vertices, uploader)?,
+            index_buffer: uploader.upload_indices(&indices)?,
+        };
+        let mut mesh = Mesh::new(name, vertices, indices, texture);
+        mesh.buffers = Some(buffers);
+        Ok(mesh)
+    }
+
+    /// Creates new mesh kept only on the CPU, which isn't drawn by the Vulkan renderer. Used with render backends, which upload meshes themselves.
+    pub fn new<S: Into<String>, V: MeshVertex>(name: S, vertices: &[V], indices: Vec<u32>, texture: Arc<Texture>) -> Self {
+        let vertices: Vec<Vertex> = vertices.iter().map(MeshVertex::to_standard).collect();
+
+        let bounds = Bounds::from_points(vertices.iter().map(|vertex| Vec3::new(vertex.position[0], vertex.position[1], vertex.position[2])));
 
         Mesh {
             name: name.into(),
-            
-            vertices: vertices,
-            vertex_buffer: vertex_buffer,
 
-            indices: indices,
-            index_buffer: index_buffer,
+            vertices,
+            indices,
+            vertex_format: V::FORMAT,
+            #[cfg(feature = "vulkan")]
+            buffers: None,
+            topology: Topology::default(),
 
             texture,
+
+            bounds,
+        }
+    }
+
+    /// Creates new mesh, uploading it with given uploader or keeping it on the CPU without one.
+    pub fn create<S: Into<String>, V: MeshVertex>(name: S, vertices: &[V], indices: Vec<u32>, texture: Arc<Texture>,
+                                                  uploader: Option<&AssetUploader>) -> Result<Self, RenderError> {
+        match uploader {
+            #[cfg(feature = "vulkan")]
+            Some(uploader) => Mesh::upload_with_format(name, vertices, indices, texture, uploader),
+            #[cfg(not(feature = "vulkan"))]
+            Some(uploader) => match *uploader {},
+            None => Ok(Mesh::new(name, vertices, indices, texture)),
         }
     }
 
@@ -72,13 +147,71 @@ impl Mesh {
         self.texture.clone()
     }
 
-    /// Returns the vertex buffer of this mesh.
-    pub fn vertex_buffer(&self) -> Arc<ImmutableBuffer<[Vertex]>> {
-        self.vertex_buffer.clone()
+    /// Returns bounds of this mesh in model space.
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Returns format of vertices in the vertex buffer of this mesh.
+    pub fn vertex_format(&self) -> VertexFormat {
+        self.vertex_format
+    }
+
+    /// Returns vertices of this mesh in the standard format.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// Returns indices of this mesh, which are assembled into primitives according to its topology.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Returns primitives which indices of this mesh form.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Sets primitives which indices of this mesh form. Meshes are triangle lists by default.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// Returns number of primitives in this mesh.
+    pub fn primitive_count(&self) -> usize {
+        self.indices.len() / self.topology.indices_per_primitive()
+    }
+
+    /// Returns number of triangles in this mesh, which is zero for line and point meshes.
+    pub fn triangle_count(&self) -> usize {
+        match self.topology {
+            Topology::TriangleList => self.primitive_count(),
+            _ => 0,
+        }
+    }
+
+    /// Returns size of vertex and index buffers of this mesh in bytes.
+    pub fn memory_size(&self) -> u64 {
+        (self.vertices.len() * self.vertex_format().stride() + self.indices.len() * std::mem::size_of::<u32>()) as u64
+    }
+
+    /// Returns the vertex buffer of this mesh, or None if it's kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn vertex_buffer(&self) -> Option<MeshVertexBuffer> {
+        self.buffers.as_ref().map(|buffers| buffers.vertex_buffer.clone())
+    }
+
+    /// Returns the index buffer of this mesh, or None if it's kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn index_buffer(&self) -> Option<Arc<SubBuffer<u32>>> {
+        self.buffers.as_ref().map(|buffers| buffers.index_buffer.clone())
     }
 
-    /// Returns the index buffer of this mesh.
-    pub fn index_buffer(&self) -> Arc<ImmutableBuffer<[u32]>> {
-        self.index_buffer.clone()
+    /// Returns vertex and index buffers drawn by the Vulkan renderer, or an error if the mesh is kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub(crate) fn gpu_buffers(&self) -> Result<(MeshVertexBuffer, Arc<SubBuffer<u32>>), RenderError> {
+        self.buffers.as_ref()
+                    .map(|buffers| (buffers.vertex_buffer.clone(), buffers.index_buffer.clone()))
+                    .ok_or_else(|| RenderError::CpuOnlyAsset(self.name.clone()))
     }
 }
\ No newline at end of file
@@ -0,0 +1,305 @@
+#[cfg(feature = "vulkan")]
+use std::sync::Arc;
+
+#[cfg(feature = "vulkan")]
+use vulkano::buffer::{BufferAccess, BufferUsage, ImmutableBuffer};
+#[cfg(feature = "vulkan")]
+use vulkano::impl_vertex;
+#[cfg(feature = "vulkan")]
+use vulkano::pipeline::vertex::{Vertex as VulkanoVertex, VertexMemberInfo};
+
+#[cfg(feature = "vulkan")]
+use crate::renderer::memory::SubBuffer;
+#[cfg(feature = "vulkan")]
+use crate::renderer::renderer_error::RenderError;
+#[cfg(feature = "vulkan")]
+use crate::renderer::upload::AssetUploader;
+use crate::resource::mesh::Vertex;
+
+/// Layout of vertices in the vertex buffer of a mesh. Pipelines are created for every format,
+/// attributes a format lacks are read by shaders as defaults: normals pointing up and zero texture and lightmap coordinates.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum VertexFormat {
+    /// Only positions, see `PositionVertex`.
+    Position,
+    /// Positions and texture coordinates, see `UvVertex`.
+    PositionUv,
+    /// Positions, normals and texture coordinates, see `Vertex`.
+    #[default]
+    Standard,
+    /// Standard attributes with tangents and colors, see `ExtendedVertex`.
+    Extended,
+    /// Standard attributes with joints and their weights, see `SkinnedVertex`.
+    Skinned,
+    /// Standard attributes with a second set of texture coordinates for lightmaps, see `LightmappedVertex`.
+    Lightmapped,
+}
+
+impl VertexFormat {
+    pub const ALL: [VertexFormat; 6] = [
+        VertexFormat::Position,
+        VertexFormat::PositionUv,
+        VertexFormat::Standard,
+        VertexFormat::Extended,
+        VertexFormat::Skinned,
+        VertexFormat::Lightmapped,
+    ];
+
+    /// Returns readable name of the format.
+    pub fn label(self) -> &'static str {
+        match self {
+            VertexFormat::Position => "Position",
+            VertexFormat::PositionUv => "Position, UV",
+            VertexFormat::Standard => "Standard",
+            VertexFormat::Extended => "Extended",
+            VertexFormat::Skinned => "Skinned",
+            VertexFormat::Lightmapped => "Lightmapped",
+        }
+    }
+
+    /// Returns size of a single vertex in bytes.
+    pub fn stride(self) -> usize {
+        match self {
+            VertexFormat::Position => std::mem::size_of::<PositionVertex>(),
+            VertexFormat::PositionUv => std::mem::size_of::<UvVertex>(),
+            VertexFormat::Standard => std::mem::size_of::<Vertex>(),
+            VertexFormat::Extended => std::mem::size_of::<ExtendedVertex>(),
+            VertexFormat::Skinned => std::mem::size_of::<SkinnedVertex>(),
+            VertexFormat::Lightmapped => std::mem::size_of::<LightmappedVertex>(),
+        }
+    }
+
+    /// Returns offset and type of attribute with given name, or None if vertices of this format don't have it.
+    #[cfg(feature = "vulkan")]
+    pub(crate) fn member(self, name: &str) -> Option<VertexMemberInfo> {
+        match self {
+            VertexFormat::Position => <PositionVertex as VulkanoVertex>::member(name),
+            VertexFormat::PositionUv => <UvVertex as VulkanoVertex>::member(name),
+            VertexFormat::Standard => <Vertex as VulkanoVertex>::member(name),
+            VertexFormat::Extended => <ExtendedVertex as VulkanoVertex>::member(name),
+            VertexFormat::Skinned => <SkinnedVertex as VulkanoVertex>::member(name),
+            VertexFormat::Lightmapped => <LightmappedVertex as VulkanoVertex>::member(name),
+        }
+    }
+}
+
+/// Vertex with only a position, e.g. for collision or shadow geometry.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PositionVertex {
+    pub position: [f32; 3],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(PositionVertex, position);
+
+/// Vertex with a position and texture coordinates, e.g. for unlit geometry.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct UvVertex {
+    pub position: [f32; 3],
+    pub tex_coord: [f32; 2],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(UvVertex, position, tex_coord);
+
+/// Vertex with tangents for normal mapping, with handedness of the bitangent in w, and vertex colors.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ExtendedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub tangent: [f32; 4],
+    pub color: [f32; 4],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(ExtendedVertex, position, normal, tex_coord, tangent, color);
+
+/// Vertex influenced by up to four joints of a skeleton, whose weights should add up to one.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SkinnedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(SkinnedVertex, position, normal, tex_coord, joints, weights);
+
+/// Vertex of static geometry with baked lighting, whose lightmap coordinates address its own, non-overlapping area of the lightmap.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LightmappedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub lightmap_coord: [f32; 2],
+}
+
+#[cfg(feature = "vulkan")]
+impl_vertex!(LightmappedVertex, position, normal, tex_coord, lightmap_coord);
+
+/// Vertex of one of the vertex formats, which meshes can be uploaded with.
+pub trait MeshVertex: Copy + Send + Sync + 'static {
+    /// Format of vertices of this type.
+    const FORMAT: VertexFormat;
+
+    /// Returns this vertex in the standard format, which is kept on the CPU for bounds, picking and static batching.
+    fn to_standard(&self) -> Vertex;
+
+    /// Creates vertex buffer with given vertices. Buffers get device memory of their own by default.
+    #[cfg(feature = "vulkan")]
+    fn upload(vertices: &[Self], uploader: &AssetUploader) -> Result<MeshVertexBuffer, RenderError> {
+        let buffer = uploader.upload_buffer(vertices.iter().cloned(), BufferUsage::vertex_buffer())?;
+        Ok(MeshVertexBuffer::from_buffer(buffer))
+    }
+}
+
+impl MeshVertex for PositionVertex {
+    const FORMAT: VertexFormat = VertexFormat::Position;
+
+    fn to_standard(&self) -> Vertex {
+        Vertex { position: self.position, normal: [0.0, 1.0, 0.0], tex_coord: [0.0; 2] }
+    }
+}
+
+impl MeshVertex for UvVertex {
+    const FORMAT: VertexFormat = VertexFormat::PositionUv;
+
+    fn to_standard(&self) -> Vertex {
+        Vertex { position: self.position, normal: [0.0, 1.0, 0.0], tex_coord: self.tex_coord }
+    }
+}
+
+impl MeshVertex for Vertex {
+    const FORMAT: VertexFormat = VertexFormat::Standard;
+
+    fn to_standard(&self) -> Vertex {
+        *self
+    }
+
+    /// Standard vertices are sub-allocated from memory blocks shared by all meshes, so that meshes can be drawn together by indirect draws.
+    #[cfg(feature = "vulkan")]
+    fn upload(vertices: &[Self], uploader: &AssetUploader) -> Result<MeshVertexBuffer, RenderError> {
+        Ok(MeshVertexBuffer::from_sub_buffer(uploader.upload_vertices(vertices)?))
+    }
+}
+
+impl MeshVertex for ExtendedVertex {
+    const FORMAT: VertexFormat = VertexFormat::Extended;
+
+    fn to_standard(&self) -> Vertex {
+        Vertex { position: self.position, normal: self.normal, tex_coord: self.tex_coord }
+    }
+}
+
+impl MeshVertex for SkinnedVertex {
+    const FORMAT: VertexFormat = VertexFormat::Skinned;
+
+    fn to_standard(&self) -> Vertex {
+        Vertex { position: self.position, normal: self.normal, tex_coord: self.tex_coord }
+    }
+}
+
+impl MeshVertex for LightmappedVertex {
+    const FORMAT: VertexFormat = VertexFormat::Lightmapped;
+
+    fn to_standard(&self) -> Vertex {
+        Vertex { position: self.position, normal: self.normal, tex_coord: self.tex_coord }
+    }
+}
+
+/// Vertex buffer of a mesh in any vertex format. Buffers sub-allocated from a memory block are addressed
+/// by their offset in the block, other buffers are blocks of their own.
+#[cfg(feature = "vulkan")]
+#[derive(Clone)]
+pub struct MeshVertexBuffer {
+    format: VertexFormat,
+    buffer: Arc<BufferAccess + Send + Sync>,
+    block_buffer: Arc<BufferAccess + Send + Sync>,
+    block_key: usize,
+    block_offset: usize,
+}
+
+#[cfg(feature = "vulkan")]
+impl MeshVertexBuffer {
+    pub(crate) fn from_sub_buffer<V: MeshVertex>(buffer: Arc<SubBuffer<V>>) -> Self {
+        MeshVertexBuffer {
+            format: V::FORMAT,
+            block_buffer: buffer.block_buffer(),
+            block_key: buffer.block_key(),
+            block_offset: buffer.block_offset(),
+            buffer,
+        }
+    }
+
+    pub(crate) fn from_buffer<V: MeshVertex>(buffer: Arc<ImmutableBuffer<[V]>>) -> Self {
+        MeshVertexBuffer {
+            format: V::FORMAT,
+            block_buffer: buffer.clone(),
+            block_key: &*buffer as *const ImmutableBuffer<[V]> as usize,
+            block_offset: 0,
+            buffer,
+        }
+    }
+
+    /// Returns format of vertices in the buffer.
+    pub fn format(&self) -> VertexFormat {
+        self.format
+    }
+
+    /// Returns number of vertices in the buffer.
+    pub fn len(&self) -> usize {
+        self.buffer.size() / self.format.stride()
+    }
+
+    /// Returns true if the buffer has no vertices.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the buffer itself.
+    pub fn buffer(&self) -> Arc<BufferAccess + Send + Sync> {
+        self.buffer.clone()
+    }
+
+    /// Returns buffer of the whole memory block the buffer was allocated from.
+    pub fn block_buffer(&self) -> Arc<BufferAccess + Send + Sync> {
+        self.block_buffer.clone()
+    }
+
+    /// Returns key which is equal for buffers allocated from the same memory block.
+    pub fn block_key(&self) -> usize {
+        self.block_key
+    }
+
+    /// Returns index of the first vertex of the buffer in its memory block.
+    pub fn block_offset(&self) -> usize {
+        self.block_offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_have_sizes_of_their_vertices() {
+        assert_eq!(64, VertexFormat::Extended.stride());
+        assert_eq!([0.0, 1.0, 0.0], PositionVertex { position: [1.0, 2.0, 3.0] }.to_standard().normal);
+    }
+
+    #[cfg(feature = "vulkan")]
+    #[test]
+    fn formats_have_attributes_of_their_vertices() {
+        assert!(VertexFormat::Position.member("normal").is_none());
+        assert_eq!(12, VertexFormat::PositionUv.member("tex_coord").unwrap().offset);
+        assert!(VertexFormat::Skinned.member("weights").is_some());
+    }
+}
@@ -0,0 +1,324 @@
+use std::path::Path;
+
+use image::{DynamicImage, GrayAlphaImage, GrayImage, RgbImage, RgbaImage};
+use log::*;
+use nalgebra_glm as glm;
+use nalgebra_glm::{U3, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::resource::mesh::Vertex;
+use crate::resource::model::model_error::ModelError;
+use crate::resource::texture;
+
+pub mod model_error;
+
+/// Extension of models converted to the binary model format, which loads without parsing text or decoding images.
+pub const BINARY_MODEL_EXTENSION: &str = "kmodel";
+/// Start of files in the binary model format, followed by the version of the format.
+const BINARY_MODEL_MAGIC: &[u8; 4] = b"KMDL";
+const BINARY_MODEL_VERSION: u32 = 2;
+
+/// Part of a loaded model drawn with a single texture.
+pub struct ModelMesh {
+    pub name: String,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    /// Index of the texture in textures of the model, or None if the part isn't textured.
+    pub texture: Option<usize>,
+    /// Lightmap coordinates of vertices, read from the second set of texture coordinates of glTF models.
+    pub lightmap_coords: Option<Vec<[f32; 2]>>,
+}
+
+/// Meshes and textures of a model loaded from a file, kept on the CPU until they're uploaded by asset manager.
+pub struct ModelData {
+    pub meshes: Vec<ModelMesh>,
+    pub textures: Vec<DynamicImage>,
+}
+
+impl ModelData {
+    /// Loads model from an OBJ, glTF 2.0 or binary model file, chosen by its extension. Faces are triangulated,
+    /// and meshes without normals get smooth normals computed from their faces.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ModelError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("").to_lowercase();
+        match extension.as_str() {
+            "obj" => load_obj(path),
+            "gltf" | "glb" => load_gltf(path),
+            BINARY_MODEL_EXTENSION => ModelData::from_binary(&std::fs::read(path)?),
+            _ => Err(ModelError::UnsupportedFormat(extension)),
+        }
+    }
+
+    /// Reads model in the binary model format, e.g. from an asset pack.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ModelError> {
+        let mut version = [0; 4];
+        if bytes.len() < 8 || &bytes[..4] != BINARY_MODEL_MAGIC {
+            return Err(ModelError::InvalidBinary);
+        }
+        version.copy_from_slice(&bytes[4..8]);
+        if u32::from_le_bytes(version) != BINARY_MODEL_VERSION {
+            return Err(ModelError::InvalidBinary);
+        }
+        let binary_model: BinaryModel = bincode::deserialize(&bytes[8..])?;
+        let textures = binary_model.textures.iter().map(|texture| texture::image_from_binary(texture)).collect::<Result<Vec<DynamicImage>, _>>()?;
+        let meshes = binary_model.meshes.into_iter().map(|mesh| ModelMesh {
+            name: mesh.name,
+            vertices: mesh.vertices.into_iter().map(|vertex| Vertex {
+                position: [vertex[0], vertex[1], vertex[2]],
+                normal: [vertex[3], vertex[4], vertex[5]],
+                tex_coord: [vertex[6], vertex[7]],
+            }).collect(),
+            indices: mesh.indices,
+            texture: mesh.texture,
+            lightmap_coords: mesh.lightmap_coords,
+        }).collect();
+        Ok(ModelData { meshes, textures })
+    }
+
+    /// Returns model in the binary model format. Textures are stored uncompressed, so they don't have to be decoded when the model is loaded.
+    pub fn to_binary(&self) -> Result<Vec<u8>, ModelError> {
+        let binary_model = BinaryModel {
+            meshes: self.meshes.iter().map(|mesh| BinaryMesh {
+                name: mesh.name.clone(),
+                vertices: mesh.vertices.iter().map(|vertex| {
+                    let (position, normal, tex_coord) = (vertex.position, vertex.normal, vertex.tex_coord);
+                    [position[0], position[1], position[2], normal[0], normal[1], normal[2], tex_coord[0], tex_coord[1]]
+                }).collect(),
+                indices: mesh.indices.clone(),
+                texture: mesh.texture,
+                lightmap_coords: mesh.lightmap_coords.clone(),
+            }).collect(),
+            textures: self.textures.iter().map(texture::image_to_binary).collect(),
+        };
+        let mut bytes = BINARY_MODEL_MAGIC.to_vec();
+        bytes.extend_from_slice(&BINARY_MODEL_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(&binary_model)?);
+        Ok(bytes)
+    }
+
+    /// Saves model in the binary model format, which should have `BINARY_MODEL_EXTENSION` to be loaded by `load`.
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> Result<(), ModelError> {
+        std::fs::write(path, self.to_binary()?)?;
+        Ok(())
+    }
+
+    /// Returns names meshes of the model get when it's added to asset manager with given name.
+    /// A model with a single mesh names it with the model name, otherwise meshes are named with their index, e.g. "ship/0".
+    pub fn mesh_names(&self, name: &str) -> Vec<String> {
+        if self.meshes.len() == 1 {
+            return vec![name.to_string()];
+        }
+        (0..self.meshes.len()).map(|index| format!("{}/{}", name, index)).collect()
+    }
+
+    /// Returns names textures of the model get when it's added to asset manager with given name, e.g. "ship/texture0".
+    pub fn texture_names(&self, name: &str) -> Vec<String> {
+        (0..self.textures.len()).map(|index| format!("{}/texture{}", name, index)).collect()
+    }
+}
+
+/// Model in the binary model format. Vertices are stored as position, normal and texture coordinates,
+/// and textures in the binary texture format.
+#[derive(Serialize, Deserialize)]
+struct BinaryModel {
+    meshes: Vec<BinaryMesh>,
+    textures: Vec<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryMesh {
+    name: String,
+    vertices: Vec<[f32; 8]>,
+    indices: Vec<u32>,
+    texture: Option<usize>,
+    lightmap_coords: Option<Vec<[f32; 2]>>,
+}
+
+
+/// Loads OBJ model. Diffuse textures of its materials are loaded relative to the model, missing ones are skipped with a warning.
+fn load_obj(path: &Path) -> Result<ModelData, ModelError> {
+    let (models, materials) = tobj::load_obj(path)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut textures = Vec::new();
+    let material_textures: Vec<Option<usize>> = materials.iter().map(|material| {
+        if material.diffuse_texture.is_empty() {
+            return None;
+        }
+        match texture::open_image(directory.join(&material.diffuse_texture)) {
+            Ok(image) => {
+                textures.push(image);
+                Some(textures.len() - 1)
+            },
+            Err(err) => {
+                warn!("Couldn't load texture {} of material {}: {}", material.diffuse_texture, material.name, err);
+                None
+            },
+        }
+    }).collect();
+
+    let meshes = models.into_iter().map(|model| {
+        let mesh = model.mesh;
+        let vertex_count = mesh.positions.len() / 3;
+        let has_normals = mesh.normals.len() == vertex_count * 3;
+        let has_tex_coords = mesh.texcoords.len() == vertex_count * 2;
+        let mut vertices: Vec<Vertex> = (0..vertex_count).map(|index| Vertex {
+            position: [mesh.positions[index * 3], mesh.positions[index * 3 + 1], mesh.positions[index * 3 + 2]],
+            normal: if has_normals { [mesh.normals[index * 3], mesh.normals[index * 3 + 1], mesh.normals[index * 3 + 2]] } else { [0.0; 3] },
+            tex_coord: if has_tex_coords { [mesh.texcoords[index * 2], mesh.texcoords[index * 2 + 1]] } else { [0.0; 2] },
+        }).collect();
+        if !has_normals {
+            compute_normals(&mut vertices, &mesh.indices);
+        }
+        ModelMesh {
+            name: model.name,
+            vertices,
+            indices: mesh.indices,
+            texture: mesh.material_id.and_then(|id| material_textures.get(id).cloned().unwrap_or(None)),
+            lightmap_coords: None,
+        }
+    }).collect();
+
+    Ok(ModelData { meshes, textures })
+}
+
+/// Loads glTF model. Every triangle primitive becomes a mesh textured with its base color texture.
+/// Vertices stay in space of their mesh, transforms of nodes aren't applied.
+fn load_gltf(path: &Path) -> Result<ModelData, ModelError> {
+    let (document, buffers, images) = gltf::import(path)?;
+    let textures = images.into_iter().enumerate().map(|(index, image)| {
+        let (width, height) = (image.width, image.height);
+        let pixel_count = (width as usize * height as usize).max(1);
+        let image = match image.pixels.len() / pixel_count {
+            1 => GrayImage::from_raw(width, height, image.pixels).map(DynamicImage::ImageLuma8),
+            2 => GrayAlphaImage::from_raw(width, height, image.pixels).map(DynamicImage::ImageLumaA8),
+            3 => RgbImage::from_raw(width, height, image.pixels).map(DynamicImage::ImageRgb8),
+            4 => RgbaImage::from_raw(width, height, image.pixels).map(DynamicImage::ImageRgba8),
+            _ => None,
+        };
+        image.ok_or(ModelError::UnsupportedImage(index))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let mesh_name = mesh.name().map(str::to_string).unwrap_or_else(|| format!("mesh{}", mesh.index()));
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                warn!("Skipped primitive of mesh {} which isn't made of triangles", mesh_name);
+                continue;
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions: Vec<[f32; 3]> = match reader.read_positions() {
+                Some(positions) => positions.collect(),
+                None => {
+                    warn!("Skipped primitive of mesh {} without positions", mesh_name);
+                    continue;
+                },
+            };
+            let normals = reader.read_normals().map(|normals| normals.collect::<Vec<[f32; 3]>>())
+                                .filter(|normals| normals.len() == positions.len());
+            let tex_coords: Option<Vec<[f32; 2]>> = reader.read_tex_coords(0).map(|tex_coords| tex_coords.into_f32().collect());
+            let lightmap_coords: Option<Vec<[f32; 2]>> = reader.read_tex_coords(1)
+                                                               .map(|tex_coords| tex_coords.into_f32().map(|uv| [uv[0], 1.0 - uv[1]]).collect())
+                                                               .filter(|lightmap_coords: &Vec<[f32; 2]>| lightmap_coords.len() == positions.len());
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            let mut vertices: Vec<Vertex> = positions.iter().enumerate().map(|(index, position)| Vertex {
+                position: *position,
+                normal: normals.as_ref().map_or([0.0; 3], |normals| normals[index]),
+                // glTF images start at the top, textures are sampled from the bottom
+                tex_coord: tex_coords.as_ref().and_then(|tex_coords| tex_coords.get(index)).map_or([0.0; 2], |uv| [uv[0], 1.0 - uv[1]]),
+            }).collect();
+            if normals.is_none() {
+                compute_normals(&mut vertices, &indices);
+            }
+            let texture = primitive.material().pbr_metallic_roughness().base_color_texture().map(|info| info.texture().source().index());
+            meshes.push(ModelMesh {
+                name: mesh_name.clone(),
+                vertices,
+                indices,
+                texture,
+                lightmap_coords,
+            });
+        }
+    }
+
+    Ok(ModelData { meshes, textures })
+}
+
+/// Sets normals of vertices to averages of normals of faces using them, weighted by face area.
+fn compute_normals(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); vertices.len()];
+    for triangle in indices.chunks(3).filter(|triangle| triangle.len() == 3) {
+        let corners: Vec<usize> = triangle.iter().map(|index| *index as usize).collect();
+        if corners.iter().any(|corner| *corner >= vertices.len()) {
+            continue;
+        }
+        let position = |corner: usize| {
+            let position = vertices[corner].position;
+            Vec3::new(position[0], position[1], position[2])
+        };
+        // length of the cross product is twice the area of the face
+        let face_normal = glm::cross::<f32, U3>(&(position(corners[1]) - position(corners[0])), &(position(corners[2]) - position(corners[0])));
+        for corner in corners {
+            normals[corner] += face_normal;
+        }
+    }
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        let normal = if glm::length(&normal) > 0.0 { glm::normalize(&normal) } else { Vec3::new(0.0, 1.0, 0.0) };
+        vertex.normal = [normal.x, normal.y, normal.z];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obj_without_normals_gets_smooth_normals() {
+        let path = std::env::temp_dir().join("ketch_model_test_quad.obj");
+        std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 1 0 -1\nv 0 0 -1\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4\n").unwrap();
+
+        let model = ModelData::load(&path).unwrap();
+
+        assert_eq!(1, model.meshes.len());
+        let mesh = &model.meshes[0];
+        assert_eq!(6, mesh.indices.len());
+        assert!(mesh.vertices.iter().all(|vertex| vertex.normal == [0.0, 1.0, 0.0]));
+        assert_eq!(None, mesh.texture);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn binary_model_keeps_meshes_and_textures() {
+        let model = ModelData {
+            meshes: vec![ModelMesh {
+                name: "quad".to_string(),
+                vertices: vec![Vertex { position: [1.0, 2.0, 3.0], normal: [0.0, 1.0, 0.0], tex_coord: [0.5, 0.25] }],
+                indices: vec![0, 0, 0],
+                texture: Some(0),
+                lightmap_coords: Some(vec![[0.75, 1.0]]),
+            }],
+            textures: vec![DynamicImage::ImageRgba8(RgbaImage::from_raw(1, 1, vec![10, 20, 30, 255]).unwrap())],
+        };
+
+        let loaded = ModelData::from_binary(&model.to_binary().unwrap()).unwrap();
+
+        let mesh = &loaded.meshes[0];
+        assert_eq!([0.5, 0.25], mesh.vertices[0].tex_coord);
+        assert_eq!(vec![0, 0, 0], mesh.indices);
+        assert_eq!(Some(vec![[0.75, 1.0]]), mesh.lightmap_coords);
+        assert_eq!(vec![10, 20, 30, 255], loaded.textures[0].to_rgba().into_raw());
+        assert_eq!(vec!["quad".to_string()], loaded.mesh_names("quad"));
+        assert!(ModelData::from_binary(b"KMDL").is_err());
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        match ModelData::load("model.fbx") {
+            Err(ModelError::UnsupportedFormat(extension)) => assert_eq!("fbx", extension),
+            _ => panic!("fbx model shouldn't load"),
+        }
+    }
+}
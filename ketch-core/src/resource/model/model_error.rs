@@ -0,0 +1,48 @@
+use quick_error::quick_error;
+
+use crate::renderer::renderer_error::RenderError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum ModelError {
+        ObjError(err: tobj::LoadError) {
+            from()
+            display("ObjError: {}", err)
+            cause(err)
+        }
+        GltfError(err: gltf::Error) {
+            from()
+            display("GltfError: {}", err)
+            cause(err)
+        }
+        ImageError(err: image::ImageError) {
+            from()
+            display("ImageError: {}", err)
+            cause(err)
+        }
+        RenderError(err: RenderError) {
+            from()
+            display("RenderError: {}", err)
+            cause(err)
+        }
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        BinaryError(err: bincode::Error) {
+            from()
+            display("BinaryError: {}", err)
+            cause(err)
+        }
+        InvalidBinary {
+            display("data isn't a model in a supported version of the binary model format")
+        }
+        UnsupportedFormat(extension: String) {
+            display("only OBJ, glTF and binary models can be loaded: {}", extension)
+        }
+        UnsupportedImage(index: usize) {
+            display("image of the model has pixels with more than four channels: {}", index)
+        }
+    }
+}
@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
 use std::sync::RwLock;
 use std::sync::Arc;
 use crate::resource::mesh::Mesh;
+use crate::resource::camera::Camera;
+use crate::resource::material::Material;
+use crate::resource::sound::SoundEmitter;
+use crate::resource::particle_effect::ParticleEmitter;
+use crate::resource::rigid_body::RigidBody;
+use crate::resource::collider::Collider;
+use crate::resource::lightmap::Lightmap;
+use crate::resource::render_queue::RenderQueue;
+use crate::resource::render_layers::RenderLayers;
+use crate::math::bounds::Bounds;
 use nalgebra_glm::{Mat4, Vec3};
 
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
@@ -30,11 +41,31 @@ pub struct Object {
     scaling_matrix: Mat4, 
 
     model_matrix: Mat4,
+    // world transform computed by `Scene::update_transforms`, valid while the transform isn't changed
+    world_matrix: Mat4,
+    world_bounds: Option<Bounds>,
+    transform_changed: bool,
 
     light_source: bool,
     uniform_scale: bool,
 
+    render_queue: Option<RenderQueue>,
+    sort_key: i32,
+    render_layers: RenderLayers,
+    is_static: bool,
+    // set while the object is drawn by a static batch of its scene
+    batched: bool,
+
     mesh: Option<Arc<RwLock<Mesh>>>,
+    camera: Option<Camera>,
+    material: Option<Arc<RwLock<Material>>>,
+    sound_emitter: Option<SoundEmitter>,
+    particle_emitter: Option<ParticleEmitter>,
+    rigid_body: Option<RigidBody>,
+    collider: Option<Collider>,
+    lightmap: Option<Lightmap>,
+    parent: Option<u32>,
+    params: BTreeMap<String, f32>,
 }
 
 
@@ -155,6 +186,7 @@ impl Object {
         self.scale_z = scale_z;
         self.update_scaling_matrix();
         self.update_model_matrix();
+        self.update_uniform_scale();
     }
 
     /// Returns object x position.
@@ -221,7 +253,7 @@ impl Object {
         self.uniform_scale
     }
 
-    /// Returns true if this object is a light source.
+    /// Returns true if this object is a light source. Light sources are drawn with an emissive material glowing with color of the main light.
     pub fn light_source(&self) -> bool {
         self.light_source
     }
@@ -231,6 +263,56 @@ impl Object {
         self.light_source = value;
     }
 
+    /// Returns render queue chosen for this object or None if it's picked based on blend mode of its material.
+    pub fn render_queue(&self) -> Option<RenderQueue> {
+        self.render_queue
+    }
+
+    /// Sets render queue the object is drawn in. If None is passed, it's picked based on blend mode of its material.
+    pub fn set_render_queue(&mut self, render_queue: Option<RenderQueue>) {
+        self.render_queue = render_queue;
+    }
+
+    /// Returns sort key of this object. Objects in the same render queue are drawn from the lowest to the highest sort key.
+    pub fn sort_key(&self) -> i32 {
+        self.sort_key
+    }
+
+    /// Sets sort key of this object. Objects with the same sort key are drawn in scene order,
+    /// except for transparent ones, which are drawn from the farthest.
+    pub fn set_sort_key(&mut self, sort_key: i32) {
+        self.sort_key = sort_key;
+    }
+
+    /// Returns render layers the object belongs to.
+    pub fn render_layers(&self) -> RenderLayers {
+        self.render_layers
+    }
+
+    /// Sets render layers the object belongs to. Cameras only draw objects in layers of their visibility mask.
+    pub fn set_render_layers(&mut self, render_layers: RenderLayers) {
+        self.render_layers = render_layers;
+    }
+
+    /// Returns true if the object was marked as static geometry, which doesn't move and can be merged into static batches.
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    /// Marks the object as static geometry. Static objects are merged into batches by `Scene::bake_static_batches`.
+    pub fn set_static(&mut self, is_static: bool) {
+        self.is_static = is_static;
+    }
+
+    /// Returns true if the object is drawn by a static batch instead of its own mesh.
+    pub fn is_batched(&self) -> bool {
+        self.batched
+    }
+
+    pub(crate) fn set_batched(&mut self, batched: bool) {
+        self.batched = batched;
+    }
+
     /// Returns model matrix.
     pub fn model_matrix(&self) -> Mat4 {
         self.model_matrix
@@ -247,33 +329,154 @@ impl Object {
     /// Sets object mesh.
     pub fn set_mesh(&mut self, mesh: Arc<RwLock<Mesh>>) {
         self.mesh = Some(mesh);
+        self.transform_changed = true;
     }
 
-    fn update_translation_matrix(&mut self) {
-        self.translation_matrix = position_to_translation_matrix(self.position_x, self.position_y, self.position_z);
+    /// Returns camera attached to this object or None if it doesn't have one.
+    pub fn camera(&self) -> Option<&Camera> {
+        self.camera.as_ref()
     }
 
-    fn update_rotation_matrix(&mut self) {
-        self.rotation_matrix = rotation_of_axes_to_rotation_matrix(self.rotation_angle_x, self.rotation_angle_y, self.rotation_angle_z);
+    /// Returns a mutable reference to camera attached to this object.
+    pub fn camera_mut(&mut self) -> Option<&mut Camera> {
+        self.camera.as_mut()
     }
 
-    fn update_scaling_matrix(&mut self) {
-        self.scaling_matrix = scale_of_axes_to_scale_matrix(self.scale_x, self.scale_y, self.scale_z);
+    /// Attaches camera to this object or removes it if None is passed.
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
     }
 
-    fn update_model_matrix(&mut self) {
-        self.model_matrix = create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix);
+    /// Returns material of this object or None if it uses the default material.
+    pub fn material(&self) -> Option<Arc<RwLock<Material>>> {
+        self.material.clone()
     }
 
-    fn update_uniform_scale(&mut self) {
-        self.uniform_scale = self.scale_x == self.scale_y && self.scale_y == self.scale_z;
+    /// Sets material of this object. If None is passed, the default material is used.
+    pub fn set_material(&mut self, material: Option<Arc<RwLock<Material>>>) {
+        self.material = material;
     }
-}
 
-impl<'a> Clone for Object {
-    fn clone(&self) -> Object {
+    /// Returns sound emitter attached to this object or None if it doesn't have one.
+    pub fn sound_emitter(&self) -> Option<&SoundEmitter> {
+        self.sound_emitter.as_ref()
+    }
+
+    /// Attaches sound emitter to this object or removes it if None is passed.
+    pub fn set_sound_emitter(&mut self, sound_emitter: Option<SoundEmitter>) {
+        self.sound_emitter = sound_emitter;
+    }
+
+    /// Returns particle emitter attached to this object or None if it doesn't have one.
+    pub fn particle_emitter(&self) -> Option<&ParticleEmitter> {
+        self.particle_emitter.as_ref()
+    }
+
+    /// Returns mutable reference to particle emitter attached to this object, used to start and stop it.
+    pub fn particle_emitter_mut(&mut self) -> Option<&mut ParticleEmitter> {
+        self.particle_emitter.as_mut()
+    }
+
+    /// Attaches particle emitter to this object or removes it if None is passed.
+    pub fn set_particle_emitter(&mut self, particle_emitter: Option<ParticleEmitter>) {
+        self.particle_emitter = particle_emitter;
+    }
+
+    /// Returns rigid body component of this object or None if it isn't simulated by physics.
+    pub fn rigid_body(&self) -> Option<&RigidBody> {
+        self.rigid_body.as_ref()
+    }
+
+    /// Sets rigid body component or removes it if None is passed.
+    pub fn set_rigid_body(&mut self, rigid_body: Option<RigidBody>) {
+        self.rigid_body = rigid_body;
+    }
+
+    /// Returns collider attached to this object or None if it doesn't have one.
+    pub fn collider(&self) -> Option<&Collider> {
+        self.collider.as_ref()
+    }
+
+    /// Attaches collider to this object or removes it if None is passed.
+    pub fn set_collider(&mut self, collider: Option<Collider>) {
+        self.collider = collider;
+    }
+
+    /// Returns baked lighting of this object or None if it's lit dynamically.
+    pub fn lightmap(&self) -> Option<&Lightmap> {
+        self.lightmap.as_ref()
+    }
+
+    /// Sets baked lighting of this object or removes it if None is passed. Lightmapped objects aren't merged into static batches.
+    pub fn set_lightmap(&mut self, lightmap: Option<Lightmap>) {
+        self.lightmap = lightmap;
+    }
+
+    /// Sets value of a custom shader parameter declared by material of this object, e.g. `object.set_param("wobble_amount", 0.3)`.
+    /// Values of parameters which the material doesn't declare are kept, but not used by the renderer.
+    pub fn set_param<S: Into<String>>(&mut self, name: S, value: f32) {
+        self.params.insert(name.into(), value);
+    }
+
+    /// Returns value of a custom shader parameter set on this object, or None if the material default is used.
+    pub fn param(&self, name: &str) -> Option<f32> {
+        self.params.get(name).cloned()
+    }
+
+    /// Removes value of a custom shader parameter, so that the material default is used again.
+    pub fn remove_param(&mut self, name: &str) -> Option<f32> {
+        self.params.remove(name)
+    }
+
+    /// Returns values of custom shader parameters set on this object.
+    pub fn params(&self) -> &BTreeMap<String, f32> {
+        &self.params
+    }
+
+    /// Returns id of the parent object or None if this object is a root.
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+
+    /// Sets parent object. Transform of this object becomes relative to the parent.
+    /// Use `Scene::set_parent` to make sure no cycles are created.
+    pub fn set_parent(&mut self, parent: Option<u32>) {
+        self.parent = parent;
+        self.transform_changed = true;
+    }
+
+    /// Returns true if transform, parent or mesh of the object changed since world transforms of its scene were last updated.
+    pub fn transform_changed(&self) -> bool {
+        self.transform_changed
+    }
+
+    /// Makes the scene recompute world transform of the object in the next update.
+    pub(crate) fn mark_transform_changed(&mut self) {
+        self.transform_changed = true;
+    }
+
+    /// Returns world matrix computed by the last `Scene::update_transforms`.
+    pub(crate) fn cached_world_matrix(&self) -> Mat4 {
+        self.world_matrix
+    }
+
+    /// Returns world bounds computed by the last `Scene::update_transforms`.
+    pub(crate) fn cached_world_bounds(&self) -> Option<Bounds> {
+        self.world_bounds
+    }
+
+    /// Stores world transform computed by the scene and marks transform as unchanged.
+    pub(crate) fn set_world_transform(&mut self, world_matrix: Mat4, world_bounds: Option<Bounds>) {
+        self.world_matrix = world_matrix;
+        self.world_bounds = world_bounds;
+        self.transform_changed = false;
+    }
+
+    /// Returns an exact copy of this object, including its id.
+    /// Used to restore objects to their previous state. To create a new object use `clone`.
+    pub fn snapshot(&self) -> Object {
         Object {
-            id: generate_id(),
+            id: self.id,
             name: self.name.clone(),
 
             position_x: self.position_x,
@@ -293,13 +496,62 @@ impl<'a> Clone for Object {
             scaling_matrix: self.scaling_matrix,
 
             model_matrix: self.model_matrix,
+            world_matrix: self.world_matrix,
+            world_bounds: self.world_bounds,
+            transform_changed: self.transform_changed,
 
             light_source: self.light_source,
             uniform_scale: self.uniform_scale,
 
+            render_queue: self.render_queue,
+            sort_key: self.sort_key,
+            render_layers: self.render_layers,
+            is_static: self.is_static,
+            batched: self.batched,
+
             mesh: self.mesh.clone(),    
+            camera: self.camera.clone(),
+            material: self.material.clone(),
+            sound_emitter: self.sound_emitter.clone(),
+            particle_emitter: self.particle_emitter.clone(),
+            rigid_body: self.rigid_body.clone(),
+            collider: self.collider.clone(),
+            lightmap: self.lightmap.clone(),
+            parent: self.parent,
+            params: self.params.clone(),
         }
     }
+
+    fn update_translation_matrix(&mut self) {
+        self.translation_matrix = position_to_translation_matrix(self.position_x, self.position_y, self.position_z);
+    }
+
+    fn update_rotation_matrix(&mut self) {
+        self.rotation_matrix = rotation_of_axes_to_rotation_matrix(self.rotation_angle_x, self.rotation_angle_y, self.rotation_angle_z);
+    }
+
+    fn update_scaling_matrix(&mut self) {
+        self.scaling_matrix = scale_of_axes_to_scale_matrix(self.scale_x, self.scale_y, self.scale_z);
+    }
+
+    fn update_model_matrix(&mut self) {
+        self.model_matrix = create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix);
+        self.transform_changed = true;
+    }
+
+    fn update_uniform_scale(&mut self) {
+        self.uniform_scale = self.scale_x == self.scale_y && self.scale_y == self.scale_z;
+    }
+}
+
+impl Clone for Object {
+    fn clone(&self) -> Object {
+        let mut object = self.snapshot();
+        object.id = generate_id();
+        // a copy isn't part of static batches of the original
+        object.batched = false;
+        object
+    }
 }
 
 /// Builder used for constructing new objects.
@@ -448,6 +700,7 @@ impl ObjectBuilder {
 
     pub fn build(&self) -> Object {
         let uniform_scale = self.scale_x == self.scale_y && self.scale_y == self.scale_z;
+        let model_matrix = create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix);
         Object {
             id: generate_id(),
             name: self.name.clone(),
@@ -468,12 +721,30 @@ impl ObjectBuilder {
             rotation_matrix: self.rotation_matrix,
             scaling_matrix: self.scaling_matrix,
 
-            model_matrix: create_model_matrix(&self.translation_matrix, &self.rotation_matrix, &self.scaling_matrix),
+            model_matrix,
+            world_matrix: model_matrix,
+            world_bounds: None,
+            transform_changed: true,
 
             light_source: false,
             uniform_scale, 
 
+            render_queue: None,
+            sort_key: 0,
+            render_layers: RenderLayers::DEFAULT,
+            is_static: false,
+            batched: false,
+
             mesh: self.mesh.clone(),  
+            camera: None,
+            material: None,
+            sound_emitter: None,
+            particle_emitter: None,
+            rigid_body: None,
+            collider: None,
+            lightmap: None,
+            parent: None,
+            params: BTreeMap::new(),
         }
     }
 }
@@ -588,4 +859,12 @@ mod tests {
 
         assert_ne!(object.id(), cloned_object.id());
     }
+
+    #[test]
+    fn snapshot_of_object_has_the_same_id() {
+        let object = ObjectBuilder::new("test").build();
+        let snapshot = object.snapshot();
+
+        assert_eq!(object.id(), snapshot.id());
+    }
 }
\ No newline at end of file
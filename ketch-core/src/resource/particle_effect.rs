@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resource::material::BlendMode;
+use crate::resource::particle_effect::particle_effect_error::ParticleEffectError;
+
+pub mod particle_effect_error;
+
+/// Value which can be interpolated by a curve.
+pub trait CurveValue: Copy {
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl CurveValue for f32 {
+    fn lerp(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl CurveValue for [f32; 4] {
+    fn lerp(self, other: [f32; 4], t: f32) -> [f32; 4] {
+        let mut value = self;
+        for (component, other) in value.iter_mut().zip(other.iter()) {
+            *component = component.lerp(*other, t);
+        }
+        value
+    }
+}
+
+/// Value changing over the lifetime of a particle, given as keys with times from 0.0 to 1.0, sorted by time.
+/// Values between keys are interpolated linearly.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Curve<T> {
+    pub keys: Vec<(f32, T)>,
+}
+
+impl<T: CurveValue> Curve<T> {
+    /// Creates curve with the same value over the whole lifetime.
+    pub fn constant(value: T) -> Self {
+        Curve {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    /// Creates curve changing linearly from start to end value.
+    pub fn linear(start: T, end: T) -> Self {
+        Curve {
+            keys: vec![(0.0, start), (1.0, end)],
+        }
+    }
+
+    /// Returns value at time t. Returns None if curve has no keys.
+    pub fn evaluate(&self, t: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        if t <= first.0 {
+            return Some(first.1);
+        }
+        for pair in self.keys.windows(2) {
+            let ((start_time, start), (end_time, end)) = (pair[0], pair[1]);
+            if t <= end_time {
+                let length = end_time - start_time;
+                let progress = if length > 0.0 { (t - start_time) / length } else { 1.0 };
+                return Some(start.lerp(end, progress));
+            }
+        }
+        self.keys.last().map(|(_, value)| *value)
+    }
+}
+
+/// Particle effect asset describing how particles are emitted and how they look over their lifetime.
+/// Textures are referenced by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct ParticleEffect {
+    name: String,
+    pub texture: Option<String>,
+    pub blend_mode: BlendMode,
+    /// Particles emitted per second.
+    pub emission_rate: f32,
+    /// Particles emitted at once whenever the effect starts.
+    pub burst: u32,
+    /// Particles over this limit aren't emitted.
+    pub max_particles: u32,
+    /// Time in seconds during which particles are emitted.
+    pub duration: f32,
+    /// Looping effects start again after their duration.
+    pub looping: bool,
+    /// Minimum and maximum lifetime of a particle in seconds.
+    pub lifetime: (f32, f32),
+    /// Minimum and maximum speed of an emitted particle.
+    pub speed: (f32, f32),
+    /// Direction particles are emitted in, relative to the emitting object.
+    pub direction: (f32, f32, f32),
+    /// Maximum angle in radians between direction and emitted particles.
+    pub spread_angle: f32,
+    /// Acceleration of particles in world space.
+    pub gravity: (f32, f32, f32),
+    /// Size of particles over their lifetime.
+    pub size: Curve<f32>,
+    /// Color of particles over their lifetime, multiplied by the texture.
+    pub color: Curve<[f32; 4]>,
+}
+
+impl ParticleEffect {
+    /// Creates looping effect emitting white particles upwards, which fade out.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        ParticleEffect {
+            name: name.into(),
+            texture: None,
+            blend_mode: BlendMode::AlphaBlend,
+            emission_rate: 10.0,
+            burst: 0,
+            max_particles: 100,
+            duration: 1.0,
+            looping: true,
+            lifetime: (1.0, 2.0),
+            speed: (1.0, 2.0),
+            direction: (0.0, 1.0, 0.0),
+            spread_angle: 0.3,
+            gravity: (0.0, 0.0, 0.0),
+            size: Curve::constant(0.2),
+            color: Curve::linear([1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]),
+        }
+    }
+
+    /// Loads particle effect from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ParticleEffectError> {
+        let serialized_effect = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized_effect)?)
+    }
+
+    /// Saves particle effect to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ParticleEffectError> {
+        let serialized_effect = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, serialized_effect)?;
+        Ok(())
+    }
+
+    /// Returns the name of this effect.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Default for ParticleEffect {
+    fn default() -> Self {
+        ParticleEffect::new("particle effect")
+    }
+}
+
+/// Plays particle effect at the position of the object it's attached to. Effects are referenced by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ParticleEmitter {
+    pub effect: String,
+    /// Emitters which aren't playing stop emitting, already emitted particles live until the end of their lifetime.
+    pub playing: bool,
+}
+
+impl ParticleEmitter {
+    /// Creates playing emitter of given effect.
+    pub fn new<S: Into<String>>(effect: S) -> Self {
+        ParticleEmitter {
+            effect: effect.into(),
+            playing: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_interpolates_between_keys() {
+        let curve = Curve { keys: vec![(0.0, 1.0), (0.5, 3.0), (1.0, 0.0)] };
+
+        assert_eq!(Some(1.0), curve.evaluate(-1.0));
+        assert_eq!(Some(2.0), curve.evaluate(0.25));
+        assert_eq!(Some(1.5), curve.evaluate(0.75));
+        assert_eq!(Some(0.0), curve.evaluate(2.0));
+        assert_eq!(None, Curve::<f32> { keys: Vec::new() }.evaluate(0.5));
+    }
+}
@@ -0,0 +1,22 @@
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum ParticleEffectError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        SerializeError(err: ron::ser::Error) {
+            from()
+            display("SerializeError: {}", err)
+            cause(err)
+        }
+        DeserializeError(err: ron::de::Error) {
+            from()
+            display("DeserializeError: {}", err)
+            cause(err)
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::f32::consts::PI;
+
+use crate::resource::mesh::Vertex;
+
+const SPHERE_SEGMENTS: u32 = 32;
+const SPHERE_RINGS: u32 = 16;
+
+/// Shapes which can be generated without loading a model.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Primitive {
+    Cube,
+    Sphere,
+    Plane,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 3] = [Primitive::Cube, Primitive::Sphere, Primitive::Plane];
+
+    /// Returns name of the mesh generated for this primitive.
+    pub fn mesh_name(self) -> &'static str {
+        match self {
+            Primitive::Cube => "primitive_cube",
+            Primitive::Sphere => "primitive_sphere",
+            Primitive::Plane => "primitive_plane",
+        }
+    }
+
+    /// Returns primitive which generates mesh with given name.
+    pub fn from_mesh_name(name: &str) -> Option<Primitive> {
+        Primitive::ALL.iter().cloned().find(|x| x.mesh_name() == name)
+    }
+
+    /// Generates vertices and indices of unit sized primitive.
+    pub fn generate(self) -> (Vec<Vertex>, Vec<u32>) {
+        match self {
+            Primitive::Cube => cube(1.0),
+            Primitive::Sphere => sphere(0.5, SPHERE_SEGMENTS, SPHERE_RINGS),
+            Primitive::Plane => plane(1.0),
+        }
+    }
+}
+
+/// Generates cube centered at the origin. Every face has its own vertices, so normals are flat.
+pub fn cube(size: f32) -> (Vec<Vertex>, Vec<u32>) {
+    let half_size = size / 2.0;
+    // normal, tangent and bitangent of every face
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, tangent, bitangent) in faces.iter() {
+        let first_index = vertices.len() as u32;
+        for (u, v) in corners.iter() {
+            let position = [
+                (normal[0] + tangent[0] * u + bitangent[0] * v) * half_size,
+                (normal[1] + tangent[1] * u + bitangent[1] * v) * half_size,
+                (normal[2] + tangent[2] * u + bitangent[2] * v) * half_size,
+            ];
+            vertices.push(Vertex { position, normal: *normal, tex_coord: [(u + 1.0) / 2.0, (1.0 - v) / 2.0] });
+        }
+        indices.extend_from_slice(&[first_index, first_index + 1, first_index + 2, first_index, first_index + 2, first_index + 3]);
+    }
+    (vertices, indices)
+}
+
+/// Generates UV sphere centered at the origin.
+pub fn sphere(radius: f32, segments: u32, rings: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(((segments + 1) * (rings + 1)) as usize);
+    let mut indices = Vec::with_capacity((segments * rings * 6) as usize);
+
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        let polar_angle = v * PI;
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let azimuth = u * 2.0 * PI;
+            let normal = [polar_angle.sin() * azimuth.cos(), polar_angle.cos(), polar_angle.sin() * azimuth.sin()];
+            let position = [normal[0] * radius, normal[1] * radius, normal[2] * radius];
+            vertices.push(Vertex { position, normal, tex_coord: [u, v] });
+        }
+    }
+
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let current = ring * (segments + 1) + segment;
+            let next = current + segments + 1;
+            indices.extend_from_slice(&[current, current + 1, next, current + 1, next + 1, next]);
+        }
+    }
+    (vertices, indices)
+}
+
+/// Generates plane lying on the XZ plane, centered at the origin and facing up.
+pub fn plane(size: f32) -> (Vec<Vertex>, Vec<u32>) {
+    let half_size = size / 2.0;
+    let normal = [0.0, 1.0, 0.0];
+    let vertices = vec![
+        Vertex { position: [-half_size, 0.0, half_size], normal, tex_coord: [0.0, 1.0] },
+        Vertex { position: [half_size, 0.0, half_size], normal, tex_coord: [1.0, 1.0] },
+        Vertex { position: [half_size, 0.0, -half_size], normal, tex_coord: [1.0, 0.0] },
+        Vertex { position: [-half_size, 0.0, -half_size], normal, tex_coord: [0.0, 0.0] },
+    ];
+    let indices = vec![0, 1, 2, 0, 2, 3];
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_has_four_vertices_and_two_triangles_per_face() {
+        let (vertices, indices) = cube(1.0);
+
+        assert_eq!(24, vertices.len());
+        assert_eq!(36, indices.len());
+        assert!(vertices.iter().all(|x| x.position.iter().all(|coordinate| coordinate.abs() == 0.5)));
+    }
+
+    #[test]
+    fn sphere_vertices_lie_on_its_surface() {
+        let (vertices, indices) = sphere(2.0, 8, 4);
+
+        assert!(indices.iter().all(|x| (*x as usize) < vertices.len()));
+        for vertex in vertices {
+            let [x, y, z] = vertex.position;
+            assert!(((x * x + y * y + z * z).sqrt() - 2.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn primitive_is_found_by_mesh_name() {
+        assert_eq!(Some(Primitive::Sphere), Primitive::from_mesh_name(Primitive::Sphere.mesh_name()));
+        assert_eq!(None, Primitive::from_mesh_name("test_mesh"));
+    }
+}
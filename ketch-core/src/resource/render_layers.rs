@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of render layers, one for every bit of the mask.
+pub const MAX_RENDER_LAYERS: usize = 32;
+
+/// Names of layers every scene has, in order of their bits.
+pub const BUILT_IN_RENDER_LAYERS: [&str; 3] = ["default", "particles", "helpers"];
+
+/// Set of render layers, one bit per layer. Objects belong to layers and cameras only draw layers included in their visibility mask.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RenderLayers(pub u32);
+
+impl RenderLayers {
+    pub const NONE: RenderLayers = RenderLayers(0);
+    pub const ALL: RenderLayers = RenderLayers(!0);
+    /// Layer objects belong to unless they're moved to other layers.
+    pub const DEFAULT: RenderLayers = RenderLayers(1);
+    /// Layer of particles, which are drawn if camera visibility mask includes it.
+    pub const PARTICLES: RenderLayers = RenderLayers(1 << 1);
+    /// Layer of debug shapes and editor helpers, which are drawn if camera visibility mask includes it.
+    pub const HELPERS: RenderLayers = RenderLayers(1 << 2);
+
+    /// Returns set containing only the layer with given index, or None if index is out of range.
+    pub fn layer(index: usize) -> Option<Self> {
+        if index < MAX_RENDER_LAYERS {
+            Some(RenderLayers(1 << index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if both sets have a layer in common.
+    pub fn intersects(self, other: RenderLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns set with layers of both sets.
+    pub fn with(self, other: RenderLayers) -> Self {
+        RenderLayers(self.0 | other.0)
+    }
+
+    /// Returns set without layers of the other set.
+    pub fn without(self, other: RenderLayers) -> Self {
+        RenderLayers(self.0 & !other.0)
+    }
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers::DEFAULT
+    }
+}
+
+/// Names of render layers of a scene, where index of a name is the bit of its layer.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RenderLayerNames {
+    names: Vec<String>,
+}
+
+impl RenderLayerNames {
+    /// Creates names containing only built-in layers.
+    pub fn new() -> Self {
+        RenderLayerNames {
+            names: BUILT_IN_RENDER_LAYERS.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Returns layer with given name, adding it if it doesn't exist yet. Returns None if all layers are already named.
+    pub fn add<S: Into<String>>(&mut self, name: S) -> Option<RenderLayers> {
+        let name = name.into();
+        if let Some(layer) = self.layer(&name) {
+            return Some(layer);
+        }
+        let layer = RenderLayers::layer(self.names.len())?;
+        self.names.push(name);
+        Some(layer)
+    }
+
+    /// Returns layer with given name.
+    pub fn layer(&self, name: &str) -> Option<RenderLayers> {
+        self.names.iter().position(|layer_name| layer_name == name).and_then(RenderLayers::layer)
+    }
+
+    /// Returns names of layers in order of their bits.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+impl Default for RenderLayerNames {
+    fn default() -> Self {
+        RenderLayerNames::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_layers_get_the_next_free_bit() {
+        let mut names = RenderLayerNames::new();
+        let minimap = names.add("minimap").unwrap();
+
+        assert_eq!(RenderLayers(1 << 3), minimap);
+        assert_eq!(Some(minimap), names.add("minimap"));
+        assert_eq!(Some(RenderLayers::PARTICLES), names.layer("particles"));
+        for index in names.names().len()..MAX_RENDER_LAYERS {
+            assert!(names.add(format!("layer {}", index)).is_some());
+        }
+        assert_eq!(None, names.add("one too many"));
+
+        let minimap_camera = RenderLayers::ALL.without(RenderLayers::PARTICLES);
+        assert!(!minimap_camera.intersects(RenderLayers::PARTICLES));
+        assert!(minimap_camera.intersects(minimap.with(RenderLayers::PARTICLES)));
+    }
+}
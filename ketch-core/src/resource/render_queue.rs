@@ -0,0 +1,80 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resource::material::BlendMode;
+
+/// Group of objects drawn together. Queues are drawn in declaration order.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum RenderQueue {
+    /// Drawn before everything else, used by skyboxes and distant scenery.
+    Background,
+    /// Default queue of opaque objects.
+    Opaque,
+    /// Default queue of blended objects, drawn from the farthest to the closest.
+    Transparent,
+    /// Drawn after everything else, used by decals and UI placed in the world.
+    Overlay,
+}
+
+impl RenderQueue {
+    /// Returns queue used by objects which didn't choose one, based on blend mode of their material.
+    pub fn for_blend_mode(blend_mode: BlendMode) -> Self {
+        if blend_mode.is_transparent() {
+            RenderQueue::Transparent
+        } else {
+            RenderQueue::Opaque
+        }
+    }
+}
+
+/// Place of an object in the drawing order of a frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DrawOrder {
+    pub queue: RenderQueue,
+    /// Objects in the same queue are drawn from the lowest to the highest sort key.
+    pub sort_key: i32,
+    /// Distance from the camera, used to draw transparent objects with the same sort key from the farthest.
+    pub distance: f32,
+}
+
+impl DrawOrder {
+    /// Compares order in which objects are drawn. Objects which compare equal keep their order in the scene.
+    pub fn compare(&self, other: &DrawOrder) -> Ordering {
+        (self.queue, self.sort_key).cmp(&(other.queue, other.sort_key)).then_with(|| {
+            if self.queue == RenderQueue::Transparent {
+                other.distance.partial_cmp(&self.distance).unwrap_or(Ordering::Equal)
+            } else {
+                Ordering::Equal
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn objects_are_ordered_by_queue_sort_key_and_distance() {
+        let order = |queue, sort_key, distance| DrawOrder { queue, sort_key, distance };
+        let mut orders = vec![
+            order(RenderQueue::Overlay, -5, 1.0),
+            order(RenderQueue::Transparent, 0, 1.0),
+            order(RenderQueue::Transparent, 0, 9.0),
+            order(RenderQueue::Opaque, 1, 1.0),
+            order(RenderQueue::Opaque, 0, 9.0),
+            order(RenderQueue::Background, 0, 1.0),
+        ];
+        orders.sort_by(DrawOrder::compare);
+
+        assert_eq!(vec![
+            order(RenderQueue::Background, 0, 1.0),
+            order(RenderQueue::Opaque, 0, 9.0),
+            order(RenderQueue::Opaque, 1, 1.0),
+            order(RenderQueue::Transparent, 0, 9.0),
+            order(RenderQueue::Transparent, 0, 1.0),
+            order(RenderQueue::Overlay, -5, 1.0),
+        ], orders);
+    }
+}
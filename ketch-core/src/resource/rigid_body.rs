@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes how a rigid body is moved.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum BodyType {
+    /// Moved by the physics simulation.
+    Dynamic,
+    /// Never moves.
+    Static,
+    /// Moved by changing transform of the object, pushes dynamic bodies away.
+    Kinematic,
+}
+
+/// Rigid body component, which makes object simulated by the physics system.
+/// Object needs a collider to collide with other objects.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct RigidBody {
+    pub body_type: BodyType,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+}
+
+impl RigidBody {
+    /// Creates rigid body of given type without damping.
+    pub fn new(body_type: BodyType) -> Self {
+        RigidBody {
+            body_type,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+        }
+    }
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        RigidBody::new(BodyType::Dynamic)
+    }
+}
@@ -1,7 +1,33 @@
+#[cfg(feature = "vulkan")]
 use crate::renderer::shader::fragment_shader::ty::LightData;
-use nalgebra_glm::Vec3;
+#[cfg(feature = "vulkan")]
+use crate::renderer::shader::fragment_shader::ty::Light as ShaderLight;
+use nalgebra_glm as glm;
+use nalgebra_glm::{DVec3, Mat4, Vec3};
 use crate::resource::object::Object;
+use crate::resource::light::{AmbientLight, Light};
+#[cfg(feature = "vulkan")]
+use crate::resource::light::MAX_LIGHTS;
 use crate::resource::camera::Camera;
+use crate::resource::scene::scene_data::SceneData;
+use crate::resource::scene::scene_error::SceneSerializationError;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use crate::math::ray::Ray;
+use crate::math::bounds::Bounds;
+use crate::event_bus::ObjectEvent;
+use crate::profile_scope;
+use crate::renderer::renderer_error::RenderError;
+use crate::renderer::upload::AssetUploader;
+use crate::resource::scene::static_batch::StaticBatch;
+use crate::resource::render_layers::{RenderLayerNames, RenderLayers};
+use crate::pause::PauseFlags;
+
+pub mod scene_data;
+pub mod scene_error;
+pub mod static_batch;
+
+const DEFAULT_LIGHT_NAME: &str = "light";
 
 /// Scene is a collection of game objects and world properties.
 /// Only one can be active at a time.
@@ -10,20 +36,37 @@ pub struct Scene {
     name: String,
 
     camera: Camera,
+    // object whose camera the scene is seen through, or None for the free camera
+    active_camera: Option<u32>,
     objects: Vec<Object>,
-    light_position: Vec3,
-    light_color: Vec3,
+    lights: Vec<Light>,
+    ambient_light: AmbientLight,
+    // objects added and removed since events were last taken
+    object_events: Vec<ObjectEvent>,
+    static_batches: Vec<StaticBatch>,
+    render_layer_names: RenderLayerNames,
+    pause_flags: PauseFlags,
+    // absolute position of the origin of scene coordinates, moved by origin shifts
+    origin: DVec3,
+    origin_shift_distance: Option<f32>,
 }
 
 impl Scene {
-    /// Creates new scene.
+    /// Creates new scene with a single white light at the origin.
     pub fn new<S: Into<String>>(name: S, camera: Camera) -> Self {
         Scene {
             name: name.into(),
             camera,
+            active_camera: None,
             objects: Vec::new(),
-            light_position: Vec3::new(0.0, 0.0, 0.0),
-            light_color: Vec3::new(1.0, 1.0, 1.0),
+            lights: vec![Light::new(DEFAULT_LIGHT_NAME)],
+            ambient_light: AmbientLight::default(),
+            object_events: Vec::new(),
+            static_batches: Vec::new(),
+            render_layer_names: RenderLayerNames::new(),
+            pause_flags: PauseFlags::NONE,
+            origin: DVec3::new(0.0, 0.0, 0.0),
+            origin_shift_distance: None,
         }
     }
 
@@ -32,16 +75,60 @@ impl Scene {
         &self.name
     }
 
+    /// Saves this scene to a file. Meshes are saved as references to their names.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SceneSerializationError> {
+        let serialized_scene = ron::ser::to_string_pretty(&SceneData::from_scene(self), Default::default())?;
+        std::fs::write(path, serialized_scene)?;
+        Ok(())
+    }
+
+    /// Returns an exact copy of this scene, with object ids preserved.
+    /// Cloning a scene gives all objects new ids.
+    pub fn snapshot(&self) -> Scene {
+        Scene {
+            name: self.name.clone(),
+            camera: self.camera.clone(),
+            active_camera: self.active_camera,
+            objects: self.objects.iter().map(Object::snapshot).collect(),
+            lights: self.lights.clone(),
+            ambient_light: self.ambient_light,
+            object_events: Vec::new(),
+            static_batches: self.static_batches.clone(),
+            render_layer_names: self.render_layer_names.clone(),
+            pause_flags: self.pause_flags,
+            origin: self.origin,
+            origin_shift_distance: self.origin_shift_distance,
+        }
+    }
+
     /// Adds object to the scene.
     pub fn add_object(&mut self, object: Object) {
+        self.object_events.push(ObjectEvent::Added(object.id()));
         self.objects.push(object);
     }
 
+    /// Adds object to the scene at given index of its object list, or at the end if index is out of bounds.
+    pub fn insert_object(&mut self, index: usize, object: Object) {
+        self.object_events.push(ObjectEvent::Added(object.id()));
+        let index = index.min(self.objects.len());
+        self.objects.insert(index, object);
+    }
+
+    /// Returns index of object with given id in the object list of the scene.
+    pub fn object_index(&self, id: u32) -> Option<usize> {
+        self.objects.iter().position(|x| x.id() == id)
+    }
+
     /// Removes object from the scene and returns it if found.
     pub fn remove_object(&mut self, id: u32) -> Option<Object> {
         let object_index = self.objects.iter().position(|x| x.id() == id);
         match object_index {
-            Some(index) => Some(self.objects.remove(index)),
+            Some(index) => {
+                self.object_events.push(ObjectEvent::Removed(id));
+                self.forget_removed_object(id);
+                self.break_static_batch(id);
+                Some(self.objects.remove(index))
+            },
             None => None,
         }
     }
@@ -55,12 +142,364 @@ impl Scene {
                                                      .collect();
 
         let mut removed_objects = Vec::new();
-        for index in object_indexes {
-            removed_objects.push(self.objects.remove(index));
+        for index in object_indexes.into_iter().rev() {
+            let object = self.objects.remove(index);
+            self.object_events.push(ObjectEvent::Removed(object.id()));
+            self.forget_removed_object(object.id());
+            self.break_static_batch(object.id());
+            removed_objects.insert(0, object);
         }
         removed_objects
     }
 
+    /// Moves objects and static batches of another scene, e.g. a streamed part of a bigger world, into this one.
+    /// Objects keep their absolute positions, when origins of the scenes differ they're moved to coordinates of this scene.
+    /// Returns ids of the moved objects. Camera, lights and render layer names of the other scene are ignored.
+    pub fn merge(&mut self, mut other: Scene) -> Vec<u32> {
+        if other.origin != self.origin {
+            let offset = self.origin - other.origin;
+            other.shift_origin(Vec3::new(offset.x as f32, offset.y as f32, offset.z as f32));
+        }
+        let ids = other.objects.iter().map(Object::id).collect();
+        for object in other.objects {
+            self.add_object(object);
+        }
+        self.static_batches.extend(other.static_batches);
+        ids
+    }
+
+    /// Removes objects with given ids, e.g. ones returned by `merge`, and returns the ones found.
+    pub fn remove_objects(&mut self, ids: &[u32]) -> Vec<Object> {
+        let ids: HashSet<u32> = ids.iter().cloned().collect();
+        let objects = std::mem::take(&mut self.objects);
+        let (removed_objects, objects): (Vec<Object>, Vec<Object>) = objects.into_iter().partition(|object| ids.contains(&object.id()));
+        self.objects = objects;
+        for object in &removed_objects {
+            self.object_events.push(ObjectEvent::Removed(object.id()));
+            self.forget_removed_object(object.id());
+            self.break_static_batch(object.id());
+        }
+        removed_objects
+    }
+
+    /// Marks transforms of children of a removed object as changed, because their parent is ignored from now on,
+    /// and switches back to the free camera if the object was the active camera.
+    fn forget_removed_object(&mut self, id: u32) {
+        for child in self.objects.iter_mut().filter(|object| object.parent() == Some(id)) {
+            child.mark_transform_changed();
+        }
+        if self.active_camera == Some(id) {
+            self.active_camera = None;
+        }
+    }
+
+    /// Returns subsystems paused while this scene is active, in addition to the ones paused globally by the engine.
+    pub fn pause_flags(&self) -> PauseFlags {
+        self.pause_flags
+    }
+
+    /// Sets subsystems paused while this scene is active, e.g. by its pause menu. Flags aren't saved with the scene.
+    pub fn set_pause_flags(&mut self, pause_flags: PauseFlags) {
+        self.pause_flags = pause_flags;
+    }
+
+    /// Returns absolute position of the origin of scene coordinates, which objects, lights and cameras are positioned relative to.
+    /// It stays at zero unless the origin is shifted.
+    pub fn origin(&self) -> DVec3 {
+        self.origin
+    }
+
+    /// Sets absolute position of the origin without moving anything, e.g. to place a world cell authored around its center far from the world origin.
+    pub fn set_origin(&mut self, origin: DVec3) {
+        self.origin = origin;
+    }
+
+    /// Sets distance from the origin at which the view camera makes the origin shift to it, so that coordinates near the camera
+    /// stay small and precise in large worlds. None disables shifting, which is the default.
+    pub fn set_origin_shift_distance(&mut self, distance: Option<f32>) {
+        self.origin_shift_distance = distance;
+    }
+
+    /// Returns distance from the origin at which the origin is shifted to the view camera.
+    pub fn origin_shift_distance(&self) -> Option<f32> {
+        self.origin_shift_distance
+    }
+
+    /// Returns absolute position of a point in scene coordinates, in double precision.
+    pub fn absolute_position(&self, position: &Vec3) -> DVec3 {
+        self.origin + DVec3::new(f64::from(position.x), f64::from(position.y), f64::from(position.z))
+    }
+
+    /// Returns position in scene coordinates of a point at the absolute position. Points far from the origin lose precision.
+    pub fn local_position(&self, absolute_position: &DVec3) -> Vec3 {
+        let position = absolute_position - self.origin;
+        Vec3::new(position.x as f32, position.y as f32, position.z as f32)
+    }
+
+    /// Moves origin of scene coordinates by the offset. Root objects, lights, the free camera and static batches are moved by the negated offset,
+    /// so their absolute positions stay the same. World transforms are moved with them, so objects aren't reported as moved and static batches stay baked.
+    /// Systems keeping positions of their own, like physics and particles, have to be shifted by the same offset.
+    pub fn shift_origin(&mut self, offset: Vec3) {
+        let translation = glm::translation(&-offset);
+        for object in &mut self.objects {
+            let unchanged = !object.transform_changed();
+            let (world_matrix, world_bounds) = (object.cached_world_matrix(), object.cached_world_bounds());
+            if object.parent().is_none() {
+                let (x, y, z) = object.position();
+                object.set_position(x - offset.x, y - offset.y, z - offset.z);
+            }
+            if unchanged {
+                object.set_world_transform(translation * world_matrix, world_bounds.map(|bounds| bounds.transformed(&translation)));
+            }
+        }
+        for light in &mut self.lights {
+            let position = light.position_vec3() - offset;
+            light.set_position(position.x, position.y, position.z);
+        }
+        let camera_position = self.camera.position_vec3() - offset;
+        self.camera.set_position_vec3(camera_position);
+        for static_batch in &mut self.static_batches {
+            static_batch.shift_origin(&offset);
+        }
+        self.origin += DVec3::new(f64::from(offset.x), f64::from(offset.y), f64::from(offset.z));
+    }
+
+    /// Shifts origin to the view camera if the camera is farther from it than the origin shift distance. Returns offset of the shift if there was one.
+    /// Called by the engine after every game update.
+    pub fn update_origin(&mut self) -> Option<Vec3> {
+        let distance = self.origin_shift_distance?;
+        let camera_position = self.view_camera().position_vec3();
+        if glm::length(&camera_position) <= distance {
+            return None;
+        }
+        self.shift_origin(camera_position);
+        Some(camera_position)
+    }
+
+    /// Returns render layer with given name, adding it if the scene doesn't have it yet.
+    /// Returns None if all layers are already named.
+    pub fn add_render_layer<S: Into<String>>(&mut self, name: S) -> Option<RenderLayers> {
+        self.render_layer_names.add(name)
+    }
+
+    /// Returns render layer with given name.
+    pub fn render_layer(&self, name: &str) -> Option<RenderLayers> {
+        self.render_layer_names.layer(name)
+    }
+
+    /// Returns names of render layers of the scene, where index of a name is the bit of its layer.
+    pub fn render_layer_names(&self) -> &[String] {
+        self.render_layer_names.names()
+    }
+
+    /// Marks object as static geometry, which is merged into static batches when they're baked. Returns false if object isn't found.
+    pub fn mark_static(&mut self, id: u32) -> bool {
+        match self.object_mut(id) {
+            Some(object) => {
+                object.set_static(true);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Merges meshes of static objects sharing material, texture and drawing order into combined meshes, replacing previously baked batches.
+    /// Batched objects are drawn by their batch. Moving or removing a batched object breaks up its batch, so the rest of it is drawn one by one again.
+    /// Without an uploader, combined meshes are kept only on the CPU like other assets of asset managers without a device.
+    pub fn bake_static_batches(&mut self, uploader: Option<&AssetUploader>) -> Result<(), RenderError> {
+        profile_scope!("bake static batches");
+        self.clear_static_batches();
+        self.update_transforms();
+        let static_batches = static_batch::bake(&self.name, self.objects.iter(), |object| object.cached_world_matrix(), uploader)?;
+        for id in static_batches.iter().flat_map(StaticBatch::sources) {
+            if let Some(object) = self.objects.iter_mut().find(|object| object.id() == *id) {
+                object.set_batched(true);
+            }
+        }
+        self.static_batches = static_batches;
+        Ok(())
+    }
+
+    /// Removes all static batches, so that static objects are drawn one by one.
+    pub fn clear_static_batches(&mut self) {
+        self.static_batches.clear();
+        for object in &mut self.objects {
+            object.set_batched(false);
+        }
+    }
+
+    /// Returns static batches drawn instead of batched objects.
+    pub fn static_batches(&self) -> &[StaticBatch] {
+        &self.static_batches
+    }
+
+    /// Removes batch containing the object, and draws the rest of its objects one by one.
+    fn break_static_batch(&mut self, id: u32) {
+        let batch_index = match self.static_batches.iter().position(|batch| batch.sources().contains(&id)) {
+            Some(batch_index) => batch_index,
+            None => return,
+        };
+        let batch = self.static_batches.remove(batch_index);
+        for object in self.objects.iter_mut().filter(|object| batch.sources().contains(&object.id())) {
+            object.set_batched(false);
+        }
+    }
+
+    /// Removes and returns events of objects added to and removed from the scene since the last call.
+    pub fn take_object_events(&mut self) -> Vec<ObjectEvent> {
+        std::mem::take(&mut self.object_events)
+    }
+
+    /// Returns id of the closest object with a mesh hit by the ray.
+    pub fn pick_object(&self, ray: &Ray) -> Option<u32> {
+        self.objects.iter()
+                    .filter_map(|object| {
+                        let bounds = object.mesh()?.read().unwrap().bounds();
+                        ray.intersect_bounds(&bounds, &self.world_matrix(object)).map(|distance| (object.id(), distance))
+                    })
+                    .min_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(id, _distance)| id)
+    }
+
+    /// Recomputes world matrices and bounds of objects which moved, or whose ancestors moved, since the last update,
+    /// and returns their ids. Static objects keep transforms computed before, so they aren't recomputed every frame.
+    pub fn update_transforms(&mut self) -> Vec<u32> {
+        let indices: HashMap<u32, usize> = self.objects.iter().enumerate().map(|(index, object)| (object.id(), index)).collect();
+        let parent_index = |object: &Object| object.parent().and_then(|id| indices.get(&id).cloned());
+
+        // objects which moved, along with their depth, so that parents are updated before their children
+        let mut moved = Vec::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            let mut changed = object.transform_changed();
+            let mut depth = 0;
+            let mut parent = parent_index(object);
+            // every object can be visited at most once, which protects against cycles
+            while let Some(index) = parent.filter(|_| depth < self.objects.len()) {
+                changed |= self.objects[index].transform_changed();
+                depth += 1;
+                parent = parent_index(&self.objects[index]);
+            }
+            if changed {
+                moved.push((depth, index));
+            }
+        }
+        moved.sort();
+
+        for &(_depth, index) in &moved {
+            let object = &self.objects[index];
+            let world_matrix = match parent_index(object) {
+                Some(parent) => self.objects[parent].cached_world_matrix() * object.model_matrix(),
+                None => object.model_matrix(),
+            };
+            let world_bounds = object.mesh().map(|mesh| mesh.read().unwrap().bounds().transformed(&world_matrix));
+            self.objects[index].set_world_transform(world_matrix, world_bounds);
+        }
+        let moved: Vec<u32> = moved.into_iter().map(|(_depth, index)| self.objects[index].id()).collect();
+        for &id in &moved {
+            if self.object(id).map(Object::is_batched).unwrap_or(false) {
+                self.break_static_batch(id);
+            }
+        }
+        moved
+    }
+
+    /// Returns true if the object or one of its ancestors moved since the last `update_transforms`.
+    fn transform_changed(&self, object: &Object) -> bool {
+        let mut current = Some(object);
+        for _ in 0..=self.objects.len() {
+            match current {
+                Some(object) if object.transform_changed() => return true,
+                Some(object) => current = object.parent().and_then(|id| self.object(id)),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Returns model matrix of the object combined with model matrices of all its ancestors.
+    /// Parents which are not in the scene are ignored. Matrices of objects which didn't move since the last `update_transforms` aren't recomputed.
+    pub fn world_matrix(&self, object: &Object) -> Mat4 {
+        if !self.transform_changed(object) {
+            return object.cached_world_matrix();
+        }
+        let mut world_matrix = object.model_matrix();
+        let mut parent_id = object.parent();
+        // every object can be visited at most once, which protects against cycles
+        for _ in 0..self.objects.len() {
+            match parent_id.and_then(|id| self.object(id)) {
+                Some(parent) => {
+                    world_matrix = parent.model_matrix() * world_matrix;
+                    parent_id = parent.parent();
+                },
+                None => break,
+            }
+        }
+        world_matrix
+    }
+
+    /// Returns world position of the object.
+    pub fn world_position(&self, object: &Object) -> Vec3 {
+        let world_matrix = self.world_matrix(object);
+        Vec3::new(world_matrix[(0, 3)], world_matrix[(1, 3)], world_matrix[(2, 3)])
+    }
+
+    /// Returns bounds of the object mesh in world space, or None if the object has no mesh.
+    /// Changes of the mesh made through its lock are picked up once the object moves or its mesh is set again.
+    pub fn world_bounds(&self, object: &Object) -> Option<Bounds> {
+        if !self.transform_changed(object) {
+            return object.cached_world_bounds();
+        }
+        let mesh = object.mesh()?;
+        let bounds = mesh.read().unwrap().bounds();
+        Some(bounds.transformed(&self.world_matrix(object)))
+    }
+
+    /// Returns copy of the camera attached to the object, placed at world position of the object.
+    pub fn object_camera(&self, object: &Object) -> Option<Camera> {
+        let mut camera = object.camera()?.clone();
+        camera.set_position_vec3(self.world_position(object));
+        Some(camera)
+    }
+
+    /// Returns true if `ancestor` is a parent of the object, or a parent of one of its parents.
+    pub fn is_ancestor(&self, ancestor: u32, id: u32) -> bool {
+        let mut parent_id = self.object(id).and_then(Object::parent);
+        for _ in 0..self.objects.len() {
+            match parent_id {
+                Some(parent) if parent == ancestor => return true,
+                Some(parent) => parent_id = self.object(parent).and_then(Object::parent),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Changes parent of the object. Returns false if object isn't found or if the change would create a cycle.
+    pub fn set_parent(&mut self, id: u32, parent: Option<u32>) -> bool {
+        if let Some(parent) = parent {
+            if parent == id || self.is_ancestor(id, parent) {
+                return false;
+            }
+        }
+        match self.object_mut(id) {
+            Some(object) => {
+                object.set_parent(parent);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns a reference to the object with given id.
+    pub fn object(&self, id: u32) -> Option<&Object> {
+        self.objects.iter().find(|x| x.id() == id)
+    }
+
+    /// Returns a mutable reference to the object with given id.
+    pub fn object_mut(&mut self, id: u32) -> Option<&mut Object> {
+        self.objects.iter_mut().find(|x| x.id() == id)
+    }
+
     /// Returns a reference to slice of all objects.
     pub fn objects(&self) -> &[Object] {
         self.objects.as_slice()
@@ -71,70 +510,344 @@ impl Scene {
         self.objects.as_mut_slice()
     }
 
-    /// Changes camera used by this scene.
+    /// Changes free camera of this scene.
     pub fn change_camera(&mut self, camera: Camera) {
         self.camera = camera;
     }
 
-    /// Returns a reference to camera used by this scene.
+    /// Returns a reference to free camera of this scene, which isn't attached to any object.
+    /// It's used by the editor, and by the game while no camera object is active.
     pub fn camera(&self) -> &Camera {
         &self.camera
     }
 
-    /// Returns a mutable reference to camera used by this scene.
+    /// Returns a mutable reference to free camera of this scene.
     pub fn camera_mut(&mut self) -> &mut Camera {
         &mut self.camera
     }
 
+    /// Makes the scene seen through camera attached to the object, e.g. a cutscene or security camera,
+    /// or through the free camera if None is passed. Returns false if object isn't found or doesn't have a camera.
+    pub fn set_active_camera(&mut self, id: Option<u32>) -> bool {
+        if let Some(id) = id {
+            if self.object(id).and_then(Object::camera).is_none() {
+                return false;
+            }
+        }
+        self.active_camera = id;
+        true
+    }
+
+    /// Returns id of the object whose camera the scene is seen through, or None if the free camera is used.
+    pub fn active_camera(&self) -> Option<u32> {
+        self.active_camera
+    }
+
+    /// Returns ids of objects with cameras attached, in order of objects.
+    pub fn camera_objects(&self) -> Vec<u32> {
+        self.objects.iter().filter(|object| object.camera().is_some()).map(Object::id).collect()
+    }
+
+    /// Returns camera the scene is seen through: camera of the active camera object placed at its world position,
+    /// or the free camera if no camera object is active, or if the active object lost its camera.
+    pub fn view_camera(&self) -> Camera {
+        self.active_camera.and_then(|id| self.object(id))
+                          .and_then(|object| self.object_camera(object))
+                          .unwrap_or_else(|| self.camera.clone())
+    }
+
+    /// Adds light to the scene. Only first `MAX_LIGHTS` lights are used by the renderer.
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    /// Removes light from the scene and returns it if found.
+    pub fn remove_light(&mut self, id: u32) -> Option<Light> {
+        let light_index = self.lights.iter().position(|x| x.id() == id)?;
+        Some(self.lights.remove(light_index))
+    }
+
+    /// Returns ambient light of the scene.
+    pub fn ambient_light(&self) -> AmbientLight {
+        self.ambient_light
+    }
+
+    /// Sets ambient light of the scene. Intensity is clamped to be non-negative.
+    pub fn set_ambient_light(&mut self, ambient_light: AmbientLight) {
+        self.ambient_light = AmbientLight {
+            intensity: ambient_light.intensity.max(0.0),
+            ..ambient_light
+        };
+    }
+
+    /// Removes all lights from the scene.
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+
+    /// Returns a reference to the light with given id.
+    pub fn light(&self, id: u32) -> Option<&Light> {
+        self.lights.iter().find(|x| x.id() == id)
+    }
+
+    /// Returns a mutable reference to the light with given id.
+    pub fn light_mut(&mut self, id: u32) -> Option<&mut Light> {
+        self.lights.iter_mut().find(|x| x.id() == id)
+    }
+
+    /// Returns a reference to slice of all lights.
+    pub fn lights(&self) -> &[Light] {
+        self.lights.as_slice()
+    }
+
+    /// Returns a reference to a mutable slice of all lights.
+    pub fn lights_mut(&mut self) -> &mut [Light] {
+        self.lights.as_mut_slice()
+    }
+
+    /// Returns id of the closest light, which is hit by the ray. Lights are treated as spheres with given radius.
+    pub fn pick_light(&self, ray: &Ray, radius: f32) -> Option<u32> {
+        self.lights.iter()
+                   .filter_map(|light| ray.intersect_sphere(&light.position_vec3(), radius).map(|distance| (light.id(), distance)))
+                   .min_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(std::cmp::Ordering::Equal))
+                   .map(|(id, _distance)| id)
+    }
+
+    /// Returns main light of the scene, which is the first added light.
+    pub fn main_light(&self) -> Option<&Light> {
+        self.lights.first()
+    }
+
+    /// Returns a mutable reference to main light of the scene. Main light is created if scene has no lights.
+    pub fn main_light_mut(&mut self) -> &mut Light {
+        if self.lights.is_empty() {
+            self.lights.push(Light::new(DEFAULT_LIGHT_NAME));
+        }
+        &mut self.lights[0]
+    }
+
     pub fn light_color_vec3(&self) -> Vec3 {
-        self.light_color
+        self.main_light().map(Light::color_vec3).unwrap_or_else(|| Vec3::new(1.0, 1.0, 1.0))
     }
 
     pub fn light_color(&self) -> (f32, f32, f32) {
-        (self.light_color.x, self.light_color.y, self.light_color.z)
+        let light_color = self.light_color_vec3();
+        (light_color.x, light_color.y, light_color.z)
     }
 
     pub fn set_light_color(&mut self, r: f32, g: f32, b: f32) {
-        self.light_color = Vec3::new(r, g, b);
+        self.main_light_mut().set_color(r, g, b);
     }
 
     pub fn set_light_position(&mut self, x: f32, y: f32, z: f32) {
-        self.light_position = Vec3::new(x, y, z);
+        self.main_light_mut().set_position(x, y, z);
     }
 
     pub fn set_light_position_x(&mut self, x: f32) {
-        self.light_position.x = x;
+        let (_, y, z) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
     pub fn set_light_position_y(&mut self, y: f32) {
-        self.light_position.y = y;
+        let (x, _, z) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
     pub fn set_light_position_z(&mut self, z: f32) {
-        self.light_position.z = z;
+        let (x, y, _) = self.light_position();
+        self.set_light_position(x, y, z);
     }
 
     pub fn light_position(&self) -> (f32, f32, f32) {
-        (self.light_position.x, self.light_position.y, self.light_position.z)
+        self.main_light().map(Light::position).unwrap_or((0.0, 0.0, 0.0))
     }
 
     pub fn light_position_x(&self) -> f32 {
-        self.light_position.x
+        self.light_position().0
     }
 
     pub fn light_position_y(&self) -> f32 {
-        self.light_position.y
+        self.light_position().1
     }
 
     pub fn light_position_z(&self) -> f32 {
-        self.light_position.z
+        self.light_position().2
     }
 
+    #[cfg(feature = "vulkan")]
     pub fn light_data(&self) -> LightData {
+        let mut lights = [ShaderLight { position_range: [0.0; 4], color_intensity: [0.0; 4], direction_type: [0.0; 4], cone: [0.0; 4] }; MAX_LIGHTS];
+        for (shader_light, light) in lights.iter_mut().zip(self.lights.iter()) {
+            let (x, y, z) = light.position();
+            let (r, g, b) = light.color();
+            let (dx, dy, dz) = light.direction();
+            shader_light.position_range = [x, y, z, light.range()];
+            shader_light.color_intensity = [r, g, b, light.intensity()];
+            shader_light.direction_type = [dx, dy, dz, light.light_type().shader_value()];
+            shader_light.cone = [light.inner_cone_angle().cos(), light.outer_cone_angle().cos(), 0.0, 0.0];
+        }
+        let (sky_r, sky_g, sky_b) = self.ambient_light.sky_color;
+        let (ground_r, ground_g, ground_b) = self.ambient_light.ground_color;
         LightData {
-            _dummy0: [0; 4],
-            light_position: self.light_position.into(),
-            light_color: self.light_color.into(),
+            light_count: self.lights.len().min(MAX_LIGHTS) as i32,
+            exposure: self.camera.exposure_multiplier(),
+            _dummy0: [0; 8],
+            ambient_sky: [sky_r, sky_g, sky_b, self.ambient_light.intensity],
+            ambient_ground: [ground_r, ground_g, ground_b, 0.0],
+            lights,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::object::ObjectBuilder;
+
+    #[test]
+    fn light_position_setters_recreate_main_light() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let main_light_id = scene.main_light().unwrap().id();
+        scene.remove_light(main_light_id);
+
+        scene.set_light_position(1.0, 2.0, 3.0);
+
+        assert_eq!(1, scene.lights().len());
+        assert_eq!((1.0, 2.0, 3.0), scene.light_position());
+    }
+
+    #[test]
+    fn child_position_is_relative_to_parent_and_cycles_are_rejected() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let parent = ObjectBuilder::new("parent").with_position(1.0, 2.0, 3.0).build();
+        let child = ObjectBuilder::new("child").with_position(1.0, 0.0, 0.0).build();
+        let (parent_id, child_id) = (parent.id(), child.id());
+        scene.add_object(parent);
+        scene.add_object(child);
+
+        assert!(scene.set_parent(child_id, Some(parent_id)));
+        assert!(!scene.set_parent(parent_id, Some(child_id)));
+        assert_eq!(Vec3::new(2.0, 2.0, 3.0), scene.world_position(scene.object(child_id).unwrap()));
+    }
+
+    #[test]
+    fn only_moved_objects_and_their_children_are_updated() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let parent = ObjectBuilder::new("parent").build();
+        let child = ObjectBuilder::new("child").with_position(1.0, 0.0, 0.0).build();
+        let still = ObjectBuilder::new("still").build();
+        let (parent_id, child_id, still_id) = (parent.id(), child.id(), still.id());
+        scene.add_object(child);
+        scene.add_object(parent);
+        scene.add_object(still);
+        scene.set_parent(child_id, Some(parent_id));
+        assert_eq!(3, scene.update_transforms().len());
+        assert!(scene.update_transforms().is_empty());
+
+        scene.object_mut(parent_id).unwrap().set_position(0.0, 2.0, 0.0);
+        assert_eq!(Vec3::new(1.0, 2.0, 0.0), scene.world_position(scene.object(child_id).unwrap()));
+        let mut moved = scene.update_transforms();
+        moved.sort();
+        let mut expected = vec![parent_id, child_id];
+        expected.sort();
+
+        assert_eq!(expected, moved);
+        assert!(!scene.object(still_id).unwrap().transform_changed());
+        assert_eq!(Vec3::new(1.0, 2.0, 0.0), scene.world_position(scene.object(child_id).unwrap()));
+    }
+
+    #[test]
+    fn added_and_removed_objects_are_reported_once() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let first = ObjectBuilder::new("crate").build();
+        let second = ObjectBuilder::new("crate").build();
+        let (first_id, second_id) = (first.id(), second.id());
+        scene.add_object(first);
+        scene.add_object(second);
+        scene.remove_objects_with_name("crate");
+
+        assert_eq!(vec![
+            ObjectEvent::Added(first_id),
+            ObjectEvent::Added(second_id),
+            ObjectEvent::Removed(second_id),
+            ObjectEvent::Removed(first_id),
+        ], scene.take_object_events());
+        assert!(scene.take_object_events().is_empty());
+        assert!(scene.objects().is_empty());
+    }
+
+    #[test]
+    fn active_camera_object_is_viewed_until_removed() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let mut security_camera = ObjectBuilder::new("security camera").with_position(5.0, 3.0, 0.0).build();
+        security_camera.set_camera(Some(Camera::new()));
+        let crate_object = ObjectBuilder::new("crate").build();
+        let (camera_id, crate_id) = (security_camera.id(), crate_object.id());
+        scene.add_object(security_camera);
+        scene.add_object(crate_object);
+
+        assert!(!scene.set_active_camera(Some(crate_id)));
+        assert!(scene.set_active_camera(Some(camera_id)));
+        assert_eq!(vec![camera_id], scene.camera_objects());
+        assert_eq!(Vec3::new(5.0, 3.0, 0.0), scene.view_camera().position_vec3());
+
+        scene.remove_object(camera_id);
+        assert_eq!(None, scene.active_camera());
+        assert_eq!(scene.camera().position_vec3(), scene.view_camera().position_vec3());
+    }
+
+    #[test]
+    fn shifting_origin_keeps_absolute_positions_of_objects() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let parent = ObjectBuilder::new("parent").with_position(10_000.0, 0.0, 0.0).build();
+        let child = ObjectBuilder::new("child").with_position(1.0, 0.0, 0.0).build();
+        let (parent_id, child_id) = (parent.id(), child.id());
+        scene.add_object(parent);
+        scene.add_object(child);
+        scene.set_parent(child_id, Some(parent_id));
+        scene.update_transforms();
+        scene.camera_mut().set_position(10_000.0, 0.0, 0.0);
+        scene.set_origin_shift_distance(Some(1000.0));
+
+        assert_eq!(Some(Vec3::new(10_000.0, 0.0, 0.0)), scene.update_origin());
+        assert_eq!(None, scene.update_origin());
+        assert!(scene.update_transforms().is_empty());
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), scene.world_position(scene.object(child_id).unwrap()));
+        assert_eq!(DVec3::new(10_001.0, 0.0, 0.0), scene.absolute_position(&scene.world_position(scene.object(child_id).unwrap())));
+        assert_eq!(Vec3::new(0.0, 0.0, 0.0), scene.camera().position_vec3());
+    }
+
+    #[test]
+    fn merged_objects_keep_absolute_positions() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        scene.set_origin(DVec3::new(100.0, 0.0, 0.0));
+        let mut cell = Scene::new("cell", Camera::new());
+        cell.set_origin(DVec3::new(150.0, 0.0, 0.0));
+        cell.add_object(ObjectBuilder::new("tree").with_position(1.0, 2.0, 0.0).build());
+
+        let merged = scene.merge(cell);
+
+        assert_eq!(Vec3::new(51.0, 2.0, 0.0), scene.world_position(scene.object(merged[0]).unwrap()));
+    }
+
+    #[test]
+    fn merged_objects_can_be_removed_again() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let mut cell = Scene::new("cell", Camera::new());
+        let own = ObjectBuilder::new("own").build();
+        let own_id = own.id();
+        scene.add_object(own);
+        cell.add_object(ObjectBuilder::new("tree").build());
+        cell.add_object(ObjectBuilder::new("rock").build());
+        scene.take_object_events();
+
+        let merged = scene.merge(cell);
+        assert_eq!(3, scene.objects().len());
+        assert_eq!(merged.iter().map(|id| ObjectEvent::Added(*id)).collect::<Vec<_>>(), scene.take_object_events());
+
+        let removed = scene.remove_objects(&merged);
+        assert_eq!(2, removed.len());
+        assert_eq!(vec![own_id], scene.objects().iter().map(Object::id).collect::<Vec<_>>());
+        assert_eq!(merged.iter().map(|id| ObjectEvent::Removed(*id)).collect::<Vec<_>>(), scene.take_object_events());
+    }
+}
@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use nalgebra_glm::DVec3;
+use serde::{Deserialize, Serialize};
+use log::*;
+
+use crate::resource::camera::{AutoExposure, Bloom, Camera};
+use crate::resource::light::{AmbientLight, Light, LightType};
+use crate::resource::mesh::Mesh;
+use crate::resource::material::Material;
+use crate::resource::object::{Object, ObjectBuilder};
+use crate::resource::scene::Scene;
+use crate::resource::scene::scene_error::SceneSerializationError;
+use crate::resource::sound::SoundEmitter;
+use crate::resource::particle_effect::ParticleEmitter;
+use crate::resource::rigid_body::RigidBody;
+use crate::resource::collider::Collider;
+use crate::resource::lightmap::Lightmap;
+use crate::resource::render_queue::RenderQueue;
+use crate::resource::render_layers::RenderLayers;
+
+/// Serializable representation of a scene. Meshes are referenced by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SceneData {
+    pub name: String,
+    pub camera: CameraData,
+    pub lights: Vec<LightData>,
+    pub objects: Vec<ObjectData>,
+    #[serde(default)]
+    pub ambient_light: AmbientLight,
+    /// Names of render layers, where index of a name is the bit of its layer.
+    #[serde(default)]
+    pub render_layers: Vec<String>,
+    /// Index of the object whose camera the scene is seen through, or None for the free camera.
+    #[serde(default)]
+    pub active_camera: Option<usize>,
+    /// Absolute position of the origin which positions in the scene are relative to.
+    #[serde(default)]
+    pub origin: (f64, f64, f64),
+    /// Distance from the origin at which the origin is shifted to the camera, or None if it isn't shifted.
+    #[serde(default)]
+    pub origin_shift_distance: Option<f32>,
+}
+
+/// Serializable representation of a camera.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CameraData {
+    pub position: (f32, f32, f32),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+    pub near_plane: f32,
+    pub far_plane: f32,
+    #[serde(default)]
+    pub exposure: f32,
+    #[serde(default)]
+    pub auto_exposure: Option<AutoExposure>,
+    #[serde(default)]
+    pub bloom: Option<Bloom>,
+    #[serde(default = "default_visibility_mask")]
+    pub visibility_mask: RenderLayers,
+}
+
+/// Serializable representation of a light.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LightData {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub color: (f32, f32, f32),
+    pub intensity: f32,
+    pub range: f32,
+    #[serde(default)]
+    pub light_type: LightType,
+    #[serde(default = "default_light_direction")]
+    pub direction: (f32, f32, f32),
+    /// Inner and outer cone angles of a spot light, in radians.
+    #[serde(default = "default_cone_angles")]
+    pub cone_angles: (f32, f32),
+}
+
+/// Serializable representation of an object.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ObjectData {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub rotation_angles: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+    pub light_source: bool,
+    pub mesh: Option<String>,
+    #[serde(default)]
+    pub camera: Option<CameraData>,
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub sound_emitter: Option<SoundEmitter>,
+    #[serde(default)]
+    pub particle_emitter: Option<ParticleEmitter>,
+    #[serde(default)]
+    pub rigid_body: Option<RigidBody>,
+    #[serde(default)]
+    pub collider: Option<Collider>,
+    #[serde(default)]
+    pub render_queue: Option<RenderQueue>,
+    #[serde(default)]
+    pub sort_key: i32,
+    /// Static objects are merged into static batches when the scene is loaded.
+    #[serde(default)]
+    pub is_static: bool,
+    #[serde(default)]
+    pub render_layers: RenderLayers,
+    /// Index of the parent object in the scene object list.
+    #[serde(default)]
+    pub parent: Option<usize>,
+    /// Values of custom shader parameters.
+    #[serde(default)]
+    pub params: BTreeMap<String, f32>,
+    #[serde(default)]
+    pub lightmap: Option<Lightmap>,
+}
+
+impl SceneData {
+    /// Creates serializable representation of a scene.
+    pub fn from_scene(scene: &Scene) -> Self {
+        SceneData {
+            name: scene.name().to_string(),
+            camera: CameraData::from_camera(scene.camera()),
+            lights: scene.lights().iter().map(LightData::from_light).collect(),
+            objects: scene.objects().iter().map(|object| {
+                let mut object_data = ObjectData::from_object(object);
+                object_data.parent = object.parent().and_then(|parent| scene.objects().iter().position(|x| x.id() == parent));
+                object_data
+            }).collect(),
+            ambient_light: scene.ambient_light(),
+            render_layers: scene.render_layer_names().to_vec(),
+            active_camera: scene.active_camera().and_then(|id| scene.objects().iter().position(|x| x.id() == id)),
+            origin: (scene.origin().x, scene.origin().y, scene.origin().z),
+            origin_shift_distance: scene.origin_shift_distance(),
+        }
+    }
+
+    /// Reads scene data from a file. It doesn't need any assets, so unlike loading a whole scene it can be done on another thread.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneSerializationError> {
+        let serialized_scene = std::fs::read_to_string(path)?;
+        SceneData::parse(&serialized_scene)
+    }
+
+    /// Parses scene data saved by `Scene::save`, e.g. embedded in the binary or fetched over the network where there's no filesystem.
+    pub fn parse(serialized_scene: &str) -> Result<Self, SceneSerializationError> {
+        Ok(ron::de::from_str(serialized_scene)?)
+    }
+
+    /// Returns structural problems of the scene which `into_scene` would ignore with a warning, like invalid parents or active camera.
+    /// References to assets aren't checked, as they depend on what's loaded.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (index, object) in self.objects.iter().enumerate() {
+            match object.parent {
+                Some(parent) if parent >= self.objects.len() => {
+                    problems.push(format!("Object {} ({}) has parent {}, but scene has only {} objects", index, object.name, parent, self.objects.len()));
+                },
+                Some(parent) if parent == index => problems.push(format!("Object {} ({}) is its own parent", index, object.name)),
+                _ => (),
+            }
+            // following parents either ends at a root or repeats an object
+            let mut ancestor = object.parent;
+            let mut steps = 0;
+            while let Some(parent) = ancestor.filter(|&parent| parent < self.objects.len() && parent != index) {
+                steps += 1;
+                if steps > self.objects.len() {
+                    break;
+                }
+                ancestor = self.objects[parent].parent;
+            }
+            if ancestor == Some(index) && object.parent != Some(index) {
+                problems.push(format!("Object {} ({}) is in a cycle of parents", index, object.name));
+            }
+        }
+        match self.active_camera {
+            Some(index) if index >= self.objects.len() => {
+                problems.push(format!("Active camera is object {}, but scene has only {} objects", index, self.objects.len()));
+            },
+            Some(index) if self.objects[index].camera.is_none() => {
+                problems.push(format!("Active camera is object {} ({}), which doesn't have a camera", index, self.objects[index].name));
+            },
+            _ => (),
+        }
+        problems
+    }
+
+    /// Creates a scene from this data. Meshes and materials are found using `mesh_lookup` and `material_lookup`,
+    /// objects referencing unknown ones are created without them.
+    pub fn into_scene<F, M>(self, mesh_lookup: F, material_lookup: M) -> Scene
+        where F: Fn(&str) -> Option<Arc<RwLock<Mesh>>>,
+              M: Fn(&str) -> Option<Arc<RwLock<Material>>> {
+        let mut scene = Scene::new(self.name, self.camera.into_camera());
+        scene.set_ambient_light(self.ambient_light);
+        let (origin_x, origin_y, origin_z) = self.origin;
+        scene.set_origin(DVec3::new(origin_x, origin_y, origin_z));
+        scene.set_origin_shift_distance(self.origin_shift_distance);
+        for render_layer in self.render_layers {
+            if scene.add_render_layer(render_layer.as_str()).is_none() {
+                warn!("Ignoring render layer {}, because scene already has all layers", render_layer);
+            }
+        }
+        scene.clear_lights();
+        for light_data in self.lights {
+            scene.add_light(light_data.into_light());
+        }
+        let parents: Vec<Option<usize>> = self.objects.iter().map(|x| x.parent).collect();
+        let objects: Vec<Object> = self.objects.into_iter().map(|x| x.into_object(&mesh_lookup, &material_lookup)).collect();
+        let object_ids: Vec<u32> = objects.iter().map(Object::id).collect();
+        for object in objects {
+            scene.add_object(object);
+        }
+        for (id, parent) in object_ids.iter().zip(parents) {
+            let parent_id = parent.and_then(|index| object_ids.get(index).cloned());
+            if parent_id.is_some() && !scene.set_parent(*id, parent_id) {
+                warn!("Ignoring parent of object {}, because it would create a cycle", id);
+            }
+        }
+        if let Some(id) = self.active_camera.and_then(|index| object_ids.get(index).cloned()) {
+            if !scene.set_active_camera(Some(id)) {
+                warn!("Ignoring active camera, because object {} doesn't have a camera", id);
+            }
+        }
+        scene
+    }
+}
+
+impl CameraData {
+    /// Creates serializable representation of a camera.
+    pub fn from_camera(camera: &Camera) -> Self {
+        CameraData {
+            position: camera.position(),
+            yaw: camera.yaw(),
+            pitch: camera.pitch(),
+            fov: camera.fov(),
+            near_plane: camera.near_plane(),
+            far_plane: camera.far_plane(),
+            exposure: camera.exposure(),
+            auto_exposure: camera.auto_exposure(),
+            bloom: camera.bloom(),
+            visibility_mask: camera.visibility_mask(),
+        }
+    }
+
+    /// Creates a camera from this data.
+    pub fn into_camera(self) -> Camera {
+        let mut camera = Camera::new();
+        let (x, y, z) = self.position;
+        camera.set_position(x, y, z);
+        camera.set_yaw(self.yaw);
+        camera.set_pitch(self.pitch);
+        camera.set_fov(self.fov);
+        camera.set_near_plane(self.near_plane);
+        camera.set_far_plane(self.far_plane);
+        camera.set_exposure(self.exposure);
+        camera.set_auto_exposure(self.auto_exposure);
+        camera.set_bloom(self.bloom);
+        camera.set_visibility_mask(self.visibility_mask);
+        camera
+    }
+}
+
+impl LightData {
+    /// Creates serializable representation of a light.
+    pub fn from_light(light: &Light) -> Self {
+        LightData {
+            name: light.name().to_string(),
+            position: light.position(),
+            color: light.color(),
+            intensity: light.intensity(),
+            range: light.range(),
+            light_type: light.light_type(),
+            direction: light.direction(),
+            cone_angles: (light.inner_cone_angle(), light.outer_cone_angle()),
+        }
+    }
+
+    /// Creates a light from this data.
+    pub fn into_light(self) -> Light {
+        let mut light = Light::new(self.name);
+        let (x, y, z) = self.position;
+        light.set_position(x, y, z);
+        let (r, g, b) = self.color;
+        light.set_color(r, g, b);
+        light.set_intensity(self.intensity);
+        light.set_range(self.range);
+        light.set_light_type(self.light_type);
+        let (x, y, z) = self.direction;
+        light.set_direction(x, y, z);
+        let (inner, outer) = self.cone_angles;
+        light.set_cone_angles(inner, outer);
+        light
+    }
+}
+
+/// Visibility mask of cameras saved before render layers were added, which draws everything.
+fn default_visibility_mask() -> RenderLayers {
+    RenderLayers::ALL
+}
+
+/// Direction of lights saved before lights had directions.
+fn default_light_direction() -> (f32, f32, f32) {
+    Light::new("").direction()
+}
+
+/// Cone angles of lights saved before spot lights were added.
+fn default_cone_angles() -> (f32, f32) {
+    let light = Light::new("");
+    (light.inner_cone_angle(), light.outer_cone_angle())
+}
+
+impl ObjectData {
+    /// Creates serializable representation of an object.
+    pub fn from_object(object: &Object) -> Self {
+        ObjectData {
+            name: object.name().to_string(),
+            position: object.position(),
+            rotation_angles: object.rotation_angles(),
+            scale: object.scale(),
+            light_source: object.light_source(),
+            mesh: object.mesh().map(|mesh| mesh.read().unwrap().name().to_string()),
+            camera: object.camera().map(CameraData::from_camera),
+            material: object.material().map(|material| material.read().unwrap().name().to_string()),
+            sound_emitter: object.sound_emitter().cloned(),
+            particle_emitter: object.particle_emitter().cloned(),
+            rigid_body: object.rigid_body().cloned(),
+            collider: object.collider().cloned(),
+            render_queue: object.render_queue(),
+            sort_key: object.sort_key(),
+            is_static: object.is_static(),
+            render_layers: object.render_layers(),
+            parent: None,
+            params: object.params().clone(),
+            lightmap: object.lightmap().cloned(),
+        }
+    }
+
+    /// Creates an object from this data.
+    pub fn into_object<F, M>(self, mesh_lookup: &F, material_lookup: &M) -> Object
+        where F: Fn(&str) -> Option<Arc<RwLock<Mesh>>>,
+              M: Fn(&str) -> Option<Arc<RwLock<Material>>> {
+        let (x, y, z) = self.position;
+        let (rotation_x, rotation_y, rotation_z) = self.rotation_angles;
+        let (scale_x, scale_y, scale_z) = self.scale;
+        let mut object_builder = ObjectBuilder::new(self.name).with_position(x, y, z)
+                                                              .with_rotation_angle(rotation_x, rotation_y, rotation_z)
+                                                              .with_scale(scale_x, scale_y, scale_z);
+        if let Some(mesh_name) = self.mesh {
+            match mesh_lookup(&mesh_name) {
+                Some(mesh) => object_builder = object_builder.with_mesh(mesh),
+                None => warn!("Mesh {} referenced by scene wasn't found", mesh_name),
+            }
+        }
+        let mut object = object_builder.build();
+        object.set_light_source(self.light_source);
+        object.set_camera(self.camera.map(CameraData::into_camera));
+        object.set_sound_emitter(self.sound_emitter);
+        object.set_particle_emitter(self.particle_emitter);
+        object.set_rigid_body(self.rigid_body);
+        object.set_collider(self.collider);
+        object.set_lightmap(self.lightmap);
+        object.set_render_queue(self.render_queue);
+        object.set_sort_key(self.sort_key);
+        object.set_static(self.is_static);
+        object.set_render_layers(self.render_layers);
+        for (name, value) in self.params {
+            object.set_param(name, value);
+        }
+        if let Some(material_name) = self.material {
+            match material_lookup(&material_name) {
+                Some(material) => object.set_material(Some(material)),
+                None => warn!("Material {} referenced by scene wasn't found", material_name),
+            }
+        }
+        object
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_data_survives_serialization() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        scene.set_light_position(1.0, 2.0, 3.0);
+        scene.add_object(ObjectBuilder::new("test_object").with_position(1.0, 0.0, -1.0).build());
+        let scene_data = SceneData::from_scene(&scene);
+
+        let serialized = ron::ser::to_string(&scene_data).unwrap();
+        let deserialized: SceneData = ron::de::from_str(&serialized).unwrap();
+
+        assert_eq!(scene_data, deserialized);
+    }
+
+    #[test]
+    fn camera_data_restores_bloom() {
+        let mut camera = Camera::new();
+        camera.set_bloom(Some(Bloom { threshold: 2.0, intensity: 0.25 }));
+
+        let serialized = ron::ser::to_string(&CameraData::from_camera(&camera)).unwrap();
+        let camera_data: CameraData = ron::de::from_str(&serialized).unwrap();
+
+        assert_eq!(camera.bloom(), camera_data.into_camera().bloom());
+    }
+
+    #[test]
+    fn into_scene_restores_ambient_light() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let ambient_light = AmbientLight { sky_color: (0.4, 0.6, 1.0), ground_color: (0.3, 0.2, 0.1), intensity: 0.5 };
+        scene.set_ambient_light(ambient_light);
+
+        let restored = SceneData::from_scene(&scene).into_scene(|_| None, |_| None);
+
+        assert_eq!(ambient_light, restored.ambient_light());
+    }
+
+    #[test]
+    fn light_data_restores_spot_light() {
+        let mut light = Light::spot("flashlight", (1.0, 2.0, 3.0), (0.0, 0.0, -1.0));
+        light.set_cone_angles(0.2, 0.4);
+
+        let restored = LightData::from_light(&light).into_light();
+
+        assert_eq!(LightType::Spot, restored.light_type());
+        assert_eq!((0.0, 0.0, -1.0), restored.direction());
+        assert_eq!((0.2, 0.4), (restored.inner_cone_angle(), restored.outer_cone_angle()));
+    }
+
+    #[test]
+    fn into_scene_restores_objects() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        scene.add_object(ObjectBuilder::new("test_object").with_scale(2.0, 2.0, 2.0).build());
+
+        let restored_scene = SceneData::from_scene(&scene).into_scene(|_| None, |_| None);
+
+        assert_eq!(1, restored_scene.objects().len());
+        assert_eq!((2.0, 2.0, 2.0), restored_scene.objects()[0].scale());
+    }
+
+    #[test]
+    fn into_scene_restores_parents() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let parent = ObjectBuilder::new("parent").build();
+        let mut child = ObjectBuilder::new("child").build();
+        child.set_parent(Some(parent.id()));
+        scene.add_object(parent);
+        scene.add_object(child);
+
+        let restored_scene = SceneData::from_scene(&scene).into_scene(|_| None, |_| None);
+
+        let restored_parent_id = restored_scene.objects()[0].id();
+        assert_eq!(Some(restored_parent_id), restored_scene.objects()[1].parent());
+    }
+
+    #[test]
+    fn validate_finds_invalid_parents_and_camera() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        scene.add_object(ObjectBuilder::new("a").build());
+        scene.add_object(ObjectBuilder::new("b").build());
+        scene.add_object(ObjectBuilder::new("c").build());
+        let mut scene_data = SceneData::from_scene(&scene);
+        assert!(scene_data.validate().is_empty());
+
+        scene_data.objects[0].parent = Some(1);
+        scene_data.objects[1].parent = Some(0);
+        scene_data.objects[2].parent = Some(5);
+        scene_data.active_camera = Some(2);
+
+        assert_eq!(4, scene_data.validate().len());
+    }
+
+    #[test]
+    fn into_scene_restores_active_camera() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let mut cutscene_camera = ObjectBuilder::new("cutscene camera").build();
+        cutscene_camera.set_camera(Some(Camera::new()));
+        let camera_id = cutscene_camera.id();
+        scene.add_object(ObjectBuilder::new("crate").build());
+        scene.add_object(cutscene_camera);
+        scene.set_active_camera(Some(camera_id));
+
+        let restored_scene = SceneData::from_scene(&scene).into_scene(|_| None, |_| None);
+
+        assert_eq!(Some(restored_scene.objects()[1].id()), restored_scene.active_camera());
+    }
+}
@@ -0,0 +1,177 @@
+use std::sync::{Arc, RwLock};
+
+use nalgebra_glm::{self as glm, Mat4, Vec3, Vec4};
+
+use crate::renderer::renderer_error::RenderError;
+use crate::renderer::upload::AssetUploader;
+use crate::resource::material::Material;
+use crate::resource::mesh::{Mesh, Topology, Vertex};
+use crate::resource::object::{Object, ObjectBuilder};
+use crate::resource::render_queue::RenderQueue;
+use crate::resource::render_layers::RenderLayers;
+
+/// Maximum number of vertices merged into a single batch, so that batches of large levels can still be culled in parts.
+const MAX_BATCH_VERTICES: usize = 65536;
+
+/// Static objects sharing material, texture, drawing order and render layers, merged into a single mesh which is drawn instead of them.
+#[derive(Clone)]
+pub struct StaticBatch {
+    object: Object,
+    sources: Vec<u32>,
+}
+
+impl StaticBatch {
+    /// Returns object drawing the merged mesh. Vertices of the mesh are in world space, so the object is placed at the origin,
+    /// until the scene shifts its origin.
+    pub fn object(&self) -> &Object {
+        &self.object
+    }
+
+    /// Moves the merged mesh by the negated offset, when the scene shifts its origin by it.
+    pub(crate) fn shift_origin(&mut self, offset: &Vec3) {
+        let (x, y, z) = self.object.position();
+        self.object.set_position(x - offset.x, y - offset.y, z - offset.z);
+    }
+
+    /// Returns ids of objects merged into the batch.
+    pub fn sources(&self) -> &[u32] {
+        &self.sources
+    }
+}
+
+/// Properties of objects which have to be equal for them to be drawn by the same batch.
+#[derive(PartialEq)]
+struct BatchKey {
+    material: usize,
+    texture: usize,
+    topology: Topology,
+    render_queue: Option<RenderQueue>,
+    sort_key: i32,
+    render_layers: RenderLayers,
+}
+
+/// Object merged into a batch, with its world matrix and mesh.
+struct BatchSource {
+    id: u32,
+    world_matrix: Mat4,
+    mesh: Arc<RwLock<Mesh>>,
+}
+
+/// Objects batched together, along with the material they share.
+type BatchGroup = (BatchKey, Option<Arc<RwLock<Material>>>, Vec<BatchSource>);
+
+/// Merges meshes of static objects into batches. Objects without a mesh, light sources, which glow with color of the light,
+/// and lightmapped objects, whose merged vertices would lose lightmap coordinates, aren't batched.
+pub(crate) fn bake<'a, I, W>(scene_name: &str, objects: I, world_matrix: W, uploader: Option<&AssetUploader>) -> Result<Vec<StaticBatch>, RenderError>
+    where I: Iterator<Item = &'a Object>,
+          W: Fn(&Object) -> Mat4 {
+    let mut groups: Vec<BatchGroup> = Vec::new();
+    for object in objects.filter(|object| object.is_static() && !object.light_source() && object.lightmap().is_none()) {
+        let mesh = match object.mesh() {
+            Some(mesh) => mesh,
+            None => continue,
+        };
+        let material = object.material();
+        let key = {
+            let mesh = mesh.read().unwrap();
+            BatchKey {
+                material: material.as_ref().map(|material| &**material as *const RwLock<Material> as usize).unwrap_or(0),
+                texture: &*mesh.texture() as *const _ as usize,
+                topology: mesh.topology(),
+                render_queue: object.render_queue(),
+                sort_key: object.sort_key(),
+                render_layers: object.render_layers(),
+            }
+        };
+        let source = BatchSource { id: object.id(), world_matrix: world_matrix(object), mesh };
+        match groups.iter_mut().find(|(group_key, _, _)| *group_key == key) {
+            Some((_, _, sources)) => sources.push(source),
+            None => groups.push((key, material, vec![source])),
+        }
+    }
+
+    let mut batches = Vec::new();
+    for (key, material, sources) in groups {
+        let mut batch_sources: Vec<&BatchSource> = Vec::new();
+        let mut vertex_count = 0;
+        for (index, source) in sources.iter().enumerate() {
+            vertex_count += source.mesh.read().unwrap().vertices().len();
+            batch_sources.push(source);
+            let next_vertex_count = sources.get(index + 1).map(|next| next.mesh.read().unwrap().vertices().len()).unwrap_or(0);
+            if index + 1 == sources.len() || vertex_count + next_vertex_count > MAX_BATCH_VERTICES {
+                let name = format!("{} static batch {}", scene_name, batches.len());
+                batches.push(upload_batch(name, &batch_sources, &key, material.clone(), uploader)?);
+                batch_sources.clear();
+                vertex_count = 0;
+            }
+        }
+    }
+    Ok(batches)
+}
+
+/// Uploads merged mesh of the sources and creates object drawing it.
+fn upload_batch(name: String, sources: &[&BatchSource], key: &BatchKey, material: Option<Arc<RwLock<Material>>>,
+                uploader: Option<&AssetUploader>) -> Result<StaticBatch, RenderError> {
+    let meshes: Vec<_> = sources.iter().map(|source| source.mesh.read().unwrap()).collect();
+    let (vertices, indices) = merge_meshes(sources.iter().zip(&meshes).map(|(source, mesh)| (source.world_matrix, mesh.vertices(), mesh.indices())));
+    let mut mesh = Mesh::create(name.clone(), &vertices[..], indices, meshes[0].texture(), uploader)?;
+    mesh.set_topology(key.topology);
+    let bounds = mesh.bounds();
+
+    let mut object = ObjectBuilder::new(name).with_mesh(Arc::new(RwLock::new(mesh))).build();
+    object.set_material(material);
+    object.set_render_queue(key.render_queue);
+    object.set_sort_key(key.sort_key);
+    object.set_render_layers(key.render_layers);
+    object.set_world_transform(Mat4::identity(), Some(bounds));
+    Ok(StaticBatch {
+        object,
+        sources: sources.iter().map(|source| source.id).collect(),
+    })
+}
+
+/// Merges meshes into a single vertex and index list, transforming vertices to world space.
+fn merge_meshes<'a, I>(meshes: I) -> (Vec<Vertex>, Vec<u32>)
+    where I: Iterator<Item = (Mat4, &'a [Vertex], &'a [u32])> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (world_matrix, mesh_vertices, mesh_indices) in meshes {
+        let normal_matrix = glm::inverse_transpose(glm::mat4_to_mat3(&world_matrix));
+        let first_index = vertices.len() as u32;
+        vertices.extend(mesh_vertices.iter().map(|vertex| {
+            let [x, y, z] = vertex.position;
+            let position = world_matrix * Vec4::new(x, y, z, 1.0);
+            let [nx, ny, nz] = vertex.normal;
+            let normal = glm::normalize(&(normal_matrix * Vec3::new(nx, ny, nz)));
+            Vertex {
+                position: [position.x, position.y, position.z],
+                normal: [normal.x, normal.y, normal.z],
+                tex_coord: vertex.tex_coord,
+            }
+        }));
+        indices.extend(mesh_indices.iter().map(|index| index + first_index));
+    }
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_meshes_are_moved_to_world_space() {
+        let vertex = |x, y, z| Vertex { position: [x, y, z], normal: [1.0, 0.0, 0.0], tex_coord: [0.0, 0.0] };
+        let triangle = [vertex(0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0)];
+        let indices = [0, 1, 2];
+        let moved = glm::translate(&Mat4::identity(), &Vec3::new(0.0, 0.0, 5.0));
+        let scaled = glm::scale(&Mat4::identity(), &Vec3::new(2.0, 1.0, 1.0));
+
+        let (vertices, merged_indices) = merge_meshes(vec![(moved, &triangle[..], &indices[..]), (scaled, &triangle[..], &indices[..])].into_iter());
+
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], merged_indices);
+        assert_eq!([0.0, 0.0, 5.0], vertices[0].position);
+        assert_eq!([2.0, 0.0, 0.0], vertices[4].position);
+        // normals are normalized after non-uniform scaling
+        assert_eq!([1.0, 0.0, 0.0], vertices[4].normal);
+    }
+}
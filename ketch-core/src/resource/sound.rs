@@ -0,0 +1,79 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// Encoded sound file, e.g. wav, ogg or flac. Sounds are decoded by the audio system when played.
+pub struct Sound {
+    name: String,
+    data: Arc<Vec<u8>>,
+}
+
+impl Sound {
+    /// Creates sound from encoded data.
+    pub fn new<S: Into<String>>(name: S, data: Vec<u8>) -> Self {
+        Sound {
+            name: name.into(),
+            data: Arc::new(data),
+        }
+    }
+
+    /// Loads sound from a file.
+    pub fn load<S: Into<String>, P: AsRef<Path>>(name: S, path: P) -> std::io::Result<Self> {
+        Ok(Sound::new(name, std::fs::read(path)?))
+    }
+
+    /// Returns the name of this sound.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns encoded data of this sound.
+    pub fn data(&self) -> Arc<Vec<u8>> {
+        self.data.clone()
+    }
+}
+
+/// Sound played at the position of the object it's attached to. Sounds are referenced by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SoundEmitter {
+    pub sound: String,
+    pub volume: f32,
+    /// Distance at which the sound can't be heard anymore.
+    pub range: f32,
+    pub looping: bool,
+}
+
+impl SoundEmitter {
+    /// Creates looping emitter of given sound with full volume.
+    pub fn new<S: Into<String>>(sound: S, range: f32) -> Self {
+        SoundEmitter {
+            sound: sound.into(),
+            volume: 1.0,
+            range,
+            looping: true,
+        }
+    }
+
+    /// Returns volume of the sound heard from given distance. Volume falls off linearly and reaches zero at the emitter range.
+    pub fn volume_at(&self, distance: f32) -> f32 {
+        if self.range <= 0.0 {
+            return 0.0;
+        }
+        self.volume * (1.0 - distance / self.range).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emitter_volume_falls_off_with_distance() {
+        let emitter = SoundEmitter::new("test_sound", 10.0);
+
+        assert_eq!(1.0, emitter.volume_at(0.0));
+        assert_eq!(0.5, emitter.volume_at(5.0));
+        assert_eq!(0.0, emitter.volume_at(20.0));
+    }
+}
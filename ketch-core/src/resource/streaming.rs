@@ -0,0 +1,184 @@
+use image::{DynamicImage, GenericImageView};
+use nalgebra_glm::Mat4;
+
+use crate::math::bounds::Bounds;
+use crate::math::frustum::Frustum;
+use crate::resource::mesh::Vertex;
+
+/// Default number of bytes of streamed assets uploaded per frame.
+pub const DEFAULT_UPLOAD_BUDGET: u64 = 8 * 1024 * 1024;
+
+/// Data of a streamed asset, kept on the CPU until it's uploaded.
+pub enum StreamedAsset {
+    Mesh { vertices: Vec<Vertex>, indices: Vec<u32> },
+    Texture(DynamicImage),
+}
+
+/// Asset waiting to be uploaded by asset manager, within the upload budget of a frame.
+pub struct StreamRequest {
+    name: String,
+    asset: StreamedAsset,
+    priority: i32,
+    bounds: Option<Bounds>,
+}
+
+impl StreamRequest {
+    /// Creates request uploading mesh with given name, vertices and indices.
+    pub fn mesh<S: Into<String>>(name: S, vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        StreamRequest::new(name, StreamedAsset::Mesh { vertices, indices })
+    }
+
+    /// Creates request uploading texture with given name from loaded image.
+    pub fn texture<S: Into<String>>(name: S, image: DynamicImage) -> Self {
+        StreamRequest::new(name, StreamedAsset::Texture(image))
+    }
+
+    fn new<S: Into<String>>(name: S, asset: StreamedAsset) -> Self {
+        StreamRequest {
+            name: name.into(),
+            asset,
+            priority: 0,
+            bounds: None,
+        }
+    }
+
+    /// Sets priority of this request. Among equally visible requests, higher priority is uploaded first.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets world space bounds of the place where asset is used. Assets seen by the camera are uploaded before all others.
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Returns name of the streamed asset.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns priority of this request.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Returns number of bytes copied to the GPU by this request. Textures are measured before downscaling by texture quality.
+    pub fn size(&self) -> u64 {
+        match &self.asset {
+            StreamedAsset::Mesh { vertices, indices } => {
+                (vertices.len() * std::mem::size_of::<Vertex>() + indices.len() * std::mem::size_of::<u32>()) as u64
+            },
+            StreamedAsset::Texture(image) => {
+                let (width, height) = image.dimensions();
+                u64::from(width) * u64::from(height) * 4
+            },
+        }
+    }
+
+    pub(crate) fn into_asset(self) -> (String, StreamedAsset) {
+        (self.name, self.asset)
+    }
+
+    fn visible(&self, frustum: Option<&Frustum>) -> bool {
+        match (frustum, &self.bounds) {
+            (Some(frustum), Some(bounds)) => frustum.intersects_bounds(bounds, &Mat4::identity()),
+            _ => false,
+        }
+    }
+}
+
+/// Assets waiting to be uploaded, which are uploaded a few at a time, so that streaming doesn't cause a hitch.
+pub struct StreamingQueue {
+    requests: Vec<StreamRequest>,
+    budget: u64,
+}
+
+impl StreamingQueue {
+    /// Creates empty queue uploading given number of bytes per frame.
+    pub fn new(budget: u64) -> Self {
+        StreamingQueue {
+            requests: Vec::new(),
+            budget,
+        }
+    }
+
+    /// Sets number of bytes uploaded per frame. Asset bigger than the budget is still uploaded, alone in its frame.
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+    }
+
+    /// Returns number of bytes uploaded per frame.
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// Adds request to the queue, replacing waiting request for asset with the same name.
+    pub fn push(&mut self, request: StreamRequest) {
+        self.requests.retain(|waiting| waiting.name != request.name);
+        self.requests.push(request);
+    }
+
+    /// Removes waiting request for asset with given name, e.g. when the level using it was unloaded.
+    pub fn cancel(&mut self, name: &str) -> Option<StreamRequest> {
+        let index = self.requests.iter().position(|request| request.name == name)?;
+        Some(self.requests.remove(index))
+    }
+
+    /// Returns number of waiting requests.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if no assets are waiting to be uploaded.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Removes and returns requests uploaded this frame: assets inside the frustum first, then by priority and order of requests.
+    /// Requests are taken in that order while they fit in the budget, and the first one is always taken, so every asset gets uploaded.
+    pub(crate) fn next_uploads(&mut self, frustum: Option<&Frustum>) -> Vec<StreamRequest> {
+        // sort is stable, so requests with equal keys stay in order they were made
+        self.requests.sort_by_key(|request| (!request.visible(frustum), -i64::from(request.priority)));
+        let mut spent = 0;
+        let count = self.requests.iter().enumerate().take_while(|(index, request)| {
+            spent += request.size();
+            *index == 0 || spent <= self.budget
+        }).count();
+        self.requests.drain(..count).collect()
+    }
+}
+
+impl Default for StreamingQueue {
+    fn default() -> Self {
+        StreamingQueue::new(DEFAULT_UPLOAD_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec3;
+
+    #[test]
+    fn visible_assets_are_uploaded_first_within_budget() {
+        let texture = |name: &str, size: u32| StreamRequest::texture(name, DynamicImage::new_rgba8(size, size));
+        let at = |x: f32| Bounds::new(Vec3::new(x - 0.1, -0.1, 0.4), Vec3::new(x + 0.1, 0.1, 0.6));
+        // identity projection sees the box from -1 to 1 on x and y, and 0 to 1 on depth
+        let frustum = Frustum::from_matrix(&Mat4::identity());
+        let mut queue = StreamingQueue::new(3 * 16 * 16 * 4);
+        queue.push(texture("far", 16).with_bounds(at(10.0)).with_priority(5));
+        queue.push(texture("unplaced", 16));
+        queue.push(texture("visible", 16).with_bounds(at(0.0)));
+        queue.push(texture("important", 16).with_priority(1));
+
+        let names = |uploads: Vec<StreamRequest>| uploads.iter().map(|request| request.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(vec!["visible", "far", "important"], names(queue.next_uploads(Some(&frustum))));
+        assert_eq!(vec!["unplaced"], names(queue.next_uploads(Some(&frustum))));
+
+        queue.push(texture("huge", 64));
+        assert_eq!(vec!["huge"], names(queue.next_uploads(None)));
+        assert!(queue.is_empty());
+    }
+}
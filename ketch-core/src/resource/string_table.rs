@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resource::string_table::string_table_error::StringTableError;
+
+pub mod string_table_error;
+
+/// Translated strings of a single language, identified by keys shared between languages.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct StringTable {
+    language: String,
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    /// Creates empty string table for the language.
+    pub fn new<S: Into<String>>(language: S) -> Self {
+        StringTable {
+            language: language.into(),
+            strings: HashMap::new(),
+        }
+    }
+
+    /// Loads string table from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, StringTableError> {
+        let serialized_table = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized_table)?)
+    }
+
+    /// Saves string table to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), StringTableError> {
+        let serialized_table = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, serialized_table)?;
+        Ok(())
+    }
+
+    /// Returns language of the strings.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Sets string for the key, replacing previous one.
+    pub fn insert<K: Into<String>, S: Into<String>>(&mut self, key: K, string: S) {
+        self.strings.insert(key.into(), string.into());
+    }
+
+    /// Returns string for the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+
+    /// Adds strings of another table, replacing strings with the same keys.
+    pub fn merge(&mut self, other: StringTable) {
+        self.strings.extend(other.strings);
+    }
+}
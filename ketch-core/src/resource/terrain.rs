@@ -0,0 +1,271 @@
+use std::path::Path;
+
+use nalgebra_glm as glm;
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::math::ray::Ray;
+use crate::resource::mesh::Vertex;
+use crate::resource::terrain::terrain_error::TerrainError;
+
+pub mod terrain_error;
+
+/// Terrain made of a square grid of heights, lying on the XZ plane and centered at the origin.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Terrain {
+    name: String,
+    /// Number of grid points along each side.
+    resolution: u32,
+    /// Width and depth of the terrain.
+    size: f32,
+    heights: Vec<f32>,
+}
+
+impl Terrain {
+    /// Creates flat terrain. Resolution is at least 2.
+    pub fn new<S: Into<String>>(name: S, resolution: u32, size: f32) -> Self {
+        let resolution = resolution.max(2);
+        let point_count = (resolution * resolution) as usize;
+        Terrain {
+            name: name.into(),
+            resolution,
+            size,
+            heights: vec![0.0; point_count],
+        }
+    }
+
+    /// Loads terrain from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TerrainError> {
+        let serialized_terrain = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized_terrain)?)
+    }
+
+    /// Saves terrain to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TerrainError> {
+        let serialized_terrain = ron::ser::to_string(self)?;
+        std::fs::write(path, serialized_terrain)?;
+        Ok(())
+    }
+
+    /// Returns the name of this terrain, which is also the name of its mesh.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns number of grid points along each side.
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Returns width and depth of the terrain.
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Returns height of the grid point.
+    pub fn height(&self, x: u32, z: u32) -> f32 {
+        self.heights[self.index(x, z)]
+    }
+
+    /// Sets height of the grid point.
+    pub fn set_height(&mut self, x: u32, z: u32, height: f32) {
+        let index = self.index(x, z);
+        self.heights[index] = height;
+    }
+
+    /// Returns height at the local position, interpolated between grid points. Positions outside the terrain are clamped to its edges.
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let (grid_x, grid_z) = self.to_grid(x, z);
+        let last = (self.resolution - 1) as f32;
+        let (grid_x, grid_z) = (grid_x.max(0.0).min(last), grid_z.max(0.0).min(last));
+        let (x0, z0) = (grid_x.floor().min(last - 1.0) as u32, grid_z.floor().min(last - 1.0) as u32);
+        let (tx, tz) = (grid_x - x0 as f32, grid_z - z0 as f32);
+        let top = self.height(x0, z0) * (1.0 - tx) + self.height(x0 + 1, z0) * tx;
+        let bottom = self.height(x0, z0 + 1) * (1.0 - tx) + self.height(x0 + 1, z0 + 1) * tx;
+        top * (1.0 - tz) + bottom * tz
+    }
+
+    /// Raises grid points within the radius around the local position by up to the amount, less towards the edge of the brush.
+    /// Negative amount lowers the terrain.
+    pub fn raise(&mut self, x: f32, z: f32, radius: f32, amount: f32) {
+        for (index, falloff) in self.brush_points(x, z, radius) {
+            self.heights[index] += amount * falloff;
+        }
+    }
+
+    /// Moves heights within the radius around the local position towards the average of their neighbours.
+    /// Strength from 0.0 to 1.0 says how far they are moved.
+    pub fn smooth(&mut self, x: f32, z: f32, radius: f32, strength: f32) {
+        let smoothed: Vec<(usize, f32)> = self.brush_points(x, z, radius).into_iter().map(|(index, falloff)| {
+            let (point_x, point_z) = (index as u32 % self.resolution, index as u32 / self.resolution);
+            let average = self.neighbour_average(point_x, point_z);
+            let t = (strength * falloff).clamp(0.0, 1.0);
+            (index, self.heights[index] + (average - self.heights[index]) * t)
+        }).collect();
+        for (index, height) in smoothed {
+            self.heights[index] = height;
+        }
+    }
+
+    /// Returns the closest point where the ray hits the terrain, in local coordinates.
+    pub fn raycast(&self, ray: &Ray) -> Option<Vec3> {
+        let step = self.cell_size() * 0.5;
+        let half_size = self.size * 0.5;
+        // the ray is marched only through the terrain bounds, extended by the range of heights
+        let (min_height, max_height) = self.heights.iter().fold((0.0f32, 0.0f32), |(min, max), height| (min.min(*height), max.max(*height)));
+        let (start, end) = ray_box_range(ray, Vec3::new(-half_size, min_height, -half_size), Vec3::new(half_size, max_height, half_size))?;
+        let mut previous = start;
+        let mut distance = start;
+        while distance <= end + step {
+            let point = ray.point_at(distance.min(end));
+            if point.y <= self.height_at(point.x, point.z) {
+                if distance == start {
+                    return Some(point);
+                }
+                // refine the hit between the last point above and the first point below the terrain
+                let (mut above, mut below) = (previous, distance.min(end));
+                for _ in 0..16 {
+                    let middle = (above + below) * 0.5;
+                    let point = ray.point_at(middle);
+                    if point.y <= self.height_at(point.x, point.z) {
+                        below = middle;
+                    } else {
+                        above = middle;
+                    }
+                }
+                return Some(ray.point_at(below));
+            }
+            previous = distance;
+            distance += step;
+        }
+        None
+    }
+
+    /// Generates vertices and indices of the terrain mesh, with normals computed from neighbouring heights.
+    pub fn generate(&self) -> (Vec<Vertex>, Vec<u32>) {
+        let resolution = self.resolution;
+        let cell_size = self.cell_size();
+        let half_size = self.size * 0.5;
+        let mut vertices = Vec::with_capacity(self.heights.len());
+        for z in 0..resolution {
+            for x in 0..resolution {
+                let left = self.height(x.saturating_sub(1), z);
+                let right = self.height((x + 1).min(resolution - 1), z);
+                let back = self.height(x, z.saturating_sub(1));
+                let front = self.height(x, (z + 1).min(resolution - 1));
+                let normal = glm::normalize(&Vec3::new(left - right, 2.0 * cell_size, back - front));
+                let u = x as f32 / (resolution - 1) as f32;
+                let v = z as f32 / (resolution - 1) as f32;
+                vertices.push(Vertex {
+                    position: [-half_size + x as f32 * cell_size, self.height(x, z), -half_size + z as f32 * cell_size],
+                    normal: [normal.x, normal.y, normal.z],
+                    tex_coord: [u, v],
+                });
+            }
+        }
+        let mut indices = Vec::with_capacity(((resolution - 1) * (resolution - 1) * 6) as usize);
+        for z in 0..resolution - 1 {
+            for x in 0..resolution - 1 {
+                let back_left = z * resolution + x;
+                let front_left = back_left + resolution;
+                indices.extend_from_slice(&[front_left, front_left + 1, back_left + 1, front_left, back_left + 1, back_left]);
+            }
+        }
+        (vertices, indices)
+    }
+
+    fn index(&self, x: u32, z: u32) -> usize {
+        (z * self.resolution + x) as usize
+    }
+
+    fn cell_size(&self) -> f32 {
+        self.size / (self.resolution - 1) as f32
+    }
+
+    fn to_grid(&self, x: f32, z: f32) -> (f32, f32) {
+        let half_size = self.size * 0.5;
+        ((x + half_size) / self.cell_size(), (z + half_size) / self.cell_size())
+    }
+
+    /// Returns indices of grid points within the radius, with falloff going smoothly from 1.0 in the center to 0.0 at the radius.
+    fn brush_points(&self, x: f32, z: f32, radius: f32) -> Vec<(usize, f32)> {
+        if radius <= 0.0 {
+            return Vec::new();
+        }
+        let (grid_x, grid_z) = self.to_grid(x, z);
+        let grid_radius = radius / self.cell_size();
+        let last = (self.resolution - 1) as f32;
+        let min_x = (grid_x - grid_radius).ceil().max(0.0);
+        let max_x = (grid_x + grid_radius).floor().min(last);
+        let min_z = (grid_z - grid_radius).ceil().max(0.0);
+        let max_z = (grid_z + grid_radius).floor().min(last);
+        let mut points = Vec::new();
+        if min_x > max_x || min_z > max_z {
+            return points;
+        }
+        for point_z in min_z as u32..=max_z as u32 {
+            for point_x in min_x as u32..=max_x as u32 {
+                let distance = ((point_x as f32 - grid_x).powi(2) + (point_z as f32 - grid_z).powi(2)).sqrt() / grid_radius;
+                if distance <= 1.0 {
+                    let t = 1.0 - distance;
+                    points.push((self.index(point_x, point_z), t * t * (3.0 - 2.0 * t)));
+                }
+            }
+        }
+        points
+    }
+
+    fn neighbour_average(&self, x: u32, z: u32) -> f32 {
+        let last = self.resolution - 1;
+        let neighbours = [
+            (x.saturating_sub(1), z),
+            ((x + 1).min(last), z),
+            (x, z.saturating_sub(1)),
+            (x, (z + 1).min(last)),
+        ];
+        neighbours.iter().map(|(x, z)| self.height(*x, *z)).sum::<f32>() / neighbours.len() as f32
+    }
+}
+
+/// Returns range of distances along the ray inside the axis aligned box, or None if the ray misses it.
+fn ray_box_range(ray: &Ray, min: Vec3, max: Vec3) -> Option<(f32, f32)> {
+    let origin = ray.origin();
+    let direction = ray.direction();
+    let mut start = 0.0f32;
+    let mut end = f32::MAX;
+    for axis in 0..3 {
+        if direction[axis].abs() < f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let first = (min[axis] - origin[axis]) / direction[axis];
+        let second = (max[axis] - origin[axis]) / direction[axis];
+        start = start.max(first.min(second));
+        end = end.min(first.max(second));
+    }
+    if start <= end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raised_terrain_is_hit_by_ray_at_its_height() {
+        let mut terrain = Terrain::new("hills", 11, 10.0);
+        terrain.raise(0.0, 0.0, 3.0, 2.0);
+
+        assert_eq!(2.0, terrain.height(5, 5));
+        assert_eq!(0.0, terrain.height(0, 0));
+
+        let hit = terrain.raycast(&Ray::new(Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0))).unwrap();
+        assert!((hit.y - 2.0).abs() < 0.001);
+        assert_eq!(None, terrain.raycast(&Ray::new(Vec3::new(20.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0))));
+    }
+}
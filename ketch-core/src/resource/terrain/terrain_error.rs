@@ -0,0 +1,29 @@
+use quick_error::quick_error;
+
+use crate::renderer::renderer_error::RenderError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum TerrainError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        SerializeError(err: ron::ser::Error) {
+            from()
+            display("SerializeError: {}", err)
+            cause(err)
+        }
+        DeserializeError(err: ron::de::Error) {
+            from()
+            display("DeserializeError: {}", err)
+            cause(err)
+        }
+        RenderError(err: RenderError) {
+            from()
+            display("RenderError: {}", err)
+            cause(err)
+        }
+    }
+}
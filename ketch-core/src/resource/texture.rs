@@ -1,75 +1,412 @@
-use image::DynamicImage;
-use vulkano::device::Device;
+use image::{DynamicImage, FilterType, GenericImage, GenericImageView, ImageError, ImageResult, RgbaImage};
+use std::sync::RwLock;
+#[cfg(feature = "vulkan")]
 use std::sync::Arc;
-use vulkano::device::Queue;
-use vulkano::image::{ImmutableImage, Dimensions};
+#[cfg(feature = "vulkan")]
+use vulkano::device::{Device, Queue};
+#[cfg(feature = "vulkan")]
+use vulkano::image::{ImageAccess, ImageViewAccess, ImmutableImage, StorageImage, Dimensions};
+#[cfg(feature = "vulkan")]
 use vulkano::sampler::{Sampler, SamplerAddressMode, Filter, MipmapMode};
+#[cfg(feature = "vulkan")]
 use vulkano::format::Format;
 use std::path::Path;
 
+use crate::renderer::renderer_error::RenderError;
+use crate::renderer::upload::AssetUploader;
+#[cfg(feature = "vulkan")]
+use crate::resource::asset_error::AssetError;
+#[cfg(feature = "vulkan")]
+use crate::settings::TextureQuality;
+
+/// Size of a single pixel in R8G8B8A8 format.
+const BYTES_PER_PIXEL: u64 = 4;
+
+/// Extension of textures converted to the binary texture format, whose pixels are uploaded without decoding.
+pub const BINARY_TEXTURE_EXTENSION: &str = "ktex";
+/// Start of files in the binary texture format, followed by the version of the format, width, height and RGBA pixels.
+const BINARY_TEXTURE_MAGIC: &[u8; 4] = b"KTEX";
+const BINARY_TEXTURE_VERSION: u32 = 1;
+const BINARY_TEXTURE_HEADER_LEN: usize = 16;
+
+/// Filtering used by the sampler of a texture.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SamplerQuality {
+    /// Largest anisotropy, 1 disables anisotropic filtering.
+    pub max_anisotropy: f32,
+    /// Bias added to mipmap level of detail.
+    pub mip_lod_bias: f32,
+}
+
+impl SamplerQuality {
+    /// Returns filtering of given texture quality, limited to what the device supports.
+    #[cfg(feature = "vulkan")]
+    pub fn for_device(quality: TextureQuality, device: &Device) -> Self {
+        let limits = device.physical_device().limits();
+        let max_anisotropy = if device.enabled_features().sampler_anisotropy {
+            quality.max_anisotropy().min(limits.max_sampler_anisotropy()).max(1.0)
+        } else {
+            1.0
+        };
+        let max_bias = limits.max_sampler_lod_bias();
+        SamplerQuality {
+            max_anisotropy,
+            mip_lod_bias: quality.mip_lod_bias().max(-max_bias).min(max_bias),
+        }
+    }
+}
+
+impl Default for SamplerQuality {
+    fn default() -> Self {
+        SamplerQuality {
+            max_anisotropy: 1.0,
+            mip_lod_bias: 0.0,
+        }
+    }
+}
+
+/// Image of a texture. Dynamic images can be updated after they're uploaded.
+enum TextureImage {
+    #[cfg(feature = "vulkan")]
+    Immutable(Arc<ImmutableImage<Format>>),
+    #[cfg(feature = "vulkan")]
+    Dynamic(Arc<StorageImage<Format>>),
+    /// Pixels of a texture kept only on the CPU, which render backends upload themselves. They can always be updated.
+    Cpu(RwLock<RgbaImage>),
+}
+
 /// Image applied on mesh
 pub struct Texture {
     name: String,
 
-    image_buffer: Arc<ImmutableImage<Format>>,
-    sampler: Arc<Sampler>,
+    image_buffer: TextureImage,
+    dimensions: (u32, u32),
+    // None for textures kept only on the CPU
+    #[cfg(feature = "vulkan")]
+    sampler: Option<Arc<Sampler>>,
 }
 
 impl Texture {
-    /// Loads texture from image file.
-    pub fn load<S: Into<String>, P: AsRef<Path>>(name: S, image_path: P, upload_queue: Arc<Queue>, device: Arc<Device>) -> Self {
-        let loaded_image = match image::open(image_path) {
-            Ok(image) => image,
-            Err(e) => panic!("Couldn't load image: {}", e),
-        };
+    /// Loads texture from an image file or a texture in the binary texture format.
+    #[cfg(feature = "vulkan")]
+    pub fn load<S: Into<String>, P: AsRef<Path>>(name: S, image_path: P, upload_queue: Arc<Queue>, device: Arc<Device>) -> Result<Self, AssetError> {
+        let loaded_image = open_image(image_path)?;
 
-        Texture::new(name, loaded_image, upload_queue, device)
+        Ok(Texture::new(name, loaded_image, upload_queue, device)?)
     }
 
     /// Creates new texture from loaded image.
-    pub fn new<S: Into<String>>(name: S, image: DynamicImage, upload_queue: Arc<Queue>, device: Arc<Device>) -> Self {
+    #[cfg(feature = "vulkan")]
+    pub fn new<S: Into<String>>(name: S, image: DynamicImage, upload_queue: Arc<Queue>, device: Arc<Device>) -> Result<Self, RenderError> {
         let image = image.flipv().to_rgba();
         let (image_buffer, _future) = ImmutableImage::from_iter(
             image.clone().into_raw().into_iter(),
             Dimensions::Dim2d { width: image.width(), height: image.height() },
             Format::R8G8B8A8Srgb,
             upload_queue,
-        ).expect("Failed to create image buffer");
+        )?;
+
+        Texture::from_image_buffer(name, TextureImage::Immutable(image_buffer), image.dimensions(), device, SamplerQuality::default())
+    }
+
+    /// Creates new texture from loaded image, copying it to the GPU on the transfer queue of asset uploader.
+    #[cfg(feature = "vulkan")]
+    pub fn upload<S: Into<String>>(name: S, image: DynamicImage, uploader: &AssetUploader, device: Arc<Device>,
+                                   sampler_quality: SamplerQuality) -> Result<Self, RenderError> {
+        let dimensions = image.dimensions();
+        let image_buffer = uploader.upload_image(image.flipv().to_rgba())?;
+
+        Texture::from_image_buffer(name, TextureImage::Immutable(image_buffer), dimensions, device, sampler_quality)
+    }
+
+    /// Creates new texture from mipmap levels of an image, each half the size of the previous one,
+    /// copying them to the GPU on the transfer queue of asset uploader. Used by streamed textures, whose mip chains are kept on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn upload_mip_levels<S: Into<String>>(name: S, mip_levels: Vec<RgbaImage>, uploader: &AssetUploader, device: Arc<Device>,
+                                              sampler_quality: SamplerQuality) -> Result<Self, RenderError> {
+        let dimensions = mip_levels[0].dimensions();
+        let flipped_levels = mip_levels.iter().map(image::imageops::flip_vertical).collect();
+        let image_buffer = uploader.upload_mip_levels(flipped_levels)?;
 
+        Texture::from_image_buffer(name, TextureImage::Immutable(image_buffer), dimensions, device, sampler_quality)
+    }
+
+    /// Creates new texture from loaded image, whose regions can be replaced later with `update_region`.
+    /// Used by textures changing while the game runs, like minimaps or video frames.
+    #[cfg(feature = "vulkan")]
+    pub fn upload_dynamic<S: Into<String>>(name: S, image: DynamicImage, uploader: &AssetUploader, device: Arc<Device>,
+                                           sampler_quality: SamplerQuality) -> Result<Self, RenderError> {
+        let dimensions = image.dimensions();
+        let image_buffer = uploader.upload_dynamic_image(image.flipv().to_rgba())?;
+
+        Texture::from_image_buffer(name, TextureImage::Dynamic(image_buffer), dimensions, device, sampler_quality)
+    }
+
+    /// Creates new texture kept only on the CPU, which isn't drawn by the Vulkan renderer. Used with render backends, which upload textures themselves.
+    pub fn from_image<S: Into<String>>(name: S, image: DynamicImage) -> Self {
+        let image = image.to_rgba();
+        Texture {
+            name: name.into(),
+            dimensions: image.dimensions(),
+            image_buffer: TextureImage::Cpu(RwLock::new(image)),
+            #[cfg(feature = "vulkan")]
+            sampler: None,
+        }
+    }
+
+    #[cfg(feature = "vulkan")]
+    fn from_image_buffer<S: Into<String>>(name: S, image_buffer: TextureImage, dimensions: (u32, u32), device: Arc<Device>,
+                                          sampler_quality: SamplerQuality) -> Result<Self, RenderError> {
+        let mip_levels = match &image_buffer {
+            TextureImage::Immutable(image_buffer) => image_buffer.mipmap_levels(),
+            TextureImage::Dynamic(image_buffer) => image_buffer.mipmap_levels(),
+            TextureImage::Cpu(_) => 1,
+        };
+        // levels of detail cover the whole mip chain, which the quality bias shifts between
         let sampler = Sampler::new(
                         device, 
                         Filter::Linear, 
                         Filter::Linear,
-                        MipmapMode::Nearest, 
+                        MipmapMode::Linear, 
                         SamplerAddressMode::Repeat, 
                         SamplerAddressMode::Repeat,
                         SamplerAddressMode::Repeat, 
+                        sampler_quality.mip_lod_bias, 
+                        sampler_quality.max_anisotropy, 
                         0.0, 
-                        1.0, 
-                        0.0, 
-                        1.0
-                     ).expect("Failed to create a sampler");
+                        mip_levels as f32
+                     )?;
 
-        Texture {
+        Ok(Texture {
             name: name.into(),
             image_buffer,
-            sampler,
+            dimensions,
+            sampler: Some(sampler),
+        })
+    }
+
+    /// Replaces pixels of a region of dynamic texture, whose top left corner is at given position.
+    /// Pixels are given in RGBA rows from the top, like in loaded images. The copy runs on the transfer queue,
+    /// and frames rendered after it show the new pixels. Textures kept on the CPU are updated without an uploader.
+    pub fn update_region(&self, uploader: Option<&AssetUploader>, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) -> Result<(), RenderError> {
+        if !self.is_dynamic() {
+            return Err(RenderError::StaticTextureUpdate(self.name.clone()));
+        }
+        let (texture_width, texture_height) = self.dimensions;
+        let fits = x.checked_add(width).is_some_and(|right| right <= texture_width)
+                   && y.checked_add(height).is_some_and(|bottom| bottom <= texture_height);
+        if !fits || pixels.len() as u64 != width as u64 * height as u64 * BYTES_PER_PIXEL {
+            return Err(RenderError::InvalidTextureRegion(self.name.clone()));
+        }
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        // without Vulkan there are only textures kept on the CPU, which don't need an uploader
+        #[cfg(not(feature = "vulkan"))]
+        let _ = uploader;
+
+        match &self.image_buffer {
+            #[cfg(feature = "vulkan")]
+            TextureImage::Dynamic(image_buffer) => {
+                let uploader = uploader.ok_or_else(|| RenderError::MissingUploader(self.name.clone()))?;
+                // textures are stored upside down, so the region is flipped too
+                let flipped_pixels = flip_rows(pixels, (width as u64 * BYTES_PER_PIXEL) as usize);
+                uploader.update_image_region(image_buffer.clone(), [x, texture_height - y - height], [width, height], flipped_pixels)
+            },
+            TextureImage::Cpu(image) => {
+                let region = RgbaImage::from_raw(width, height, pixels.to_vec()).ok_or_else(|| RenderError::InvalidTextureRegion(self.name.clone()))?;
+                image.write().unwrap().copy_from(&region, x, y);
+                Ok(())
+            },
+            #[cfg(feature = "vulkan")]
+            TextureImage::Immutable(_) => Err(RenderError::StaticTextureUpdate(self.name.clone())),
         }
     }
 
+    /// Returns true if regions of this texture can be updated.
+    pub fn is_dynamic(&self) -> bool {
+        match self.image_buffer {
+            #[cfg(feature = "vulkan")]
+            TextureImage::Dynamic(_) => true,
+            TextureImage::Cpu(_) => true,
+            #[cfg(feature = "vulkan")]
+            TextureImage::Immutable(_) => false,
+        }
+    }
+
+    /// Returns width and height of texture image in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
     /// Returns name of this texture.
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    /// Returns image buffer used by this texture.
-    pub fn image_buffer(&self) -> Arc<ImmutableImage<vulkano::format::Format>>
-    {
-        self.image_buffer.clone()
+    /// Returns size of texture image in bytes.
+    pub fn memory_size(&self) -> u64 {
+        let (width, height) = self.dimensions;
+        width as u64 * height as u64 * BYTES_PER_PIXEL
+    }
+
+    /// Returns number of mipmap levels of texture image.
+    pub fn mip_levels(&self) -> u32 {
+        match &self.image_buffer {
+            #[cfg(feature = "vulkan")]
+            TextureImage::Immutable(image_buffer) => image_buffer.mipmap_levels(),
+            #[cfg(feature = "vulkan")]
+            TextureImage::Dynamic(image_buffer) => image_buffer.mipmap_levels(),
+            TextureImage::Cpu(_) => 1,
+        }
+    }
+
+    /// Returns image buffer used by this texture, or None if it's kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn image_buffer(&self) -> Option<Arc<ImageViewAccess + Send + Sync>> {
+        match &self.image_buffer {
+            TextureImage::Immutable(image_buffer) => Some(image_buffer.clone()),
+            TextureImage::Dynamic(image_buffer) => Some(image_buffer.clone()),
+            TextureImage::Cpu(_) => None,
+        }
     }
 
-    /// Returns sampler used by this texture.
-    pub fn sampler(&self) -> Arc<vulkano::sampler::Sampler>{
+    /// Returns sampler used by this texture, or None if it's kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub fn sampler(&self) -> Option<Arc<vulkano::sampler::Sampler>> {
         self.sampler.clone()
     }
-}
\ No newline at end of file
+
+    /// Returns image and sampler bound in descriptor sets, or an error if the texture is kept only on the CPU.
+    #[cfg(feature = "vulkan")]
+    pub(crate) fn sampled_image(&self) -> Result<(Arc<ImageViewAccess + Send + Sync>, Arc<vulkano::sampler::Sampler>), RenderError> {
+        match (self.image_buffer(), self.sampler()) {
+            (Some(image_buffer), Some(sampler)) => Ok((image_buffer, sampler)),
+            _ => Err(RenderError::CpuOnlyAsset(self.name.clone())),
+        }
+    }
+
+    /// Returns pixels of texture kept on the CPU, in rows from the top like in loaded images. Pixels of uploaded textures can't be read back.
+    pub fn pixels(&self) -> Option<RgbaImage> {
+        match &self.image_buffer {
+            TextureImage::Cpu(image) => Some(image.read().unwrap().clone()),
+            #[cfg(feature = "vulkan")]
+            _ => None,
+        }
+    }
+}
+
+/// Returns pixel rows of given length in reversed order.
+#[cfg(feature = "vulkan")]
+fn flip_rows(pixels: &[u8], row_len: usize) -> Vec<u8> {
+    pixels.chunks(row_len).rev().flat_map(|row| row.iter().cloned()).collect()
+}
+
+/// Opens image file, either in the binary texture format or one of formats supported by the image crate, chosen by its extension.
+pub fn open_image<P: AsRef<Path>>(path: P) -> ImageResult<DynamicImage> {
+    let path = path.as_ref();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case(BINARY_TEXTURE_EXTENSION) => image_from_binary(&std::fs::read(path)?),
+        _ => image::open(path),
+    }
+}
+
+/// Reads image in the binary texture format, e.g. from an asset pack.
+pub fn image_from_binary(bytes: &[u8]) -> ImageResult<DynamicImage> {
+    let read_u32 = |offset: usize| {
+        let mut value = [0; 4];
+        value.copy_from_slice(&bytes[offset..offset + 4]);
+        u32::from_le_bytes(value)
+    };
+    if bytes.len() < BINARY_TEXTURE_HEADER_LEN || &bytes[..4] != BINARY_TEXTURE_MAGIC || read_u32(4) != BINARY_TEXTURE_VERSION {
+        return Err(ImageError::FormatError("data isn't a texture in a supported version of the binary texture format".to_string()));
+    }
+    RgbaImage::from_raw(read_u32(8), read_u32(12), bytes[BINARY_TEXTURE_HEADER_LEN..].to_vec())
+        .map(DynamicImage::ImageRgba8)
+        .ok_or(ImageError::ImageEnd)
+}
+
+/// Returns image in the binary texture format. Pixels are stored as RGBA, 8 bits per channel.
+pub fn image_to_binary(image: &DynamicImage) -> Vec<u8> {
+    let image = image.to_rgba();
+    let mut bytes = BINARY_TEXTURE_MAGIC.to_vec();
+    bytes.extend_from_slice(&BINARY_TEXTURE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&image.width().to_le_bytes());
+    bytes.extend_from_slice(&image.height().to_le_bytes());
+    bytes.extend(image.into_raw());
+    bytes
+}
+
+/// Returns number of mipmap levels of an image with given dimensions, halving it down to a single pixel.
+pub fn mip_level_count((width, height): (u32, u32)) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Returns mipmap levels of an image, starting with the image itself, each half the size of the previous one.
+pub fn mip_chain(image: RgbaImage) -> Vec<RgbaImage> {
+    let level_count = mip_level_count(image.dimensions());
+    let mut levels = Vec::with_capacity(level_count as usize);
+    levels.push(image);
+    for _ in 1..level_count {
+        let previous = &levels[levels.len() - 1];
+        let width = (previous.width() / 2).max(1);
+        let height = (previous.height() / 2).max(1);
+        let level = image::imageops::resize(previous, width, height, FilterType::Triangle);
+        levels.push(level);
+    }
+    levels
+}
+
+/// Halves width and height of image given number of times, keeping at least one pixel.
+pub fn downscale(image: DynamicImage, times: u32) -> DynamicImage {
+    if times == 0 {
+        return image;
+    }
+    let width = image.width().checked_shr(times).unwrap_or(0).max(1);
+    let height = image.height().checked_shr(times).unwrap_or(0).max(1);
+    image.resize_exact(width, height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_textures_keep_pixels() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_raw(2, 1, vec![1, 2, 3, 4, 5, 6, 7, 8]).unwrap());
+
+        let loaded = image_from_binary(&image_to_binary(&image)).unwrap();
+
+        assert_eq!((2, 1), loaded.dimensions());
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], loaded.to_rgba().into_raw());
+        assert!(image_from_binary(&image_to_binary(&image)[..20]).is_err());
+        assert!(image_from_binary(b"KTEX").is_err());
+    }
+
+    #[test]
+    fn images_are_downscaled_by_halving() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(256, 6));
+        assert_eq!((256, 6), downscale(image.clone(), 0).dimensions());
+        assert_eq!((64, 1), downscale(image.clone(), 2).dimensions());
+        assert_eq!((1, 1), downscale(image, 10).dimensions());
+    }
+
+    #[test]
+    fn mip_chains_are_halved_down_to_a_single_pixel() {
+        assert_eq!(1, mip_level_count((1, 1)));
+        assert_eq!(9, mip_level_count((256, 6)));
+        assert_eq!(11, mip_level_count((1000, 1024)));
+
+        let levels = mip_chain(RgbaImage::from_pixel(8, 2, image::Rgba([10, 20, 30, 255])));
+        let dimensions: Vec<(u32, u32)> = levels.iter().map(|level| level.dimensions()).collect();
+        assert_eq!(vec![(8, 2), (4, 1), (2, 1), (1, 1)], dimensions);
+        assert_eq!(image::Rgba([10, 20, 30, 255]), *levels[3].get_pixel(0, 0));
+    }
+
+    #[cfg(feature = "vulkan")]
+    #[test]
+    fn region_rows_are_flipped() {
+        let pixels = [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3];
+        assert_eq!(vec![3, 3, 3, 3, 2, 2, 2, 2, 1, 1, 1, 1], flip_rows(&pixels, 4));
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use image::{DynamicImage, RgbaImage};
+
+use crate::resource::texture;
+
+/// Default number of bytes of GPU memory used by streamed textures.
+pub const DEFAULT_TEXTURE_MEMORY_BUDGET: u64 = 256 * 1024 * 1024;
+/// Default distance up to which streamed textures are shown at full resolution.
+pub const DEFAULT_FULL_DETAIL_DISTANCE: f32 = 10.0;
+/// Longest side of the lowest mip of a streamed texture, which is uploaded first and kept while the texture isn't seen.
+const LOWEST_MIP_SIZE: u32 = 64;
+
+/// Texture whose sharpest mips are evicted when objects using it are far from the camera.
+struct StreamedTexture {
+    name: String,
+    /// Full mip chain, generated when the texture is added.
+    levels: Vec<RgbaImage>,
+    resident_mip: u32,
+    lowest_mip: u32,
+}
+
+impl StreamedTexture {
+    /// Returns size in bytes of the mips from given one down to the smallest, which are uploaded when it's the sharpest resident mip.
+    fn mip_size(&self, mip: u32) -> u64 {
+        self.levels[mip as usize..].iter().map(|level| u64::from(level.width()) * u64::from(level.height()) * 4).sum()
+    }
+}
+
+/// Chooses the sharpest resident mip of streamed textures by distance to the closest object using them, within a budget of texture memory.
+/// Textures start with mips from their lowest one down, and get one sharper mip per update, so they load in progressively.
+/// Mip chains are kept on the CPU, and the GPU copy of a texture holds mips from its resident mip down to the smallest one.
+pub struct TextureStreamer {
+    textures: Vec<StreamedTexture>,
+    memory_budget: u64,
+    full_detail_distance: f32,
+}
+
+impl TextureStreamer {
+    /// Creates streamer keeping streamed textures within given number of bytes.
+    pub fn new(memory_budget: u64) -> Self {
+        TextureStreamer {
+            textures: Vec::new(),
+            memory_budget,
+            full_detail_distance: DEFAULT_FULL_DETAIL_DISTANCE,
+        }
+    }
+
+    /// Adds texture with given full resolution image, replacing streamed texture with the same name.
+    /// Returns mips from the lowest one down, which the texture starts with.
+    pub(crate) fn add<S: Into<String>>(&mut self, name: S, image: DynamicImage) -> Vec<RgbaImage> {
+        let name = name.into();
+        self.remove(&name);
+        let levels = texture::mip_chain(image.to_rgba());
+        let lowest_mip = levels.iter().position(|level| level.width().max(level.height()) <= LOWEST_MIP_SIZE).unwrap_or(0) as u32;
+        let lowest_mips = levels[lowest_mip as usize..].to_vec();
+        self.textures.push(StreamedTexture {
+            name,
+            levels,
+            resident_mip: lowest_mip,
+            lowest_mip,
+        });
+        lowest_mips
+    }
+
+    /// Stops streaming texture with given name. Returns false if it wasn't streamed.
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        let count = self.textures.len();
+        self.textures.retain(|texture| texture.name != name);
+        self.textures.len() != count
+    }
+
+    /// Returns mips of streamed texture from given one down to the smallest, which are uploaded when it's the sharpest resident mip.
+    pub(crate) fn mip_levels(&self, name: &str, mip: u32) -> Option<Vec<RgbaImage>> {
+        let streamed = self.textures.iter().find(|texture| texture.name == name)?;
+        streamed.levels.get(mip as usize..).map(<[RgbaImage]>::to_vec)
+    }
+
+    /// Returns true if no textures are streamed.
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// Returns true if texture with given name is streamed.
+    pub fn is_streamed(&self, name: &str) -> bool {
+        self.textures.iter().any(|texture| texture.name == name)
+    }
+
+    /// Returns sharpest mip of the uploaded texture, 0 for full resolution.
+    pub fn resident_mip(&self, name: &str) -> Option<u32> {
+        self.textures.iter().find(|texture| texture.name == name).map(|texture| texture.resident_mip)
+    }
+
+    /// Returns number of bytes used by uploaded streamed textures.
+    pub fn memory_usage(&self) -> u64 {
+        self.textures.iter().map(|texture| texture.mip_size(texture.resident_mip)).sum()
+    }
+
+    /// Sets number of bytes of GPU memory used by streamed textures. Textures further from the camera lose their sharpest mips until they fit.
+    /// Lowest mips are always kept, even over the budget.
+    pub fn set_memory_budget(&mut self, memory_budget: u64) {
+        self.memory_budget = memory_budget;
+    }
+
+    /// Returns number of bytes of GPU memory streamed textures should fit in.
+    pub fn memory_budget(&self) -> u64 {
+        self.memory_budget
+    }
+
+    /// Sets distance up to which textures are shown at full resolution. Resolution is halved every time the distance doubles.
+    pub fn set_full_detail_distance(&mut self, full_detail_distance: f32) {
+        self.full_detail_distance = full_detail_distance;
+    }
+
+    /// Returns distance up to which textures are shown at full resolution.
+    pub fn full_detail_distance(&self) -> f32 {
+        self.full_detail_distance
+    }
+
+    /// Chooses sharpest mips of textures for distances of the closest objects using them, and returns textures which should be uploaded again from a new mip.
+    /// Textures over their target are lowered right away. Textures under it get one mip sharper, closest first, while uploads fit in `upload_budget` bytes.
+    pub(crate) fn update(&mut self, distances: &HashMap<String, f32>, upload_budget: u64) -> Vec<(String, u32)> {
+        let distances: Vec<f32> = self.textures.iter()
+                                               .map(|texture| distances.get(&texture.name).cloned().unwrap_or(f32::INFINITY))
+                                               .collect();
+        let mut targets: Vec<u32> = self.textures.iter().zip(&distances)
+                                        .map(|(texture, distance)| self.mip_for_distance(*distance).min(texture.lowest_mip))
+                                        .collect();
+
+        // textures furthest from the camera lose detail until all of them fit in the budget
+        let mut total: u64 = self.textures.iter().zip(&targets).map(|(texture, mip)| texture.mip_size(*mip)).sum();
+        while total > self.memory_budget {
+            let furthest = (0..self.textures.len()).filter(|index| targets[*index] < self.textures[*index].lowest_mip)
+                                                   .max_by(|a, b| distances[*a].partial_cmp(&distances[*b]).unwrap_or(std::cmp::Ordering::Equal));
+            let index = match furthest {
+                Some(index) => index,
+                None => break,
+            };
+            total -= self.textures[index].mip_size(targets[index]);
+            targets[index] += 1;
+            total += self.textures[index].mip_size(targets[index]);
+        }
+
+        let mut changes = Vec::new();
+        for (texture, target) in self.textures.iter_mut().zip(&targets) {
+            if *target > texture.resident_mip {
+                texture.resident_mip = *target;
+                changes.push((texture.name.clone(), *target));
+            }
+        }
+
+        let mut sharpened: Vec<usize> = (0..self.textures.len()).filter(|index| targets[*index] < self.textures[*index].resident_mip).collect();
+        sharpened.sort_by(|a, b| distances[*a].partial_cmp(&distances[*b]).unwrap_or(std::cmp::Ordering::Equal));
+        let mut spent = 0;
+        for (order, index) in sharpened.into_iter().enumerate() {
+            let texture = &mut self.textures[index];
+            let mip = texture.resident_mip - 1;
+            spent += texture.mip_size(mip);
+            // the closest texture is always uploaded, so textures bigger than the budget still load
+            if order > 0 && spent > upload_budget {
+                break;
+            }
+            texture.resident_mip = mip;
+            changes.push((texture.name.clone(), mip));
+        }
+        changes
+    }
+
+    fn mip_for_distance(&self, distance: f32) -> u32 {
+        if distance <= self.full_detail_distance {
+            return 0;
+        }
+        let mip = (distance / self.full_detail_distance.max(f32::EPSILON)).log2().ceil();
+        if mip.is_finite() { mip as u32 } else { u32::MAX }
+    }
+}
+
+impl Default for TextureStreamer {
+    fn default() -> Self {
+        TextureStreamer::new(DEFAULT_TEXTURE_MEMORY_BUDGET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_textures_sharpen_one_mip_per_update_within_memory_budget() {
+        // full mip chain of a 512x512 texture, and two chains starting at 64x64
+        let mip_chain_size = |size: u64| (0..).map(|mip| size >> mip).take_while(|side| *side > 0).map(|side| side * side * 4).sum::<u64>();
+        let mut streamer = TextureStreamer::new(mip_chain_size(512) + 2 * mip_chain_size(64));
+        let lowest_mips = streamer.add("near", DynamicImage::new_rgba8(512, 512));
+        assert_eq!(vec![(64, 64), (32, 32), (16, 16), (8, 8), (4, 4), (2, 2), (1, 1)],
+                   lowest_mips.iter().map(|level| level.dimensions()).collect::<Vec<_>>());
+        streamer.add("far", DynamicImage::new_rgba8(512, 512));
+        streamer.add("unused", DynamicImage::new_rgba8(256, 256));
+        let distances: HashMap<String, f32> = vec![("near".to_string(), 5.0), ("far".to_string(), 15.0)].into_iter().collect();
+        assert_eq!(Some(3), streamer.resident_mip("near"));
+
+        // far texture would need mip 1, but only its lowest mip fits in the budget next to the near one
+        assert_eq!(vec![("near".to_string(), 2)], streamer.update(&distances, u64::MAX));
+        assert_eq!(vec![("near".to_string(), 1)], streamer.update(&distances, 0));
+        streamer.update(&distances, u64::MAX);
+        streamer.update(&distances, u64::MAX);
+
+        assert_eq!(Some(0), streamer.resident_mip("near"));
+        assert_eq!(Some(3), streamer.resident_mip("far"));
+        assert_eq!(Some(2), streamer.resident_mip("unused"));
+        assert_eq!(streamer.memory_budget(), streamer.memory_usage());
+        assert_eq!(Some(10), streamer.mip_levels("near", 0).map(|levels| levels.len()));
+
+        // moving away evicts high mips right away
+        let distances: HashMap<String, f32> = vec![("near".to_string(), 35.0)].into_iter().collect();
+        assert_eq!(vec![("near".to_string(), 2)], streamer.update(&distances, u64::MAX));
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use image::{DynamicImage, ImageResult};
+
+/// Video stored as a sequence of image files, one per frame, with an optional soundtrack.
+/// Frames are decoded only when they're shown, so long videos don't have to fit in memory.
+pub struct Video {
+    name: String,
+    frames: Vec<PathBuf>,
+    frame_rate: f32,
+    soundtrack: Option<String>,
+}
+
+impl Video {
+    /// Creates video from paths of frame images, shown with given number of frames per second.
+    pub fn new<S: Into<String>>(name: S, frames: Vec<PathBuf>, frame_rate: f32) -> Self {
+        Video {
+            name: name.into(),
+            frames,
+            frame_rate: frame_rate.max(f32::EPSILON),
+            soundtrack: None,
+        }
+    }
+
+    /// Loads video from a directory of frame images. Frames are ordered by file name,
+    /// so they should be numbered with leading zeros, e.g. frame_0001.png.
+    pub fn load<S: Into<String>, P: AsRef<Path>>(name: S, directory: P, frame_rate: f32) -> std::io::Result<Self> {
+        let mut frames: Vec<PathBuf> = std::fs::read_dir(directory)?.filter_map(|entry| entry.ok())
+                                                                   .map(|entry| entry.path())
+                                                                   .filter(|path| path.is_file())
+                                                                   .collect();
+        frames.sort();
+        Ok(Video::new(name, frames, frame_rate))
+    }
+
+    /// Returns the name of this video.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns number of frames of this video.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns number of frames shown per second.
+    pub fn frame_rate(&self) -> f32 {
+        self.frame_rate
+    }
+
+    /// Returns time it takes to play the whole video once.
+    pub fn duration(&self) -> Duration {
+        let nanos = (self.frames.len() as f64 / f64::from(self.frame_rate) * 1e9) as u64;
+        Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+    }
+
+    /// Returns name of the sound played along with the video.
+    pub fn soundtrack(&self) -> Option<&str> {
+        self.soundtrack.as_deref()
+    }
+
+    /// Sets name of the sound played along with the video.
+    pub fn set_soundtrack(&mut self, soundtrack: Option<String>) {
+        self.soundtrack = soundtrack;
+    }
+
+    /// Returns index of the frame shown at given playback position. Positions after the end show the last frame.
+    pub fn frame_at(&self, position: Duration) -> usize {
+        let seconds = position.as_secs() as f64 + f64::from(position.subsec_nanos()) / 1e9;
+        let index = (seconds * f64::from(self.frame_rate)) as usize;
+        index.min(self.frames.len().saturating_sub(1))
+    }
+
+    /// Decodes image of the frame with given index.
+    pub fn decode_frame(&self, index: usize) -> ImageResult<DynamicImage> {
+        match self.frames.get(index) {
+            Some(path) => image::open(path),
+            None => Err(image::ImageError::ImageEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_picked_by_position() {
+        let frames = (0..10).map(|index| PathBuf::from(format!("frame_{:02}.png", index))).collect();
+        let video = Video::new("test_video", frames, 5.0);
+
+        assert_eq!(Duration::from_secs(2), video.duration());
+        assert_eq!(0, video.frame_at(Duration::from_millis(100)));
+        assert_eq!(6, video.frame_at(Duration::from_millis(1300)));
+        assert_eq!(9, video.frame_at(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use log::*;
+use nalgebra_glm::Vec3;
+
+use crate::math::bounds::Bounds;
+use crate::resource::scene::scene_data::SceneData;
+use crate::resource::scene::scene_error::SceneSerializationError;
+
+/// Default distance from the camera to bounds of a cell at which the cell is loaded.
+pub const DEFAULT_LOAD_DISTANCE: f32 = 200.0;
+/// Cells are unloaded further than load distance multiplied by this, so that cells at the edge aren't reloaded every few frames.
+const UNLOAD_DISTANCE_FACTOR: f32 = 1.25;
+
+/// State of a world cell.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellState {
+    Unloaded,
+    /// Scene file of the cell is being read on another thread.
+    Loading,
+    /// Objects of the cell are merged into the active scene.
+    Loaded,
+    /// Scene file of the cell couldn't be read. Loading is tried again after the camera moves away from the cell and comes back.
+    Failed,
+}
+
+/// Change of the active scene requested by `World::update`.
+pub enum CellChange {
+    /// Scene data of the cell was read and should be merged into the active scene, reporting ids of its objects with `World::set_merged_objects`.
+    Loaded(String, Box<SceneData>),
+    /// Objects with these ids should be removed from the active scene.
+    Unloaded(Vec<u32>),
+}
+
+enum CellContent {
+    Unloaded,
+    // receivers aren't Sync, the mutex keeps world and asset manager owning it shareable between threads
+    Loading(Mutex<Receiver<Result<SceneData, SceneSerializationError>>>),
+    Loaded(Vec<u32>),
+    Failed,
+}
+
+/// Part of the world stored in its own scene file, merged into the active scene while the camera is close to its bounds.
+pub struct WorldCell {
+    name: String,
+    path: PathBuf,
+    bounds: Bounds,
+    content: CellContent,
+}
+
+impl WorldCell {
+    /// Returns name of the cell.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns path of the scene file of the cell.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Returns bounds of the cell, which are used to find its distance to the camera.
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// Returns state of the cell.
+    pub fn state(&self) -> CellState {
+        match self.content {
+            CellContent::Unloaded => CellState::Unloaded,
+            CellContent::Loading(_) => CellState::Loading,
+            CellContent::Loaded(_) => CellState::Loaded,
+            CellContent::Failed => CellState::Failed,
+        }
+    }
+}
+
+/// World divided into cells whose scenes are loaded and unloaded by camera distance, so levels don't have to fit into memory at once.
+/// Scene files of cells are read on other threads and their objects are merged into the active scene by asset manager.
+/// Meshes and materials used by cells have to be added to asset manager before, like when loading a scene.
+pub struct World {
+    cells: Vec<WorldCell>,
+    load_distance: f32,
+}
+
+impl World {
+    /// Creates world without cells, loading cells closer than `load_distance` to the camera.
+    pub fn new(load_distance: f32) -> Self {
+        World {
+            cells: Vec::new(),
+            load_distance,
+        }
+    }
+
+    /// Adds cell with given name, scene file and bounds. A cell with the same name is replaced,
+    /// which should only be done while it's unloaded, as its objects would stay in the active scene.
+    pub fn add_cell<S: Into<String>, P: Into<PathBuf>>(&mut self, name: S, path: P, bounds: Bounds) {
+        let name = name.into();
+        self.cells.retain(|cell| cell.name != name);
+        self.cells.push(WorldCell {
+            name,
+            path: path.into(),
+            bounds,
+            content: CellContent::Unloaded,
+        });
+    }
+
+    /// Returns cells of the world.
+    pub fn cells(&self) -> &[WorldCell] {
+        &self.cells
+    }
+
+    /// Returns state of the cell with given name.
+    pub fn cell_state(&self, name: &str) -> Option<CellState> {
+        self.cells.iter().find(|cell| cell.name == name).map(WorldCell::state)
+    }
+
+    /// Sets distance from the camera at which cells are loaded. Default is `DEFAULT_LOAD_DISTANCE`.
+    pub fn set_load_distance(&mut self, load_distance: f32) {
+        self.load_distance = load_distance;
+    }
+
+    /// Returns distance from the camera at which cells are loaded.
+    pub fn load_distance(&self) -> f32 {
+        self.load_distance
+    }
+
+    /// Starts loading cells which came close to the camera, and returns cells which finished loading and cells which should be unloaded.
+    pub fn update(&mut self, camera_position: Vec3) -> Vec<CellChange> {
+        let unload_distance = self.load_distance * UNLOAD_DISTANCE_FACTOR;
+        let mut changes = Vec::new();
+        for cell in &mut self.cells {
+            let distance = distance_to_bounds(&cell.bounds, camera_position);
+            let content = std::mem::replace(&mut cell.content, CellContent::Unloaded);
+            cell.content = match content {
+                CellContent::Unloaded if distance <= self.load_distance => {
+                    let (sender, receiver) = mpsc::channel();
+                    let path = cell.path.clone();
+                    thread::spawn(move || {
+                        // the cell may have been unloaded in the meantime, then nobody waits for the data
+                        let _ = sender.send(SceneData::load(path));
+                    });
+                    CellContent::Loading(Mutex::new(receiver))
+                },
+                CellContent::Unloaded => CellContent::Unloaded,
+                CellContent::Loaded(ids) => if distance > unload_distance {
+                    changes.push(CellChange::Unloaded(ids));
+                    CellContent::Unloaded
+                } else {
+                    CellContent::Loaded(ids)
+                },
+                CellContent::Failed => if distance > unload_distance { CellContent::Unloaded } else { CellContent::Failed },
+                CellContent::Loading(_) if distance > unload_distance => CellContent::Unloaded,
+                CellContent::Loading(mut receiver) => match receiver.get_mut().unwrap().try_recv() {
+                    Ok(Ok(scene_data)) => {
+                        changes.push(CellChange::Loaded(cell.name.clone(), Box::new(scene_data)));
+                        CellContent::Loaded(Vec::new())
+                    },
+                    Ok(Err(err)) => {
+                        error!("Couldn't load world cell {} from {}: {}", cell.name, cell.path.display(), err);
+                        CellContent::Failed
+                    },
+                    Err(TryRecvError::Empty) => CellContent::Loading(receiver),
+                    Err(TryRecvError::Disconnected) => {
+                        error!("Loading of world cell {} stopped unexpectedly", cell.name);
+                        CellContent::Failed
+                    },
+                },
+            };
+        }
+        changes
+    }
+
+    /// Remembers ids of objects merged into the active scene from a loaded cell, which are removed when the cell is unloaded.
+    pub fn set_merged_objects(&mut self, name: &str, ids: Vec<u32>) {
+        if let Some(cell) = self.cells.iter_mut().find(|cell| cell.name == name) {
+            cell.content = CellContent::Loaded(ids);
+        }
+    }
+
+    /// Marks all cells as unloaded and returns ids of objects of the loaded ones, e.g. when the active scene changes.
+    pub fn unload_all(&mut self) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for cell in &mut self.cells {
+            if let CellContent::Loaded(cell_ids) = std::mem::replace(&mut cell.content, CellContent::Unloaded) {
+                ids.extend(cell_ids);
+            }
+        }
+        ids
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World::new(DEFAULT_LOAD_DISTANCE)
+    }
+}
+
+/// Returns distance from the point to the closest point of the bounds, which is zero inside them.
+fn distance_to_bounds(bounds: &Bounds, point: Vec3) -> f32 {
+    let (min, max) = (bounds.min(), bounds.max());
+    let closest = Vec3::new(point.x.max(min.x).min(max.x), point.y.max(min.y).min(max.y), point.z.max(min.z).min(max.z));
+    (point - closest).norm()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_load_near_camera_and_unload_far_from_it() {
+        let mut world = World::new(10.0);
+        world.add_cell("cell", "missing_cell.ron", Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(10.0, 10.0, 10.0)));
+
+        assert!(world.update(Vec3::new(-20.0, 0.0, 0.0)).is_empty());
+        assert_eq!(Some(CellState::Unloaded), world.cell_state("cell"));
+
+        world.update(Vec3::new(-5.0, 5.0, 5.0));
+        assert_ne!(Some(CellState::Unloaded), world.cell_state("cell"));
+
+        // still within unload distance, so the cell stays
+        world.update(Vec3::new(-12.0, 5.0, 5.0));
+        assert_ne!(Some(CellState::Unloaded), world.cell_state("cell"));
+
+        world.set_merged_objects("cell", vec![1, 2]);
+        let changes = world.update(Vec3::new(-20.0, 5.0, 5.0));
+        assert_eq!(1, changes.len());
+        match &changes[0] {
+            CellChange::Unloaded(ids) => assert_eq!(&vec![1, 2], ids),
+            CellChange::Loaded(..) => panic!("cell wasn't unloaded"),
+        }
+        assert_eq!(Some(CellState::Unloaded), world.cell_state("cell"));
+    }
+
+    #[test]
+    fn distance_to_bounds_is_zero_inside() {
+        let bounds = Bounds::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0));
+
+        assert_eq!(0.0, distance_to_bounds(&bounds, Vec3::new(1.0, 1.0, 1.0)));
+        assert_eq!(5.0, distance_to_bounds(&bounds, Vec3::new(5.0, 6.0, 1.0)));
+    }
+}
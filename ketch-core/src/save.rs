@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use log::*;
+
+use crate::resource::AssetManager;
+use crate::resource::object::Object;
+use crate::resource::scene::Scene;
+use crate::save::save_error::SaveError;
+
+pub mod save_error;
+
+/// Version of the save file format written by this engine.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+const SAVE_EXTENSION: &str = "sav";
+
+/// Saved transform of a scene object. Objects are matched by name, because ids change when a scene is loaded.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ObjectState {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub rotation_angles: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl ObjectState {
+    /// Creates state from current transform of the object.
+    pub fn from_object(object: &Object) -> Self {
+        ObjectState {
+            name: object.name().to_string(),
+            position: object.position(),
+            rotation_angles: object.rotation_angles(),
+            scale: object.scale(),
+        }
+    }
+
+    fn apply(&self, object: &mut Object) {
+        let (x, y, z) = self.position;
+        let (rotation_x, rotation_y, rotation_z) = self.rotation_angles;
+        let (scale_x, scale_y, scale_z) = self.scale;
+        object.set_position(x, y, z);
+        object.set_rotation_angles(rotation_x, rotation_y, rotation_z);
+        object.set_scale_xyz(scale_x, scale_y, scale_z);
+    }
+}
+
+/// Saved state of a game: transforms of designated objects of the active scene, seed of the game's random number generator
+/// and any serde serializable game state, like components of objects keyed by their names.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SaveGame<G> {
+    /// Version of the save file format, checked when the save is loaded.
+    pub format_version: u32,
+    /// Version of the game state set by the game, used to migrate saves made by older versions of the game.
+    pub game_version: u32,
+    pub scene_name: String,
+    pub objects: Vec<ObjectState>,
+    pub rng_seed: u64,
+    pub game: G,
+}
+
+impl<G> SaveGame<G> {
+    /// Captures transforms of scene objects accepted by the filter, along with game state.
+    pub fn capture<F: Fn(&Object) -> bool>(scene: &Scene, filter: F, game_version: u32, rng_seed: u64, game: G) -> Self {
+        SaveGame {
+            format_version: SAVE_FORMAT_VERSION,
+            game_version,
+            scene_name: scene.name().to_string(),
+            objects: scene.objects().iter().filter(|object| filter(object)).map(ObjectState::from_object).collect(),
+            rng_seed,
+            game,
+        }
+    }
+
+    /// Sets transforms of scene objects with matching names.
+    pub fn apply(&self, scene: &mut Scene) {
+        for state in &self.objects {
+            match scene.objects_mut().iter_mut().find(|object| object.name() == state.name) {
+                Some(object) => state.apply(object),
+                None => warn!("Saved object {} wasn't found in scene {}", state.name, scene.name()),
+            }
+        }
+    }
+
+    /// Makes saved scene active, if it isn't already, and applies saved transforms to it.
+    /// Returns false if asset manager doesn't have the saved scene.
+    pub fn restore(&self, asset_manager: &mut AssetManager) -> bool {
+        if asset_manager.active_scene().map(Scene::name) != Some(self.scene_name.as_str()) {
+            asset_manager.change_active_scene(&self.scene_name);
+        }
+        match asset_manager.active_scene_mut().filter(|scene| scene.name() == self.scene_name) {
+            Some(scene) => {
+                self.apply(scene);
+                true
+            },
+            None => {
+                warn!("Saved scene {} wasn't found", self.scene_name);
+                false
+            },
+        }
+    }
+}
+
+/// Information about a save slot.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SlotInfo {
+    pub name: String,
+    /// Time the slot was last saved.
+    pub modified: SystemTime,
+}
+
+/// Named save slots stored as files in a directory.
+pub struct SaveSlots {
+    directory: PathBuf,
+}
+
+impl SaveSlots {
+    /// Creates save slots stored in the directory, which is created when the first slot is saved.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        SaveSlots {
+            directory: directory.into(),
+        }
+    }
+
+    /// Returns directory the slots are stored in.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Writes save to the slot, replacing previous save. Save is written to a temporary file first,
+    /// so that the previous save isn't lost if writing fails.
+    pub fn save<G: Serialize>(&self, slot: &str, save_game: &SaveGame<G>) -> Result<(), SaveError> {
+        let path = self.slot_path(slot)?;
+        let serialized_save = ron::ser::to_string_pretty(save_game, Default::default())?;
+        std::fs::create_dir_all(&self.directory)?;
+        let temporary_path = path.with_extension("tmp");
+        std::fs::write(&temporary_path, serialized_save)?;
+        std::fs::rename(&temporary_path, &path)?;
+        Ok(())
+    }
+
+    /// Reads save from the slot. Fails if the save was written in a newer format than this engine supports.
+    pub fn load<G: DeserializeOwned>(&self, slot: &str) -> Result<SaveGame<G>, SaveError> {
+        let serialized_save = std::fs::read_to_string(self.slot_path(slot)?)?;
+        let save_game: SaveGame<G> = ron::de::from_str(&serialized_save)?;
+        if save_game.format_version > SAVE_FORMAT_VERSION {
+            return Err(SaveError::UnsupportedVersion(save_game.format_version));
+        }
+        Ok(save_game)
+    }
+
+    /// Returns true if the slot was saved.
+    pub fn exists(&self, slot: &str) -> bool {
+        self.slot_path(slot).map(|path| path.is_file()).unwrap_or(false)
+    }
+
+    /// Deletes save from the slot.
+    pub fn delete(&self, slot: &str) -> Result<(), SaveError> {
+        std::fs::remove_file(self.slot_path(slot)?)?;
+        Ok(())
+    }
+
+    /// Returns saved slots, the most recently saved first.
+    pub fn slots(&self) -> Result<Vec<SlotInfo>, SaveError> {
+        if !self.directory.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut slots = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some(SAVE_EXTENSION) {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                slots.push(SlotInfo { name: name.to_string(), modified: std::fs::metadata(&path)?.modified()? });
+            }
+        }
+        slots.sort_by(|first, second| second.modified.cmp(&first.modified).then_with(|| first.name.cmp(&second.name)));
+        Ok(slots)
+    }
+
+    fn slot_path(&self, slot: &str) -> Result<PathBuf, SaveError> {
+        let valid = !slot.is_empty() && slot.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_');
+        if !valid {
+            return Err(SaveError::InvalidSlotName(slot.to_string()));
+        }
+        Ok(self.directory.join(slot).with_extension(SAVE_EXTENSION))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::camera::Camera;
+    use crate::resource::object::ObjectBuilder;
+
+    #[test]
+    fn saved_state_is_restored_from_slot() {
+        let mut scene = Scene::new("level", Camera::new());
+        scene.add_object(ObjectBuilder::new("player").with_position(1.0, 2.0, 3.0).build());
+        let slots = SaveSlots::new(std::env::temp_dir().join(format!("ketch_save_test_{}", std::process::id())));
+
+        let save_game = SaveGame::capture(&scene, |_| true, 1, 42, vec![String::from("key")]);
+        slots.save("slot_1", &save_game).unwrap();
+        scene.objects_mut()[0].set_position(0.0, 0.0, 0.0);
+        let loaded: SaveGame<Vec<String>> = slots.load("slot_1").unwrap();
+        loaded.apply(&mut scene);
+
+        assert_eq!(save_game, loaded);
+        assert_eq!((1.0, 2.0, 3.0), scene.objects()[0].position());
+        assert_eq!(vec![String::from("slot_1")], slots.slots().unwrap().into_iter().map(|slot| slot.name).collect::<Vec<_>>());
+        assert!(slots.save("../escape", &save_game).is_err());
+        std::fs::remove_dir_all(slots.directory()).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum SaveError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        SerializeError(err: ron::ser::Error) {
+            from()
+            display("SerializeError: {}", err)
+            cause(err)
+        }
+        DeserializeError(err: ron::de::Error) {
+            from()
+            display("DeserializeError: {}", err)
+            cause(err)
+        }
+        UnsupportedVersion(version: u32) {
+            display("save file was written by a newer version of the game: {}", version)
+        }
+        InvalidSlotName(name: String) {
+            display("slot names can only contain letters, digits, '-' and '_': {}", name)
+        }
+    }
+}
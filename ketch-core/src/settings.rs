@@ -1,14 +1,140 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+#[cfg(feature = "vulkan")]
+use std::path::Path;
+#[cfg(feature = "vulkan")]
 use std::time::Duration;
+#[cfg(feature = "vulkan")]
+use log::LevelFilter;
+#[cfg(feature = "vulkan")]
+use vulkano::format::Format;
+#[cfg(feature = "vulkan")]
 use winit::dpi::PhysicalSize;
+#[cfg(feature = "vulkan")]
+use winit::VirtualKeyCode;
+
+#[cfg(feature = "vulkan")]
+use crate::renderer::render_scale::UpscaleFilter;
+#[cfg(feature = "vulkan")]
+use crate::resource::streaming::DEFAULT_UPLOAD_BUDGET;
+#[cfg(feature = "vulkan")]
+use crate::resource::texture_streaming::DEFAULT_TEXTURE_MEMORY_BUDGET;
+
+/// Environment variable selecting renderer kind when settings are created. Set it to `software` to render on the CPU, to `wgpu` to render with wgpu,
+/// or to `null` to run without a window or GPU.
+pub const RENDERER_ENVIRONMENT_VARIABLE: &str = "KETCH_RENDERER";
+
+/// Renderer used by the engine and kind of devices it prefers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RendererKind {
+    /// Vulkan renderer preferring discrete GPUs, then virtual and integrated ones.
+    Hardware,
+    /// Vulkan renderer preferring implementations running on the CPU, like lavapipe or SwiftShader, so that games and tests run on machines without a GPU.
+    Software,
+    /// Scene renderer drawing with the null backend, which needs neither a window nor a GPU, so that games and tests run on any CI machine.
+    Null,
+    /// Scene renderer drawing with the wgpu backend, on platforms without Vulkan. It draws only objects of the active scene.
+    #[cfg(feature = "wgpu-backend")]
+    Wgpu,
+}
+
+impl RendererKind {
+    /// Returns renderer kind with given name, which is `hardware`, `software`, `null` or `wgpu`.
+    pub fn from_name(name: &str) -> Option<RendererKind> {
+        match name.trim().to_lowercase().as_str() {
+            "hardware" => Some(RendererKind::Hardware),
+            "software" => Some(RendererKind::Software),
+            "null" => Some(RendererKind::Null),
+            #[cfg(feature = "wgpu-backend")]
+            "wgpu" => Some(RendererKind::Wgpu),
+            _ => None,
+        }
+    }
+}
+
+/// Quality tier of textures, trading sharpness for memory and sampling cost.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureQuality {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+impl TextureQuality {
+    /// Returns largest anisotropy of texture samplers, 1 disables anisotropic filtering. Devices may support less.
+    pub fn max_anisotropy(self) -> f32 {
+        match self {
+            TextureQuality::Low => 1.0,
+            TextureQuality::Medium => 4.0,
+            TextureQuality::High => 8.0,
+            TextureQuality::Ultra => 16.0,
+        }
+    }
+
+    /// Returns bias added to mipmap level of detail, positive values sample smaller mipmaps.
+    pub fn mip_lod_bias(self) -> f32 {
+        match self {
+            TextureQuality::Low => 1.0,
+            TextureQuality::Medium => 0.5,
+            TextureQuality::High | TextureQuality::Ultra => 0.0,
+        }
+    }
+
+    /// Returns how many times width and height of textures are halved when they're uploaded.
+    pub fn downscale(self) -> u32 {
+        match self {
+            TextureQuality::Low => 2,
+            TextureQuality::Medium => 1,
+            TextureQuality::High | TextureQuality::Ultra => 0,
+        }
+    }
+}
+
+/// Destination of engine metrics exported for monitoring long running games, soak tests and servers.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TelemetryExport {
+    /// Appends a JSON object with metrics to the file as a line per export interval.
+    JsonLines(PathBuf),
+    /// Serves latest metrics in Prometheus text format over HTTP on the address, for any path.
+    Prometheus(SocketAddr),
+}
 
 /// Stores engine settings.
+#[cfg(feature = "vulkan")]
 pub struct Settings {
     window_title: String,
     initial_window_size: PhysicalSize,
     time_per_update: Duration,
     log_fps_frequency: Duration,
+    editor_toggle_key: Option<VirtualKeyCode>,
+    headless: bool,
+    random_seed: Option<u64>,
+    batched_rendering: bool,
+    gpu_culling: bool,
+    swapchain_format: Option<Format>,
+    hdr_output: bool,
+    renderer_kind: RendererKind,
+    texture_quality: TextureQuality,
+    upload_budget: u64,
+    texture_memory_budget: u64,
+    log_level: LevelFilter,
+    module_log_levels: Vec<(String, LevelFilter)>,
+    log_file: Option<PathBuf>,
+    photo_mode_key: Option<VirtualKeyCode>,
+    photo_key: Option<VirtualKeyCode>,
+    photo_supersampling: u32,
+    photo_directory: PathBuf,
+    render_scale: f32,
+    upscale_filter: UpscaleFilter,
+    target_frame_time: Option<Duration>,
+    diagnostics_overlay_key: Option<VirtualKeyCode>,
+    telemetry_export: Option<TelemetryExport>,
+    telemetry_interval: Duration,
+    input_bindings_file: Option<PathBuf>,
 }
 
+#[cfg(feature = "vulkan")]
 impl Settings {
     /// Creates new settings struct with given window title and screen size.
     pub fn new<S: Into<String>>(window_title: S, scr_width: f64, scr_height: f64) -> Self {
@@ -17,6 +143,33 @@ impl Settings {
             initial_window_size: PhysicalSize::new(scr_width, scr_height),
             time_per_update: Duration::from_millis(16),
             log_fps_frequency: Duration::from_secs(5),
+            editor_toggle_key: Some(VirtualKeyCode::F12),
+            headless: false,
+            random_seed: None,
+            batched_rendering: false,
+            gpu_culling: false,
+            swapchain_format: None,
+            hdr_output: false,
+            renderer_kind: std::env::var(RENDERER_ENVIRONMENT_VARIABLE).ok()
+                                                                        .and_then(|name| RendererKind::from_name(&name))
+                                                                        .unwrap_or(RendererKind::Hardware),
+            texture_quality: TextureQuality::High,
+            upload_budget: DEFAULT_UPLOAD_BUDGET,
+            texture_memory_budget: DEFAULT_TEXTURE_MEMORY_BUDGET,
+            log_level: LevelFilter::Info,
+            module_log_levels: Vec::new(),
+            log_file: None,
+            photo_mode_key: Some(VirtualKeyCode::F9),
+            photo_key: Some(VirtualKeyCode::F10),
+            photo_supersampling: 2,
+            photo_directory: PathBuf::from("screenshots"),
+            render_scale: 1.0,
+            upscale_filter: UpscaleFilter::Bilinear,
+            target_frame_time: None,
+            diagnostics_overlay_key: Some(VirtualKeyCode::F3),
+            telemetry_export: None,
+            telemetry_interval: Duration::from_secs(10),
+            input_bindings_file: None,
         }
     }
 
@@ -49,4 +202,278 @@ impl Settings {
     pub fn log_fps_frequency(&self) -> Duration {
         self.log_fps_frequency
     }
-}
\ No newline at end of file
+
+    /// Sets key which shows or hides the GUI editor. None disables toggling editor with keyboard.
+    pub fn set_editor_toggle_key(&mut self, value: Option<VirtualKeyCode>) {
+        self.editor_toggle_key = value;
+    }
+
+    /// Returns key which shows or hides the GUI editor.
+    pub fn editor_toggle_key(&self) -> Option<VirtualKeyCode> {
+        self.editor_toggle_key
+    }
+
+    /// Sets headless mode, in which no window is created and nothing is rendered. Used to host games on servers.
+    pub fn set_headless(&mut self, value: bool) {
+        self.headless = value;
+    }
+
+    /// Returns true if engine runs in headless mode.
+    pub fn headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Sets global seed of random numbers used by the game and engine. None picks a seed based on current time.
+    pub fn set_random_seed(&mut self, value: Option<u64>) {
+        self.random_seed = value;
+    }
+
+    /// Returns global seed of random numbers.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Sets batched rendering, which sorts opaque objects by material and mesh and draws consecutive objects sharing them with a single draw call.
+    pub fn set_batched_rendering(&mut self, value: bool) {
+        self.batched_rendering = value;
+    }
+
+    /// Returns true if objects are rendered in batches.
+    pub fn batched_rendering(&self) -> bool {
+        self.batched_rendering
+    }
+
+    /// Sets GPU culling, which culls objects against the camera frustum in a compute pass and draws them with indirect draw commands.
+    /// Together with batched rendering, objects with different meshes are drawn by a single indirect draw call when their meshes share memory blocks.
+    pub fn set_gpu_culling(&mut self, value: bool) {
+        self.gpu_culling = value;
+    }
+
+    /// Returns true if objects are culled on the GPU.
+    pub fn gpu_culling(&self) -> bool {
+        self.gpu_culling
+    }
+
+    /// Sets swapchain format used if the surface supports it. None picks the best supported format.
+    pub fn set_swapchain_format(&mut self, value: Option<Format>) {
+        self.swapchain_format = value;
+    }
+
+    /// Returns swapchain format preferred over the default ones.
+    pub fn swapchain_format(&self) -> Option<Format> {
+        self.swapchain_format
+    }
+
+    /// Sets HDR output, which prefers swapchain formats with more than 8 bits per channel if the surface supports them.
+    pub fn set_hdr_output(&mut self, value: bool) {
+        self.hdr_output = value;
+    }
+
+    /// Returns true if HDR swapchain formats are preferred.
+    pub fn hdr_output(&self) -> bool {
+        self.hdr_output
+    }
+
+    /// Sets renderer used by the engine and kind of devices it prefers.
+    pub fn set_renderer_kind(&mut self, value: RendererKind) {
+        self.renderer_kind = value;
+    }
+
+    /// Returns renderer used by the engine and kind of devices it prefers. Defaults to the kind named by `KETCH_RENDERER` environment variable, or hardware.
+    pub fn renderer_kind(&self) -> RendererKind {
+        self.renderer_kind
+    }
+
+    /// Sets quality of textures loaded after the change.
+    pub fn set_texture_quality(&mut self, value: TextureQuality) {
+        self.texture_quality = value;
+    }
+
+    /// Returns quality of textures, high by default.
+    pub fn texture_quality(&self) -> TextureQuality {
+        self.texture_quality
+    }
+
+    /// Sets number of bytes of streamed assets uploaded per frame. Lower budget streams assets in slower but avoids hitches.
+    pub fn set_upload_budget(&mut self, value: u64) {
+        self.upload_budget = value;
+    }
+
+    /// Returns number of bytes of streamed assets uploaded per frame, 8 MiB by default.
+    pub fn upload_budget(&self) -> u64 {
+        self.upload_budget
+    }
+
+    /// Sets number of bytes of GPU memory used by streamed textures. Textures far from the camera are shown in lower resolution to fit in it.
+    pub fn set_texture_memory_budget(&mut self, value: u64) {
+        self.texture_memory_budget = value;
+    }
+
+    /// Returns number of bytes of GPU memory used by streamed textures, 256 MiB by default.
+    pub fn texture_memory_budget(&self) -> u64 {
+        self.texture_memory_budget
+    }
+
+    /// Sets level of records logged by modules which don't have their own level.
+    pub fn set_log_level(&mut self, value: LevelFilter) {
+        self.log_level = value;
+    }
+
+    /// Returns level of records logged by modules which don't have their own level, info by default.
+    pub fn log_level(&self) -> LevelFilter {
+        self.log_level
+    }
+
+    /// Sets level of records logged by the module and its submodules, e.g. `ketch_core::renderer`, replacing its previous level.
+    pub fn set_module_log_level<S: Into<String>>(&mut self, module: S, value: LevelFilter) {
+        let module = module.into();
+        self.module_log_levels.retain(|(existing, _)| *existing != module);
+        self.module_log_levels.push((module, value));
+    }
+
+    /// Returns modules with their own log levels.
+    pub fn module_log_levels(&self) -> &[(String, LevelFilter)] {
+        &self.module_log_levels
+    }
+
+    /// Sets file which logged records are appended to, in addition to stderr.
+    pub fn set_log_file(&mut self, value: Option<PathBuf>) {
+        self.log_file = value;
+    }
+
+    /// Returns file which logged records are appended to, if any.
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_ref().map(PathBuf::as_path)
+    }
+
+    /// Sets key which enters and exits photo mode, or None if photo mode can't be entered with a key.
+    pub fn set_photo_mode_key(&mut self, value: Option<VirtualKeyCode>) {
+        self.photo_mode_key = value;
+    }
+
+    /// Returns key which enters and exits photo mode, F9 by default.
+    pub fn photo_mode_key(&self) -> Option<VirtualKeyCode> {
+        self.photo_mode_key
+    }
+
+    /// Sets key which saves a photo while in photo mode.
+    pub fn set_photo_key(&mut self, value: Option<VirtualKeyCode>) {
+        self.photo_key = value;
+    }
+
+    /// Returns key which saves a photo while in photo mode, F10 by default.
+    pub fn photo_key(&self) -> Option<VirtualKeyCode> {
+        self.photo_key
+    }
+
+    /// Sets how many times larger than the window photos are rendered before they're downsampled. 1 disables super-sampling.
+    pub fn set_photo_supersampling(&mut self, value: u32) {
+        self.photo_supersampling = value.max(1);
+    }
+
+    /// Returns how many times larger than the window photos are rendered, 2 by default.
+    pub fn photo_supersampling(&self) -> u32 {
+        self.photo_supersampling
+    }
+
+    /// Sets directory which photos taken with the photo key are saved to.
+    pub fn set_photo_directory<P: Into<PathBuf>>(&mut self, value: P) {
+        self.photo_directory = value.into();
+    }
+
+    /// Returns directory which photos taken with the photo key are saved to, `screenshots` by default.
+    pub fn photo_directory(&self) -> &Path {
+        &self.photo_directory
+    }
+
+    /// Sets fraction of the window resolution the scene is rendered at, which is upscaled to the window. Values below `MIN_RENDER_SCALE` are raised to it.
+    pub fn set_render_scale(&mut self, value: f32) {
+        self.render_scale = value;
+    }
+
+    /// Returns fraction of the window resolution the scene is rendered at, 1 by default.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Sets filter used to upscale the scene rendered below the window resolution.
+    pub fn set_upscale_filter(&mut self, value: UpscaleFilter) {
+        self.upscale_filter = value;
+    }
+
+    /// Returns filter used to upscale the scene, bilinear by default.
+    pub fn upscale_filter(&self) -> UpscaleFilter {
+        self.upscale_filter
+    }
+
+    /// Sets frame time which render scale is lowered to reach, up to the render scale from settings. None keeps render scale fixed.
+    pub fn set_target_frame_time(&mut self, value: Option<Duration>) {
+        self.target_frame_time = value;
+    }
+
+    /// Returns frame time which render scale is adjusted to, None by default.
+    pub fn target_frame_time(&self) -> Option<Duration> {
+        self.target_frame_time
+    }
+
+    /// Sets key which shows or hides the diagnostics overlay. None disables toggling it with keyboard.
+    pub fn set_diagnostics_overlay_key(&mut self, value: Option<VirtualKeyCode>) {
+        self.diagnostics_overlay_key = value;
+    }
+
+    /// Returns key which shows or hides the diagnostics overlay, F3 by default.
+    pub fn diagnostics_overlay_key(&self) -> Option<VirtualKeyCode> {
+        self.diagnostics_overlay_key
+    }
+
+    /// Sets destination which engine metrics are exported to. None disables exporting metrics.
+    pub fn set_telemetry_export(&mut self, value: Option<TelemetryExport>) {
+        self.telemetry_export = value;
+    }
+
+    /// Returns destination which engine metrics are exported to, None by default.
+    pub fn telemetry_export(&self) -> Option<&TelemetryExport> {
+        self.telemetry_export.as_ref()
+    }
+
+    /// Sets duration between exported metrics. Frame statistics are aggregated over it.
+    pub fn set_telemetry_interval(&mut self, value: Duration) {
+        self.telemetry_interval = value;
+    }
+
+    /// Returns duration between exported metrics, 10 seconds by default.
+    pub fn telemetry_interval(&self) -> Duration {
+        self.telemetry_interval
+    }
+
+    /// Sets controls settings file, which action and axis bindings are loaded from when the game is initialized
+    /// and saved to when the engine stops, so that bindings changed by players are kept. Default bindings are made
+    /// before the engine runs and only apply until the file is first saved.
+    pub fn set_input_bindings_file(&mut self, value: Option<PathBuf>) {
+        self.input_bindings_file = value;
+    }
+
+    /// Returns controls settings file, if any.
+    pub fn input_bindings_file(&self) -> Option<&Path> {
+        self.input_bindings_file.as_ref().map(PathBuf::as_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renderer_kind_is_parsed_from_name() {
+        assert_eq!(Some(RendererKind::Software), RendererKind::from_name(" Software\n"));
+        assert_eq!(Some(RendererKind::Hardware), RendererKind::from_name("hardware"));
+        assert_eq!(Some(RendererKind::Null), RendererKind::from_name("null"));
+        assert_eq!(None, RendererKind::from_name("vulkan"));
+    }
+
+    #[cfg(feature = "wgpu-backend")]
+    #[test]
+    fn wgpu_renderer_kind_is_parsed_from_name() {
+        assert_eq!(Some(RendererKind::Wgpu), RendererKind::from_name("WGPU"));
+    }
+}
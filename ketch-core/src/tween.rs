@@ -0,0 +1,402 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use log::*;
+use nalgebra_glm::Vec3;
+
+use crate::resource::AssetManager;
+use crate::resource::scene::Scene;
+use crate::ui::Ui;
+
+/// Function mapping linear progress of a tween to eased progress, both from 0.0 to 1.0.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    /// Overshoots the target slightly before settling on it.
+    EaseOutBack,
+}
+
+impl Easing {
+    /// Returns eased progress for linear progress t, which is clamped to the range from 0.0 to 1.0.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInQuad => t * t,
+            Easing::EaseOutQuad => t * (2.0 - t),
+            Easing::EaseInOutQuad => if t < 0.5 { 2.0 * t * t } else { 1.0 - 2.0 * (1.0 - t) * (1.0 - t) },
+            Easing::EaseInCubic => t * t * t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => if t < 0.5 { 4.0 * t * t * t } else { 1.0 - 4.0 * (1.0 - t).powi(3) },
+            Easing::EaseOutBack => {
+                let overshoot = 1.70158;
+                let t = t - 1.0;
+                1.0 + (overshoot + 1.0) * t * t * t + overshoot * t * t
+            },
+        }
+    }
+}
+
+/// Property animated by a tween. Objects are looked up by id in the active scene.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TweenTarget {
+    ObjectPosition(u32),
+    ObjectRotation(u32),
+    ObjectScale(u32),
+    UiOffset(u32),
+    UiColor(u32),
+}
+
+/// Value of a tweened property, unused components are zero.
+type TweenValue = [f32; 4];
+
+/// Access to properties animated by tweens.
+trait TweenProperties {
+    fn read(&self, target: TweenTarget) -> Option<TweenValue>;
+    fn write(&mut self, target: TweenTarget, value: TweenValue);
+}
+
+/// Function called when a tween finishes.
+pub type TweenCallback = Box<dyn FnMut(&mut AssetManager, &mut Ui) + Send + Sync>;
+
+/// Animation of a single property from its value at the start of the tween to the target value.
+/// Tweens chained with `then` start one after another when the previous one finishes.
+pub struct Tween {
+    target: TweenTarget,
+    to: TweenValue,
+    duration: Duration,
+    easing: Easing,
+    delay: Duration,
+    on_complete: Option<TweenCallback>,
+    next: Vec<Tween>,
+}
+
+impl Tween {
+    fn new(target: TweenTarget, to: TweenValue, duration: Duration, easing: Easing) -> Self {
+        Tween {
+            target,
+            to,
+            duration,
+            easing,
+            delay: Duration::new(0, 0),
+            on_complete: None,
+            next: Vec::new(),
+        }
+    }
+
+    /// Creates tween moving object to the position.
+    pub fn position(object_id: u32, position: (f32, f32, f32), duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenTarget::ObjectPosition(object_id), [position.0, position.1, position.2, 0.0], duration, easing)
+    }
+
+    /// Creates tween rotating object to the rotation angles in radians.
+    pub fn rotation(object_id: u32, rotation_angles: (f32, f32, f32), duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenTarget::ObjectRotation(object_id), [rotation_angles.0, rotation_angles.1, rotation_angles.2, 0.0], duration, easing)
+    }
+
+    /// Creates tween scaling object to the scale.
+    pub fn scale(object_id: u32, scale: (f32, f32, f32), duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenTarget::ObjectScale(object_id), [scale.0, scale.1, scale.2, 0.0], duration, easing)
+    }
+
+    /// Creates tween moving UI node to the offset from its anchor.
+    pub fn ui_offset(node_id: u32, offset: (f32, f32), duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenTarget::UiOffset(node_id), [offset.0, offset.1, 0.0, 0.0], duration, easing)
+    }
+
+    /// Creates tween changing color of UI node, used e.g. to fade nodes in and out.
+    pub fn ui_color(node_id: u32, color: [f32; 4], duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenTarget::UiColor(node_id), color, duration, easing)
+    }
+
+    /// Sets time waited before the tween starts.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets function called when the tween finishes.
+    pub fn on_complete<F: FnMut(&mut AssetManager, &mut Ui) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Appends tween started after this tween and tweens chained before it finish.
+    pub fn then(mut self, tween: Tween) -> Self {
+        self.next.push(tween);
+        self
+    }
+}
+
+struct ActiveTween {
+    id: u32,
+    tween: Tween,
+    /// Value of the property when the tween started, None while it's delayed.
+    from: Option<TweenValue>,
+    elapsed: Duration,
+    queue: VecDeque<Tween>,
+}
+
+/// Runs tweens, updated by the engine after every game update.
+pub struct TweenSystem {
+    tweens: Vec<ActiveTween>,
+    next_id: u32,
+}
+
+impl TweenSystem {
+    pub fn new() -> Self {
+        TweenSystem {
+            tweens: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts tween along with tweens chained to it. Returns id of the chain.
+    pub fn start(&mut self, mut tween: Tween) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let queue = tween.next.drain(..).collect();
+        self.tweens.push(ActiveTween { id, tween, from: None, elapsed: Duration::new(0, 0), queue });
+        id
+    }
+
+    /// Stops chain of tweens, leaving the property at its current value. Completion callbacks aren't called.
+    pub fn stop(&mut self, id: u32) {
+        self.tweens.retain(|active| active.id != id);
+    }
+
+    /// Stops all tweens animating the property.
+    pub fn stop_target(&mut self, target: TweenTarget) {
+        self.tweens.retain(|active| active.tween.target != target);
+    }
+
+    /// Returns true if any tween of the chain didn't finish yet.
+    pub fn is_running(&self, id: u32) -> bool {
+        self.tweens.iter().any(|active| active.id == id)
+    }
+
+    /// Stops all tweens.
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+
+    /// Moves start and target positions of tweens moving root objects of the scene by the negated offset, after the scene shifted its origin by it.
+    /// Children are positioned relative to their parents, so their tweens are left as they are.
+    pub fn shift_origin(&mut self, offset: &Vec3, scene: &Scene) {
+        let is_root = |id| scene.object(id).is_some_and(|object| object.parent().is_none());
+        for active in &mut self.tweens {
+            if let (TweenTarget::ObjectPosition(id), Some(from)) = (active.tween.target, &mut active.from) {
+                if is_root(id) {
+                    shift_position(from, offset);
+                }
+            }
+            shift_tween(&mut active.tween, offset, &is_root);
+            for tween in &mut active.queue {
+                shift_tween(tween, offset, &is_root);
+            }
+        }
+    }
+
+    /// Advances tweens by elapsed time, setting animated properties and calling callbacks of finished tweens.
+    pub fn update(&mut self, elapsed: Duration, asset_manager: &mut AssetManager, ui: &mut Ui) {
+        let callbacks = self.advance(elapsed, &mut EngineProperties { asset_manager, ui });
+        for mut callback in callbacks {
+            callback(asset_manager, ui);
+        }
+    }
+
+    /// Advances tweens animating given properties. Returns callbacks of tweens which finished.
+    /// Chains whose property can't be read, e.g. because its object was removed, are dropped.
+    fn advance<P: TweenProperties>(&mut self, elapsed: Duration, properties: &mut P) -> Vec<TweenCallback> {
+        let mut callbacks = Vec::new();
+        let mut finished_ids = Vec::new();
+        for active in &mut self.tweens {
+            let mut remaining = elapsed;
+            loop {
+                if active.from.is_none() {
+                    if remaining < active.tween.delay {
+                        active.tween.delay -= remaining;
+                        break;
+                    }
+                    remaining -= active.tween.delay;
+                    active.tween.delay = Duration::new(0, 0);
+                    match properties.read(active.tween.target) {
+                        Some(value) => active.from = Some(value),
+                        None => {
+                            warn!("Property of {:?} doesn't exist, tween is stopped", active.tween.target);
+                            finished_ids.push(active.id);
+                            break;
+                        },
+                    }
+                }
+                let step = remaining.min(active.tween.duration - active.elapsed);
+                active.elapsed += step;
+                remaining -= step;
+                let progress = if active.tween.duration == Duration::new(0, 0) {
+                    1.0
+                } else {
+                    duration_secs(active.elapsed) / duration_secs(active.tween.duration)
+                };
+                let from = active.from.expect("start value is read above");
+                properties.write(active.tween.target, lerp(from, active.tween.to, active.tween.easing.apply(progress)));
+                if active.elapsed < active.tween.duration {
+                    break;
+                }
+
+                if let Some(callback) = active.tween.on_complete.take() {
+                    callbacks.push(callback);
+                }
+                match active.queue.pop_front() {
+                    Some(mut next) => {
+                        // tweens chained to chained tweens run before the rest of the chain
+                        for nested in next.next.drain(..).rev() {
+                            active.queue.push_front(nested);
+                        }
+                        active.tween = next;
+                        active.from = None;
+                        active.elapsed = Duration::new(0, 0);
+                    },
+                    None => {
+                        finished_ids.push(active.id);
+                        break;
+                    },
+                }
+            }
+        }
+        self.tweens.retain(|active| !finished_ids.contains(&active.id));
+        callbacks
+    }
+}
+
+impl Default for TweenSystem {
+    fn default() -> Self {
+        TweenSystem::new()
+    }
+}
+
+/// Properties of objects in the active scene and UI nodes.
+struct EngineProperties<'a> {
+    asset_manager: &'a mut AssetManager,
+    ui: &'a mut Ui,
+}
+
+impl<'a> TweenProperties for EngineProperties<'a> {
+    fn read(&self, target: TweenTarget) -> Option<TweenValue> {
+        let object = |id| self.asset_manager.active_scene().and_then(|scene| scene.object(id));
+        match target {
+            TweenTarget::ObjectPosition(id) => object(id).map(|object| tuple_value(object.position())),
+            TweenTarget::ObjectRotation(id) => object(id).map(|object| tuple_value(object.rotation_angles())),
+            TweenTarget::ObjectScale(id) => object(id).map(|object| tuple_value(object.scale())),
+            TweenTarget::UiOffset(id) => self.ui.node(id).map(|node| [node.offset.0, node.offset.1, 0.0, 0.0]),
+            TweenTarget::UiColor(id) => self.ui.node(id).map(|node| node.color),
+        }
+    }
+
+    fn write(&mut self, target: TweenTarget, value: TweenValue) {
+        let [x, y, z, w] = value;
+        match target {
+            TweenTarget::ObjectPosition(id) | TweenTarget::ObjectRotation(id) | TweenTarget::ObjectScale(id) => {
+                if let Some(object) = self.asset_manager.active_scene_mut().and_then(|scene| scene.object_mut(id)) {
+                    match target {
+                        TweenTarget::ObjectPosition(_) => object.set_position(x, y, z),
+                        TweenTarget::ObjectRotation(_) => object.set_rotation_angles(x, y, z),
+                        _ => object.set_scale_xyz(x, y, z),
+                    }
+                }
+            },
+            TweenTarget::UiOffset(id) => if let Some(node) = self.ui.node_mut(id) {
+                node.offset = (x, y);
+            },
+            TweenTarget::UiColor(id) => if let Some(node) = self.ui.node_mut(id) {
+                node.color = [x, y, z, w];
+            },
+        }
+    }
+}
+
+fn tuple_value(value: (f32, f32, f32)) -> TweenValue {
+    [value.0, value.1, value.2, 0.0]
+}
+
+/// Shifts target position of the tween and of tweens chained to it, if they move root objects.
+fn shift_tween<F: Fn(u32) -> bool>(tween: &mut Tween, offset: &Vec3, is_root: &F) {
+    if let TweenTarget::ObjectPosition(id) = tween.target {
+        if is_root(id) {
+            shift_position(&mut tween.to, offset);
+        }
+    }
+    for next in &mut tween.next {
+        shift_tween(next, offset, is_root);
+    }
+}
+
+fn shift_position(position: &mut TweenValue, offset: &Vec3) {
+    position[0] -= offset.x;
+    position[1] -= offset.y;
+    position[2] -= offset.z;
+}
+
+fn lerp(from: TweenValue, to: TweenValue, t: f32) -> TweenValue {
+    let mut value = from;
+    for (component, to) in value.iter_mut().zip(to.iter()) {
+        *component += (to - *component) * t;
+    }
+    value
+}
+
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    impl TweenProperties for HashMap<TweenTarget, TweenValue> {
+        fn read(&self, target: TweenTarget) -> Option<TweenValue> {
+            self.get(&target).cloned()
+        }
+
+        fn write(&mut self, target: TweenTarget, value: TweenValue) {
+            self.insert(target, value);
+        }
+    }
+
+    #[test]
+    fn easing_starts_and_ends_at_bounds() {
+        for easing in &[Easing::Linear, Easing::EaseInQuad, Easing::EaseOutQuad, Easing::EaseInOutQuad,
+                        Easing::EaseInCubic, Easing::EaseOutCubic, Easing::EaseInOutCubic, Easing::EaseOutBack] {
+            assert!(easing.apply(0.0).abs() < 0.0001);
+            assert!((easing.apply(1.0) - 1.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn chained_tween_starts_after_delay_from_where_previous_ended() {
+        let target = TweenTarget::UiOffset(0);
+        let mut properties = HashMap::new();
+        properties.insert(target, [0.0; 4]);
+        let mut tween_system = TweenSystem::new();
+        let id = tween_system.start(Tween::ui_offset(0, (10.0, 0.0), Duration::from_millis(100), Easing::Linear)
+                                         .then(Tween::ui_offset(0, (10.0, 20.0), Duration::from_millis(100), Easing::Linear)
+                                                    .with_delay(Duration::from_millis(50))));
+
+        tween_system.advance(Duration::from_millis(50), &mut properties);
+        assert_eq!([5.0, 0.0, 0.0, 0.0], properties[&target]);
+        tween_system.advance(Duration::from_millis(100), &mut properties);
+        assert_eq!([10.0, 0.0, 0.0, 0.0], properties[&target]);
+        tween_system.advance(Duration::from_millis(50), &mut properties);
+        assert_eq!([10.0, 10.0, 0.0, 0.0], properties[&target]);
+        assert!(tween_system.is_running(id));
+        tween_system.advance(Duration::from_millis(50), &mut properties);
+        assert_eq!([10.0, 20.0, 0.0, 0.0], properties[&target]);
+        assert!(!tween_system.is_running(id));
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use log::*;
+
+#[cfg(feature = "window")]
+use crate::input::input_event::{ElementState, InputEvent, MouseButton};
+use crate::resource::AssetManager;
+use crate::ui::ui_node::{Layout, Rect, UiElement, UiNode};
+
+pub mod ui_node;
+
+/// Events produced by UI from input.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UiEvent {
+    /// Button was pressed and released with the left mouse button.
+    Clicked(u32),
+}
+
+struct UiEntry {
+    id: u32,
+    parent: Option<u32>,
+    node: UiNode,
+}
+
+/// Retained in-game UI, such as menus and HUDs. Nodes form a tree, children are placed relative to their parents
+/// and drawn on top of them. Layout is updated by the engine every frame, before UI is drawn on top of the scene.
+pub struct Ui {
+    /// Nodes in draw order, parents are always before their children.
+    entries: Vec<UiEntry>,
+    next_id: u32,
+    rects: HashMap<u32, Rect>,
+    cursor: Option<(f32, f32)>,
+    hovered: Option<u32>,
+    pressed: Option<u32>,
+}
+
+impl Ui {
+    /// Creates empty UI.
+    pub fn new() -> Self {
+        Ui {
+            entries: Vec::new(),
+            next_id: 0,
+            rects: HashMap::new(),
+            cursor: None,
+            hovered: None,
+            pressed: None,
+        }
+    }
+
+    /// Adds node as a child of parent node, or as a root node placed relative to the window if parent is None.
+    /// Returns id of the added node.
+    pub fn add(&mut self, parent: Option<u32>, node: UiNode) -> u32 {
+        let parent = match parent {
+            Some(parent) if !self.entries.iter().any(|entry| entry.id == parent) => {
+                warn!("UI node {} doesn't exist, node is added as a root", parent);
+                None
+            },
+            parent => parent,
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        // node goes after all descendants of its parent, so that it's drawn on top of its older siblings
+        let position = match parent {
+            Some(parent) => {
+                let parent_position = self.entries.iter().position(|entry| entry.id == parent).expect("parent was checked above");
+                let mut position = parent_position + 1;
+                while position < self.entries.len() && self.is_descendant(self.entries[position].id, parent) {
+                    position += 1;
+                }
+                position
+            },
+            None => self.entries.len(),
+        };
+        self.entries.insert(position, UiEntry { id, parent, node });
+        id
+    }
+
+    /// Removes node along with all its children. Returns removed node.
+    pub fn remove(&mut self, id: u32) -> Option<UiNode> {
+        let position = self.entries.iter().position(|entry| entry.id == id)?;
+        let removed_ids: Vec<u32> = self.entries.iter().map(|entry| entry.id).filter(|entry_id| self.is_descendant(*entry_id, id)).collect();
+        self.entries.retain(|entry| !removed_ids.contains(&entry.id));
+        for removed_id in removed_ids {
+            self.rects.remove(&removed_id);
+        }
+        self.rects.remove(&id);
+        let entry = self.entries.remove(position);
+        Some(entry.node)
+    }
+
+    /// Removes all nodes.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.rects.clear();
+        self.hovered = None;
+        self.pressed = None;
+    }
+
+    /// Returns node with the given id.
+    pub fn node(&self, id: u32) -> Option<&UiNode> {
+        self.entries.iter().find(|entry| entry.id == id).map(|entry| &entry.node)
+    }
+
+    /// Returns a mutable reference to node with the given id. Changes are visible after the next layout update.
+    pub fn node_mut(&mut self, id: u32) -> Option<&mut UiNode> {
+        self.entries.iter_mut().find(|entry| entry.id == id).map(|entry| &mut entry.node)
+    }
+
+    /// Returns rectangle of the node after the last layout update, or None if node isn't visible.
+    pub fn rect(&self, id: u32) -> Option<Rect> {
+        self.rects.get(&id).cloned()
+    }
+
+    /// Returns true if cursor is over the button.
+    pub fn is_hovered(&self, id: u32) -> bool {
+        self.hovered == Some(id)
+    }
+
+    /// Returns true if the button is held down.
+    pub fn is_pressed(&self, id: u32) -> bool {
+        self.pressed == Some(id)
+    }
+
+    /// Returns true if cursor is over any visible node, used by games to ignore clicks meant for the UI.
+    pub fn cursor_over_ui(&self) -> bool {
+        match self.cursor {
+            Some((x, y)) => self.rects.values().any(|rect| rect.contains(x, y)),
+            None => false,
+        }
+    }
+
+    /// Places nodes in a window of given size in pixels, measuring text with fonts from asset manager,
+    /// replaced by fonts overridden for the current language.
+    pub fn update_layout(&mut self, window_size: (f32, f32), asset_manager: &AssetManager) {
+        self.update_layout_with(window_size, |text, font, font_size| match asset_manager.font(asset_manager.localization().font(font)) {
+            Some(font) => font.text_size(text, font_size),
+            None => (0.0, 0.0),
+        });
+    }
+
+    /// Places nodes in a window of given size in pixels, measuring text with given function.
+    pub fn update_layout_with<F: Fn(&str, &str, f32) -> (f32, f32)>(&mut self, window_size: (f32, f32), text_size: F) {
+        let window_rect = Rect { x: 0.0, y: 0.0, width: window_size.0, height: window_size.1 };
+        let mut rects: HashMap<u32, Rect> = HashMap::new();
+        // position of the next child in stacked layouts
+        let mut stack_positions: HashMap<u32, f32> = HashMap::new();
+
+        for entry in &self.entries {
+            let (parent_rect, parent_layout, parent_padding) = match entry.parent {
+                Some(parent) => match (rects.get(&parent), self.node(parent)) {
+                    (Some(parent_rect), Some(parent_node)) => (*parent_rect, parent_node.layout, parent_node.padding),
+                    // parent is hidden
+                    _ => continue,
+                },
+                None => (window_rect, Layout::Free, 0.0),
+            };
+            if !entry.node.visible {
+                continue;
+            }
+
+            let (width, height) = match &entry.node.element {
+                UiElement::Text { text, font, font_size } => text_size(text, font, *font_size),
+                _ => entry.node.size,
+            };
+            let (offset_x, offset_y) = entry.node.offset;
+            let inner_x = parent_rect.x + parent_padding;
+            let inner_y = parent_rect.y + parent_padding;
+            let (x, y) = match parent_layout {
+                Layout::Free => {
+                    let (anchor_x, anchor_y) = entry.node.anchor.relative_position();
+                    let inner_width = parent_rect.width - parent_padding * 2.0;
+                    let inner_height = parent_rect.height - parent_padding * 2.0;
+                    (inner_x + anchor_x * (inner_width - width) + offset_x, inner_y + anchor_y * (inner_height - height) + offset_y)
+                },
+                Layout::Vertical { spacing } => {
+                    let stack_position = stack_positions.entry(entry.parent.unwrap_or(entry.id)).or_insert(inner_y);
+                    let y = *stack_position;
+                    *stack_position += height + spacing;
+                    (inner_x + offset_x, y + offset_y)
+                },
+                Layout::Horizontal { spacing } => {
+                    let stack_position = stack_positions.entry(entry.parent.unwrap_or(entry.id)).or_insert(inner_x);
+                    let x = *stack_position;
+                    *stack_position += width + spacing;
+                    (x + offset_x, inner_y + offset_y)
+                },
+            };
+            rects.insert(entry.id, Rect { x, y, width, height });
+        }
+        self.rects = rects;
+        self.update_hovered();
+    }
+
+    /// Returns visible nodes along with their rectangles, in the order they should be drawn.
+    pub fn draw_list(&self) -> Vec<(u32, &UiNode, Rect)> {
+        self.entries.iter()
+                    .filter_map(|entry| self.rects.get(&entry.id).map(|rect| (entry.id, &entry.node, *rect)))
+                    .collect()
+    }
+
+    /// Updates cursor position and button states. Cursor position is converted from logical to window pixels using dpi factor.
+    #[cfg(feature = "window")]
+    pub fn handle_input(&mut self, input_events: &[InputEvent], dpi_factor: f64) -> Vec<UiEvent> {
+        let mut ui_events = Vec::new();
+        for input_event in input_events {
+            match input_event {
+                InputEvent::CursorMoved { x, y } => {
+                    self.cursor = Some(((x * dpi_factor) as f32, (y * dpi_factor) as f32));
+                    self.update_hovered();
+                },
+                InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed } => self.pressed = self.hovered,
+                InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Released } => {
+                    if let Some(pressed) = self.pressed.take() {
+                        if self.hovered == Some(pressed) {
+                            ui_events.push(UiEvent::Clicked(pressed));
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+        ui_events
+    }
+
+    /// Returns true if input event was meant for the UI and shouldn't reach gameplay, which is the case for mouse buttons and wheel over a button.
+    /// Has to be called after the event was handled.
+    #[cfg(feature = "window")]
+    pub fn consumes_input(&self, input_event: &InputEvent) -> bool {
+        match input_event {
+            InputEvent::MouseInput { .. } | InputEvent::MouseWheel(_) => self.hovered.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Finds the topmost button under the cursor.
+    fn update_hovered(&mut self) {
+        self.hovered = match self.cursor {
+            Some((x, y)) => self.draw_list().into_iter()
+                                            .rev()
+                                            .find(|(_, node, rect)| match node.element {
+                                                UiElement::Button { .. } => rect.contains(x, y),
+                                                _ => false,
+                                            })
+                                            .map(|(id, _, _)| id),
+            None => None,
+        };
+    }
+
+    /// Returns true if node is a child of ancestor, or a child of one of its children.
+    fn is_descendant(&self, id: u32, ancestor: u32) -> bool {
+        let mut parent = self.entries.iter().find(|entry| entry.id == id).and_then(|entry| entry.parent);
+        while let Some(parent_id) = parent {
+            if parent_id == ancestor {
+                return true;
+            }
+            parent = self.entries.iter().find(|entry| entry.id == parent_id).and_then(|entry| entry.parent);
+        }
+        false
+    }
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Ui::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ui_node::Anchor;
+
+    #[test]
+    fn stacked_children_are_placed_inside_anchored_parent() {
+        let mut ui = Ui::new();
+        let menu = ui.add(None, UiNode::panel(200.0, 300.0, [0.0; 4]).with_anchor(Anchor::BottomRight, -10.0, -10.0)
+                                                                   .with_layout(Layout::Vertical { spacing: 5.0 }, 20.0));
+        let first = ui.add(Some(menu), UiNode::button("first", "font", 16.0, 160.0, 40.0));
+        let second = ui.add(Some(menu), UiNode::button("second", "font", 16.0, 160.0, 40.0));
+
+        ui.update_layout_with((800.0, 600.0), |_, _, _| (0.0, 0.0));
+
+        assert_eq!(Some(Rect { x: 590.0, y: 290.0, width: 200.0, height: 300.0 }), ui.rect(menu));
+        assert_eq!(Some(Rect { x: 610.0, y: 310.0, width: 160.0, height: 40.0 }), ui.rect(first));
+        assert_eq!(Some(Rect { x: 610.0, y: 355.0, width: 160.0, height: 40.0 }), ui.rect(second));
+    }
+
+    #[cfg(feature = "window")]
+    #[test]
+    fn button_is_clicked_when_released_over_it() {
+        let mut ui = Ui::new();
+        let button = ui.add(None, UiNode::button("play", "font", 16.0, 100.0, 50.0).with_anchor(Anchor::TopLeft, 10.0, 10.0));
+        ui.update_layout_with((800.0, 600.0), |_, _, _| (0.0, 0.0));
+
+        let events = ui.handle_input(&[
+            InputEvent::CursorMoved { x: 50.0, y: 30.0 },
+            InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Pressed },
+            InputEvent::MouseInput { button: MouseButton::Left, state: ElementState::Released },
+        ], 1.0);
+
+        assert_eq!(vec![UiEvent::Clicked(button)], events);
+    }
+}
@@ -0,0 +1,144 @@
+/// Point of the parent rectangle a node is attached to. Node is aligned to the same point of its own rectangle,
+/// so that e.g. node anchored to bottom right corner grows towards the top left.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Returns position of the anchor point relative to rectangle size, from (0.0, 0.0) at top left to (1.0, 1.0) at bottom right.
+    pub fn relative_position(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// Describes how children of a node are placed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layout {
+    /// Children are placed using their anchors and offsets.
+    Free,
+    /// Children are stacked from top to bottom, separated by spacing. Their anchors are ignored.
+    Vertical { spacing: f32 },
+    /// Children are stacked from left to right, separated by spacing. Their anchors are ignored.
+    Horizontal { spacing: f32 },
+}
+
+/// Content of a UI node.
+#[derive(Clone, PartialEq, Debug)]
+pub enum UiElement {
+    /// Rectangle filled with node color.
+    Panel,
+    /// Texture from asset manager tinted with node color.
+    Image { texture: String },
+    /// Single line of text drawn with node color. Node is sized to fit the text.
+    Text { text: String, font: String, font_size: f32 },
+    /// Rectangle filled with node color, which reports clicks. Label is centered and drawn with label color.
+    Button { label: String, font: String, font_size: f32, label_color: [f32; 4] },
+}
+
+/// Element of in-game UI along with its placement.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UiNode {
+    pub element: UiElement,
+    pub anchor: Anchor,
+    /// Offset from the anchor point in pixels, positive values move the node right and down.
+    pub offset: (f32, f32),
+    /// Size in pixels. Ignored by text, which is sized to fit.
+    pub size: (f32, f32),
+    pub color: [f32; 4],
+    /// Hidden nodes aren't drawn and don't receive input, neither do their children.
+    pub visible: bool,
+    pub layout: Layout,
+    /// Space between edges of this node and its children in pixels.
+    pub padding: f32,
+}
+
+impl UiNode {
+    fn new(element: UiElement, size: (f32, f32), color: [f32; 4]) -> Self {
+        UiNode {
+            element,
+            anchor: Anchor::TopLeft,
+            offset: (0.0, 0.0),
+            size,
+            color,
+            visible: true,
+            layout: Layout::Free,
+            padding: 0.0,
+        }
+    }
+
+    /// Creates panel of given size filled with color.
+    pub fn panel(width: f32, height: f32, color: [f32; 4]) -> Self {
+        UiNode::new(UiElement::Panel, (width, height), color)
+    }
+
+    /// Creates image of given size showing texture with the given name.
+    pub fn image<S: Into<String>>(texture: S, width: f32, height: f32) -> Self {
+        UiNode::new(UiElement::Image { texture: texture.into() }, (width, height), [1.0, 1.0, 1.0, 1.0])
+    }
+
+    /// Creates white text drawn with font with the given name.
+    pub fn text<S: Into<String>, F: Into<String>>(text: S, font: F, font_size: f32) -> Self {
+        UiNode::new(UiElement::Text { text: text.into(), font: font.into(), font_size }, (0.0, 0.0), [1.0, 1.0, 1.0, 1.0])
+    }
+
+    /// Creates gray button of given size with white label.
+    pub fn button<S: Into<String>, F: Into<String>>(label: S, font: F, font_size: f32, width: f32, height: f32) -> Self {
+        let element = UiElement::Button { label: label.into(), font: font.into(), font_size, label_color: [1.0, 1.0, 1.0, 1.0] };
+        UiNode::new(element, (width, height), [0.3, 0.3, 0.3, 1.0])
+    }
+
+    /// Sets anchor and offset from it.
+    pub fn with_anchor(mut self, anchor: Anchor, offset_x: f32, offset_y: f32) -> Self {
+        self.anchor = anchor;
+        self.offset = (offset_x, offset_y);
+        self
+    }
+
+    /// Sets color.
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets layout of children and padding around them.
+    pub fn with_layout(mut self, layout: Layout, padding: f32) -> Self {
+        self.layout = layout;
+        self.padding = padding;
+        self
+    }
+}
+
+/// Rectangle in window pixels, with origin at top left corner of the window.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    /// Returns true if point is inside the rectangle.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::resource::video::Video;
+
+/// Video played into a texture.
+#[derive(Clone, PartialEq, Debug)]
+pub struct VideoPlayback {
+    video: String,
+    position: Duration,
+    looping: bool,
+    paused: bool,
+    shown_frame: Option<usize>,
+    id: u64,
+}
+
+impl VideoPlayback {
+    /// Returns name of the played video.
+    pub fn video(&self) -> &str {
+        &self.video
+    }
+
+    /// Returns time elapsed since the beginning of the video.
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Returns true if the video starts again after it ends.
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Returns true if the video is paused.
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns number identifying this playback, which changes every time the video is started again.
+    /// It's used by the audio system to restart the soundtrack together with the video.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+/// Plays videos into textures. Every time a new frame is shown, the texture with the playback name is replaced in asset manager,
+/// so videos can be shown by materials using that texture as albedo texture, or by ui images.
+pub struct VideoPlayer {
+    playbacks: HashMap<String, VideoPlayback>,
+    next_id: u64,
+}
+
+impl VideoPlayer {
+    /// Creates video player without any playing videos.
+    pub fn new() -> Self {
+        VideoPlayer {
+            playbacks: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Starts playing video from the beginning into the texture with given name, replacing video played into it before.
+    pub fn play<T: Into<String>, V: Into<String>>(&mut self, texture: T, video: V, looping: bool) {
+        self.next_id += 1;
+        self.playbacks.insert(texture.into(), VideoPlayback {
+            video: video.into(),
+            position: Duration::from_secs(0),
+            looping,
+            paused: false,
+            shown_frame: None,
+            id: self.next_id,
+        });
+    }
+
+    /// Stops video played into given texture. Texture keeps showing the last frame. Returns false if no video was played into it.
+    pub fn stop(&mut self, texture: &str) -> bool {
+        self.playbacks.remove(texture).is_some()
+    }
+
+    /// Pauses or resumes video played into given texture.
+    pub fn set_paused(&mut self, texture: &str, paused: bool) {
+        if let Some(playback) = self.playbacks.get_mut(texture) {
+            playback.paused = paused;
+        }
+    }
+
+    /// Returns video played into given texture.
+    pub fn playback(&self, texture: &str) -> Option<&VideoPlayback> {
+        self.playbacks.get(texture)
+    }
+
+    /// Returns names of textures along with videos played into them.
+    pub fn playbacks(&self) -> impl Iterator<Item = (&str, &VideoPlayback)> {
+        self.playbacks.iter().map(|(texture, playback)| (texture.as_str(), playback))
+    }
+
+    /// Returns true if video played into given texture isn't looping and reached its end.
+    pub fn finished(&self, texture: &str, video: &Video) -> bool {
+        self.playbacks.get(texture).map(|playback| !playback.looping && playback.position >= video.duration()).unwrap_or(false)
+    }
+
+    /// Advances playing videos and returns textures which should show a different frame, with the video and index of the frame.
+    /// Playbacks of videos which aren't in asset manager are removed.
+    pub(crate) fn advance<F>(&mut self, elapsed: Duration, video_lookup: F) -> Vec<(String, Arc<Video>, usize)>
+        where F: Fn(&str) -> Option<Arc<Video>> {
+        let mut changed_frames = Vec::new();
+        self.playbacks.retain(|texture, playback| {
+            let video = match video_lookup(&playback.video) {
+                Some(video) => video,
+                None => return false,
+            };
+            if video.frame_count() == 0 {
+                return true;
+            }
+            if !playback.paused {
+                let duration = video.duration();
+                playback.position += elapsed;
+                if playback.position >= duration {
+                    playback.position = if playback.looping {
+                        wrap(playback.position, duration)
+                    } else {
+                        duration
+                    };
+                }
+            }
+            let frame = video.frame_at(playback.position);
+            if playback.shown_frame != Some(frame) {
+                playback.shown_frame = Some(frame);
+                changed_frames.push((texture.clone(), video, frame));
+            }
+            true
+        });
+        changed_frames
+    }
+}
+
+impl Default for VideoPlayer {
+    fn default() -> Self {
+        VideoPlayer::new()
+    }
+}
+
+/// Returns position wrapped to the beginning of a looping video.
+fn wrap(position: Duration, duration: Duration) -> Duration {
+    let duration_nanos = duration.as_secs() as u128 * 1_000_000_000 + u128::from(duration.subsec_nanos());
+    if duration_nanos == 0 {
+        return Duration::from_secs(0);
+    }
+    let position_nanos = position.as_secs() as u128 * 1_000_000_000 + u128::from(position.subsec_nanos());
+    let wrapped = (position_nanos % duration_nanos) as u64;
+    Duration::new(wrapped / 1_000_000_000, (wrapped % 1_000_000_000) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn frames_change_while_video_plays() {
+        let frames = (0..4).map(|index| PathBuf::from(format!("frame_{}.png", index))).collect();
+        let video = Arc::new(Video::new("test_video", frames, 4.0));
+        let lookup = |name: &str| Some(video.clone()).filter(|video| video.name() == name);
+        let mut player = VideoPlayer::new();
+        player.play("screen", "test_video", true);
+
+        let changed: Vec<usize> = player.advance(Duration::from_millis(100), lookup).into_iter().map(|(_, _, frame)| frame).collect();
+        assert_eq!(vec![0], changed);
+        assert!(player.advance(Duration::from_millis(100), lookup).is_empty());
+
+        let changed: Vec<usize> = player.advance(Duration::from_millis(300), lookup).into_iter().map(|(_, _, frame)| frame).collect();
+        assert_eq!(vec![2], changed);
+
+        let changed: Vec<usize> = player.advance(Duration::from_millis(600), lookup).into_iter().map(|(_, _, frame)| frame).collect();
+        assert_eq!(vec![0], changed);
+        assert_eq!(Duration::from_millis(100), player.playback("screen").unwrap().position());
+    }
+}
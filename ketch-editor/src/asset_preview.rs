@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use ketch_core::renderer::asset_preview::TextureChannel;
+use ketch_core::resource::AssetManager;
+
+use crate::Editor;
+
+/// Rotation speed of previewed meshes in radians per second.
+const MESH_PREVIEW_ROTATION_SPEED: f32 = 1.0;
+
+/// Asset shown in the preview window.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PreviewedAsset {
+    Texture { name: String, channel: TextureChannel, mip_level: u32, mip_levels: u32 },
+    Mesh { name: String, rotation: f32, wireframe: bool },
+}
+
+impl PreviewedAsset {
+    /// Returns name of the previewed asset.
+    pub fn name(&self) -> &str {
+        match self {
+            PreviewedAsset::Texture { name, .. } => name,
+            PreviewedAsset::Mesh { name, .. } => name,
+        }
+    }
+}
+
+/// Actions from the asset browser and the preview window.
+pub enum AssetPreviewAction {
+    OpenTexture(String),
+    OpenMesh(String),
+    SetChannel(TextureChannel),
+    ChangeMipLevel { increase: bool },
+    ToggleWireframe,
+    Close,
+}
+
+impl Editor {
+    /// Executes asset preview action, opening, changing or closing the preview window.
+    pub(crate) fn execute_asset_preview_action(&mut self, asset_preview_action: AssetPreviewAction, asset_manager: &AssetManager) {
+        match (asset_preview_action, &mut self.previewed_asset) {
+            (AssetPreviewAction::OpenTexture(name), previewed_asset) => {
+                if let Some(texture) = asset_manager.texture(&name) {
+                    *previewed_asset = Some(PreviewedAsset::Texture { name, channel: TextureChannel::All, mip_level: 0, mip_levels: texture.mip_levels() });
+                }
+            },
+            (AssetPreviewAction::OpenMesh(name), previewed_asset) => {
+                if asset_manager.mesh(&name).is_some() {
+                    *previewed_asset = Some(PreviewedAsset::Mesh { name, rotation: 0.0, wireframe: false });
+                }
+            },
+            (AssetPreviewAction::SetChannel(new_channel), Some(PreviewedAsset::Texture { channel, .. })) => *channel = new_channel,
+            (AssetPreviewAction::ChangeMipLevel { increase }, Some(PreviewedAsset::Texture { mip_level, mip_levels, .. })) => {
+                *mip_level = if increase {
+                    (*mip_level + 1).min(mip_levels.saturating_sub(1))
+                } else {
+                    mip_level.saturating_sub(1)
+                };
+            },
+            (AssetPreviewAction::ToggleWireframe, Some(PreviewedAsset::Mesh { wireframe, .. })) => *wireframe = !*wireframe,
+            (AssetPreviewAction::Close, previewed_asset) => *previewed_asset = None,
+            _ => (),
+        }
+        self.update_gui();
+    }
+
+    /// Spins previewed mesh.
+    pub(crate) fn update_asset_preview(&mut self, update_time_delta: Duration) {
+        if let Some(PreviewedAsset::Mesh { rotation, .. }) = &mut self.previewed_asset {
+            *rotation = (*rotation + MESH_PREVIEW_ROTATION_SPEED * update_time_delta.as_millis() as f32 / 1000.0) % (2.0 * std::f32::consts::PI);
+        }
+    }
+}
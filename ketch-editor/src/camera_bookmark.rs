@@ -0,0 +1,120 @@
+use ketch_core::input::input_event::VirtualKeyCode;
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::camera::Camera;
+use serde::{Deserialize, Serialize};
+
+use crate::Editor;
+
+/// Number of bookmark slots, recalled with number keys 1 to 9.
+pub const BOOKMARK_SLOTS: u32 = 9;
+
+/// Named position and orientation of the editor camera.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl CameraBookmark {
+    /// Creates bookmark of current camera position and orientation.
+    pub fn from_camera<S: Into<String>>(name: S, camera: &Camera) -> Self {
+        CameraBookmark {
+            name: name.into(),
+            position: camera.position(),
+            yaw: camera.yaw(),
+            pitch: camera.pitch(),
+        }
+    }
+
+    /// Moves and rotates camera to the bookmarked position and orientation.
+    pub fn apply(&self, camera: &mut Camera) {
+        let (x, y, z) = self.position;
+        camera.set_position(x, y, z);
+        camera.set_yaw(self.yaw);
+        camera.set_pitch(self.pitch);
+    }
+}
+
+/// Returns default name of a bookmark saved in given slot.
+pub fn default_bookmark_name(slot: u32) -> String {
+    format!("bookmark {}", slot)
+}
+
+/// Returns bookmark slot assigned to a number key.
+pub fn slot_from_key(keycode: VirtualKeyCode) -> Option<u32> {
+    match keycode {
+        VirtualKeyCode::Key1 => Some(1),
+        VirtualKeyCode::Key2 => Some(2),
+        VirtualKeyCode::Key3 => Some(3),
+        VirtualKeyCode::Key4 => Some(4),
+        VirtualKeyCode::Key5 => Some(5),
+        VirtualKeyCode::Key6 => Some(6),
+        VirtualKeyCode::Key7 => Some(7),
+        VirtualKeyCode::Key8 => Some(8),
+        VirtualKeyCode::Key9 => Some(9),
+        _ => None,
+    }
+}
+
+/// Actions from number keys and the editor Bookmarks panel.
+pub enum BookmarkAction {
+    Save(u32),
+    Recall(u32),
+    Rename(u32, String),
+}
+
+impl Editor {
+    /// Executes bookmark action. Bookmarks are saved with the editor config.
+    pub(crate) fn execute_bookmark_action(&mut self, bookmark_action: BookmarkAction, asset_manager: &mut AssetManager) {
+        match bookmark_action {
+            BookmarkAction::Save(slot) => {
+                let camera = match asset_manager.active_scene() {
+                    Some(scene) => scene.camera(),
+                    None => return,
+                };
+                // name typed into the Bookmarks panel is used, so that bookmark can be named before it's saved
+                let name = self.current_editor_state.bookmark_name_text_box_contents.get(slot as usize - 1)
+                                                                                    .filter(|name| !name.is_empty())
+                                                                                    .cloned()
+                                                                                    .unwrap_or_else(|| default_bookmark_name(slot));
+                self.config.camera_bookmarks.insert(slot, CameraBookmark::from_camera(name, camera));
+                self.config_changed = true;
+                self.sync_editor(asset_manager);
+            },
+            BookmarkAction::Recall(slot) => {
+                if let (Some(bookmark), Some(scene)) = (self.config.camera_bookmarks.get(&slot), asset_manager.active_scene_mut()) {
+                    bookmark.apply(scene.camera_mut());
+                }
+            },
+            BookmarkAction::Rename(slot, name) => {
+                if let Some(bookmark) = self.config.camera_bookmarks.get_mut(&slot) {
+                    bookmark.name = name;
+                    self.config_changed = true;
+                }
+                self.sync_editor(asset_manager);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applied_bookmark_restores_camera_orientation() {
+        let mut camera = Camera::new();
+        camera.set_position(1.0, 2.0, 3.0);
+        camera.set_yaw(30.0);
+        camera.set_pitch(-20.0);
+        let bookmark = CameraBookmark::from_camera("test", &camera);
+
+        let mut restored_camera = Camera::new();
+        bookmark.apply(&mut restored_camera);
+
+        assert_eq!(camera.position(), restored_camera.position());
+        assert_eq!(camera.front(), restored_camera.front());
+    }
+}
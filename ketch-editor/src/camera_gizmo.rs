@@ -0,0 +1,29 @@
+use nalgebra_glm::Vec3;
+
+use ketch_core::renderer::debug_draw::DebugDraw;
+use ketch_core::resource::camera::Camera;
+
+/// Distance at which drawn frustum is cut, so that far plane of the camera doesn't fill the whole viewport.
+const FRUSTUM_GIZMO_DISTANCE: f32 = 5.0;
+const FRUSTUM_COLOR: (f32, f32, f32) = (0.9, 0.9, 0.2);
+/// Height of the triangle marking the top of the frustum, relative to frustum height.
+const UP_MARKER_SIZE: f32 = 0.2;
+
+/// Draws frustum of a game camera, with a triangle above it showing which side is up.
+pub fn draw_camera_gizmo(debug_draw: &mut DebugDraw, camera: &Camera, aspect_ratio: f32) {
+    let (r, g, b) = FRUSTUM_COLOR;
+    let color = Vec3::new(r, g, b);
+    let corners = camera.frustum_corners(aspect_ratio, FRUSTUM_GIZMO_DISTANCE);
+
+    for i in 0..4 {
+        let next = (i + 1) % 4;
+        debug_draw.line(corners[i], corners[next], color);
+        debug_draw.line(corners[4 + i], corners[4 + next], color);
+        debug_draw.line(corners[i], corners[4 + i], color);
+    }
+
+    let (far_top_right, far_top_left, far_bottom_left) = (corners[6], corners[7], corners[4]);
+    let up_marker_tip = (far_top_left + far_top_right) * 0.5 + (far_top_left - far_bottom_left) * UP_MARKER_SIZE;
+    debug_draw.line(far_top_left, up_marker_tip, color);
+    debug_draw.line(up_marker_tip, far_top_right, color);
+}
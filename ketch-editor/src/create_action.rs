@@ -0,0 +1,101 @@
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::camera::Camera;
+use ketch_core::resource::light::Light;
+use ketch_core::resource::object::ObjectBuilder;
+use ketch_core::resource::primitives::Primitive;
+
+use crate::editor_event::EditorEvent;
+use crate::Editor;
+
+use log::*;
+
+/// Distance from the camera at which new objects are created.
+const SPAWN_DISTANCE: f32 = 5.0;
+
+/// Actions from the editor Create menu.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CreateAction {
+    Cube,
+    Sphere,
+    Plane,
+    Empty,
+    Camera,
+    Light,
+}
+
+impl CreateAction {
+    pub const ALL: [CreateAction; 6] = [
+        CreateAction::Cube,
+        CreateAction::Sphere,
+        CreateAction::Plane,
+        CreateAction::Empty,
+        CreateAction::Camera,
+        CreateAction::Light,
+    ];
+
+    /// Returns label shown in the Create menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            CreateAction::Cube => "Cube",
+            CreateAction::Sphere => "Sphere",
+            CreateAction::Plane => "Plane",
+            CreateAction::Empty => "Empty",
+            CreateAction::Camera => "Camera",
+            CreateAction::Light => "Light",
+        }
+    }
+
+    fn primitive(self) -> Option<Primitive> {
+        match self {
+            CreateAction::Cube => Some(Primitive::Cube),
+            CreateAction::Sphere => Some(Primitive::Sphere),
+            CreateAction::Plane => Some(Primitive::Plane),
+            _ => None,
+        }
+    }
+}
+
+impl Editor {
+    /// Creates object or light in front of the editor camera and selects it. Creation can be undone.
+    pub(crate) fn execute_create_action(&mut self, create_action: CreateAction, asset_manager: &mut AssetManager) {
+        let (focus_point, object_count, light_count) = match asset_manager.active_scene() {
+            Some(scene) => {
+                let camera = scene.camera();
+                (camera.position_vec3() + camera.front() * SPAWN_DISTANCE, scene.objects().len(), scene.lights().len())
+            },
+            None => {
+                error!("There is no active scene to create {} in", create_action.label());
+                return;
+            },
+        };
+        let (x, y, z) = (focus_point.x, focus_point.y, focus_point.z);
+
+        if create_action == CreateAction::Light {
+            let mut light = Light::new(format!("light {}", light_count + 1));
+            light.set_position(x, y, z);
+            self.selected_light = Some(light.id());
+            self.pending_editor_events.push(EditorEvent::LightAdded(light));
+            return;
+        }
+
+        let object_name = format!("{} {}", create_action.label().to_lowercase(), object_count + 1);
+        let mut object_builder = ObjectBuilder::new(object_name).with_position(x, y, z);
+        if let Some(primitive) = create_action.primitive() {
+            match asset_manager.primitive_mesh(primitive) {
+                Ok(mesh) => object_builder = object_builder.with_mesh(mesh),
+                Err(err) => {
+                    error!("Couldn't create mesh of {}: {}", create_action.label(), err);
+                    return;
+                },
+            }
+        }
+        let mut object = object_builder.build();
+        if create_action == CreateAction::Camera {
+            let mut camera = Camera::new();
+            camera.set_position(x, y, z);
+            object.set_camera(Some(camera));
+        }
+        self.selection.select(object.id());
+        self.pending_editor_events.push(EditorEvent::ObjectAdded(object));
+    }
+}
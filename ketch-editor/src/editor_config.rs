@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::editor_error::EditorConfigError;
+use crate::editor_settings::EditorSettings;
+use crate::camera_bookmark::CameraBookmark;
+use crate::layout::EditorLayout;
+
+/// Editor configuration which is saved between editor sessions.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct EditorConfig {
+    pub layout: EditorLayout,
+    pub settings: EditorSettings,
+    /// Camera bookmarks by slot number.
+    pub camera_bookmarks: BTreeMap<u32, CameraBookmark>,
+}
+
+impl EditorConfig {
+    /// Loads editor configuration from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, EditorConfigError> {
+        let serialized_config = std::fs::read_to_string(path)?;
+        Ok(ron::de::from_str(&serialized_config)?)
+    }
+
+    /// Saves editor configuration to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), EditorConfigError> {
+        let serialized_config = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, serialized_config)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{DockSide, Panel};
+
+    #[test]
+    fn editor_config_survives_serialization() {
+        let mut config = EditorConfig::default();
+        config.layout.panel_mut(Panel::Hierarchy).dock = DockSide::Bottom;
+        config.layout.panel_mut(Panel::File).visible = false;
+
+        let serialized = ron::ser::to_string(&config).unwrap();
+        let deserialized: EditorConfig = ron::de::from_str(&serialized).unwrap();
+
+        assert_eq!(config, deserialized);
+    }
+}
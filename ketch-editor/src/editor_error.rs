@@ -2,6 +2,7 @@ use std::error::Error;
 
 use conrod_core::text::font;
 use conrod_vulkano::RendererCreationError;
+use ketch_core::renderer::renderer_error::RendererCreationError as MaterialPreviewCreationError;
 
 use quick_error::quick_error; 
 
@@ -21,5 +22,31 @@ quick_error! {
             display(x) -> ("{}: {}", x.description(), err)
             cause(err)
         }
+        MaterialPreviewCreationError(err: MaterialPreviewCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum EditorConfigError {
+        IoError(err: std::io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        SerializeError(err: ron::ser::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        DeserializeError(err: ron::de::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
     }
 }
@@ -1,27 +1,240 @@
-use crate::editor_event::EditorEvent::LightPositionChanged;
+use ketch_core::resource::object::Object;
+use ketch_core::resource::light::Light;
 use ketch_core::resource::AssetManager;
-use crate::Editor;
-use conrod_core::widget::id::Id;
+use ketch_core::resource::material::Material;
+use ketch_core::resource::particle_effect::ParticleEmitter;
+use ketch_core::resource::scene::Scene;
+use ketch_core::resource::terrain::Terrain;
 
+use std::sync::{Arc, RwLock};
+
+use log::*;
+
+/// Position, rotation angles and scale of an object.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ObjectTransform {
+    pub position: (f32, f32, f32),
+    pub rotation_angles: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl ObjectTransform {
+    /// Reads transform of given object.
+    pub fn from_object(object: &Object) -> Self {
+        ObjectTransform {
+            position: object.position(),
+            rotation_angles: object.rotation_angles(),
+            scale: object.scale(),
+        }
+    }
+
+    /// Applies this transform to given object.
+    pub fn apply(&self, object: &mut Object) {
+        let (x, y, z) = self.position;
+        object.set_position(x, y, z);
+        let (x, y, z) = self.rotation_angles;
+        object.set_rotation_angles(x, y, z);
+        let (x, y, z) = self.scale;
+        object.set_scale_xyz(x, y, z);
+    }
+}
+
+/// Editable properties of a light.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LightProperties {
+    pub position: (f32, f32, f32),
+    pub color: (f32, f32, f32),
+    pub intensity: f32,
+    pub range: f32,
+}
+
+impl LightProperties {
+    /// Reads properties of given light.
+    pub fn from_light(light: &Light) -> Self {
+        LightProperties {
+            position: light.position(),
+            color: light.color(),
+            intensity: light.intensity(),
+            range: light.range(),
+        }
+    }
+
+    /// Applies these properties to given light.
+    pub fn apply(&self, light: &mut Light) {
+        let (x, y, z) = self.position;
+        light.set_position(x, y, z);
+        let (r, g, b) = self.color;
+        light.set_color(r, g, b);
+        light.set_intensity(self.intensity);
+        light.set_range(self.range);
+    }
+}
+
+/// Operations performed by the editor on the game state.
+/// Every executed event returns its inverse, which is used to undo it.
 pub enum EditorEvent {
-    LightPositionChanged((f32, f32, f32)),
+    LightChanged(u32, LightProperties),
+    LightAdded(Light),
+    LightRemoved(u32),
+    ObjectTransformChanged(u32, ObjectTransform),
+    ObjectNameChanged(u32, String),
+    ObjectAdded(Object),
+    /// Puts removed object back at its former index in the object list of the scene.
+    ObjectRestored(usize, Object),
+    ObjectRemoved(u32),
+    ObjectParentChanged(u32, Option<u32>),
+    ObjectMaterialChanged(u32, Option<Arc<RwLock<Material>>>),
+    ObjectParticleEmitterChanged(u32, Option<ParticleEmitter>),
+    /// Replaces properties of the material with the same name.
+    MaterialChanged(Material),
+    /// Replaces heights of the terrain with the same name.
+    TerrainChanged(Terrain),
+    /// Events executed together, which are undone as a single operation.
+    Group(Vec<EditorEvent>),
 }
 
 impl EditorEvent {
-    pub fn execute(self, asset_manager: &mut AssetManager) {
+    /// Executes event and returns event which reverts it. Returns None if event couldn't be executed.
+    pub fn execute(self, asset_manager: &mut AssetManager) -> Option<EditorEvent> {
         match self {
-            LightPositionChanged((x, y, z)) => EditorEvent::handle_light_position_changed(x, y, z, asset_manager),
+            EditorEvent::MaterialChanged(material) => EditorEvent::handle_material_changed(material, asset_manager),
+            EditorEvent::TerrainChanged(terrain) => EditorEvent::handle_terrain_changed(terrain, asset_manager),
+            EditorEvent::Group(events) => EditorEvent::handle_group(events, |event| event.execute(asset_manager)),
+            event => event.execute_in_scene(asset_manager.active_scene_mut()?),
         }
     }
 
-    fn handle_light_position_changed(x: f32, y: f32, z: f32, asset_manager: &mut AssetManager) {
-        if let Some(scene) = asset_manager.active_scene_mut() { 
-            scene.set_light_position(x, y, z);
+    /// Executes event on given scene and returns event which reverts it.
+    /// Returns None for events changing assets outside of the scene, like materials and terrains.
+    pub fn execute_in_scene(self, scene: &mut Scene) -> Option<EditorEvent> {
+        match self {
+            EditorEvent::LightChanged(id, properties) => EditorEvent::handle_light_changed(id, properties, scene),
+            EditorEvent::LightAdded(light) => EditorEvent::handle_light_added(light, scene),
+            EditorEvent::LightRemoved(id) => EditorEvent::handle_light_removed(id, scene),
+            EditorEvent::ObjectTransformChanged(id, transform) => EditorEvent::handle_object_transform_changed(id, transform, scene),
+            EditorEvent::ObjectNameChanged(id, name) => EditorEvent::handle_object_name_changed(id, name, scene),
+            EditorEvent::ObjectAdded(object) => EditorEvent::handle_object_added(object, scene),
+            EditorEvent::ObjectRestored(index, object) => EditorEvent::handle_object_restored(index, object, scene),
+            EditorEvent::ObjectRemoved(id) => EditorEvent::handle_object_removed(id, scene),
+            EditorEvent::ObjectParentChanged(id, parent) => EditorEvent::handle_object_parent_changed(id, parent, scene),
+            EditorEvent::ObjectMaterialChanged(id, material) => EditorEvent::handle_object_material_changed(id, material, scene),
+            EditorEvent::ObjectParticleEmitterChanged(id, particle_emitter) => EditorEvent::handle_object_particle_emitter_changed(id, particle_emitter, scene),
+            EditorEvent::MaterialChanged(_) | EditorEvent::TerrainChanged(_) => None,
+            EditorEvent::Group(events) => EditorEvent::handle_group(events, |event| event.execute_in_scene(scene)),
+        }
+    }
+
+    /// Returns true if both events change the same light or material. History keeps only the first of such
+    /// consecutive events, so that dragging a slider is undone as a single operation.
+    pub fn changes_same_target(&self, other: &EditorEvent) -> bool {
+        match (self, other) {
+            (EditorEvent::LightChanged(id, _), EditorEvent::LightChanged(other_id, _)) => id == other_id,
+            (EditorEvent::MaterialChanged(material), EditorEvent::MaterialChanged(other_material)) => material.name() == other_material.name(),
+            _ => false,
+        }
+    }
+
+    fn handle_light_changed(id: u32, properties: LightProperties, scene: &mut Scene) -> Option<EditorEvent> {
+        let is_main_light = scene.main_light()?.id() == id;
+        let light = scene.light_mut(id)?;
+        let previous_properties = LightProperties::from_light(light);
+        properties.apply(light);
+        if is_main_light {
+            let (x, y, z) = properties.position;
             if let Some(light_object) = scene.objects_mut().iter_mut().find(|x| x.light_source()) {
                 light_object.set_position(x, y, z);
             }
         }
+        Some(EditorEvent::LightChanged(id, previous_properties))
+    }
+
+    fn handle_light_added(light: Light, scene: &mut Scene) -> Option<EditorEvent> {
+        let id = light.id();
+        scene.add_light(light);
+        Some(EditorEvent::LightRemoved(id))
+    }
+
+    fn handle_light_removed(id: u32, scene: &mut Scene) -> Option<EditorEvent> {
+        let light = scene.remove_light(id)?;
+        Some(EditorEvent::LightAdded(light))
+    }
+
+    fn handle_object_transform_changed(id: u32, transform: ObjectTransform, scene: &mut Scene) -> Option<EditorEvent> {
+        let object = scene.objects_mut().iter_mut().find(|x| x.id() == id)?;
+        let previous_transform = ObjectTransform::from_object(object);
+        transform.apply(object);
+        Some(EditorEvent::ObjectTransformChanged(id, previous_transform))
+    }
+
+    fn handle_object_name_changed(id: u32, name: String, scene: &mut Scene) -> Option<EditorEvent> {
+        let object = scene.objects_mut().iter_mut().find(|x| x.id() == id)?;
+        let previous_name = object.name().to_string();
+        object.set_name(name);
+        Some(EditorEvent::ObjectNameChanged(id, previous_name))
     }
-}
 
+    fn handle_object_added(object: Object, scene: &mut Scene) -> Option<EditorEvent> {
+        let id = object.id();
+        scene.add_object(object);
+        Some(EditorEvent::ObjectRemoved(id))
+    }
+
+    fn handle_object_restored(index: usize, object: Object, scene: &mut Scene) -> Option<EditorEvent> {
+        let id = object.id();
+        scene.insert_object(index, object);
+        Some(EditorEvent::ObjectRemoved(id))
+    }
 
+    fn handle_object_removed(id: u32, scene: &mut Scene) -> Option<EditorEvent> {
+        let index = scene.object_index(id)?;
+        let object = scene.remove_object(id)?;
+        Some(EditorEvent::ObjectRestored(index, object))
+    }
+
+    fn handle_object_parent_changed(id: u32, parent: Option<u32>, scene: &mut Scene) -> Option<EditorEvent> {
+        let previous_parent = scene.object(id)?.parent();
+        if !scene.set_parent(id, parent) {
+            return None;
+        }
+        Some(EditorEvent::ObjectParentChanged(id, previous_parent))
+    }
+
+    fn handle_object_material_changed(id: u32, material: Option<Arc<RwLock<Material>>>, scene: &mut Scene) -> Option<EditorEvent> {
+        let object = scene.object_mut(id)?;
+        let previous_material = object.material();
+        object.set_material(material);
+        Some(EditorEvent::ObjectMaterialChanged(id, previous_material))
+    }
+
+    fn handle_object_particle_emitter_changed(id: u32, particle_emitter: Option<ParticleEmitter>, scene: &mut Scene) -> Option<EditorEvent> {
+        let object = scene.object_mut(id)?;
+        let previous_particle_emitter = object.particle_emitter().cloned();
+        object.set_particle_emitter(particle_emitter);
+        Some(EditorEvent::ObjectParticleEmitterChanged(id, previous_particle_emitter))
+    }
+
+    fn handle_material_changed(material: Material, asset_manager: &mut AssetManager) -> Option<EditorEvent> {
+        let previous_material = std::mem::replace(&mut *asset_manager.material(material.name())?.write().unwrap(), material);
+        Some(EditorEvent::MaterialChanged(previous_material))
+    }
+
+    fn handle_terrain_changed(terrain: Terrain, asset_manager: &mut AssetManager) -> Option<EditorEvent> {
+        let name = terrain.name().to_string();
+        let previous_terrain = std::mem::replace(&mut *asset_manager.terrain(&name)?.write().unwrap(), terrain);
+        if let Err(err) = asset_manager.update_terrain_mesh(&name) {
+            error!("Couldn't update mesh of terrain {}: {}", name, err);
+        }
+        Some(EditorEvent::TerrainChanged(previous_terrain))
+    }
+
+    fn handle_group<F: FnMut(EditorEvent) -> Option<EditorEvent>>(events: Vec<EditorEvent>, execute: F) -> Option<EditorEvent> {
+        let mut inverse_events: Vec<EditorEvent> = events.into_iter()
+                                                         .filter_map(execute)
+                                                         .collect();
+        if inverse_events.is_empty() {
+            return None;
+        }
+        inverse_events.reverse();
+        Some(EditorEvent::Group(inverse_events))
+    }
+}
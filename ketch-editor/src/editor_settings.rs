@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::theme::EditorTheme;
+
+const UI_SCALE_STEP: f32 = 0.25;
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 3.0;
+
+/// Settings of editor helpers.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct EditorSettings {
+    pub show_grid: bool,
+    pub grid_spacing: f32,
+    pub grid_major_line_every: u32,
+    pub grid_fade_distance: f32,
+    /// If true, dragging snaps to increments unless snap modifier is held. Otherwise snapping happens only while it's held.
+    pub snap_by_default: bool,
+    pub translation_snap: f32,
+    /// Rotation increment in degrees.
+    pub rotation_snap: f32,
+    pub scale_snap: f32,
+    /// Scale of the GUI applied on top of window DPI.
+    pub ui_scale: f32,
+    pub theme: EditorTheme,
+}
+
+impl EditorSettings {
+    pub fn new() -> Self {
+        EditorSettings {
+            show_grid: true,
+            grid_spacing: 1.0,
+            grid_major_line_every: 10,
+            grid_fade_distance: 50.0,
+            snap_by_default: false,
+            translation_snap: 0.5,
+            rotation_snap: 15.0,
+            scale_snap: 0.1,
+            ui_scale: 1.0,
+            theme: EditorTheme::Dark,
+        }
+    }
+
+    /// Makes GUI bigger or smaller by one step.
+    pub fn change_ui_scale(&mut self, grow: bool) {
+        let step = if grow { UI_SCALE_STEP } else { -UI_SCALE_STEP };
+        self.ui_scale = (self.ui_scale + step).max(MIN_UI_SCALE).min(MAX_UI_SCALE);
+    }
+
+    /// Returns true if dragged values should be snapped.
+    pub fn snapping_active(&self, snap_modifier_pressed: bool) -> bool {
+        self.snap_by_default != snap_modifier_pressed
+    }
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        EditorSettings::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ui_scale_is_clamped() {
+        let mut settings = EditorSettings::new();
+        for _ in 0..20 {
+            settings.change_ui_scale(false);
+        }
+        assert_eq!(MIN_UI_SCALE, settings.ui_scale);
+
+        for _ in 0..20 {
+            settings.change_ui_scale(true);
+        }
+        assert_eq!(MAX_UI_SCALE, settings.ui_scale);
+    }
+}
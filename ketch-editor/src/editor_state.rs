@@ -1,20 +1,186 @@
 use ketch_core::settings::Settings;
+use ketch_core::resource::object::Object;
+use ketch_core::resource::light::Light;
+use ketch_core::resource::material::Material;
+use ketch_core::resource::particle_effect::ParticleEffect;
+use ketch_core::input::input_event::ElementState::{self, Pressed, Released};
+use ketch_core::input::input_event::VirtualKeyCode;
+
+use crate::editor_event::{LightProperties, ObjectTransform};
+use crate::selection_action::SelectionAction;
+
+/// Object data shown in the editor panels.
+#[derive(Clone)]
+pub struct ObjectSummary {
+    pub id: u32,
+    pub name: String,
+    pub transform: ObjectTransform,
+    pub parent: Option<u32>,
+    /// True if a game camera is attached to the object.
+    pub has_camera: bool,
+}
+
+impl ObjectSummary {
+    pub fn from_object(object: &Object) -> Self {
+        ObjectSummary {
+            id: object.id(),
+            name: object.name().to_string(),
+            transform: ObjectTransform::from_object(object),
+            parent: object.parent(),
+            has_camera: object.camera().is_some(),
+        }
+    }
+}
+
+/// Light data shown in the editor panels.
+#[derive(Clone)]
+pub struct LightSummary {
+    pub id: u32,
+    pub name: String,
+    pub properties: LightProperties,
+}
+
+impl LightSummary {
+    pub fn from_light(light: &Light) -> Self {
+        LightSummary {
+            id: light.id(),
+            name: light.name().to_string(),
+            properties: LightProperties::from_light(light),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct EditorState {
-    pub run_game: bool,
+    pub lights: Vec<LightSummary>,
     pub x_light_text_box_content: String,
     pub y_light_text_box_content: String,
     pub z_light_text_box_content: String,
+    pub r_light_color_text_box_content: String,
+    pub g_light_color_text_box_content: String,
+    pub b_light_color_text_box_content: String,
+    pub light_intensity_text_box_content: String,
+    pub light_range_text_box_content: String,
+    pub scene_path_text_box_content: String,
+    pub show_exit_prompt: bool,
+    /// Why the editor couldn't exit after user chose to save, shown in the exit prompt.
+    pub exit_prompt_error: Option<String>,
+    pub show_view_panel: bool,
+    pub show_create_menu: bool,
+    pub objects: Vec<ObjectSummary>,
+    pub object_name_text_box_content: String,
+    pub x_object_position_text_box_content: String,
+    pub y_object_position_text_box_content: String,
+    pub z_object_position_text_box_content: String,
+    pub material_names: Vec<String>,
+    pub texture_names: Vec<String>,
+    pub mesh_names: Vec<String>,
+    pub edited_material: Option<Material>,
+    pub r_material_color_text_box_content: String,
+    pub g_material_color_text_box_content: String,
+    pub b_material_color_text_box_content: String,
+    pub a_material_color_text_box_content: String,
+    pub particle_effect_names: Vec<String>,
+    pub edited_particle_effect: Option<ParticleEffect>,
+    pub terrain_names: Vec<String>,
+    pub edited_terrain: Option<String>,
+    /// Names of camera bookmarks, empty for unused slots.
+    pub bookmark_name_text_box_contents: Vec<String>,
 }
 
 impl EditorState {
     pub fn new() -> Self {
         EditorState {
-            run_game: false,
+            lights: Vec::new(),
             x_light_text_box_content: String::from("0.0"),
             y_light_text_box_content: String::from("0.0"),
             z_light_text_box_content: String::from("0.0"),
+            r_light_color_text_box_content: String::from("1.0"),
+            g_light_color_text_box_content: String::from("1.0"),
+            b_light_color_text_box_content: String::from("1.0"),
+            light_intensity_text_box_content: String::from("1.0"),
+            light_range_text_box_content: String::from("50.0"),
+            scene_path_text_box_content: String::from("scene.ron"),
+            show_exit_prompt: false,
+            exit_prompt_error: None,
+            show_view_panel: false,
+            show_create_menu: false,
+            objects: Vec::new(),
+            object_name_text_box_content: String::new(),
+            x_object_position_text_box_content: String::from("0.0"),
+            y_object_position_text_box_content: String::from("0.0"),
+            z_object_position_text_box_content: String::from("0.0"),
+            material_names: Vec::new(),
+            texture_names: Vec::new(),
+            mesh_names: Vec::new(),
+            edited_material: None,
+            r_material_color_text_box_content: String::from("1.0"),
+            g_material_color_text_box_content: String::from("1.0"),
+            b_material_color_text_box_content: String::from("1.0"),
+            a_material_color_text_box_content: String::from("1.0"),
+            particle_effect_names: Vec::new(),
+            edited_particle_effect: None,
+            terrain_names: Vec::new(),
+            edited_terrain: None,
+            bookmark_name_text_box_contents: Vec::new(),
+        }
+    }
+
+    /// Fills light panel text boxes with data of light with given id.
+    pub fn fill_light_text_boxes(&mut self, id: u32) {
+        if let Some(light) = self.lights.iter().find(|x| x.id == id) {
+            let properties = light.properties;
+            let (x, y, z) = properties.position;
+            let (r, g, b) = properties.color;
+            self.x_light_text_box_content = x.to_string();
+            self.y_light_text_box_content = y.to_string();
+            self.z_light_text_box_content = z.to_string();
+            self.r_light_color_text_box_content = r.to_string();
+            self.g_light_color_text_box_content = g.to_string();
+            self.b_light_color_text_box_content = b.to_string();
+            self.light_intensity_text_box_content = properties.intensity.to_string();
+            self.light_range_text_box_content = properties.range.to_string();
+        }
+    }
+
+    /// Fills material panel text boxes with base color of the edited material.
+    pub fn fill_material_text_boxes(&mut self) {
+        if let Some(material) = &self.edited_material {
+            let (r, g, b, a) = material.base_color();
+            self.r_material_color_text_box_content = r.to_string();
+            self.g_material_color_text_box_content = g.to_string();
+            self.b_material_color_text_box_content = b.to_string();
+            self.a_material_color_text_box_content = a.to_string();
+        }
+    }
+
+    /// Returns true if `ancestor` is a parent of the object, or a parent of one of its parents.
+    pub fn is_ancestor(&self, ancestor: u32, id: u32) -> bool {
+        let parent_of = |id: u32| self.objects.iter().find(|x| x.id == id).and_then(|x| x.parent);
+        let mut parent_id = parent_of(id);
+        for _ in 0..self.objects.len() {
+            match parent_id {
+                Some(parent) if parent == ancestor => return true,
+                Some(parent) => parent_id = parent_of(parent),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Returns number of ancestors of the object, used to indent it in the hierarchy.
+    pub fn object_depth(&self, id: u32) -> usize {
+        self.objects.iter().filter(|x| self.is_ancestor(x.id, id)).count()
+    }
+
+    /// Fills inspector text boxes with data of object with given id.
+    pub fn fill_object_text_boxes(&mut self, id: u32) {
+        if let Some(object) = self.objects.iter().find(|x| x.id == id) {
+            let (x, y, z) = object.transform.position;
+            self.object_name_text_box_content = object.name.clone();
+            self.x_object_position_text_box_content = x.to_string();
+            self.y_object_position_text_box_content = y.to_string();
+            self.z_object_position_text_box_content = z.to_string();
         }
     }
 }
@@ -29,6 +195,13 @@ pub struct EditorInputState {
     pub down: bool,
     pub left: bool,
     pub right: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub undo_requested: bool,
+    pub redo_requested: bool,
+    pub cursor_position: (f32, f32),
+    pub pick_requested: bool,
+    pub left_mouse_button_pressed: bool,
 }
 
 impl EditorInputState {
@@ -43,6 +216,78 @@ impl EditorInputState {
             down: false,
             left: false,
             right: false,
+            ctrl: false,
+            shift: false,
+            undo_requested: false,
+            redo_requested: false,
+            cursor_position: (0.0, 0.0),
+            pick_requested: false,
+            left_mouse_button_pressed: false,
+        }
+    }
+
+    /// Updates held movement and modifier keys, and handles ctrl shortcuts for undo, redo and duplicating the selection.
+    /// Returns false if the key isn't used by any of them.
+    pub fn handle_key(&mut self, keycode: VirtualKeyCode, state: ElementState, pending_selection_actions: &mut Vec<SelectionAction>) -> bool {
+        match keycode {
+            VirtualKeyCode::Z if state == Pressed && self.ctrl => self.undo_requested = true,
+            VirtualKeyCode::Y if state == Pressed && self.ctrl => self.redo_requested = true,
+            VirtualKeyCode::D if state == Pressed && self.ctrl => pending_selection_actions.push(SelectionAction::Duplicate),
+            VirtualKeyCode::LControl | VirtualKeyCode::RControl => self.ctrl = state == Pressed,
+            VirtualKeyCode::LShift | VirtualKeyCode::RShift => self.shift = state == Pressed,
+            // keys pressed with ctrl are shortcuts, so they don't start moving the camera, but releasing them still stops it
+            _ if state == Pressed && self.ctrl => return false,
+            VirtualKeyCode::W if state == Pressed => self.up = true,
+            VirtualKeyCode::S if state == Pressed => self.down = true,
+            VirtualKeyCode::A if state == Pressed => self.left = true,
+            VirtualKeyCode::D if state == Pressed => self.right = true,
+            VirtualKeyCode::W if state == Released => self.up = false,
+            VirtualKeyCode::S if state == Released => self.down = false,
+            VirtualKeyCode::A if state == Released => self.left = false,
+            VirtualKeyCode::D if state == Released => self.right = false,
+            _ => return false,
         }
+        true
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctrl_d_duplicates_selection_instead_of_moving_camera() {
+        let mut input_state = EditorInputState::new();
+        let mut pending_selection_actions = Vec::new();
+
+        input_state.handle_key(VirtualKeyCode::LControl, Pressed, &mut pending_selection_actions);
+        input_state.handle_key(VirtualKeyCode::D, Pressed, &mut pending_selection_actions);
+
+        assert_eq!(vec![SelectionAction::Duplicate], pending_selection_actions);
+        assert!(!input_state.right);
+    }
+
+    #[test]
+    fn ctrl_s_does_not_move_camera() {
+        let mut input_state = EditorInputState::new();
+        let mut pending_selection_actions = Vec::new();
+
+        input_state.handle_key(VirtualKeyCode::LControl, Pressed, &mut pending_selection_actions);
+        let handled = input_state.handle_key(VirtualKeyCode::S, Pressed, &mut pending_selection_actions);
+
+        assert!(!handled);
+        assert!(!input_state.down);
+        assert!(pending_selection_actions.is_empty());
+    }
+
+    #[test]
+    fn d_without_ctrl_moves_camera() {
+        let mut input_state = EditorInputState::new();
+        let mut pending_selection_actions = Vec::new();
+
+        input_state.handle_key(VirtualKeyCode::D, Pressed, &mut pending_selection_actions);
+
+        assert!(pending_selection_actions.is_empty());
+        assert!(input_state.right);
+    }
+}
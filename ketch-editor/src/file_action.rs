@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::camera::Camera;
+use ketch_core::resource::scene::Scene;
+
+use crate::Editor;
+
+use log::*;
+
+const NEW_SCENE_NAME: &str = "untitled";
+const NOT_SAVED_BEFORE_ERROR: &str = "Scene wasn't saved before. Enter a path in the File panel and use Save As.";
+const SAVE_FAILED_ERROR: &str = "Scene couldn't be saved, see the log for details.";
+
+/// Actions from the editor File panel.
+pub enum FileAction {
+    New,
+    Open(PathBuf),
+    Save,
+    SaveAs(PathBuf),
+    Exit { save: bool },
+}
+
+impl Editor {
+    /// Executes file action, changing the active scene or saving it.
+    pub(crate) fn execute_file_action(&mut self, file_action: FileAction, asset_manager: &mut AssetManager) {
+        match file_action {
+            FileAction::New => {
+                asset_manager.set_active_scene(Scene::new(NEW_SCENE_NAME, Camera::new()));
+                self.scene_path = None;
+                self.scene_changed(asset_manager);
+            },
+            FileAction::Open(path) => match asset_manager.load_scene(&path) {
+                Ok(scene) => {
+                    info!("Loaded scene from {}", path.display());
+                    asset_manager.set_active_scene(scene);
+                    self.scene_path = Some(path);
+                    self.scene_changed(asset_manager);
+                },
+                Err(err) => error!("Couldn't load scene from {}: {}", path.display(), err),
+            },
+            FileAction::Save => match self.scene_path.clone() {
+                Some(path) => self.save_scene(path, asset_manager),
+                None => error!("Scene wasn't saved before, use Save As to choose a file"),
+            },
+            FileAction::SaveAs(path) => self.save_scene(path, asset_manager),
+            FileAction::Exit { save } => {
+                if save {
+                    self.execute_file_action(FileAction::Save, asset_manager);
+                }
+                match exit_error(save, self.scene_path.is_some(), self.unsaved_changes) {
+                    Some(error) => {
+                        self.current_editor_state.show_exit_prompt = true;
+                        self.current_editor_state.exit_prompt_error = Some(error.to_string());
+                        self.update_gui();
+                    },
+                    None => self.exit_confirmed = true,
+                }
+            },
+        }
+    }
+
+    fn save_scene(&mut self, path: PathBuf, asset_manager: &AssetManager) {
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => {
+                error!("There is no active scene to save");
+                return;
+            },
+        };
+        match scene.save(&path) {
+            Ok(()) => {
+                info!("Saved scene to {}", path.display());
+                self.scene_path = Some(path);
+                self.unsaved_changes = false;
+            },
+            Err(err) => error!("Couldn't save scene to {}: {}", path.display(), err),
+        }
+    }
+
+    fn scene_changed(&mut self, asset_manager: &AssetManager) {
+        self.history.clear();
+        self.light_preview_origin = None;
+        self.dragged_light = None;
+        self.object_drag = None;
+        self.unsaved_changes = false;
+        self.sync_editor(asset_manager);
+    }
+}
+
+/// Returns why the editor can't exit after user chose whether to save changes, so that the exit prompt stays open.
+fn exit_error(save: bool, has_scene_path: bool, unsaved_changes: bool) -> Option<&'static str> {
+    match (save && unsaved_changes, has_scene_path) {
+        (false, _) => None,
+        (true, false) => Some(NOT_SAVED_BEFORE_ERROR),
+        (true, true) => Some(SAVE_FAILED_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_never_saved_scene_on_exit_keeps_prompt_open() {
+        assert_eq!(Some(NOT_SAVED_BEFORE_ERROR), exit_error(true, false, true));
+    }
+
+    #[test]
+    fn exit_is_confirmed_once_changes_are_saved_or_discarded() {
+        assert_eq!(None, exit_error(true, true, false));
+        assert_eq!(None, exit_error(false, false, true));
+    }
+}
@@ -0,0 +1,82 @@
+use nalgebra_glm::Vec3;
+
+use ketch_core::renderer::debug_draw::DebugDraw;
+
+use crate::editor_settings::EditorSettings;
+
+const MINOR_LINE_COLOR: (f32, f32, f32) = (0.25, 0.25, 0.25);
+const MAJOR_LINE_COLOR: (f32, f32, f32) = (0.5, 0.5, 0.5);
+const X_AXIS_COLOR: (f32, f32, f32) = (0.9, 0.2, 0.2);
+const Y_AXIS_COLOR: (f32, f32, f32) = (0.2, 0.9, 0.2);
+const Z_AXIS_COLOR: (f32, f32, f32) = (0.2, 0.2, 0.9);
+
+/// Draws ground grid around the camera with world axes. Grid lines fade out with distance from the camera.
+pub fn draw_grid(debug_draw: &mut DebugDraw, camera_position: Vec3, settings: &EditorSettings) {
+    let spacing = settings.grid_spacing;
+    let fade_distance = settings.grid_fade_distance;
+    if spacing <= 0.0 || fade_distance <= 0.0 {
+        return;
+    }
+
+    let line_count = (fade_distance / spacing).ceil() as i64;
+    let center_x = (camera_position.x / spacing).round() as i64;
+    let center_z = (camera_position.z / spacing).round() as i64;
+
+    for line in (center_x - line_count)..=(center_x + line_count) {
+        let color = line_color(line, settings.grid_major_line_every, Z_AXIS_COLOR);
+        for segment in (center_z - line_count)..(center_z + line_count) {
+            let from = Vec3::new(line as f32 * spacing, 0.0, segment as f32 * spacing);
+            let to = Vec3::new(line as f32 * spacing, 0.0, (segment + 1) as f32 * spacing);
+            faded_line(debug_draw, from, to, color, camera_position, fade_distance);
+        }
+    }
+
+    for line in (center_z - line_count)..=(center_z + line_count) {
+        let color = line_color(line, settings.grid_major_line_every, X_AXIS_COLOR);
+        for segment in (center_x - line_count)..(center_x + line_count) {
+            let from = Vec3::new(segment as f32 * spacing, 0.0, line as f32 * spacing);
+            let to = Vec3::new((segment + 1) as f32 * spacing, 0.0, line as f32 * spacing);
+            faded_line(debug_draw, from, to, color, camera_position, fade_distance);
+        }
+    }
+
+    let (r, g, b) = Y_AXIS_COLOR;
+    debug_draw.line(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, spacing * settings.grid_major_line_every as f32, 0.0), Vec3::new(r, g, b));
+}
+
+fn line_color(line: i64, major_line_every: u32, axis_color: (f32, f32, f32)) -> Vec3 {
+    let (r, g, b) = if line == 0 {
+        axis_color
+    } else if major_line_every > 0 && line % major_line_every as i64 == 0 {
+        MAJOR_LINE_COLOR
+    } else {
+        MINOR_LINE_COLOR
+    };
+    Vec3::new(r, g, b)
+}
+
+fn faded_line(debug_draw: &mut DebugDraw, from: Vec3, to: Vec3, color: Vec3, camera_position: Vec3, fade_distance: f32) {
+    let from_fade = fade(from, camera_position, fade_distance);
+    let to_fade = fade(to, camera_position, fade_distance);
+    if from_fade > 0.0 || to_fade > 0.0 {
+        debug_draw.line_gradient(from, color * from_fade, to, color * to_fade);
+    }
+}
+
+fn fade(point: Vec3, camera_position: Vec3, fade_distance: f32) -> f32 {
+    let distance = ((point.x - camera_position.x).powi(2) + (point.z - camera_position.z).powi(2)).sqrt();
+    (1.0 - distance / fade_distance).max(0.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_is_full_at_camera_and_zero_at_fade_distance() {
+        let camera_position = Vec3::new(0.0, 5.0, 0.0);
+
+        assert_eq!(1.0, fade(Vec3::new(0.0, 0.0, 0.0), camera_position, 10.0));
+        assert_eq!(0.0, fade(Vec3::new(10.0, 0.0, 0.0), camera_position, 10.0));
+    }
+}
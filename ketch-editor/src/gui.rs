@@ -1,7 +1,30 @@
-use crate::editor_event::EditorEvent;
+use crate::editor_event::{EditorEvent, LightProperties};
+use crate::file_action::FileAction;
+use crate::play_action::{PlayAction, PlayState};
+use crate::create_action::CreateAction;
+use crate::selection::Selection;
+use crate::selection_action::SelectionAction;
+use crate::object_drag::TransformMode;
+use crate::material_action::MaterialAction;
+use crate::particle_action::ParticleAction;
+use crate::terrain_action::TerrainAction;
+use crate::terrain_brush::{TerrainBrush, TerrainTool};
+use crate::camera_bookmark::{BookmarkAction, CameraBookmark};
+use crate::asset_preview::{AssetPreviewAction, PreviewedAsset};
+use crate::validation::{ValidationAction, ValidationReport};
+use ketch_core::renderer::asset_preview::{TextureChannel, ASSET_PREVIEW_SIZE};
 use crate::editor_state::EditorState;
 use crate::widget_ids::Ids;
+use crate::layout::{EditorLayout, Panel, PanelPlacement};
+use crate::editor_settings::EditorSettings;
+use crate::theme::EditorTheme;
 use ketch_core::resource::AssetManager;
+use ketch_core::resource::light::Light;
+use ketch_core::resource::material::{BlendMode, CullMode, FrontFace, MAX_EDITOR_EMISSIVE_INTENSITY};
+use ketch_core::resource::particle_effect::{Curve, CurveValue, ParticleEffect};
+use ketch_core::renderer::material_preview::MATERIAL_PREVIEW_SIZE;
+use ketch_core::profiling::{self, ProfileFrame};
+use ketch_core::logging::LogRecord;
 use conrod_core::position::Positionable;
 use conrod_core::widget::Widget;
 use conrod_core::widget::text_box;
@@ -11,92 +34,1498 @@ use conrod_core::position::Dimension;
 use conrod_core::position::Align;
 use conrod_core::position::Relative;
 use conrod_core::position::Place;
-use conrod_core::color;
 use conrod_core::Labelable;
+use conrod_core::Colorable;
 use conrod_core::widget;
+use conrod_core::color;
+use conrod_core::event;
 
 use crate::Editor;
 
 use log::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
 mod gui_event;
 
+/// Number of sliders in the Particles panel.
+pub(crate) const PARTICLE_SLIDER_COUNT: usize = 13;
+/// Most spans drawn in the Profiler panel.
+pub(crate) const PROFILER_MAX_SPANS: usize = 128;
+
 use gui_event::light_text_box_event_execute;
+use gui_event::object_position_text_box_event_execute;
+use gui_event::object_name_text_box_event_execute;
+use gui_event::material_color_text_box_event_execute;
 
 impl Editor {
     pub fn update_gui(&mut self) {
-        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
         let mut ui = self.ui.set_widgets();
+        let (window_width, window_height) = (ui.win_w, ui.win_h);
+        let layout = &self.config.layout;
 
-        run_button(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
-        light_panel(&self.widget_ids, &mut ui, &self.synced_editor_state, &mut self.current_editor_state, &mut self.pending_editor_events);
+        toolbar(&self.widget_ids, &mut ui, self.play_state, &mut self.current_editor_state, &mut self.pending_play_actions);
+        if let Some(placement) = layout.placement(Panel::Light, window_width, window_height) {
+            light_panel(&self.widget_ids, &mut ui, placement, &mut self.selected_light, &self.synced_editor_state, &mut self.current_editor_state,
+                        &mut self.pending_editor_events, &mut self.pending_light_previews);
+        }
+        if let Some(placement) = layout.placement(Panel::File, window_width, window_height) {
+            file_panel(&self.widget_ids, &mut ui, placement, &mut self.current_editor_state, &mut self.pending_file_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Hierarchy, window_width, window_height) {
+            hierarchy_panel(&self.widget_ids, &mut ui, placement, self.config.settings.theme, self.editor_input_state.ctrl, &mut self.current_editor_state, &mut self.selection);
+        }
+        let has_selection = !self.selection.is_empty();
+        if let Some(placement) = layout.placement(Panel::Inspector, window_width, window_height).filter(|_| has_selection) {
+            inspector_panel(&self.widget_ids, &mut ui, placement, &self.selection, self.transform_mode, &self.synced_editor_state, &mut self.current_editor_state,
+                            &mut self.pending_editor_events, &mut self.pending_selection_actions);
+        }
+        if let Some(selection_box_start) = self.selection_box_start {
+            selection_box(&self.widget_ids, &mut ui, &self.config.settings, selection_box_start, self.editor_input_state.cursor_position);
+        }
+        if let Some(placement) = layout.placement(Panel::Statistics, window_width, window_height) {
+            statistics_panel(&self.widget_ids, &mut ui, placement, &self.statistics_text);
+        }
+        if let Some(placement) = layout.placement(Panel::Profiler, window_width, window_height) {
+            profiler_panel(&self.widget_ids, &mut ui, placement, self.config.settings.theme, &self.profile_frame);
+        }
+        if let Some(placement) = layout.placement(Panel::Console, window_width, window_height) {
+            console_panel(&self.widget_ids, &mut ui, placement, &self.log_records);
+        }
+        if let Some(placement) = layout.placement(Panel::Bookmarks, window_width, window_height) {
+            bookmarks_panel(&self.widget_ids, &mut ui, placement, &self.config.camera_bookmarks, &mut self.current_editor_state, &mut self.pending_bookmark_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Assets, window_width, window_height) {
+            assets_panel(&self.widget_ids, &mut ui, placement, &self.current_editor_state, &mut self.pending_asset_preview_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Validation, window_width, window_height) {
+            validation_panel(&self.widget_ids, &mut ui, placement, &self.validation_report, &self.selection, self.config.settings.theme,
+                             &mut self.pending_validation_actions);
+        }
+        let objects = &self.current_editor_state.objects;
+        let previewed_camera = self.selection.primary().and_then(|id| objects.iter().find(|x| x.id == id && x.has_camera));
+        if let Some(camera_object) = previewed_camera {
+            camera_preview_window(&self.widget_ids, &mut ui, self.camera_preview_image, &camera_object.name);
+        }
+        if let Some(previewed_asset) = &self.previewed_asset {
+            asset_preview_window(&self.widget_ids, &mut ui, self.asset_preview_image, self.asset_preview.supports_wireframe(), previewed_asset,
+                                 &mut self.pending_asset_preview_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Material, window_width, window_height) {
+            material_panel(&self.widget_ids, &mut ui, placement, self.material_preview_image, &mut self.current_editor_state, &mut self.pending_material_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Particles, window_width, window_height) {
+            particles_panel(&self.widget_ids, &mut ui, placement, &self.current_editor_state, &mut self.pending_particle_actions);
+        }
+        if let Some(placement) = layout.placement(Panel::Terrain, window_width, window_height) {
+            terrain_panel(&self.widget_ids, &mut ui, placement, &self.current_editor_state, self.terrain_brush, &mut self.pending_terrain_actions);
+        }
+        if self.current_editor_state.show_create_menu {
+            create_menu(&self.widget_ids, &mut ui, &mut self.current_editor_state, &mut self.pending_create_actions);
+        }
+        if self.current_editor_state.show_view_panel {
+            view_panel(&self.widget_ids, &mut ui, &mut self.config.layout, &mut self.config.settings, &mut self.config_changed);
+        }
+        if self.current_editor_state.show_exit_prompt {
+            exit_prompt(&self.widget_ids, &mut ui, &mut self.current_editor_state, &mut self.pending_file_actions);
+        }
     }
 }
 
-fn light_panel(ids: &Ids, ui: &mut conrod_core::UiCell, 
+/// Creates canvas of a panel docked according to the editor layout.
+fn docked_canvas<'a>(ui: &conrod_core::UiCell, placement: PanelPlacement, title: &'a str) -> widget::Canvas<'a> {
+    widget::Canvas::new().floating(true)
+                         .top_left_with_margins_on(ui.window, placement.top, placement.left)
+                         .w_h(placement.width, placement.height)
+                         .title_bar(title)
+}
+
+fn light_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+               placement: PanelPlacement,
+               selected_light: &mut Option<u32>,
                synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
-               pending_editor_events: &mut Vec<EditorEvent>) {
-    const PANEL_TITLE: &str = "Light";
-    const PANEL_WIDTH: f64 = 300.0;
-    const PANEL_HEIGHT: f64 = 150.0;
+               pending_editor_events: &mut Vec<EditorEvent>,
+               pending_light_previews: &mut Vec<(u32, LightProperties)>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const LABEL_WIDTH: f64 = 60.0;
+    const BUTTON_WIDTH: f64 = 65.0;
+    const LIST_HEIGHT: f64 = 25.0;
+
+    docked_canvas(ui, placement, Panel::Light.title()).set(ids.light_panel_canvas, ui);
+
+    let light_names: Vec<&str> = current_editor_state.lights.iter().map(|x| x.name.as_str()).collect();
+    let selected_index = current_editor_state.lights.iter().position(|x| Some(x.id) == *selected_light);
+    let light_list_width = placement.width - 2.0 * MARGIN - 2.0 * (BUTTON_WIDTH + WIDGET_DISTANCE);
+
+    let mut clicked_light = None;
+    for index in widget::DropDownList::new(&light_names, selected_index)
+                                      .top_left_with_margin_on(ids.light_panel_canvas, MARGIN)
+                                      .w_h(light_list_width, LIST_HEIGHT)
+                                      .set(ids.light_list, ui)
+    {
+        clicked_light = Some(current_editor_state.lights[index].id);
+    }
+
+    for _press in widget::Button::new().label("Add")
+                                       .right_from(ids.light_list, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, LIST_HEIGHT)
+                                       .set(ids.add_light_button, ui)
+    {
+        let light_name = format!("light {}", current_editor_state.lights.len() + 1);
+        pending_editor_events.push(EditorEvent::LightAdded(Light::new(light_name)));
+    }
+
+    for _press in widget::Button::new().label("Remove")
+                                       .right_from(ids.add_light_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, LIST_HEIGHT)
+                                       .set(ids.remove_light_button, ui)
+    {
+        if let Some(id) = *selected_light {
+            pending_editor_events.push(EditorEvent::LightRemoved(id));
+        }
+    }
+
+    if let Some(id) = clicked_light {
+        *selected_light = Some(id);
+        current_editor_state.fill_light_text_boxes(id);
+    }
+
+    let selected_light = match *selected_light {
+        Some(selected_light) => selected_light,
+        None => return,
+    };
 
-    const COLUMN_PADDING: f64 = 10.0;
+    widget::Text::new("position:").down_from(ids.light_list, WIDGET_DISTANCE * 2.0)
+                                  .w(LABEL_WIDTH)
+                                  .set(ids.light_position_label, ui);
+
+    let text_boxes = [
+        (ids.x_light_text_box, "x", current_editor_state.x_light_text_box_content.clone()),
+        (ids.y_light_text_box, "y", current_editor_state.y_light_text_box_content.clone()),
+        (ids.z_light_text_box, "z", current_editor_state.z_light_text_box_content.clone()),
+    ];
+    light_text_box_row(ui, ids.light_position_label, &text_boxes,
+                       selected_light, synced_editor_state, current_editor_state, pending_editor_events, pending_light_previews);
+
+    widget::Text::new("color:").down_from(ids.light_position_label, WIDGET_DISTANCE * 2.0)
+                               .w(LABEL_WIDTH)
+                               .set(ids.light_color_label, ui);
+
+    let text_boxes = [
+        (ids.r_light_color_text_box, "r", current_editor_state.r_light_color_text_box_content.clone()),
+        (ids.g_light_color_text_box, "g", current_editor_state.g_light_color_text_box_content.clone()),
+        (ids.b_light_color_text_box, "b", current_editor_state.b_light_color_text_box_content.clone()),
+    ];
+    light_text_box_row(ui, ids.light_color_label, &text_boxes,
+                       selected_light, synced_editor_state, current_editor_state, pending_editor_events, pending_light_previews);
+
+    widget::Text::new("intensity:").down_from(ids.light_color_label, WIDGET_DISTANCE * 2.0)
+                                   .w(LABEL_WIDTH)
+                                   .set(ids.light_intensity_label, ui);
+
+    let text_boxes = [
+        (ids.light_intensity_text_box, "intensity", current_editor_state.light_intensity_text_box_content.clone()),
+    ];
+    light_text_box_row(ui, ids.light_intensity_label, &text_boxes,
+                       selected_light, synced_editor_state, current_editor_state, pending_editor_events, pending_light_previews);
+
+    widget::Text::new("range:").right_from(ids.light_intensity_text_box, WIDGET_DISTANCE)
+                               .w(LABEL_WIDTH)
+                               .set(ids.light_range_label, ui);
+
+    let text_boxes = [
+        (ids.light_range_text_box, "range", current_editor_state.light_range_text_box_content.clone()),
+    ];
+    light_text_box_row(ui, ids.light_range_label, &text_boxes,
+                       selected_light, synced_editor_state, current_editor_state, pending_editor_events, pending_light_previews);
+}
+
+/// Sets light panel text boxes in a row, to the right of given label.
+fn light_text_box_row(ui: &mut conrod_core::UiCell,
+                      label: widget::Id,
+                      text_boxes: &[(widget::Id, &str, String)],
+                      selected_light: u32,
+                      synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+                      pending_editor_events: &mut Vec<EditorEvent>,
+                      pending_light_previews: &mut Vec<(u32, LightProperties)>) {
     const WIDGET_DISTANCE: f64 = 10.0;
 
     const TEXT_BOX_WIDTH: f64 = 50.0;
     const TEXT_BOX_HEIGHT: f64 = 25.0;
 
-    let floating = widget::Canvas::new().floating(true).top_left()
-                                        .w_h(PANEL_WIDTH, PANEL_HEIGHT)
-                                        .title_bar(PANEL_TITLE);
+    let mut previous_widget = label;
+    for (id, updated_text_box, content) in text_boxes {
+        let text_box = widget::TextBox::new(content).right_from(previous_widget, WIDGET_DISTANCE)
+                                                    .w_h(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT);
+
+        pending_editor_events.extend(text_box.set(*id, ui).into_iter()
+                                             .filter_map(|event| light_text_box_event_execute(event, updated_text_box, selected_light, synced_editor_state,
+                                                                                              current_editor_state, pending_light_previews)));
+        previous_widget = *id;
+    }
+}
+
+fn toolbar(ids: &Ids, ui: &mut conrod_core::UiCell,
+           play_state: PlayState,
+           current_editor_state: &mut EditorState,
+           pending_play_actions: &mut Vec<PlayAction>) {
+    const TOOLBAR_WIDTH: f64 = 440.0;
+    const TOOLBAR_HEIGHT: f64 = 45.0;
+
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const BUTTON_WIDTH: f64 = 65.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+
+    let play_label = match play_state {
+        PlayState::Paused => "Resume",
+        _ => "Play",
+    };
+
+    widget::Canvas::new().floating(true).top_right()
+                         .w_h(TOOLBAR_WIDTH, TOOLBAR_HEIGHT)
+                         .set(ids.toolbar_canvas, ui);
+
+    for _press in widget::Button::new().label(play_label)
+                                       .mid_left_with_margin_on(ids.toolbar_canvas, MARGIN)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.play_button, ui)
+    {
+        pending_play_actions.push(PlayAction::Play);
+    }
+
+    for _press in widget::Button::new().label("Pause")
+                                       .right_from(ids.play_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.pause_button, ui)
+    {
+        pending_play_actions.push(PlayAction::Pause);
+    }
+
+    for _press in widget::Button::new().label("Step")
+                                       .right_from(ids.pause_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.step_button, ui)
+    {
+        pending_play_actions.push(PlayAction::Step);
+    }
+
+    for _press in widget::Button::new().label("Stop")
+                                       .right_from(ids.step_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.stop_button, ui)
+    {
+        pending_play_actions.push(PlayAction::Stop);
+    }
+
+    for _press in widget::Button::new().label("View")
+                                       .right_from(ids.stop_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.view_button, ui)
+    {
+        current_editor_state.show_view_panel = !current_editor_state.show_view_panel;
+    }
+
+    for _press in widget::Button::new().label("Create")
+                                       .right_from(ids.view_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.create_button, ui)
+    {
+        current_editor_state.show_create_menu = !current_editor_state.show_create_menu;
+    }
+}
+
+fn create_menu(ids: &Ids, ui: &mut conrod_core::UiCell,
+               current_editor_state: &mut EditorState,
+               pending_create_actions: &mut Vec<CreateAction>) {
+    const MENU_WIDTH: f64 = 85.0;
+    const TOOLBAR_HEIGHT: f64 = 45.0;
+
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const BUTTON_WIDTH: f64 = 65.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+
+    let menu_height = 2.0 * MARGIN + CreateAction::ALL.len() as f64 * (BUTTON_HEIGHT + WIDGET_DISTANCE) - WIDGET_DISTANCE;
+
+    widget::Canvas::new().floating(true).top_right_with_margins_on(ui.window, TOOLBAR_HEIGHT, 0.0)
+                         .w_h(MENU_WIDTH, menu_height)
+                         .set(ids.create_menu_canvas, ui);
+
+    for (i, create_action) in CreateAction::ALL.iter().enumerate() {
+        let top_margin = MARGIN + i as f64 * (BUTTON_HEIGHT + WIDGET_DISTANCE);
+        for _press in widget::Button::new().label(create_action.label())
+                                           .top_left_with_margins_on(ids.create_menu_canvas, top_margin, MARGIN)
+                                           .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                           .set(ids.create_menu_buttons[i], ui)
+        {
+            current_editor_state.show_create_menu = false;
+            pending_create_actions.push(*create_action);
+        }
+    }
+}
+
+fn file_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+              placement: PanelPlacement,
+              current_editor_state: &mut EditorState,
+              pending_file_actions: &mut Vec<FileAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const BUTTON_WIDTH: f64 = 65.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+
+    docked_canvas(ui, placement, Panel::File.title()).set(ids.file_panel_canvas, ui);
+
+    for event in widget::TextBox::new(&current_editor_state.scene_path_text_box_content)
+                                 .top_left_with_margin_on(ids.file_panel_canvas, MARGIN)
+                                 .w_h(placement.width - 2.0 * MARGIN, BUTTON_HEIGHT)
+                                 .set(ids.scene_path_text_box, ui)
+    {
+        if let text_box::Event::Update(new_val) = event {
+            current_editor_state.scene_path_text_box_content = new_val;
+        }
+    }
+
+    let scene_path = PathBuf::from(&current_editor_state.scene_path_text_box_content);
+
+    for _press in widget::Button::new().label("New")
+                                       .down_from(ids.scene_path_text_box, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.new_button, ui)
+    {
+        pending_file_actions.push(FileAction::New);
+    }
+
+    for _press in widget::Button::new().label("Open")
+                                       .right_from(ids.new_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.open_button, ui)
+    {
+        pending_file_actions.push(FileAction::Open(scene_path.clone()));
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .right_from(ids.open_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.save_button, ui)
+    {
+        pending_file_actions.push(FileAction::Save);
+    }
+
+    for _press in widget::Button::new().label("Save As")
+                                       .right_from(ids.save_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.save_as_button, ui)
+    {
+        pending_file_actions.push(FileAction::SaveAs(scene_path.clone()));
+    }
+}
+
+fn statistics_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                    placement: PanelPlacement,
+                    statistics_text: &str) {
+    const MARGIN: f64 = 10.0;
+
+    docked_canvas(ui, placement, Panel::Statistics.title()).set(ids.statistics_canvas, ui);
+
+    widget::Text::new(statistics_text).top_left_with_margin_on(ids.statistics_canvas, MARGIN)
+                                      .w(placement.width - 2.0 * MARGIN)
+                                      .set(ids.statistics_text, ui);
+}
+
+/// Shows spans of the last profiled frame as a flame graph, with one row per nesting depth and span widths proportional to their duration.
+fn profiler_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                  placement: PanelPlacement,
+                  theme: EditorTheme,
+                  frame: &ProfileFrame) {
+    const MARGIN: f64 = 10.0;
+    const TEXT_HEIGHT: f64 = 20.0;
+    const ROW_HEIGHT: f64 = 18.0;
+    const MIN_LABEL_WIDTH: f64 = 40.0;
+
+    docked_canvas(ui, placement, Panel::Profiler.title()).set(ids.profiler_canvas, ui);
+
+    let frame_seconds = seconds(frame.duration());
+    let summary = if !profiling::ENABLED {
+        "built without profiling feature".to_string()
+    } else {
+        format!("frame: {:.2} ms, spans: {}", frame_seconds * 1000.0, frame.spans().len())
+    };
+    widget::Text::new(&summary).top_left_with_margin_on(ids.profiler_canvas, MARGIN)
+                               .w(placement.width - 2.0 * MARGIN)
+                               .set(ids.profiler_text, ui);
+    if frame_seconds <= 0.0 {
+        return;
+    }
+
+    let graph_width = placement.width - 2.0 * MARGIN;
+    let span_widgets = ids.profiler_span_rects.iter().zip(ids.profiler_span_labels.iter());
+    for (span, (&rect_id, &label_id)) in frame.spans().iter().zip(span_widgets) {
+        let left = MARGIN + graph_width * seconds(span.start) / frame_seconds;
+        let top = MARGIN + TEXT_HEIGHT + f64::from(span.depth) * ROW_HEIGHT;
+        let width = (graph_width * seconds(span.duration) / frame_seconds).max(1.0);
+
+        widget::Rectangle::fill_with([width, ROW_HEIGHT - 2.0], theme.selection_color())
+                          .top_left_with_margins_on(ids.profiler_canvas, top, left)
+                          .set(rect_id, ui);
+        if width >= MIN_LABEL_WIDTH {
+            widget::Text::new(span.name).font_size(10)
+                                        .no_line_wrap()
+                                        .mid_left_with_margin_on(rect_id, 2.0)
+                                        .w(width - 4.0)
+                                        .set(label_id, ui);
+        }
+    }
+}
+
+/// Shows records captured by the engine logger, the latest at the bottom. Warnings and errors are highlighted.
+fn console_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                 placement: PanelPlacement,
+                 log_records: &[LogRecord]) {
+    const MARGIN: f64 = 10.0;
+    const ITEM_HEIGHT: f64 = 16.0;
+
+    docked_canvas(ui, placement, Panel::Console.title()).set(ids.console_canvas, ui);
+
+    let list_height = (placement.height - 2.0 * MARGIN).max(ITEM_HEIGHT);
+    let (mut items, scrollbar) = widget::List::flow_up(log_records.len())
+                                              .item_size(ITEM_HEIGHT)
+                                              .scrollbar_on_top()
+                                              .top_left_with_margin_on(ids.console_canvas, MARGIN)
+                                              .w_h(placement.width - 2.0 * MARGIN, list_height)
+                                              .set(ids.console_list, ui);
+
+    while let Some(item) = items.next(ui) {
+        let record = &log_records[log_records.len() - 1 - item.i];
+        let color = match record.level {
+            Level::Error => color::RED,
+            Level::Warn => color::YELLOW,
+            _ => ui.theme.label_color,
+        };
+        let line = format!("[{} {}] {}", record.level, record.target, record.message);
+        item.set(widget::Text::new(&line).font_size(12).no_line_wrap().color(color), ui);
+    }
+
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+fn material_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                  placement: PanelPlacement,
+                  material_preview_image: conrod_core::image::Id,
+                  current_editor_state: &mut EditorState,
+                  pending_material_actions: &mut Vec<MaterialAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const LABEL_WIDTH: f64 = 60.0;
+    const BUTTON_WIDTH: f64 = 65.0;
+    const TEXT_BOX_WIDTH: f64 = 45.0;
+    const ROW_HEIGHT: f64 = 25.0;
+    const MESH_TEXTURE_LABEL: &str = "mesh texture";
+
+    docked_canvas(ui, placement, Panel::Material.title()).set(ids.material_canvas, ui);
+
+    let selected_index = current_editor_state.edited_material.as_ref()
+                                                             .and_then(|material| current_editor_state.material_names.iter().position(|x| x == material.name()));
+    let material_list_width = placement.width - 2.0 * MARGIN - 2.0 * (BUTTON_WIDTH + WIDGET_DISTANCE);
+    for index in widget::DropDownList::new(&current_editor_state.material_names, selected_index)
+                                      .top_left_with_margin_on(ids.material_canvas, MARGIN)
+                                      .w_h(material_list_width, ROW_HEIGHT)
+                                      .set(ids.material_list, ui)
+    {
+        pending_material_actions.push(MaterialAction::Select(current_editor_state.material_names[index].clone()));
+    }
+
+    for _press in widget::Button::new().label("New")
+                                       .right_from(ids.material_list, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.new_material_button, ui)
+    {
+        pending_material_actions.push(MaterialAction::New);
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .right_from(ids.new_material_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.save_material_button, ui)
+    {
+        pending_material_actions.push(MaterialAction::Save);
+    }
+
+    let material = match current_editor_state.edited_material.clone() {
+        Some(material) => material,
+        None => return,
+    };
+
+    widget::Image::new(material_preview_image).down_from(ids.material_list, WIDGET_DISTANCE)
+                                              .w_h(MATERIAL_PREVIEW_SIZE as f64, MATERIAL_PREVIEW_SIZE as f64)
+                                              .set(ids.material_preview_image, ui);
+
+    for _press in widget::Button::new().label("Assign")
+                                       .right_from(ids.material_preview_image, WIDGET_DISTANCE)
+                                       .align_top_of(ids.material_preview_image)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.assign_material_button, ui)
+    {
+        pending_material_actions.push(MaterialAction::AssignToSelection);
+    }
+
+    widget::Text::new("texture:").down_from(ids.material_preview_image, WIDGET_DISTANCE * 2.0)
+                                 .w(LABEL_WIDTH)
+                                 .set(ids.material_texture_label, ui);
+
+    let texture_names: Vec<&str> = std::iter::once(MESH_TEXTURE_LABEL).chain(current_editor_state.texture_names.iter().map(String::as_str))
+                                                                      .collect();
+    let selected_texture = match material.albedo_texture() {
+        Some(texture) => current_editor_state.texture_names.iter().position(|x| x == texture).map(|index| index + 1),
+        None => Some(0),
+    };
+    for index in widget::DropDownList::new(&texture_names, selected_texture)
+                                      .right_from(ids.material_texture_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.material_texture_list, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_albedo_texture(index.checked_sub(1).map(|index| current_editor_state.texture_names[index].clone()));
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    widget::Text::new("color:").down_from(ids.material_texture_label, WIDGET_DISTANCE * 2.0)
+                               .w(LABEL_WIDTH)
+                               .set(ids.material_color_label, ui);
+
+    let text_boxes = [
+        (ids.r_material_color_text_box, "r", current_editor_state.r_material_color_text_box_content.clone()),
+        (ids.g_material_color_text_box, "g", current_editor_state.g_material_color_text_box_content.clone()),
+        (ids.b_material_color_text_box, "b", current_editor_state.b_material_color_text_box_content.clone()),
+        (ids.a_material_color_text_box, "a", current_editor_state.a_material_color_text_box_content.clone()),
+    ];
+    let mut previous_widget = ids.material_color_label;
+    for (id, updated_text_box, content) in text_boxes.iter() {
+        let text_box = widget::TextBox::new(content).right_from(previous_widget, WIDGET_DISTANCE)
+                                                    .w_h(TEXT_BOX_WIDTH, ROW_HEIGHT);
+        pending_material_actions.extend(text_box.set(*id, ui).into_iter()
+                                                .filter_map(|event| material_color_text_box_event_execute(event, updated_text_box, current_editor_state)));
+        previous_widget = *id;
+    }
+
+    let slider_width = placement.width - 2.0 * MARGIN;
+    let metallic_label = format!("metallic: {:.2}", material.metallic());
+    if let Some(metallic) = widget::Slider::new(material.metallic(), 0.0, 1.0).label(&metallic_label)
+                                                                             .down_from(ids.material_color_label, WIDGET_DISTANCE * 2.0)
+                                                                             .w_h(slider_width, ROW_HEIGHT)
+                                                                             .set(ids.material_metallic_slider, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_metallic(metallic);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    let roughness_label = format!("roughness: {:.2}", material.roughness());
+    if let Some(roughness) = widget::Slider::new(material.roughness(), 0.0, 1.0).label(&roughness_label)
+                                                                               .down_from(ids.material_metallic_slider, WIDGET_DISTANCE)
+                                                                               .w_h(slider_width, ROW_HEIGHT)
+                                                                               .set(ids.material_roughness_slider, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_roughness(roughness);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    let emissive_label = format!("emissive: {:.2}", material.emissive_intensity());
+    if let Some(emissive_intensity) = widget::Slider::new(material.emissive_intensity(), 0.0, MAX_EDITOR_EMISSIVE_INTENSITY)
+                                                     .label(&emissive_label)
+                                                     .down_from(ids.material_roughness_slider, WIDGET_DISTANCE)
+                                                     .w_h(slider_width, ROW_HEIGHT)
+                                                     .set(ids.material_emissive_slider, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_emissive_intensity(emissive_intensity);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    widget::Text::new("blend:").down_from(ids.material_emissive_slider, WIDGET_DISTANCE * 2.0)
+                               .w(LABEL_WIDTH)
+                               .set(ids.material_blend_mode_label, ui);
+
+    let blend_mode_labels: Vec<&str> = BlendMode::ALL.iter().map(|x| x.label()).collect();
+    let selected_blend_mode = BlendMode::ALL.iter().position(|x| *x == material.blend_mode());
+    for index in widget::DropDownList::new(&blend_mode_labels, selected_blend_mode)
+                                      .right_from(ids.material_blend_mode_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.material_blend_mode_list, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_blend_mode(BlendMode::ALL[index]);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    widget::Text::new("cull:").down_from(ids.material_blend_mode_label, WIDGET_DISTANCE * 2.0)
+                              .w(LABEL_WIDTH)
+                              .set(ids.material_cull_mode_label, ui);
+
+    let cull_mode_labels: Vec<&str> = CullMode::ALL.iter().map(|x| x.label()).collect();
+    let selected_cull_mode = CullMode::ALL.iter().position(|x| *x == material.cull_mode());
+    for index in widget::DropDownList::new(&cull_mode_labels, selected_cull_mode)
+                                      .right_from(ids.material_cull_mode_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.material_cull_mode_list, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_cull_mode(CullMode::ALL[index]);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+
+    widget::Text::new("front:").down_from(ids.material_cull_mode_label, WIDGET_DISTANCE * 2.0)
+                               .w(LABEL_WIDTH)
+                               .set(ids.material_front_face_label, ui);
+
+    let front_face_labels: Vec<&str> = FrontFace::ALL.iter().map(|x| x.label()).collect();
+    let selected_front_face = FrontFace::ALL.iter().position(|x| *x == material.front_face());
+    for index in widget::DropDownList::new(&front_face_labels, selected_front_face)
+                                      .right_from(ids.material_front_face_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.material_front_face_list, ui)
+    {
+        let mut changed_material = material.clone();
+        changed_material.set_front_face(FrontFace::ALL[index]);
+        pending_material_actions.push(MaterialAction::Changed(changed_material));
+    }
+}
+
+fn particles_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                   placement: PanelPlacement,
+                   current_editor_state: &EditorState,
+                   pending_particle_actions: &mut Vec<ParticleAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const LABEL_WIDTH: f64 = 60.0;
+    const BUTTON_WIDTH: f64 = 65.0;
+    const ROW_HEIGHT: f64 = 25.0;
+    const NO_TEXTURE_LABEL: &str = "no texture";
+
+    docked_canvas(ui, placement, Panel::Particles.title()).set(ids.particles_canvas, ui);
+
+    let selected_index = current_editor_state.edited_particle_effect.as_ref()
+                                                                    .and_then(|effect| current_editor_state.particle_effect_names.iter().position(|x| x == effect.name()));
+    let effect_list_width = placement.width - 2.0 * MARGIN - 2.0 * (BUTTON_WIDTH + WIDGET_DISTANCE);
+    for index in widget::DropDownList::new(&current_editor_state.particle_effect_names, selected_index)
+                                      .top_left_with_margin_on(ids.particles_canvas, MARGIN)
+                                      .w_h(effect_list_width, ROW_HEIGHT)
+                                      .set(ids.particle_effect_list, ui)
+    {
+        pending_particle_actions.push(ParticleAction::Select(current_editor_state.particle_effect_names[index].clone()));
+    }
+
+    for _press in widget::Button::new().label("New")
+                                       .right_from(ids.particle_effect_list, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.new_particle_effect_button, ui)
+    {
+        pending_particle_actions.push(ParticleAction::New);
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .right_from(ids.new_particle_effect_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.save_particle_effect_button, ui)
+    {
+        pending_particle_actions.push(ParticleAction::Save);
+    }
+
+    let effect = match current_editor_state.edited_particle_effect.clone() {
+        Some(effect) => effect,
+        None => return,
+    };
 
-    floating.flow_right(&[
-        (ids.light_panel_x_column, widget::Canvas::new().pad(COLUMN_PADDING)),
-        (ids.light_panel_y_column, widget::Canvas::new().pad(COLUMN_PADDING)),
-        (ids.light_panel_z_column, widget::Canvas::new().pad(COLUMN_PADDING)),
-    ]).set(ids.light_panel_canvas, ui);
+    for _press in widget::Button::new().label("Assign")
+                                       .down_from(ids.particle_effect_list, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.assign_particle_effect_button, ui)
+    {
+        pending_particle_actions.push(ParticleAction::AssignToSelection);
+    }
 
-    widget::Text::new("x:").mid_left_of(ids.light_panel_x_column)
-                           .set(ids.x_light_label, ui);
+    for _press in widget::Button::new().label("Remove")
+                                       .right_from(ids.assign_particle_effect_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.remove_particle_effect_button, ui)
+    {
+        pending_particle_actions.push(ParticleAction::RemoveFromSelection);
+    }
 
-    let x_light_text_box = widget::TextBox::new(&current_editor_state.x_light_text_box_content).right_from(ids.x_light_label, WIDGET_DISTANCE)
-                                            .wh([TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+    for _press in widget::Button::new().label("Restart")
+                                       .right_from(ids.remove_particle_effect_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.restart_particle_effect_button, ui)
+    {
+        pending_particle_actions.push(ParticleAction::RestartSelection);
+    }
 
-    pending_editor_events.extend(x_light_text_box.set(ids.x_light_text_box, ui).into_iter()
-                                                 .filter_map(|event| light_text_box_event_execute(event, "x", synced_editor_state, current_editor_state)));
+    widget::Text::new("texture:").down_from(ids.assign_particle_effect_button, WIDGET_DISTANCE * 2.0)
+                                 .w(LABEL_WIDTH)
+                                 .set(ids.particle_texture_label, ui);
 
-    widget::Text::new("y:").mid_left_of(ids.light_panel_y_column)
-                           .set(ids.y_light_label, ui);
+    let texture_names: Vec<&str> = std::iter::once(NO_TEXTURE_LABEL).chain(current_editor_state.texture_names.iter().map(String::as_str))
+                                                                    .collect();
+    let selected_texture = match &effect.texture {
+        Some(texture) => current_editor_state.texture_names.iter().position(|x| x == texture).map(|index| index + 1),
+        None => Some(0),
+    };
+    for index in widget::DropDownList::new(&texture_names, selected_texture)
+                                      .right_from(ids.particle_texture_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.particle_texture_list, ui)
+    {
+        let mut changed_effect = effect.clone();
+        changed_effect.texture = index.checked_sub(1).map(|index| current_editor_state.texture_names[index].clone());
+        pending_particle_actions.push(ParticleAction::Changed(changed_effect));
+    }
 
-    let y_light_text_box = widget::TextBox::new(&current_editor_state.y_light_text_box_content).right_from(ids.y_light_label, WIDGET_DISTANCE)
-                                            .wh([TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+    widget::Text::new("blend:").down_from(ids.particle_texture_label, WIDGET_DISTANCE * 2.0)
+                               .w(LABEL_WIDTH)
+                               .set(ids.particle_blend_mode_label, ui);
 
-    pending_editor_events.extend(y_light_text_box.set(ids.y_light_text_box, ui).into_iter()
-                                                 .filter_map(|event| light_text_box_event_execute(event, "y", synced_editor_state, current_editor_state)));
+    let blend_mode_labels: Vec<&str> = BlendMode::ALL.iter().map(|x| x.label()).collect();
+    let selected_blend_mode = BlendMode::ALL.iter().position(|x| *x == effect.blend_mode);
+    for index in widget::DropDownList::new(&blend_mode_labels, selected_blend_mode)
+                                      .right_from(ids.particle_blend_mode_label, WIDGET_DISTANCE)
+                                      .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, ROW_HEIGHT)
+                                      .set(ids.particle_blend_mode_list, ui)
+    {
+        let mut changed_effect = effect.clone();
+        changed_effect.blend_mode = BlendMode::ALL[index];
+        pending_particle_actions.push(ParticleAction::Changed(changed_effect));
+    }
 
-    widget::Text::new("z:").mid_left_of(ids.light_panel_z_column)
-                           .set(ids.z_light_label, ui);
+    let slider_width = placement.width - 2.0 * MARGIN;
+    for looping in widget::Toggle::new(effect.looping).label("Looping")
+                                                      .down_from(ids.particle_blend_mode_label, WIDGET_DISTANCE * 2.0)
+                                                      .w_h(slider_width, ROW_HEIGHT)
+                                                      .set(ids.particle_looping_toggle, ui)
+    {
+        let mut changed_effect = effect.clone();
+        changed_effect.looping = looping;
+        pending_particle_actions.push(ParticleAction::Changed(changed_effect));
+    }
 
-    let z_light_text_box = widget::TextBox::new(&current_editor_state.z_light_text_box_content).right_from(ids.z_light_label, WIDGET_DISTANCE)
-                                            .wh([TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT]);
+    // curves are edited through their first and last keys, keys between them are kept
+    let start_size = effect.size.evaluate(0.0).unwrap_or(1.0);
+    let end_size = effect.size.evaluate(1.0).unwrap_or(start_size);
+    let end_alpha = effect.color.evaluate(1.0).map(|color| color[3]).unwrap_or(1.0);
+    let sliders: [(&str, f32, f32, f32); PARTICLE_SLIDER_COUNT] = [
+        ("emission rate", effect.emission_rate, 0.0, 200.0),
+        ("burst", effect.burst as f32, 0.0, 200.0),
+        ("max particles", effect.max_particles as f32, 1.0, 1000.0),
+        ("duration", effect.duration, 0.1, 10.0),
+        ("min lifetime", effect.lifetime.0, 0.1, 10.0),
+        ("max lifetime", effect.lifetime.1, 0.1, 10.0),
+        ("min speed", effect.speed.0, 0.0, 20.0),
+        ("max speed", effect.speed.1, 0.0, 20.0),
+        ("spread angle", effect.spread_angle, 0.0, std::f32::consts::PI),
+        ("gravity", effect.gravity.1, -20.0, 20.0),
+        ("start size", start_size, 0.0, 5.0),
+        ("end size", end_size, 0.0, 5.0),
+        ("end alpha", end_alpha, 0.0, 1.0),
+    ];
+    let mut previous_widget = ids.particle_looping_toggle;
+    for (i, (label, value, min, max)) in sliders.iter().enumerate() {
+        let label = format!("{}: {:.2}", label, value);
+        if let Some(value) = widget::Slider::new(*value, *min, *max).label(&label)
+                                                                    .down_from(previous_widget, WIDGET_DISTANCE)
+                                                                    .w_h(slider_width, ROW_HEIGHT)
+                                                                    .set(ids.particle_sliders[i], ui)
+        {
+            let mut changed_effect = effect.clone();
+            set_particle_parameter(&mut changed_effect, i, value);
+            pending_particle_actions.push(ParticleAction::Changed(changed_effect));
+        }
+        previous_widget = ids.particle_sliders[i];
+    }
+}
 
-    pending_editor_events.extend(z_light_text_box.set(ids.z_light_text_box, ui).into_iter()
-                                                 .filter_map(|event| light_text_box_event_execute(event, "z", synced_editor_state, current_editor_state)));
+/// Sets parameter of particle effect edited by the slider with given index.
+fn set_particle_parameter(effect: &mut ParticleEffect, slider: usize, value: f32) {
+    match slider {
+        0 => effect.emission_rate = value,
+        1 => effect.burst = value.round() as u32,
+        2 => effect.max_particles = value.round() as u32,
+        3 => effect.duration = value,
+        4 => effect.lifetime = (value, effect.lifetime.1.max(value)),
+        5 => effect.lifetime = (effect.lifetime.0.min(value), value),
+        6 => effect.speed = (value, effect.speed.1.max(value)),
+        7 => effect.speed = (effect.speed.0.min(value), value),
+        8 => effect.spread_angle = value,
+        9 => effect.gravity.1 = value,
+        10 => set_curve_key(&mut effect.size, 0.0, value),
+        11 => set_curve_key(&mut effect.size, 1.0, value),
+        12 => {
+            let mut color = effect.color.evaluate(1.0).unwrap_or([1.0; 4]);
+            color[3] = value;
+            set_curve_key(&mut effect.color, 1.0, color);
+        },
+        _ => {},
+    }
+}
 
+/// Sets value of the first or the last key of the curve, adding the key if the curve doesn't start or end at that time.
+fn set_curve_key<T: CurveValue>(curve: &mut Curve<T>, time: f32, value: T) {
+    let index = if time <= 0.0 { 0 } else { curve.keys.len().saturating_sub(1) };
+    match curve.keys.get(index).map(|key| key.0) {
+        Some(key_time) if key_time == time => curve.keys[index].1 = value,
+        _ if time <= 0.0 => curve.keys.insert(0, (time, value)),
+        _ => curve.keys.push((time, value)),
+    }
 }
 
-fn run_button(ids: &Ids, ui: &mut conrod_core::UiCell, 
-               _synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
-               _pending_editor_events: &mut Vec<EditorEvent>) {
-    const BUTTON_TITLE: &str = "Run";
-    const BUTTON_WIDTH: f64 = 300.0;
-    const BUTTON_HEIGHT: f64 = 150.0;
+fn terrain_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                 placement: PanelPlacement,
+                 current_editor_state: &EditorState,
+                 terrain_brush: TerrainBrush,
+                 pending_terrain_actions: &mut Vec<TerrainAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const LABEL_WIDTH: f64 = 45.0;
+    const BUTTON_WIDTH: f64 = 65.0;
+    const ROW_HEIGHT: f64 = 25.0;
+    const MAX_BRUSH_RADIUS: f32 = 20.0;
+    const MAX_BRUSH_STRENGTH: f32 = 10.0;
+
+    docked_canvas(ui, placement, Panel::Terrain.title()).set(ids.terrain_canvas, ui);
 
-    for _press in widget::Button::new().label(BUTTON_TITLE)
-                                       .top_right()
-                                       .wh([BUTTON_WIDTH, BUTTON_HEIGHT])
-                                       .set(ids.run_button, ui) 
+    let selected_index = current_editor_state.edited_terrain.as_ref()
+                                                            .and_then(|terrain| current_editor_state.terrain_names.iter().position(|x| x == terrain));
+    let terrain_list_width = placement.width - 2.0 * MARGIN - 2.0 * (BUTTON_WIDTH + WIDGET_DISTANCE);
+    for index in widget::DropDownList::new(&current_editor_state.terrain_names, selected_index)
+                                      .top_left_with_margin_on(ids.terrain_canvas, MARGIN)
+                                      .w_h(terrain_list_width, ROW_HEIGHT)
+                                      .set(ids.terrain_list, ui)
     {
-        current_editor_state.run_game = true;
+        pending_terrain_actions.push(TerrainAction::Select(current_editor_state.terrain_names[index].clone()));
     }
-}
\ No newline at end of file
+
+    for _press in widget::Button::new().label("New")
+                                       .right_from(ids.terrain_list, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.new_terrain_button, ui)
+    {
+        pending_terrain_actions.push(TerrainAction::New);
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .right_from(ids.new_terrain_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.save_terrain_button, ui)
+    {
+        pending_terrain_actions.push(TerrainAction::Save);
+    }
+
+    if current_editor_state.edited_terrain.is_none() {
+        return;
+    }
+
+    for _press in widget::Button::new().label("Add to scene")
+                                       .down_from(ids.terrain_list, WIDGET_DISTANCE)
+                                       .w_h(placement.width - 2.0 * MARGIN, ROW_HEIGHT)
+                                       .set(ids.add_terrain_to_scene_button, ui)
+    {
+        pending_terrain_actions.push(TerrainAction::AddToScene);
+    }
+
+    widget::Text::new("tool:").down_from(ids.add_terrain_to_scene_button, WIDGET_DISTANCE * 2.0)
+                              .w(LABEL_WIDTH)
+                              .set(ids.terrain_tool_label, ui);
+
+    let toggle_width = (placement.width - 2.0 * MARGIN - LABEL_WIDTH) / TerrainTool::ALL.len() as f64 - WIDGET_DISTANCE;
+    let mut previous_widget = ids.terrain_tool_label;
+    for (i, tool) in TerrainTool::ALL.iter().enumerate() {
+        // clicking the chosen tool again puts the brush away, so that objects can be selected
+        for active in widget::Toggle::new(terrain_brush.tool == Some(*tool)).label(tool.label())
+                                                                           .right_from(previous_widget, WIDGET_DISTANCE)
+                                                                           .w_h(toggle_width, ROW_HEIGHT)
+                                                                           .set(ids.terrain_tool_toggles[i], ui)
+        {
+            pending_terrain_actions.push(TerrainAction::SetTool(Some(*tool).filter(|_| active)));
+        }
+        previous_widget = ids.terrain_tool_toggles[i];
+    }
+
+    let slider_width = placement.width - 2.0 * MARGIN;
+    let radius_label = format!("radius: {:.1}", terrain_brush.radius);
+    if let Some(radius) = widget::Slider::new(terrain_brush.radius, 0.1, MAX_BRUSH_RADIUS).label(&radius_label)
+                                                                                       .down_from(ids.terrain_tool_label, WIDGET_DISTANCE * 2.0)
+                                                                                       .w_h(slider_width, ROW_HEIGHT)
+                                                                                       .set(ids.terrain_radius_slider, ui)
+    {
+        pending_terrain_actions.push(TerrainAction::SetRadius(radius));
+    }
+
+    let strength_label = format!("strength: {:.2}", terrain_brush.strength);
+    if let Some(strength) = widget::Slider::new(terrain_brush.strength, 0.01, MAX_BRUSH_STRENGTH).label(&strength_label)
+                                                                                                .down_from(ids.terrain_radius_slider, WIDGET_DISTANCE)
+                                                                                                .w_h(slider_width, ROW_HEIGHT)
+                                                                                                .set(ids.terrain_strength_slider, ui)
+    {
+        pending_terrain_actions.push(TerrainAction::SetStrength(strength));
+    }
+}
+
+fn bookmarks_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                   placement: PanelPlacement,
+                   camera_bookmarks: &BTreeMap<u32, CameraBookmark>,
+                   current_editor_state: &mut EditorState,
+                   pending_bookmark_actions: &mut Vec<BookmarkAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const BUTTON_WIDTH: f64 = 50.0;
+    const ROW_HEIGHT: f64 = 25.0;
+
+    docked_canvas(ui, placement, Panel::Bookmarks.title()).set(ids.bookmarks_canvas, ui);
+
+    let text_box_width = placement.width - 2.0 * MARGIN - 2.0 * (BUTTON_WIDTH + WIDGET_DISTANCE);
+    for i in 0..current_editor_state.bookmark_name_text_box_contents.len() {
+        let slot = i as u32 + 1;
+        let top_margin = MARGIN + i as f64 * (ROW_HEIGHT + WIDGET_DISTANCE);
+
+        for event in widget::TextBox::new(&current_editor_state.bookmark_name_text_box_contents[i])
+                                     .top_left_with_margins_on(ids.bookmarks_canvas, top_margin, MARGIN)
+                                     .w_h(text_box_width, ROW_HEIGHT)
+                                     .set(ids.bookmark_name_text_boxes[i], ui)
+        {
+            match event {
+                text_box::Event::Update(new_val) => current_editor_state.bookmark_name_text_box_contents[i] = new_val,
+                text_box::Event::Enter => {
+                    pending_bookmark_actions.push(BookmarkAction::Rename(slot, current_editor_state.bookmark_name_text_box_contents[i].clone()));
+                },
+            }
+        }
+
+        let recall_label = if camera_bookmarks.contains_key(&slot) { format!("Go {}", slot) } else { String::from("-") };
+        for _press in widget::Button::new().label(&recall_label)
+                                           .right_from(ids.bookmark_name_text_boxes[i], WIDGET_DISTANCE)
+                                           .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                           .set(ids.bookmark_recall_buttons[i], ui)
+        {
+            pending_bookmark_actions.push(BookmarkAction::Recall(slot));
+        }
+
+        for _press in widget::Button::new().label("Set")
+                                           .right_from(ids.bookmark_recall_buttons[i], WIDGET_DISTANCE)
+                                           .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                           .set(ids.bookmark_save_buttons[i], ui)
+        {
+            pending_bookmark_actions.push(BookmarkAction::Save(slot));
+        }
+    }
+}
+
+/// Shows scene statistics and problems found by the last scan. Clicking a problem jumps to the object it was found in.
+fn validation_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                    placement: PanelPlacement,
+                    validation_report: &ValidationReport,
+                    selection: &Selection,
+                    theme: EditorTheme,
+                    pending_validation_actions: &mut Vec<ValidationAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const BUTTON_WIDTH: f64 = 65.0;
+    const ROW_HEIGHT: f64 = 25.0;
+    const ITEM_HEIGHT: f64 = 25.0;
+
+    docked_canvas(ui, placement, Panel::Validation.title()).set(ids.validation_canvas, ui);
+
+    for _press in widget::Button::new().label("Scan")
+                                       .top_left_with_margin_on(ids.validation_canvas, MARGIN)
+                                       .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.validation_scan_button, ui)
+    {
+        pending_validation_actions.push(ValidationAction::Scan);
+    }
+
+    let summary = format!("objects: {}, triangles: {}\nproblems: {}",
+                          validation_report.object_count, validation_report.triangle_count, validation_report.issues.len());
+    widget::Text::new(&summary).right_from(ids.validation_scan_button, WIDGET_DISTANCE)
+                               .align_top_of(ids.validation_scan_button)
+                               .set(ids.validation_summary_text, ui);
+
+    let list_height = (placement.height - 3.0 * MARGIN - ROW_HEIGHT - WIDGET_DISTANCE).max(ITEM_HEIGHT);
+    let (mut items, scrollbar) = widget::List::flow_down(validation_report.issues.len())
+                                              .item_size(ITEM_HEIGHT)
+                                              .scrollbar_on_top()
+                                              .down_from(ids.validation_scan_button, WIDGET_DISTANCE)
+                                              .w_h(placement.width - 2.0 * MARGIN, list_height)
+                                              .set(ids.validation_issue_list, ui);
+
+    while let Some(item) = items.next(ui) {
+        let issue = &validation_report.issues[item.i];
+        let label = format!("{}: {}", issue.object_name, issue.problem.description());
+        let button_color = if selection.primary() == Some(issue.object_id) { theme.primary_selection_color() } else { ui.theme.shape_color };
+        for _click in item.set(widget::Button::new().label(&label).color(button_color), ui) {
+            pending_validation_actions.push(ValidationAction::JumpTo(issue.object_id));
+        }
+    }
+
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+}
+
+/// Shows what the selected game camera sees, stretched to proportions of the window.
+fn camera_preview_window(ids: &Ids, ui: &mut conrod_core::UiCell,
+                         camera_preview_image: conrod_core::image::Id,
+                         camera_object_name: &str) {
+    const MARGIN: f64 = 10.0;
+    const TITLE_BAR_HEIGHT: f64 = 25.0;
+    const IMAGE_HEIGHT: f64 = 180.0;
+
+    let image_width = IMAGE_HEIGHT * ui.win_w / ui.win_h;
+
+    widget::Canvas::new().floating(true)
+                         .mid_top_with_margin_on(ui.window, MARGIN)
+                         .w_h(image_width + 2.0 * MARGIN, IMAGE_HEIGHT + 2.0 * MARGIN + TITLE_BAR_HEIGHT)
+                         .title_bar(&format!("Camera: {}", camera_object_name))
+                         .set(ids.camera_preview_canvas, ui);
+
+    widget::Image::new(camera_preview_image).mid_bottom_with_margin_on(ids.camera_preview_canvas, MARGIN)
+                                            .w_h(image_width, IMAGE_HEIGHT)
+                                            .set(ids.camera_preview_image, ui);
+}
+
+/// Lists textures and meshes added to the asset manager. Double-clicking an asset opens its preview.
+fn assets_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                placement: PanelPlacement,
+                current_editor_state: &EditorState,
+                pending_asset_preview_actions: &mut Vec<AssetPreviewAction>) {
+    const ITEM_HEIGHT: f64 = 25.0;
+
+    docked_canvas(ui, placement, Panel::Assets.title()).set(ids.assets_canvas, ui);
+
+    let texture_count = current_editor_state.texture_names.len();
+    let (mut items, scrollbar) = widget::List::flow_down(texture_count + current_editor_state.mesh_names.len())
+                                              .item_size(ITEM_HEIGHT)
+                                              .scrollbar_on_top()
+                                              .middle_of(ids.assets_canvas)
+                                              .wh_of(ids.assets_canvas)
+                                              .set(ids.assets_list, ui);
+
+    while let Some(item) = items.next(ui) {
+        let (label, action) = if item.i < texture_count {
+            let name = &current_editor_state.texture_names[item.i];
+            (format!("texture: {}", name), AssetPreviewAction::OpenTexture(name.clone()))
+        } else {
+            let name = &current_editor_state.mesh_names[item.i - texture_count];
+            (format!("mesh: {}", name), AssetPreviewAction::OpenMesh(name.clone()))
+        };
+        let double_clicked = ui.widget_input(item.widget_id).events().any(|event| match event {
+            event::Widget::DoubleClick(_) => true,
+            _ => false,
+        });
+        item.set(widget::Button::new().label(&label), ui);
+        if double_clicked {
+            pending_asset_preview_actions.push(action);
+        }
+    }
+
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+}
+
+fn asset_preview_window(ids: &Ids, ui: &mut conrod_core::UiCell,
+                        asset_preview_image: conrod_core::image::Id,
+                        supports_wireframe: bool,
+                        previewed_asset: &PreviewedAsset,
+                        pending_asset_preview_actions: &mut Vec<AssetPreviewAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const BUTTON_WIDTH: f64 = 45.0;
+    const ROW_HEIGHT: f64 = 25.0;
+
+    let preview_size = ASSET_PREVIEW_SIZE as f64;
+    let window_width = preview_size + 2.0 * MARGIN;
+    let window_height = preview_size + 2.0 * MARGIN + 3.0 * (ROW_HEIGHT + WIDGET_DISTANCE) + ROW_HEIGHT;
+
+    widget::Canvas::new().floating(true)
+                         .middle_of(ui.window)
+                         .w_h(window_width, window_height)
+                         .title_bar(&format!("Preview: {}", previewed_asset.name()))
+                         .set(ids.asset_preview_canvas, ui);
+
+    widget::Image::new(asset_preview_image).top_left_with_margin_on(ids.asset_preview_canvas, MARGIN)
+                                           .w_h(preview_size, preview_size)
+                                           .set(ids.asset_preview_image, ui);
+
+    match previewed_asset {
+        PreviewedAsset::Texture { channel, mip_level, mip_levels, .. } => {
+            for (index, texture_channel) in TextureChannel::ALL.iter().enumerate() {
+                let label = if texture_channel == channel { format!("[{}]", texture_channel.label()) } else { texture_channel.label().to_string() };
+                let button = widget::Button::new().label(&label).w_h(BUTTON_WIDTH, ROW_HEIGHT);
+                let button = if index == 0 {
+                    button.down_from(ids.asset_preview_image, WIDGET_DISTANCE)
+                } else {
+                    button.right_from(ids.asset_preview_channel_buttons[index - 1], WIDGET_DISTANCE)
+                };
+                for _press in button.set(ids.asset_preview_channel_buttons[index], ui) {
+                    pending_asset_preview_actions.push(AssetPreviewAction::SetChannel(*texture_channel));
+                }
+            }
+
+            for _press in widget::Button::new().label("-")
+                                               .down_from(ids.asset_preview_channel_buttons[0], WIDGET_DISTANCE)
+                                               .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                               .set(ids.asset_preview_mip_down_button, ui)
+            {
+                pending_asset_preview_actions.push(AssetPreviewAction::ChangeMipLevel { increase: false });
+            }
+
+            for _press in widget::Button::new().label("+")
+                                               .right_from(ids.asset_preview_mip_down_button, WIDGET_DISTANCE)
+                                               .w_h(BUTTON_WIDTH, ROW_HEIGHT)
+                                               .set(ids.asset_preview_mip_up_button, ui)
+            {
+                pending_asset_preview_actions.push(AssetPreviewAction::ChangeMipLevel { increase: true });
+            }
+
+            widget::Text::new(&format!("mip level {} of {}", mip_level, mip_levels)).right_from(ids.asset_preview_mip_up_button, WIDGET_DISTANCE)
+                                                                                    .set(ids.asset_preview_info_text, ui);
+        },
+        PreviewedAsset::Mesh { wireframe, .. } => {
+            if supports_wireframe {
+                for _toggle in widget::Toggle::new(*wireframe).label("Wireframe")
+                                                                .down_from(ids.asset_preview_image, WIDGET_DISTANCE)
+                                                                .w_h(preview_size, ROW_HEIGHT)
+                                                                .set(ids.asset_preview_wireframe_toggle, ui)
+                {
+                    pending_asset_preview_actions.push(AssetPreviewAction::ToggleWireframe);
+                }
+            } else {
+                widget::Text::new("wireframe isn't supported by this device").down_from(ids.asset_preview_image, WIDGET_DISTANCE)
+                                                                             .set(ids.asset_preview_info_text, ui);
+            }
+        },
+    }
+
+    for _press in widget::Button::new().label("Close")
+                                       .bottom_right_with_margin_on(ids.asset_preview_canvas, MARGIN)
+                                       .w_h(2.0 * BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.asset_preview_close_button, ui)
+    {
+        pending_asset_preview_actions.push(AssetPreviewAction::Close);
+    }
+}
+
+fn view_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+              layout: &mut EditorLayout,
+              settings: &mut EditorSettings,
+              config_changed: &mut bool) {
+    const PANEL_TITLE: &str = "View";
+    const PANEL_WIDTH: f64 = 330.0;
+    const TOOLBAR_HEIGHT: f64 = 45.0;
+
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 5.0;
+
+    const TOGGLE_WIDTH: f64 = 120.0;
+    const DOCK_BUTTON_WIDTH: f64 = 80.0;
+    const RESIZE_BUTTON_WIDTH: f64 = 40.0;
+    const ROW_HEIGHT: f64 = 25.0;
+
+    let panel_height = 2.0 * MARGIN + (Panel::ALL.len() + 1) as f64 * (ROW_HEIGHT + WIDGET_DISTANCE);
+
+    widget::Canvas::new().floating(true).top_right_with_margins_on(ui.window, TOOLBAR_HEIGHT, 0.0)
+                         .w_h(PANEL_WIDTH, panel_height)
+                         .title_bar(PANEL_TITLE)
+                         .set(ids.view_panel_canvas, ui);
+
+    for (i, panel) in Panel::ALL.iter().enumerate() {
+        let panel_layout = layout.panel_mut(*panel);
+        let top_margin = MARGIN + i as f64 * (ROW_HEIGHT + WIDGET_DISTANCE);
+
+        for visible in widget::Toggle::new(panel_layout.visible).label(panel.title())
+                                                                 .top_left_with_margins_on(ids.view_panel_canvas, top_margin, MARGIN)
+                                                                 .w_h(TOGGLE_WIDTH, ROW_HEIGHT)
+                                                                 .set(ids.view_visibility_toggles[i], ui)
+        {
+            panel_layout.visible = visible;
+            *config_changed = true;
+        }
+
+        for _press in widget::Button::new().label(&format!("{:?}", panel_layout.dock))
+                                           .right_from(ids.view_visibility_toggles[i], WIDGET_DISTANCE)
+                                           .w_h(DOCK_BUTTON_WIDTH, ROW_HEIGHT)
+                                           .set(ids.view_dock_buttons[i], ui)
+        {
+            panel_layout.dock = panel_layout.dock.next();
+            *config_changed = true;
+        }
+
+        for _press in widget::Button::new().label("-")
+                                           .right_from(ids.view_dock_buttons[i], WIDGET_DISTANCE)
+                                           .w_h(RESIZE_BUTTON_WIDTH, ROW_HEIGHT)
+                                           .set(ids.view_shrink_buttons[i], ui)
+        {
+            panel_layout.resize(false);
+            *config_changed = true;
+        }
+
+        for _press in widget::Button::new().label("+")
+                                           .right_from(ids.view_shrink_buttons[i], WIDGET_DISTANCE)
+                                           .w_h(RESIZE_BUTTON_WIDTH, ROW_HEIGHT)
+                                           .set(ids.view_grow_buttons[i], ui)
+        {
+            panel_layout.resize(true);
+            *config_changed = true;
+        }
+    }
+
+    let top_margin = MARGIN + Panel::ALL.len() as f64 * (ROW_HEIGHT + WIDGET_DISTANCE);
+    for _press in widget::Button::new().label(settings.theme.label())
+                                       .top_left_with_margins_on(ids.view_panel_canvas, top_margin, MARGIN)
+                                       .w_h(TOGGLE_WIDTH, ROW_HEIGHT)
+                                       .set(ids.view_theme_button, ui)
+    {
+        settings.theme = settings.theme.next();
+        *config_changed = true;
+    }
+
+    widget::Text::new(&format!("scale: {:.2}", settings.ui_scale)).right_from(ids.view_theme_button, WIDGET_DISTANCE)
+                                                                   .w(DOCK_BUTTON_WIDTH)
+                                                                   .set(ids.view_ui_scale_text, ui);
+
+    for _press in widget::Button::new().label("-")
+                                       .right_from(ids.view_ui_scale_text, WIDGET_DISTANCE)
+                                       .w_h(RESIZE_BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.view_ui_scale_down_button, ui)
+    {
+        settings.change_ui_scale(false);
+        *config_changed = true;
+    }
+
+    for _press in widget::Button::new().label("+")
+                                       .right_from(ids.view_ui_scale_down_button, WIDGET_DISTANCE)
+                                       .w_h(RESIZE_BUTTON_WIDTH, ROW_HEIGHT)
+                                       .set(ids.view_ui_scale_up_button, ui)
+    {
+        settings.change_ui_scale(true);
+        *config_changed = true;
+    }
+}
+
+fn exit_prompt(ids: &Ids, ui: &mut conrod_core::UiCell,
+               current_editor_state: &mut EditorState,
+               pending_file_actions: &mut Vec<FileAction>) {
+    const PROMPT_TITLE: &str = "Unsaved changes";
+    const PROMPT_TEXT: &str = "Scene has unsaved changes. Save before exiting?";
+    const PROMPT_WIDTH: f64 = 400.0;
+    const PROMPT_HEIGHT: f64 = 150.0;
+
+    const MARGIN: f64 = 20.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const BUTTON_WIDTH: f64 = 80.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+
+    widget::Canvas::new().floating(true).middle()
+                         .w_h(PROMPT_WIDTH, PROMPT_HEIGHT)
+                         .title_bar(PROMPT_TITLE)
+                         .set(ids.exit_prompt_canvas, ui);
+
+    widget::Text::new(PROMPT_TEXT).mid_top_with_margin_on(ids.exit_prompt_canvas, MARGIN)
+                                  .set(ids.exit_prompt_text, ui);
+
+    if let Some(error) = &current_editor_state.exit_prompt_error {
+        widget::Text::new(error).down_from(ids.exit_prompt_text, WIDGET_DISTANCE)
+                                .align_middle_x_of(ids.exit_prompt_text)
+                                .font_size(12)
+                                .color(color::RED)
+                                .set(ids.exit_prompt_error_text, ui);
+    }
+
+    for _press in widget::Button::new().label("Save")
+                                       .bottom_left_with_margin_on(ids.exit_prompt_canvas, MARGIN)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.exit_prompt_save_button, ui)
+    {
+        current_editor_state.show_exit_prompt = false;
+        pending_file_actions.push(FileAction::Exit { save: true });
+    }
+
+    for _press in widget::Button::new().label("Discard")
+                                       .right_from(ids.exit_prompt_save_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.exit_prompt_discard_button, ui)
+    {
+        current_editor_state.show_exit_prompt = false;
+        pending_file_actions.push(FileAction::Exit { save: false });
+    }
+
+    for _press in widget::Button::new().label("Cancel")
+                                       .right_from(ids.exit_prompt_discard_button, WIDGET_DISTANCE)
+                                       .w_h(BUTTON_WIDTH, BUTTON_HEIGHT)
+                                       .set(ids.exit_prompt_cancel_button, ui)
+    {
+        current_editor_state.show_exit_prompt = false;
+    }
+}
+
+/// Draws outline of the box selection. Corners are measured from the top left corner of the window.
+/// Draws box selection rectangle. Corners are given in window coordinates, which are converted to GUI points using user scale.
+fn selection_box(ids: &Ids, ui: &mut conrod_core::UiCell, settings: &EditorSettings, corner: (f32, f32), opposite_corner: (f32, f32)) {
+    let scale = settings.ui_scale;
+    let left = (corner.0.min(opposite_corner.0) / scale) as f64;
+    let top = (corner.1.min(opposite_corner.1) / scale) as f64;
+    let width = ((corner.0 - opposite_corner.0).abs() / scale) as f64;
+    let height = ((corner.1 - opposite_corner.1).abs() / scale) as f64;
+
+    widget::Rectangle::outline([width, height]).top_left_with_margins_on(ui.window, top, left)
+                                               .color(settings.theme.primary_selection_color())
+                                               .set(ids.selection_box, ui);
+}
+
+fn hierarchy_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                   placement: PanelPlacement,
+                   theme: EditorTheme,
+                   ctrl_pressed: bool,
+                   current_editor_state: &mut EditorState,
+                   selection: &mut Selection) {
+    const ITEM_HEIGHT: f64 = 25.0;
+    const INDENTATION: &str = "    ";
+
+    docked_canvas(ui, placement, Panel::Hierarchy.title()).set(ids.hierarchy_canvas, ui);
+
+    let (mut items, scrollbar) = widget::List::flow_down(current_editor_state.objects.len())
+                                              .item_size(ITEM_HEIGHT)
+                                              .scrollbar_on_top()
+                                              .middle_of(ids.hierarchy_canvas)
+                                              .wh_of(ids.hierarchy_canvas)
+                                              .set(ids.hierarchy_list, ui);
+
+    let mut clicked_object = None;
+    while let Some(item) = items.next(ui) {
+        let object = &current_editor_state.objects[item.i];
+        let button_color = if selection.primary() == Some(object.id) {
+            theme.primary_selection_color()
+        } else if selection.contains(object.id) {
+            theme.selection_color()
+        } else {
+            ui.theme.shape_color
+        };
+        let label = format!("{}{}", INDENTATION.repeat(current_editor_state.object_depth(object.id)), object.name);
+        let button = widget::Button::new().label(&label).color(button_color);
+        for _click in item.set(button, ui) {
+            clicked_object = Some(object.id);
+        }
+    }
+
+    if let Some(scrollbar) = scrollbar {
+        scrollbar.set(ui);
+    }
+
+    if let Some(id) = clicked_object {
+        if ctrl_pressed {
+            selection.toggle(id);
+        } else {
+            selection.select(id);
+        }
+        if let Some(primary_object) = selection.primary() {
+            current_editor_state.fill_object_text_boxes(primary_object);
+        }
+    }
+}
+
+fn inspector_panel(ids: &Ids, ui: &mut conrod_core::UiCell,
+                   placement: PanelPlacement,
+                   selection: &Selection,
+                   transform_mode: TransformMode,
+                   synced_editor_state: &EditorState, current_editor_state: &mut EditorState,
+                   pending_editor_events: &mut Vec<EditorEvent>,
+                   pending_selection_actions: &mut Vec<SelectionAction>) {
+    const MARGIN: f64 = 10.0;
+    const WIDGET_DISTANCE: f64 = 10.0;
+
+    const LABEL_WIDTH: f64 = 60.0;
+    const TEXT_BOX_WIDTH: f64 = 60.0;
+    const TEXT_BOX_HEIGHT: f64 = 25.0;
+    const BUTTON_HEIGHT: f64 = 25.0;
+    const LABEL_FONT_SIZE: u32 = 12;
+
+    let selected_object = match selection.primary() {
+        Some(selected_object) => selected_object,
+        None => return,
+    };
+
+    docked_canvas(ui, placement, Panel::Inspector.title()).set(ids.inspector_canvas, ui);
+
+    widget::Text::new("name:").top_left_with_margin_on(ids.inspector_canvas, MARGIN)
+                              .w(LABEL_WIDTH)
+                              .set(ids.object_name_label, ui);
+
+    let object_name_text_box = widget::TextBox::new(&current_editor_state.object_name_text_box_content)
+                                               .right_from(ids.object_name_label, WIDGET_DISTANCE)
+                                               .w_h(placement.width - LABEL_WIDTH - 2.0 * MARGIN - WIDGET_DISTANCE, TEXT_BOX_HEIGHT);
+
+    pending_editor_events.extend(object_name_text_box.set(ids.object_name_text_box, ui).into_iter()
+                                                     .filter_map(|event| object_name_text_box_event_execute(event, selected_object, current_editor_state)));
+
+    widget::Text::new("position:").down_from(ids.object_name_label, WIDGET_DISTANCE * 2.0)
+                                  .w(LABEL_WIDTH)
+                                  .set(ids.object_position_label, ui);
+
+    let x_object_position_text_box = widget::TextBox::new(&current_editor_state.x_object_position_text_box_content)
+                                                     .right_from(ids.object_position_label, WIDGET_DISTANCE)
+                                                     .w_h(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT);
+
+    pending_editor_events.extend(x_object_position_text_box.set(ids.x_object_position_text_box, ui).into_iter()
+                                                           .filter_map(|event| object_position_text_box_event_execute(event, "x", selection.objects(), synced_editor_state, current_editor_state)));
+
+    let y_object_position_text_box = widget::TextBox::new(&current_editor_state.y_object_position_text_box_content)
+                                                     .right_from(ids.x_object_position_text_box, WIDGET_DISTANCE)
+                                                     .w_h(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT);
+
+    pending_editor_events.extend(y_object_position_text_box.set(ids.y_object_position_text_box, ui).into_iter()
+                                                           .filter_map(|event| object_position_text_box_event_execute(event, "y", selection.objects(), synced_editor_state, current_editor_state)));
+
+    let z_object_position_text_box = widget::TextBox::new(&current_editor_state.z_object_position_text_box_content)
+                                                     .right_from(ids.y_object_position_text_box, WIDGET_DISTANCE)
+                                                     .w_h(TEXT_BOX_WIDTH, TEXT_BOX_HEIGHT);
+
+    pending_editor_events.extend(z_object_position_text_box.set(ids.z_object_position_text_box, ui).into_iter()
+                                                           .filter_map(|event| object_position_text_box_event_execute(event, "z", selection.objects(), synced_editor_state, current_editor_state)));
+
+    let selection_text = format!("{} selected, {} mode (T/R/Y to change)", selection.objects().len(), transform_mode.label());
+    widget::Text::new(&selection_text).down_from(ids.object_position_label, WIDGET_DISTANCE * 2.0)
+                                      .w(placement.width - 2.0 * MARGIN)
+                                      .set(ids.selection_text, ui);
+
+    let button_width = (placement.width - 2.0 * MARGIN - (SelectionAction::ALL.len() - 1) as f64 * WIDGET_DISTANCE) / SelectionAction::ALL.len() as f64;
+    for (index, selection_action) in SelectionAction::ALL.iter().enumerate() {
+        let button = widget::Button::new().label(selection_action.label())
+                                          .label_font_size(LABEL_FONT_SIZE)
+                                          .w_h(button_width, BUTTON_HEIGHT);
+        let button = if index == 0 {
+            button.down_from(ids.selection_text, WIDGET_DISTANCE)
+        } else {
+            button.right_from(ids.selection_action_buttons[index - 1], WIDGET_DISTANCE)
+        };
+        for _press in button.set(ids.selection_action_buttons[index], ui) {
+            pending_selection_actions.push(*selection_action);
+        }
+    }
+}
@@ -1,52 +1,164 @@
 use crate::editor_state::EditorState;
 use crate::editor_event::EditorEvent;
+use crate::editor_event::ObjectTransform;
+use crate::editor_event::LightProperties;
+use crate::material_action::MaterialAction;
 use conrod_core::widget::text_box;
 
 use log::*;
 
-pub fn light_text_box_event_execute(event: text_box::Event, 
+/// Handles events of light panel text boxes. Every valid change is previewed immediately,
+/// pressing enter commits the change.
+pub fn light_text_box_event_execute(event: text_box::Event,
                                     updated_text_box: &str,
-                                    synced_editor_state: &EditorState, 
-                                    current_editor_state: &mut EditorState) -> Option<EditorEvent> {
+                                    selected_light: u32,
+                                    synced_editor_state: &EditorState,
+                                    current_editor_state: &mut EditorState,
+                                    pending_light_previews: &mut Vec<(u32, LightProperties)>) -> Option<EditorEvent> {
     match event {
         text_box::Event::Enter => {
-            let x = match current_editor_state.x_light_text_box_content.parse() {
-                Ok(x) => x,
-                Err(err) => {
-                    error!("Couldn't parse x light source text box: {}", err);
-                    current_editor_state.x_light_text_box_content = synced_editor_state.x_light_text_box_content.clone();
-                    current_editor_state.x_light_text_box_content.parse().unwrap()
-                }
-            };
-
-            let y = match current_editor_state.y_light_text_box_content.parse() {
-                Ok(y) => y,
-                Err(err) => {
-                    error!("Couldn't parse y light source text box: {}", err);
-                    current_editor_state.y_light_text_box_content = synced_editor_state.y_light_text_box_content.clone();
-                    current_editor_state.y_light_text_box_content.parse().unwrap()
-                }
-            };
-
-            let z = match current_editor_state.z_light_text_box_content.parse() {
-                Ok(z) => z,
-                Err(err) => {
-                    error!("Couldn't parse z light source text box: {}", err);
-                    current_editor_state.z_light_text_box_content = synced_editor_state.z_light_text_box_content.clone();
-                    current_editor_state.z_light_text_box_content.parse().unwrap()
-                }
-            };
-
-            Some(EditorEvent::LightPositionChanged((x, y, z)))
+            let synced_properties = synced_editor_state.lights.iter().find(|x| x.id == selected_light)?.properties;
+            let (synced_x, synced_y, synced_z) = synced_properties.position;
+            let (synced_r, synced_g, synced_b) = synced_properties.color;
+
+            let x = parse_text_box(&mut current_editor_state.x_light_text_box_content, synced_x, "x light position");
+            let y = parse_text_box(&mut current_editor_state.y_light_text_box_content, synced_y, "y light position");
+            let z = parse_text_box(&mut current_editor_state.z_light_text_box_content, synced_z, "z light position");
+            let r = parse_text_box(&mut current_editor_state.r_light_color_text_box_content, synced_r, "r light color");
+            let g = parse_text_box(&mut current_editor_state.g_light_color_text_box_content, synced_g, "g light color");
+            let b = parse_text_box(&mut current_editor_state.b_light_color_text_box_content, synced_b, "b light color");
+            let intensity = parse_text_box(&mut current_editor_state.light_intensity_text_box_content, synced_properties.intensity, "light intensity");
+            let range = parse_text_box(&mut current_editor_state.light_range_text_box_content, synced_properties.range, "light range");
+
+            Some(EditorEvent::LightChanged(selected_light, LightProperties { position: (x, y, z), color: (r, g, b), intensity, range }))
         },
         text_box::Event::Update(new_val) => {
             match updated_text_box {
                 "x" => current_editor_state.x_light_text_box_content = new_val,
                 "y" => current_editor_state.y_light_text_box_content = new_val,
                 "z" => current_editor_state.z_light_text_box_content = new_val,
+                "r" => current_editor_state.r_light_color_text_box_content = new_val,
+                "g" => current_editor_state.g_light_color_text_box_content = new_val,
+                "b" => current_editor_state.b_light_color_text_box_content = new_val,
+                "intensity" => current_editor_state.light_intensity_text_box_content = new_val,
+                "range" => current_editor_state.light_range_text_box_content = new_val,
+                _   => panic!("updated_text_box field should have value x, y, z, r, g, b, intensity or range!")
+            }
+            if let Some(properties) = light_properties_from_text_boxes(current_editor_state) {
+                pending_light_previews.push((selected_light, properties));
+            }
+            None
+        }
+    }
+}
+
+/// Parses light properties from light panel text boxes. Returns None if any of them is invalid.
+fn light_properties_from_text_boxes(current_editor_state: &EditorState) -> Option<LightProperties> {
+    Some(LightProperties {
+        position: (current_editor_state.x_light_text_box_content.parse().ok()?,
+                   current_editor_state.y_light_text_box_content.parse().ok()?,
+                   current_editor_state.z_light_text_box_content.parse().ok()?),
+        color: (current_editor_state.r_light_color_text_box_content.parse().ok()?,
+                current_editor_state.g_light_color_text_box_content.parse().ok()?,
+                current_editor_state.b_light_color_text_box_content.parse().ok()?),
+        intensity: current_editor_state.light_intensity_text_box_content.parse().ok()?,
+        range: current_editor_state.light_range_text_box_content.parse().ok()?,
+    })
+}
+
+/// Parses text box content. If it is invalid, content is reset to synced value, which is returned.
+fn parse_text_box(text_box_content: &mut String, synced_value: f32, text_box_name: &str) -> f32 {
+    text_box_content.parse().unwrap_or_else(|err| {
+        error!("Couldn't parse {} text box: {}", text_box_name, err);
+        *text_box_content = synced_value.to_string();
+        synced_value
+    })
+}
+
+/// Handles events of inspector position text boxes. Primary selected object is moved to entered position,
+/// other selected objects are moved by the same offset.
+pub fn object_position_text_box_event_execute(event: text_box::Event,
+                                              updated_text_box: &str,
+                                              selected_objects: &[u32],
+                                              synced_editor_state: &EditorState,
+                                              current_editor_state: &mut EditorState) -> Option<EditorEvent> {
+    match event {
+        text_box::Event::Enter => {
+            let primary_object = *selected_objects.last()?;
+            let synced_transform = synced_editor_state.objects.iter().find(|x| x.id == primary_object)?.transform;
+            let (synced_x, synced_y, synced_z) = synced_transform.position;
+
+            let x = parse_text_box(&mut current_editor_state.x_object_position_text_box_content, synced_x, "x object position");
+            let y = parse_text_box(&mut current_editor_state.y_object_position_text_box_content, synced_y, "y object position");
+            let z = parse_text_box(&mut current_editor_state.z_object_position_text_box_content, synced_z, "z object position");
+            let offset = (x - synced_x, y - synced_y, z - synced_z);
+
+            // objects whose ancestor is also selected are moved by their ancestor
+            let events = synced_editor_state.objects.iter()
+                                                    .filter(|object| selected_objects.contains(&object.id))
+                                                    .filter(|object| object.id == primary_object || !selected_objects.iter().any(|x| synced_editor_state.is_ancestor(*x, object.id)))
+                                                    .map(|object| {
+                                                        let (object_x, object_y, object_z) = object.transform.position;
+                                                        let position = (object_x + offset.0, object_y + offset.1, object_z + offset.2);
+                                                        EditorEvent::ObjectTransformChanged(object.id, ObjectTransform { position, ..object.transform })
+                                                    })
+                                                    .collect();
+            Some(EditorEvent::Group(events))
+        },
+        text_box::Event::Update(new_val) => {
+            match updated_text_box {
+                "x" => current_editor_state.x_object_position_text_box_content = new_val,
+                "y" => current_editor_state.y_object_position_text_box_content = new_val,
+                "z" => current_editor_state.z_object_position_text_box_content = new_val,
                 _   => panic!("updated_text_box field should have value x, y or z!")
             }
             None
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn object_name_text_box_event_execute(event: text_box::Event,
+                                          selected_object: u32,
+                                          current_editor_state: &mut EditorState) -> Option<EditorEvent> {
+    match event {
+        text_box::Event::Enter => Some(EditorEvent::ObjectNameChanged(selected_object, current_editor_state.object_name_text_box_content.clone())),
+        text_box::Event::Update(new_val) => {
+            current_editor_state.object_name_text_box_content = new_val;
+            None
+        }
+    }
+}
+
+/// Handles events of material panel color text boxes. Every valid change is applied to the edited material immediately,
+/// pressing enter resets invalid text boxes.
+pub fn material_color_text_box_event_execute(event: text_box::Event,
+                                             updated_text_box: &str,
+                                             current_editor_state: &mut EditorState) -> Option<MaterialAction> {
+    let mut material = current_editor_state.edited_material.clone()?;
+    let (synced_r, synced_g, synced_b, synced_a) = material.base_color();
+    match event {
+        text_box::Event::Enter => {
+            let r = parse_text_box(&mut current_editor_state.r_material_color_text_box_content, synced_r, "r material color");
+            let g = parse_text_box(&mut current_editor_state.g_material_color_text_box_content, synced_g, "g material color");
+            let b = parse_text_box(&mut current_editor_state.b_material_color_text_box_content, synced_b, "b material color");
+            let a = parse_text_box(&mut current_editor_state.a_material_color_text_box_content, synced_a, "a material color");
+            material.set_base_color(r, g, b, a);
+            Some(MaterialAction::Changed(material))
+        },
+        text_box::Event::Update(new_val) => {
+            match updated_text_box {
+                "r" => current_editor_state.r_material_color_text_box_content = new_val,
+                "g" => current_editor_state.g_material_color_text_box_content = new_val,
+                "b" => current_editor_state.b_material_color_text_box_content = new_val,
+                "a" => current_editor_state.a_material_color_text_box_content = new_val,
+                _   => panic!("updated_text_box field should have value r, g, b or a!")
+            }
+            let r = current_editor_state.r_material_color_text_box_content.parse().ok()?;
+            let g = current_editor_state.g_material_color_text_box_content.parse().ok()?;
+            let b = current_editor_state.b_material_color_text_box_content.parse().ok()?;
+            let a = current_editor_state.a_material_color_text_box_content.parse().ok()?;
+            material.set_base_color(r, g, b, a);
+            Some(MaterialAction::Changed(material))
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+
+use ketch_core::resource::AssetManager;
+
+use crate::editor_event::EditorEvent;
+
+const DEFAULT_HISTORY_CAPACITY: usize = 100;
+
+/// Stores inverses of executed editor events, so they can be undone and redone.
+pub struct History {
+    undo_stack: VecDeque<EditorEvent>,
+    redo_stack: Vec<EditorEvent>,
+    capacity: usize,
+    // whether the last pushed event can still be merged with following changes of the same target
+    coalescing: bool,
+}
+
+impl History {
+    /// Creates new history with default capacity.
+    pub fn new() -> Self {
+        History::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+
+    /// Creates new history which remembers at most `capacity` operations.
+    pub fn with_capacity(capacity: usize) -> Self {
+        History {
+            undo_stack: VecDeque::with_capacity(capacity),
+            redo_stack: Vec::new(),
+            capacity,
+            coalescing: false,
+        }
+    }
+
+    /// Records inverse of a newly executed event. Clears redo history.
+    /// If the event changes the same light or material as the previous one, they're merged into a single operation,
+    /// until `end_coalescing` is called.
+    pub fn push(&mut self, inverse_event: EditorEvent) {
+        self.redo_stack.clear();
+        let coalesced = self.coalescing && self.undo_stack.back().map_or(false, |last_event| last_event.changes_same_target(&inverse_event));
+        // the inverse recorded first restores state from before all merged changes
+        if !coalesced {
+            self.push_undo(inverse_event);
+        }
+        self.coalescing = true;
+    }
+
+    /// Stops merging pushed events with the last recorded operation.
+    pub fn end_coalescing(&mut self) {
+        self.coalescing = false;
+    }
+
+    /// Undoes last operation. Returns true if there was anything to undo.
+    pub fn undo(&mut self, asset_manager: &mut AssetManager) -> bool {
+        self.undo_with(|event| event.execute(asset_manager))
+    }
+
+    /// Redoes last undone operation. Returns true if there was anything to redo.
+    pub fn redo(&mut self, asset_manager: &mut AssetManager) -> bool {
+        self.redo_with(|event| event.execute(asset_manager))
+    }
+
+    /// Removes all recorded operations.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.coalescing = false;
+    }
+
+    /// Returns number of operations which can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Returns number of operations which can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    fn undo_with<F: FnOnce(EditorEvent) -> Option<EditorEvent>>(&mut self, execute: F) -> bool {
+        self.coalescing = false;
+        match self.undo_stack.pop_back() {
+            Some(event) => {
+                if let Some(inverse_event) = execute(event) {
+                    self.redo_stack.push(inverse_event);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn redo_with<F: FnOnce(EditorEvent) -> Option<EditorEvent>>(&mut self, execute: F) -> bool {
+        self.coalescing = false;
+        match self.redo_stack.pop() {
+            Some(event) => {
+                if let Some(inverse_event) = execute(event) {
+                    self.push_undo(inverse_event);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn push_undo(&mut self, inverse_event: EditorEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(inverse_event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_event::{LightProperties, ObjectTransform};
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+    use ketch_core::resource::scene::Scene;
+
+    fn execute(event: EditorEvent, scene: &mut Scene, history: &mut History) {
+        let inverse_event = event.execute_in_scene(scene).unwrap();
+        history.push(inverse_event);
+    }
+
+    fn object_names(scene: &Scene) -> Vec<String> {
+        scene.objects().iter().map(|object| object.name().to_string()).collect()
+    }
+
+    #[test]
+    fn history_does_not_exceed_capacity() {
+        let mut history = History::with_capacity(2);
+
+        history.push(EditorEvent::LightRemoved(1));
+        history.push(EditorEvent::LightRemoved(2));
+        history.push(EditorEvent::LightRemoved(3));
+
+        assert_eq!(2, history.undo_len());
+    }
+
+    #[test]
+    fn clear_removes_all_operations() {
+        let mut history = History::new();
+
+        history.push(EditorEvent::LightRemoved(1));
+        history.clear();
+
+        assert_eq!(0, history.undo_len());
+        assert_eq!(0, history.redo_len());
+    }
+
+    #[test]
+    fn transform_change_is_undone_and_redone() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let object = ObjectBuilder::new("box").with_position(1.0, 2.0, 3.0).build();
+        let id = object.id();
+        scene.add_object(object);
+        let mut history = History::new();
+        let mut transform = ObjectTransform::from_object(scene.object(id).unwrap());
+        transform.position = (4.0, 5.0, 6.0);
+
+        execute(EditorEvent::ObjectTransformChanged(id, transform), &mut scene, &mut history);
+        assert_eq!((4.0, 5.0, 6.0), scene.object(id).unwrap().position());
+
+        assert!(history.undo_with(|event| event.execute_in_scene(&mut scene)));
+        assert_eq!((1.0, 2.0, 3.0), scene.object(id).unwrap().position());
+
+        assert!(history.redo_with(|event| event.execute_in_scene(&mut scene)));
+        assert_eq!((4.0, 5.0, 6.0), scene.object(id).unwrap().position());
+        assert_eq!(1, history.undo_len());
+        assert_eq!(0, history.redo_len());
+    }
+
+    #[test]
+    fn added_and_removed_objects_are_undone_and_redone() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let middle = ObjectBuilder::new("middle").build();
+        let middle_id = middle.id();
+        scene.add_object(ObjectBuilder::new("first").build());
+        scene.add_object(middle);
+        scene.add_object(ObjectBuilder::new("last").build());
+        let mut history = History::new();
+
+        execute(EditorEvent::ObjectAdded(ObjectBuilder::new("added").build()), &mut scene, &mut history);
+        execute(EditorEvent::ObjectRemoved(middle_id), &mut scene, &mut history);
+        assert_eq!(vec!["first", "last", "added"], object_names(&scene));
+
+        assert!(history.undo_with(|event| event.execute_in_scene(&mut scene)));
+        assert_eq!(vec!["first", "middle", "last", "added"], object_names(&scene));
+        assert!(history.undo_with(|event| event.execute_in_scene(&mut scene)));
+        assert_eq!(vec!["first", "middle", "last"], object_names(&scene));
+
+        assert!(history.redo_with(|event| event.execute_in_scene(&mut scene)));
+        assert!(history.redo_with(|event| event.execute_in_scene(&mut scene)));
+        assert_eq!(vec!["first", "last", "added"], object_names(&scene));
+    }
+
+    #[test]
+    fn consecutive_changes_of_the_same_light_are_undone_together() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let light_id = scene.main_light().unwrap().id();
+        let original_properties = LightProperties::from_light(scene.light(light_id).unwrap());
+        let mut history = History::new();
+
+        for intensity in &[2.0, 3.0, 4.0] {
+            let properties = LightProperties { intensity: *intensity, ..original_properties };
+            execute(EditorEvent::LightChanged(light_id, properties), &mut scene, &mut history);
+        }
+        assert_eq!(1, history.undo_len());
+
+        history.end_coalescing();
+        execute(EditorEvent::LightChanged(light_id, LightProperties { intensity: 5.0, ..original_properties }), &mut scene, &mut history);
+        assert_eq!(2, history.undo_len());
+
+        history.undo_with(|event| event.execute_in_scene(&mut scene));
+        assert_eq!(4.0, scene.light(light_id).unwrap().intensity());
+        history.undo_with(|event| event.execute_in_scene(&mut scene));
+        assert_eq!(original_properties.intensity, scene.light(light_id).unwrap().intensity());
+    }
+}
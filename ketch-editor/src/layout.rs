@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+const TOOLBAR_HEIGHT: f64 = 45.0;
+const SIZE_STEP: f64 = 1.1;
+const MIN_PANEL_SIZE: f64 = 50.0;
+
+/// Editor panels which can be docked and hidden.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Panel {
+    Light,
+    File,
+    Hierarchy,
+    Inspector,
+    Statistics,
+    Material,
+    Bookmarks,
+    Assets,
+    Validation,
+    Particles,
+    Terrain,
+    Profiler,
+    Console,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 13] = [
+        Panel::Light, Panel::File, Panel::Hierarchy, Panel::Inspector, Panel::Statistics, Panel::Material, Panel::Bookmarks, Panel::Assets,
+        Panel::Validation, Panel::Particles, Panel::Terrain, Panel::Profiler, Panel::Console,
+    ];
+
+    /// Returns title shown in the panel title bar.
+    pub fn title(self) -> &'static str {
+        match self {
+            Panel::Light => "Light",
+            Panel::File => "File",
+            Panel::Hierarchy => "Hierarchy",
+            Panel::Inspector => "Inspector",
+            Panel::Statistics => "Statistics",
+            Panel::Material => "Material",
+            Panel::Bookmarks => "Bookmarks",
+            Panel::Assets => "Assets",
+            Panel::Validation => "Validation",
+            Panel::Particles => "Particles",
+            Panel::Terrain => "Terrain",
+            Panel::Profiler => "Profiler",
+            Panel::Console => "Console",
+        }
+    }
+}
+
+/// Side of the window a panel is docked to.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum DockSide {
+    Left,
+    Right,
+    Bottom,
+}
+
+impl DockSide {
+    /// Returns next dock side, used to cycle through them.
+    pub fn next(self) -> DockSide {
+        match self {
+            DockSide::Left => DockSide::Right,
+            DockSide::Right => DockSide::Bottom,
+            DockSide::Bottom => DockSide::Left,
+        }
+    }
+}
+
+/// Dock side, size and visibility of a panel.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct PanelLayout {
+    pub dock: DockSide,
+    pub width: f64,
+    pub height: f64,
+    pub visible: bool,
+}
+
+impl PanelLayout {
+    /// Makes panel bigger or smaller by one step.
+    pub fn resize(&mut self, grow: bool) {
+        let factor = if grow { SIZE_STEP } else { 1.0 / SIZE_STEP };
+        self.width = (self.width * factor).max(MIN_PANEL_SIZE);
+        self.height = (self.height * factor).max(MIN_PANEL_SIZE);
+    }
+}
+
+/// Position and size of a panel in window coordinates, measured from the top left corner.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PanelPlacement {
+    pub top: f64,
+    pub left: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Layout of all editor panels. Panels docked to the same side are stacked in order of `Panel::ALL`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct EditorLayout {
+    panels: BTreeMap<Panel, PanelLayout>,
+}
+
+impl EditorLayout {
+    /// Creates default layout.
+    pub fn new() -> Self {
+        let mut panels = BTreeMap::new();
+        panels.insert(Panel::Light, PanelLayout { dock: DockSide::Left, width: 300.0, height: 190.0, visible: true });
+        panels.insert(Panel::File, PanelLayout { dock: DockSide::Left, width: 300.0, height: 100.0, visible: true });
+        panels.insert(Panel::Hierarchy, PanelLayout { dock: DockSide::Right, width: 300.0, height: 300.0, visible: true });
+        panels.insert(Panel::Inspector, PanelLayout { dock: DockSide::Right, width: 300.0, height: 170.0, visible: true });
+        panels.insert(Panel::Statistics, PanelLayout { dock: DockSide::Bottom, width: 300.0, height: 170.0, visible: false });
+        panels.insert(Panel::Material, PanelLayout { dock: DockSide::Left, width: 300.0, height: 400.0, visible: false });
+        panels.insert(Panel::Bookmarks, PanelLayout { dock: DockSide::Right, width: 300.0, height: 320.0, visible: false });
+        panels.insert(Panel::Assets, PanelLayout { dock: DockSide::Bottom, width: 300.0, height: 170.0, visible: false });
+        panels.insert(Panel::Validation, PanelLayout { dock: DockSide::Right, width: 300.0, height: 250.0, visible: false });
+        panels.insert(Panel::Particles, PanelLayout { dock: DockSide::Left, width: 300.0, height: 560.0, visible: false });
+        panels.insert(Panel::Terrain, PanelLayout { dock: DockSide::Right, width: 300.0, height: 300.0, visible: false });
+        panels.insert(Panel::Profiler, PanelLayout { dock: DockSide::Bottom, width: 300.0, height: 200.0, visible: false });
+        panels.insert(Panel::Console, PanelLayout { dock: DockSide::Bottom, width: 300.0, height: 200.0, visible: false });
+        EditorLayout {
+            panels,
+        }
+    }
+
+    /// Returns layout of given panel.
+    pub fn panel(&self, panel: Panel) -> PanelLayout {
+        match self.panels.get(&panel) {
+            Some(panel_layout) => *panel_layout,
+            None => EditorLayout::new().panels[&panel],
+        }
+    }
+
+    /// Returns mutable layout of given panel.
+    pub fn panel_mut(&mut self, panel: Panel) -> &mut PanelLayout {
+        let default_layout = EditorLayout::new().panels[&panel];
+        self.panels.entry(panel).or_insert(default_layout)
+    }
+
+    /// Computes where the panel should be placed in a window of given size. Returns None if panel is hidden.
+    pub fn placement(&self, panel: Panel, window_width: f64, window_height: f64) -> Option<PanelPlacement> {
+        let panel_layout = self.panel(panel);
+        if !panel_layout.visible {
+            return None;
+        }
+
+        let preceding_panels = Panel::ALL.iter()
+                                         .take_while(|x| **x != panel)
+                                         .map(|x| self.panel(*x))
+                                         .filter(|x| x.visible && x.dock == panel_layout.dock);
+
+        let placement = match panel_layout.dock {
+            DockSide::Left => PanelPlacement {
+                top: preceding_panels.map(|x| x.height).sum(),
+                left: 0.0,
+                width: panel_layout.width,
+                height: panel_layout.height,
+            },
+            DockSide::Right => PanelPlacement {
+                top: TOOLBAR_HEIGHT + preceding_panels.map(|x| x.height).sum::<f64>(),
+                left: window_width - panel_layout.width,
+                width: panel_layout.width,
+                height: panel_layout.height,
+            },
+            DockSide::Bottom => PanelPlacement {
+                top: window_height - panel_layout.height,
+                left: preceding_panels.map(|x| x.width).sum(),
+                width: panel_layout.width,
+                height: panel_layout.height,
+            },
+        };
+        Some(placement)
+    }
+}
+
+impl Default for EditorLayout {
+    fn default() -> Self {
+        EditorLayout::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panels_docked_to_the_same_side_are_stacked() {
+        let layout = EditorLayout::new();
+
+        let light_placement = layout.placement(Panel::Light, 1000.0, 800.0).unwrap();
+        let file_placement = layout.placement(Panel::File, 1000.0, 800.0).unwrap();
+
+        assert_eq!(0.0, light_placement.top);
+        assert_eq!(light_placement.height, file_placement.top);
+    }
+
+    #[test]
+    fn hidden_panel_has_no_placement_and_does_not_take_space() {
+        let mut layout = EditorLayout::new();
+        layout.panel_mut(Panel::Light).visible = false;
+
+        assert_eq!(None, layout.placement(Panel::Light, 1000.0, 800.0));
+        assert_eq!(0.0, layout.placement(Panel::File, 1000.0, 800.0).unwrap().top);
+    }
+
+    #[test]
+    fn panel_docked_right_touches_right_window_edge() {
+        let layout = EditorLayout::new();
+
+        let placement = layout.placement(Panel::Hierarchy, 1000.0, 800.0).unwrap();
+
+        assert_eq!(1000.0, placement.left + placement.width);
+    }
+}
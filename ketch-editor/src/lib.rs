@@ -1,14 +1,18 @@
+#![recursion_limit = "256"]
+
 use ketch_core::input::InputSystem;
+use ketch_core::input::cursor_icon::CursorIcon;
 use ketch_core::input::input_event::ElementState::Released;
 use ketch_core::input::input_event::ElementState::Pressed;
 use ketch_core::input::input_event::VirtualKeyCode;
 use ketch_core::input::input_event::MouseButton;
 use ketch_core::resource::camera::Direction;
 
-use crate::editor_state::EditorInputState;
+use crate::editor_state::{EditorInputState, LightSummary, ObjectSummary};
+use ketch_core::math::ray::Ray;
 use ketch_core::settings::Settings;
-use std::time::Duration;
-use crate::editor_event::EditorEvent;
+use std::time::{Duration, Instant};
+use crate::editor_event::{EditorEvent, LightProperties};
 use crate::editor_error::EditorCreationError;
 use ketch_core::resource::AssetManager;
 use vulkano::swapchain::Surface;
@@ -20,17 +24,80 @@ use ketch_core::input::input_event::InputEvent;
 use conrod_vulkano::Image;
 use vulkano::command_buffer::AutoCommandBufferBuilder;
 use ketch_core::renderer::Renderer;
+use ketch_core::renderer::renderer_error::RenderError;
 use conrod_core::render::Primitives;
 use editor_state::EditorState;
 
 use crate::widget_ids::Ids;
+use crate::history::History;
+use crate::light_gizmo::LIGHT_GIZMO_RADIUS;
+use crate::editor_settings::EditorSettings;
+use crate::editor_config::EditorConfig;
+use crate::layout::Panel;
+use ketch_core::renderer::debug_draw::DebugDraw;
+use ketch_core::renderer::renderer_stats::RendererStats;
+use ketch_core::profiling::{self, ProfileFrame};
+use ketch_core::logging::{LogCapture, LogRecord};
+use crate::file_action::FileAction;
+use crate::play_action::{PlayAction, PlayState};
+use crate::create_action::CreateAction;
+use crate::selection::Selection;
+use crate::selection_action::SelectionAction;
+use crate::object_drag::{ObjectDrag, TransformMode};
+use crate::material_action::MaterialAction;
+use crate::particle_action::ParticleAction;
+use crate::terrain_action::TerrainAction;
+use crate::terrain_brush::{TerrainBrush, TerrainTool};
+use ketch_core::resource::terrain::Terrain;
+use crate::camera_bookmark::{BookmarkAction, BOOKMARK_SLOTS};
+use crate::asset_preview::{AssetPreviewAction, PreviewedAsset};
+use crate::validation::{ValidationAction, ValidationReport};
+use ketch_core::renderer::asset_preview::{AssetPreview, TextureChannel, ASSET_PREVIEW_SIZE};
+use crate::scaling::ScaledWindow;
+use ketch_core::renderer::material_preview::{MaterialPreview, MATERIAL_PREVIEW_SIZE};
+use ketch_core::renderer::camera_preview::{CameraPreview, CAMERA_PREVIEW_HEIGHT, CAMERA_PREVIEW_WIDTH};
+use ketch_core::resource::camera::Camera;
+use ketch_core::resource::scene::Scene;
+use std::path::PathBuf;
 use conrod_core::Ui;
+use log::*;
 
 mod widget_ids;
 mod editor_state;
 mod editor_error;
 mod gui;
 mod editor_event;
+mod history;
+mod file_action;
+pub mod create_action;
+pub mod selection_action;
+mod selection;
+mod object_drag;
+mod material_action;
+mod particle_action;
+mod terrain_action;
+mod terrain_brush;
+mod camera_bookmark;
+mod asset_preview;
+mod validation;
+mod snapping;
+mod scaling;
+pub mod theme;
+pub mod play_action;
+pub mod editor_settings;
+mod grid;
+mod light_gizmo;
+mod camera_gizmo;
+mod light_edit;
+pub mod layout;
+mod editor_config;
+
+const EDITOR_CONFIG_PATH: &str = "editor_config.ron";
+const BYTES_PER_MEGABYTE: f64 = 1024.0 * 1024.0;
+/// Time between refreshes of spans shown in the Profiler panel.
+const PROFILER_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+/// Distance in pixels the cursor has to move with left mouse button pressed to start box selection.
+const BOX_SELECTION_THRESHOLD: f32 = 5.0;
 
 pub struct Editor {
     ui: Ui,
@@ -42,6 +109,52 @@ pub struct Editor {
     current_editor_state: EditorState,
     editor_input_state: EditorInputState,
     pending_editor_events: Vec<EditorEvent>,
+    history: History,
+    pending_file_actions: Vec<FileAction>,
+    pending_create_actions: Vec<CreateAction>,
+    scene_path: Option<PathBuf>,
+    unsaved_changes: bool,
+    exit_confirmed: bool,
+    play_state: PlayState,
+    pending_play_actions: Vec<PlayAction>,
+    pending_steps: u32,
+    scene_snapshot: Option<Scene>,
+    selection: Selection,
+    pending_selection_actions: Vec<SelectionAction>,
+    selection_box_start: Option<(f32, f32)>,
+    object_drag: Option<ObjectDrag>,
+    transform_mode: TransformMode,
+    edited_material: Option<String>,
+    pending_material_actions: Vec<MaterialAction>,
+    edited_particle_effect: Option<String>,
+    pending_particle_actions: Vec<ParticleAction>,
+    restarted_emitters: Vec<u32>,
+    edited_terrain: Option<String>,
+    terrain_brush: TerrainBrush,
+    terrain_stroke: Option<Terrain>,
+    pending_terrain_actions: Vec<TerrainAction>,
+    pending_bookmark_actions: Vec<BookmarkAction>,
+    material_preview: MaterialPreview,
+    material_preview_image: conrod_core::image::Id,
+    previewed_asset: Option<PreviewedAsset>,
+    pending_asset_preview_actions: Vec<AssetPreviewAction>,
+    asset_preview: AssetPreview,
+    asset_preview_image: conrod_core::image::Id,
+    camera_preview: CameraPreview,
+    camera_preview_image: conrod_core::image::Id,
+    selected_light: Option<u32>,
+    pending_light_previews: Vec<(u32, LightProperties)>,
+    light_preview_origin: Option<(u32, LightProperties)>,
+    dragged_light: Option<u32>,
+    config: EditorConfig,
+    config_changed: bool,
+    statistics_text: String,
+    profile_frame: ProfileFrame,
+    last_profiler_update: Instant,
+    log_records: Vec<LogRecord>,
+    log_records_total: u64,
+    validation_report: ValidationReport,
+    pending_validation_actions: Vec<ValidationAction>,
 }
 
 impl Editor {
@@ -54,17 +167,59 @@ impl Editor {
             None => return Err(EditorCreationError::SubpassCreationError),
         };
 
+        let config = match EditorConfig::load(EDITOR_CONFIG_PATH) {
+            Ok(config) => config,
+            Err(err) => {
+                info!("Couldn't load editor config from {}, using defaults: {}", EDITOR_CONFIG_PATH, err);
+                EditorConfig::default()
+            },
+        };
+        let scale_factor = scaling::ui_scale_factor(ketch_core::renderer::get_window_dpi(surface.window()), config.settings.ui_scale);
+
         let conrod_renderer = conrod_vulkano::Renderer::new(
             renderer.device(),
             subpass,
             renderer.queues().graphics_queue().family(),
             [window_dimensions.width as u32, window_dimensions.height as u32],
-            ketch_core::renderer::get_window_dpi(surface.window()),
+            scale_factor,
         )?;
 
-        let mut ui = conrod_core::UiBuilder::new([window_dimensions.width, window_dimensions.height]).theme(Editor::theme()).build();
-        let widget_ids = widget_ids::Ids::new(ui.widget_id_generator());
-        let image_map = conrod_core::image::Map::new();
+        let (ui_width, ui_height) = scaling::ui_size(window_dimensions.width, window_dimensions.height, scale_factor);
+        let mut ui = conrod_core::UiBuilder::new([ui_width, ui_height]).theme(config.settings.theme.conrod_theme()).build();
+        let mut widget_ids = widget_ids::Ids::new(ui.widget_id_generator());
+        widget_ids.view_visibility_toggles.resize(Panel::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.view_dock_buttons.resize(Panel::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.view_shrink_buttons.resize(Panel::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.view_grow_buttons.resize(Panel::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.create_menu_buttons.resize(CreateAction::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.selection_action_buttons.resize(SelectionAction::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.terrain_tool_toggles.resize(TerrainTool::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.particle_sliders.resize(gui::PARTICLE_SLIDER_COUNT, &mut ui.widget_id_generator());
+        widget_ids.bookmark_name_text_boxes.resize(BOOKMARK_SLOTS as usize, &mut ui.widget_id_generator());
+        widget_ids.bookmark_recall_buttons.resize(BOOKMARK_SLOTS as usize, &mut ui.widget_id_generator());
+        widget_ids.bookmark_save_buttons.resize(BOOKMARK_SLOTS as usize, &mut ui.widget_id_generator());
+        widget_ids.asset_preview_channel_buttons.resize(TextureChannel::ALL.len(), &mut ui.widget_id_generator());
+        widget_ids.profiler_span_rects.resize(gui::PROFILER_MAX_SPANS, &mut ui.widget_id_generator());
+        widget_ids.profiler_span_labels.resize(gui::PROFILER_MAX_SPANS, &mut ui.widget_id_generator());
+        let mut image_map = conrod_core::image::Map::new();
+        let material_preview = renderer.create_material_preview()?;
+        let material_preview_image = image_map.insert(Image {
+            image_access: material_preview.image(),
+            width: MATERIAL_PREVIEW_SIZE,
+            height: MATERIAL_PREVIEW_SIZE,
+        });
+        let asset_preview = renderer.create_asset_preview()?;
+        let asset_preview_image = image_map.insert(Image {
+            image_access: asset_preview.image(),
+            width: ASSET_PREVIEW_SIZE,
+            height: ASSET_PREVIEW_SIZE,
+        });
+        let camera_preview = renderer.create_camera_preview()?;
+        let camera_preview_image = image_map.insert(Image {
+            image_access: camera_preview.image(),
+            width: CAMERA_PREVIEW_WIDTH,
+            height: CAMERA_PREVIEW_HEIGHT,
+        });
         ui.fonts.insert_from_file("ketch-editor/assets/fonts/NotoSans-Regular.ttf")?;
 
         Ok(
@@ -80,32 +235,56 @@ impl Editor {
                 editor_input_state: EditorInputState::new(),
 
                 pending_editor_events: Vec::new(),
+                history: History::new(),
+                pending_file_actions: Vec::new(),
+                pending_create_actions: Vec::new(),
+                scene_path: None,
+                unsaved_changes: false,
+                exit_confirmed: false,
+                play_state: PlayState::Stopped,
+                pending_play_actions: Vec::new(),
+                pending_steps: 0,
+                scene_snapshot: None,
+                selection: Selection::new(),
+                pending_selection_actions: Vec::new(),
+                selection_box_start: None,
+                object_drag: None,
+                transform_mode: TransformMode::Translate,
+                edited_material: None,
+                pending_material_actions: vec![MaterialAction::Reload],
+                edited_particle_effect: None,
+                pending_particle_actions: vec![ParticleAction::Reload],
+                restarted_emitters: Vec::new(),
+                edited_terrain: None,
+                terrain_brush: TerrainBrush::new(),
+                terrain_stroke: None,
+                pending_terrain_actions: vec![TerrainAction::Reload],
+                pending_bookmark_actions: Vec::new(),
+                material_preview,
+                material_preview_image,
+                previewed_asset: None,
+                pending_asset_preview_actions: Vec::new(),
+                asset_preview,
+                asset_preview_image,
+                camera_preview,
+                camera_preview_image,
+                selected_light: None,
+                pending_light_previews: Vec::new(),
+                light_preview_origin: None,
+                dragged_light: None,
+                config,
+                config_changed: false,
+                statistics_text: String::new(),
+                profile_frame: ProfileFrame::default(),
+                last_profiler_update: Instant::now(),
+                log_records: Vec::new(),
+                log_records_total: 0,
+                validation_report: ValidationReport::default(),
+                pending_validation_actions: Vec::new(),
             }
         )
     }
 
-    pub fn theme() -> conrod_core::Theme {
-        use conrod_core::position::{Align, Direction, Padding, Position, Relative};
-        conrod_core::Theme {
-            name: "Ketch Editor Theme".to_string(),
-            padding: Padding::none(),
-            x_position: Position::Relative(Relative::Align(Align::Start), None),
-            y_position: Position::Relative(Relative::Direction(Direction::Backwards, 20.0), None),
-            background_color: conrod_core::color::DARK_CHARCOAL,
-            shape_color: conrod_core::color::LIGHT_CHARCOAL,
-            border_color: conrod_core::color::BLACK,
-            border_width: 0.0,
-            label_color: conrod_core::color::WHITE,
-            font_id: None,
-            font_size_large: 26,
-            font_size_medium: 18,
-            font_size_small: 12,
-            widget_styling: conrod_core::theme::StyleMap::default(),
-            mouse_drag_threshold: 0.0,
-            double_click_threshold: std::time::Duration::from_millis(500),
-        }
-    }
-
     pub fn draw_ui(&self) -> Primitives {
         self.ui.draw()
     }
@@ -121,16 +300,34 @@ impl Editor {
     fn handle_camera_input(&mut self, input_events: Vec<InputEvent>, input_system: &mut InputSystem) {
         input_events.into_iter().for_each(|event| {
             match event {
-                InputEvent::KeyboardInput { keycode, state } => match keycode {
-                    VirtualKeyCode::W if state == Pressed => self.editor_input_state.up = true,
-                    VirtualKeyCode::S if state == Pressed => self.editor_input_state.down = true,
-                    VirtualKeyCode::A if state == Pressed => self.editor_input_state.left = true,
-                    VirtualKeyCode::D if state == Pressed => self.editor_input_state.right = true,
-                    VirtualKeyCode::W if state == Released => self.editor_input_state.up = false,
-                    VirtualKeyCode::S if state == Released => self.editor_input_state.down = false,
-                    VirtualKeyCode::A if state == Released => self.editor_input_state.left = false,
-                    VirtualKeyCode::D if state == Released => self.editor_input_state.right = false,
-                    _ => (),
+                InputEvent::KeyboardInput { keycode, state } => {
+                    let handled = self.editor_input_state.handle_key(keycode, state, &mut self.pending_selection_actions);
+                    match keycode {
+                        _ if handled => (),
+                        VirtualKeyCode::Delete if state == Pressed && self.ui.global_input().current.widget_capturing_keyboard.is_none() => {
+                            self.pending_selection_actions.push(SelectionAction::Delete);
+                        },
+                        VirtualKeyCode::T | VirtualKeyCode::R | VirtualKeyCode::Y
+                            if state == Pressed && !self.editor_input_state.ctrl && self.ui.global_input().current.widget_capturing_keyboard.is_none() => {
+                            self.transform_mode = match keycode {
+                                VirtualKeyCode::T => TransformMode::Translate,
+                                VirtualKeyCode::R => TransformMode::Rotate,
+                                _ => TransformMode::Scale,
+                            };
+                            self.update_gui();
+                        },
+                        _ if state == Pressed && self.ui.global_input().current.widget_capturing_keyboard.is_none() => {
+                            // number keys recall camera bookmarks, with ctrl they save current camera to the bookmark
+                            if let Some(slot) = camera_bookmark::slot_from_key(keycode) {
+                                if self.editor_input_state.ctrl {
+                                    self.pending_bookmark_actions.push(BookmarkAction::Save(slot));
+                                } else {
+                                    self.pending_bookmark_actions.push(BookmarkAction::Recall(slot));
+                                }
+                            }
+                        },
+                        _ => (),
+                    }
                 },
                 InputEvent::MouseInput { button, state } => match button {
                     MouseButton::Right if state == Pressed => {
@@ -143,8 +340,22 @@ impl Editor {
                         input_system.grab_cursor(false);
                         input_system.hide_cursor(false);
                     }
+                    MouseButton::Left if state == Pressed => {
+                        self.editor_input_state.left_mouse_button_pressed = true;
+                        // every click starts a new edit, e.g. grabbing a slider again is undone separately
+                        self.history.end_coalescing();
+                        if self.ui.global_input().current.widget_under_mouse.is_none() {
+                            self.editor_input_state.pick_requested = true;
+                        }
+                    }
+                    MouseButton::Left if state == Released => {
+                        self.editor_input_state.left_mouse_button_pressed = false;
+                    }
                     _ => (),
                 }
+                InputEvent::CursorMoved { x, y } => {
+                    self.editor_input_state.cursor_position = (x as f32, y as f32);
+                },
                 InputEvent::MouseMotion { delta } => {
                     self.editor_input_state.mouse_delta_changed = true;
                     self.editor_input_state.mouse_delta = (delta.0 as f32, delta.1 as f32);
@@ -157,21 +368,41 @@ impl Editor {
     pub fn handle_input(&mut self, input_events: Vec<Event>, input_system: &mut InputSystem) {
         self.handle_camera_input(ketch_core::input::convert_to_input_events(input_events.clone()), input_system);
         if let Some(window) = input_system.window() {
-            input_events.into_iter().filter_map(|event| conrod_winit::convert_event(event, window))
+            let scaled_window = ScaledWindow { window, user_scale: self.config.settings.ui_scale };
+            input_events.into_iter().filter_map(|event| conrod_winit::convert_event(event, &scaled_window))
                                     .for_each(|event| self.ui.handle_event(event));
         }
+        self.update_ui_size();
         if self.ui.global_input().events().next().is_some() {
             self.update_gui();
         }
+        input_system.set_cursor_icon(self.cursor_icon());
+    }
+
+    /// Returns cursor icon showing what dragging in the viewport currently does.
+    fn cursor_icon(&self) -> CursorIcon {
+        if self.object_drag.is_some() {
+            match self.transform_mode {
+                TransformMode::Translate => CursorIcon::Move,
+                TransformMode::Rotate => CursorIcon::Grabbing,
+                TransformMode::Scale => CursorIcon::ResizeDiagonal,
+            }
+        } else if self.dragged_light.is_some() {
+            CursorIcon::Move
+        } else if self.selection_box_start.is_some() {
+            CursorIcon::Crosshair
+        } else {
+            CursorIcon::Arrow
+        }
     }
 
     pub fn add_glyph_commands(&mut self, mut command_buffer_builder: AutoCommandBufferBuilder) -> AutoCommandBufferBuilder {
         let primitives = self.ui.draw();
         let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
-        let dpi = ketch_core::renderer::get_window_dpi(self.surface.window());
+        let scale_factor = self.ui_scale_factor();
 
         let viewport = [0.0, 0.0, window_dimensions.width as f32, window_dimensions.height as f32];
-        let mut cmds = self.conrod_renderer.fill(&self.image_map, viewport, dpi, primitives).unwrap();
+        let mut cmds = self.conrod_renderer.fill(&self.image_map, viewport, scale_factor, primitives).unwrap();
 
         for cmd in cmds.commands.drain(..) {
             let buffer = cmds.glyph_cpu_buffer_pool.chunk(cmd.data.iter().cloned()).unwrap();
@@ -221,26 +452,71 @@ impl Editor {
 
     pub fn sync_editor(&mut self, asset_manager: &AssetManager) {
         let mut editor_state = EditorState::new();
+        editor_state.scene_path_text_box_content = self.current_editor_state.scene_path_text_box_content.clone();
+        editor_state.show_exit_prompt = self.current_editor_state.show_exit_prompt;
+        editor_state.exit_prompt_error = self.current_editor_state.exit_prompt_error.clone();
+        editor_state.show_view_panel = self.current_editor_state.show_view_panel;
+        editor_state.show_create_menu = self.current_editor_state.show_create_menu;
         if let Some(scene) = asset_manager.active_scene() {
-            let (light_x, light_y, light_z) = scene.light_position();
-            editor_state.x_light_text_box_content = light_x.to_string();
-            editor_state.y_light_text_box_content = light_y.to_string();
-            editor_state.z_light_text_box_content = light_z.to_string();
+            editor_state.lights = scene.lights().iter().map(LightSummary::from_light).collect();
+            editor_state.objects = scene.objects().iter().map(ObjectSummary::from_object).collect();
         }
+        self.selected_light = self.selected_light.filter(|id| editor_state.lights.iter().any(|x| x.id == *id))
+                                                 .or_else(|| editor_state.lights.first().map(|x| x.id));
+        if let Some(id) = self.selected_light {
+            editor_state.fill_light_text_boxes(id);
+        }
+        self.selection.retain(|id| editor_state.objects.iter().any(|x| x.id == *id));
+        if let Some(id) = self.selection.primary() {
+            editor_state.fill_object_text_boxes(id);
+        }
+        editor_state.material_names = asset_manager.material_names();
+        editor_state.texture_names = asset_manager.texture_names();
+        editor_state.mesh_names = asset_manager.mesh_names();
+        self.edited_material = self.edited_material.take().filter(|name| editor_state.material_names.contains(name))
+                                                          .or_else(|| editor_state.material_names.first().cloned());
+        editor_state.edited_material = self.edited_material.as_ref()
+                                                           .and_then(|name| asset_manager.material(name))
+                                                           .map(|material| material.read().unwrap().clone());
+        editor_state.fill_material_text_boxes();
+        editor_state.particle_effect_names = asset_manager.particle_effect_names();
+        self.edited_particle_effect = self.edited_particle_effect.take().filter(|name| editor_state.particle_effect_names.contains(name))
+                                                                        .or_else(|| editor_state.particle_effect_names.first().cloned());
+        editor_state.edited_particle_effect = self.edited_particle_effect.as_ref()
+                                                                         .and_then(|name| asset_manager.particle_effect(name))
+                                                                         .map(|effect| effect.read().unwrap().clone());
+        editor_state.terrain_names = asset_manager.terrain_names();
+        self.edited_terrain = self.edited_terrain.take().filter(|name| editor_state.terrain_names.contains(name))
+                                                        .or_else(|| editor_state.terrain_names.first().cloned());
+        editor_state.edited_terrain = self.edited_terrain.clone();
+        editor_state.bookmark_name_text_box_contents = (1..=BOOKMARK_SLOTS).map(|slot| {
+            self.config.camera_bookmarks.get(&slot).map(|bookmark| bookmark.name.clone()).unwrap_or_default()
+        }).collect();
         self.synced_editor_state = editor_state.clone();
         self.current_editor_state = editor_state;
     }
 
+    /// Returns true if game is currently running and should receive input and updates.
     pub fn run_game(&self) -> bool {
-        self.current_editor_state.run_game
+        self.play_state == PlayState::Playing
+    }
+
+    /// Returns current state of the game simulation.
+    pub fn play_state(&self) -> PlayState {
+        self.play_state
     }
 
-    pub fn set_run_game(&mut self, run_game: bool, asset_manager: Option<&AssetManager>) {
-        if !run_game {
-            assert!(asset_manager.is_some(), "Asset manager has to be supplied when going back to the editor to sync game state with the editor!");
-            self.sync_editor(asset_manager.unwrap());
+    /// Pauses running game and goes back to the editor. Game state is synced with the editor.
+    pub fn pause_game(&mut self, asset_manager: &AssetManager) {
+        if self.play_state == PlayState::Playing {
+            self.play_state = PlayState::Paused;
         }
-        self.current_editor_state.run_game = run_game;
+        self.sync_editor(asset_manager);
+    }
+
+    /// Returns number of single game updates requested by the user and resets it.
+    pub fn take_pending_steps(&mut self) -> u32 {
+        std::mem::replace(&mut self.pending_steps, 0)
     }
 
     fn update_camera(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) {
@@ -274,9 +550,341 @@ impl Editor {
         }
     } 
 
+    fn update_history(&mut self, asset_manager: &mut AssetManager) {
+        if self.editor_input_state.undo_requested || self.editor_input_state.redo_requested {
+            self.revert_light_preview(asset_manager);
+            self.cancel_object_drag(asset_manager);
+        }
+        let mut history_changed = false;
+        if self.editor_input_state.undo_requested {
+            history_changed |= self.history.undo(asset_manager);
+            self.editor_input_state.undo_requested = false;
+        }
+        if self.editor_input_state.redo_requested {
+            history_changed |= self.history.redo(asset_manager);
+            self.editor_input_state.redo_requested = false;
+        }
+        if history_changed {
+            self.unsaved_changes = true;
+            self.sync_editor(asset_manager);
+            self.update_gui();
+        }
+    }
+
+    fn update_selection(&mut self, asset_manager: &AssetManager) {
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => return,
+        };
+
+        if self.editor_input_state.pick_requested {
+            self.editor_input_state.pick_requested = false;
+            let ray = match self.cursor_ray(scene) {
+                Some(ray) => ray,
+                None => return,
+            };
+            match scene.pick_light(&ray, LIGHT_GIZMO_RADIUS) {
+                Some(light_id) => {
+                    self.selected_light = Some(light_id);
+                    self.dragged_light = Some(light_id);
+                    self.sync_editor(asset_manager);
+                    self.update_gui();
+                },
+                None => {
+                    let picked_object = scene.pick_object(&ray);
+                    match picked_object {
+                        Some(id) if self.selection.contains(id) && !self.editor_input_state.ctrl => self.start_object_drag(scene),
+                        _ => self.selection_box_start = Some(self.editor_input_state.cursor_position),
+                    }
+                },
+            }
+        }
+
+        if self.editor_input_state.left_mouse_button_pressed {
+            return;
+        }
+        if let Some(selection_box_start) = self.selection_box_start.take() {
+            let cursor_position = self.editor_input_state.cursor_position;
+            let box_size = (cursor_position.0 - selection_box_start.0).abs().max((cursor_position.1 - selection_box_start.1).abs());
+            let window_size = match self.surface.window().get_inner_size() {
+                Some(window_size) => window_size,
+                None => return,
+            };
+            if box_size < BOX_SELECTION_THRESHOLD {
+                let picked_object = self.cursor_ray(scene).and_then(|ray| scene.pick_object(&ray));
+                match picked_object {
+                    Some(id) if self.editor_input_state.ctrl => self.selection.toggle(id),
+                    Some(id) => self.selection.select(id),
+                    None if self.editor_input_state.ctrl => (),
+                    None => self.selection.clear(),
+                }
+            } else {
+                let boxed_objects = selection::objects_in_screen_rect(scene, selection_box_start, cursor_position,
+                                                                      window_size.width as f32, window_size.height as f32);
+                if self.editor_input_state.ctrl {
+                    boxed_objects.into_iter().for_each(|id| self.selection.add(id));
+                } else {
+                    self.selection.set(boxed_objects);
+                }
+            }
+            self.sync_editor(asset_manager);
+            self.update_gui();
+        }
+    }
+
+    /// Returns a ray going from the camera through the mouse cursor.
+    fn cursor_ray(&self, scene: &Scene) -> Option<Ray> {
+        self.ray_at(scene, self.editor_input_state.cursor_position)
+    }
+
+    /// Returns a ray going from the camera through given point on the screen.
+    fn ray_at(&self, scene: &Scene, screen_position: (f32, f32)) -> Option<Ray> {
+        let window_size = self.surface.window().get_inner_size()?;
+        let (x, y) = screen_position;
+        Some(Ray::from_screen_position(scene.camera(), x, y, window_size.width as f32, window_size.height as f32))
+    }
+
+    /// Returns editor settings.
+    pub fn settings(&self) -> &EditorSettings {
+        &self.config.settings
+    }
+
+    /// Returns mutable editor settings. Changed settings are saved to the editor config file.
+    pub fn settings_mut(&mut self) -> &mut EditorSettings {
+        self.config_changed = true;
+        &mut self.config.settings
+    }
+
+    /// Returns factor the GUI is scaled by, combining window DPI with user scale.
+    fn ui_scale_factor(&self) -> f64 {
+        scaling::ui_scale_factor(ketch_core::renderer::get_window_dpi(self.surface.window()), self.config.settings.ui_scale)
+    }
+
+    /// Resizes the GUI to match window size and GUI scale. Window resize events converted by conrod don't take user scale into account.
+    fn update_ui_size(&mut self) {
+        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
+        let (ui_width, ui_height) = scaling::ui_size(window_dimensions.width, window_dimensions.height, self.ui_scale_factor());
+        if ui_width != self.ui.win_w || ui_height != self.ui.win_h {
+            self.ui.handle_event(conrod_core::event::Input::Resize(ui_width, ui_height));
+            self.update_gui();
+        }
+    }
+
+    /// Applies GUI theme and scale from editor settings.
+    fn apply_appearance(&mut self) {
+        self.ui.theme = self.config.settings.theme.conrod_theme();
+        self.update_ui_size();
+    }
+
+    fn save_config(&mut self) {
+        self.config_changed = false;
+        if let Err(err) = self.config.save(EDITOR_CONFIG_PATH) {
+            error!("Couldn't save editor config to {}: {}", EDITOR_CONFIG_PATH, err);
+        }
+    }
+
+    /// Adds editor helpers, like the ground grid and light gizmos, to debug draw.
+    pub fn draw_helpers(&self, debug_draw: &mut DebugDraw, asset_manager: &AssetManager) {
+        if let Some(scene) = asset_manager.active_scene() {
+            if self.config.settings.show_grid {
+                grid::draw_grid(debug_draw, scene.camera().position_vec3(), &self.config.settings);
+            }
+            for light in scene.lights() {
+                light_gizmo::draw_light_gizmo(debug_draw, light, self.selected_light == Some(light.id()));
+            }
+            let aspect_ratio = self.window_aspect_ratio();
+            for camera in self.selection.objects().iter().filter_map(|id| scene.object(*id)).filter_map(|object| scene.object_camera(object)) {
+                camera_gizmo::draw_camera_gizmo(debug_draw, &camera, aspect_ratio);
+            }
+        }
+    }
+
+    /// Updates runtime statistics shown in the Statistics panel.
+    pub fn update_statistics(&mut self, renderer_stats: RendererStats, asset_manager: &AssetManager) {
+        if !self.config.layout.panel(Panel::Statistics).visible {
+            return;
+        }
+        let (object_count, light_count) = match asset_manager.active_scene() {
+            Some(scene) => (scene.objects().len(), scene.lights().len()),
+            None => (0, 0),
+        };
+        let (mesh_memory_allocated, mesh_memory_used) = asset_manager.uploader().map(|uploader| uploader.mesh_memory_usage()).unwrap_or((0, 0));
+        let mesh_memory_blocks = asset_manager.uploader().map(|uploader| uploader.mesh_memory_blocks()).unwrap_or(0);
+        let statistics_text = format!(
+            "objects: {} ({} rendered, {} culled)\ninstances: {}\ntriangles: {}\nlights: {}\ntransforms updated: {}\ndraw calls: {}\ndescriptor sets created: {}\nuniform buffers allocated: {}\n\
+             meshes: {}, textures: {}\nestimated VRAM: {:.1} MB\nmesh memory blocks: {} ({:.1} of {:.1} MB used)\nswapchain recreations: {}",
+            object_count, renderer_stats.objects_rendered, renderer_stats.objects_culled,
+            renderer_stats.instances_rendered,
+            renderer_stats.triangles_rendered,
+            light_count,
+            renderer_stats.transforms_updated,
+            renderer_stats.draw_calls,
+            renderer_stats.descriptor_sets_created,
+            renderer_stats.uniform_buffers_allocated,
+            asset_manager.mesh_count(), asset_manager.texture_count(),
+            asset_manager.estimated_memory_usage() as f64 / BYTES_PER_MEGABYTE,
+            mesh_memory_blocks,
+            mesh_memory_used as f64 / BYTES_PER_MEGABYTE, mesh_memory_allocated as f64 / BYTES_PER_MEGABYTE,
+            renderer_stats.swapchain_recreations,
+        );
+        if statistics_text != self.statistics_text {
+            self.statistics_text = statistics_text;
+            self.update_gui();
+        }
+    }
+
+    /// Updates spans of the last frame shown in the Profiler panel. Spans are refreshed a few times per second, so that they can be read.
+    pub fn update_profiler(&mut self) {
+        if !profiling::ENABLED || !self.config.layout.panel(Panel::Profiler).visible {
+            return;
+        }
+        if self.last_profiler_update.elapsed() < PROFILER_REFRESH_INTERVAL {
+            return;
+        }
+        self.last_profiler_update = Instant::now();
+        self.profile_frame = profiling::last_frame();
+        self.update_gui();
+    }
+
+    /// Updates records shown in the Console panel, if anything was logged since the last update.
+    pub fn update_console(&mut self, log_capture: &LogCapture) {
+        if !self.config.layout.panel(Panel::Console).visible || log_capture.total() == self.log_records_total {
+            return;
+        }
+        self.log_records_total = log_capture.total();
+        self.log_records = log_capture.records();
+        self.update_gui();
+    }
+
+    /// Returns game camera attached to the primary selected object, placed at the object position.
+    fn previewed_camera(&self, asset_manager: &AssetManager) -> Option<Camera> {
+        let scene = asset_manager.active_scene()?;
+        scene.object_camera(scene.object(self.selection.primary()?)?)
+    }
+
+    fn window_aspect_ratio(&self) -> f32 {
+        let window_dimensions = ketch_core::renderer::get_window_dimensions(self.surface.window());
+        (window_dimensions.width / window_dimensions.height) as f32
+    }
+
+    /// Adds commands rendering preview of the edited material while the Material panel is visible,
+    /// of the asset shown in the preview window and of what the selected game camera sees.
+    /// Has to be called before the scene is rendered.
+    pub fn add_preview_commands(&self, renderer: &mut Renderer, mut command_buffer: AutoCommandBufferBuilder,
+                                asset_manager: &mut AssetManager) -> Result<AutoCommandBufferBuilder, RenderError> {
+        if let Some(material) = self.current_editor_state.edited_material.as_ref().filter(|_| self.config.layout.panel(Panel::Material).visible) {
+            command_buffer = renderer.add_material_preview_commands(command_buffer, &self.material_preview, material, asset_manager)?;
+        }
+        if let Some(camera) = self.previewed_camera(asset_manager) {
+            command_buffer = renderer.add_camera_preview_commands(command_buffer, &self.camera_preview, &camera, self.window_aspect_ratio(), asset_manager)?;
+        }
+        match &self.previewed_asset {
+            Some(PreviewedAsset::Texture { name, channel, mip_level, .. }) => match asset_manager.texture(name) {
+                Some(texture) => renderer.add_texture_preview_commands(command_buffer, &self.asset_preview, &texture, *channel, *mip_level),
+                None => Ok(command_buffer),
+            },
+            Some(PreviewedAsset::Mesh { name, rotation, wireframe }) => match asset_manager.mesh(name) {
+                Some(mesh) => renderer.add_mesh_preview_commands(command_buffer, &self.asset_preview, &mesh.read().unwrap(), *rotation, *wireframe),
+                None => Ok(command_buffer),
+            },
+            None => Ok(command_buffer),
+        }
+    }
+
+    /// Returns ids of objects selected in the editor.
+    pub fn selected_objects(&self) -> Vec<u32> {
+        self.selection.objects().to_vec()
+    }
+
     pub fn update(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) {
         self.update_camera(asset_manager, update_time_delta);
-        self.pending_editor_events.drain(..).for_each(|event| event.execute(asset_manager));
+        self.update_terrain_brush(asset_manager, update_time_delta);
+        self.update_selection(asset_manager);
+        self.update_lights(asset_manager);
+        self.update_object_drag(asset_manager);
+        let create_actions: Vec<CreateAction> = self.pending_create_actions.drain(..).collect();
+        for create_action in create_actions {
+            self.execute_create_action(create_action, asset_manager);
+        }
+        let selection_actions: Vec<SelectionAction> = self.pending_selection_actions.drain(..).collect();
+        for selection_action in selection_actions {
+            self.execute_selection_action(selection_action, asset_manager);
+        }
+        let material_actions: Vec<MaterialAction> = self.pending_material_actions.drain(..).collect();
+        for material_action in material_actions {
+            self.execute_material_action(material_action, asset_manager);
+        }
+        let particle_actions: Vec<ParticleAction> = self.pending_particle_actions.drain(..).collect();
+        for particle_action in particle_actions {
+            self.execute_particle_action(particle_action, asset_manager);
+        }
+        let terrain_actions: Vec<TerrainAction> = self.pending_terrain_actions.drain(..).collect();
+        for terrain_action in terrain_actions {
+            self.execute_terrain_action(terrain_action, asset_manager);
+        }
+        let bookmark_actions: Vec<BookmarkAction> = self.pending_bookmark_actions.drain(..).collect();
+        for bookmark_action in bookmark_actions {
+            self.execute_bookmark_action(bookmark_action, asset_manager);
+        }
+        let asset_preview_actions: Vec<AssetPreviewAction> = self.pending_asset_preview_actions.drain(..).collect();
+        for asset_preview_action in asset_preview_actions {
+            self.execute_asset_preview_action(asset_preview_action, asset_manager);
+        }
+        self.update_asset_preview(update_time_delta);
+        let validation_actions: Vec<ValidationAction> = self.pending_validation_actions.drain(..).collect();
+        for validation_action in validation_actions {
+            self.execute_validation_action(validation_action, asset_manager);
+        }
+        if !self.pending_editor_events.is_empty() {
+            self.revert_light_preview(asset_manager);
+        }
+        let mut scene_changed = false;
+        for event in self.pending_editor_events.drain(..) {
+            if let Some(inverse_event) = event.execute(asset_manager) {
+                self.history.push(inverse_event);
+                self.unsaved_changes = true;
+                scene_changed = true;
+            }
+        }
+        if scene_changed {
+            self.sync_editor(asset_manager);
+            // problems are rescanned while they're shown, so that fixed ones disappear from the list
+            if self.config.layout.panel(Panel::Validation).visible {
+                self.execute_validation_action(ValidationAction::Scan, asset_manager);
+            }
+            self.update_gui();
+        }
+        self.update_history(asset_manager);
+        let play_actions: Vec<PlayAction> = self.pending_play_actions.drain(..).collect();
+        for play_action in play_actions {
+            self.execute_play_action(play_action, asset_manager);
+        }
+        let file_actions: Vec<FileAction> = self.pending_file_actions.drain(..).collect();
+        for file_action in file_actions {
+            self.execute_file_action(file_action, asset_manager);
+        }
+        if self.config_changed {
+            self.save_config();
+            self.apply_appearance();
+            self.update_gui();
+        }
+    }
+
+    /// Returns true if scene was modified since it was last saved or loaded.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved_changes
+    }
+
+    /// Shows a prompt asking user what to do with unsaved changes before exiting.
+    pub fn request_exit(&mut self) {
+        self.current_editor_state.show_exit_prompt = true;
+        self.current_editor_state.exit_prompt_error = None;
+        self.update_gui();
+    }
+
+    /// Returns true if user confirmed exiting the editor.
+    pub fn exit_confirmed(&self) -> bool {
+        self.exit_confirmed
     }
 }
 
@@ -0,0 +1,82 @@
+use ketch_core::math::ray::Ray;
+use ketch_core::resource::AssetManager;
+
+use crate::editor_event::{EditorEvent, LightProperties};
+use crate::snapping;
+use crate::Editor;
+
+impl Editor {
+    /// Applies light properties without recording them in history, so changes can be seen while editing.
+    /// Properties from before the first preview are remembered and restored when the preview is reverted.
+    pub(crate) fn preview_light(&mut self, id: u32, properties: LightProperties, asset_manager: &mut AssetManager) {
+        match self.light_preview_origin {
+            Some((origin_id, _)) if origin_id != id => self.revert_light_preview(asset_manager),
+            _ => (),
+        }
+        if self.light_preview_origin.is_none() {
+            let origin = asset_manager.active_scene()
+                                      .and_then(|scene| scene.light(id))
+                                      .map(LightProperties::from_light);
+            self.light_preview_origin = origin.map(|origin| (id, origin));
+        }
+        EditorEvent::LightChanged(id, properties).execute(asset_manager);
+    }
+
+    /// Restores properties of the previewed light. Called before executing editor events,
+    /// so a committed light change is undone back to properties from before the preview.
+    pub(crate) fn revert_light_preview(&mut self, asset_manager: &mut AssetManager) {
+        if let Some((id, properties)) = self.light_preview_origin.take() {
+            EditorEvent::LightChanged(id, properties).execute(asset_manager);
+        }
+    }
+
+    /// Previews changes from the light panel and moves dragged light gizmo.
+    /// Releasing the dragged gizmo commits its new position.
+    pub(crate) fn update_lights(&mut self, asset_manager: &mut AssetManager) {
+        let light_previews: Vec<(u32, LightProperties)> = self.pending_light_previews.drain(..).collect();
+        for (id, properties) in light_previews {
+            self.preview_light(id, properties, asset_manager);
+        }
+
+        let dragged_light = match self.dragged_light {
+            Some(dragged_light) => dragged_light,
+            None => return,
+        };
+        let (ray, properties, camera_front) = match asset_manager.active_scene() {
+            Some(scene) => match (self.cursor_ray(scene), scene.light(dragged_light)) {
+                (Some(ray), Some(light)) => (ray, LightProperties::from_light(light), scene.camera().front()),
+                _ => return,
+            },
+            None => return,
+        };
+
+        if !self.editor_input_state.left_mouse_button_pressed {
+            self.dragged_light = None;
+            if self.light_preview_origin.map(|(id, _)| id) == Some(dragged_light) {
+                self.pending_editor_events.push(EditorEvent::LightChanged(dragged_light, properties));
+            }
+            return;
+        }
+
+        let mut new_position = drag_position(&ray, properties.position, &camera_front);
+        if self.config.settings.snapping_active(self.editor_input_state.shift) {
+            new_position = new_position.map(|position| snapping::snap_position(position, self.config.settings.translation_snap));
+        }
+        if let Some((x, y, z)) = new_position.filter(|position| *position != properties.position) {
+            self.preview_light(dragged_light, LightProperties { position: (x, y, z), ..properties }, asset_manager);
+            self.current_editor_state.x_light_text_box_content = x.to_string();
+            self.current_editor_state.y_light_text_box_content = y.to_string();
+            self.current_editor_state.z_light_text_box_content = z.to_string();
+            self.update_gui();
+        }
+    }
+}
+
+/// Returns point where the ray hits the plane facing the camera, which goes through the dragged position.
+fn drag_position(ray: &Ray, position: (f32, f32, f32), camera_front: &nalgebra_glm::Vec3) -> Option<(f32, f32, f32)> {
+    let (x, y, z) = position;
+    let plane_point = nalgebra_glm::Vec3::new(x, y, z);
+    let distance = ray.intersect_plane(&plane_point, camera_front)?;
+    let point = ray.point_at(distance);
+    Some((point.x, point.y, point.z))
+}
@@ -0,0 +1,43 @@
+use nalgebra_glm::Vec3;
+
+use ketch_core::renderer::debug_draw::DebugDraw;
+use ketch_core::resource::light::Light;
+
+/// Radius of the light gizmo, also used when picking lights in the viewport.
+pub const LIGHT_GIZMO_RADIUS: f32 = 0.25;
+
+const SELECTED_COLOR: (f32, f32, f32) = (1.0, 0.6, 0.0);
+const RANGE_COLOR_STRENGTH: f32 = 0.3;
+const CIRCLE_SEGMENTS: u32 = 16;
+const RANGE_CIRCLE_SEGMENTS: u32 = 48;
+
+/// Draws light gizmo made of three circles around the light position, in the light color.
+/// Selected light is highlighted and its range is shown.
+pub fn draw_light_gizmo(debug_draw: &mut DebugDraw, light: &Light, selected: bool) {
+    let position = light.position_vec3();
+    let color = if selected {
+        let (r, g, b) = SELECTED_COLOR;
+        Vec3::new(r, g, b)
+    } else {
+        light.color_vec3()
+    };
+
+    draw_circle(debug_draw, position, LIGHT_GIZMO_RADIUS, Vec3::x(), Vec3::y(), color, CIRCLE_SEGMENTS);
+    draw_circle(debug_draw, position, LIGHT_GIZMO_RADIUS, Vec3::x(), Vec3::z(), color, CIRCLE_SEGMENTS);
+    draw_circle(debug_draw, position, LIGHT_GIZMO_RADIUS, Vec3::y(), Vec3::z(), color, CIRCLE_SEGMENTS);
+
+    if selected {
+        let range_color = light.color_vec3() * RANGE_COLOR_STRENGTH;
+        draw_circle(debug_draw, position, light.range(), Vec3::x(), Vec3::z(), range_color, RANGE_CIRCLE_SEGMENTS);
+    }
+}
+
+fn draw_circle(debug_draw: &mut DebugDraw, center: Vec3, radius: f32, axis_a: Vec3, axis_b: Vec3, color: Vec3, segments: u32) {
+    let point = |segment: u32| {
+        let angle = segment as f32 / segments as f32 * 2.0 * std::f32::consts::PI;
+        center + (axis_a * angle.cos() + axis_b * angle.sin()) * radius
+    };
+    for segment in 0..segments {
+        debug_draw.line(point(segment), point(segment + 1), color);
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::material::Material;
+
+use crate::editor_event::EditorEvent;
+use crate::Editor;
+
+use log::*;
+
+/// Directory material assets are saved to and loaded from.
+const MATERIALS_DIRECTORY: &str = "materials";
+const NEW_MATERIAL_NAME: &str = "material";
+
+/// Actions from the editor Material panel.
+pub enum MaterialAction {
+    New,
+    Select(String),
+    Changed(Material),
+    Save,
+    AssignToSelection,
+    Reload,
+}
+
+impl Editor {
+    /// Executes material action. Changes of materials are applied immediately to every object using them.
+    /// Consecutive changes of the same material, like dragging a slider, are stored in the undo history as one operation.
+    pub(crate) fn execute_material_action(&mut self, material_action: MaterialAction, asset_manager: &mut AssetManager) {
+        match material_action {
+            MaterialAction::New => {
+                let material_names = asset_manager.material_names();
+                let name = (1..).map(|index| format!("{} {}", NEW_MATERIAL_NAME, index))
+                                .find(|name| !material_names.contains(name))
+                                .unwrap_or_else(|| NEW_MATERIAL_NAME.to_string());
+                asset_manager.add_material(Arc::new(RwLock::new(Material::new(name.as_str()))));
+                self.edited_material = Some(name);
+            },
+            MaterialAction::Select(name) => self.edited_material = Some(name),
+            MaterialAction::Changed(changed_material) => {
+                // text boxes aren't refilled, so that values being typed in aren't reformatted
+                if let Some(material) = self.edited_material.as_ref().and_then(|name| asset_manager.material(name)) {
+                    let previous_material = std::mem::replace(&mut *material.write().unwrap(), changed_material.clone());
+                    self.history.push(EditorEvent::MaterialChanged(previous_material));
+                    self.synced_editor_state.edited_material = Some(changed_material.clone());
+                    self.current_editor_state.edited_material = Some(changed_material);
+                    self.update_gui();
+                }
+                return;
+            },
+            MaterialAction::Save => {
+                if let Some(material) = self.edited_material.as_ref().and_then(|name| asset_manager.material(name)) {
+                    let material = material.read().unwrap();
+                    let path = material_path(material.name());
+                    let result = std::fs::create_dir_all(MATERIALS_DIRECTORY).map_err(From::from)
+                                                                             .and_then(|()| material.save(&path));
+                    match result {
+                        Ok(()) => info!("Saved material to {}", path.display()),
+                        Err(err) => error!("Couldn't save material to {}: {}", path.display(), err),
+                    }
+                }
+            },
+            MaterialAction::AssignToSelection => {
+                let material = self.edited_material.as_ref().and_then(|name| asset_manager.material(name));
+                if material.is_some() {
+                    let events = self.selection.objects().iter()
+                                                         .map(|id| EditorEvent::ObjectMaterialChanged(*id, material.clone()))
+                                                         .collect();
+                    self.pending_editor_events.push(EditorEvent::Group(events));
+                }
+            },
+            MaterialAction::Reload => load_materials(Path::new(MATERIALS_DIRECTORY), asset_manager),
+        }
+        self.sync_editor(asset_manager);
+        self.update_gui();
+    }
+}
+
+/// Returns path of the file material with given name is saved to.
+fn material_path(name: &str) -> PathBuf {
+    Path::new(MATERIALS_DIRECTORY).join(format!("{}.ron", name))
+}
+
+/// Loads every material file from given directory into the asset manager.
+fn load_materials(directory: &Path, asset_manager: &mut AssetManager) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            info!("Couldn't read materials from {}: {}", directory.display(), err);
+            return;
+        },
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("ron") {
+            continue;
+        }
+        if let Err(err) = asset_manager.load_material(&path) {
+            error!("Couldn't load material from {}: {}", path.display(), err);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::scene::Scene;
+use nalgebra_glm::Vec3;
+
+use crate::editor_event::{EditorEvent, ObjectTransform};
+use crate::snapping;
+use crate::Editor;
+
+/// Degrees of rotation per pixel of horizontal cursor movement.
+const ROTATION_SPEED: f32 = 0.5;
+/// Scale change per pixel of horizontal cursor movement.
+const SCALE_SPEED: f32 = 0.01;
+const MIN_SCALE: f32 = 0.01;
+
+/// Transform changed by dragging selected objects.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TransformMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl TransformMode {
+    /// Returns name of the mode shown in the inspector.
+    pub fn label(self) -> &'static str {
+        match self {
+            TransformMode::Translate => "translate",
+            TransformMode::Rotate => "rotate",
+            TransformMode::Scale => "scale",
+        }
+    }
+}
+
+/// State of selected objects being dragged in the viewport.
+pub struct ObjectDrag {
+    start_cursor_position: (f32, f32),
+    start_point: Vec3,
+    primary_object: u32,
+    origins: Vec<(u32, ObjectTransform)>,
+}
+
+impl Editor {
+    /// Starts dragging selected objects. Objects whose ancestor is selected are moved by the ancestor.
+    pub(crate) fn start_object_drag(&mut self, scene: &Scene) {
+        let primary_object = match self.selection.primary().and_then(|id| scene.object(id)) {
+            Some(primary_object) => primary_object,
+            None => return,
+        };
+        let origins = scene.objects().iter()
+                                     .filter(|x| self.selection.contains(x.id()))
+                                     .filter(|x| x.id() == primary_object.id() || !self.selection.objects().iter().any(|selected| scene.is_ancestor(*selected, x.id())))
+                                     .map(|x| (x.id(), ObjectTransform::from_object(x)))
+                                     .collect();
+        self.object_drag = Some(ObjectDrag {
+            start_cursor_position: self.editor_input_state.cursor_position,
+            start_point: scene.world_position(primary_object),
+            primary_object: primary_object.id(),
+            origins,
+        });
+    }
+
+    /// Restores transforms of dragged objects and stops dragging.
+    pub(crate) fn cancel_object_drag(&mut self, asset_manager: &mut AssetManager) {
+        if let (Some(object_drag), Some(scene)) = (self.object_drag.take(), asset_manager.active_scene_mut()) {
+            for (id, transform) in object_drag.origins {
+                if let Some(object) = scene.object_mut(id) {
+                    transform.apply(object);
+                }
+            }
+        }
+    }
+
+    /// Previews transforms of dragged objects. Releasing left mouse button commits them as a single operation.
+    pub(crate) fn update_object_drag(&mut self, asset_manager: &mut AssetManager) {
+        let transforms = match (&self.object_drag, asset_manager.active_scene()) {
+            (Some(object_drag), Some(scene)) => self.dragged_transforms(object_drag, scene),
+            _ => return,
+        };
+
+        if !self.editor_input_state.left_mouse_button_pressed {
+            let origins = self.object_drag.as_ref().map(|x| x.origins.clone()).unwrap_or_default();
+            self.cancel_object_drag(asset_manager);
+            if transforms != origins {
+                let events = transforms.into_iter().map(|(id, transform)| EditorEvent::ObjectTransformChanged(id, transform)).collect();
+                self.pending_editor_events.push(EditorEvent::Group(events));
+            }
+            return;
+        }
+
+        if let Some(scene) = asset_manager.active_scene_mut() {
+            for (id, transform) in transforms.iter() {
+                if let Some(object) = scene.object_mut(*id) {
+                    transform.apply(object);
+                }
+            }
+        }
+        let primary_transform = self.object_drag.as_ref()
+                                                .and_then(|object_drag| transforms.iter().find(|(id, _)| *id == object_drag.primary_object))
+                                                .map(|(_, transform)| *transform);
+        if let Some(ObjectTransform { position: (x, y, z), .. }) = primary_transform {
+            let (x, y, z) = (x.to_string(), y.to_string(), z.to_string());
+            if self.current_editor_state.x_object_position_text_box_content != x
+               || self.current_editor_state.y_object_position_text_box_content != y
+               || self.current_editor_state.z_object_position_text_box_content != z {
+                self.current_editor_state.x_object_position_text_box_content = x;
+                self.current_editor_state.y_object_position_text_box_content = y;
+                self.current_editor_state.z_object_position_text_box_content = z;
+                self.update_gui();
+            }
+        }
+    }
+
+    /// Computes transforms of dragged objects for current cursor position.
+    fn dragged_transforms(&self, object_drag: &ObjectDrag, scene: &Scene) -> Vec<(u32, ObjectTransform)> {
+        let settings = &self.config.settings;
+        let snapping_active = settings.snapping_active(self.editor_input_state.shift);
+        let cursor_offset = self.editor_input_state.cursor_position.0 - object_drag.start_cursor_position.0;
+
+        match self.transform_mode {
+            TransformMode::Translate => {
+                let start_ray = self.ray_at(scene, object_drag.start_cursor_position);
+                let current_ray = self.cursor_ray(scene);
+                let camera_front = scene.camera().front();
+                let offset = match (start_ray, current_ray) {
+                    (Some(start_ray), Some(current_ray)) => {
+                        let start_distance = start_ray.intersect_plane(&object_drag.start_point, &camera_front);
+                        let current_distance = current_ray.intersect_plane(&object_drag.start_point, &camera_front);
+                        match (start_distance, current_distance) {
+                            (Some(start_distance), Some(current_distance)) => current_ray.point_at(current_distance) - start_ray.point_at(start_distance),
+                            _ => Vec3::zeros(),
+                        }
+                    },
+                    _ => Vec3::zeros(),
+                };
+                // snapping aligns the primary object to the grid, other objects keep their distance to it
+                let primary_origin = object_drag.origins.iter()
+                                                        .find(|(id, _)| *id == object_drag.primary_object)
+                                                        .map(|(_, transform)| transform.position)
+                                                        .unwrap_or((0.0, 0.0, 0.0));
+                let mut primary_position = (primary_origin.0 + offset.x, primary_origin.1 + offset.y, primary_origin.2 + offset.z);
+                if snapping_active {
+                    primary_position = snapping::snap_position(primary_position, settings.translation_snap);
+                }
+                let offset = (primary_position.0 - primary_origin.0, primary_position.1 - primary_origin.1, primary_position.2 - primary_origin.2);
+                object_drag.origins.iter().map(|(id, transform)| {
+                    let (x, y, z) = transform.position;
+                    (*id, ObjectTransform { position: (x + offset.0, y + offset.1, z + offset.2), ..*transform })
+                }).collect()
+            },
+            TransformMode::Rotate => {
+                let mut angle = cursor_offset * ROTATION_SPEED;
+                if snapping_active {
+                    angle = snapping::snap(angle, settings.rotation_snap);
+                }
+                object_drag.origins.iter().map(|(id, transform)| {
+                    let (x, y, z) = transform.rotation_angles;
+                    (*id, ObjectTransform { rotation_angles: (x, y + angle.to_radians(), z), ..*transform })
+                }).collect()
+            },
+            TransformMode::Scale => {
+                let factor = 1.0 + cursor_offset * SCALE_SPEED;
+                object_drag.origins.iter().map(|(id, transform)| {
+                    let (x, y, z) = transform.scale;
+                    let mut scale = (x * factor, y * factor, z * factor);
+                    if snapping_active {
+                        scale = snapping::snap_position(scale, settings.scale_snap);
+                    }
+                    (*id, ObjectTransform { scale: (scale.0.max(MIN_SCALE), scale.1.max(MIN_SCALE), scale.2.max(MIN_SCALE)), ..*transform })
+                }).collect()
+            },
+        }
+    }
+}
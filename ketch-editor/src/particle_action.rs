@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::particle_effect::{ParticleEffect, ParticleEmitter};
+
+use crate::editor_event::EditorEvent;
+use crate::Editor;
+
+use log::*;
+
+/// Directory particle effect assets are saved to and loaded from.
+const PARTICLES_DIRECTORY: &str = "particles";
+const NEW_PARTICLE_EFFECT_NAME: &str = "particle effect";
+
+/// Actions from the editor Particles panel.
+pub enum ParticleAction {
+    New,
+    Select(String),
+    Changed(ParticleEffect),
+    Save,
+    AssignToSelection,
+    RemoveFromSelection,
+    RestartSelection,
+    Reload,
+}
+
+impl Editor {
+    /// Executes particle action. Changes of effects are applied immediately to every playing emitter, which previews them in the scene,
+    /// and aren't stored in the undo history, only attaching emitters to objects is.
+    pub(crate) fn execute_particle_action(&mut self, particle_action: ParticleAction, asset_manager: &mut AssetManager) {
+        match particle_action {
+            ParticleAction::New => {
+                let effect_names = asset_manager.particle_effect_names();
+                let name = (1..).map(|index| format!("{} {}", NEW_PARTICLE_EFFECT_NAME, index))
+                                .find(|name| !effect_names.contains(name))
+                                .unwrap_or_else(|| NEW_PARTICLE_EFFECT_NAME.to_string());
+                asset_manager.add_particle_effect(Arc::new(RwLock::new(ParticleEffect::new(name.as_str()))));
+                self.edited_particle_effect = Some(name);
+            },
+            ParticleAction::Select(name) => self.edited_particle_effect = Some(name),
+            ParticleAction::Changed(changed_effect) => {
+                if let Some(effect) = self.edited_particle_effect.as_ref().and_then(|name| asset_manager.particle_effect(name)) {
+                    *effect.write().unwrap() = changed_effect.clone();
+                    self.synced_editor_state.edited_particle_effect = Some(changed_effect.clone());
+                    self.current_editor_state.edited_particle_effect = Some(changed_effect);
+                    self.update_gui();
+                }
+                return;
+            },
+            ParticleAction::Save => {
+                if let Some(effect) = self.edited_particle_effect.as_ref().and_then(|name| asset_manager.particle_effect(name)) {
+                    let effect = effect.read().unwrap();
+                    let path = particle_effect_path(effect.name());
+                    let result = std::fs::create_dir_all(PARTICLES_DIRECTORY).map_err(From::from)
+                                                                             .and_then(|()| effect.save(&path));
+                    match result {
+                        Ok(()) => info!("Saved particle effect to {}", path.display()),
+                        Err(err) => error!("Couldn't save particle effect to {}: {}", path.display(), err),
+                    }
+                }
+            },
+            ParticleAction::AssignToSelection => {
+                if let Some(name) = self.edited_particle_effect.clone() {
+                    let events = self.selection.objects().iter()
+                                                         .map(|id| EditorEvent::ObjectParticleEmitterChanged(*id, Some(ParticleEmitter::new(name.as_str()))))
+                                                         .collect();
+                    self.pending_editor_events.push(EditorEvent::Group(events));
+                }
+            },
+            ParticleAction::RemoveFromSelection => {
+                let events = self.selection.objects().iter()
+                                                     .map(|id| EditorEvent::ObjectParticleEmitterChanged(*id, None))
+                                                     .collect();
+                self.pending_editor_events.push(EditorEvent::Group(events));
+            },
+            ParticleAction::RestartSelection => self.restarted_emitters.extend_from_slice(self.selection.objects()),
+            ParticleAction::Reload => load_particle_effects(Path::new(PARTICLES_DIRECTORY), asset_manager),
+        }
+        self.sync_editor(asset_manager);
+        self.update_gui();
+    }
+
+    /// Returns ids of objects whose particle effects should be played again from the beginning.
+    pub fn take_restarted_emitters(&mut self) -> Vec<u32> {
+        self.restarted_emitters.drain(..).collect()
+    }
+}
+
+/// Returns path of the file particle effect with given name is saved to.
+fn particle_effect_path(name: &str) -> PathBuf {
+    Path::new(PARTICLES_DIRECTORY).join(format!("{}.ron", name))
+}
+
+/// Loads every particle effect file from given directory into the asset manager.
+fn load_particle_effects(directory: &Path, asset_manager: &mut AssetManager) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            info!("Couldn't read particle effects from {}: {}", directory.display(), err);
+            return;
+        },
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("ron") {
+            continue;
+        }
+        if let Err(err) = asset_manager.load_particle_effect(&path) {
+            error!("Couldn't load particle effect from {}: {}", path.display(), err);
+        }
+    }
+}
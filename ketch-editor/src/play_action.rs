@@ -0,0 +1,59 @@
+use ketch_core::resource::AssetManager;
+
+use crate::Editor;
+
+/// State of the game simulation inside the editor.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlayState {
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// Actions from the editor toolbar.
+pub enum PlayAction {
+    Play,
+    Pause,
+    Step,
+    Stop,
+}
+
+impl Editor {
+    /// Executes toolbar action. Starting the game from stopped state stores a snapshot of the active scene,
+    /// which is restored when the game is stopped.
+    pub(crate) fn execute_play_action(&mut self, play_action: PlayAction, asset_manager: &mut AssetManager) {
+        match play_action {
+            PlayAction::Play => {
+                self.take_scene_snapshot(asset_manager);
+                self.play_state = PlayState::Playing;
+            },
+            PlayAction::Pause => {
+                if self.play_state == PlayState::Playing {
+                    self.play_state = PlayState::Paused;
+                }
+            },
+            PlayAction::Step => {
+                self.take_scene_snapshot(asset_manager);
+                self.play_state = PlayState::Paused;
+                self.pending_steps += 1;
+            },
+            PlayAction::Stop => {
+                self.light_preview_origin = None;
+                self.dragged_light = None;
+                self.object_drag = None;
+                if let Some(scene_snapshot) = self.scene_snapshot.take() {
+                    asset_manager.set_active_scene(scene_snapshot);
+                }
+                self.play_state = PlayState::Stopped;
+                self.pending_steps = 0;
+                self.sync_editor(asset_manager);
+            },
+        }
+    }
+
+    fn take_scene_snapshot(&mut self, asset_manager: &AssetManager) {
+        if self.play_state == PlayState::Stopped {
+            self.scene_snapshot = asset_manager.active_scene().map(|scene| scene.snapshot());
+        }
+    }
+}
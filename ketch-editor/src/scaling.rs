@@ -0,0 +1,42 @@
+use winit::Window;
+
+/// Returns factor editor GUI is scaled by, combining window DPI with user scale.
+pub fn ui_scale_factor(dpi: f64, user_scale: f32) -> f64 {
+    dpi * user_scale as f64
+}
+
+/// Returns size of the GUI in points for a window of given size in physical pixels.
+pub fn ui_size(physical_width: f64, physical_height: f64, scale_factor: f64) -> (f64, f64) {
+    (physical_width / scale_factor, physical_height / scale_factor)
+}
+
+/// Window as seen by the GUI, whose points are scaled by the user scale on top of window DPI.
+pub struct ScaledWindow<'a> {
+    pub window: &'a Window,
+    pub user_scale: f32,
+}
+
+impl<'a> conrod_winit::WinitWindow for ScaledWindow<'a> {
+    fn get_inner_size(&self) -> Option<(u32, u32)> {
+        let size = self.window.get_inner_size()?;
+        let user_scale = self.user_scale as f64;
+        Some(((size.width / user_scale) as u32, (size.height / user_scale) as u32))
+    }
+
+    fn hidpi_factor(&self) -> f32 {
+        ui_scale_factor(self.window.get_hidpi_factor(), self.user_scale) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gui_size_shrinks_with_scale_factor() {
+        let scale_factor = ui_scale_factor(2.0, 1.5);
+
+        assert_eq!(3.0, scale_factor);
+        assert_eq!((1280.0, 720.0), ui_size(3840.0, 2160.0, scale_factor));
+    }
+}
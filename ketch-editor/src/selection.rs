@@ -0,0 +1,116 @@
+use ketch_core::resource::scene::Scene;
+
+/// Objects selected in the editor. The last selected object is the primary one, which is shown in the inspector.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Selection {
+    objects: Vec<u32>,
+}
+
+impl Selection {
+    /// Creates empty selection.
+    pub fn new() -> Self {
+        Selection {
+            objects: Vec::new(),
+        }
+    }
+
+    /// Replaces selection with a single object.
+    pub fn select(&mut self, id: u32) {
+        self.objects.clear();
+        self.objects.push(id);
+    }
+
+    /// Replaces selection with given objects.
+    pub fn set(&mut self, ids: Vec<u32>) {
+        self.objects = ids;
+    }
+
+    /// Adds object to the selection and makes it the primary one.
+    pub fn add(&mut self, id: u32) {
+        self.objects.retain(|x| *x != id);
+        self.objects.push(id);
+    }
+
+    /// Removes object from the selection if it's selected, otherwise adds it.
+    pub fn toggle(&mut self, id: u32) {
+        if self.contains(id) {
+            self.objects.retain(|x| *x != id);
+        } else {
+            self.objects.push(id);
+        }
+    }
+
+    /// Removes all objects from the selection.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    /// Keeps only objects for which predicate returns true.
+    pub fn retain<F: FnMut(&u32) -> bool>(&mut self, predicate: F) {
+        self.objects.retain(predicate);
+    }
+
+    /// Returns true if object is selected.
+    pub fn contains(&self, id: u32) -> bool {
+        self.objects.contains(&id)
+    }
+
+    /// Returns the last selected object.
+    pub fn primary(&self) -> Option<u32> {
+        self.objects.last().cloned()
+    }
+
+    /// Returns ids of selected objects in order of selection.
+    pub fn objects(&self) -> &[u32] {
+        self.objects.as_slice()
+    }
+
+    /// Returns true if nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+/// Returns ids of objects whose world position is inside screen rectangle spanned by two corners.
+/// Coordinates are measured from the top left corner of the window.
+pub fn objects_in_screen_rect(scene: &Scene, corner: (f32, f32), opposite_corner: (f32, f32), window_width: f32, window_height: f32) -> Vec<u32> {
+    let (min_x, max_x) = (corner.0.min(opposite_corner.0), corner.0.max(opposite_corner.0));
+    let (min_y, max_y) = (corner.1.min(opposite_corner.1), corner.1.max(opposite_corner.1));
+    scene.objects().iter()
+                   .filter(|object| {
+                       match scene.camera().world_to_screen(&scene.world_position(object), window_width, window_height) {
+                           Some((x, y)) => x >= min_x && x <= max_x && y >= min_y && y <= max_y,
+                           None => false,
+                       }
+                   })
+                   .map(|object| object.id())
+                   .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_removes_selected_object_and_keeps_last_one_primary() {
+        let mut selection = Selection::new();
+        selection.select(1);
+        selection.toggle(2);
+        selection.toggle(3);
+
+        selection.toggle(3);
+
+        assert_eq!(&[1, 2], selection.objects());
+        assert_eq!(Some(2), selection.primary());
+    }
+
+    #[test]
+    fn add_moves_already_selected_object_to_the_end() {
+        let mut selection = Selection::new();
+        selection.set(vec![1, 2]);
+
+        selection.add(1);
+
+        assert_eq!(&[2, 1], selection.objects());
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use ketch_core::resource::AssetManager;
+
+use crate::editor_event::EditorEvent;
+use crate::Editor;
+
+use log::*;
+
+/// Operations applied to all selected objects at once.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SelectionAction {
+    Duplicate,
+    Delete,
+    Parent,
+    Unparent,
+}
+
+impl SelectionAction {
+    pub const ALL: [SelectionAction; 4] = [
+        SelectionAction::Duplicate,
+        SelectionAction::Delete,
+        SelectionAction::Parent,
+        SelectionAction::Unparent,
+    ];
+
+    /// Returns label of the inspector button.
+    pub fn label(self) -> &'static str {
+        match self {
+            SelectionAction::Duplicate => "Duplicate",
+            SelectionAction::Delete => "Delete",
+            SelectionAction::Parent => "Parent",
+            SelectionAction::Unparent => "Unparent",
+        }
+    }
+}
+
+impl Editor {
+    /// Applies action to all selected objects. Every action is undone as a single operation.
+    pub(crate) fn execute_selection_action(&mut self, selection_action: SelectionAction, asset_manager: &mut AssetManager) {
+        let scene = match asset_manager.active_scene() {
+            Some(scene) => scene,
+            None => return,
+        };
+        if self.selection.is_empty() {
+            return;
+        }
+
+        let events = match selection_action {
+            SelectionAction::Duplicate => {
+                // duplicates of children selected together with their parents are attached to duplicated parents
+                let duplicates: Vec<_> = scene.objects().iter()
+                                                        .filter(|x| self.selection.contains(x.id()))
+                                                        .map(|x| (x.id(), x.clone()))
+                                                        .collect();
+                let duplicate_ids: HashMap<u32, u32> = duplicates.iter().map(|(id, duplicate)| (*id, duplicate.id())).collect();
+                self.selection.set(duplicates.iter().map(|(_id, duplicate)| duplicate.id()).collect());
+                duplicates.into_iter()
+                          .map(|(_id, mut duplicate)| {
+                              if let Some(duplicated_parent) = duplicate.parent().and_then(|parent| duplicate_ids.get(&parent)) {
+                                  duplicate.set_parent(Some(*duplicated_parent));
+                              }
+                              EditorEvent::ObjectAdded(duplicate)
+                          })
+                          .collect()
+            },
+            SelectionAction::Delete => {
+                // children are deleted together with their parents
+                let deleted_objects = scene.objects().iter()
+                                                     .map(|x| x.id())
+                                                     .filter(|id| self.selection.objects().iter().any(|selected| selected == id || scene.is_ancestor(*selected, *id)));
+                let events = deleted_objects.map(EditorEvent::ObjectRemoved).collect();
+                self.selection.clear();
+                events
+            },
+            SelectionAction::Parent => {
+                let parent = self.selection.primary();
+                self.selection.objects().iter()
+                                        .filter(|id| Some(**id) != parent)
+                                        .filter(|id| {
+                                            let creates_cycle = parent.map(|parent| scene.is_ancestor(**id, parent)).unwrap_or(false);
+                                            if creates_cycle {
+                                                warn!("Object {} can't be parented to its own descendant", id);
+                                            }
+                                            !creates_cycle
+                                        })
+                                        .map(|id| EditorEvent::ObjectParentChanged(*id, parent))
+                                        .collect()
+            },
+            SelectionAction::Unparent => {
+                self.selection.objects().iter()
+                                        .filter(|id| scene.object(**id).and_then(|x| x.parent()).is_some())
+                                        .map(|id| EditorEvent::ObjectParentChanged(*id, None))
+                                        .collect()
+            },
+        };
+        self.pending_editor_events.push(EditorEvent::Group(events));
+    }
+}
@@ -0,0 +1,30 @@
+/// Rounds value to the nearest multiple of step. Values are not snapped if step isn't positive.
+pub fn snap(value: f32, step: f32) -> f32 {
+    if step <= 0.0 {
+        return value;
+    }
+    (value / step).round() * step
+}
+
+/// Snaps every coordinate of position to the nearest multiple of step.
+pub fn snap_position(position: (f32, f32, f32), step: f32) -> (f32, f32, f32) {
+    let (x, y, z) = position;
+    (snap(x, step), snap(y, step), snap(z, step))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_rounded_to_nearest_multiple_of_step() {
+        assert_eq!(1.5, snap(1.4, 0.5));
+        assert_eq!(-45.0, snap(-40.0, 15.0));
+        assert_eq!((1.0, 0.0, -2.0), snap_position((1.2, 0.4, -1.6), 1.0));
+    }
+
+    #[test]
+    fn values_are_not_snapped_to_zero_step() {
+        assert_eq!(1.4, snap(1.4, 0.0));
+    }
+}
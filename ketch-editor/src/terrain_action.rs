@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::object::ObjectBuilder;
+use ketch_core::resource::terrain::Terrain;
+
+use crate::editor_event::EditorEvent;
+use crate::terrain_brush::TerrainTool;
+use crate::Editor;
+
+use log::*;
+
+/// Directory terrain assets are saved to and loaded from.
+const TERRAINS_DIRECTORY: &str = "terrains";
+const NEW_TERRAIN_NAME: &str = "terrain";
+const NEW_TERRAIN_RESOLUTION: u32 = 65;
+const NEW_TERRAIN_SIZE: f32 = 64.0;
+
+/// Actions from the editor Terrain panel.
+pub enum TerrainAction {
+    New,
+    Select(String),
+    SetTool(Option<TerrainTool>),
+    SetRadius(f32),
+    SetStrength(f32),
+    AddToScene,
+    Save,
+    Reload,
+}
+
+impl Editor {
+    /// Executes terrain action. Brush strokes are applied by `update_terrain_brush`.
+    pub(crate) fn execute_terrain_action(&mut self, terrain_action: TerrainAction, asset_manager: &mut AssetManager) {
+        match terrain_action {
+            TerrainAction::New => {
+                let terrain_names = asset_manager.terrain_names();
+                let mesh_names = asset_manager.mesh_names();
+                let name = (1..).map(|index| format!("{} {}", NEW_TERRAIN_NAME, index))
+                                .find(|name| !terrain_names.contains(name) && !mesh_names.contains(name))
+                                .unwrap_or_else(|| NEW_TERRAIN_NAME.to_string());
+                if let Err(err) = asset_manager.add_terrain(Arc::new(RwLock::new(Terrain::new(name.as_str(), NEW_TERRAIN_RESOLUTION, NEW_TERRAIN_SIZE)))) {
+                    error!("Couldn't create mesh of terrain {}: {}", name, err);
+                }
+                self.edited_terrain = Some(name);
+            },
+            TerrainAction::Select(name) => self.edited_terrain = Some(name),
+            TerrainAction::SetTool(tool) => self.terrain_brush.tool = tool,
+            TerrainAction::SetRadius(radius) => self.terrain_brush.radius = radius,
+            TerrainAction::SetStrength(strength) => self.terrain_brush.strength = strength,
+            TerrainAction::AddToScene => {
+                if let Some(mesh) = self.edited_terrain.as_ref().and_then(|name| asset_manager.mesh(name)) {
+                    let name = mesh.read().unwrap().name().to_string();
+                    let object = ObjectBuilder::new(name).with_mesh(mesh).build();
+                    self.selection.select(object.id());
+                    self.pending_editor_events.push(EditorEvent::ObjectAdded(object));
+                }
+            },
+            TerrainAction::Save => {
+                if let Some(terrain) = self.edited_terrain.as_ref().and_then(|name| asset_manager.terrain(name)) {
+                    let terrain = terrain.read().unwrap();
+                    let path = terrain_path(terrain.name());
+                    let result = std::fs::create_dir_all(TERRAINS_DIRECTORY).map_err(From::from)
+                                                                            .and_then(|()| terrain.save(&path));
+                    match result {
+                        Ok(()) => info!("Saved terrain to {}", path.display()),
+                        Err(err) => error!("Couldn't save terrain to {}: {}", path.display(), err),
+                    }
+                }
+            },
+            TerrainAction::Reload => load_terrains(Path::new(TERRAINS_DIRECTORY), asset_manager),
+        }
+        self.sync_editor(asset_manager);
+        self.update_gui();
+    }
+}
+
+/// Returns path of the file terrain with given name is saved to.
+fn terrain_path(name: &str) -> PathBuf {
+    Path::new(TERRAINS_DIRECTORY).join(format!("{}.ron", name))
+}
+
+/// Loads every terrain file from given directory into the asset manager.
+fn load_terrains(directory: &Path, asset_manager: &mut AssetManager) {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            info!("Couldn't read terrains from {}: {}", directory.display(), err);
+            return;
+        },
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("ron") {
+            continue;
+        }
+        if let Err(err) = asset_manager.load_terrain(&path) {
+            error!("Couldn't load terrain from {}: {}", path.display(), err);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use ketch_core::math::ray::Ray;
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::scene::Scene;
+use ketch_core::resource::terrain::Terrain;
+use nalgebra_glm as glm;
+use nalgebra_glm::{Vec3, Vec4};
+use log::*;
+
+use crate::editor_event::EditorEvent;
+use crate::layout::Panel;
+use crate::Editor;
+
+/// Operation performed by the terrain brush.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TerrainTool {
+    Raise,
+    Lower,
+    Smooth,
+}
+
+impl TerrainTool {
+    pub const ALL: [TerrainTool; 3] = [TerrainTool::Raise, TerrainTool::Lower, TerrainTool::Smooth];
+
+    /// Returns name of the tool shown in the Terrain panel.
+    pub fn label(self) -> &'static str {
+        match self {
+            TerrainTool::Raise => "raise",
+            TerrainTool::Lower => "lower",
+            TerrainTool::Smooth => "smooth",
+        }
+    }
+}
+
+/// Settings of the terrain brush. Brush is used instead of selecting objects while a tool is chosen and the Terrain panel is visible.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TerrainBrush {
+    pub tool: Option<TerrainTool>,
+    pub radius: f32,
+    /// Height change per second for raising and lowering, blend amount per second for smoothing.
+    pub strength: f32,
+}
+
+impl TerrainBrush {
+    pub fn new() -> Self {
+        TerrainBrush {
+            tool: None,
+            radius: 2.0,
+            strength: 1.0,
+        }
+    }
+}
+
+impl Editor {
+    /// Returns true if pressing left mouse button in the viewport paints with the terrain brush.
+    pub(crate) fn terrain_brush_active(&self) -> bool {
+        self.terrain_brush.tool.is_some() && self.edited_terrain.is_some() && self.config.layout.panel(Panel::Terrain).visible
+    }
+
+    /// Applies the brush under the cursor while left mouse button is held. Heights are previewed immediately
+    /// and releasing the button commits the stroke as a single operation, which can be undone.
+    pub(crate) fn update_terrain_brush(&mut self, asset_manager: &mut AssetManager, update_time_delta: Duration) {
+        if self.editor_input_state.pick_requested && self.terrain_brush_active() {
+            self.editor_input_state.pick_requested = false;
+            self.terrain_stroke = self.edited_terrain.as_ref()
+                                                     .and_then(|name| asset_manager.terrain(name))
+                                                     .map(|terrain| terrain.read().unwrap().clone());
+        }
+        let stroke_start = match &self.terrain_stroke {
+            Some(stroke_start) => stroke_start,
+            None => return,
+        };
+        let name = stroke_start.name().to_string();
+        let terrain = match asset_manager.terrain(&name) {
+            Some(terrain) => terrain,
+            None => {
+                self.terrain_stroke = None;
+                return;
+            },
+        };
+
+        if !self.editor_input_state.left_mouse_button_pressed {
+            let stroke_start = self.terrain_stroke.take().unwrap();
+            let stroke_end = std::mem::replace(&mut *terrain.write().unwrap(), stroke_start.clone());
+            if stroke_end != stroke_start {
+                self.pending_editor_events.push(EditorEvent::TerrainChanged(stroke_end));
+            }
+            return;
+        }
+
+        let hit = asset_manager.active_scene().and_then(|scene| self.cursor_ray(scene).and_then(|ray| terrain_hit(scene, &name, &terrain.read().unwrap(), &ray)));
+        if let Some(hit) = hit {
+            let elapsed = update_time_delta.as_millis() as f32 / 1000.0;
+            let brush = self.terrain_brush;
+            {
+                let mut terrain = terrain.write().unwrap();
+                match brush.tool {
+                    Some(TerrainTool::Raise) => terrain.raise(hit.x, hit.z, brush.radius, brush.strength * elapsed),
+                    Some(TerrainTool::Lower) => terrain.raise(hit.x, hit.z, brush.radius, -brush.strength * elapsed),
+                    Some(TerrainTool::Smooth) => terrain.smooth(hit.x, hit.z, brush.radius, brush.strength * elapsed),
+                    None => (),
+                }
+            }
+            if let Err(err) = asset_manager.update_terrain_mesh(&name) {
+                error!("Couldn't update mesh of terrain {}: {}", name, err);
+            }
+        }
+    }
+}
+
+/// Returns the closest point in terrain coordinates where the ray hits an object showing the terrain mesh.
+/// Objects are expected not to be rotated or scaled non-uniformly, brush radius is measured in terrain coordinates.
+fn terrain_hit(scene: &Scene, name: &str, terrain: &Terrain, ray: &Ray) -> Option<Vec3> {
+    scene.objects().iter()
+                   .filter(|object| object.mesh().map(|mesh| mesh.read().unwrap().name() == name).unwrap_or(false))
+                   .filter_map(|object| {
+                       let inverse_world_matrix = glm::inverse(&scene.world_matrix(object));
+                       let origin = inverse_world_matrix * Vec4::new(ray.origin().x, ray.origin().y, ray.origin().z, 1.0);
+                       let direction = inverse_world_matrix * Vec4::new(ray.direction().x, ray.direction().y, ray.direction().z, 0.0);
+                       let local_ray = Ray::new(Vec3::new(origin.x, origin.y, origin.z), Vec3::new(direction.x, direction.y, direction.z));
+                       terrain.raycast(&local_ray).map(|hit| (glm::distance(&local_ray.origin(), &hit), hit))
+                   })
+                   .min_by(|(first, _), (second, _)| first.partial_cmp(second).unwrap_or(std::cmp::Ordering::Equal))
+                   .map(|(_, hit)| hit)
+}
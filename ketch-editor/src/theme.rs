@@ -0,0 +1,77 @@
+use conrod_core::color::{self, Color};
+use conrod_core::Theme;
+use serde::{Deserialize, Serialize};
+
+/// Color schemes of the editor GUI.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum EditorTheme {
+    Dark,
+    Light,
+}
+
+impl EditorTheme {
+    /// Returns the other theme, used to switch between them.
+    pub fn next(self) -> EditorTheme {
+        match self {
+            EditorTheme::Dark => EditorTheme::Light,
+            EditorTheme::Light => EditorTheme::Dark,
+        }
+    }
+
+    /// Returns name of the theme shown in the View panel.
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorTheme::Dark => "Dark theme",
+            EditorTheme::Light => "Light theme",
+        }
+    }
+
+    /// Returns color of the primary selected object in the hierarchy and of the selection box.
+    pub fn primary_selection_color(self) -> Color {
+        match self {
+            EditorTheme::Dark => color::LIGHT_BLUE,
+            EditorTheme::Light => color::BLUE,
+        }
+    }
+
+    /// Returns color of the other selected objects in the hierarchy.
+    pub fn selection_color(self) -> Color {
+        match self {
+            EditorTheme::Dark => color::BLUE,
+            EditorTheme::Light => color::LIGHT_BLUE,
+        }
+    }
+
+    /// Returns conrod theme styling all editor widgets.
+    pub fn conrod_theme(self) -> Theme {
+        use conrod_core::position::{Align, Direction, Padding, Position, Relative};
+        let (background_color, shape_color, border_color, label_color) = match self {
+            EditorTheme::Dark => (color::DARK_CHARCOAL, color::LIGHT_CHARCOAL, color::BLACK, color::WHITE),
+            EditorTheme::Light => (color::LIGHT_GREY, color::WHITE, color::DARK_GREY, color::BLACK),
+        };
+        Theme {
+            name: format!("Ketch Editor {}", self.label()),
+            padding: Padding::none(),
+            x_position: Position::Relative(Relative::Align(Align::Start), None),
+            y_position: Position::Relative(Relative::Direction(Direction::Backwards, 20.0), None),
+            background_color,
+            shape_color,
+            border_color,
+            border_width: 0.0,
+            label_color,
+            font_id: None,
+            font_size_large: 26,
+            font_size_medium: 18,
+            font_size_small: 12,
+            widget_styling: conrod_core::theme::StyleMap::default(),
+            mouse_drag_threshold: 0.0,
+            double_click_threshold: std::time::Duration::from_millis(500),
+        }
+    }
+}
+
+impl Default for EditorTheme {
+    fn default() -> Self {
+        EditorTheme::Dark
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use ketch_core::resource::{AssetManager, DEFAULT_TEXTURE_NAME};
+use ketch_core::resource::object::Object;
+use ketch_core::resource::scene::Scene;
+
+use crate::Editor;
+
+/// Distance from which the editor camera looks at an object it jumped to.
+const JUMP_DISTANCE: f32 = 5.0;
+
+/// Problem found in an object of the scene.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ValidationProblem {
+    MissingMesh,
+    MissingTexture(String),
+    InvalidTransform,
+    MeshNotLoaded(String),
+    MaterialNotLoaded(String),
+    TextureNotLoaded(String),
+}
+
+impl ValidationProblem {
+    /// Returns description of the problem shown in the Validation panel.
+    pub fn description(&self) -> String {
+        match self {
+            ValidationProblem::MissingMesh => "has no mesh".to_string(),
+            ValidationProblem::MissingTexture(mesh) => format!("mesh {} has no texture", mesh),
+            ValidationProblem::InvalidTransform => "transform isn't a finite number".to_string(),
+            ValidationProblem::MeshNotLoaded(mesh) => format!("mesh {} isn't loaded", mesh),
+            ValidationProblem::MaterialNotLoaded(material) => format!("material {} isn't loaded", material),
+            ValidationProblem::TextureNotLoaded(texture) => format!("texture {} isn't loaded", texture),
+        }
+    }
+}
+
+/// Problem found in the scene, along with the object it was found in.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ValidationIssue {
+    pub object_id: u32,
+    pub object_name: String,
+    pub problem: ValidationProblem,
+}
+
+/// Names of assets added to the asset manager, which scene objects are checked against.
+#[derive(Default)]
+pub struct LoadedAssets {
+    pub meshes: HashSet<String>,
+    pub textures: HashSet<String>,
+    pub materials: HashSet<String>,
+}
+
+impl LoadedAssets {
+    pub fn from_asset_manager(asset_manager: &AssetManager) -> Self {
+        LoadedAssets {
+            meshes: asset_manager.mesh_names().into_iter().collect(),
+            textures: asset_manager.texture_names().into_iter().collect(),
+            materials: asset_manager.material_names().into_iter().collect(),
+        }
+    }
+}
+
+/// Scene statistics and problems shown in the Validation panel.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct ValidationReport {
+    pub object_count: usize,
+    pub triangle_count: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// Scans all objects of the scene for problems.
+/// Objects without a mesh are fine if they hold a camera or are parents of other objects.
+pub fn validate_scene(scene: &Scene, loaded_assets: &LoadedAssets) -> ValidationReport {
+    let mut report = ValidationReport { object_count: scene.objects().len(), ..Default::default() };
+    for object in scene.objects() {
+        let mut problems = Vec::new();
+        let (x, y, z) = object.position();
+        let (rotation_x, rotation_y, rotation_z) = object.rotation_angles();
+        let (scale_x, scale_y, scale_z) = object.scale();
+        if ![x, y, z, rotation_x, rotation_y, rotation_z, scale_x, scale_y, scale_z].iter().all(|value| value.is_finite()) {
+            problems.push(ValidationProblem::InvalidTransform);
+        }
+
+        let material_texture = object.material().and_then(|material| {
+            let material = material.read().unwrap();
+            if !loaded_assets.materials.contains(material.name()) {
+                problems.push(ValidationProblem::MaterialNotLoaded(material.name().to_string()));
+            }
+            material.albedo_texture().map(str::to_string)
+        });
+        if let Some(texture) = &material_texture {
+            if !loaded_assets.textures.contains(texture) {
+                problems.push(ValidationProblem::TextureNotLoaded(texture.clone()));
+            }
+        }
+
+        match object.mesh() {
+            Some(mesh) => {
+                let mesh = mesh.read().unwrap();
+                report.triangle_count += mesh.triangle_count();
+                if !loaded_assets.meshes.contains(mesh.name()) {
+                    problems.push(ValidationProblem::MeshNotLoaded(mesh.name().to_string()));
+                }
+                // mesh texture isn't used when material has its own
+                if material_texture.is_none() {
+                    let texture = mesh.texture();
+                    if texture.name() == DEFAULT_TEXTURE_NAME {
+                        problems.push(ValidationProblem::MissingTexture(mesh.name().to_string()));
+                    } else if !loaded_assets.textures.contains(texture.name()) {
+                        problems.push(ValidationProblem::TextureNotLoaded(texture.name().to_string()));
+                    }
+                }
+            },
+            None => if object.camera().is_none() && !has_children(scene, object) {
+                problems.push(ValidationProblem::MissingMesh);
+            },
+        }
+
+        report.issues.extend(problems.into_iter().map(|problem| ValidationIssue {
+            object_id: object.id(),
+            object_name: object.name().to_string(),
+            problem,
+        }));
+    }
+    report
+}
+
+fn has_children(scene: &Scene, object: &Object) -> bool {
+    scene.objects().iter().any(|x| x.parent() == Some(object.id()))
+}
+
+/// Actions from the Validation panel.
+pub enum ValidationAction {
+    Scan,
+    JumpTo(u32),
+}
+
+impl Editor {
+    /// Executes validation action. Jumping to an object selects it and points the editor camera at it.
+    pub(crate) fn execute_validation_action(&mut self, validation_action: ValidationAction, asset_manager: &mut AssetManager) {
+        match validation_action {
+            ValidationAction::Scan => {
+                self.validation_report = match asset_manager.active_scene() {
+                    Some(scene) => validate_scene(scene, &LoadedAssets::from_asset_manager(asset_manager)),
+                    None => ValidationReport::default(),
+                };
+            },
+            ValidationAction::JumpTo(id) => {
+                let scene = match asset_manager.active_scene_mut() {
+                    Some(scene) => scene,
+                    None => return,
+                };
+                let target = match scene.object(id) {
+                    Some(object) => scene.world_position(object),
+                    None => return,
+                };
+                self.selection.select(id);
+                // objects with invalid transforms are only selected, camera would be moved to nowhere
+                if target.iter().all(|value| value.is_finite()) {
+                    let camera = scene.camera_mut();
+                    let position = target - camera.front() * JUMP_DISTANCE;
+                    camera.set_position_vec3(position);
+                }
+                self.sync_editor(asset_manager);
+            },
+        }
+        self.update_gui();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+
+    #[test]
+    fn objects_without_mesh_and_with_invalid_transform_are_reported() {
+        let mut scene = Scene::new("test_scene", Camera::new());
+        let parent = ObjectBuilder::new("parent").build();
+        let mut child = ObjectBuilder::new("child").with_position(std::f32::NAN, 0.0, 0.0).build();
+        child.set_parent(Some(parent.id()));
+        let mut camera_object = ObjectBuilder::new("camera").build();
+        camera_object.set_camera(Some(Camera::new()));
+        let child_id = child.id();
+        scene.add_object(parent);
+        scene.add_object(child);
+        scene.add_object(camera_object);
+
+        let report = validate_scene(&scene, &LoadedAssets::default());
+
+        assert_eq!(3, report.object_count);
+        assert_eq!(vec![ValidationProblem::InvalidTransform, ValidationProblem::MissingMesh],
+                   report.issues.iter().map(|x| x.problem.clone()).collect::<Vec<_>>());
+        assert!(report.issues.iter().all(|x| x.object_id == child_id));
+    }
+}
@@ -4,15 +4,134 @@ use conrod_core::widget_ids;
 widget_ids! {
     pub struct Ids {
         light_panel_canvas,
-        light_panel_x_column,
-        light_panel_y_column,
-        light_panel_z_column,
-        x_light_label,
+        light_list,
+        add_light_button,
+        remove_light_button,
+        light_position_label,
         x_light_text_box,
-        y_light_label,
         y_light_text_box,
-        z_light_label,
         z_light_text_box,
-        run_button,
+        light_color_label,
+        r_light_color_text_box,
+        g_light_color_text_box,
+        b_light_color_text_box,
+        light_intensity_label,
+        light_intensity_text_box,
+        light_range_label,
+        light_range_text_box,
+        toolbar_canvas,
+        play_button,
+        pause_button,
+        step_button,
+        stop_button,
+        view_button,
+        create_button,
+        create_menu_canvas,
+        create_menu_buttons[],
+        view_panel_canvas,
+        view_visibility_toggles[],
+        view_dock_buttons[],
+        view_shrink_buttons[],
+        view_grow_buttons[],
+        view_theme_button,
+        view_ui_scale_text,
+        view_ui_scale_down_button,
+        view_ui_scale_up_button,
+        file_panel_canvas,
+        new_button,
+        open_button,
+        save_button,
+        save_as_button,
+        scene_path_text_box,
+        exit_prompt_canvas,
+        exit_prompt_text,
+        exit_prompt_error_text,
+        exit_prompt_save_button,
+        exit_prompt_discard_button,
+        exit_prompt_cancel_button,
+        hierarchy_canvas,
+        hierarchy_list,
+        statistics_canvas,
+        statistics_text,
+        profiler_canvas,
+        profiler_text,
+        profiler_span_rects[],
+        profiler_span_labels[],
+        console_canvas,
+        console_list,
+        inspector_canvas,
+        object_name_label,
+        object_name_text_box,
+        object_position_label,
+        x_object_position_text_box,
+        y_object_position_text_box,
+        z_object_position_text_box,
+        selection_text,
+        selection_action_buttons[],
+        selection_box,
+        material_canvas,
+        material_list,
+        new_material_button,
+        save_material_button,
+        assign_material_button,
+        material_preview_image,
+        material_texture_label,
+        material_texture_list,
+        material_color_label,
+        r_material_color_text_box,
+        g_material_color_text_box,
+        b_material_color_text_box,
+        a_material_color_text_box,
+        material_metallic_slider,
+        material_roughness_slider,
+        material_emissive_slider,
+        material_blend_mode_label,
+        material_blend_mode_list,
+        material_cull_mode_label,
+        material_cull_mode_list,
+        material_front_face_label,
+        material_front_face_list,
+        particles_canvas,
+        particle_effect_list,
+        new_particle_effect_button,
+        save_particle_effect_button,
+        assign_particle_effect_button,
+        remove_particle_effect_button,
+        restart_particle_effect_button,
+        particle_texture_label,
+        particle_texture_list,
+        particle_blend_mode_label,
+        particle_blend_mode_list,
+        particle_looping_toggle,
+        particle_sliders[],
+        terrain_canvas,
+        terrain_list,
+        new_terrain_button,
+        save_terrain_button,
+        add_terrain_to_scene_button,
+        terrain_tool_label,
+        terrain_tool_toggles[],
+        terrain_radius_slider,
+        terrain_strength_slider,
+        bookmarks_canvas,
+        bookmark_name_text_boxes[],
+        bookmark_recall_buttons[],
+        bookmark_save_buttons[],
+        assets_canvas,
+        assets_list,
+        asset_preview_canvas,
+        asset_preview_image,
+        asset_preview_info_text,
+        asset_preview_channel_buttons[],
+        asset_preview_mip_down_button,
+        asset_preview_mip_up_button,
+        asset_preview_wireframe_toggle,
+        asset_preview_close_button,
+        camera_preview_canvas,
+        camera_preview_image,
+        validation_canvas,
+        validation_scan_button,
+        validation_summary_text,
+        validation_issue_list,
     }
 }
\ No newline at end of file
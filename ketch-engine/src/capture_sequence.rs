@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ketch_core::renderer::Renderer;
+use ketch_core::renderer::scene_capture::SceneCapture;
+use ketch_core::resource::AssetManager;
+
+use log::*;
+
+/// Consecutive frames of the active scene saved as numbered PNG images, e.g. for trailers or automated visual diffing.
+pub(crate) struct CaptureSequence {
+    directory: PathBuf,
+    scene_capture: SceneCapture,
+    frames: u32,
+    captured: u32,
+}
+
+impl CaptureSequence {
+    /// Creates sequence saving given number of frames to the directory.
+    pub(crate) fn new(directory: PathBuf, scene_capture: SceneCapture, frames: u32) -> Self {
+        CaptureSequence {
+            directory,
+            scene_capture,
+            frames,
+            captured: 0,
+        }
+    }
+
+    /// Captures the active scene and saves it as the next frame. Failed frames are logged and skipped, so numbering stays continuous.
+    pub(crate) fn capture_frame(&mut self, renderer: &mut Renderer, asset_manager: &AssetManager) {
+        let path = self.directory.join(frame_file_name(self.captured));
+        self.captured += 1;
+        match renderer.capture_scene(&self.scene_capture, asset_manager) {
+            Ok(image) => if let Err(err) = image.save(&path) {
+                error!("Couldn't save captured frame {}: {}", path.display(), err);
+            },
+            Err(err) => error!("Couldn't capture frame {}: {}", path.display(), err),
+        }
+    }
+
+    /// Returns true if all frames were captured.
+    pub(crate) fn finished(&self) -> bool {
+        self.captured >= self.frames
+    }
+
+    /// Called once all frames were captured. Encodes frames to a video if engine was built with video-encoding feature.
+    pub(crate) fn finish(self, time_per_update: Duration) {
+        info!("Captured {} frames to {}", self.captured, self.directory.display());
+        if cfg!(feature = "video-encoding") {
+            encode_video(&self.directory, time_per_update);
+        }
+    }
+}
+
+/// Returns name of the image file of frame with given index, e.g. frame_00042.png.
+pub(crate) fn frame_file_name(index: u32) -> String {
+    format!("frame_{:05}.png", index)
+}
+
+/// Returns frame rate of captured sequence as a fraction accepted by ffmpeg, since one frame is captured per update.
+fn frame_rate(time_per_update: Duration) -> String {
+    let nanos = time_per_update.as_secs() as u128 * 1_000_000_000 + u128::from(time_per_update.subsec_nanos());
+    format!("1000000000/{}", nanos.max(1))
+}
+
+/// Encodes captured frames to capture.mp4 in the same directory with external ffmpeg, which has to be in PATH.
+fn encode_video(directory: &Path, time_per_update: Duration) {
+    let video = directory.join("capture.mp4");
+    let status = std::process::Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-framerate").arg(frame_rate(time_per_update))
+        .arg("-i").arg(directory.join("frame_%05d.png"))
+        .arg("-pix_fmt").arg("yuv420p")
+        .arg(&video)
+        .status();
+    match status {
+        Ok(status) if status.success() => info!("Encoded captured frames to {}", video.display()),
+        Ok(status) => error!("Couldn't encode captured frames, ffmpeg exited with {}", status),
+        Err(err) => error!("Couldn't run ffmpeg to encode captured frames: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_numbered_for_encoders() {
+        assert_eq!("frame_00000.png", frame_file_name(0));
+        assert_eq!("frame_01234.png", frame_file_name(1234));
+        assert_eq!("1000000000/16000000", frame_rate(Duration::from_millis(16)));
+    }
+}
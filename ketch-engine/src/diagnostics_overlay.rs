@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use ketch_core::logging::LogRecord;
+use ketch_core::renderer::overlay::Overlay;
+use ketch_core::renderer::renderer_stats::RendererStats;
+use ketch_core::ui::ui_node::Rect;
+
+use log::Level;
+use nalgebra_glm::Vec3;
+
+/// Number of frames shown in the frame time graph.
+const FRAME_HISTORY: usize = 120;
+/// Number of latest warnings and errors listed below the statistics.
+const WARNINGS_SHOWN: usize = 5;
+/// Frame time at the top of the graph, in milliseconds. Longer frames are cut off.
+const GRAPH_MAX_MILLIS: f32 = 50.0;
+/// Frame time marked with a line across the graph, in milliseconds, which is 60 frames per second.
+const GRAPH_TARGET_MILLIS: f32 = 1000.0 / 60.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+const MARGIN: f32 = 8.0;
+const LINE_SPACING: f32 = 6.0;
+const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+/// State of the active scene shown by the overlay.
+pub(crate) struct SceneSummary {
+    pub(crate) name: String,
+    pub(crate) objects: usize,
+}
+
+/// Overlay showing frame times, renderer statistics, the active scene and recent warnings, toggled with a key even without the editor.
+pub(crate) struct DiagnosticsOverlay {
+    frame_times: VecDeque<Duration>,
+}
+
+impl DiagnosticsOverlay {
+    pub(crate) fn new() -> Self {
+        DiagnosticsOverlay {
+            frame_times: VecDeque::with_capacity(FRAME_HISTORY),
+        }
+    }
+
+    /// Records time of a frame. Frames are recorded while the overlay is hidden too, so the graph is full when it's shown.
+    pub(crate) fn record_frame(&mut self, frame_time: Duration) {
+        if self.frame_times.len() >= FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// Adds the overlay to the top left corner of the window. Warnings are taken from the end of the records.
+    pub(crate) fn draw(&self, overlay: &mut Overlay, stats: RendererStats, scene: Option<SceneSummary>, records: &[LogRecord]) {
+        let text = summary(self.average_frame_time(), stats, scene);
+        let warnings: Vec<&LogRecord> = records.iter().filter(|record| record.level <= Level::Warn).collect();
+        let warnings = &warnings[warnings.len().saturating_sub(WARNINGS_SHOWN)..];
+
+        let (text_width, text_height) = Overlay::text_size(&text);
+        let warning_sizes: Vec<(f32, f32)> = warnings.iter().map(|record| Overlay::text_size(&record.message)).collect();
+        let graph_width = FRAME_HISTORY as f32 * 2.0;
+        let width = warning_sizes.iter().map(|(width, _)| *width).fold(text_width.max(graph_width), f32::max);
+        let height = GRAPH_HEIGHT + LINE_SPACING + text_height + warning_sizes.iter().map(|(_, height)| height + LINE_SPACING).sum::<f32>();
+        overlay.rect(Rect { x: 0.0, y: 0.0, width: width + MARGIN * 2.0, height: height + MARGIN * 2.0 }, BACKGROUND_COLOR);
+
+        self.draw_graph(overlay, MARGIN, MARGIN, graph_width);
+        let mut y = MARGIN + GRAPH_HEIGHT + LINE_SPACING;
+        overlay.text(MARGIN, y, &text, Vec3::new(1.0, 1.0, 1.0));
+        y += text_height + LINE_SPACING;
+        for (record, (_, height)) in warnings.iter().zip(warning_sizes) {
+            let color = if record.level == Level::Error { Vec3::new(1.0, 0.3, 0.3) } else { Vec3::new(1.0, 0.9, 0.3) };
+            overlay.text(MARGIN, y, &record.message, color);
+            y += height + LINE_SPACING;
+        }
+    }
+
+    /// Draws a bar for every recorded frame, green below the target frame time and red above it, with the newest frame on the right.
+    fn draw_graph(&self, overlay: &mut Overlay, x: f32, y: f32, width: f32) {
+        let bottom = y + GRAPH_HEIGHT;
+        let bar_width = width / FRAME_HISTORY as f32;
+        let first_bar = FRAME_HISTORY - self.frame_times.len();
+        for (index, frame_time) in self.frame_times.iter().enumerate() {
+            let millis = duration_millis(*frame_time);
+            let bar_x = x + (first_bar + index) as f32 * bar_width;
+            let bar_height = millis.min(GRAPH_MAX_MILLIS) / GRAPH_MAX_MILLIS * GRAPH_HEIGHT;
+            let color = if millis > GRAPH_TARGET_MILLIS { Vec3::new(1.0, 0.3, 0.3) } else { Vec3::new(0.3, 1.0, 0.3) };
+            overlay.line((bar_x, bottom), (bar_x, bottom - bar_height), color);
+        }
+        let target_y = bottom - GRAPH_TARGET_MILLIS / GRAPH_MAX_MILLIS * GRAPH_HEIGHT;
+        overlay.line((x, target_y), (x + width, target_y), Vec3::new(0.6, 0.6, 0.6));
+    }
+
+    fn average_frame_time(&self) -> Option<Duration> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        Some(self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32)
+    }
+}
+
+/// Returns lines of statistics shown below the graph.
+fn summary(average_frame_time: Option<Duration>, stats: RendererStats, scene: Option<SceneSummary>) -> String {
+    let frame_line = match average_frame_time {
+        Some(frame_time) => {
+            let millis = duration_millis(frame_time);
+            format!("FPS {:.0} ({:.1} ms)", 1000.0 / millis.max(0.001), millis)
+        },
+        None => "FPS -".to_string(),
+    };
+    let scene_line = match scene {
+        Some(scene) => format!("Scene {} ({} objects)", scene.name, scene.objects),
+        None => "No active scene".to_string(),
+    };
+    format!("{}\nDraw calls {}  Triangles {}\nRendered {}  Culled {}\n{}",
+            frame_line, stats.draw_calls, stats.triangles_rendered, stats.objects_rendered, stats.objects_culled, scene_line)
+}
+
+fn duration_millis(duration: Duration) -> f32 {
+    duration.as_secs() as f32 * 1000.0 + duration.subsec_nanos() as f32 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_shows_average_of_recorded_frames() {
+        let mut diagnostics_overlay = DiagnosticsOverlay::new();
+        for millis in &[10, 30] {
+            diagnostics_overlay.record_frame(Duration::from_millis(*millis));
+        }
+        let stats = RendererStats { draw_calls: 3, triangles_rendered: 12, ..RendererStats::default() };
+        let scene = SceneSummary { name: "level".to_string(), objects: 4 };
+
+        let text = summary(diagnostics_overlay.average_frame_time(), stats, Some(scene));
+
+        assert_eq!("FPS 50 (20.0 ms)\nDraw calls 3  Triangles 12\nRendered 0  Culled 0\nScene level (4 objects)", text);
+    }
+}
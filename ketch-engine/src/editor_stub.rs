@@ -0,0 +1,97 @@
+//! Stand-in for the GUI editor, used when the engine is built without the `editor` feature.
+//! It can't be created, so editor of the engine is always None and none of these methods is called.
+
+use std::time::Duration;
+
+use winit::Event;
+
+use ketch_core::input::InputSystem;
+use ketch_core::logging::LogCapture;
+use ketch_core::renderer::Renderer;
+use ketch_core::renderer::debug_draw::DebugDraw;
+use ketch_core::renderer::renderer_error::RenderError;
+use ketch_core::renderer::renderer_stats::RendererStats;
+use ketch_core::resource::AssetManager;
+
+/// Error returned when the editor is created.
+pub const EDITOR_DISABLED: &str = "engine was built without editor feature";
+
+pub enum Editor {}
+
+impl Editor {
+    pub fn new(_renderer: &Renderer) -> Result<Self, &'static str> {
+        Err(EDITOR_DISABLED)
+    }
+
+    pub fn handle_input(&mut self, _input_events: Vec<Event>, _input_system: &mut InputSystem) {
+        match *self {}
+    }
+
+    pub fn add_glyph_commands<C>(&mut self, _command_buffer_builder: C) -> C {
+        match *self {}
+    }
+
+    pub fn add_draw_commands<Q, C>(&mut self, _queue: Q, _command_buffer_builder: C) -> C {
+        match *self {}
+    }
+
+    pub fn sync_editor(&mut self, _asset_manager: &AssetManager) {
+        match *self {}
+    }
+
+    pub fn run_game(&self) -> bool {
+        match *self {}
+    }
+
+    pub fn pause_game(&mut self, _asset_manager: &AssetManager) {
+        match *self {}
+    }
+
+    pub fn take_pending_steps(&mut self) -> u32 {
+        match *self {}
+    }
+
+    pub fn take_restarted_emitters(&mut self) -> Vec<u32> {
+        match *self {}
+    }
+
+    pub fn draw_helpers(&self, _debug_draw: &mut DebugDraw, _asset_manager: &AssetManager) {
+        match *self {}
+    }
+
+    pub fn update_statistics(&mut self, _renderer_stats: RendererStats, _asset_manager: &AssetManager) {
+        match *self {}
+    }
+
+    pub fn update_profiler(&mut self) {
+        match *self {}
+    }
+
+    pub fn update_console(&mut self, _log_capture: &LogCapture) {
+        match *self {}
+    }
+
+    pub fn add_preview_commands<C>(&self, _renderer: &mut Renderer, _command_buffer: C, _asset_manager: &mut AssetManager) -> Result<C, RenderError> {
+        match *self {}
+    }
+
+    pub fn selected_objects(&self) -> Vec<u32> {
+        match *self {}
+    }
+
+    pub fn update(&mut self, _asset_manager: &mut AssetManager, _update_time_delta: Duration) {
+        match *self {}
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        match *self {}
+    }
+
+    pub fn request_exit(&mut self) {
+        match *self {}
+    }
+
+    pub fn exit_confirmed(&self) -> bool {
+        match *self {}
+    }
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+
+use quick_error::quick_error;
+
+use ketch_core::renderer::renderer_error::{RenderError, RendererCreationError};
+use ketch_core::resource::asset_error::AssetError;
+use ketch_core::logging::logging_error::LoggingError;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum EngineCreationError {
+        RendererCreationError(err: RendererCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        NoWindowError {
+            display("NoWindowError: renderer needs a window, but input system was created without one")
+        }
+        WindowCreationError(err: winit::CreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        /// Render backends selected by renderer kind have error types of their own.
+        RenderBackendError(err: Box<Error + Send + Sync>) {
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(&**err)
+        }
+        AssetError(err: AssetError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        LoggingError(err: LoggingError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        IoError(err: std::io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum CaptureError {
+        UnsupportedRendererError {
+            display("UnsupportedRendererError: scenes can only be captured by the Vulkan renderer")
+        }
+        IoError(err: std::io::Error) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        RendererCreationError(err: RendererCreationError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+        RenderError(err: RenderError) {
+            from()
+            display(x) -> ("{}: {}", x.description(), err)
+            cause(err)
+        }
+    }
+}
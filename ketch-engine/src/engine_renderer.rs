@@ -0,0 +1,144 @@
+#[cfg(feature = "wgpu-backend")]
+use std::sync::Arc;
+
+#[cfg(feature = "wgpu-backend")]
+use winit::Window;
+use winit::dpi::PhysicalSize;
+use log::*;
+
+use ketch_core::input::InputSystem;
+use ketch_core::renderer::{get_window_dimensions, get_window_dpi, Renderer};
+use ketch_core::renderer::renderer_error::RenderError;
+use ketch_core::renderer::renderer_stats::RendererStats;
+use ketch_core::renderer::backend::RenderBackend;
+use ketch_core::renderer::null_backend::NullBackend;
+use ketch_core::renderer::scene_renderer::SceneRenderer;
+#[cfg(feature = "wgpu-backend")]
+use ketch_core::renderer::{self, wgpu_backend::WgpuBackend};
+use ketch_core::resource::AssetManager;
+use ketch_core::settings::{RendererKind, Settings};
+
+use crate::engine_error::EngineCreationError;
+
+/// Renderer of the engine, selected by `Settings::renderer_kind`. Only the Vulkan renderer draws particles, UI, overlays and the editor,
+/// and captures photos and frame sequences, the others draw objects of the active scene.
+pub(crate) enum EngineRenderer {
+    Vulkan(Renderer),
+    /// Null renderer has no window, so it keeps the size windows would have.
+    Null(SceneRenderer<NullBackend>, PhysicalSize),
+    #[cfg(feature = "wgpu-backend")]
+    Wgpu(SceneRenderer<WgpuBackend>, Arc<Window>),
+}
+
+/// Returns kind of renderer created for settings. Headless mode always uses the null renderer, so no window, surface or device is created.
+pub(crate) fn renderer_kind(settings: &Settings) -> RendererKind {
+    if settings.headless() {
+        RendererKind::Null
+    } else {
+        settings.renderer_kind()
+    }
+}
+
+impl EngineRenderer {
+    /// Creates renderer selected by settings together with its window, which is set as the window of the input system,
+    /// and asset manager whose assets it can draw. Only the null renderer can be created with an input system without a window.
+    pub fn new(settings: &Settings, input_system: &mut InputSystem) -> Result<(Self, AssetManager), EngineCreationError> {
+        match renderer_kind(settings) {
+            RendererKind::Null => {
+                let scene_renderer = SceneRenderer::new(NullBackend::new()).map_err(|err| EngineCreationError::RenderBackendError(Box::new(err)))?;
+                info!("Rendering with {}", scene_renderer.backend().name());
+                Ok((EngineRenderer::Null(scene_renderer, *settings.initial_window_size()), AssetManager::without_device()?))
+            },
+            #[cfg(feature = "wgpu-backend")]
+            RendererKind::Wgpu => {
+                let events_loop = input_system.events_loop().ok_or(EngineCreationError::NoWindowError)?;
+                let window = Arc::new(renderer::window_builder(settings).build(events_loop)?);
+                input_system.set_window(window.clone());
+                let scene_renderer = WgpuBackend::with_window(window.clone()).and_then(SceneRenderer::new)
+                                                                               .map_err(|err| EngineCreationError::RenderBackendError(Box::new(err)))?;
+                info!("Rendering with {}", scene_renderer.backend().name());
+                // assets are kept on the CPU, scene renderer uploads them to the backend when they're drawn
+                Ok((EngineRenderer::Wgpu(scene_renderer, window), AssetManager::without_device()?))
+            },
+            _ => {
+                let events_loop = input_system.events_loop().ok_or(EngineCreationError::NoWindowError)?;
+                let renderer = Renderer::new(settings, events_loop)?;
+                input_system.set_surface(renderer.surface());
+                let asset_manager = renderer.create_asset_manager()?;
+                Ok((EngineRenderer::Vulkan(renderer), asset_manager))
+            },
+        }
+    }
+
+    /// Returns the Vulkan renderer, or None if the engine renders with another backend.
+    pub fn vulkan(&self) -> Option<&Renderer> {
+        match self {
+            EngineRenderer::Vulkan(renderer) => Some(renderer),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the Vulkan renderer, or None if the engine renders with another backend.
+    pub fn vulkan_mut(&mut self) -> Option<&mut Renderer> {
+        match self {
+            EngineRenderer::Vulkan(renderer) => Some(renderer),
+            _ => None,
+        }
+    }
+
+    /// Returns size of the window in physical pixels.
+    pub fn window_dimensions(&self) -> PhysicalSize {
+        match self {
+            EngineRenderer::Vulkan(renderer) => get_window_dimensions(renderer.surface().window()),
+            EngineRenderer::Null(_scene_renderer, dimensions) => *dimensions,
+            #[cfg(feature = "wgpu-backend")]
+            EngineRenderer::Wgpu(_scene_renderer, window) => get_window_dimensions(window),
+        }
+    }
+
+    /// Returns DPI factor of the window.
+    pub fn window_dpi(&self) -> f64 {
+        match self {
+            EngineRenderer::Vulkan(renderer) => get_window_dpi(renderer.surface().window()),
+            EngineRenderer::Null(_scene_renderer, _dimensions) => 1.0,
+            #[cfg(feature = "wgpu-backend")]
+            EngineRenderer::Wgpu(_scene_renderer, window) => get_window_dpi(window),
+        }
+    }
+
+    /// Returns statistics of the last frame.
+    pub fn stats(&self) -> RendererStats {
+        match self {
+            EngineRenderer::Vulkan(renderer) => renderer.stats(),
+            EngineRenderer::Null(scene_renderer, _dimensions) => scene_renderer.stats(),
+            #[cfg(feature = "wgpu-backend")]
+            EngineRenderer::Wgpu(scene_renderer, _window) => scene_renderer.stats(),
+        }
+    }
+
+    /// Adapts frames to the window after it was resized or moved to a screen with another DPI factor.
+    pub fn resize(&mut self) {
+        match self {
+            EngineRenderer::Vulkan(renderer) => renderer.force_recreate_swapchain(),
+            EngineRenderer::Null(_scene_renderer, _dimensions) => (),
+            #[cfg(feature = "wgpu-backend")]
+            EngineRenderer::Wgpu(scene_renderer, window) => {
+                let dimensions = get_window_dimensions(window);
+                scene_renderer.backend_mut().resize(dimensions.width as u32, dimensions.height as u32);
+            },
+        }
+    }
+
+    /// Waits until the GPU finishes all submitted work.
+    pub fn wait_idle(&mut self) -> Result<(), RenderError> {
+        match self {
+            EngineRenderer::Vulkan(renderer) => renderer.wait_idle(),
+            EngineRenderer::Null(_scene_renderer, _dimensions) => Ok(()),
+            #[cfg(feature = "wgpu-backend")]
+            EngineRenderer::Wgpu(scene_renderer, _window) => {
+                scene_renderer.backend().wait_idle();
+                Ok(())
+            },
+        }
+    }
+}
@@ -1,12 +1,41 @@
 use winit::KeyboardInput;
+#[cfg(feature = "editor")]
 use ketch_editor::Editor;
-use std::error::Error;
+#[cfg(not(feature = "editor"))]
+mod editor_stub;
+#[cfg(not(feature = "editor"))]
+use crate::editor_stub::Editor;
+use crate::engine_error::{CaptureError, EngineCreationError};
+use crate::capture_sequence::CaptureSequence;
+use crate::photo_mode::PhotoMode;
+use crate::diagnostics_overlay::{DiagnosticsOverlay, SceneSummary};
+use crate::telemetry::{EngineMetrics, TelemetryExporter};
+use crate::engine_renderer::{renderer_kind, EngineRenderer};
 use ketch_core::input::input_event::InputEvent;
 use ketch_core::resource::AssetManager;
-use ketch_core::renderer::{Renderer};
-use ketch_core::settings::Settings;
+use ketch_core::renderer::scene_capture;
+use ketch_core::renderer::renderer_stats::RendererStats;
+use ketch_core::renderer::overlay::Overlay;
+use ketch_core::resource::camera::Camera;
+use ketch_core::settings::{RendererKind, Settings, TelemetryExport};
 use ketch_core::input::InputSystem;
+use ketch_core::input::cursor_icon::CursorIcon;
+use ketch_core::input::input_router::UI_PRIORITY;
 use ketch_core::input;
+use ketch_core::event_bus::{EventBus, GameEvent, SceneEvent};
+use ketch_core::collision::CollisionSystem;
+use ketch_core::ui::Ui;
+use ketch_core::tween::TweenSystem;
+use ketch_core::particles::ParticleSystem;
+use ketch_core::pause::PauseFlags;
+use ketch_core::random;
+use ketch_core::logging::{self, LogCapture};
+use ketch_core::logging::logging_error::LoggingError;
+use ketch_core::{profile_scope, profiling};
+use ketch_audio::AudioSystem;
+use ketch_physics::PhysicsSystem;
+use ketch_net::{Network, NetworkEvent};
+use ketch_net::network_error::NetworkError;
 
 use winit::Event;
 use winit::WindowEvent;
@@ -14,8 +43,20 @@ use winit::VirtualKeyCode;
 use winit::ElementState;
 
 pub use ketch_core::renderer::{get_window_dimensions, get_window_dpi};
+pub use ketch_audio;
+pub use ketch_physics;
+pub use ketch_net;
 
-use std::time::{Duration, Instant};
+pub mod engine_error;
+mod capture_sequence;
+mod photo_mode;
+mod diagnostics_overlay;
+mod telemetry;
+mod engine_renderer;
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use fps_counter::FPSCounter;
 
@@ -26,56 +67,171 @@ use log::*;
 #[derive(StructOpt, Debug)]
 #[structopt(name = "basic")]
 struct Opts {
-    /// Activate GUI Editor
+    /// Activate GUI Editor, if engine was built with editor feature
     #[structopt(short = "g", long = "gui-editor")]
     gui_editor: bool,
+    /// Run without rendering, used to host games on servers
+    #[structopt(long = "headless")]
+    headless: bool,
+    /// Render on the CPU with a software implementation of Vulkan, like lavapipe
+    #[structopt(long = "software-renderer")]
+    software_renderer: bool,
+    /// Append engine metrics as JSON lines to the file
+    #[structopt(long = "telemetry-json", parse(from_os_str))]
+    telemetry_json: Option<PathBuf>,
+    /// Serve engine metrics to Prometheus on the address, e.g. 0.0.0.0:9100
+    #[structopt(long = "telemetry-prometheus")]
+    telemetry_prometheus: Option<SocketAddr>,
 }
 
 /// A struct representing the top level of this engine.
 /// It provides access to all the subsystems that can be used.
 pub struct Engine {
-    renderer: Renderer,
+    renderer: EngineRenderer,
     asset_manager: AssetManager,
     input_system: InputSystem,
+    audio_system: AudioSystem,
+    physics_system: PhysicsSystem,
+    collision_system: CollisionSystem,
+    event_bus: EventBus,
+    ui: Ui,
+    tween_system: TweenSystem,
+    particle_system: ParticleSystem,
+    network: Option<Network>,
     editor: Option<Editor>,
+    editor_enabled: bool,
+    capture_sequence: Option<CaptureSequence>,
+    log_capture: Option<LogCapture>,
+    pause_flags: PauseFlags,
+    photo_mode: Option<PhotoMode>,
+    diagnostics_overlay: DiagnosticsOverlay,
+    diagnostics_overlay_enabled: bool,
+    exit_requested: bool,
+    telemetry: Option<TelemetryExporter>,
     settings: Settings,
 }
 
 impl Engine {
-    /// Creates and returns a new instance of this engine.
-    pub fn new(settings: Settings) -> Self {
+    /// Creates and returns a new instance of this engine, applying command line options to given settings.
+    /// Fails if the window, the renderer or the default assets can't be created.
+    pub fn new(mut settings: Settings) -> Result<Self, EngineCreationError> {
         let opts = Opts::from_args();
+        if opts.headless {
+            settings.set_headless(true);
+        }
+        if opts.software_renderer {
+            settings.set_renderer_kind(RendererKind::Software);
+        }
+        if let Some(path) = opts.telemetry_json {
+            settings.set_telemetry_export(Some(TelemetryExport::JsonLines(path)));
+        }
+        if let Some(address) = opts.telemetry_prometheus {
+            settings.set_telemetry_export(Some(TelemetryExport::Prometheus(address)));
+        }
 
-        let mut input_system = InputSystem::new();
-        let renderer = match Renderer::new(&settings, input_system.events_loop()) {
-            Ok(renderer) => renderer,
-            Err(e) => {
-                error!("Couldn't create renderer: {}", e);
-                panic!("Couldn't create renderer!");
-            },
-        };
-        input_system.set_surface(renderer.surface());
-        let asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+        let mut engine = Engine::from_settings(settings)?;
+        if opts.gui_editor {
+            engine.set_editor_enabled(true);
+        }
+        Ok(engine)
+    }
 
-        let editor = if opts.gui_editor {
-            match Editor::new(&renderer) {
-                Ok(editor) => Some(editor),
-                Err(e) => {
-                    error!("Couldn't create editor: {}", e);
-                    panic!("Couldn't create editor!");
-                },
-            }
-        } else {
-            None
+    /// Creates engine using only given settings, without reading command line options, e.g. in tests or when the engine is embedded.
+    /// Subsystems are created the same way they can be created without the engine: input system first, then renderer and asset manager.
+    /// Renderer is selected by `Settings::renderer_kind`, other renderers than the Vulkan one draw only objects of the active scene.
+    /// Engine logger is set up from logging settings first. If the host application already set up its own logger, that one is used instead.
+    /// Fails as well if telemetry export is set and its file can't be opened or its address can't be bound.
+    pub fn from_settings(settings: Settings) -> Result<Self, EngineCreationError> {
+        let log_capture = match logging::init(&settings) {
+            Ok(log_capture) => Some(log_capture),
+            Err(LoggingError::LoggerAlreadySet) => None,
+            Err(err) => return Err(err.into()),
+        };
+        // the null renderer has no window whose events would be read
+        let mut input_system = match renderer_kind(&settings) {
+            RendererKind::Null => InputSystem::without_window(),
+            _ => InputSystem::new(),
         };
-        
-        Engine {
+        let (renderer, mut asset_manager) = EngineRenderer::new(&settings, &mut input_system)?;
+        asset_manager.set_texture_quality(settings.texture_quality());
+        asset_manager.streaming_queue_mut().set_budget(settings.upload_budget());
+        asset_manager.texture_streamer_mut().set_memory_budget(settings.texture_memory_budget());
+        let random_seed = settings.random_seed().unwrap_or_else(random::time_seed);
+        info!("Random seed: {}", random_seed);
+        asset_manager.random_mut().reseed(random_seed);
+        let particle_system = ParticleSystem::new(asset_manager.random_mut().stream("particles").next_u64());
+        let audio_system = AudioSystem::new();
+        let telemetry = match settings.telemetry_export() {
+            Some(export) => Some(TelemetryExporter::new(export, settings.telemetry_interval())?),
+            None => None,
+        };
+
+        Ok(Engine {
             renderer,
             asset_manager,
             input_system,
+            audio_system,
+            physics_system: PhysicsSystem::new(),
+            collision_system: CollisionSystem::new(),
+            event_bus: EventBus::new(),
+            ui: Ui::new(),
+            tween_system: TweenSystem::new(),
+            particle_system,
+            network: None,
             settings,
-            editor,
+            editor: None,
+            editor_enabled: false,
+            capture_sequence: None,
+            log_capture,
+            pause_flags: PauseFlags::NONE,
+            photo_mode: None,
+            diagnostics_overlay: DiagnosticsOverlay::new(),
+            diagnostics_overlay_enabled: false,
+            exit_requested: false,
+            telemetry,
+        })
+    }
+
+    /// Shows or hides the GUI editor. Editor is created when it's enabled for the first time.
+    /// Enabling the editor pauses the game. Editor can't be enabled if engine was built without editor feature.
+    pub fn set_editor_enabled(&mut self, enabled: bool) {
+        if enabled && self.settings.headless() {
+            warn!("Editor can't be enabled in headless mode");
+            return;
         }
+        if enabled {
+            self.exit_photo_mode();
+        }
+        if enabled && self.editor.is_none() {
+            let renderer = match self.renderer.vulkan() {
+                Some(renderer) => renderer,
+                None => {
+                    warn!("Editor can only be enabled with the Vulkan renderer");
+                    return;
+                },
+            };
+            match Editor::new(renderer) {
+                Ok(editor) => self.editor = Some(editor),
+                Err(e) => {
+                    error!("Couldn't create editor: {}", e);
+                    return;
+                },
+            }
+        }
+        self.editor_enabled = enabled;
+        self.input_system.set_cursor_icon(CursorIcon::Arrow);
+
+        if let Some(editor) = self.editor.as_mut().filter(|_| enabled) {
+            self.input_system.grab_cursor(false);
+            self.input_system.hide_cursor(false);
+            self.input_system.actions_mut().release_all();
+            editor.pause_game(&self.asset_manager);
+        }
+    }
+
+    /// Returns true if the GUI editor is shown.
+    pub fn editor_enabled(&self) -> bool {
+        self.editor_enabled
     }
 
     /// Returns settings used by this engine.
@@ -83,32 +239,308 @@ impl Engine {
         &self.settings
     }
 
+    /// Returns statistics of the last frame rendered by the renderer selected in settings.
+    pub fn renderer_stats(&self) -> RendererStats {
+        self.renderer.stats()
+    }
+
     /// Returns a reference to input system, which updates input mapping implemented by the user.
     pub fn input_system_mut(&mut self) -> &mut InputSystem {
         &mut self.input_system
     }
 
+    /// Returns a mutable reference to audio system, which plays sounds and music.
+    pub fn audio_system_mut(&mut self) -> &mut AudioSystem {
+        &mut self.audio_system
+    }
+
+    /// Returns a mutable reference to physics system, which simulates objects with rigid bodies and colliders.
+    pub fn physics_system_mut(&mut self) -> &mut PhysicsSystem {
+        &mut self.physics_system
+    }
+
+    /// Starts hosting game on given port, replacing current client or server.
+    pub fn host(&mut self, port: u16) -> Result<(), NetworkError> {
+        self.network = Some(Network::host(port)?);
+        Ok(())
+    }
+
+    /// Starts connecting to a game hosted on given address, replacing current client or server.
+    pub fn connect(&mut self, server_address: &str) -> Result<(), NetworkError> {
+        self.network = Some(Network::connect(server_address)?);
+        Ok(())
+    }
+
+    /// Closes all connections and stops hosting or connecting.
+    pub fn close_network(&mut self) {
+        if let Some(mut network) = self.network.take() {
+            for connection in network.connections() {
+                network.disconnect(connection);
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the client or server, or None if game isn't networked.
+    pub fn network_mut(&mut self) -> Option<&mut Network> {
+        self.network.as_mut()
+    }
+
+    /// Returns a reference to collision system, which tracks objects inside triggers.
+    pub fn collision_system(&self) -> &CollisionSystem {
+        &self.collision_system
+    }
+
+    /// Returns a mutable reference to in-game UI, which is drawn on top of the scene and publishes `GameEvent::Ui` events.
+    pub fn ui_mut(&mut self) -> &mut Ui {
+        &mut self.ui
+    }
+
+    /// Returns a mutable reference to tween system, which animates object transforms and UI nodes.
+    pub fn tween_system_mut(&mut self) -> &mut TweenSystem {
+        &mut self.tween_system
+    }
+
+    /// Returns a mutable reference to particle system, which simulates particle emitters of the active scene.
+    pub fn particle_system_mut(&mut self) -> &mut ParticleSystem {
+        &mut self.particle_system
+    }
+
+    /// Returns latest records logged through the engine logger, or None if the host application set up its own logger.
+    pub fn log_capture(&self) -> Option<&LogCapture> {
+        self.log_capture.as_ref()
+    }
+
+    /// Returns subsystems paused globally, whichever scene is active.
+    pub fn pause_flags(&self) -> PauseFlags {
+        self.pause_flags
+    }
+
+    /// Sets subsystems paused globally, e.g. by a pause menu or a photo mode. The game itself keeps being updated.
+    /// Scenes can pause more subsystems with `Scene::set_pause_flags`.
+    pub fn set_pause_flags(&mut self, pause_flags: PauseFlags) {
+        self.pause_flags = pause_flags;
+    }
+
+    /// Enters photo mode: simulation is frozen and the scene is rendered without UI through its free camera,
+    /// which starts where the view camera was and is moved by the game in `EventHandler::update_photo_camera`.
+    /// Photo mode can't be entered while the editor is shown.
+    pub fn enter_photo_mode(&mut self) {
+        if self.editor_enabled {
+            warn!("Photo mode can't be entered while the editor is shown");
+            return;
+        }
+        if self.photo_mode.is_some() {
+            return;
+        }
+        let free_camera = self.asset_manager.active_scene().map(|scene| scene.camera().clone());
+        self.photo_mode = Some(PhotoMode::new(free_camera, self.pause_flags));
+        self.pause_flags = PauseFlags::ALL;
+        if let Some(scene) = self.asset_manager.active_scene_mut() {
+            let view_camera = scene.view_camera();
+            scene.change_camera(view_camera);
+        }
+    }
+
+    /// Exits photo mode, restoring free camera of the active scene and global pause flags.
+    pub fn exit_photo_mode(&mut self) {
+        let photo_mode = match self.photo_mode.take() {
+            Some(photo_mode) => photo_mode,
+            None => return,
+        };
+        self.pause_flags = photo_mode.pause_flags();
+        if let (Some(scene), Some(free_camera)) = (self.asset_manager.active_scene_mut(), photo_mode.free_camera()) {
+            scene.change_camera(free_camera.clone());
+        }
+    }
+
+    /// Shows or hides the diagnostics overlay with frame times, renderer statistics, the active scene and recent warnings.
+    pub fn set_diagnostics_overlay_enabled(&mut self, enabled: bool) {
+        self.diagnostics_overlay_enabled = enabled;
+    }
+
+    /// Returns true if the diagnostics overlay is shown.
+    pub fn diagnostics_overlay_enabled(&self) -> bool {
+        self.diagnostics_overlay_enabled
+    }
+
+    /// Returns overlay drawn on top of the frame, which is empty unless the diagnostics overlay is shown.
+    fn build_overlay(&self) -> Overlay {
+        let mut overlay = Overlay::new();
+        if self.diagnostics_overlay_enabled && self.photo_mode.is_none() {
+            let scene = self.asset_manager.active_scene().map(|scene| SceneSummary {
+                name: scene.name().to_string(),
+                objects: scene.objects().len(),
+            });
+            let records = self.log_capture.as_ref().map(|log_capture| log_capture.records()).unwrap_or_default();
+            self.diagnostics_overlay.draw(&mut overlay, self.renderer.stats(), scene, &records);
+        }
+        overlay
+    }
+
+    /// Returns true if photo mode is active.
+    pub fn photo_mode(&self) -> bool {
+        self.photo_mode.is_some()
+    }
+
+    /// Saves photo of the active scene to a PNG file. Photo is rendered `Settings::photo_supersampling` times larger than the window
+    /// and downsampled, which smooths its edges. Like other scene captures, it doesn't show particles, UI or debug shapes.
+    pub fn take_photo<P: AsRef<Path>>(&mut self, path: P) -> Result<(), CaptureError> {
+        let supersampling = self.settings.photo_supersampling();
+        let dimensions = self.renderer.window_dimensions();
+        let renderer = self.renderer.vulkan_mut().ok_or(CaptureError::UnsupportedRendererError)?;
+        let scene_capture = renderer.create_scene_capture(dimensions.width as u32 * supersampling, dimensions.height as u32 * supersampling)?;
+        let image = renderer.capture_scene(&scene_capture, &self.asset_manager)?;
+        scene_capture::downsample(&image, supersampling).save(path)?;
+        Ok(())
+    }
+
+    /// Saves photo to the photo directory from settings, logging the result.
+    fn save_photo(&mut self) {
+        let directory = self.settings.photo_directory().to_path_buf();
+        let path = photo_mode::photo_path(&directory, SystemTime::now());
+        let result = std::fs::create_dir_all(&directory).map_err(CaptureError::from).and_then(|()| self.take_photo(&path));
+        match result {
+            Ok(()) => info!("Saved photo {}", path.display()),
+            Err(err) => error!("Couldn't save photo {}: {}", path.display(), err),
+        }
+    }
+
+    /// Returns subsystems paused globally or by the active scene.
+    fn paused(&self) -> PauseFlags {
+        match self.asset_manager.active_scene() {
+            Some(scene) => self.pause_flags.combined(scene.pause_flags()),
+            None => self.pause_flags,
+        }
+    }
+
     /// Returns a mutable reference to the asset manager.
     pub fn asset_manager_mut(&mut self) -> &mut AssetManager {
         &mut self.asset_manager
     }
 
+    /// Starts saving given number of consecutive frames of the active scene to the directory, as frame_00000.png and so on,
+    /// replacing capture in progress. Frames are captured like screenshots, at window size and without particles, UI or debug shapes.
+    /// While capturing, game advances by exactly one update per frame, so frames are evenly spaced however long capturing takes.
+    /// If engine was built with video-encoding feature, frames are also encoded to capture.mp4 with external ffmpeg.
+    pub fn capture_sequence<P: Into<PathBuf>>(&mut self, dir: P, frames: u32) -> Result<(), CaptureError> {
+        let directory = dir.into();
+        std::fs::create_dir_all(&directory)?;
+        let dimensions = self.renderer.window_dimensions();
+        let renderer = self.renderer.vulkan_mut().ok_or(CaptureError::UnsupportedRendererError)?;
+        let scene_capture = renderer.create_scene_capture(dimensions.width as u32, dimensions.height as u32)?;
+        self.capture_sequence = Some(CaptureSequence::new(directory, scene_capture, frames));
+        Ok(())
+    }
+
+    /// Returns true if a capture sequence is in progress.
+    pub fn capturing(&self) -> bool {
+        self.capture_sequence.is_some()
+    }
+
+    /// Captures the next frame of capture sequence in progress, finishing it after its last frame.
+    fn update_capture(&mut self) {
+        profile_scope!("capture");
+        let finished = match &mut self.capture_sequence {
+            Some(sequence) => {
+                // sequences are only started with the Vulkan renderer
+                if let Some(renderer) = self.renderer.vulkan_mut().filter(|_| !sequence.finished()) {
+                    sequence.capture_frame(renderer, &self.asset_manager);
+                }
+                sequence.finished()
+            },
+            None => false,
+        };
+        if finished {
+            if let Some(sequence) = self.capture_sequence.take() {
+                sequence.finish(self.settings.time_per_update());
+            }
+        }
+    }
+
+    /// Uploads streamed assets within the budget of this frame, prioritizing assets seen by the camera of the active scene.
+    fn update_streaming(&mut self) {
+        if self.asset_manager.streaming_queue().is_empty() {
+            return;
+        }
+        let window_dimensions = self.renderer.window_dimensions();
+        let frustum = self.asset_manager.active_scene()
+                                        .map(|scene| scene.view_camera().frustum(window_dimensions.width as f32, window_dimensions.height as f32));
+        self.asset_manager.update_streaming(frustum.as_ref());
+    }
+
+    /// Records the frame for telemetry, answers pending Prometheus scrapes and exports metrics once per telemetry interval.
+    fn update_telemetry(&mut self, elapsed: Duration) {
+        let telemetry = match &mut self.telemetry {
+            Some(telemetry) => telemetry,
+            None => return,
+        };
+        telemetry.record_frame(elapsed);
+        if telemetry.export_due() {
+            let stats = self.renderer.stats();
+            let (mesh_memory_allocated, mesh_memory_used) = self.asset_manager.uploader().map(|uploader| uploader.mesh_memory_usage()).unwrap_or((0, 0));
+            telemetry.export(EngineMetrics {
+                objects: self.asset_manager.active_scene().map_or(0, |scene| scene.objects().len()),
+                objects_rendered: stats.objects_rendered,
+                draw_calls: stats.draw_calls,
+                triangles_rendered: stats.triangles_rendered,
+                meshes: self.asset_manager.mesh_count(),
+                textures: self.asset_manager.texture_count(),
+                estimated_gpu_memory: self.asset_manager.estimated_memory_usage(),
+                mesh_memory_allocated,
+                mesh_memory_used,
+                streamed_texture_memory: self.asset_manager.texture_streamer().memory_usage(),
+                network_connections: self.network.as_ref().map_or(0, |network| network.connections().len()),
+            });
+        }
+        telemetry.serve();
+    }
+
     fn handle_input<S: EventHandler>(&mut self, game: &mut S) {
+        profile_scope!("input");
         let pending_events = self.input_system.fetch_pending_events();
         let mut esc_pressed = false;
+        let mut editor_toggle_pressed = false;
+        let mut photo_mode_toggle_pressed = false;
+        let mut photo_pressed = false;
+        let mut diagnostics_overlay_toggle_pressed = false;
+        let mut close_requested = false;
+        let editor_toggle_key = self.settings.editor_toggle_key();
+        let photo_mode_key = self.settings.photo_mode_key();
+        let photo_key = self.settings.photo_key();
+        let diagnostics_overlay_key = self.settings.diagnostics_overlay_key();
 
         for event in pending_events.iter() {
             match event {
                 Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::CloseRequested => std::process::exit(0),
-                    WindowEvent::Resized(_window_size) => self.renderer.force_recreate_swapchain(),
-                    WindowEvent::HiDpiFactorChanged(_dpi) => self.renderer.force_recreate_swapchain(),
+                    WindowEvent::CloseRequested => close_requested = true,
+                    WindowEvent::Resized(_window_size) => self.renderer.resize(),
+                    WindowEvent::HiDpiFactorChanged(_dpi) => self.renderer.resize(),
                     WindowEvent::KeyboardInput { input, .. } => match input {
                         KeyboardInput {
                             virtual_keycode: Some(VirtualKeyCode::Escape),
                             state: ElementState::Pressed,
                             .. 
                         } => esc_pressed = true,
+                        KeyboardInput {
+                            virtual_keycode: Some(keycode),
+                            state: ElementState::Pressed,
+                            ..
+                        } if Some(*keycode) == editor_toggle_key => editor_toggle_pressed = true,
+                        KeyboardInput {
+                            virtual_keycode: Some(keycode),
+                            state: ElementState::Pressed,
+                            ..
+                        } if Some(*keycode) == photo_mode_key => photo_mode_toggle_pressed = true,
+                        KeyboardInput {
+                            virtual_keycode: Some(keycode),
+                            state: ElementState::Pressed,
+                            ..
+                        } if Some(*keycode) == photo_key => photo_pressed = true,
+                        KeyboardInput {
+                            virtual_keycode: Some(keycode),
+                            state: ElementState::Pressed,
+                            ..
+                        } if Some(*keycode) == diagnostics_overlay_key => diagnostics_overlay_toggle_pressed = true,
                         _ => (),
                     },
                     _ => (),
@@ -117,32 +549,204 @@ impl Engine {
             }
         }
 
-        match &mut self.editor {
+        if editor_toggle_pressed {
+            let editor_enabled = !self.editor_enabled;
+            self.set_editor_enabled(editor_enabled);
+            return;
+        }
+        if photo_mode_toggle_pressed {
+            if self.photo_mode.is_some() {
+                self.exit_photo_mode();
+            } else {
+                self.enter_photo_mode();
+            }
+        }
+        if photo_pressed && self.photo_mode.is_some() {
+            self.save_photo();
+        }
+        if diagnostics_overlay_toggle_pressed {
+            self.diagnostics_overlay_enabled = !self.diagnostics_overlay_enabled;
+        }
+
+        let editor_enabled = self.editor_enabled;
+
+        if close_requested {
+            match self.editor.as_mut().filter(|_| editor_enabled) {
+                Some(editor) if editor.has_unsaved_changes() => {
+                    if editor.run_game() {
+                        self.input_system.grab_cursor(false);
+                        self.input_system.hide_cursor(false);
+                        self.input_system.actions_mut().release_all();
+                        editor.pause_game(&self.asset_manager);
+                    }
+                    editor.request_exit();
+                },
+                _ => self.exit_requested = true,
+            }
+        }
+
+        let dpi = self.renderer.window_dpi();
+        match self.editor.as_mut().filter(|_| editor_enabled) {
             Some(editor) => {
                 if editor.run_game() && esc_pressed {
                     self.input_system.grab_cursor(false);
                     self.input_system.hide_cursor(false);
-                    editor.set_run_game(false, Some(&self.asset_manager));
+                    self.input_system.actions_mut().release_all();
+                    editor.pause_game(&self.asset_manager);
                 } else if editor.run_game() && !esc_pressed {
-                    game.process_input(&mut self.input_system, input::convert_to_input_events(pending_events));
+                    Self::process_game_input(game, &mut self.input_system, &mut self.ui, &mut self.event_bus, dpi, pending_events);
                 } else {
                     editor.handle_input(pending_events, &mut self.input_system);
                 }
             },
-            None => game.process_input(&mut self.input_system, input::convert_to_input_events(pending_events)),
+            None => Self::process_game_input(game, &mut self.input_system, &mut self.ui, &mut self.event_bus, dpi, pending_events),
         }  
     }
 
+    /// Passes input events through input layers and UI, then updates input actions and passes remaining events to the game.
+    /// Layers above UI see events first, UI consumes clicks on its buttons and layers below it see the rest.
+    /// UI events are handed to the game on the next update.
+    fn process_game_input<S: EventHandler>(game: &mut S, input_system: &mut InputSystem, ui: &mut Ui, event_bus: &mut EventBus,
+                                           dpi: f64, pending_events: Vec<Event>) {
+        let mut input_events = input::convert_to_input_events(pending_events);
+        input_events.extend(input_system.fetch_gamepad_events());
+        let input_events = input_system.route_events(input_events, UI_PRIORITY + 1, std::i32::MAX);
+        let mut ui_events = Vec::new();
+        let input_events: Vec<InputEvent> = input_events.into_iter().filter(|input_event| {
+            ui_events.extend(ui.handle_input(std::slice::from_ref(input_event), dpi));
+            !ui.consumes_input(input_event)
+        }).collect();
+        let input_events = input_system.route_events(input_events, std::i32::MIN, UI_PRIORITY);
+
+        for input_event in &input_events {
+            input_system.handle_event(input_event);
+        }
+        input_system.update_axes();
+        for ui_event in ui_events {
+            event_bus.publish(GameEvent::Ui(ui_event));
+        }
+        game.process_input(input_system, input_events);
+    }
+
     fn update<S: EventHandler>(&mut self, game: &mut S, time_per_update: Duration) {
-        match &mut self.editor {
+        let editor_enabled = self.editor_enabled;
+        let game_updates = match self.editor.as_mut().filter(|_| editor_enabled) {
             Some(editor) => {
                 if editor.run_game() {
-                    game.update(&self.settings, &mut self.asset_manager, time_per_update);
+                    1
                 } else {
                     editor.update(&mut self.asset_manager, time_per_update);
+                    if editor.exit_confirmed() {
+                        self.exit_requested = true;
+                        return;
+                    }
+                    editor.take_pending_steps()
                 }
             },
-            None => game.update(&self.settings, &mut self.asset_manager, time_per_update),
+            None => 1,
+        };
+        for _ in 0..game_updates {
+            if self.photo_mode.is_some() {
+                if let Some(scene) = self.asset_manager.active_scene_mut() {
+                    game.update_photo_camera(scene.camera_mut(), time_per_update);
+                }
+            } else {
+                self.update_game(game, time_per_update);
+            }
+        }
+    }
+
+    /// Runs single fixed update of the game, preceded by receiving network messages and followed by tweens, origin shift, videos, physics step and trigger detection,
+    /// unless they're paused.
+    /// Events published during the update, including objects added and removed and scene changes, are handed to the game right after it.
+    fn update_game<S: EventHandler>(&mut self, game: &mut S, time_per_update: Duration) {
+        profile_scope!("update");
+        if let Some(network) = &mut self.network {
+            profile_scope!("network");
+            for event in network.update() {
+                game.handle_network_event(event, network, &mut self.asset_manager);
+            }
+        }
+        {
+            profile_scope!("game update");
+            if game.update(&self.settings, &mut self.asset_manager, time_per_update) == ControlFlow::Exit {
+                self.exit_requested = true;
+            }
+        }
+        let paused = self.paused();
+        if !paused.animations {
+            self.tween_system.update(time_per_update, &mut self.asset_manager, &mut self.ui);
+        }
+        self.update_origin();
+        if !paused.videos {
+            self.asset_manager.update_videos(time_per_update);
+        }
+        if !paused.physics {
+            {
+                profile_scope!("physics");
+                self.physics_system.step(&mut self.asset_manager, time_per_update, &mut self.event_bus);
+            }
+            profile_scope!("collision");
+            match self.asset_manager.active_scene() {
+                Some(scene) => self.collision_system.update(scene, &mut self.event_bus),
+                None => self.collision_system.clear(),
+            }
+        }
+        if let Some(language) = self.asset_manager.localization_mut().take_language_change() {
+            self.event_bus.publish(GameEvent::LanguageChanged(language));
+        }
+        for event in self.asset_manager.take_scene_events() {
+            self.event_bus.publish(event);
+        }
+        for event in self.event_bus.drain() {
+            game.handle_event(&event, &mut self.asset_manager);
+        }
+        game.update_audio(&mut self.audio_system, &self.asset_manager);
+    }
+
+    /// Shifts origin of the active scene to its camera when the camera moved far from it, along with bodies, particles and tweens,
+    /// so that the next physics step and frame use small coordinates.
+    fn update_origin(&mut self) {
+        let scene = match self.asset_manager.active_scene_mut() {
+            Some(scene) => scene,
+            None => return,
+        };
+        if let Some(offset) = scene.update_origin() {
+            debug!("Shifted origin of scene {} by {:?}", scene.name(), offset);
+            self.physics_system.shift_origin(offset.x, offset.y, offset.z);
+            self.particle_system.shift_origin(&offset);
+            self.tween_system.shift_origin(&offset, scene);
+            self.event_bus.publish(GameEvent::Scene(SceneEvent::OriginShifted(offset)));
+        }
+    }
+
+    /// Updates sound emitters while the game runs, sounds are paused while the game is edited or audio is paused.
+    fn update_audio(&mut self) {
+        profile_scope!("audio");
+        let editor_enabled = self.editor_enabled;
+        let game_running = self.editor.as_ref().filter(|_| editor_enabled).map(Editor::run_game).unwrap_or(true);
+        let playing = game_running && !self.paused().audio;
+        self.audio_system.set_paused(!playing);
+        if playing {
+            self.audio_system.update(&self.asset_manager);
+        }
+    }
+
+    /// Simulates particles once per frame, unless they're paused. Particles keep playing while the game is edited, so that edited effects can be previewed.
+    fn update_particles(&mut self, elapsed: Duration) {
+        profile_scope!("particles");
+        let editor_enabled = self.editor_enabled;
+        if let Some(editor) = self.editor.as_mut().filter(|_| editor_enabled) {
+            for object in editor.take_restarted_emitters() {
+                self.particle_system.restart(object);
+            }
+        }
+        let paused = self.paused().particles;
+        let asset_manager = &self.asset_manager;
+        match asset_manager.active_scene() {
+            Some(_) if paused => (),
+            Some(scene) => self.particle_system.update(elapsed, scene, &|name| asset_manager.particle_effect(name)),
+            None => self.particle_system.clear(),
         }
     }
 
@@ -156,25 +760,82 @@ impl Engine {
         let mut lag = Duration::new(0, 0);
 
         game.init(&self.settings, &mut self.asset_manager);
+        self.load_input_bindings();
 
         if let Some(editor) = &mut self.editor {
             editor.sync_editor(&mut self.asset_manager);
         }
 
         loop {
+            // spans of the previous iteration are all closed here
+            profiling::finish_frame();
             let elapsed = previous_time.elapsed();
             previous_time = Instant::now();
             lag += elapsed;
             
             self.handle_input(&mut game);
+            if self.exit_requested {
+                break;
+            }
+            if self.capture_sequence.is_some() {
+                lag = time_per_update;
+            }
 
-            while lag >= time_per_update {
+            while lag >= time_per_update && !self.exit_requested {
                 self.update(&mut game, time_per_update);
 
                 lag -= time_per_update;
             }
+            if self.exit_requested {
+                break;
+            }
+            self.update_audio();
+            self.update_streaming();
+            self.asset_manager.update_world();
+            self.asset_manager.update_texture_streaming();
+            self.update_capture();
+            self.update_telemetry(elapsed);
+
+            if self.settings.headless() {
+                // no frame is rendered which would wait for uploaded assets
+                if let Err(err) = self.asset_manager.uploader().map_or(Ok(()), |uploader| uploader.wait_for_uploads()) {
+                    error!("Failed to upload assets: {}", err);
+                }
+                if let Some(remaining) = time_per_update.checked_sub(lag) {
+                    std::thread::sleep(remaining);
+                }
+                continue;
+            }
+            self.update_particles(elapsed);
+            self.diagnostics_overlay.record_frame(elapsed);
+
+            profile_scope!("render");
+            let window_dimensions = self.renderer.window_dimensions();
+            self.ui.update_layout((window_dimensions.width as f32, window_dimensions.height as f32), &self.asset_manager);
+            let overlay = self.build_overlay();
+
+            let renderer = match &mut self.renderer {
+                EngineRenderer::Vulkan(renderer) => renderer,
+                EngineRenderer::Null(scene_renderer, _dimensions) => {
+                    match scene_renderer.render(&self.asset_manager) {
+                        Ok(()) => Self::log_fps(&mut fps_counter, &mut last_fps_counter_log, log_fps_frequency),
+                        Err(err) => error!("Couldn't render scene: {}", err),
+                    }
+                    continue;
+                },
+                #[cfg(feature = "wgpu-backend")]
+                EngineRenderer::Wgpu(scene_renderer, _window) => {
+                    match scene_renderer.render(&self.asset_manager) {
+                        Ok(()) => Self::log_fps(&mut fps_counter, &mut last_fps_counter_log, log_fps_frequency),
+                        Err(err) => error!("Couldn't render scene: {}", err),
+                    }
+                    continue;
+                },
+            };
+            renderer.update_render_scale(elapsed);
 
-            let mut command_buffer = match self.renderer.create_command_buffer() {
+            let editor_enabled = self.editor_enabled;
+            let mut command_buffer = match renderer.create_command_buffer() {
                 Ok(res) => res,
                 Err(err) => {
                     error!("Couldn't create command buffer: {}", err);
@@ -182,13 +843,34 @@ impl Engine {
                 }
             };
 
-            if let Some(editor) = &mut self.editor {
+            if let Some(editor) = self.editor.as_mut().filter(|_| editor_enabled) {
                 if !editor.run_game() {
+                    command_buffer = match editor.add_preview_commands(renderer, command_buffer, &mut self.asset_manager) {
+                        Ok(res) => res,
+                        Err(err) => {
+                            error!("Couldn't render editor previews: {}", err);
+                            continue;
+                        }
+                    };
+                    editor.update_statistics(renderer.stats(), &self.asset_manager);
+                    editor.update_profiler();
+                    if let Some(log_capture) = &self.log_capture {
+                        editor.update_console(log_capture);
+                    }
                     command_buffer = editor.add_glyph_commands(command_buffer);
+                    renderer.set_selected_objects(editor.selected_objects());
+                    renderer.set_free_camera(true);
+                    editor.draw_helpers(renderer.debug_draw_mut(), &self.asset_manager);
+                } else {
+                    renderer.set_selected_objects(Vec::new());
+                    renderer.set_free_camera(false);
                 }
+            } else {
+                renderer.set_selected_objects(Vec::new());
+                renderer.set_free_camera(self.photo_mode.is_some());
             }
 
-            let (image_num, acquire_future, mut command_buffer) = match self.renderer.render_scene(command_buffer, &mut self.asset_manager) {
+            let (image_num, acquire_future, mut command_buffer) = match renderer.render_scene(command_buffer, &mut self.asset_manager) {
                 Ok(res) => res,
                 Err(err) => {
                     error!("Couldn't render scene: {}", err);
@@ -196,30 +878,109 @@ impl Engine {
                 }
             };
 
-            if let Some(editor) = &mut self.editor {
+            command_buffer = match renderer.add_particle_commands(command_buffer, &self.particle_system, &self.asset_manager) {
+                Ok(res) => res,
+                Err(err) => {
+                    error!("Couldn't render particles: {}", err);
+                    continue;
+                }
+            };
+
+            if self.photo_mode.is_none() {
+                command_buffer = match renderer.add_ui_commands(command_buffer, &self.ui, &self.asset_manager) {
+                    Ok(res) => res,
+                    Err(err) => {
+                        error!("Couldn't render UI: {}", err);
+                        continue;
+                    }
+                };
+            }
+
+            command_buffer = match renderer.add_overlay_commands(command_buffer, &overlay) {
+                Ok(res) => res,
+                Err(err) => {
+                    error!("Couldn't render overlay: {}", err);
+                    continue;
+                }
+            };
+
+            if let Some(editor) = self.editor.as_mut().filter(|_| editor_enabled) {
                 if !editor.run_game() {
-                    command_buffer = editor.add_draw_commands(self.renderer.queues().graphics_queue(), command_buffer);
+                    command_buffer = editor.add_draw_commands(renderer.queues().graphics_queue(), command_buffer);
                 }
             }
 
-            match self.renderer.execute_command_buffer(image_num, acquire_future, command_buffer) {
-                Ok(()) => {
-                    let fps = fps_counter.tick();
-                    if last_fps_counter_log.elapsed() >= log_fps_frequency {
-                        info!("Current FPS: {}", fps);
-                        last_fps_counter_log = Instant::now();
-                    }
-                },
+            match renderer.execute_command_buffer(image_num, acquire_future, command_buffer) {
+                Ok(()) => Self::log_fps(&mut fps_counter, &mut last_fps_counter_log, log_fps_frequency),
                 Err(err) => {
                     error!("Couldn't execute command buffer for frame: {}", err);
                 } 
             }
         }
+
+        self.shutdown(&mut game);
+    }
+
+    /// Counts rendered frame, logging frames per second once per given interval.
+    fn log_fps(fps_counter: &mut FPSCounter, last_fps_counter_log: &mut Instant, log_fps_frequency: Duration) {
+        let fps = fps_counter.tick();
+        if last_fps_counter_log.elapsed() >= log_fps_frequency {
+            info!("Current FPS: {}", fps);
+            *last_fps_counter_log = Instant::now();
+        }
+    }
+
+    /// Replaces default input bindings with ones saved in the controls settings file, if it exists.
+    fn load_input_bindings(&mut self) {
+        if let Some(path) = self.settings.input_bindings_file().filter(|path| path.exists()) {
+            match self.input_system.load_bindings(path) {
+                Ok(()) => info!("Loaded input bindings from {}", path.display()),
+                Err(err) => warn!("Couldn't load input bindings from {}, keeping default bindings: {}", path.display(), err),
+            }
+        }
     }
+
+    /// Lets the game save its state, then waits for the GPU and asset uploads to finish, so that everything can be dropped safely.
+    fn shutdown<S: EventHandler>(&mut self, game: &mut S) {
+        info!("Shutting down");
+        game.shutdown(&self.settings, &mut self.asset_manager);
+        if let Some(path) = self.settings.input_bindings_file() {
+            if let Err(err) = self.input_system.save_bindings(path) {
+                error!("Couldn't save input bindings to {}: {}", path.display(), err);
+            }
+        }
+        if let Err(err) = self.asset_manager.uploader().map_or(Ok(()), |uploader| uploader.wait_for_uploads()) {
+            error!("Failed to finish asset uploads: {}", err);
+        }
+        if !self.settings.headless() {
+            if let Err(err) = self.renderer.wait_idle() {
+                error!("Couldn't wait for the GPU to finish: {}", err);
+            }
+        }
+    }
+}
+
+/// Tells the engine whether to keep running after an update of the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlFlow {
+    Continue,
+    /// Stops the main loop after the current update, then calls `EventHandler::shutdown` and returns from `Engine::run`.
+    Exit,
 }
 
 pub trait EventHandler {
     fn process_input(&mut self, input_system: &mut InputSystem, input_events: Vec<InputEvent>);
-    fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration);
+    /// Called at fixed intervals, returns `ControlFlow::Exit` to quit the game.
+    fn update(&mut self, settings: &Settings, asset_manager: &mut AssetManager, elapsed_time: Duration) -> ControlFlow;
     fn init(&mut self, settings: &Settings, asset_manager: &mut AssetManager);
+    /// Called once when the engine stops, after the window is closed or an update requested exit, used to persist game state.
+    fn shutdown(&mut self, _settings: &Settings, _asset_manager: &mut AssetManager) {}
+    /// Called instead of update while photo mode is active, used to move the free camera photos are taken with.
+    fn update_photo_camera(&mut self, _camera: &mut Camera, _elapsed_time: Duration) {}
+    /// Called after every update while the game runs, used to play sounds and music.
+    fn update_audio(&mut self, _audio_system: &mut AudioSystem, _asset_manager: &AssetManager) {}
+    /// Called after every update for each event published during it, such as collisions of objects or objects entering triggers.
+    fn handle_event(&mut self, _event: &GameEvent, _asset_manager: &mut AssetManager) {}
+    /// Called before every update for each network event, if engine hosts or connects to a game.
+    fn handle_network_event(&mut self, _event: NetworkEvent, _network: &mut Network, _asset_manager: &mut AssetManager) {}
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ketch_core::pause::PauseFlags;
+use ketch_core::resource::camera::Camera;
+
+/// State changed by photo mode, which is restored when it ends.
+pub(crate) struct PhotoMode {
+    free_camera: Option<Camera>,
+    pause_flags: PauseFlags,
+}
+
+impl PhotoMode {
+    /// Remembers free camera of the active scene, if there is one, and global pause flags.
+    pub(crate) fn new(free_camera: Option<Camera>, pause_flags: PauseFlags) -> Self {
+        PhotoMode {
+            free_camera,
+            pause_flags,
+        }
+    }
+
+    /// Returns free camera the active scene had before photo mode moved it.
+    pub(crate) fn free_camera(&self) -> Option<&Camera> {
+        self.free_camera.as_ref()
+    }
+
+    /// Returns global pause flags from before photo mode.
+    pub(crate) fn pause_flags(&self) -> PauseFlags {
+        self.pause_flags
+    }
+}
+
+/// Returns path of a new photo in the directory, named after the time it's taken, e.g. photo_1554112233445.png.
+pub(crate) fn photo_path(directory: &Path, time: SystemTime) -> PathBuf {
+    let millis = time.duration_since(UNIX_EPOCH).map(|duration| duration.as_millis()).unwrap_or(0);
+    directory.join(format!("photo_{}.png", millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn photos_are_named_after_time() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_554_112_233_445);
+
+        assert_eq!(Path::new("screenshots/photo_1554112233445.png"), photo_path(Path::new("screenshots"), time));
+    }
+}
@@ -0,0 +1,211 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ketch_core::settings::TelemetryExport;
+
+use log::*;
+
+/// Prefix of metric names served to Prometheus.
+const METRIC_PREFIX: &str = "ketch_";
+/// Time a Prometheus scraper has to send its request before metrics are sent anyway.
+const REQUEST_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// State of the engine when metrics are exported, besides frame statistics which the exporter collects itself.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub(crate) struct EngineMetrics {
+    pub(crate) objects: usize,
+    pub(crate) objects_rendered: u32,
+    pub(crate) draw_calls: u32,
+    pub(crate) triangles_rendered: u64,
+    pub(crate) meshes: usize,
+    pub(crate) textures: usize,
+    pub(crate) estimated_gpu_memory: u64,
+    pub(crate) mesh_memory_allocated: u64,
+    pub(crate) mesh_memory_used: u64,
+    pub(crate) streamed_texture_memory: u64,
+    pub(crate) network_connections: usize,
+}
+
+/// Metrics of an export interval, as names with help texts and values.
+struct Sample {
+    timestamp: f64,
+    metrics: Vec<(&'static str, &'static str, f64)>,
+}
+
+impl Sample {
+    /// Returns sample as a single line JSON object with a timestamp in seconds since the Unix epoch.
+    fn to_json_line(&self) -> String {
+        let mut line = format!("{{\"timestamp\":{:.3}", self.timestamp);
+        for (name, _, value) in &self.metrics {
+            line.push_str(&format!(",\"{}\":{}", name, value));
+        }
+        line.push('}');
+        line
+    }
+
+    /// Returns sample in Prometheus text exposition format, with every metric as a gauge.
+    fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        for (name, help, value) in &self.metrics {
+            text.push_str(&format!("# HELP {prefix}{name} {help}\n# TYPE {prefix}{name} gauge\n{prefix}{name} {value}\n",
+                                   prefix = METRIC_PREFIX, name = name, help = help, value = value));
+        }
+        text
+    }
+}
+
+enum Sink {
+    JsonLines(File),
+    Prometheus { listener: TcpListener, latest: String },
+}
+
+/// Exports frame statistics, asset counts and memory usage at fixed intervals, so that long soak tests and servers can be monitored.
+pub(crate) struct TelemetryExporter {
+    sink: Sink,
+    interval: Duration,
+    last_export: Instant,
+    frames: u32,
+    frame_time_total: Duration,
+    frame_time_max: Duration,
+}
+
+impl TelemetryExporter {
+    /// Opens the JSON lines file for appending or starts listening for Prometheus scrapers.
+    pub(crate) fn new(export: &TelemetryExport, interval: Duration) -> io::Result<Self> {
+        let sink = match export {
+            TelemetryExport::JsonLines(path) => {
+                info!("Exporting telemetry to {}", path.display());
+                Sink::JsonLines(OpenOptions::new().create(true).append(true).open(path)?)
+            },
+            TelemetryExport::Prometheus(address) => {
+                let listener = TcpListener::bind(address)?;
+                listener.set_nonblocking(true)?;
+                info!("Serving telemetry for Prometheus on {}", listener.local_addr()?);
+                Sink::Prometheus { listener, latest: String::new() }
+            },
+        };
+        Ok(TelemetryExporter {
+            sink,
+            interval,
+            last_export: Instant::now(),
+            frames: 0,
+            frame_time_total: Duration::new(0, 0),
+            frame_time_max: Duration::new(0, 0),
+        })
+    }
+
+    /// Records time of a frame, or of an iteration of the main loop in headless mode.
+    pub(crate) fn record_frame(&mut self, frame_time: Duration) {
+        self.frames += 1;
+        self.frame_time_total += frame_time;
+        self.frame_time_max = self.frame_time_max.max(frame_time);
+    }
+
+    /// Returns true if the export interval passed since metrics were last exported.
+    pub(crate) fn export_due(&self) -> bool {
+        self.last_export.elapsed() >= self.interval
+    }
+
+    /// Exports given metrics with frame statistics recorded since the last export, which are then reset.
+    /// Failed writes are logged, so that monitoring never stops the game.
+    pub(crate) fn export(&mut self, engine_metrics: EngineMetrics) {
+        let sample = self.sample(engine_metrics, SystemTime::now());
+        self.last_export = Instant::now();
+        self.frames = 0;
+        self.frame_time_total = Duration::new(0, 0);
+        self.frame_time_max = Duration::new(0, 0);
+        match &mut self.sink {
+            Sink::JsonLines(file) => if let Err(err) = writeln!(file, "{}", sample.to_json_line()) {
+                error!("Couldn't write telemetry: {}", err);
+            },
+            Sink::Prometheus { latest, .. } => *latest = sample.to_prometheus(),
+        }
+    }
+
+    /// Answers pending requests of Prometheus scrapers with the latest exported metrics. Does nothing for other sinks.
+    pub(crate) fn serve(&mut self) {
+        if let Sink::Prometheus { listener, latest } = &self.sink {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => if let Err(err) = respond(stream, latest) {
+                        warn!("Couldn't send telemetry to Prometheus: {}", err);
+                    },
+                    Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(err) => {
+                        warn!("Couldn't accept telemetry connection: {}", err);
+                        break;
+                    },
+                }
+            }
+        }
+    }
+
+    fn sample(&self, engine_metrics: EngineMetrics, now: SystemTime) -> Sample {
+        let average_frame_time = if self.frames > 0 { self.frame_time_total / self.frames } else { Duration::new(0, 0) };
+        let mut metrics = vec![
+            ("frames", "Frames since the previous export, or main loop iterations in headless mode.", f64::from(self.frames)),
+            ("frame_time_average_ms", "Average frame time since the previous export in milliseconds.", millis(average_frame_time)),
+            ("frame_time_max_ms", "Longest frame time since the previous export in milliseconds.", millis(self.frame_time_max)),
+            ("objects", "Objects in the active scene.", engine_metrics.objects as f64),
+            ("objects_rendered", "Objects drawn in the last frame.", f64::from(engine_metrics.objects_rendered)),
+            ("draw_calls", "Draw calls recorded in the last frame.", f64::from(engine_metrics.draw_calls)),
+            ("triangles_rendered", "Triangles drawn in the last frame.", engine_metrics.triangles_rendered as f64),
+            ("meshes", "Meshes added to the asset manager.", engine_metrics.meshes as f64),
+            ("textures", "Textures added to the asset manager.", engine_metrics.textures as f64),
+            ("estimated_gpu_memory_bytes", "Estimated GPU memory used by meshes and textures.", engine_metrics.estimated_gpu_memory as f64),
+            ("mesh_memory_allocated_bytes", "GPU memory allocated for mesh memory blocks.", engine_metrics.mesh_memory_allocated as f64),
+            ("mesh_memory_used_bytes", "GPU memory of mesh memory blocks used by meshes.", engine_metrics.mesh_memory_used as f64),
+            ("streamed_texture_memory_bytes", "GPU memory used by streamed textures.", engine_metrics.streamed_texture_memory as f64),
+            ("network_connections", "Open network connections.", engine_metrics.network_connections as f64),
+        ];
+        if let Some(resident_memory) = resident_memory() {
+            metrics.push(("resident_memory_bytes", "Resident memory of the process.", resident_memory as f64));
+        }
+        let timestamp = now.duration_since(UNIX_EPOCH).map(|since_epoch| since_epoch.as_secs() as f64 + f64::from(since_epoch.subsec_millis()) / 1000.0)
+                           .unwrap_or(0.0);
+        Sample { timestamp, metrics }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
+
+/// Reads the request of a scraper, whatever its path is, and sends metrics in response.
+fn respond(mut stream: TcpStream, metrics: &str) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = [0; 1024];
+    // the request only needs to be read, so that the scraper isn't reset
+    let _ = stream.read(&mut request);
+    write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", metrics.len(), metrics)?;
+    stream.flush()
+}
+
+/// Returns resident memory of the process in bytes, which is only known on Linux.
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_is_formatted_as_json_line_and_prometheus_gauges() {
+        let sample = Sample {
+            timestamp: 12.5,
+            metrics: vec![("frames", "Frames.", 60.0), ("frame_time_max_ms", "Longest frame.", 16.5)],
+        };
+
+        assert_eq!("{\"timestamp\":12.500,\"frames\":60,\"frame_time_max_ms\":16.5}", sample.to_json_line());
+        assert_eq!("# HELP ketch_frames Frames.\n# TYPE ketch_frames gauge\nketch_frames 60\n\
+                    # HELP ketch_frame_time_max_ms Longest frame.\n# TYPE ketch_frame_time_max_ms gauge\nketch_frame_time_max_ms 16.5\n",
+                   sample.to_prometheus());
+    }
+}
@@ -1,39 +1,89 @@
-use ketch_engine::Engine;
-use ketch_core::settings::Settings;
+use ketch_engine::{ControlFlow, Engine, EventHandler};
+use ketch_core::settings::{RendererKind, Settings};
 use ketch_core::input::InputSystem;
+use ketch_core::input::input_event::InputEvent;
 use ketch_core::renderer::Renderer;
 use ketch_core::resource::AssetManager;
 use ketch_core::resource::camera::Camera;
 use ketch_core::resource::scene::Scene;
 use ketch_core::resource::object::ObjectBuilder;
+use ketch_core::renderer::golden_image::{assert_golden_image, GoldenTolerance};
 
 mod common;
 
-use std::cell::RefCell;
-use std::rc::Rc;
 use std::path::Path;
+use std::time::Duration;
+
+/// Returns settings of the null renderer, which runs without a window or GPU.
+fn null_settings() -> Settings {
+    let mut settings = Settings::new("test", 600.0, 400.0);
+    settings.set_renderer_kind(RendererKind::Null);
+    settings
+}
+
+/// Game showing a cube, which quits after a few updates.
+struct CubeGame {
+    updates_left: u32,
+}
+
+impl EventHandler for CubeGame {
+    fn process_input(&mut self, _input_system: &mut InputSystem, _input_events: Vec<InputEvent>) {}
+
+    fn update(&mut self, _settings: &Settings, _asset_manager: &mut AssetManager, _elapsed_time: Duration) -> ControlFlow {
+        self.updates_left = self.updates_left.saturating_sub(1);
+        if self.updates_left == 0 {
+            ControlFlow::Exit
+        } else {
+            ControlFlow::Continue
+        }
+    }
+
+    fn init(&mut self, _settings: &Settings, asset_manager: &mut AssetManager) {
+        let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices()).unwrap();
+        asset_manager.set_active_scene(Scene::new("test_scene", Camera::new()));
+        let object = ObjectBuilder::new("test_object").with_mesh(mesh).build();
+        asset_manager.active_scene_mut().unwrap().add_object(object);
+    }
+}
 
 #[test]
-#[ignore]
 fn engine_is_created() {
-    let settings = Settings::new("test", 600.0, 400.0);
-    let _engine = Engine::new(settings);
+    let _engine = Engine::from_settings(null_settings()).unwrap();
+}
+
+#[test]
+fn null_renderer_engine_runs_game_until_it_exits() {
+    let mut engine = Engine::from_settings(null_settings()).unwrap();
+
+    engine.run(CubeGame { updates_left: 3 });
+
+    assert_eq!(1, engine.renderer_stats().objects_rendered);
+}
+
+#[test]
+fn headless_engine_runs_game_without_window() {
+    let mut settings = Settings::new("test", 600.0, 400.0);
+    settings.set_renderer_kind(RendererKind::Hardware);
+    settings.set_headless(true);
+    let mut engine = Engine::from_settings(settings).unwrap();
+
+    engine.run(CubeGame { updates_left: 3 });
+
+    assert!(engine.input_system_mut().window().is_none());
 }
 
 #[test]
 #[ignore]
 fn surface_is_set_in_input_after_engine_creation() {
     let settings = Settings::new("test", 600.0, 400.0);
-    let mut engine = Engine::new(settings);
+    let mut engine = Engine::from_settings(settings).unwrap();
 
     assert!(engine.input_system_mut().window().is_some())
 }
 
 #[test]
-#[ignore]
 fn fetch_pending_events_does_not_panic() {
-    let settings = Settings::new("test", 600.0, 400.0);
-    let mut engine = Engine::new(settings);
+    let mut engine = Engine::from_settings(null_settings()).unwrap();
 
     let _input = engine.input_system_mut().fetch_pending_events();
 }
@@ -44,8 +94,8 @@ fn render_renders_empty_frame_without_error() {
     let settings = Settings::new("test", 600.0, 400.0);
     let input_system = InputSystem::new();
     
-    let mut renderer = Renderer::new(&settings, input_system.events_loop()).unwrap();
-    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+    let mut renderer = Renderer::new(&settings, input_system.events_loop().unwrap()).unwrap();
+    let mut asset_manager = renderer.create_asset_manager().unwrap();
     let command_buffer_result = renderer.create_command_buffer();
     assert!(command_buffer_result.is_ok());
     let command_buffer = command_buffer_result.unwrap();
@@ -62,10 +112,10 @@ fn render_simple_cube_without_texture() {
     let settings = Settings::new("test", 600.0, 400.0);
     let input_system = InputSystem::new();
     
-    let mut renderer = Renderer::new(&settings, input_system.events_loop()).unwrap();
-    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+    let mut renderer = Renderer::new(&settings, input_system.events_loop().unwrap()).unwrap();
+    let mut asset_manager = renderer.create_asset_manager().unwrap();
 
-    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices()).unwrap();
     asset_manager.add_mesh(mesh);
     let camera = Camera::new();
     asset_manager.set_active_scene(Scene::new("test_scene", camera));
@@ -88,11 +138,11 @@ fn render_simple_cube_with_texture() {
     let settings = Settings::new("test", 600.0, 400.0);
     let input_system = InputSystem::new();
     
-    let mut renderer = Renderer::new(&settings, input_system.events_loop()).unwrap();
-    let mut asset_manager = AssetManager::new(renderer.queues(), renderer.device());
+    let mut renderer = Renderer::new(&settings, input_system.events_loop().unwrap()).unwrap();
+    let mut asset_manager = renderer.create_asset_manager().unwrap();
 
-    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices());
-    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png"));
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices()).unwrap();
+    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png")).unwrap();
     asset_manager.add_texture(texture.clone());
     mesh.write().unwrap().set_texture(texture);
     asset_manager.add_mesh(mesh);
@@ -111,3 +161,34 @@ fn render_simple_cube_with_texture() {
     assert!(renderer.execute_command_buffer(image_num, acquire_future, command_buffer).is_ok());
 }
 
+#[test]
+fn loading_missing_texture_returns_error() {
+    let asset_manager = AssetManager::without_device().unwrap();
+
+    assert!(asset_manager.load_texture("missing_texture", Path::new("tests/common/data/missing.png")).is_err());
+}
+
+#[test]
+#[ignore = "needs a Vulkan device and a display, see Running without a GPU in the README"]
+fn textured_cube_matches_golden_image() {
+    let settings = Settings::new("test", 600.0, 400.0);
+    let input_system = InputSystem::new();
+
+    let mut renderer = Renderer::new(&settings, input_system.events_loop().unwrap()).unwrap();
+    let mut asset_manager = renderer.create_asset_manager().unwrap();
+
+    let mesh = asset_manager.create_mesh("test_mesh", common::model::generate_vertices(), common::model::generate_indices()).unwrap();
+    let texture = asset_manager.load_texture("test_texture", Path::new("tests/common/data/rust_logo.png")).unwrap();
+    asset_manager.add_texture(texture.clone());
+    mesh.write().unwrap().set_texture(texture);
+    asset_manager.add_mesh(mesh);
+    let mut camera = Camera::new();
+    camera.set_position(0.0, 0.0, 3.0);
+    asset_manager.set_active_scene(Scene::new("test_scene", camera));
+    let object = ObjectBuilder::new("test_object").with_mesh(asset_manager.mesh("test_mesh").unwrap()).build();
+    asset_manager.active_scene_mut().unwrap().add_object(object);
+
+    let scene_capture = renderer.create_scene_capture(256, 256).unwrap();
+    let image = renderer.capture_scene(&scene_capture, &asset_manager).unwrap();
+    assert_golden_image(&image, "tests/common/data/golden/textured_cube.png", &GoldenTolerance::default());
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::network_error::NetworkError;
+use crate::packet::{Packet, MAX_FRAGMENTS};
+
+/// Time after which unacknowledged reliable messages and connect requests are sent again.
+pub(crate) const RESEND_INTERVAL: Duration = Duration::from_millis(200);
+/// Time without any packet being sent after which heartbeat is sent, so that connection doesn't time out.
+pub(crate) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Time without any packet being received after which connection is dropped.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(5);
+/// Number of fragmented packets collected at once. Packets which lost a fragment are never completed, so only the newest ones are kept.
+const MAX_PARTIAL_PACKETS: usize = 4;
+/// Number of reliable messages from the next expected one which are accepted. Messages further ahead are dropped without acknowledgement,
+/// so the peer sends them again later. Also the number of unacknowledged messages sent at once.
+pub(crate) const MAX_PENDING_RELIABLE: u32 = 256;
+/// Number of reliable messages waiting for acknowledgement, after which queueing more fails.
+pub(crate) const MAX_UNACKED_RELIABLE: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum ConnectionState {
+    /// Client waits for the server to accept connection.
+    Connecting,
+    Connected,
+}
+
+/// Fragments of a packet received so far.
+struct PartialPacket {
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// State of a connection with a single peer, which makes reliable messages arrive once and in order.
+pub(crate) struct Connection {
+    id: u32,
+    address: SocketAddr,
+    state: ConnectionState,
+    last_received: Instant,
+    last_sent: Instant,
+    next_send_sequence: u32,
+    /// Reliable messages waiting for acknowledgement, with the time they were last sent.
+    unacked: BTreeMap<u32, (Vec<u8>, Option<Instant>)>,
+    next_receive_sequence: u32,
+    /// Reliable messages which arrived before messages preceding them.
+    received: BTreeMap<u32, Vec<u8>>,
+    partial_packets: BTreeMap<u32, PartialPacket>,
+}
+
+impl Connection {
+    pub(crate) fn new(id: u32, address: SocketAddr, state: ConnectionState) -> Self {
+        let now = Instant::now();
+        Connection {
+            id,
+            address,
+            state,
+            last_received: now,
+            last_sent: now,
+            next_send_sequence: 0,
+            unacked: BTreeMap::new(),
+            next_receive_sequence: 0,
+            received: BTreeMap::new(),
+            partial_packets: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub(crate) fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub(crate) fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    pub(crate) fn set_state(&mut self, state: ConnectionState) {
+        self.state = state;
+    }
+
+    /// Queues reliable message, it's sent on the next update. Fails if too many messages weren't acknowledged yet.
+    pub(crate) fn queue_reliable(&mut self, data: Vec<u8>) -> Result<(), NetworkError> {
+        if self.unacked.len() >= MAX_UNACKED_RELIABLE {
+            return Err(NetworkError::ReliableQueueFull(self.id));
+        }
+        self.unacked.insert(self.next_send_sequence, (data, None));
+        self.next_send_sequence += 1;
+        Ok(())
+    }
+
+    /// Returns true if no packet was received for longer than timeout.
+    pub(crate) fn timed_out(&self) -> bool {
+        self.last_received.elapsed() > TIMEOUT
+    }
+
+    pub(crate) fn mark_sent(&mut self) {
+        self.last_sent = Instant::now();
+    }
+
+    /// Handles packet received from the peer. Returns packets to send back and messages ready to be delivered.
+    pub(crate) fn receive(&mut self, packet: Packet) -> (Vec<Packet>, Vec<Vec<u8>>) {
+        self.last_received = Instant::now();
+        match packet {
+            Packet::Unreliable(data) => (Vec::new(), vec![data]),
+            Packet::Reliable { sequence, data } => {
+                if sequence >= self.next_receive_sequence {
+                    if sequence - self.next_receive_sequence >= MAX_PENDING_RELIABLE {
+                        return (Vec::new(), Vec::new());
+                    }
+                    self.received.insert(sequence, data);
+                }
+                let mut messages = Vec::new();
+                while let Some(data) = self.received.remove(&self.next_receive_sequence) {
+                    messages.push(data);
+                    self.next_receive_sequence += 1;
+                }
+                // duplicates are acknowledged too, because previous acknowledgement could have been lost
+                (vec![Packet::Ack { sequence }], messages)
+            },
+            Packet::Ack { sequence } => {
+                self.unacked.remove(&sequence);
+                (Vec::new(), Vec::new())
+            },
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Collects fragment of a packet. Returns the serialized packet once all of its fragments were received.
+    pub(crate) fn receive_fragment(&mut self, packet: u32, index: u16, count: u16, data: Vec<u8>) -> Option<Vec<u8>> {
+        self.last_received = Instant::now();
+        let (index, count) = (index as usize, count as usize);
+        if index >= count || count > MAX_FRAGMENTS {
+            return None;
+        }
+        let partial_packet = self.partial_packets.entry(packet).or_insert_with(|| PartialPacket { fragments: vec![None; count], received: 0 });
+        if partial_packet.fragments.len() != count {
+            return None;
+        }
+        if partial_packet.fragments[index].is_none() {
+            partial_packet.fragments[index] = Some(data);
+            partial_packet.received += 1;
+        }
+        if partial_packet.received == count {
+            return self.partial_packets.remove(&packet).map(|partial_packet| partial_packet.fragments.into_iter().flatten().flatten().collect());
+        }
+        while self.partial_packets.len() > MAX_PARTIAL_PACKETS {
+            let oldest = *self.partial_packets.keys().next()?;
+            self.partial_packets.remove(&oldest);
+        }
+        None
+    }
+
+    /// Returns reliable messages which were never sent or weren't acknowledged in time, and heartbeat if connection is idle.
+    /// Only messages within `MAX_PENDING_RELIABLE` from the oldest unacknowledged one are sent, since the peer drops the rest.
+    pub(crate) fn pending_packets(&mut self) -> Vec<Packet> {
+        if self.state != ConnectionState::Connected {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        let window_end = self.unacked.keys().next().map(|first| first.saturating_add(MAX_PENDING_RELIABLE)).unwrap_or(0);
+        let mut packets: Vec<Packet> = self.unacked.range_mut(..window_end)
+                                                   .filter(|(_, (_, last_sent))| last_sent.map(|last_sent| now - last_sent >= RESEND_INTERVAL).unwrap_or(true))
+                                                   .map(|(sequence, (data, last_sent))| {
+                                                       *last_sent = Some(now);
+                                                       Packet::Reliable { sequence: *sequence, data: data.clone() }
+                                                   })
+                                                   .collect();
+        if packets.is_empty() && now - self.last_sent >= HEARTBEAT_INTERVAL {
+            packets.push(Packet::Heartbeat);
+        }
+        packets
+    }
+
+    /// Returns true if connect request should be sent again.
+    pub(crate) fn should_resend_request(&self) -> bool {
+        self.state == ConnectionState::Connecting && self.last_sent.elapsed() >= RESEND_INTERVAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reliable_messages_are_delivered_once_in_order() {
+        let mut connection = Connection::new(0, "127.0.0.1:5000".parse().unwrap(), ConnectionState::Connected);
+
+        let (_, first) = connection.receive(Packet::Reliable { sequence: 1, data: vec![1] });
+        let (acks, second) = connection.receive(Packet::Reliable { sequence: 0, data: vec![0] });
+        let (_, duplicate) = connection.receive(Packet::Reliable { sequence: 1, data: vec![1] });
+
+        assert!(first.is_empty());
+        assert_eq!(vec![Packet::Ack { sequence: 0 }], acks);
+        assert_eq!(vec![vec![0], vec![1]], second);
+        assert!(duplicate.is_empty());
+    }
+
+    #[test]
+    fn fragments_are_joined_in_order() {
+        let mut connection = Connection::new(0, "127.0.0.1:5000".parse().unwrap(), ConnectionState::Connected);
+
+        let first = connection.receive_fragment(7, 1, 2, vec![3, 4]);
+        let second = connection.receive_fragment(7, 0, 2, vec![1, 2]);
+
+        assert_eq!(None, first);
+        assert_eq!(Some(vec![1, 2, 3, 4]), second);
+    }
+
+    #[test]
+    fn reliable_messages_outside_receive_window_are_not_acknowledged() {
+        let mut connection = Connection::new(0, "127.0.0.1:5000".parse().unwrap(), ConnectionState::Connected);
+
+        let (outside_acks, outside) = connection.receive(Packet::Reliable { sequence: MAX_PENDING_RELIABLE, data: vec![1] });
+        let (inside_acks, inside) = connection.receive(Packet::Reliable { sequence: MAX_PENDING_RELIABLE - 1, data: vec![0] });
+
+        assert!(outside_acks.is_empty());
+        assert!(outside.is_empty());
+        assert_eq!(vec![Packet::Ack { sequence: MAX_PENDING_RELIABLE - 1 }], inside_acks);
+        assert!(inside.is_empty());
+    }
+
+    #[test]
+    fn queueing_reliable_message_fails_when_too_many_are_unacknowledged() {
+        let mut connection = Connection::new(0, "127.0.0.1:5000".parse().unwrap(), ConnectionState::Connected);
+
+        for _ in 0..MAX_UNACKED_RELIABLE {
+            connection.queue_reliable(vec![0]).unwrap();
+        }
+
+        assert!(connection.queue_reliable(vec![0]).is_err());
+    }
+
+    #[test]
+    fn pending_packets_contain_at_most_receive_window_of_reliable_messages() {
+        let mut connection = Connection::new(0, "127.0.0.1:5000".parse().unwrap(), ConnectionState::Connected);
+        for _ in 0..=MAX_PENDING_RELIABLE {
+            connection.queue_reliable(vec![0]).unwrap();
+        }
+
+        let first = connection.pending_packets();
+        connection.receive(Packet::Ack { sequence: 0 });
+        let second = connection.pending_packets();
+
+        assert_eq!(MAX_PENDING_RELIABLE as usize, first.len());
+        assert_eq!(vec![Packet::Reliable { sequence: MAX_PENDING_RELIABLE, data: vec![0] }], second);
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use log::*;
+
+use crate::connection::{Connection, ConnectionState};
+use crate::network_error::NetworkError;
+use crate::packet::{Packet, FRAGMENT_SIZE, MAX_FRAGMENTS, MAX_PACKET_SIZE, PROTOCOL_ID};
+
+mod connection;
+mod packet;
+pub mod network_error;
+pub mod replication;
+
+/// Default maximum number of clients connected to a server.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+
+/// Id of the connection with the server on the client side.
+pub const SERVER_CONNECTION_ID: u32 = 0;
+
+/// Describes how a message is delivered.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Channel {
+    /// Message can be lost, duplicated or arrive out of order. Used for frequently sent state, like snapshots.
+    Unreliable,
+    /// Message is resent until it's acknowledged and messages are delivered in the order they were sent.
+    Reliable,
+}
+
+/// Event produced while receiving packets.
+#[derive(Clone, PartialEq, Debug)]
+pub enum NetworkEvent {
+    /// Client connected to the server, or server accepted connection of this client.
+    Connected(u32),
+    /// Connection was closed by the peer or timed out.
+    Disconnected(u32),
+    /// Serialized message received from the connection. Use `decode` to read it.
+    Message { connection: u32, data: Vec<u8> },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Role {
+    Server { max_connections: usize },
+    Client,
+}
+
+/// Client or server sending serde serializable messages over UDP.
+/// Server accepts connections from many clients, client is connected to a single server.
+pub struct Network {
+    socket: UdpSocket,
+    role: Role,
+    connections: HashMap<SocketAddr, Connection>,
+    next_connection_id: u32,
+    next_fragmented_packet: u32,
+}
+
+impl Network {
+    /// Creates server listening on given port of all interfaces. Port 0 picks any free port.
+    pub fn host(port: u16) -> Result<Self, NetworkError> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        info!("Hosting game on {}", socket.local_addr()?);
+        Ok(Network {
+            socket,
+            role: Role::Server { max_connections: DEFAULT_MAX_CONNECTIONS },
+            connections: HashMap::new(),
+            next_connection_id: SERVER_CONNECTION_ID + 1,
+            next_fragmented_packet: 0,
+        })
+    }
+
+    /// Creates client and starts connecting to the server. `NetworkEvent::Connected` is produced when the server accepts connection.
+    pub fn connect<A: ToSocketAddrs>(server_address: A) -> Result<Self, NetworkError> {
+        let server_address = server_address.to_socket_addrs()?
+                                           .next()
+                                           .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "no server address"))?;
+        let local_address: SocketAddr = if server_address.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local_address)?;
+        socket.set_nonblocking(true)?;
+        let mut network = Network {
+            socket,
+            role: Role::Client,
+            connections: HashMap::new(),
+            next_connection_id: SERVER_CONNECTION_ID,
+            next_fragmented_packet: 0,
+        };
+        network.connections.insert(server_address, Connection::new(SERVER_CONNECTION_ID, server_address, ConnectionState::Connecting));
+        network.send_packet(server_address, &Packet::ConnectRequest { protocol_id: PROTOCOL_ID })?;
+        Ok(network)
+    }
+
+    /// Returns true if this is a server.
+    pub fn is_server(&self) -> bool {
+        match self.role {
+            Role::Server { .. } => true,
+            Role::Client => false,
+        }
+    }
+
+    /// Sets maximum number of connected clients. Does nothing on a client.
+    pub fn set_max_connections(&mut self, value: usize) {
+        if let Role::Server { max_connections } = &mut self.role {
+            *max_connections = value;
+        }
+    }
+
+    /// Returns address this network is bound to.
+    pub fn local_address(&self) -> Result<SocketAddr, NetworkError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Returns ids of established connections, sorted.
+    pub fn connections(&self) -> Vec<u32> {
+        let mut connections: Vec<u32> = self.connections.values()
+                                                        .filter(|connection| connection.state() == ConnectionState::Connected)
+                                                        .map(Connection::id)
+                                                        .collect();
+        connections.sort();
+        connections
+    }
+
+    /// Sends message to the connection. Reliable messages are sent on the next update.
+    /// Messages which don't fit in a single packet are split into fragments, and are lost if any of the fragments is lost.
+    /// Sending reliable messages fails while too many of them weren't acknowledged by the connection.
+    pub fn send<M: Serialize>(&mut self, connection: u32, channel: Channel, message: &M) -> Result<(), NetworkError> {
+        let data = bincode::serialize(message)?;
+        // sequence of reliable packets is assigned when they're queued, but it doesn't change their size
+        let packet = match channel {
+            Channel::Unreliable => Packet::Unreliable(data),
+            Channel::Reliable => Packet::Reliable { sequence: 0, data },
+        };
+        let size = bincode::serialized_size(&packet)? as usize;
+        if size > MAX_FRAGMENTS * FRAGMENT_SIZE {
+            return Err(NetworkError::MessageTooLarge(size));
+        }
+        let address = self.connections.values()
+                                      .find(|candidate| candidate.id() == connection)
+                                      .map(Connection::address)
+                                      .ok_or(NetworkError::UnknownConnection(connection))?;
+        match packet {
+            Packet::Reliable { data, .. } => {
+                match self.connections.get_mut(&address) {
+                    Some(connection) => connection.queue_reliable(data),
+                    None => Ok(()),
+                }
+            },
+            packet => self.send_packet(address, &packet),
+        }
+    }
+
+    /// Sends message to all established connections.
+    pub fn broadcast<M: Serialize>(&mut self, channel: Channel, message: &M) -> Result<(), NetworkError> {
+        for connection in self.connections() {
+            self.send(connection, channel, message)?;
+        }
+        Ok(())
+    }
+
+    /// Closes connection, peer is notified, but it isn't guaranteed to receive the notification.
+    pub fn disconnect(&mut self, connection: u32) {
+        let address = self.connections.values().find(|candidate| candidate.id() == connection).map(Connection::address);
+        if let Some(address) = address {
+            if let Err(err) = self.send_packet(address, &Packet::Disconnect) {
+                warn!("Couldn't notify {} about disconnection: {}", address, err);
+            }
+            self.connections.remove(&address);
+        }
+    }
+
+    /// Receives pending packets, drops connections which timed out, sends pending reliable messages and heartbeats.
+    /// Should be called every update.
+    pub fn update(&mut self) -> Vec<NetworkEvent> {
+        let mut events = Vec::new();
+        let mut buffer = [0u8; MAX_PACKET_SIZE * 2];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, address)) => match bincode::deserialize::<Packet>(&buffer[..size]) {
+                    Ok(packet) => self.handle_packet(address, packet, &mut events),
+                    Err(err) => debug!("Ignoring malformed packet from {}: {}", address, err),
+                },
+                Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                // ICMP port unreachable is reported by some platforms as an error of the next receive
+                Err(ref err) if err.kind() == std::io::ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    error!("Couldn't receive packet: {}", err);
+                    break;
+                },
+            }
+        }
+
+        let timed_out: Vec<SocketAddr> = self.connections.values().filter(|connection| connection.timed_out()).map(Connection::address).collect();
+        for address in timed_out {
+            if let Some(connection) = self.connections.remove(&address) {
+                info!("Connection with {} timed out", address);
+                events.push(NetworkEvent::Disconnected(connection.id()));
+            }
+        }
+
+        let mut outgoing = Vec::new();
+        for connection in self.connections.values_mut() {
+            if connection.should_resend_request() {
+                outgoing.push((connection.address(), Packet::ConnectRequest { protocol_id: PROTOCOL_ID }));
+            }
+            outgoing.extend(connection.pending_packets().into_iter().map(|packet| (connection.address(), packet)));
+        }
+        for (address, packet) in outgoing {
+            if let Err(err) = self.send_packet(address, &packet) {
+                error!("Couldn't send packet to {}: {}", address, err);
+            }
+        }
+        events
+    }
+
+    fn handle_packet(&mut self, address: SocketAddr, packet: Packet, events: &mut Vec<NetworkEvent>) {
+        match (self.role, packet) {
+            (Role::Server { max_connections }, Packet::ConnectRequest { protocol_id }) => {
+                if protocol_id != PROTOCOL_ID {
+                    return;
+                }
+                if !self.connections.contains_key(&address) {
+                    if self.connections.len() >= max_connections {
+                        self.reply(address, &Packet::ConnectRejected);
+                        return;
+                    }
+                    let id = self.next_connection_id;
+                    self.next_connection_id += 1;
+                    self.connections.insert(address, Connection::new(id, address, ConnectionState::Connected));
+                    info!("Client {} connected from {}", id, address);
+                    events.push(NetworkEvent::Connected(id));
+                }
+                // accept is sent again for repeated requests, because previous one could have been lost
+                self.reply(address, &Packet::ConnectAccepted);
+            },
+            (Role::Client, Packet::ConnectAccepted) => {
+                if let Some(connection) = self.connections.get_mut(&address).filter(|connection| connection.state() == ConnectionState::Connecting) {
+                    connection.set_state(ConnectionState::Connected);
+                    events.push(NetworkEvent::Connected(connection.id()));
+                }
+            },
+            (Role::Client, Packet::ConnectRejected) => {
+                if let Some(connection) = self.connections.remove(&address) {
+                    warn!("Server {} rejected connection", address);
+                    events.push(NetworkEvent::Disconnected(connection.id()));
+                }
+            },
+            (_, Packet::Disconnect) => {
+                if let Some(connection) = self.connections.remove(&address) {
+                    events.push(NetworkEvent::Disconnected(connection.id()));
+                }
+            },
+            (_, Packet::Fragment { packet, index, count, data }) => {
+                let data = match self.connections.get_mut(&address).filter(|connection| connection.state() == ConnectionState::Connected) {
+                    Some(connection) => connection.receive_fragment(packet, index, count, data),
+                    None => return,
+                };
+                match data.map(|data| bincode::deserialize::<Packet>(&data)) {
+                    Some(Ok(Packet::Fragment { .. })) => debug!("Ignoring fragment nested in a fragmented packet from {}", address),
+                    Some(Ok(packet)) => self.handle_packet(address, packet, events),
+                    Some(Err(err)) => debug!("Ignoring malformed fragmented packet from {}: {}", address, err),
+                    None => (),
+                }
+            },
+            (_, packet) => {
+                let connection = match self.connections.get_mut(&address).filter(|connection| connection.state() == ConnectionState::Connected) {
+                    Some(connection) => connection,
+                    None => return,
+                };
+                let id = connection.id();
+                let (replies, messages) = connection.receive(packet);
+                events.extend(messages.into_iter().map(|data| NetworkEvent::Message { connection: id, data }));
+                for reply in replies {
+                    self.reply(address, &reply);
+                }
+            },
+        }
+    }
+
+    fn reply(&mut self, address: SocketAddr, packet: &Packet) {
+        if let Err(err) = self.send_packet(address, packet) {
+            error!("Couldn't send packet to {}: {}", address, err);
+        }
+    }
+
+    fn send_packet(&mut self, address: SocketAddr, packet: &Packet) -> Result<(), NetworkError> {
+        let data = bincode::serialize(packet)?;
+        if data.len() <= MAX_PACKET_SIZE {
+            self.socket.send_to(&data, address)?;
+        } else {
+            let fragmented_packet = self.next_fragmented_packet;
+            self.next_fragmented_packet = self.next_fragmented_packet.wrapping_add(1);
+            for fragment in packet::fragments(fragmented_packet, &data) {
+                self.socket.send_to(&bincode::serialize(&fragment)?, address)?;
+            }
+        }
+        if let Some(connection) = self.connections.get_mut(&address) {
+            connection.mark_sent();
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes message received in `NetworkEvent::Message`.
+pub fn decode<M: DeserializeOwned>(data: &[u8]) -> Result<M, NetworkError> {
+    Ok(bincode::deserialize(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+    use ketch_core::resource::scene::Scene;
+
+    use crate::replication::TransformSnapshot;
+
+    fn connected_pair() -> (Network, Network, u32) {
+        let mut server = Network::host(0).unwrap();
+        let server_port = server.local_address().unwrap().port();
+        let mut client = Network::connect(("127.0.0.1", server_port)).unwrap();
+        for _ in 0..100 {
+            client.update();
+            if let Some(connection) = server.update().into_iter().find_map(|event| match event {
+                NetworkEvent::Connected(connection) => Some(connection),
+                _ => None,
+            }) {
+                return (server, client, connection);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("client didn't connect");
+    }
+
+    fn scene_with_objects(count: usize) -> Scene {
+        let mut scene = Scene::new("level", Camera::new());
+        for index in 0..count {
+            scene.add_object(ObjectBuilder::new(format!("object_{}", index)).build());
+        }
+        scene
+    }
+
+    #[test]
+    fn client_receives_reliable_message_from_server() {
+        let mut server = Network::host(0).unwrap();
+        let server_port = server.local_address().unwrap().port();
+        let mut client = Network::connect(("127.0.0.1", server_port)).unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            for event in server.update() {
+                if let NetworkEvent::Connected(connection) = event {
+                    server.send(connection, Channel::Reliable, &String::from("welcome")).unwrap();
+                }
+            }
+            for event in client.update() {
+                if let NetworkEvent::Message { data, .. } = event {
+                    received.push(decode::<String>(&data).unwrap());
+                }
+            }
+            if !received.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(vec![String::from("welcome")], received);
+    }
+
+    #[test]
+    fn snapshot_larger_than_a_packet_is_replicated() {
+        let (mut server, mut client, connection) = connected_pair();
+        let mut server_scene = scene_with_objects(150);
+        for (index, object) in server_scene.objects_mut().iter_mut().enumerate() {
+            object.set_position(index as f32, 1.0, -2.0);
+        }
+        let mut client_scene = scene_with_objects(150);
+        let snapshot = TransformSnapshot::from_scene(1, &server_scene, |_| true);
+
+        let mut replicated = false;
+        for _ in 0..100 {
+            server.send(connection, Channel::Unreliable, &snapshot).unwrap();
+            server.update();
+            for event in client.update() {
+                if let NetworkEvent::Message { data, .. } = event {
+                    decode::<TransformSnapshot>(&data).unwrap().apply(&mut client_scene);
+                    replicated = true;
+                }
+            }
+            if replicated {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert!(bincode::serialized_size(&snapshot).unwrap() as usize > MAX_PACKET_SIZE);
+        assert!(replicated);
+        for (index, object) in client_scene.objects().iter().enumerate() {
+            assert_eq!((index as f32, 1.0, -2.0), object.position());
+        }
+    }
+
+    #[test]
+    fn reliable_message_larger_than_a_packet_is_delivered() {
+        let (mut server, mut client, connection) = connected_pair();
+        let message: Vec<u32> = (0..2000).collect();
+
+        server.send(connection, Channel::Reliable, &message).unwrap();
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            server.update();
+            for event in client.update() {
+                if let NetworkEvent::Message { data, .. } = event {
+                    received.push(decode::<Vec<u32>>(&data).unwrap());
+                }
+            }
+            if !received.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(vec![message], received);
+    }
+
+    #[test]
+    fn message_larger_than_all_fragments_is_rejected() {
+        let (mut server, _client, connection) = connected_pair();
+        let message = vec![0u8; MAX_FRAGMENTS * FRAGMENT_SIZE];
+
+        let result = server.send(connection, Channel::Unreliable, &message);
+
+        assert!(matches!(result, Err(NetworkError::MessageTooLarge(_))));
+    }
+}
@@ -0,0 +1,26 @@
+use quick_error::quick_error;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum NetworkError {
+        IoError(err: std::io::Error) {
+            from()
+            display("IoError: {}", err)
+            cause(err)
+        }
+        SerializationError(err: bincode::Error) {
+            from()
+            display("SerializationError: {}", err)
+            cause(err)
+        }
+        MessageTooLarge(size: usize) {
+            display("MessageTooLarge: message of {} bytes doesn't fit in the largest number of fragments", size)
+        }
+        UnknownConnection(id: u32) {
+            display("UnknownConnection: there is no connection with id {}", id)
+        }
+        ReliableQueueFull(id: u32) {
+            display("ReliableQueueFull: too many reliable messages to connection {} weren't acknowledged", id)
+        }
+    }
+}
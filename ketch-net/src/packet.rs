@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Identifies packets of this protocol, packets with other id are ignored.
+pub(crate) const PROTOCOL_ID: u32 = 0x6b65_7463;
+
+/// Largest packet which is sent, so that it isn't fragmented by the network.
+pub(crate) const MAX_PACKET_SIZE: usize = 1200;
+
+/// Bytes of a serialized packet carried by a single fragment, leaving room for the fragment header.
+pub(crate) const FRAGMENT_SIZE: usize = MAX_PACKET_SIZE - 32;
+
+/// Largest number of fragments a packet is split into, which limits size of messages.
+pub(crate) const MAX_FRAGMENTS: usize = 256;
+
+/// Datagram exchanged between client and server.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) enum Packet {
+    ConnectRequest { protocol_id: u32 },
+    ConnectAccepted,
+    ConnectRejected,
+    Disconnect,
+    Heartbeat,
+    Unreliable(Vec<u8>),
+    Reliable { sequence: u32, data: Vec<u8> },
+    Ack { sequence: u32 },
+    /// Part of a serialized packet larger than `MAX_PACKET_SIZE`. Packet is handled once all its fragments arrive.
+    Fragment { packet: u32, index: u16, count: u16, data: Vec<u8> },
+}
+
+/// Splits serialized packet into fragments, which are sent as separate datagrams.
+pub(crate) fn fragments(packet: u32, data: &[u8]) -> Vec<Packet> {
+    let count = data.chunks(FRAGMENT_SIZE).len();
+    data.chunks(FRAGMENT_SIZE)
+        .enumerate()
+        .map(|(index, chunk)| Packet::Fragment { packet, index: index as u16, count: count as u16, data: chunk.to_vec() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragments_fit_in_a_packet() {
+        let data = vec![7; FRAGMENT_SIZE * 2 + 1];
+
+        let fragments = fragments(u32::MAX, &data);
+
+        assert_eq!(3, fragments.len());
+        for fragment in &fragments {
+            assert!(bincode::serialized_size(fragment).unwrap() as usize <= MAX_PACKET_SIZE);
+        }
+    }
+}
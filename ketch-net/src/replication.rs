@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use log::*;
+
+use ketch_core::resource::object::Object;
+use ketch_core::resource::scene::Scene;
+
+/// Transform of a replicated object. Objects are matched by name, because ids differ between machines.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ObjectTransform {
+    pub name: String,
+    pub position: (f32, f32, f32),
+    pub rotation_angles: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl ObjectTransform {
+    /// Creates transform from current state of the object.
+    pub fn from_object(object: &Object) -> Self {
+        ObjectTransform {
+            name: object.name().to_string(),
+            position: object.position(),
+            rotation_angles: object.rotation_angles(),
+            scale: object.scale(),
+        }
+    }
+
+    /// Returns transform between this and the next transform, where 0.0 is this transform and 1.0 is the next one.
+    pub fn interpolate(&self, next: &ObjectTransform, t: f32) -> ObjectTransform {
+        ObjectTransform {
+            name: self.name.clone(),
+            position: lerp(self.position, next.position, t),
+            rotation_angles: (
+                lerp_angle(self.rotation_angles.0, next.rotation_angles.0, t),
+                lerp_angle(self.rotation_angles.1, next.rotation_angles.1, t),
+                lerp_angle(self.rotation_angles.2, next.rotation_angles.2, t),
+            ),
+            scale: lerp(self.scale, next.scale, t),
+        }
+    }
+
+    fn apply(&self, object: &mut Object) {
+        let (x, y, z) = self.position;
+        let (rotation_x, rotation_y, rotation_z) = self.rotation_angles;
+        let (scale_x, scale_y, scale_z) = self.scale;
+        object.set_position(x, y, z);
+        object.set_rotation_angles(rotation_x, rotation_y, rotation_z);
+        object.set_scale_xyz(scale_x, scale_y, scale_z);
+    }
+}
+
+/// Transforms of replicated objects at a given server tick. Server broadcasts snapshots and clients apply them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TransformSnapshot {
+    pub tick: u64,
+    pub objects: Vec<ObjectTransform>,
+}
+
+impl TransformSnapshot {
+    /// Creates snapshot of scene objects accepted by the filter.
+    pub fn from_scene<F: Fn(&Object) -> bool>(tick: u64, scene: &Scene, filter: F) -> Self {
+        TransformSnapshot {
+            tick,
+            objects: scene.objects().iter().filter(|object| filter(object)).map(ObjectTransform::from_object).collect(),
+        }
+    }
+
+    /// Sets transforms of scene objects with matching names.
+    pub fn apply(&self, scene: &mut Scene) {
+        for transform in &self.objects {
+            match scene.objects_mut().iter_mut().find(|object| object.name() == transform.name) {
+                Some(object) => transform.apply(object),
+                None => warn!("Replicated object {} wasn't found in scene", transform.name),
+            }
+        }
+    }
+
+    /// Returns snapshot between this and the next snapshot, used to smooth movement between snapshots arriving less often than frames.
+    /// Objects missing from the next snapshot keep their transform.
+    pub fn interpolate(&self, next: &TransformSnapshot, t: f32) -> TransformSnapshot {
+        let t = t.clamp(0.0, 1.0);
+        TransformSnapshot {
+            tick: if t < 1.0 { self.tick } else { next.tick },
+            objects: self.objects.iter()
+                                 .map(|transform| match next.objects.iter().find(|next_transform| next_transform.name == transform.name) {
+                                     Some(next_transform) => transform.interpolate(next_transform, t),
+                                     None => transform.clone(),
+                                 })
+                                 .collect(),
+        }
+    }
+}
+
+/// Keeps the newest snapshot received, ignoring snapshots which arrived out of order.
+pub struct SnapshotReceiver {
+    latest: Option<TransformSnapshot>,
+}
+
+impl SnapshotReceiver {
+    pub fn new() -> Self {
+        SnapshotReceiver {
+            latest: None,
+        }
+    }
+
+    /// Stores snapshot if it's newer than the latest one. Returns true if it was stored.
+    pub fn receive(&mut self, snapshot: TransformSnapshot) -> bool {
+        if self.latest.as_ref().map(|latest| snapshot.tick > latest.tick).unwrap_or(true) {
+            self.latest = Some(snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the newest snapshot received.
+    pub fn latest(&self) -> Option<&TransformSnapshot> {
+        self.latest.as_ref()
+    }
+}
+
+impl Default for SnapshotReceiver {
+    fn default() -> Self {
+        SnapshotReceiver::new()
+    }
+}
+
+fn lerp(from: (f32, f32, f32), to: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t, from.2 + (to.2 - from.2) * t)
+}
+
+/// Interpolates angle in radians along the shorter way around the circle.
+fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let two_pi = 2.0 * std::f32::consts::PI;
+    let mut difference = (to - from) % two_pi;
+    if difference > std::f32::consts::PI {
+        difference -= two_pi;
+    } else if difference < -std::f32::consts::PI {
+        difference += two_pi;
+    }
+    from + difference * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn older_snapshots_are_ignored() {
+        let mut receiver = SnapshotReceiver::new();
+
+        assert!(receiver.receive(TransformSnapshot { tick: 5, objects: Vec::new() }));
+        assert!(!receiver.receive(TransformSnapshot { tick: 3, objects: Vec::new() }));
+        assert_eq!(5, receiver.latest().unwrap().tick);
+    }
+
+    #[test]
+    fn angles_are_interpolated_the_shorter_way() {
+        let angle = lerp_angle(3.0, -3.0, 0.5);
+
+        assert!((angle.abs() - std::f32::consts::PI).abs() < 0.01);
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crossbeam::channel::{self, Receiver};
+use rapier3d::dynamics::{CCDSolver, IntegrationParameters, IslandManager, JointSet, RigidBodyBuilder, RigidBodyHandle, RigidBodySet};
+use rapier3d::geometry::{BroadPhase, ColliderBuilder, ColliderHandle, ColliderSet, ContactEvent, IntersectionEvent, NarrowPhase};
+use rapier3d::math::{Isometry, Vector};
+use rapier3d::na::UnitQuaternion;
+use rapier3d::pipeline::{ActiveEvents, ChannelEventCollector, PhysicsPipeline};
+use log::*;
+
+use ketch_core::event_bus::{CollisionEvent, EventBus, GameEvent};
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::collider::{Collider, ColliderShape};
+use ketch_core::resource::object::Object;
+use ketch_core::resource::rigid_body::{BodyType, RigidBody};
+
+/// Gravity used by default, in units per second squared.
+pub const DEFAULT_GRAVITY: (f32, f32, f32) = (0.0, -9.81, 0.0);
+
+type Transform = ((f32, f32, f32), (f32, f32, f32));
+
+/// Rapier body created for an object, along with the components it was created from.
+struct PhysicsBody {
+    handle: RigidBodyHandle,
+    rigid_body: Option<RigidBody>,
+    collider: Option<Collider>,
+    scale: (f32, f32, f32),
+    /// Transform of the object after the last step, used to find objects moved outside of physics.
+    transform: Transform,
+}
+
+/// Simulates objects of the active scene, which have a rigid body or a collider, using rapier.
+/// Objects with a collider but without a rigid body are kinematic, they follow their transform.
+/// Only root objects are simulated, because positions of children are relative to their parents.
+pub struct PhysicsSystem {
+    gravity: Vector<f32>,
+    integration_parameters: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    joints: JointSet,
+    ccd_solver: CCDSolver,
+    contact_events: Receiver<ContactEvent>,
+    intersection_events: Receiver<IntersectionEvent>,
+    event_collector: ChannelEventCollector,
+    objects: HashMap<u32, PhysicsBody>,
+    scene_name: Option<String>,
+}
+
+impl PhysicsSystem {
+    /// Creates physics system with default gravity.
+    pub fn new() -> Self {
+        let (contact_sender, contact_events) = channel::unbounded();
+        let (intersection_sender, intersection_events) = channel::unbounded();
+        let (x, y, z) = DEFAULT_GRAVITY;
+        PhysicsSystem {
+            gravity: Vector::new(x, y, z),
+            integration_parameters: IntegrationParameters::default(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            joints: JointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            contact_events,
+            intersection_events,
+            event_collector: ChannelEventCollector::new(intersection_sender, contact_sender),
+            objects: HashMap::new(),
+            scene_name: None,
+        }
+    }
+
+    /// Sets gravity in units per second squared.
+    pub fn set_gravity(&mut self, x: f32, y: f32, z: f32) {
+        self.gravity = Vector::new(x, y, z);
+    }
+
+    /// Returns gravity in units per second squared.
+    pub fn gravity(&self) -> (f32, f32, f32) {
+        (self.gravity.x, self.gravity.y, self.gravity.z)
+    }
+
+    /// Sets linear velocity of the object in units per second. Does nothing if object isn't a dynamic body.
+    pub fn set_linear_velocity(&mut self, id: u32, x: f32, y: f32, z: f32) {
+        let handle = match self.objects.get(&id) {
+            Some(physics_body) => physics_body.handle,
+            None => return,
+        };
+        if let Some(body) = self.bodies.get_mut(handle) {
+            body.set_linvel(Vector::new(x, y, z), true);
+        }
+    }
+
+    /// Returns linear velocity of the object or None if it isn't simulated.
+    pub fn linear_velocity(&self, id: u32) -> Option<(f32, f32, f32)> {
+        let velocity = self.bodies.get(self.objects.get(&id)?.handle)?.linvel();
+        Some((velocity.x, velocity.y, velocity.z))
+    }
+
+    /// Applies impulse to the center of mass of the object. Does nothing if object isn't a dynamic body.
+    pub fn apply_impulse(&mut self, id: u32, x: f32, y: f32, z: f32) {
+        let handle = match self.objects.get(&id) {
+            Some(physics_body) => physics_body.handle,
+            None => return,
+        };
+        if let Some(body) = self.bodies.get_mut(handle) {
+            body.apply_impulse(Vector::new(x, y, z), true);
+        }
+    }
+
+    /// Moves all bodies by the negated offset without changing their velocities, after the active scene shifted its origin by it.
+    /// Otherwise bodies would be treated as moved outside of physics and stopped.
+    pub fn shift_origin(&mut self, x: f32, y: f32, z: f32) {
+        let offset = Vector::new(x, y, z);
+        for physics_body in self.objects.values_mut() {
+            if let Some(body) = self.bodies.get_mut(physics_body.handle) {
+                let mut position = *body.position();
+                position.translation.vector -= offset;
+                body.set_position(position, false);
+            }
+            let ((position_x, position_y, position_z), rotation) = physics_body.transform;
+            physics_body.transform = ((position_x - x, position_y - y, position_z - z), rotation);
+        }
+    }
+
+    /// Synchronizes bodies with objects of the active scene, advances simulation by given time
+    /// and writes transforms of dynamic bodies back to the objects.
+    /// Collisions which started or stopped during the step are published on the event bus.
+    pub fn step(&mut self, asset_manager: &mut AssetManager, time_delta: Duration, event_bus: &mut EventBus) {
+        let scene = match asset_manager.active_scene_mut() {
+            Some(scene) => scene,
+            None => {
+                self.clear();
+                return;
+            },
+        };
+        if self.scene_name.as_deref() != Some(scene.name()) {
+            self.clear();
+            self.scene_name = Some(scene.name().to_string());
+        }
+
+        let mut simulated_objects = HashSet::new();
+        for object in scene.objects().iter().filter(|object| object.parent().is_none()) {
+            if object.rigid_body().is_none() && object.collider().is_none() {
+                continue;
+            }
+            simulated_objects.insert(object.id());
+            self.sync_body(object);
+        }
+        let removed_objects: Vec<u32> = self.objects.keys().filter(|id| !simulated_objects.contains(id)).cloned().collect();
+        for id in removed_objects {
+            self.remove_body(id);
+        }
+
+        self.integration_parameters.dt = time_delta.as_millis() as f32 / 1000.0;
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joints,
+            &mut self.ccd_solver,
+            &(),
+            &self.event_collector,
+        );
+
+        for object in scene.objects_mut() {
+            let physics_body = match self.objects.get_mut(&object.id()) {
+                Some(physics_body) => physics_body,
+                None => continue,
+            };
+            let body = &self.bodies[physics_body.handle];
+            if !body.is_dynamic() {
+                continue;
+            }
+            let translation = body.position().translation.vector;
+            let (rotation_x, rotation_y, rotation_z) = body.position().rotation.euler_angles();
+            object.set_position(translation.x, translation.y, translation.z);
+            object.set_rotation_angles(rotation_x, rotation_y, rotation_z);
+            physics_body.transform = object_transform(object);
+        }
+
+        self.publish_collisions(event_bus);
+    }
+
+    /// Removes all bodies, used when active scene changes.
+    pub fn clear(&mut self) {
+        let ids: Vec<u32> = self.objects.keys().cloned().collect();
+        for id in ids {
+            self.remove_body(id);
+        }
+        self.scene_name = None;
+    }
+
+    /// Creates body for a new object, recreates it if components of the object changed
+    /// and moves it if object was moved outside of physics.
+    fn sync_body(&mut self, object: &Object) {
+        let transform = object_transform(object);
+        let outdated = match self.objects.get(&object.id()) {
+            Some(physics_body) => physics_body.rigid_body.as_ref() != object.rigid_body()
+                                  || physics_body.collider.as_ref() != object.collider()
+                                  || physics_body.scale != object.scale(),
+            None => true,
+        };
+        if outdated {
+            self.remove_body(object.id());
+            let handle = self.create_body(object, transform);
+            self.objects.insert(object.id(), PhysicsBody {
+                handle,
+                rigid_body: object.rigid_body().cloned(),
+                collider: object.collider().cloned(),
+                scale: object.scale(),
+                transform,
+            });
+            return;
+        }
+
+        let physics_body = self.objects.get_mut(&object.id()).expect("body was created above");
+        let body = &mut self.bodies[physics_body.handle];
+        if body.is_kinematic() {
+            body.set_next_kinematic_position(isometry(transform));
+        } else if physics_body.transform != transform {
+            body.set_position(isometry(transform), true);
+            body.set_linvel(Vector::zeros(), true);
+            body.set_angvel(Vector::zeros(), true);
+        }
+        physics_body.transform = transform;
+    }
+
+    fn create_body(&mut self, object: &Object, transform: Transform) -> RigidBodyHandle {
+        let body_builder = match object.rigid_body().map(|rigid_body| rigid_body.body_type) {
+            Some(BodyType::Dynamic) => RigidBodyBuilder::new_dynamic(),
+            Some(BodyType::Static) => RigidBodyBuilder::new_static(),
+            Some(BodyType::Kinematic) | None => RigidBodyBuilder::new_kinematic_position_based(),
+        };
+        let (linear_damping, angular_damping) = object.rigid_body().map(|rigid_body| (rigid_body.linear_damping, rigid_body.angular_damping))
+                                                                   .unwrap_or((0.0, 0.0));
+        let body = body_builder.position(isometry(transform))
+                               .linear_damping(linear_damping)
+                               .angular_damping(angular_damping)
+                               .user_data(u128::from(object.id()))
+                               .build();
+        let handle = self.bodies.insert(body);
+
+        match object.collider() {
+            Some(collider) => {
+                let collider_builder = match collider.shape.scaled(object.scale()) {
+                    ColliderShape::Cuboid { half_extents: (x, y, z) } => ColliderBuilder::cuboid(x, y, z),
+                    ColliderShape::Sphere { radius } => ColliderBuilder::ball(radius),
+                    ColliderShape::Capsule { half_height, radius } => ColliderBuilder::capsule_y(half_height, radius),
+                };
+                let collider = collider_builder.sensor(collider.sensor)
+                                               .density(collider.density)
+                                               .friction(collider.friction)
+                                               .restitution(collider.restitution)
+                                               .active_events(ActiveEvents::CONTACT_EVENTS | ActiveEvents::INTERSECTION_EVENTS)
+                                               .user_data(u128::from(object.id()))
+                                               .build();
+                self.colliders.insert_with_parent(collider, handle, &mut self.bodies);
+            },
+            None => warn!("Object {} has a rigid body without a collider, it won't collide with anything", object.name()),
+        }
+        handle
+    }
+
+    fn remove_body(&mut self, id: u32) {
+        if let Some(physics_body) = self.objects.remove(&id) {
+            self.bodies.remove(physics_body.handle, &mut self.islands, &mut self.colliders, &mut self.joints);
+        }
+    }
+
+    fn publish_collisions(&self, event_bus: &mut EventBus) {
+        while let Ok(contact_event) = self.contact_events.try_recv() {
+            let collision_event = match contact_event {
+                ContactEvent::Started(collider1, collider2) => self.object_ids(collider1, collider2).map(|(id1, id2)| CollisionEvent::Started(id1, id2)),
+                ContactEvent::Stopped(collider1, collider2) => self.object_ids(collider1, collider2).map(|(id1, id2)| CollisionEvent::Stopped(id1, id2)),
+            };
+            if let Some(collision_event) = collision_event {
+                event_bus.publish(GameEvent::Collision(collision_event));
+            }
+        }
+        while let Ok(intersection_event) = self.intersection_events.try_recv() {
+            if let Some((id1, id2)) = self.object_ids(intersection_event.collider1, intersection_event.collider2) {
+                let collision_event = if intersection_event.intersecting {
+                    CollisionEvent::Started(id1, id2)
+                } else {
+                    CollisionEvent::Stopped(id1, id2)
+                };
+                event_bus.publish(GameEvent::Collision(collision_event));
+            }
+        }
+    }
+
+    /// Returns ids of objects colliders belong to, or None if one of colliders was already removed.
+    fn object_ids(&self, collider1: ColliderHandle, collider2: ColliderHandle) -> Option<(u32, u32)> {
+        let id1 = self.colliders.get(collider1)?.user_data as u32;
+        let id2 = self.colliders.get(collider2)?.user_data as u32;
+        Some((id1, id2))
+    }
+}
+
+impl Default for PhysicsSystem {
+    fn default() -> Self {
+        PhysicsSystem::new()
+    }
+}
+
+fn object_transform(object: &Object) -> Transform {
+    (object.position(), object.rotation_angles())
+}
+
+/// Converts object position and rotation angles to isometry.
+/// Object rotation is applied around x, then y, then z axis, same as rapier euler angles.
+fn isometry(((x, y, z), (rotation_x, rotation_y, rotation_z)): Transform) -> Isometry<f32> {
+    Isometry::from_parts(Vector::new(x, y, z).into(), UnitQuaternion::from_euler_angles(rotation_x, rotation_y, rotation_z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ketch_core::resource::camera::Camera;
+    use ketch_core::resource::object::ObjectBuilder;
+    use ketch_core::resource::scene::Scene;
+
+    /// Returns asset manager with active scene containing given objects.
+    fn asset_manager_with(objects: Vec<Object>) -> AssetManager {
+        let mut asset_manager = AssetManager::without_device().unwrap();
+        let mut scene = Scene::new("test", Camera::new());
+        for object in objects {
+            scene.add_object(object);
+        }
+        asset_manager.set_active_scene(scene);
+        asset_manager
+    }
+
+    fn object_with_body(name: &str, body_type: BodyType, shape: ColliderShape) -> Object {
+        let mut object = ObjectBuilder::new(name).build();
+        object.set_rigid_body(Some(RigidBody::new(body_type)));
+        object.set_collider(Some(Collider::new(shape)));
+        object
+    }
+
+    #[test]
+    fn dynamic_body_falls_and_moves_its_object() {
+        let mut object = object_with_body("falling", BodyType::Dynamic, ColliderShape::Sphere { radius: 0.5 });
+        object.set_position(0.0, 10.0, 0.0);
+        let id = object.id();
+        let mut asset_manager = asset_manager_with(vec![object]);
+        let mut physics_system = PhysicsSystem::new();
+        let mut event_bus = EventBus::new();
+
+        for _ in 0..10 {
+            physics_system.step(&mut asset_manager, Duration::from_millis(16), &mut event_bus);
+        }
+
+        let (x, y, z) = asset_manager.active_scene().unwrap().object(id).unwrap().position();
+        assert!(y < 10.0);
+        assert_eq!((0.0, 0.0), (x, z));
+        assert!(physics_system.linear_velocity(id).unwrap().1 < 0.0);
+    }
+
+    #[test]
+    fn overlapping_colliders_publish_collision() {
+        let falling = object_with_body("falling", BodyType::Dynamic, ColliderShape::Sphere { radius: 1.0 });
+        let ground = object_with_body("ground", BodyType::Static, ColliderShape::Cuboid { half_extents: (2.0, 1.0, 2.0) });
+        let (falling_id, ground_id) = (falling.id(), ground.id());
+        let mut asset_manager = asset_manager_with(vec![falling, ground]);
+        let mut physics_system = PhysicsSystem::new();
+        let mut event_bus = EventBus::new();
+
+        physics_system.step(&mut asset_manager, Duration::from_millis(16), &mut event_bus);
+
+        let collided = event_bus.drain().into_iter().any(|event| match event {
+            GameEvent::Collision(CollisionEvent::Started(id1, id2)) => (id1, id2) == (falling_id, ground_id) || (id1, id2) == (ground_id, falling_id),
+            _ => false,
+        });
+        assert!(collided);
+    }
+
+    #[test]
+    fn isometry_rotates_like_object() {
+        let object = ketch_core::resource::object::ObjectBuilder::new("test").with_rotation_angle(0.3, -1.2, 2.0).build();
+
+        let (rotation_x, rotation_y, rotation_z) = isometry(object_transform(&object)).rotation.euler_angles();
+
+        assert!((rotation_x - 0.3).abs() < 1e-5);
+        assert!((rotation_y + 1.2).abs() < 1e-5);
+        assert!((rotation_z - 2.0).abs() < 1e-5);
+    }
+}
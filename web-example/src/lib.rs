@@ -0,0 +1,81 @@
+//! Spinning cube drawn to a canvas of a web page with the wgpu backend, with its texture fetched from the server.
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlCanvasElement;
+
+use ketch_core::renderer::scene_renderer::SceneRenderer;
+use ketch_core::renderer::wgpu_backend::WgpuBackend;
+use ketch_core::resource::AssetManager;
+use ketch_core::resource::camera::Camera;
+use ketch_core::resource::object::ObjectBuilder;
+use ketch_core::resource::primitives::Primitive;
+use ketch_core::resource::scene::Scene;
+
+/// Id of the canvas element of index.html frames are drawn to.
+const CANVAS_ID: &str = "ketch-canvas";
+/// Rotation of the cube in every frame, in radians.
+const ROTATION_STEP: f32 = 0.01;
+
+type Frame = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+#[wasm_bindgen(start)]
+pub fn start() {
+    wasm_bindgen_futures::spawn_local(async {
+        if let Err(err) = run().await {
+            web_sys::console::error_1(&JsValue::from_str(&err));
+        }
+    });
+}
+
+async fn run() -> Result<(), String> {
+    let window = web_sys::window().ok_or("page has no window")?;
+    let canvas: HtmlCanvasElement = window.document()
+                                          .and_then(|document| document.get_element_by_id(CANVAS_ID))
+                                          .and_then(|element| element.dyn_into().ok())
+                                          .ok_or("page has no canvas")?;
+    let backend = WgpuBackend::with_canvas(canvas).await.map_err(|err| err.to_string())?;
+    let mut renderer = SceneRenderer::new(backend).map_err(|err| err.to_string())?;
+
+    let mut asset_manager = AssetManager::without_device().map_err(|err| err.to_string())?;
+    let texture = asset_manager.fetch_texture("christmas", "data/christmas.jpg").await.map_err(|err| err.to_string())?;
+    asset_manager.add_texture(texture.clone());
+    let mesh = asset_manager.primitive_mesh(Primitive::Cube).map_err(|err| err.to_string())?;
+    mesh.write().unwrap().set_texture(texture);
+    let mut camera = Camera::new();
+    camera.set_position(0.0, 0.0, 3.0);
+    let mut scene = Scene::new("web", camera);
+    let cube = ObjectBuilder::new("cube").with_mesh(mesh).build();
+    let cube_id = cube.id();
+    scene.add_object(cube);
+    asset_manager.set_active_scene(scene);
+
+    // every frame requests the next one, until rendering fails
+    let frame: Frame = Rc::new(RefCell::new(None));
+    let next_frame = frame.clone();
+    *frame.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        if let Some(cube) = asset_manager.active_scene_mut().and_then(|scene| scene.object_mut(cube_id)) {
+            let (x, y, z) = cube.rotation_angles();
+            cube.set_rotation_angles(x + ROTATION_STEP, y + ROTATION_STEP, z);
+        }
+        if let Err(err) = renderer.render(&asset_manager) {
+            web_sys::console::error_1(&JsValue::from_str(&err.to_string()));
+            return;
+        }
+        request_animation_frame(&next_frame);
+    }) as Box<dyn FnMut()>));
+    request_animation_frame(&frame);
+    Ok(())
+}
+
+fn request_animation_frame(frame: &Frame) {
+    if let (Some(window), Some(callback)) = (web_sys::window(), frame.borrow().as_ref()) {
+        if let Err(err) = window.request_animation_frame(callback.as_ref().unchecked_ref()) {
+            web_sys::console::error_1(&err);
+        }
+    }
+}